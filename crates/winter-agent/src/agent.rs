@@ -1,11 +1,15 @@
 //! Agent for invoking Claude with MCP tools.
 
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use futures_util::StreamExt;
-use winter_claude::{Client, Config as ClaudeConfig, Message, StreamFormat};
+use winter_claude::{
+    Client, Config as ClaudeConfig, EnvScope, ExtractedToolCall, Message, StreamFormat,
+};
 use tracing::{debug, info, warn};
 use winter_mcp::ToolRegistry;
 
@@ -13,9 +17,108 @@ use crate::{AgentContext, AgentError, PromptBuilder};
 
 const DEFAULT_MODEL: &str = "claude-opus-4-6";
 
+/// Whether Winter's extended thinking is recorded as reflection thoughts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThinkingCapture {
+    /// Thinking is not recorded.
+    #[default]
+    Off,
+    /// Thinking is recorded word for word.
+    Full,
+    /// Only a placeholder noting that Winter thought, and for how long, is
+    /// recorded, keeping the reasoning itself out of the public thoughtstream.
+    Redacted,
+}
+
+impl ThinkingCapture {
+    /// The thought content to record for a thinking block, if any.
+    ///
+    /// Thinking the API already redacted has no text to record, so it is
+    /// recorded as a placeholder whenever capture is on.
+    pub fn content(self, thinking: &str, redacted: bool) -> Option<String> {
+        match self {
+            Self::Off => None,
+            _ if redacted => Some("[thinking redacted by the model]".to_string()),
+            Self::Full => Some(thinking.to_string()),
+            Self::Redacted => Some(format!(
+                "[thinking redacted: {} characters]",
+                thinking.chars().count()
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ThinkingCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Full => write!(f, "full"),
+            Self::Redacted => write!(f, "redacted"),
+        }
+    }
+}
+
+impl FromStr for ThinkingCapture {
+    type Err = String;
+
+    /// Parse `off`, `full` or `redacted`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" => Ok(Self::Off),
+            "full" => Ok(Self::Full),
+            "redacted" => Ok(Self::Redacted),
+            other => Err(format!(
+                "invalid thinking capture '{}': expected off, full or redacted",
+                other
+            )),
+        }
+    }
+}
+
+/// Built-in tool calls awaiting their result in the stream.
+///
+/// Built-in tools run inside Claude, so the only timing available is the gap
+/// between the `tool_use` event and its matching `tool_result`. That includes
+/// stream latency, so durations are approximate.
+#[derive(Debug, Default)]
+struct PendingBuiltinTools {
+    pending: HashMap<String, (ExtractedToolCall, Instant)>,
+}
+
+impl PendingBuiltinTools {
+    /// Note a tool use seen at `at`. MCP tools are ignored; the MCP server
+    /// records those itself, with exact timing.
+    fn start(&mut self, call: ExtractedToolCall, at: Instant) {
+        if !call.is_mcp() {
+            self.pending.insert(call.id.clone(), (call, at));
+        }
+    }
+
+    /// Match a tool result seen at `at` to its pending use, returning the
+    /// call and how long it took in milliseconds.
+    fn finish(&mut self, tool_use_id: &str, at: Instant) -> Option<(ExtractedToolCall, u64)> {
+        let (call, started) = self.pending.remove(tool_use_id)?;
+        let duration_ms = at.saturating_duration_since(started).as_millis() as u64;
+        Some((call, duration_ms))
+    }
+
+    /// Take the calls that never received a result.
+    fn drain(&mut self) -> Vec<ExtractedToolCall> {
+        self.pending.drain().map(|(_, (call, _))| call).collect()
+    }
+}
+
 /// Agent that wraps the Claude SDK for Winter.
 pub struct Agent {
     mcp_config_path: PathBuf,
+    thinking: ThinkingCapture,
+    working_dir: Option<PathBuf>,
+    /// URL of the MCP HTTP server's `/mcp` endpoint; `None` means stdio.
+    mcp_url: Option<String>,
+    /// Variables the stdio MCP server reads from the environment.
+    mcp_server_env_vars: Vec<String>,
+    /// Config file the stdio MCP server reads, passed as `WINTER_CONFIG`.
+    config_file: Option<PathBuf>,
 }
 
 impl Agent {
@@ -23,9 +126,54 @@ impl Agent {
     pub fn new(mcp_config_path: impl AsRef<Path>) -> Self {
         Self {
             mcp_config_path: mcp_config_path.as_ref().to_path_buf(),
+            thinking: ThinkingCapture::default(),
+            working_dir: None,
+            mcp_url: None,
+            mcp_server_env_vars: Vec::new(),
+            config_file: None,
         }
     }
 
+    /// Talk to the MCP HTTP server at `url` (its `/mcp` endpoint) instead of
+    /// a stdio server.
+    pub fn with_mcp_url(mut self, url: Option<String>) -> Self {
+        self.mcp_url = url;
+        self
+    }
+
+    /// Run the Claude CLI in `dir`, which its built-in file tools are
+    /// relative to. Unset, it runs in the daemon's working directory.
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Pass these variables (`*` suffix for a prefix) through to the stdio
+    /// MCP server, which the Claude CLI spawns with its own environment.
+    pub fn with_mcp_server_env_vars<I, S>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.mcp_server_env_vars = vars.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Point the stdio MCP server at the config file the daemon loaded.
+    ///
+    /// Should be absolute, since the server may not share the daemon's
+    /// working directory.
+    pub fn with_config_file(mut self, path: Option<PathBuf>) -> Self {
+        self.config_file = path;
+        self
+    }
+
+    /// Record Winter's extended thinking as reflection thoughts.
+    pub fn with_thinking(mut self, thinking: ThinkingCapture) -> Self {
+        self.thinking = thinking;
+        self
+    }
+
     /// Get the allowed tools list for Winter's MCP server.
     ///
     /// This combines the MCP tools from winter-mcp (using the colocated permission
@@ -44,13 +192,37 @@ impl Agent {
         tools
     }
 
+    /// Which of the daemon's environment variables the Claude subprocess inherits.
+    ///
+    /// Over HTTP the MCP config only substitutes the bearer token. The stdio
+    /// config spawns `winter mcp-server` as a child of the CLI, which gets
+    /// the variables set by [`Agent::with_mcp_server_env_vars`].
+    pub fn env_scope(&self) -> EnvScope {
+        let scope = EnvScope::restricted().with_vars(["RUST_LOG"]);
+        if self.mcp_url.is_some() {
+            scope.with_vars(["WINTER_MCP_TOKEN"])
+        } else {
+            scope.with_vars(self.mcp_server_env_vars.iter().cloned())
+        }
+    }
+
     /// Build environment variables for the Claude subprocess.
     ///
     /// This includes the WINTER_TRIGGER variable for HTTP header substitution,
-    /// allowing tool calls to be associated with their originating session.
-    fn build_env(context: &AgentContext) -> HashMap<String, String> {
+    /// allowing tool calls to be associated with their originating session,
+    /// and the config file for a stdio MCP server.
+    fn build_env(&self, context: &AgentContext) -> HashMap<String, String> {
         let mut env = HashMap::new();
 
+        if self.mcp_url.is_none()
+            && let Some(ref path) = self.config_file
+        {
+            env.insert(
+                "WINTER_CONFIG".to_string(),
+                path.to_string_lossy().into_owned(),
+            );
+        }
+
         // Set trigger for MCP HTTP header substitution
         if let Some(ref trigger) = context.trigger
             && let Some(trigger_str) = trigger.trigger_string()
@@ -85,18 +257,22 @@ impl Agent {
         info!("persistent session starting");
 
         let system_prompt = PromptBuilder::build(&context);
-        let env = Self::build_env(&context);
-        let _trigger = context.trigger.as_ref().and_then(|t| t.trigger_string());
+        let env = self.build_env(&context);
+        let trigger = context.trigger.as_ref().and_then(|t| t.trigger_string());
 
-        let claude_config = ClaudeConfig::builder()
+        let mut claude_config = ClaudeConfig::builder()
             .model(DEFAULT_MODEL)
             .system_prompt(&system_prompt)
             .mcp_config(&self.mcp_config_path)
             .allowed_tools(Self::allowed_tools())
             .env(env)
+            .env_scope(self.env_scope())
             .stream_format(StreamFormat::StreamJson)
-            .timeout_secs(14400) // 4 hours
-            .build()?;
+            .timeout_secs(14400); // 4 hours
+        if let Some(ref dir) = self.working_dir {
+            claude_config = claude_config.working_dir(dir);
+        }
+        let claude_config = claude_config.build()?;
 
         let client = Client::new(claude_config);
         let mut stream = client
@@ -105,14 +281,31 @@ impl Agent {
             .await?;
 
         // Get MCP URL for pushing metrics
-        let mcp_base_url = std::env::var("WINTER_MCP_URL")
-            .ok()
-            .and_then(|url| url.strip_suffix("/mcp").map(String::from))
-            .unwrap_or_else(|| "http://127.0.0.1:3847".to_string());
+        let mcp_base_url = winter_mcp::http::base_url(self.mcp_url.as_deref());
         let metrics_url = format!("{}/session-metrics", mcp_base_url);
-        let http_client = reqwest::Client::new();
+        let thinking_url = format!("{}/thinking", mcp_base_url);
+        let builtin_tool_url = format!("{}/builtin-tool-call", mcp_base_url);
+        let http_client = winter_mcp::http::authenticated_client();
+
+        let record_builtin_tool = |call: ExtractedToolCall, duration_ms: Option<u64>| {
+            let payload = serde_json::json!({
+                "id": call.id,
+                "name": call.name,
+                "input": call.input,
+                "duration_ms": duration_ms,
+                "trigger": trigger,
+            });
+            let client = http_client.clone();
+            let url = builtin_tool_url.clone();
+            tokio::spawn(async move {
+                if let Err(e) = client.post(&url).json(&payload).send().await {
+                    warn!(error = %e, "failed to record built-in tool call");
+                }
+            });
+        };
 
         let mut content = String::new();
+        let mut builtin_tools = PendingBuiltinTools::default();
 
         while let Some(msg_result) = stream.next().await {
             match msg_result {
@@ -127,6 +320,7 @@ impl Agent {
                             "total_tokens": tokens.total,
                             "cost_usd": meta.cost_usd.unwrap_or(0.0),
                             "is_turn": true,
+                            "model": meta.model.as_deref().unwrap_or(DEFAULT_MODEL),
                         });
                         let client = http_client.clone();
                         let url = metrics_url.clone();
@@ -137,6 +331,25 @@ impl Agent {
                         });
                     }
                 }
+                Ok(Message::Thinking {
+                    content: thinking,
+                    redacted,
+                    ..
+                }) => {
+                    if let Some(content) = self.thinking.content(&thinking, redacted) {
+                        let payload = serde_json::json!({
+                            "content": content,
+                            "trigger": trigger,
+                        });
+                        let client = http_client.clone();
+                        let url = thinking_url.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = client.post(&url).json(&payload).send().await {
+                                warn!(error = %e, "failed to record thinking");
+                            }
+                        });
+                    }
+                }
                 Ok(Message::Result { stats, .. }) => {
                     debug!(
                         total_tokens = stats.total_tokens.total,
@@ -145,13 +358,38 @@ impl Agent {
                         "persistent session final stats"
                     );
                 }
-                Ok(_) => {} // Init, User, System, Tool, ToolResult — ignore
+                Ok(Message::Tool {
+                    id: Some(id),
+                    name,
+                    parameters,
+                    ..
+                }) => {
+                    let call = ExtractedToolCall {
+                        id,
+                        name,
+                        input: parameters,
+                    };
+                    builtin_tools.start(call, Instant::now());
+                }
+                Ok(Message::ToolResult { tool_name, .. }) => {
+                    if let Some((call, duration_ms)) =
+                        builtin_tools.finish(&tool_name, Instant::now())
+                    {
+                        record_builtin_tool(call, Some(duration_ms));
+                    }
+                }
+                Ok(_) => {} // Init, User, System — ignore
                 Err(e) => {
                     warn!(error = %e, "stream error during persistent session");
                 }
             }
         }
 
+        // Calls interrupted before their result still belong in the stream
+        for call in builtin_tools.drain() {
+            record_builtin_tool(call, None);
+        }
+
         debug!(
             response_len = content.len(),
             "persistent session complete"
@@ -161,3 +399,86 @@ impl Agent {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thinking_capture_content() {
+        let thinking = "Reply to the mention first.";
+
+        assert_eq!(ThinkingCapture::Off.content(thinking, false), None);
+        assert_eq!(
+            ThinkingCapture::Full.content(thinking, false).as_deref(),
+            Some(thinking)
+        );
+        assert_eq!(
+            ThinkingCapture::Redacted
+                .content(thinking, false)
+                .as_deref(),
+            Some("[thinking redacted: 27 characters]")
+        );
+        assert_eq!(
+            ThinkingCapture::Full.content("", true).as_deref(),
+            Some("[thinking redacted by the model]")
+        );
+    }
+
+    fn tool_call(id: &str, name: &str) -> ExtractedToolCall {
+        ExtractedToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_paired_builtin_tool_events_yield_duration() {
+        let mut pending = PendingBuiltinTools::default();
+        let start = Instant::now();
+
+        pending.start(tool_call("toolu_01", "WebSearch"), start);
+        pending.start(tool_call("toolu_02", "Read"), start);
+
+        let (call, duration_ms) = pending
+            .finish("toolu_02", start + Duration::from_millis(40))
+            .unwrap();
+        assert_eq!(call.name, "Read");
+        assert_eq!(duration_ms, 40);
+
+        let (call, duration_ms) = pending
+            .finish("toolu_01", start + Duration::from_millis(1500))
+            .unwrap();
+        assert_eq!(call.name, "WebSearch");
+        assert_eq!(duration_ms, 1500);
+
+        // A result is only matched once
+        assert!(pending.finish("toolu_01", start).is_none());
+    }
+
+    #[test]
+    fn test_mcp_tool_events_are_not_tracked() {
+        let mut pending = PendingBuiltinTools::default();
+        let start = Instant::now();
+
+        pending.start(tool_call("toolu_01", "mcp__winter__query_facts"), start);
+        pending.start(tool_call("toolu_02", "WebFetch"), start);
+
+        assert!(pending.finish("toolu_01", start).is_none());
+        let unfinished: Vec<String> = pending.drain().into_iter().map(|c| c.name).collect();
+        assert_eq!(unfinished, vec!["WebFetch"]);
+    }
+
+    #[test]
+    fn test_parse_thinking_capture() {
+        for capture in [
+            ThinkingCapture::Off,
+            ThinkingCapture::Full,
+            ThinkingCapture::Redacted,
+        ] {
+            assert_eq!(capture.to_string().parse::<ThinkingCapture>(), Ok(capture));
+        }
+        assert!("verbose".parse::<ThinkingCapture>().is_err());
+    }
+}
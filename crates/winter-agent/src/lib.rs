@@ -14,7 +14,7 @@ mod identity;
 mod prompt;
 mod state;
 
-pub use agent::Agent;
+pub use agent::{Agent, ThinkingCapture};
 pub use context::{AgentContext, ContextTrigger, ConversationHistoryMessage, CustomToolSummary};
 pub use error::AgentError;
 pub use identity::IdentityManager;
@@ -33,6 +33,7 @@ impl StateManager {
                     notification_cursor: None,
                     dm_cursor: None,
                     followers: Vec::new(),
+                    followers_synced_at: None,
                     created_at: now,
                     last_updated: now,
                 };
@@ -71,17 +72,23 @@ impl StateManager {
         self.update(&state).await
     }
 
-    /// Get the followers list.
-    pub async fn get_followers(&self) -> Result<Vec<String>, AgentError> {
+    /// Get the followers list, or `None` if followers have never been synced.
+    pub async fn get_followers(&self) -> Result<Option<Vec<String>>, AgentError> {
         let state = self.load().await?;
-        Ok(state.followers)
+        // Records from before followers_synced_at only stored non-empty lists
+        if state.followers_synced_at.is_none() && state.followers.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(state.followers))
     }
 
     /// Set the followers list.
     pub async fn set_followers(&self, followers: Vec<String>) -> Result<(), AgentError> {
         let mut state = self.load().await?;
+        let now = Utc::now();
         state.followers = followers;
-        state.last_updated = Utc::now();
+        state.followers_synced_at = Some(now);
+        state.last_updated = now;
         self.update(&state).await
     }
 
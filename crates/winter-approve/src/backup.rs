@@ -0,0 +1,174 @@
+//! Approval export/import for backups and handing off to another operator.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use winter_atproto::ToolApproval;
+
+/// Current export file format version.
+const EXPORT_VERSION: u32 = 1;
+
+/// A portable dump of an operator's approval records.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// DID of the operator whose approvals were exported.
+    pub operator_did: String,
+    /// Approval records keyed by rkey.
+    pub approvals: BTreeMap<String, ToolApproval>,
+}
+
+impl ApprovalExport {
+    pub fn new(operator_did: String, approvals: HashMap<String, ToolApproval>) -> Self {
+        Self {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            operator_did,
+            approvals: approvals.into_iter().collect(),
+        }
+    }
+
+    /// Write the export as pretty-printed JSON.
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize approvals: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Read an export previously written by `write_to`.
+    pub fn read_from(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let export: Self =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid export file: {}", e))?;
+        if export.version != EXPORT_VERSION {
+            return Err(format!(
+                "Unsupported export version {} (expected {})",
+                export.version, EXPORT_VERSION
+            ));
+        }
+        Ok(export)
+    }
+}
+
+/// Result of merging an export into the existing approvals.
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    /// Records to write, in rkey order.
+    pub to_write: Vec<(String, ToolApproval)>,
+    /// Rkeys skipped because the existing record is at least as new.
+    pub skipped: Vec<String>,
+}
+
+/// Merge imported approvals into existing ones, preferring the newer `created_at`.
+///
+/// Ties keep the existing record, so re-importing the same file is a no-op.
+pub fn merge_newest_wins(
+    existing: &HashMap<String, ToolApproval>,
+    incoming: BTreeMap<String, ToolApproval>,
+) -> ImportPlan {
+    let mut plan = ImportPlan::default();
+    for (rkey, approval) in incoming {
+        match existing.get(&rkey) {
+            Some(current) if current.created_at >= approval.created_at => {
+                plan.skipped.push(rkey);
+            }
+            _ => plan.to_write.push((rkey, approval)),
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use winter_atproto::ToolApprovalStatus;
+
+    fn approval(rkey: &str, created_at: DateTime<Utc>) -> ToolApproval {
+        ToolApproval {
+            tool_rkey: rkey.to_string(),
+            tool_version: 2,
+            status: ToolApprovalStatus::Approved,
+            allow_network: Some(true),
+            allowed_secrets: vec!["API_KEY".to_string()],
+            workspace_path: None,
+            allow_workspace_read: None,
+            allow_workspace_write: None,
+            allowed_commands: Vec::new(),
+            allowed_tools: vec!["query_facts".to_string()],
+            allowed_import_hosts: Vec::new(),
+            tool_code: Some("export default () => 1".to_string()),
+            winter_did: Some("did:plc:winter".to_string()),
+            operator_did: Some("did:plc:op".to_string()),
+            approved_by: Some("did:plc:op".to_string()),
+            reason: Some("looks fine".to_string()),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_export_round_trips_through_file() {
+        let now = Utc::now();
+        let mut approvals = HashMap::new();
+        approvals.insert("a".to_string(), approval("a", now));
+        approvals.insert("b".to_string(), approval("b", now - Duration::days(1)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+        ApprovalExport::new("did:plc:op".to_string(), approvals)
+            .write_to(&path)
+            .unwrap();
+
+        let read = ApprovalExport::read_from(&path).unwrap();
+        assert_eq!(read.operator_did, "did:plc:op");
+        assert_eq!(read.approvals.len(), 2);
+        let a = &read.approvals["a"];
+        assert_eq!(a.tool_version, 2);
+        assert_eq!(a.allowed_secrets, vec!["API_KEY"]);
+        assert_eq!(a.tool_code.as_deref(), Some("export default () => 1"));
+        assert_eq!(a.created_at, now);
+    }
+
+    #[test]
+    fn test_read_rejects_unknown_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 99, "exportedAt": "2025-01-01T00:00:00Z", "operatorDid": "did:plc:op", "approvals": {}}"#,
+        )
+        .unwrap();
+        assert!(ApprovalExport::read_from(&path).is_err());
+    }
+
+    #[test]
+    fn test_merge_prefers_newer_created_at() {
+        let now = Utc::now();
+        let mut existing = HashMap::new();
+        existing.insert(
+            "older".to_string(),
+            approval("older", now - Duration::hours(2)),
+        );
+        existing.insert("newer".to_string(), approval("newer", now));
+        existing.insert("same".to_string(), approval("same", now));
+
+        let mut incoming = BTreeMap::new();
+        incoming.insert("older".to_string(), approval("older", now));
+        incoming.insert(
+            "newer".to_string(),
+            approval("newer", now - Duration::hours(2)),
+        );
+        incoming.insert("same".to_string(), approval("same", now));
+        incoming.insert("missing".to_string(), approval("missing", now));
+
+        let plan = merge_newest_wins(&existing, incoming);
+        let written: Vec<&str> = plan.to_write.iter().map(|(r, _)| r.as_str()).collect();
+        assert_eq!(written, vec!["missing", "older"]);
+        assert_eq!(plan.skipped, vec!["newer", "same"]);
+    }
+}
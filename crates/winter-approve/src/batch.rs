@@ -0,0 +1,473 @@
+//! Batch approve/deny from a manifest file.
+//!
+//! The manifest is YAML or JSON mapping tool rkeys to the decision and
+//! permission set for each tool:
+//!
+//! ```yaml
+//! reason: weekly review
+//! tools:
+//!   3kabc:
+//!     network: true
+//!     secrets: [API_KEY]
+//!     import_hosts: [esm.sh]
+//!   3kdef:
+//!     action: deny
+//!     reason: shells out to curl
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use chrono::Utc;
+use serde::Deserialize;
+use winter_atproto::{CustomTool, ToolApproval, ToolApprovalStatus};
+
+use crate::permissions::{PermissionSet, list_changes};
+
+/// A batch approval manifest.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchManifest {
+    /// Reason recorded for entries that don't give their own.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Tool rkey -> decision, in rkey order.
+    pub tools: BTreeMap<String, ManifestEntry>,
+}
+
+/// What to do with a tool in the manifest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchAction {
+    #[default]
+    Approve,
+    Deny,
+}
+
+/// The decision and permissions for a single tool.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    #[serde(default)]
+    pub action: BatchAction,
+    #[serde(default)]
+    pub network: bool,
+    #[serde(default)]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Remote hosts the tool may import modules from. Operator-granted only;
+    /// tools never request these.
+    #[serde(default)]
+    pub import_hosts: Vec<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl ManifestEntry {
+    fn status(&self) -> ToolApprovalStatus {
+        match self.action {
+            BatchAction::Approve => ToolApprovalStatus::Approved,
+            BatchAction::Deny => ToolApprovalStatus::Denied,
+        }
+    }
+
+    /// Permissions this entry grants. Denials grant nothing.
+    fn permissions(&self) -> PermissionSet {
+        match self.action {
+            BatchAction::Approve => PermissionSet {
+                network: self.network,
+                secrets: self.secrets.clone(),
+                commands: self.commands.clone(),
+                tools: self.tools.clone(),
+            },
+            BatchAction::Deny => PermissionSet::default(),
+        }
+    }
+
+    /// Import hosts this entry grants. Denials grant nothing.
+    fn import_hosts(&self) -> Vec<String> {
+        match self.action {
+            BatchAction::Approve => self.import_hosts.clone(),
+            BatchAction::Deny => Vec::new(),
+        }
+    }
+}
+
+/// Parse a manifest from YAML, which also accepts JSON.
+pub fn parse_manifest(input: &str) -> Result<BatchManifest, String> {
+    let manifest: BatchManifest =
+        serde_yaml::from_str(input).map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.tools.is_empty() {
+        return Err("Manifest lists no tools".to_string());
+    }
+    Ok(manifest)
+}
+
+/// Rkeys referenced by the manifest that don't exist in Winter's PDS.
+pub fn missing_rkeys(manifest: &BatchManifest, tools: &[(String, CustomTool)]) -> Vec<String> {
+    manifest
+        .tools
+        .keys()
+        .filter(|rkey| !tools.iter().any(|(r, _)| r == *rkey))
+        .cloned()
+        .collect()
+}
+
+/// How a planned write relates to the current approval.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// No approval exists yet.
+    New,
+    /// An approval exists and differs; one line per changed field.
+    Updated(Vec<String>),
+    /// The current approval already matches the manifest.
+    Unchanged,
+}
+
+/// A single write the batch would perform.
+#[derive(Debug)]
+pub struct PlannedChange<'a> {
+    pub rkey: &'a str,
+    pub tool: &'a CustomTool,
+    pub entry: &'a ManifestEntry,
+    pub kind: ChangeKind,
+}
+
+/// Compute the changes needed to bring approvals in line with the manifest.
+///
+/// Entries whose rkey isn't in `tools` are skipped; call `missing_rkeys`
+/// first to reject those manifests outright.
+pub fn plan<'a>(
+    manifest: &'a BatchManifest,
+    tools: &'a [(String, CustomTool)],
+    approvals: &HashMap<String, ToolApproval>,
+) -> Vec<PlannedChange<'a>> {
+    manifest
+        .tools
+        .iter()
+        .filter_map(|(rkey, entry)| {
+            let (_, tool) = tools.iter().find(|(r, _)| r == rkey)?;
+            let kind = match approvals.get(rkey) {
+                None => ChangeKind::New,
+                Some(current) => {
+                    let changes = approval_changes(current, tool, entry);
+                    if changes.is_empty() {
+                        ChangeKind::Unchanged
+                    } else {
+                        ChangeKind::Updated(changes)
+                    }
+                }
+            };
+            Some(PlannedChange {
+                rkey,
+                tool,
+                entry,
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn approval_changes(
+    current: &ToolApproval,
+    tool: &CustomTool,
+    entry: &ManifestEntry,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    let status = entry.status();
+    if current.status != status {
+        changes.push(format!(
+            "status: {} -> {}",
+            status_name(&current.status),
+            status_name(&status)
+        ));
+    }
+    if current.tool_version != tool.version {
+        changes.push(format!(
+            "version: v{} -> v{}",
+            current.tool_version, tool.version
+        ));
+    }
+    changes.extend(PermissionSet::from_approval(current).changes_to(&entry.permissions()));
+    list_changes(
+        "import hosts",
+        &current.allowed_import_hosts,
+        &entry.import_hosts(),
+        &mut changes,
+    );
+    changes
+}
+
+/// Display name for an approval status.
+pub fn status_name(status: &ToolApprovalStatus) -> &'static str {
+    match status {
+        ToolApprovalStatus::Approved => "approved",
+        ToolApprovalStatus::Denied => "denied",
+        ToolApprovalStatus::Revoked => "revoked",
+    }
+}
+
+/// Render a plan as human-readable text (used for `--dry-run`).
+pub fn render_plan(changes: &[PlannedChange<'_>]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        let action = match change.entry.action {
+            BatchAction::Approve => "approve",
+            BatchAction::Deny => "deny",
+        };
+        let label = match &change.kind {
+            ChangeKind::New => "new",
+            ChangeKind::Updated(_) => "update",
+            ChangeKind::Unchanged => "unchanged",
+        };
+        let _ = writeln!(
+            out,
+            "{} {} (v{}) [{}] - {}",
+            action, change.tool.name, change.tool.version, label, change.rkey
+        );
+        if let ChangeKind::Updated(lines) = &change.kind {
+            for line in lines {
+                let _ = writeln!(out, "    {}", line);
+            }
+        }
+    }
+    out
+}
+
+/// Build the approval record for a planned change.
+pub fn build_approval(
+    change: &PlannedChange<'_>,
+    default_reason: Option<&str>,
+    winter_did: &str,
+    operator_did: &str,
+) -> ToolApproval {
+    let permissions = change.entry.permissions();
+    ToolApproval {
+        tool_rkey: change.rkey.to_string(),
+        tool_version: change.tool.version,
+        status: change.entry.status(),
+        allow_network: match change.entry.action {
+            BatchAction::Approve => Some(permissions.network),
+            BatchAction::Deny => None,
+        },
+        allowed_secrets: permissions.secrets,
+        workspace_path: None,
+        allow_workspace_read: None,
+        allow_workspace_write: None,
+        allowed_commands: permissions.commands,
+        allowed_tools: permissions.tools,
+        allowed_import_hosts: change.entry.import_hosts(),
+        tool_code: match change.entry.action {
+            BatchAction::Approve => Some(change.tool.code.clone()),
+            BatchAction::Deny => None,
+        },
+        winter_did: Some(winter_did.to_string()),
+        operator_did: Some(operator_did.to_string()),
+        approved_by: Some(operator_did.to_string()),
+        reason: change
+            .entry
+            .reason
+            .clone()
+            .or_else(|| default_reason.map(String::from)),
+        created_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, version: i32) -> CustomTool {
+        CustomTool {
+            name: name.to_string(),
+            description: String::new(),
+            code: "export default () => 1".to_string(),
+            input_schema: serde_json::json!({}),
+            required_secrets: Vec::new(),
+            requires_workspace: None,
+            requires_network: None,
+            required_commands: Vec::new(),
+            required_tools: Vec::new(),
+            cache_ttl_secs: None,
+            version,
+            created_at: Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    fn approval(rkey: &str, version: i32, network: bool) -> ToolApproval {
+        ToolApproval {
+            tool_rkey: rkey.to_string(),
+            tool_version: version,
+            status: ToolApprovalStatus::Approved,
+            allow_network: Some(network),
+            allowed_secrets: Vec::new(),
+            workspace_path: None,
+            allow_workspace_read: None,
+            allow_workspace_write: None,
+            allowed_commands: Vec::new(),
+            allowed_tools: Vec::new(),
+            allowed_import_hosts: Vec::new(),
+            tool_code: None,
+            winter_did: None,
+            operator_did: None,
+            approved_by: None,
+            reason: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_manifest_with_defaults() {
+        let manifest = parse_manifest(
+            r#"{
+                "reason": "bulk review",
+                "tools": {
+                    "a": { "network": true, "secrets": ["KEY"] },
+                    "b": { "action": "deny" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.reason.as_deref(), Some("bulk review"));
+        let a = &manifest.tools["a"];
+        assert_eq!(a.action, BatchAction::Approve);
+        assert!(a.network);
+        assert_eq!(a.secrets, vec!["KEY"]);
+        assert_eq!(manifest.tools["b"].action, BatchAction::Deny);
+    }
+
+    #[test]
+    fn test_parse_yaml_manifest() {
+        let manifest = parse_manifest(
+            "reason: bulk review\n\
+             tools:\n  \
+               a:\n    \
+                 network: true\n    \
+                 secrets: [KEY]\n  \
+               b:\n    \
+                 action: deny\n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.reason.as_deref(), Some("bulk review"));
+        assert!(manifest.tools["a"].network);
+        assert_eq!(manifest.tools["a"].secrets, vec!["KEY"]);
+        assert_eq!(manifest.tools["b"].action, BatchAction::Deny);
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_unknown_fields_and_empty() {
+        assert!(parse_manifest(r#"{"tools": {"a": {"netwrok": true}}}"#).is_err());
+        assert!(parse_manifest(r#"{"tools": {}}"#).is_err());
+        assert!(parse_manifest("not a manifest").is_err());
+    }
+
+    #[test]
+    fn test_missing_rkeys() {
+        let manifest = parse_manifest(r#"{"tools": {"a": {}, "zzz": {}}}"#).unwrap();
+        let tools = vec![("a".to_string(), tool("alpha", 1))];
+        assert_eq!(missing_rkeys(&manifest, &tools), vec!["zzz"]);
+    }
+
+    #[test]
+    fn test_plan_computes_diff_against_current_approvals() {
+        let manifest = parse_manifest(
+            r#"{"tools": {
+                "new": { "network": true },
+                "same": { "network": true },
+                "bumped": { "network": true, "tools": ["query_facts"] },
+                "denied": { "action": "deny" }
+            }}"#,
+        )
+        .unwrap();
+        let tools = vec![
+            ("new".to_string(), tool("new_tool", 1)),
+            ("same".to_string(), tool("same_tool", 2)),
+            ("bumped".to_string(), tool("bumped_tool", 3)),
+            ("denied".to_string(), tool("denied_tool", 1)),
+        ];
+        let mut approvals = HashMap::new();
+        approvals.insert("same".to_string(), approval("same", 2, true));
+        approvals.insert("bumped".to_string(), approval("bumped", 2, false));
+        approvals.insert("denied".to_string(), approval("denied", 1, false));
+
+        let changes = plan(&manifest, &tools, &approvals);
+        let kind = |rkey: &str| &changes.iter().find(|c| c.rkey == rkey).unwrap().kind;
+
+        assert_eq!(kind("new"), &ChangeKind::New);
+        assert_eq!(kind("same"), &ChangeKind::Unchanged);
+        assert_eq!(
+            kind("bumped"),
+            &ChangeKind::Updated(vec![
+                "version: v2 -> v3".to_string(),
+                "network: false -> true".to_string(),
+                "tools: +query_facts".to_string(),
+            ])
+        );
+        assert_eq!(
+            kind("denied"),
+            &ChangeKind::Updated(vec!["status: approved -> denied".to_string()])
+        );
+
+        let rendered = render_plan(&changes);
+        assert!(rendered.contains("approve bumped_tool (v3) [update] - bumped"));
+        assert!(rendered.contains("    tools: +query_facts"));
+        assert!(rendered.contains("deny denied_tool (v1) [update] - denied"));
+    }
+
+    #[test]
+    fn test_import_hosts_are_granted_and_diffed() {
+        let manifest =
+            parse_manifest(r#"{"tools": {"a": { "network": true, "import_hosts": ["esm.sh"] }}}"#)
+                .unwrap();
+        let tools = vec![("a".to_string(), tool("alpha", 1))];
+        let mut approvals = HashMap::new();
+        approvals.insert("a".to_string(), approval("a", 1, true));
+
+        let changes = plan(&manifest, &tools, &approvals);
+        assert_eq!(
+            changes[0].kind,
+            ChangeKind::Updated(vec!["import hosts: +esm.sh".to_string()])
+        );
+
+        let a = build_approval(&changes[0], None, "did:winter", "did:op");
+        assert_eq!(a.allowed_import_hosts, vec!["esm.sh"]);
+    }
+
+    #[test]
+    fn test_build_approval_uses_default_reason() {
+        let manifest = parse_manifest(
+            r#"{"reason": "batch", "tools": {"a": {"network": true}, "b": {"reason": "own"}}}"#,
+        )
+        .unwrap();
+        let tools = vec![
+            ("a".to_string(), tool("alpha", 4)),
+            ("b".to_string(), tool("beta", 1)),
+        ];
+        let changes = plan(&manifest, &tools, &HashMap::new());
+
+        let a = build_approval(
+            &changes[0],
+            manifest.reason.as_deref(),
+            "did:winter",
+            "did:op",
+        );
+        assert_eq!(a.tool_version, 4);
+        assert_eq!(a.allow_network, Some(true));
+        assert_eq!(a.reason.as_deref(), Some("batch"));
+        assert_eq!(a.operator_did.as_deref(), Some("did:op"));
+
+        let b = build_approval(
+            &changes[1],
+            manifest.reason.as_deref(),
+            "did:winter",
+            "did:op",
+        );
+        assert_eq!(b.reason.as_deref(), Some("own"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Diffs between an approved tool version and the latest one.
+
+use std::fmt::Write;
+
+use similar::TextDiff;
+
+use crate::permissions::PermissionSet;
+
+/// Render a unified diff of two versions of a tool's code.
+///
+/// Returns an empty string when the code is identical.
+pub fn render_code_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_label, new_label)
+        .to_string()
+}
+
+/// Summarize how the latest version's requested permissions differ from
+/// what was approved.
+pub fn render_permission_summary(approved: &PermissionSet, requested: &PermissionSet) -> String {
+    let changes = approved.changes_to(requested);
+    if changes.is_empty() {
+        return "Permissions: unchanged\n".to_string();
+    }
+
+    let mut out = String::from("Permission changes (approved -> requested):\n");
+    for change in changes {
+        let _ = writeln!(out, "  {}", change);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_diff_identical_is_empty() {
+        assert_eq!(render_code_diff("a\nb\n", "a\nb\n", "v1", "v2"), "");
+    }
+
+    #[test]
+    fn test_code_diff_is_unified() {
+        let old = "const x = 1;\nreturn x;\n";
+        let new = "const x = 2;\nreturn x;\n";
+
+        let diff = render_code_diff(old, new, "approved (v1)", "latest (v2)");
+        assert!(diff.starts_with("--- approved (v1)\n+++ latest (v2)\n"));
+        assert!(diff.contains("@@"));
+        assert!(diff.contains("-const x = 1;\n"));
+        assert!(diff.contains("+const x = 2;\n"));
+        assert!(diff.contains(" return x;\n"));
+    }
+
+    #[test]
+    fn test_permission_summary() {
+        let approved = PermissionSet {
+            network: false,
+            secrets: vec!["A".to_string()],
+            ..Default::default()
+        };
+        let requested = PermissionSet {
+            network: true,
+            secrets: vec!["A".to_string(), "B".to_string()],
+            commands: vec!["git".to_string()],
+            ..Default::default()
+        };
+
+        let summary = render_permission_summary(&approved, &requested);
+        assert_eq!(
+            summary,
+            "Permission changes (approved -> requested):\n  network: false -> true\n  secrets: +B\n  commands: +git\n"
+        );
+        assert_eq!(
+            render_permission_summary(&approved, &approved),
+            "Permissions: unchanged\n"
+        );
+    }
+}
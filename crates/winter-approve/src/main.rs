@@ -3,12 +3,21 @@
 //! This tool runs on the operator's machine, authenticates to the operator's PDS,
 //! and creates approval records there. Winter reads these approvals via public XRPC.
 
+mod backup;
+mod batch;
+mod diff;
+mod permissions;
+mod sync_safe;
+
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use chrono::Utc;
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use winter_atproto::{CustomTool, ToolApproval, ToolApprovalStatus, code_needs_network};
+use winter_atproto::{
+    CustomTool, SAFE_TOOLS, ToolApproval, ToolApprovalStatus, code_needs_network,
+};
 
 const TOOL_COLLECTION: &str = "diy.razorgirl.winter.tool";
 const TOOL_APPROVAL_COLLECTION: &str = "diy.razorgirl.winter.toolApproval";
@@ -66,6 +75,9 @@ enum Commands {
         /// MCP/custom tools to allow calling (comma-separated)
         #[arg(long, value_delimiter = ',')]
         tools: Vec<String>,
+        /// Remote hosts the tool may import modules from (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        import_hosts: Vec<String>,
         /// Reason for approval
         #[arg(long)]
         reason: Option<String>,
@@ -88,6 +100,38 @@ enum Commands {
     },
     /// Migrate existing approvals from Winter's PDS to operator's PDS
     Migrate,
+    /// Show what changed in a tool since its approval (code diff and permissions)
+    Diff {
+        /// Tool rkey
+        rkey: String,
+    },
+    /// Export all approval records from your PDS to a JSON file
+    Export {
+        /// Output file path
+        output: PathBuf,
+    },
+    /// Import approval records from an export file into your PDS.
+    /// Existing records with a newer or equal createdAt are kept.
+    Import {
+        /// Export file path
+        input: PathBuf,
+    },
+    /// List safe (auto-approved) tools and whether your PDS has an explicit
+    /// approval for them; use --record to write the missing ones
+    SyncSafe {
+        /// Write explicit approval records for safe tools that lack one
+        #[arg(long)]
+        record: bool,
+    },
+    /// Approve or deny many tools at once from a YAML or JSON manifest
+    /// mapping tool rkeys to permission sets
+    Batch {
+        /// Path to the manifest file
+        manifest: PathBuf,
+        /// Show the changes against current approvals without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 /// ATProto session response.
@@ -410,44 +454,16 @@ async fn resolve_pds_for_did(did: &str) -> Option<String> {
     None
 }
 
-/// Check if a tool is safe (auto-approvable).
-fn is_safe_tool(tool: &CustomTool) -> bool {
-    // Check explicit or detected network need
-    let needs_network = tool.requires_network.unwrap_or_else(|| code_needs_network(&tool.code));
-    !needs_network
-        && tool.required_secrets.is_empty()
-        && tool.required_commands.is_empty()
-        && tool.required_tools.iter().all(|t| {
-            // Same safe MCP tools list as in permissions.rs
-            matches!(
-                t.as_str(),
-                "query_facts"
-                    | "list_rules"
-                    | "list_directives"
-                    | "list_jobs"
-                    | "list_notes"
-                    | "get_note"
-                    | "list_facts"
-                    | "list_fact_declarations"
-                    | "get_thread_context"
-                    | "search_posts"
-                    | "get_identity"
-                    | "query_and_enrich"
-                    | "list_predicates"
-                    | "list_custom_tools"
-                    | "get_custom_tool"
-                    | "list_secrets"
-                    | "list_thoughts"
-                    | "get_thought"
-                    | "list_blog_posts"
-                    | "get_blog_post"
-                    | "check_interruption"
-                    | "pds_list_records"
-                    | "pds_get_record"
-                    | "pds_get_records"
-                    | "search_users"
-            )
-        })
+/// Prompt for a free-form comma-separated list. Blank input means none.
+fn prompt_list(prompt: &str) -> Vec<String> {
+    eprint!("{} (comma-separated, blank for none): ", prompt);
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).unwrap_or(0);
+    input
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
 }
 
 /// Resolve a tool reference to a friendly display name.
@@ -584,6 +600,9 @@ async fn approve_tool_interactive(
         all_tools,
     );
 
+    // Remote imports — tools never request these, so the operator names them
+    let imports = prompt_list("Remote hosts to allow module imports from");
+
     // Summary
     println!();
     println!("Summary:");
@@ -601,6 +620,9 @@ async fn approve_tool_interactive(
             .collect();
         println!("  Allowed tools: {}", names.join(", "));
     }
+    if !imports.is_empty() {
+        println!("  Import hosts: {}", imports.join(", "));
+    }
     println!();
 
     if !prompt_yn("Approve with these permissions?", false) {
@@ -608,7 +630,10 @@ async fn approve_tool_interactive(
         return false;
     }
 
-    write_approval(client, winter_did, rkey, tool, all_tools, net, secs, cmds, tls, None).await
+    write_approval(
+        client, winter_did, rkey, tool, all_tools, net, secs, cmds, tls, imports, None,
+    )
+    .await
 }
 
 /// Approve a tool using explicit flags (non-interactive).
@@ -623,9 +648,14 @@ async fn approve_tool_with_flags(
     secrets: Vec<String>,
     commands: Vec<String>,
     tools: Vec<String>,
+    import_hosts: Vec<String>,
     reason: Option<String>,
 ) {
-    write_approval(client, winter_did, rkey, tool, all_tools, network, secrets, commands, tools, reason).await;
+    write_approval(
+        client, winter_did, rkey, tool, all_tools, network, secrets, commands, tools, import_hosts,
+        reason,
+    )
+    .await;
 }
 
 /// Write an approval record to the operator's PDS.
@@ -640,6 +670,7 @@ async fn write_approval(
     secrets: Vec<String>,
     commands: Vec<String>,
     tools: Vec<String>,
+    import_hosts: Vec<String>,
     reason: Option<String>,
 ) -> bool {
     let approval = ToolApproval {
@@ -653,6 +684,8 @@ async fn write_approval(
         allow_workspace_write: None,
         allowed_commands: commands,
         allowed_tools: tools,
+        allowed_import_hosts: import_hosts,
+        tool_code: Some(tool.code.clone()),
         winter_did: Some(winter_did.to_string()),
         operator_did: Some(client.did.clone()),
         approved_by: Some(client.did.clone()),
@@ -676,6 +709,9 @@ async fn write_approval(
                     .collect();
                 println!("  Allowed tools: {}", names.join(", "));
             }
+            if !approval.allowed_import_hosts.is_empty() {
+                println!("  Import hosts: {}", approval.allowed_import_hosts.join(", "));
+            }
             println!("Approval written to your PDS.");
             true
         }
@@ -710,7 +746,7 @@ async fn main() {
 
             // Categorize tools
             let (safe, unsafe_tools): (Vec<_>, Vec<_>) =
-                tools.iter().partition(|(_, t)| is_safe_tool(t));
+                tools.iter().partition(|(_, t)| t.is_safe(&SAFE_TOOLS));
 
             // Split unsafe tools into pending vs already handled
             let mut pending = Vec::new();
@@ -781,7 +817,7 @@ async fn main() {
                     println!("Tool: {} (v{})", tool.name, tool.version);
                     println!("Description: {}", tool.description);
                     let needs_network = tool.requires_network.unwrap_or_else(|| code_needs_network(&tool.code));
-                    println!("Safe: {}", if is_safe_tool(tool) { "yes" } else { "no" });
+                    println!("Safe: {}", if tool.is_safe(&SAFE_TOOLS) { "yes" } else { "no" });
                     println!();
                     println!("Requested permissions:");
                     if needs_network {
@@ -825,6 +861,7 @@ async fn main() {
             secrets,
             commands,
             tools,
+            import_hosts,
             reason,
             yes,
         } => {
@@ -846,7 +883,7 @@ async fn main() {
                     }
                 };
 
-                if is_safe_tool(tool) {
+                if tool.is_safe(&SAFE_TOOLS) {
                     println!("Tool '{}' is safe and auto-approved. No action needed.", tool.name);
                     return;
                 }
@@ -856,13 +893,14 @@ async fn main() {
                     || !secrets.is_empty()
                     || !commands.is_empty()
                     || !tools.is_empty()
+                    || !import_hosts.is_empty()
                     || yes;
 
                 let client = authenticate(&cli.pds, &cli.handle).await;
                 if has_flags {
                     approve_tool_with_flags(
                         &client, &cli.winter_did, &rkey, tool, &all_tools,
-                        network, secrets, commands, tools, reason,
+                        network, secrets, commands, tools, import_hosts, reason,
                     ).await;
                 } else {
                     approve_tool_interactive(&client, &cli.winter_did, &rkey, tool, &all_tools).await;
@@ -873,7 +911,7 @@ async fn main() {
 
                 let pending: Vec<_> = all_tools
                     .iter()
-                    .filter(|(_, t)| !is_safe_tool(t))
+                    .filter(|(_, t)| !t.is_safe(&SAFE_TOOLS))
                     .filter(|(rkey, tool)| {
                         let approval = approvals.get(rkey.as_str());
                         !matches!(
@@ -930,6 +968,8 @@ async fn main() {
                 allow_workspace_write: None,
                 allowed_commands: Vec::new(),
                 allowed_tools: Vec::new(),
+                allowed_import_hosts: Vec::new(),
+                tool_code: None,
                 winter_did: Some(cli.winter_did.clone()),
                 operator_did: Some(client.did.clone()),
                 approved_by: Some(client.did.clone()),
@@ -981,6 +1021,8 @@ async fn main() {
                 allow_workspace_write: None,
                 allowed_commands: Vec::new(),
                 allowed_tools: Vec::new(),
+                allowed_import_hosts: Vec::new(),
+                tool_code: None,
                 winter_did: Some(cli.winter_did.clone()),
                 operator_did: Some(client.did.clone()),
                 approved_by: Some(client.did.clone()),
@@ -1018,7 +1060,7 @@ async fn main() {
                 .unwrap_or_default();
 
             let (safe, unsafe_tools): (Vec<_>, Vec<_>) =
-                tools.iter().partition(|(_, t)| is_safe_tool(t));
+                tools.iter().partition(|(_, t)| t.is_safe(&SAFE_TOOLS));
 
             println!(
                 "Found {} tools total:",
@@ -1074,6 +1116,8 @@ async fn main() {
                     allow_workspace_write: old.allow_workspace_write,
                     allowed_commands: old.allowed_commands.clone(),
                     allowed_tools: old.allowed_tools.clone(),
+                    allowed_import_hosts: old.allowed_import_hosts.clone(),
+                    tool_code: old.tool_code.clone(),
                     winter_did: Some(cli.winter_did.clone()),
                     operator_did: Some(client.did.clone()),
                     approved_by: Some(client.did.clone()),
@@ -1102,5 +1146,329 @@ async fn main() {
                 migrated
             );
         }
+
+        Commands::Diff { rkey } => {
+            let all_tools = match list_tools_from_winter(&cli.winter_did).await {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let tool = match all_tools.iter().find(|(r, _)| r == &rkey) {
+                Some((_, t)) => t,
+                None => {
+                    eprintln!("Tool '{}' not found", rkey);
+                    std::process::exit(1);
+                }
+            };
+
+            let approvals = get_all_approvals(&cli.pds, &cli.handle, &cli.winter_did).await;
+            let Some(approval) = approvals.get(rkey.as_str()) else {
+                println!(
+                    "'{}' (v{}) has no approval record; nothing to diff against.",
+                    tool.name, tool.version
+                );
+                println!("Use `winter-approve show {}` to review it.", rkey);
+                return;
+            };
+
+            println!(
+                "Tool: {} - {} v{}, latest v{}",
+                tool.name,
+                batch::status_name(&approval.status),
+                approval.tool_version,
+                tool.version
+            );
+            println!();
+
+            print!(
+                "{}",
+                diff::render_permission_summary(
+                    &permissions::PermissionSet::from_approval(approval),
+                    &permissions::PermissionSet::requested_by(tool),
+                )
+            );
+            println!();
+
+            match approval.tool_code {
+                Some(ref approved_code) => {
+                    let code_diff = diff::render_code_diff(
+                        approved_code,
+                        &tool.code,
+                        &format!("approved (v{})", approval.tool_version),
+                        &format!("latest (v{})", tool.version),
+                    );
+                    if code_diff.is_empty() {
+                        println!("Code: unchanged");
+                    } else {
+                        print!("{}", code_diff);
+                    }
+                }
+                None => {
+                    println!(
+                        "The approval for v{} predates code snapshots, so there is no \
+                         approved code to diff against.",
+                        approval.tool_version
+                    );
+                    println!("Use `winter-approve show {}` to review the latest code.", rkey);
+                }
+            }
+        }
+
+        Commands::Export { output } => {
+            let Some(operator_did) = resolve_handle(&cli.pds, &cli.handle).await else {
+                eprintln!("Could not resolve {} to a DID", cli.handle);
+                std::process::exit(1);
+            };
+
+            let approvals = match list_approvals_from_did(&operator_did).await {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let count = approvals.len();
+            let export = backup::ApprovalExport::new(operator_did, approvals);
+            if let Err(e) = export.write_to(&output) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            println!("Exported {} approval(s) to {}", count, output.display());
+        }
+
+        Commands::Import { input } => {
+            let export = match backup::ApprovalExport::read_from(&input) {
+                Ok(e) => e,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!(
+                "Read {} approval(s) exported from {} at {}",
+                export.approvals.len(),
+                export.operator_did,
+                export.exported_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+
+            let client = authenticate(&cli.pds, &cli.handle).await;
+            // Merging against an empty set would overwrite newer records, so
+            // a failed fetch aborts the import
+            let existing = match list_approvals_from_did(&client.did).await {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let plan = backup::merge_newest_wins(&existing, export.approvals);
+            for rkey in &plan.skipped {
+                println!("  Kept existing (same or newer): {}", rkey);
+            }
+
+            let mut imported = 0;
+            let mut failed = 0;
+            for (rkey, mut approval) in plan.to_write {
+                // The importing operator now vouches for the record
+                approval.operator_did = Some(client.did.clone());
+                let record_value = serde_json::to_value(&approval).unwrap();
+                match client
+                    .put_record(TOOL_APPROVAL_COLLECTION, &rkey, &record_value)
+                    .await
+                {
+                    Ok(()) => {
+                        println!("  Imported: {}", rkey);
+                        imported += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("  Failed to import {}: {}", rkey, e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            println!(
+                "\nImport complete. {} written, {} kept.",
+                imported,
+                plan.skipped.len()
+            );
+            if failed > 0 {
+                eprintln!("{} approval(s) failed to import", failed);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::SyncSafe { record } => {
+            let all_tools = match list_tools_from_winter(&cli.winter_did).await {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            // Only the operator's own records count; Winter's auto-approvals
+            // are exactly what this command is meant to make explicit.
+            let Some(operator_did) = resolve_handle(&cli.pds, &cli.handle).await else {
+                eprintln!("Could not resolve {} to a DID", cli.handle);
+                std::process::exit(1);
+            };
+            let operator_approvals = match list_approvals_from_did(&operator_did).await {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let selection = sync_safe::select_safe_tools(&all_tools, &operator_approvals);
+
+            if !selection.already_recorded.is_empty() {
+                println!("Already recorded:");
+                for (rkey, tool) in &selection.already_recorded {
+                    println!("  {} (v{}) - {}", tool.name, tool.version, rkey);
+                }
+            }
+            if !selection.overridden.is_empty() {
+                println!("Explicitly denied or revoked (left unchanged):");
+                for (rkey, tool) in &selection.overridden {
+                    println!("  {} (v{}) - {}", tool.name, tool.version, rkey);
+                }
+            }
+            if !selection.to_record.is_empty() {
+                println!("Missing explicit approval:");
+                for (rkey, tool) in &selection.to_record {
+                    println!("  {} (v{}) - {}", tool.name, tool.version, rkey);
+                }
+            }
+
+            println!(
+                "\n{} safe tool(s): {} recorded, {} missing, {} overridden.",
+                selection.already_recorded.len()
+                    + selection.to_record.len()
+                    + selection.overridden.len(),
+                selection.already_recorded.len(),
+                selection.to_record.len(),
+                selection.overridden.len()
+            );
+
+            if selection.to_record.is_empty() {
+                return;
+            }
+            if !record {
+                println!("Use --record to write explicit approvals for the missing tools.");
+                return;
+            }
+
+            let client = authenticate(&cli.pds, &cli.handle).await;
+            let mut recorded = 0;
+            let mut failed = 0;
+            for (rkey, tool) in &selection.to_record {
+                let approval = sync_safe::safe_approval(rkey, tool, &cli.winter_did, &client.did);
+                let record_value = serde_json::to_value(&approval).unwrap();
+                match client
+                    .put_record(TOOL_APPROVAL_COLLECTION, rkey, &record_value)
+                    .await
+                {
+                    Ok(()) => {
+                        println!("  Recorded: {} (v{})", tool.name, tool.version);
+                        recorded += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("  Failed to record {}: {}", tool.name, e);
+                        failed += 1;
+                    }
+                }
+            }
+            println!("Wrote {} approval record(s) to your PDS.", recorded);
+            if failed > 0 {
+                eprintln!("{} approval(s) failed to record", failed);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Batch { manifest, dry_run } => {
+            let contents = match std::fs::read_to_string(&manifest) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", manifest.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            let manifest = match batch::parse_manifest(&contents) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let all_tools = match list_tools_from_winter(&cli.winter_did).await {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            // Validate every rkey before writing anything
+            let missing = batch::missing_rkeys(&manifest, &all_tools);
+            if !missing.is_empty() {
+                eprintln!("Tools not found in Winter's PDS: {}", missing.join(", "));
+                eprintln!("Nothing was written.");
+                std::process::exit(1);
+            }
+
+            let approvals = get_all_approvals(&cli.pds, &cli.handle, &cli.winter_did).await;
+            let changes = batch::plan(&manifest, &all_tools, &approvals);
+            print!("{}", batch::render_plan(&changes));
+
+            let to_write: Vec<_> = changes
+                .iter()
+                .filter(|c| c.kind != batch::ChangeKind::Unchanged)
+                .collect();
+            println!(
+                "\n{} change(s), {} unchanged.",
+                to_write.len(),
+                changes.len() - to_write.len()
+            );
+
+            if dry_run || to_write.is_empty() {
+                return;
+            }
+
+            let client = authenticate(&cli.pds, &cli.handle).await;
+
+            let mut failed = 0;
+            for change in &to_write {
+                let approval = batch::build_approval(
+                    change,
+                    manifest.reason.as_deref(),
+                    &cli.winter_did,
+                    &client.did,
+                );
+                let record_value = serde_json::to_value(&approval).unwrap();
+                if let Err(e) = client
+                    .put_record(TOOL_APPROVAL_COLLECTION, change.rkey, &record_value)
+                    .await
+                {
+                    eprintln!("  Failed to write {}: {}", change.tool.name, e);
+                    failed += 1;
+                }
+            }
+
+            println!(
+                "Wrote {} approval record(s) to your PDS.",
+                to_write.len() - failed
+            );
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
     }
 }
@@ -0,0 +1,113 @@
+//! Permission sets and the differences between them.
+//!
+//! A `PermissionSet` is the comparable part of an approval: what the tool is
+//! allowed to do, independent of who approved it or when.
+
+use winter_atproto::{CustomTool, ToolApproval, code_needs_network};
+
+/// The permissions granted to (or requested by) a tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    pub network: bool,
+    pub secrets: Vec<String>,
+    pub commands: Vec<String>,
+    pub tools: Vec<String>,
+}
+
+impl PermissionSet {
+    /// Permissions granted by an existing approval record.
+    pub fn from_approval(approval: &ToolApproval) -> Self {
+        Self {
+            network: approval.allow_network.unwrap_or(false),
+            secrets: approval.allowed_secrets.clone(),
+            commands: approval.allowed_commands.clone(),
+            tools: approval.allowed_tools.clone(),
+        }
+    }
+
+    /// Permissions a tool requests (explicitly or, for network, detected from code).
+    pub fn requested_by(tool: &CustomTool) -> Self {
+        Self {
+            network: tool
+                .requires_network
+                .unwrap_or_else(|| code_needs_network(&tool.code)),
+            secrets: tool.required_secrets.clone(),
+            commands: tool.required_commands.clone(),
+            tools: tool.required_tools.clone(),
+        }
+    }
+
+    /// Describe what changes going from `self` to `other`, one line per field.
+    ///
+    /// List fields report added (`+`) and removed (`-`) entries; an empty
+    /// result means the sets are equivalent.
+    pub fn changes_to(&self, other: &PermissionSet) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.network != other.network {
+            changes.push(format!("network: {} -> {}", self.network, other.network));
+        }
+        list_changes("secrets", &self.secrets, &other.secrets, &mut changes);
+        list_changes("commands", &self.commands, &other.commands, &mut changes);
+        list_changes("tools", &self.tools, &other.tools, &mut changes);
+        changes
+    }
+}
+
+/// Append a `field: +added, -removed` line when two lists differ.
+pub(crate) fn list_changes(
+    field: &str,
+    before: &[String],
+    after: &[String],
+    out: &mut Vec<String>,
+) {
+    let mut parts: Vec<String> = after
+        .iter()
+        .filter(|v| !before.contains(v))
+        .map(|v| format!("+{}", v))
+        .collect();
+    parts.extend(
+        before
+            .iter()
+            .filter(|v| !after.contains(v))
+            .map(|v| format!("-{}", v)),
+    );
+    if !parts.is_empty() {
+        out.push(format!("{}: {}", field, parts.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(network: bool, secrets: &[&str], tools: &[&str]) -> PermissionSet {
+        PermissionSet {
+            network,
+            secrets: secrets.iter().map(|s| s.to_string()).collect(),
+            commands: Vec::new(),
+            tools: tools.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_no_changes_for_equal_sets() {
+        let a = set(true, &["API_KEY"], &["query_facts"]);
+        assert!(a.changes_to(&a.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_changes_report_additions_and_removals() {
+        let before = set(false, &["OLD"], &["query_facts"]);
+        let after = set(true, &["NEW"], &["query_facts", "list_notes"]);
+
+        let changes = before.changes_to(&after);
+        assert_eq!(
+            changes,
+            vec![
+                "network: false -> true".to_string(),
+                "secrets: +NEW, -OLD".to_string(),
+                "tools: +list_notes".to_string(),
+            ]
+        );
+    }
+}
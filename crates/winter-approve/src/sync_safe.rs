@@ -0,0 +1,162 @@
+//! Reconcile explicit approval records for safe tools.
+//!
+//! Safe tools are auto-approved without operator involvement. `sync-safe`
+//! lets an operator record explicit approvals for them anyway, so the audit
+//! trail in the operator's PDS covers every tool Winter can run.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use winter_atproto::{CustomTool, SAFE_TOOLS, ToolApproval, ToolApprovalStatus};
+
+/// Safe tools grouped by the state of their operator approval record.
+#[derive(Debug, Default)]
+pub struct SafeSelection<'a> {
+    /// Safe tools with no current explicit approval.
+    pub to_record: Vec<(&'a str, &'a CustomTool)>,
+    /// Safe tools already approved at their current version.
+    pub already_recorded: Vec<(&'a str, &'a CustomTool)>,
+    /// Safe tools the operator explicitly denied or revoked; left untouched.
+    pub overridden: Vec<(&'a str, &'a CustomTool)>,
+}
+
+/// Classify safe tools against the operator's existing approvals.
+///
+/// Unsafe tools are ignored. An approval for an older version counts as
+/// missing, since the recorded decision no longer covers the current code.
+pub fn select_safe_tools<'a>(
+    tools: &'a [(String, CustomTool)],
+    operator_approvals: &HashMap<String, ToolApproval>,
+) -> SafeSelection<'a> {
+    let mut selection = SafeSelection::default();
+    for (rkey, tool) in tools.iter().filter(|(_, t)| t.is_safe(&SAFE_TOOLS)) {
+        let entry = (rkey.as_str(), tool);
+        match operator_approvals.get(rkey) {
+            Some(a)
+                if a.status == ToolApprovalStatus::Approved && a.tool_version == tool.version =>
+            {
+                selection.already_recorded.push(entry)
+            }
+            Some(a) if a.status != ToolApprovalStatus::Approved => selection.overridden.push(entry),
+            _ => selection.to_record.push(entry),
+        }
+    }
+    selection
+}
+
+/// Build the explicit approval record for a safe tool.
+pub fn safe_approval(
+    rkey: &str,
+    tool: &CustomTool,
+    winter_did: &str,
+    operator_did: &str,
+) -> ToolApproval {
+    ToolApproval {
+        tool_rkey: rkey.to_string(),
+        tool_version: tool.version,
+        status: ToolApprovalStatus::Approved,
+        allow_network: Some(false),
+        allowed_secrets: Vec::new(),
+        workspace_path: None,
+        allow_workspace_read: None,
+        allow_workspace_write: None,
+        allowed_commands: Vec::new(),
+        allowed_tools: tool.required_tools.clone(),
+        allowed_import_hosts: Vec::new(),
+        tool_code: Some(tool.code.clone()),
+        winter_did: Some(winter_did.to_string()),
+        operator_did: Some(operator_did.to_string()),
+        approved_by: Some(operator_did.to_string()),
+        reason: Some("Recorded by sync-safe: safe tool".to_string()),
+        created_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, version: i32, code: &str) -> CustomTool {
+        CustomTool {
+            name: name.to_string(),
+            description: String::new(),
+            code: code.to_string(),
+            input_schema: serde_json::json!({}),
+            required_secrets: Vec::new(),
+            requires_workspace: None,
+            requires_network: None,
+            required_commands: Vec::new(),
+            required_tools: Vec::new(),
+            cache_ttl_secs: None,
+            version,
+            created_at: Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    fn approval(version: i32, status: ToolApprovalStatus) -> ToolApproval {
+        let mut a = safe_approval("x", &tool("x", version, ""), "did:winter", "did:op");
+        a.status = status;
+        a
+    }
+
+    #[test]
+    fn test_selects_only_safe_tools() {
+        let mut secretive = tool("secretive", 1, "return 1;");
+        secretive.required_secrets = vec!["KEY".to_string()];
+        let tools = vec![
+            ("safe".to_string(), tool("safe", 1, "return 1;")),
+            (
+                "net".to_string(),
+                tool("net", 1, "await fetch('https://x');"),
+            ),
+            ("secretive".to_string(), secretive),
+        ];
+
+        let selection = select_safe_tools(&tools, &HashMap::new());
+        let rkeys: Vec<&str> = selection.to_record.iter().map(|(r, _)| *r).collect();
+        assert_eq!(rkeys, vec!["safe"]);
+        assert!(selection.already_recorded.is_empty());
+    }
+
+    #[test]
+    fn test_skips_already_recorded_and_overridden() {
+        let tools = vec![
+            ("current".to_string(), tool("current", 2, "return 1;")),
+            ("stale".to_string(), tool("stale", 3, "return 1;")),
+            ("denied".to_string(), tool("denied", 1, "return 1;")),
+        ];
+        let mut approvals = HashMap::new();
+        approvals.insert(
+            "current".to_string(),
+            approval(2, ToolApprovalStatus::Approved),
+        );
+        approvals.insert(
+            "stale".to_string(),
+            approval(2, ToolApprovalStatus::Approved),
+        );
+        approvals.insert(
+            "denied".to_string(),
+            approval(1, ToolApprovalStatus::Denied),
+        );
+
+        let selection = select_safe_tools(&tools, &approvals);
+        let names =
+            |v: &[(&str, &CustomTool)]| v.iter().map(|(r, _)| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&selection.already_recorded), vec!["current"]);
+        assert_eq!(names(&selection.to_record), vec!["stale"]);
+        assert_eq!(names(&selection.overridden), vec!["denied"]);
+    }
+
+    #[test]
+    fn test_safe_approval_record() {
+        let mut t = tool("chain", 4, "return 1;");
+        t.required_tools = vec!["query_facts".to_string()];
+        let a = safe_approval("rk", &t, "did:winter", "did:op");
+        assert_eq!(a.tool_version, 4);
+        assert_eq!(a.status, ToolApprovalStatus::Approved);
+        assert_eq!(a.allow_network, Some(false));
+        assert_eq!(a.allowed_tools, vec!["query_facts"]);
+        assert_eq!(a.approved_by.as_deref(), Some("did:op"));
+    }
+}
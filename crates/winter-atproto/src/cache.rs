@@ -3,13 +3,14 @@
 //! Provides thread-safe caching of facts and rules with support for
 //! real-time updates via Jetstream subscription.
 
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 use dashmap::DashMap;
 use tokio::sync::{RwLock, broadcast};
 use tracing::{debug, trace};
 
+use crate::firehose_metrics::FirehoseMetrics;
 use crate::{
     BlogEntry, CustomTool, DaemonState, Directive, Fact, FactDeclaration, Follow, Identity, Job,
     Like, Note, Post, Repost, Rule, Thought, ToolApproval, Trigger, WikiEntry, WikiLink,
@@ -45,6 +46,16 @@ pub struct CachedRecord<T> {
     pub cid: String,
 }
 
+/// A change to one of our own records, as delivered by Jetstream.
+#[derive(Debug, Clone)]
+pub struct LiveWrite {
+    pub collection: String,
+    pub rkey: String,
+    pub cid: String,
+    /// The new record value, or `None` for a delete.
+    pub record: Option<serde_json::Value>,
+}
+
 /// Filter for scoping thought retrieval by conversation context.
 ///
 /// Used to prevent cross-contamination when multiple workers process
@@ -279,6 +290,11 @@ pub struct RepoCache {
     /// This prevents broadcast channel lag during initial sync (which
     /// can trigger expensive full TSV regeneration in DatalogCache).
     suppress_broadcasts: AtomicBool,
+    /// Live writes seen since a replay started, re-applied once the replay
+    /// has rebuilt the cache. `None` when no replay is running.
+    live_writes: Mutex<Option<Vec<LiveWrite>>>,
+    /// Event rate, lag and staleness of the Jetstream subscription.
+    firehose: FirehoseMetrics,
 }
 
 /// Broadcast channel capacity for cache updates.
@@ -314,6 +330,8 @@ impl RepoCache {
             repo_rev: RwLock::new(None),
             updates_tx,
             suppress_broadcasts: AtomicBool::new(false),
+            live_writes: Mutex::new(None),
+            firehose: FirehoseMetrics::new(),
         })
     }
 
@@ -367,6 +385,64 @@ impl RepoCache {
         self.suppress_broadcasts.load(Ordering::SeqCst)
     }
 
+    /// Start keeping a copy of every live write, so a replay can re-apply the
+    /// ones its repo download missed.
+    pub fn buffer_live_writes(&self) {
+        *self.live_writes() = Some(Vec::new());
+    }
+
+    /// Apply a live write, keeping a copy if a replay is buffering them.
+    ///
+    /// The write is applied while the buffer is locked, so it can't interleave
+    /// with [`Self::drain_live_writes`] re-applying older writes.
+    pub fn record_live_write<T>(&self, write: LiveWrite, apply: impl FnOnce(&LiveWrite) -> T) -> T {
+        let mut buffer = self.live_writes();
+        let result = apply(&write);
+        if let Some(buffer) = buffer.as_mut() {
+            buffer.push(write);
+        }
+        result
+    }
+
+    /// Stop buffering live writes and re-apply the buffered ones in the order
+    /// they arrived.
+    pub fn drain_live_writes(&self, mut apply: impl FnMut(&LiveWrite)) {
+        let mut buffer = self.live_writes();
+        for write in buffer.take().unwrap_or_default() {
+            apply(&write);
+        }
+    }
+
+    fn live_writes(&self) -> MutexGuard<'_, Option<Vec<LiveWrite>>> {
+        self.live_writes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record the timestamp of a firehose event (microseconds since epoch).
+    pub fn record_event_time(&self, time_us: i64) {
+        self.firehose.record(Some(time_us), None);
+    }
+
+    /// Get the timestamp of the most recent firehose event, if any.
+    pub fn last_event_time_us(&self) -> Option<i64> {
+        self.firehose.last_event_time_us()
+    }
+
+    /// Event rate, lag and staleness of the firehose feeding this cache.
+    pub fn firehose_metrics(&self) -> &FirehoseMetrics {
+        &self.firehose
+    }
+
+    /// Get the lag between now and the most recent firehose event, in seconds.
+    ///
+    /// Returns `None` if no event has been received yet.
+    pub fn firehose_lag_secs(&self) -> Option<f64> {
+        let last = self.last_event_time_us()?;
+        let now = chrono::Utc::now().timestamp_micros();
+        Some((now - last).max(0) as f64 / 1_000_000.0)
+    }
+
     /// Send a cache update to subscribers, respecting suppression flag.
     ///
     /// Returns true if the update was sent, false if suppressed or no subscribers.
@@ -1823,6 +1899,8 @@ impl Default for RepoCache {
             repo_rev: RwLock::new(None),
             updates_tx,
             suppress_broadcasts: AtomicBool::new(false),
+            live_writes: Mutex::new(None),
+            firehose: FirehoseMetrics::new(),
         }
     }
 }
@@ -1842,6 +1920,7 @@ mod tests {
             tags: vec![],
             created_at: Utc::now(),
             expires_at: None,
+            deleted_at: None,
         }
     }
 
@@ -67,6 +67,17 @@ pub struct CarParseResult {
     pub wiki_links: HashMap<String, (WikiLink, String)>,
     /// Triggers extracted from the repo, keyed by rkey.
     pub triggers: HashMap<String, (Trigger, String)>,
+    /// Records in collections registered with [`Dispatcher`](crate::Dispatcher).
+    pub custom: Vec<CustomRecord>,
+}
+
+/// A record from a registered collection, decoded to JSON.
+#[derive(Debug, Clone)]
+pub struct CustomRecord {
+    pub collection: String,
+    pub rkey: String,
+    pub cid: String,
+    pub value: serde_json::Value,
 }
 
 /// Parse a CAR file and extract all records.
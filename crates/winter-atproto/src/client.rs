@@ -35,6 +35,26 @@ pub enum WriteOp {
     Delete { collection: String, rkey: String },
 }
 
+impl WriteOp {
+    /// Collection the operation writes to.
+    pub fn collection(&self) -> &str {
+        match self {
+            Self::Create { collection, .. }
+            | Self::Update { collection, .. }
+            | Self::Delete { collection, .. } => collection,
+        }
+    }
+
+    /// Record key the operation writes to.
+    pub fn rkey(&self) -> &str {
+        match self {
+            Self::Create { rkey, .. } | Self::Update { rkey, .. } | Self::Delete { rkey, .. } => {
+                rkey
+            }
+        }
+    }
+}
+
 /// Response from `com.atproto.repo.applyWrites`.
 #[derive(Debug, Deserialize)]
 pub struct ApplyWritesResponse {
@@ -62,6 +82,9 @@ pub enum WriteResult {
 }
 
 /// Client for interacting with an ATProto PDS.
+///
+/// Clones share the same session.
+#[derive(Clone)]
 pub struct AtprotoClient {
     http: Client,
     pds_url: String,
@@ -532,6 +555,21 @@ impl AtprotoClient {
         collection: &str,
         rkey: &str,
         record: &T,
+    ) -> Result<CreateRecordResponse, AtprotoError> {
+        self.put_record_swap(collection, rkey, record, None).await
+    }
+
+    /// Update (put) a record only if its current CID is `swap_cid`.
+    ///
+    /// Passes `swapRecord` to the PDS, which rejects the write with
+    /// `InvalidSwap` (see [`AtprotoError::is_swap_conflict`]) if the record
+    /// changed since `swap_cid` was read. With `None` this is `put_record`.
+    pub async fn put_record_swap<T: Serialize>(
+        &self,
+        collection: &str,
+        rkey: &str,
+        record: &T,
+        swap_cid: Option<&str>,
     ) -> Result<CreateRecordResponse, AtprotoError> {
         let did = self
             .did()
@@ -554,6 +592,8 @@ impl AtprotoClient {
             collection: &'a str,
             rkey: &'a str,
             record: serde_json::Value,
+            #[serde(rename = "swapRecord", skip_serializing_if = "Option::is_none")]
+            swap_record: Option<&'a str>,
         }
 
         let url = format!("{}/xrpc/com.atproto.repo.putRecord", self.pds_url);
@@ -572,6 +612,7 @@ impl AtprotoClient {
                     collection,
                     rkey,
                     record: record_value.clone(),
+                    swap_record: swap_cid,
                 })
                 .send()
                 .await?;
@@ -689,6 +730,20 @@ impl AtprotoClient {
 
     /// Delete a record.
     pub async fn delete_record(&self, collection: &str, rkey: &str) -> Result<(), AtprotoError> {
+        self.delete_record_swap(collection, rkey, None).await
+    }
+
+    /// Delete a record only if its current CID is `swap_cid`.
+    ///
+    /// Like [`put_record_swap`](Self::put_record_swap), the PDS rejects the
+    /// delete with `InvalidSwap` if the record changed since `swap_cid` was
+    /// read. With `None` this is `delete_record`.
+    pub async fn delete_record_swap(
+        &self,
+        collection: &str,
+        rkey: &str,
+        swap_cid: Option<&str>,
+    ) -> Result<(), AtprotoError> {
         let did = self
             .did()
             .await
@@ -699,6 +754,8 @@ impl AtprotoClient {
             repo: &'a str,
             collection: &'a str,
             rkey: &'a str,
+            #[serde(rename = "swapRecord", skip_serializing_if = "Option::is_none")]
+            swap_record: Option<&'a str>,
         }
 
         let url = format!("{}/xrpc/com.atproto.repo.deleteRecord", self.pds_url);
@@ -716,6 +773,7 @@ impl AtprotoClient {
                     repo: &did,
                     collection,
                     rkey,
+                    swap_record: swap_cid,
                 })
                 .send()
                 .await?;
@@ -1039,7 +1097,7 @@ struct UploadBlobResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{body_partial_json, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
@@ -1189,4 +1247,48 @@ mod tests {
             AtprotoError::RateLimited { .. }
         ));
     }
+
+    #[tokio::test]
+    async fn test_put_record_swap_conflict() {
+        let mock_server = MockServer::start().await;
+        let client = crate::test_support::logged_in_client(&mock_server).await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.repo.putRecord"))
+            .and(body_partial_json(
+                serde_json::json!({ "swapRecord": "bafyold" }),
+            ))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "InvalidSwap",
+                "message": "Record was at bafynew"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.repo.putRecord"))
+            .and(body_partial_json(
+                serde_json::json!({ "swapRecord": "bafynew" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uri": "at://did:plc:testuser123/test.collection/abc",
+                "cid": "bafynewer"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let record = serde_json::json!({ "text": "hello" });
+
+        let err = client
+            .put_record_swap("test.collection", "abc", &record, Some("bafyold"))
+            .await
+            .unwrap_err();
+        assert!(err.is_swap_conflict(), "{err}");
+
+        let response = client
+            .put_record_swap("test.collection", "abc", &record, Some("bafynew"))
+            .await
+            .unwrap();
+        assert_eq!(response.cid, "bafynewer");
+    }
 }
@@ -4,7 +4,7 @@
 //! repetitive boilerplate in Jetstream event processing and CAR file parsing.
 //!
 //! The `define_record_dispatch!` macro generates:
-//! - `is_tracked_collection()` - check if a collection is tracked
+//! - `is_tracked_collection()` - check if a collection is tracked (built in or registered)
 //! - `dispatch_create_or_update_json()` - decode JSON and upsert to cache (Jetstream)
 //! - `dispatch_delete()` - delete from cache
 //! - `extract_record_to_result()` - decode CBOR and insert into CarParseResult (CAR hydration)
@@ -67,15 +67,110 @@
 //! - They return `false` from `dispatch_create_or_update_json()`
 //! - Handle them explicitly in `jetstream.rs::handle_commit()` and `car.rs::extract_record()`
 //!
+//! ## Collections Defined Outside This Crate
+//!
+//! Downstream crates can track their own lexicons without editing this file by
+//! registering a [`CollectionHandler`] with [`Dispatcher::register`]. Registered
+//! collections are reported by `is_tracked_collection()`, subscribed to on
+//! Jetstream, and routed to the handler for creates, updates and deletes
+//! (including records found during CAR hydration). Built-in collections always
+//! take precedence and cannot be overridden.
+//!
 //! ## CacheUpdate Events
 //!
 //! If you need to broadcast cache updates for the new type, add variants to
 //! `CacheUpdate` in `cache.rs` and emit them from your upsert/delete methods.
 
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
 use tracing::trace;
 
 use crate::AtprotoError;
 use crate::cache::RepoCache;
+use crate::validate::Validate;
+
+/// Handler for a collection registered at runtime via [`Dispatcher::register`].
+///
+/// The handler owns wherever the records end up; the cache is passed through
+/// so handlers can keep derived state alongside the built-in collections.
+pub trait CollectionHandler: Send + Sync {
+    /// Handle a created or updated record.
+    fn upsert(
+        &self,
+        cache: &RepoCache,
+        rkey: &str,
+        cid: &str,
+        record: serde_json::Value,
+    ) -> Result<(), AtprotoError>;
+
+    /// Handle a deleted record.
+    fn delete(&self, cache: &RepoCache, rkey: &str);
+}
+
+type HandlerMap = HashMap<String, Arc<dyn CollectionHandler>>;
+
+static REGISTRY: LazyLock<RwLock<HandlerMap>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Process-wide registry of handlers for collections not built into this crate.
+pub struct Dispatcher;
+
+impl Dispatcher {
+    /// Register a handler for `collection`, replacing any previous handler.
+    ///
+    /// Returns false (and registers nothing) if the collection is built in.
+    pub fn register(
+        collection: impl Into<String>,
+        handler: impl CollectionHandler + 'static,
+    ) -> bool {
+        let collection = collection.into();
+        if is_builtin_collection(&collection) {
+            return false;
+        }
+        REGISTRY
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(collection, Arc::new(handler));
+        true
+    }
+
+    /// Remove the handler for `collection`. Returns whether one was registered.
+    pub fn unregister(collection: &str) -> bool {
+        REGISTRY
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(collection)
+            .is_some()
+    }
+
+    /// Whether a handler is registered for `collection`.
+    pub fn is_registered(collection: &str) -> bool {
+        REGISTRY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(collection)
+    }
+
+    /// Collections with a registered handler.
+    pub fn registered_collections() -> Vec<String> {
+        let mut collections: Vec<String> = REGISTRY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        collections.sort();
+        collections
+    }
+
+    fn handler(collection: &str) -> Option<Arc<dyn CollectionHandler>> {
+        REGISTRY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(collection)
+            .cloned()
+    }
+}
 
 /// Macro to define record dispatch for all tracked collections.
 ///
@@ -108,8 +203,14 @@ macro_rules! define_record_dispatch {
         /// Check if a collection is one we track.
         ///
         /// Returns true for all collections defined in the dispatch macro,
-        /// plus special collections (identity, state) handled separately.
+        /// special collections (identity, state) handled separately, and
+        /// collections registered with [`Dispatcher`].
         pub fn is_tracked_collection(collection: &str) -> bool {
+            is_builtin_collection(collection) || Dispatcher::is_registered(collection)
+        }
+
+        /// Check if a collection is handled by this crate itself.
+        fn is_builtin_collection(collection: &str) -> bool {
             // Regular records
             $(
                 if collection == $collection {
@@ -144,6 +245,17 @@ macro_rules! define_record_dispatch {
                     let value: $type = serde_json::from_value(record).map_err(|e| {
                         AtprotoError::Json(e)
                     })?;
+                    if let Err(reason) = value.validate() {
+                        tracing::warn!(
+                            collection = %collection,
+                            rkey = %rkey,
+                            reason = %reason,
+                            "skipping record that fails lexicon validation"
+                        );
+                        // The repo no longer holds the version we have cached
+                        cache.$delete(rkey);
+                        return Ok(true);
+                    }
                     cache.$upsert(rkey.to_string(), value, cid.to_string());
                     return Ok(true);
                 }
@@ -154,6 +266,17 @@ macro_rules! define_record_dispatch {
                     let value: $ins_type = serde_json::from_value(record).map_err(|e| {
                         AtprotoError::Json(e)
                     })?;
+                    if let Err(reason) = value.validate() {
+                        tracing::warn!(
+                            collection = %collection,
+                            rkey = %rkey,
+                            reason = %reason,
+                            "skipping record that fails lexicon validation"
+                        );
+                        // The repo no longer holds the version we have cached
+                        cache.$ins_delete(rkey);
+                        return Ok(true);
+                    }
                     cache.$insert(rkey.to_string(), value, cid.to_string());
                     return Ok(true);
                 }
@@ -162,6 +285,11 @@ macro_rules! define_record_dispatch {
             if collection == crate::IDENTITY_COLLECTION || collection == crate::STATE_COLLECTION {
                 return Ok(false);
             }
+            // Registered collections
+            if let Some(handler) = Dispatcher::handler(collection) {
+                handler.upsert(cache, rkey, cid, record)?;
+                return Ok(true);
+            }
             // Unknown collection
             trace!(collection = %collection, rkey = %rkey, "ignoring unknown collection");
             Ok(true)
@@ -190,6 +318,11 @@ macro_rules! define_record_dispatch {
             if collection == crate::IDENTITY_COLLECTION || collection == crate::STATE_COLLECTION {
                 return;
             }
+            // Registered collections
+            if let Some(handler) = Dispatcher::handler(collection) {
+                handler.delete(cache, rkey);
+                return;
+            }
             // Unknown collection
             trace!(collection = %collection, rkey = %rkey, "ignoring unknown collection in delete");
         }
@@ -197,8 +330,11 @@ macro_rules! define_record_dispatch {
         /// Extract a record from CBOR data into a CarParseResult.
         ///
         /// Used during CAR file parsing for initial cache hydration.
+        /// Records in registered collections are decoded to JSON and kept in
+        /// `result.custom` for [`apply_custom_records`].
+        ///
         /// Returns true if the collection was handled (even if parsing failed),
-        /// false if the collection is not recognized by the dispatch macro.
+        /// false if the collection is not recognized.
         pub fn extract_record_to_result(
             collection: &str,
             rkey: &str,
@@ -210,12 +346,22 @@ macro_rules! define_record_dispatch {
             $(
                 if collection == $collection {
                     match serde_ipld_dagcbor::from_slice::<$type>(data) {
-                        Ok(value) => {
-                            result.$car_field.insert(
-                                rkey.to_string(),
-                                (value, value_cid.to_string()),
-                            );
-                        }
+                        Ok(value) => match value.validate() {
+                            Ok(()) => {
+                                result.$car_field.insert(
+                                    rkey.to_string(),
+                                    (value, value_cid.to_string()),
+                                );
+                            }
+                            Err(reason) => {
+                                tracing::warn!(
+                                    collection = %collection,
+                                    rkey = %rkey,
+                                    reason = %reason,
+                                    "skipping record that fails lexicon validation"
+                                );
+                            }
+                        },
                         Err(e) => {
                             tracing::warn!(
                                 collection = %collection,
@@ -232,12 +378,22 @@ macro_rules! define_record_dispatch {
             $(
                 if collection == $ins_collection {
                     match serde_ipld_dagcbor::from_slice::<$ins_type>(data) {
-                        Ok(value) => {
-                            result.$ins_car_field.insert(
-                                rkey.to_string(),
-                                (value, value_cid.to_string()),
-                            );
-                        }
+                        Ok(value) => match value.validate() {
+                            Ok(()) => {
+                                result.$ins_car_field.insert(
+                                    rkey.to_string(),
+                                    (value, value_cid.to_string()),
+                                );
+                            }
+                            Err(reason) => {
+                                tracing::warn!(
+                                    collection = %collection,
+                                    rkey = %rkey,
+                                    reason = %reason,
+                                    "skipping record that fails lexicon validation"
+                                );
+                            }
+                        },
                         Err(e) => {
                             tracing::warn!(
                                 collection = %collection,
@@ -250,6 +406,28 @@ macro_rules! define_record_dispatch {
                     return true;
                 }
             )*
+            // Registered collections
+            if Dispatcher::is_registered(collection) {
+                match serde_ipld_dagcbor::from_slice::<serde_json::Value>(data) {
+                    Ok(value) => {
+                        result.custom.push(crate::car::CustomRecord {
+                            collection: collection.to_string(),
+                            rkey: rkey.to_string(),
+                            cid: value_cid.to_string(),
+                            value,
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            collection = %collection,
+                            rkey = %rkey,
+                            error = %e,
+                            "failed to parse CBOR record"
+                        );
+                    }
+                }
+                return true;
+            }
             false
         }
     };
@@ -290,6 +468,30 @@ define_record_dispatch! {
     @insert crate::WIKI_LINK_COLLECTION => crate::WikiLink, insert_wiki_link, delete_wiki_link, wiki_links;
 }
 
+/// Hand records collected for registered collections during CAR parsing to
+/// their handlers.
+pub fn apply_custom_records(
+    cache: &RepoCache,
+    records: impl IntoIterator<Item = crate::car::CustomRecord>,
+) {
+    for record in records {
+        if let Err(e) = dispatch_create_or_update_json(
+            cache,
+            &record.collection,
+            &record.rkey,
+            &record.cid,
+            record.value,
+        ) {
+            tracing::warn!(
+                collection = %record.collection,
+                rkey = %record.rkey,
+                error = %e,
+                "failed to apply registered collection record"
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -379,4 +581,200 @@ mod tests {
         // Special collections return Ok(false) for separate handling
         assert!(matches!(result, Ok(false)));
     }
+
+    /// Records every call so tests can see what was dispatched.
+    #[derive(Default, Clone)]
+    struct RecordingHandler {
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl CollectionHandler for RecordingHandler {
+        fn upsert(
+            &self,
+            _cache: &RepoCache,
+            rkey: &str,
+            cid: &str,
+            record: serde_json::Value,
+        ) -> Result<(), AtprotoError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("upsert {} {} {}", rkey, cid, record["text"]));
+            Ok(())
+        }
+
+        fn delete(&self, _cache: &RepoCache, rkey: &str) {
+            self.calls.lock().unwrap().push(format!("delete {}", rkey));
+        }
+    }
+
+    // The registry is process-wide, so each test uses its own collection.
+
+    #[test]
+    fn test_registered_collection_is_tracked() {
+        let collection = "com.example.test.tracked";
+        assert!(!is_tracked_collection(collection));
+
+        assert!(Dispatcher::register(
+            collection,
+            RecordingHandler::default()
+        ));
+        assert!(is_tracked_collection(collection));
+        assert!(Dispatcher::registered_collections().contains(&collection.to_string()));
+
+        assert!(Dispatcher::unregister(collection));
+        assert!(!is_tracked_collection(collection));
+    }
+
+    #[test]
+    fn test_registered_collection_dispatches_to_handler() {
+        let collection = "com.example.test.dispatch";
+        let handler = RecordingHandler::default();
+        Dispatcher::register(collection, handler.clone());
+        let cache = RepoCache::new();
+
+        let result = dispatch_create_or_update_json(
+            &cache,
+            collection,
+            "a",
+            "bafya",
+            serde_json::json!({ "text": "hello" }),
+        );
+        assert!(matches!(result, Ok(true)));
+        dispatch_delete(&cache, collection, "a");
+
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["upsert a bafya \"hello\"", "delete a"]
+        );
+        Dispatcher::unregister(collection);
+    }
+
+    #[test]
+    fn test_register_builtin_collection_is_refused() {
+        assert!(!Dispatcher::register(
+            FACT_COLLECTION,
+            RecordingHandler::default()
+        ));
+        assert!(!Dispatcher::is_registered(FACT_COLLECTION));
+    }
+
+    #[test]
+    fn test_registered_collection_extracted_from_car() {
+        let collection = "com.example.test.car";
+        let handler = RecordingHandler::default();
+        Dispatcher::register(collection, handler.clone());
+
+        let data = serde_ipld_dagcbor::to_vec(&serde_json::json!({ "text": "from car" })).unwrap();
+        let mut result = crate::car::CarParseResult::default();
+        assert!(extract_record_to_result(
+            collection,
+            "b",
+            "bafyb",
+            &data,
+            &mut result
+        ));
+        assert_eq!(result.custom.len(), 1);
+
+        apply_custom_records(&RepoCache::new(), result.custom);
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["upsert b bafyb \"from car\""]
+        );
+        Dispatcher::unregister(collection);
+    }
+
+    /// Collects the messages of WARN events emitted while it is the default.
+    #[derive(Default, Clone)]
+    struct WarnCapture {
+        messages: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl tracing::Subscriber for WarnCapture {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Message<'a>(&'a mut String);
+            impl tracing::field::Visit for Message<'_> {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" {
+                        *self.0 = format!("{:?}", value);
+                    }
+                }
+            }
+            if *event.metadata().level() == tracing::Level::WARN {
+                let mut message = String::new();
+                event.record(&mut Message(&mut message));
+                self.messages.lock().unwrap().push(message);
+            }
+        }
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    fn malformed_fact() -> serde_json::Value {
+        serde_json::json!({
+            "predicate": "not a predicate",
+            "args": ["a"],
+            "createdAt": "2026-01-01T00:00:00Z"
+        })
+    }
+
+    #[test]
+    fn test_malformed_fact_from_firehose_is_skipped() {
+        let cache = RepoCache::new();
+        let capture = WarnCapture::default();
+
+        let result = tracing::subscriber::with_default(capture.clone(), || {
+            dispatch_create_or_update_json(&cache, FACT_COLLECTION, "bad", "cid", malformed_fact())
+        });
+
+        assert!(matches!(result, Ok(true)));
+        assert!(cache.get_fact("bad").is_none());
+        assert_eq!(
+            *capture.messages.lock().unwrap(),
+            vec!["skipping record that fails lexicon validation"]
+        );
+    }
+
+    #[test]
+    fn test_malformed_fact_update_evicts_cached_version() {
+        let cache = RepoCache::new();
+        let mut good = malformed_fact();
+        good["predicate"] = serde_json::json!("likes");
+        dispatch_create_or_update_json(&cache, FACT_COLLECTION, "f", "cid1", good).unwrap();
+        assert!(cache.get_fact("f").is_some());
+
+        dispatch_create_or_update_json(&cache, FACT_COLLECTION, "f", "cid2", malformed_fact())
+            .unwrap();
+        assert!(cache.get_fact("f").is_none());
+    }
+
+    #[test]
+    fn test_malformed_fact_from_car_is_skipped() {
+        let data = serde_ipld_dagcbor::to_vec(&malformed_fact()).unwrap();
+        let mut result = crate::car::CarParseResult::default();
+        let capture = WarnCapture::default();
+
+        let handled = tracing::subscriber::with_default(capture.clone(), || {
+            extract_record_to_result(FACT_COLLECTION, "bad", "cid", &data, &mut result)
+        });
+
+        assert!(handled);
+        assert!(result.facts.is_empty());
+        assert_eq!(
+            *capture.messages.lock().unwrap(),
+            vec!["skipping record that fails lexicon validation"]
+        );
+    }
 }
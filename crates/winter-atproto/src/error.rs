@@ -71,3 +71,11 @@ pub enum AtprotoError {
     #[error("invalid MIME type: {0}")]
     InvalidMimeType(String),
 }
+
+impl AtprotoError {
+    /// Whether the PDS rejected a write because the record's CID no longer
+    /// matched the `swapRecord` it was given.
+    pub fn is_swap_conflict(&self) -> bool {
+        matches!(self, AtprotoError::Xrpc { error, .. } if error == "InvalidSwap")
+    }
+}
@@ -0,0 +1,234 @@
+//! Throughput, lag and staleness tracking for firehose consumers.
+//!
+//! Shared by the Jetstream subscription behind [`RepoCache`](crate::RepoCache)
+//! and the relay consumer in winter-wiki-web, so both report the same numbers
+//! in their health output.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Width of the sliding window used to compute events/sec.
+pub const RATE_WINDOW_SECS: i64 = 60;
+
+/// Counters for a single firehose subscription.
+///
+/// Times are passed in explicitly by the `*_at` methods so the accounting can
+/// be tested without sleeping; the plain variants use the wall clock.
+pub struct FirehoseMetrics {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// When tracking started, used as the staleness reference before the
+    /// first event arrives.
+    started_at_us: i64,
+    total_events: u64,
+    /// Per-second event counts, oldest first, covering at most
+    /// [`RATE_WINDOW_SECS`] seconds.
+    buckets: VecDeque<(i64, u64)>,
+    /// Wall-clock time the most recent event was received.
+    last_received_us: Option<i64>,
+    /// Timestamp carried by the most recent event itself.
+    last_event_time_us: Option<i64>,
+    /// Sequence number of the most recent event, if the stream has one.
+    last_seq: Option<i64>,
+}
+
+/// Point-in-time view of [`FirehoseMetrics`], suitable for health output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FirehoseSnapshot {
+    pub total_events: u64,
+    /// Average rate over the last [`RATE_WINDOW_SECS`] seconds (or since
+    /// tracking started, if that is more recent).
+    pub events_per_sec: f64,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub last_seq: Option<i64>,
+    /// Seconds between now and the timestamp of the most recent event.
+    pub lag_seconds: Option<f64>,
+    /// Seconds since an event was last received.
+    pub idle_seconds: f64,
+    /// No event has been received within the staleness threshold.
+    pub stale: bool,
+}
+
+impl FirehoseMetrics {
+    pub fn new() -> Self {
+        Self::new_at(Utc::now().timestamp_micros())
+    }
+
+    /// Create metrics whose tracking started at `now_us`.
+    pub fn new_at(now_us: i64) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                started_at_us: now_us,
+                total_events: 0,
+                buckets: VecDeque::new(),
+                last_received_us: None,
+                last_event_time_us: None,
+                last_seq: None,
+            }),
+        }
+    }
+
+    /// Record an event received now.
+    ///
+    /// `event_time_us` is the timestamp carried by the event (Jetstream's
+    /// `time_us`, a commit's `time`), and `seq` its sequence number if the
+    /// stream provides one.
+    pub fn record(&self, event_time_us: Option<i64>, seq: Option<i64>) {
+        self.record_at(Utc::now().timestamp_micros(), event_time_us, seq);
+    }
+
+    /// Record an event received at `now_us`.
+    pub fn record_at(&self, now_us: i64, event_time_us: Option<i64>, seq: Option<i64>) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.total_events += 1;
+        inner.last_received_us = Some(now_us);
+        if event_time_us.is_some() {
+            inner.last_event_time_us = event_time_us;
+        }
+        if seq.is_some() {
+            inner.last_seq = seq;
+        }
+
+        let second = now_us.div_euclid(1_000_000);
+        match inner.buckets.back_mut() {
+            Some((s, count)) if *s == second => *count += 1,
+            _ => inner.buckets.push_back((second, 1)),
+        }
+        inner.evict(second);
+    }
+
+    /// Timestamp carried by the most recent event, if any.
+    pub fn last_event_time_us(&self) -> Option<i64> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_event_time_us
+    }
+
+    /// Snapshot the metrics now, treating the stream as stale once no event
+    /// has been received for `stale_after`.
+    pub fn snapshot(&self, stale_after: Duration) -> FirehoseSnapshot {
+        self.snapshot_at(Utc::now().timestamp_micros(), stale_after)
+    }
+
+    /// Snapshot the metrics as of `now_us`.
+    pub fn snapshot_at(&self, now_us: i64, stale_after: Duration) -> FirehoseSnapshot {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let second = now_us.div_euclid(1_000_000);
+        inner.evict(second);
+
+        let windowed: u64 = inner.buckets.iter().map(|(_, count)| count).sum();
+        let tracked_secs = (now_us - inner.started_at_us).max(0) as f64 / 1_000_000.0;
+        let window_secs = tracked_secs.clamp(1.0, RATE_WINDOW_SECS as f64);
+
+        let idle_since = inner.last_received_us.unwrap_or(inner.started_at_us);
+        let idle_seconds = (now_us - idle_since).max(0) as f64 / 1_000_000.0;
+
+        FirehoseSnapshot {
+            total_events: inner.total_events,
+            events_per_sec: windowed as f64 / window_secs,
+            last_event_at: inner
+                .last_event_time_us
+                .and_then(DateTime::<Utc>::from_timestamp_micros),
+            last_seq: inner.last_seq,
+            lag_seconds: inner
+                .last_event_time_us
+                .map(|t| (now_us - t).max(0) as f64 / 1_000_000.0),
+            idle_seconds,
+            stale: idle_seconds > stale_after.as_secs_f64(),
+        }
+    }
+}
+
+impl Default for FirehoseMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inner {
+    /// Drop buckets that have fallen out of the rate window ending at `second`.
+    fn evict(&mut self, second: i64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(s, _)| *s <= second - RATE_WINDOW_SECS)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: i64 = 1_000_000;
+    const T0: i64 = 1_700_000_000 * SEC;
+
+    #[test]
+    fn test_accumulates_events_and_rate() {
+        let metrics = FirehoseMetrics::new_at(T0);
+        for i in 0..120 {
+            // Two events per second for a minute.
+            metrics.record_at(T0 + i * SEC / 2, Some(T0 + i * SEC / 2), Some(i));
+        }
+
+        let snap = metrics.snapshot_at(T0 + 60 * SEC, Duration::from_secs(30));
+        assert_eq!(snap.total_events, 120);
+        assert_eq!(snap.last_seq, Some(119));
+        assert!(
+            (snap.events_per_sec - 2.0).abs() < 0.1,
+            "{}",
+            snap.events_per_sec
+        );
+        assert!(!snap.stale);
+    }
+
+    #[test]
+    fn test_rate_window_slides() {
+        let metrics = FirehoseMetrics::new_at(T0);
+        for _ in 0..50 {
+            metrics.record_at(T0, None, None);
+        }
+
+        let snap = metrics.snapshot_at(T0 + 2 * RATE_WINDOW_SECS * SEC, Duration::from_secs(600));
+        assert_eq!(snap.total_events, 50);
+        assert_eq!(snap.events_per_sec, 0.0);
+    }
+
+    #[test]
+    fn test_lag_uses_event_timestamp() {
+        let metrics = FirehoseMetrics::new_at(T0);
+        // Received now, but the event itself is five seconds old.
+        metrics.record_at(T0 + 10 * SEC, Some(T0 + 5 * SEC), None);
+
+        let snap = metrics.snapshot_at(T0 + 10 * SEC, Duration::from_secs(30));
+        assert_eq!(snap.lag_seconds, Some(5.0));
+        assert_eq!(snap.idle_seconds, 0.0);
+        assert_eq!(
+            snap.last_event_at.map(|t| t.timestamp_micros()),
+            Some(T0 + 5 * SEC)
+        );
+    }
+
+    #[test]
+    fn test_staleness_threshold() {
+        let metrics = FirehoseMetrics::new_at(T0);
+        let threshold = Duration::from_secs(30);
+
+        // Before any event, staleness counts from when tracking started.
+        assert!(!metrics.snapshot_at(T0 + 30 * SEC, threshold).stale);
+        assert!(metrics.snapshot_at(T0 + 31 * SEC, threshold).stale);
+
+        metrics.record_at(T0 + 40 * SEC, None, None);
+        assert!(!metrics.snapshot_at(T0 + 70 * SEC, threshold).stale);
+        assert!(metrics.snapshot_at(T0 + 71 * SEC, threshold).stale);
+    }
+}
@@ -12,8 +12,10 @@ use tokio::sync::watch;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, trace, warn};
 
-use crate::cache::{RepoCache, SyncState};
-use crate::dispatch::{dispatch_create_or_update_json, dispatch_delete, is_tracked_collection};
+use crate::cache::{LiveWrite, RepoCache, SyncState};
+use crate::dispatch::{
+    Dispatcher, dispatch_create_or_update_json, dispatch_delete, is_tracked_collection,
+};
 use crate::{AtprotoError, IDENTITY_COLLECTION, IDENTITY_KEY, Identity};
 
 /// Default Jetstream endpoint.
@@ -56,6 +58,16 @@ pub enum OperatorEvent {
     },
 }
 
+/// Check that a Jetstream endpoint accepts WebSocket connections, closing
+/// the connection straight away.
+pub async fn probe(url: &str) -> Result<(), AtprotoError> {
+    let (mut ws_stream, _) = connect_async(url)
+        .await
+        .map_err(|e| AtprotoError::WebSocket(format!("connection failed: {}", e)))?;
+    let _ = ws_stream.close(None).await;
+    Ok(())
+}
+
 /// Jetstream WebSocket client.
 pub struct JetstreamClient {
     /// Cache to update with events.
@@ -115,7 +127,12 @@ impl JetstreamClient {
         }
 
         // wantedCollections
-        for col in WANTED_COLLECTIONS {
+        let registered = Dispatcher::registered_collections();
+        for col in WANTED_COLLECTIONS
+            .iter()
+            .copied()
+            .chain(registered.iter().map(String::as_str))
+        {
             url.push('&');
             url.push_str("wantedCollections=");
             url.push_str(col);
@@ -287,6 +304,7 @@ impl JetstreamClient {
 
         // Update cursor
         *last_time_us = Some(event.time_us);
+        self.cache.record_event_time(event.time_us);
 
         match event.kind.as_str() {
             "commit" => {
@@ -345,34 +363,54 @@ impl JetstreamClient {
             return Ok(());
         }
 
-        match commit.operation.as_str() {
-            "create" | "update" => {
-                if let Some(record) = commit.record {
-                    let cid = commit.cid.as_deref().unwrap_or("unknown");
-
-                    // Handle special collections (identity)
-                    let handled =
-                        dispatch_create_or_update_json(&self.cache, collection, rkey, cid, record.clone())?;
-
-                    if !handled && collection == IDENTITY_COLLECTION && rkey == IDENTITY_KEY {
-                        if let Ok(identity) = serde_json::from_value::<Identity>(record) {
-                            if let Ok(rt) = tokio::runtime::Handle::try_current() {
-                                rt.block_on(self.cache.set_identity(identity, cid.to_string()));
-                            }
-                        }
-                    }
-                }
-            }
-            "delete" => {
-                dispatch_delete(&self.cache, collection, rkey);
-            }
+        let record = match commit.operation.as_str() {
+            "create" | "update" => match commit.record {
+                Some(record) => Some(record),
+                None => return Ok(()),
+            },
+            "delete" => None,
             _ => {
                 trace!(op = %commit.operation, "unknown jetstream operation");
+                return Ok(());
             }
-        }
+        };
+        let write = LiveWrite {
+            collection: commit.collection,
+            rkey: commit.rkey,
+            cid: commit.cid.unwrap_or_else(|| "unknown".to_string()),
+            record,
+        };
+
+        self.cache
+            .record_live_write(write, |write| apply_live_write(&self.cache, write))
+    }
+}
 
-        Ok(())
+/// Apply a change to one of our own records to the cache.
+pub(crate) fn apply_live_write(cache: &RepoCache, write: &LiveWrite) -> Result<(), AtprotoError> {
+    let Some(ref record) = write.record else {
+        dispatch_delete(cache, &write.collection, &write.rkey);
+        return Ok(());
+    };
+
+    // Handle special collections (identity)
+    let handled = dispatch_create_or_update_json(
+        cache,
+        &write.collection,
+        &write.rkey,
+        &write.cid,
+        record.clone(),
+    )?;
+
+    if !handled && write.collection == IDENTITY_COLLECTION && write.rkey == IDENTITY_KEY {
+        if let Ok(identity) = serde_json::from_value::<Identity>(record.clone()) {
+            if let Ok(rt) = tokio::runtime::Handle::try_current() {
+                rt.block_on(cache.set_identity(identity, write.cid.clone()));
+            }
+        }
     }
+
+    Ok(())
 }
 
 // =============================================================================
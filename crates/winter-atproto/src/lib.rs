@@ -8,6 +8,9 @@
 //! - **HTTP Client**: XRPC client for record CRUD operations
 //! - **Jetstream**: JSON WebSocket subscription for real-time updates
 //! - **Cache**: Thread-safe in-memory cache for facts and rules
+//! - **Firehose metrics**: Event rate, lag and staleness for health reporting
+//! - **Markdown**: Sanitized rendering of note and wiki content
+//! - **Validation**: Lexicon checks applied to records before they are cached
 //! - **Sync**: Coordinator for list_all_records hydration with Jetstream subscription
 
 pub mod cache;
@@ -16,21 +19,34 @@ mod client;
 pub mod deno_detect;
 pub mod dispatch;
 mod error;
+pub mod firehose_metrics;
 pub mod jetstream;
+pub mod markdown;
 mod records;
+pub mod safe_tools;
 pub mod sync;
+#[cfg(test)]
+mod test_support;
 mod types;
 mod uri;
+pub mod validate;
 
 pub use cache::{CacheUpdate, CachedRecord, RepoCache, ScopeFilter, SyncState};
 pub use deno_detect::code_needs_network;
-pub use car::{CarParseResult, parse_car};
-pub use dispatch::{dispatch_create_or_update_json, dispatch_delete, extract_record_to_result, is_tracked_collection};
+pub use car::{CarParseResult, CustomRecord, parse_car};
+pub use dispatch::{
+    CollectionHandler, Dispatcher, dispatch_create_or_update_json, dispatch_delete,
+    extract_record_to_result, is_tracked_collection,
+};
 pub use client::{ApplyWritesResponse, AtprotoClient, CommitInfo, WriteOp, WriteResult};
 pub use error::AtprotoError;
+pub use firehose_metrics::{FirehoseMetrics, FirehoseSnapshot};
 pub use jetstream::{DEFAULT_JETSTREAM_URL, JetstreamClient, OperatorEvent, OperatorEventCallback};
+pub use markdown::render_markdown;
 pub use records::*;
-pub use sync::{SyncCoordinator, SyncCoordinatorBuilder};
+pub use safe_tools::{SAFE_TOOLS, SafeToolSet};
+pub use sync::{ReplaySummary, SyncCoordinator, SyncCoordinatorBuilder};
 pub use types::*;
 pub use types::{FactDeclArg, FactDeclaration};
 pub use uri::{AtUri, AtUriError};
+pub use validate::Validate;
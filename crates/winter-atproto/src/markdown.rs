@@ -0,0 +1,145 @@
+//! Markdown rendering for user-authored content.
+//!
+//! Notes, wiki entries and blog posts are written by Winter and by operators,
+//! and are shown in the web UIs. The renderer only ever emits the tags that
+//! pulldown-cmark produces for Markdown syntax (paragraphs, emphasis, lists,
+//! code, tables, links, images and so on). Raw HTML in the source is escaped
+//! and shown as text rather than passed through, so `<script>` blocks and
+//! `onclick` attributes never reach the page, and links or images with a
+//! scheme other than `http`, `https` or `mailto` are rewritten to `#`.
+
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, html};
+
+/// URL schemes allowed in links and images. Scheme-less (relative) URLs are
+/// always allowed.
+const SAFE_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Render Markdown to sanitized HTML.
+pub fn render_markdown(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+
+    let parser = Parser::new_ext(content, options).map(sanitize_event);
+    let mut output = String::with_capacity(content.len() * 3 / 2);
+    html::push_html(&mut output, parser);
+    output
+}
+
+/// Whether `url` is safe to use as a link target or image source.
+pub fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters inside a scheme,
+    // so "java\tscript:" must be treated like "javascript:".
+    let normalized: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+
+    match normalized.find([':', '/', '?', '#']) {
+        Some(i) if normalized[i..].starts_with(':') => {
+            let scheme = normalized[..i].to_ascii_lowercase();
+            SAFE_SCHEMES.contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+fn sanitize_event(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    }
+}
+
+fn sanitize_url(url: CowStr<'_>) -> CowStr<'_> {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_emphasis_and_links() {
+        let html =
+            render_markdown("Some *emphasis*, **strong** and [a link](https://example.com).");
+        assert!(html.contains("<em>emphasis</em>"));
+        assert!(html.contains("<strong>strong</strong>"));
+        assert!(html.contains(r#"<a href="https://example.com">a link</a>"#));
+    }
+
+    #[test]
+    fn test_renders_block_elements() {
+        let html = render_markdown("# Title\n\n- one\n- two\n\n```\ncode\n```\n\n~~old~~");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<li>one</li>"));
+        assert!(html.contains("<pre><code>code\n</code></pre>"));
+        assert!(html.contains("<del>old</del>"));
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped() {
+        let html = render_markdown(
+            "<script>alert('xss')</script>\n\nclick <a href=\"#\" onclick=\"steal()\">here</a>",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<a href=\"#\" onclick"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("&lt;a href="));
+    }
+
+    #[test]
+    fn test_unsafe_link_schemes_are_neutralized() {
+        for source in [
+            "[x](javascript:alert(1))",
+            "[x](JavaScript:alert(1))",
+            "<javascript:alert(1)>",
+            "![x](data:text/html;base64,PHNjcmlwdD4=)",
+            "[x](vbscript:msgbox)",
+        ] {
+            let html = render_markdown(source).to_ascii_lowercase();
+            for attr in ["=\"javascript", "=\"vbscript", "=\"data:"] {
+                assert!(!html.contains(attr), "{source} -> {html}");
+            }
+            assert!(html.contains("=\"#\""), "{source} -> {html}");
+        }
+    }
+
+    #[test]
+    fn test_is_safe_url() {
+        assert!(is_safe_url("https://example.com"));
+        assert!(is_safe_url("mailto:winter@example.com"));
+        assert!(is_safe_url("/notes/abc"));
+        assert!(is_safe_url("#heading"));
+        assert!(is_safe_url("page?a=b:c"));
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url("java\tscript:alert(1)"));
+        assert!(!is_safe_url(" data:text/html,hi"));
+    }
+}
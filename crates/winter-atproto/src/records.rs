@@ -45,6 +45,9 @@ pub const WIKI_LINK_COLLECTION: &str = "diy.razorgirl.winter.wikiLink";
 /// Lexicon NSID for Winter trigger records.
 pub const TRIGGER_COLLECTION: &str = "diy.razorgirl.winter.trigger";
 
+/// Lexicon NSID for Winter migration state records.
+pub const MIGRATION_STATE_COLLECTION: &str = "diy.razorgirl.winter.migrationState";
+
 /// Lexicon NSID for WhiteWind blog entry records.
 pub const BLOG_COLLECTION: &str = "com.whtwnd.blog.entry";
 
@@ -57,6 +60,9 @@ pub const STATE_KEY: &str = "self";
 /// The singleton key for the secret metadata record.
 pub const SECRET_META_KEY: &str = "self";
 
+/// The singleton key for the migration state record.
+pub const MIGRATION_STATE_KEY: &str = "self";
+
 // =============================================================================
 // Bluesky Collection Constants
 // =============================================================================
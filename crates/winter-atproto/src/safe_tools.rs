@@ -0,0 +1,178 @@
+//! Canonical safe-tool classification.
+//!
+//! A custom tool is "safe" (auto-approvable without an operator) when it
+//! needs no network, secrets, or commands, and only chains to read-only
+//! built-in MCP tools. Both the MCP server and the operator's approval CLI
+//! use this module so the two sides can't disagree about which tools need
+//! approval.
+
+use crate::deno_detect::code_needs_network;
+use crate::types::CustomTool;
+
+/// A set of built-in MCP tool names that custom tools may call without approval.
+#[derive(Debug, Clone, Copy)]
+pub struct SafeToolSet {
+    names: &'static [&'static str],
+}
+
+impl SafeToolSet {
+    /// Create a set from a static list of tool names.
+    pub const fn new(names: &'static [&'static str]) -> Self {
+        Self { names }
+    }
+
+    /// Whether `name` is in the set.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&name)
+    }
+
+    /// All tool names in the set.
+    pub const fn names(&self) -> &'static [&'static str] {
+        self.names
+    }
+
+    /// Whether a tool needing these permissions is safe to run without
+    /// operator approval: no network, secrets or commands, and only tools
+    /// from this set.
+    pub fn permits<'a>(
+        &self,
+        network: bool,
+        secrets: usize,
+        commands: usize,
+        mut tools: impl Iterator<Item = &'a str>,
+    ) -> bool {
+        !network && secrets == 0 && commands == 0 && tools.all(|t| self.contains(t))
+    }
+}
+
+/// Built-in MCP tools that are safe to call without operator approval.
+/// These are all read-only operations that don't modify state.
+pub const SAFE_TOOLS: SafeToolSet = SafeToolSet::new(&[
+    "query_facts",
+    "list_rules",
+    "list_directives",
+    "get_directive_history",
+    "detect_directive_conflicts",
+    "list_jobs",
+    "list_notes",
+    "get_note",
+    "list_facts",
+    "get_fact",
+    "list_fact_declarations",
+    "get_thread_context",
+    "search_posts",
+    "get_identity",
+    "query_and_enrich",
+    "list_predicates",
+    "predicate_graph",
+    "derived_fact_stats",
+    "list_custom_tools",
+    "get_custom_tool",
+    "list_secrets",
+    "list_thoughts",
+    "get_thought",
+    "list_blog_posts",
+    "get_blog_post",
+    "check_interruption",
+    "pds_list_records",
+    "pds_get_record",
+    "pds_get_records",
+    "search_users",
+]);
+
+impl CustomTool {
+    /// Whether this tool is safe to run without operator approval.
+    ///
+    /// Network need is taken from `requires_network` when set, otherwise
+    /// detected from the code. References to other custom tools (AT URIs)
+    /// are never in a `SafeToolSet`, so they always make a tool unsafe here;
+    /// the MCP server may still auto-approve those after resolving them.
+    pub fn is_safe(&self, safe_tools: &SafeToolSet) -> bool {
+        let needs_network = self
+            .requires_network
+            .unwrap_or_else(|| code_needs_network(&self.code));
+        safe_tools.permits(
+            needs_network,
+            self.required_secrets.len(),
+            self.required_commands.len(),
+            self.required_tools.iter().map(String::as_str),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn tool(code: &str) -> CustomTool {
+        CustomTool {
+            name: "t".to_string(),
+            description: String::new(),
+            code: code.to_string(),
+            input_schema: serde_json::json!({}),
+            required_secrets: Vec::new(),
+            requires_workspace: None,
+            requires_network: None,
+            required_commands: Vec::new(),
+            required_tools: Vec::new(),
+            cache_ttl_secs: None,
+            version: 1,
+            created_at: Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    #[test]
+    fn test_pure_tool_is_safe() {
+        assert!(tool("return 1;").is_safe(&SAFE_TOOLS));
+    }
+
+    #[test]
+    fn test_network_makes_unsafe() {
+        assert!(!tool("await fetch(url);").is_safe(&SAFE_TOOLS));
+
+        let mut explicit = tool("return 1;");
+        explicit.requires_network = Some(true);
+        assert!(!explicit.is_safe(&SAFE_TOOLS));
+
+        // Explicit `false` overrides detection
+        let mut overridden = tool("await fetch(url);");
+        overridden.requires_network = Some(false);
+        assert!(overridden.is_safe(&SAFE_TOOLS));
+    }
+
+    #[test]
+    fn test_secrets_and_commands_make_unsafe() {
+        let mut t = tool("return 1;");
+        t.required_secrets = vec!["KEY".to_string()];
+        assert!(!t.is_safe(&SAFE_TOOLS));
+
+        let mut t = tool("return 1;");
+        t.required_commands = vec!["git".to_string()];
+        assert!(!t.is_safe(&SAFE_TOOLS));
+    }
+
+    #[test]
+    fn test_chaining_only_to_safe_tools() {
+        let mut t = tool("return 1;");
+        t.required_tools = vec!["query_facts".to_string(), "list_notes".to_string()];
+        assert!(t.is_safe(&SAFE_TOOLS));
+
+        t.required_tools.push("create_fact".to_string());
+        assert!(!t.is_safe(&SAFE_TOOLS));
+
+        let mut t = tool("return 1;");
+        t.required_tools = vec!["at://did:plc:x/diy.razorgirl.winter.tool/abc".to_string()];
+        assert!(!t.is_safe(&SAFE_TOOLS));
+    }
+
+    #[test]
+    fn test_custom_set() {
+        const ONLY_FACTS: SafeToolSet = SafeToolSet::new(&["query_facts"]);
+        let mut t = tool("return 1;");
+        t.required_tools = vec!["list_notes".to_string()];
+        assert!(t.is_safe(&SAFE_TOOLS));
+        assert!(!t.is_safe(&ONLY_FACTS));
+    }
+}
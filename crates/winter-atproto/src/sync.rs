@@ -4,20 +4,73 @@
 //! 1. Download full repo as CAR file (single HTTP request)
 //! 2. Parse MST and populate cache
 //! 3. Start Jetstream WebSocket for live updates
+//!
+//! [`SyncCoordinator::replay`] repeats steps 1-2 on demand so changes to cache
+//! logic can be applied to existing records without restarting.
 
 use std::sync::Arc;
 
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
-use tracing::{error, info};
+use serde::Serialize;
+use tracing::{error, info, warn};
 
 use crate::cache::{RepoCache, SyncState};
-use crate::car;
+use crate::{car, dispatch, jetstream};
 use crate::jetstream::{
     DEFAULT_JETSTREAM_URL, JetstreamClient, OperatorEventCallback,
 };
 use crate::{AtprotoClient, AtprotoError};
 
+/// Record counts in the cache after a [`SyncCoordinator::replay`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReplaySummary {
+    /// Repository revision the cache was rebuilt from.
+    pub rev: Option<String>,
+    pub facts: usize,
+    pub rules: usize,
+    pub thoughts: usize,
+    pub notes: usize,
+    pub jobs: usize,
+    pub directives: usize,
+    pub declarations: usize,
+    pub tools: usize,
+    pub tool_approvals: usize,
+    pub triggers: usize,
+    pub posts: usize,
+    pub follows: usize,
+    pub likes: usize,
+    pub reposts: usize,
+    pub blog_entries: usize,
+    pub wiki_entries: usize,
+    pub wiki_links: usize,
+}
+
+impl ReplaySummary {
+    fn from_cache(cache: &RepoCache, rev: Option<String>) -> Self {
+        Self {
+            rev,
+            facts: cache.fact_count(),
+            rules: cache.rule_count(),
+            thoughts: cache.thought_count(),
+            notes: cache.note_count(),
+            jobs: cache.job_count(),
+            directives: cache.directive_count(),
+            declarations: cache.declaration_count(),
+            tools: cache.tool_count(),
+            tool_approvals: cache.tool_approval_count(),
+            triggers: cache.trigger_count(),
+            posts: cache.post_count(),
+            follows: cache.follow_count(),
+            likes: cache.like_count(),
+            reposts: cache.repost_count(),
+            blog_entries: cache.blog_entry_count(),
+            wiki_entries: cache.wiki_entry_count(),
+            wiki_links: cache.wiki_link_count(),
+        }
+    }
+}
+
 /// Sync coordinator for managing cache synchronization.
 pub struct SyncCoordinator {
     /// ATProto client for fetching records.
@@ -134,20 +187,75 @@ impl SyncCoordinator {
         Ok(jetstream_handle)
     }
 
+    /// Re-download the repo and rebuild the cache from it.
+    ///
+    /// The CAR is fetched and parsed before anything is cleared, so a failed
+    /// download leaves the existing cache intact. Cache updates are not
+    /// broadcast during the rebuild; subscribers that derive state from the
+    /// cache (the datalog cache) should repopulate from it afterwards.
+    ///
+    /// Jetstream writes that arrive once the download has started are
+    /// buffered and re-applied on top of the rebuilt cache, since the CAR may
+    /// predate them.
+    pub async fn replay(&self) -> Result<ReplaySummary, AtprotoError> {
+        info!(did = %self.did, "replaying repo into cache");
+        self.cache.buffer_live_writes();
+        let parsed = match self.fetch_repo().await {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                // Buffered writes were applied as they arrived; nothing to redo
+                self.cache.drain_live_writes(|_| {});
+                return Err(e);
+            }
+        };
+        let rev = parsed.rev.clone();
+
+        self.cache.set_state(SyncState::Syncing);
+        self.cache.clear();
+        self.apply_parsed(parsed).await;
+        self.cache.drain_live_writes(|write| {
+            if let Err(e) = jetstream::apply_live_write(&self.cache, write) {
+                warn!(
+                    collection = %write.collection,
+                    rkey = %write.rkey,
+                    error = %e,
+                    "failed to re-apply live write after replay"
+                );
+            }
+        });
+        self.cache.set_state(SyncState::Live);
+
+        let summary = ReplaySummary::from_cache(&self.cache, rev);
+        info!(
+            facts = summary.facts,
+            rules = summary.rules,
+            "repo replayed into cache"
+        );
+        Ok(summary)
+    }
+
     /// Populate the cache by downloading the full repo as a CAR file.
     ///
     /// This is much faster than fetching per-collection via list_all_records
     /// because it's a single HTTP request for the entire repo.
     async fn populate_cache(&self) -> Result<(), AtprotoError> {
-        // Suppress broadcasts during bulk population
-        self.cache.set_suppress_broadcasts(true);
+        let parsed = self.fetch_repo().await?;
+        self.apply_parsed(parsed).await;
+        Ok(())
+    }
 
-        // Download full repo as CAR
+    /// Download the full repo as a CAR file and parse it.
+    async fn fetch_repo(&self) -> Result<car::CarParseResult, AtprotoError> {
         let (car_bytes, _rev) = self.client.get_repo(&self.did).await?;
         info!(bytes = car_bytes.len(), "downloaded repo CAR file");
 
-        // Parse CAR and extract all records
-        let parsed = car::parse_car(&car_bytes).await?;
+        car::parse_car(&car_bytes).await
+    }
+
+    /// Load parsed CAR records into the cache.
+    async fn apply_parsed(&self, parsed: car::CarParseResult) {
+        // Suppress broadcasts during bulk population
+        self.cache.set_suppress_broadcasts(true);
 
         // Set repo revision
         if let Some(ref rev) = parsed.rev {
@@ -175,6 +283,7 @@ impl SyncCoordinator {
             parsed.wiki_links.into_iter().map(|(k, (v, c))| (k, v, c)),
             parsed.triggers.into_iter().map(|(k, (v, c))| (k, v, c)),
         );
+        dispatch::apply_custom_records(&self.cache, parsed.custom);
 
         // Set identity and daemon state from CAR (singletons handled separately)
         if let Some((identity, cid)) = parsed.daemon_state {
@@ -184,8 +293,6 @@ impl SyncCoordinator {
 
         // Re-enable broadcasts
         self.cache.set_suppress_broadcasts(false);
-
-        Ok(())
     }
 }
 
@@ -282,4 +389,249 @@ mod tests {
 
         assert_eq!(coordinator.operator_did, Some("did:plc:operator".to_string()));
     }
+
+    /// Builds CAR files shaped like a `com.atproto.sync.getRepo` export: a
+    /// commit pointing at a single-node MST holding the given records.
+    mod fixture {
+        use ipld_core::cid::Cid;
+        use ipld_core::cid::multihash::Multihash;
+        use iroh_car::{CarHeader, CarWriter};
+        use serde::Serialize;
+
+        #[derive(Serialize)]
+        struct Entry {
+            p: u64,
+            #[serde(with = "serde_bytes")]
+            k: Vec<u8>,
+            v: Cid,
+            t: Option<Cid>,
+        }
+
+        #[derive(Serialize)]
+        struct Node {
+            l: Option<Cid>,
+            e: Vec<Entry>,
+        }
+
+        #[derive(Serialize)]
+        struct Commit {
+            did: String,
+            version: u32,
+            data: Cid,
+            rev: String,
+            prev: Option<Cid>,
+            #[serde(with = "serde_bytes")]
+            sig: Vec<u8>,
+        }
+
+        /// A distinct CID per block; the parser never checks digests.
+        fn cid(n: usize) -> Cid {
+            let digest = [n as u8; 32];
+            Cid::new_v1(0x71, Multihash::<64>::wrap(0x12, &digest).unwrap())
+        }
+
+        pub async fn car(rev: &str, records: &[(&str, serde_json::Value)]) -> Vec<u8> {
+            let mut records = records.to_vec();
+            records.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut blocks = Vec::new();
+            let mut entries = Vec::new();
+            for (key, value) in records {
+                let cid = cid(blocks.len() + 1);
+                blocks.push((cid, serde_ipld_dagcbor::to_vec(&value).unwrap()));
+                entries.push(Entry {
+                    p: 0,
+                    k: key.as_bytes().to_vec(),
+                    v: cid,
+                    t: None,
+                });
+            }
+
+            let node_cid = cid(blocks.len() + 1);
+            let node = Node {
+                l: None,
+                e: entries,
+            };
+            blocks.push((node_cid, serde_ipld_dagcbor::to_vec(&node).unwrap()));
+
+            let commit_cid = cid(blocks.len() + 1);
+            let commit = Commit {
+                did: crate::test_support::DID.to_string(),
+                version: 3,
+                data: node_cid,
+                rev: rev.to_string(),
+                prev: None,
+                sig: vec![0; 64],
+            };
+            blocks.push((commit_cid, serde_ipld_dagcbor::to_vec(&commit).unwrap()));
+
+            let mut buf = Vec::new();
+            let mut writer = CarWriter::new(CarHeader::new_v1(vec![commit_cid]), &mut buf);
+            for (cid, data) in blocks {
+                writer.write(cid, data).await.unwrap();
+            }
+            writer.finish().await.unwrap();
+            buf
+        }
+    }
+
+    mod replay {
+        use super::*;
+        use serde_json::json;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+        use crate::cache::LiveWrite;
+        use crate::test_support::{DID, logged_in_client};
+
+        fn fact(predicate: &str) -> serde_json::Value {
+            json!({
+                "predicate": predicate,
+                "args": ["did:plc:a"],
+                "createdAt": "2026-01-01T00:00:00Z"
+            })
+        }
+
+        fn stale_fact() -> crate::Fact {
+            serde_json::from_value(fact("stale")).unwrap()
+        }
+
+        #[tokio::test]
+        async fn replay_rebuilds_cache_from_car() {
+            let server = MockServer::start().await;
+            let car = fixture::car(
+                "rev2",
+                &[
+                    ("diy.razorgirl.winter.fact/a", fact("likes")),
+                    ("diy.razorgirl.winter.fact/b", fact("follows")),
+                    (
+                        "diy.razorgirl.winter.rule/r",
+                        json!({
+                            "name": "mutual",
+                            "description": "mutual follows",
+                            "head": "mutual(X, Y)",
+                            "body": ["follows(X, Y)", "follows(Y, X)"],
+                            "createdAt": "2026-01-01T00:00:00Z"
+                        }),
+                    ),
+                    (
+                        "diy.razorgirl.winter.note/n",
+                        json!({
+                            "title": "Note",
+                            "content": "body",
+                            "createdAt": "2026-01-01T00:00:00Z",
+                            "lastUpdated": "2026-01-01T00:00:00Z"
+                        }),
+                    ),
+                ],
+            )
+            .await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.sync.getRepo"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(car))
+                .mount(&server)
+                .await;
+
+            let cache = RepoCache::new();
+            cache.upsert_fact("stale".to_string(), stale_fact(), "bafystale".to_string());
+            let coordinator =
+                SyncCoordinator::new(logged_in_client(&server).await, DID, Arc::clone(&cache));
+
+            let summary = coordinator.replay().await.unwrap();
+
+            assert_eq!(summary.rev.as_deref(), Some("rev2"));
+            assert_eq!(summary.facts, 2);
+            assert_eq!(summary.rules, 1);
+            assert_eq!(summary.notes, 1);
+            assert_eq!(summary.thoughts, 0);
+            assert!(cache.get_fact("stale").is_none());
+            assert!(cache.get_fact("a").is_some());
+            assert_eq!(cache.state(), SyncState::Live);
+            assert_eq!(cache.repo_rev().await.as_deref(), Some("rev2"));
+        }
+
+        /// Serves a CAR, delivering Jetstream writes while the download is in
+        /// flight so the CAR doesn't include them.
+        struct WritesDuringDownload {
+            cache: Arc<RepoCache>,
+            car: Vec<u8>,
+            writes: Vec<LiveWrite>,
+        }
+
+        impl Respond for WritesDuringDownload {
+            fn respond(&self, _: &Request) -> ResponseTemplate {
+                for write in &self.writes {
+                    self.cache
+                        .record_live_write(write.clone(), |write| {
+                            jetstream::apply_live_write(&self.cache, write)
+                        })
+                        .unwrap();
+                }
+                ResponseTemplate::new(200).set_body_bytes(self.car.clone())
+            }
+        }
+
+        #[tokio::test]
+        async fn writes_during_replay_survive_the_rebuild() {
+            let server = MockServer::start().await;
+            let car = fixture::car(
+                "rev2",
+                &[
+                    ("diy.razorgirl.winter.fact/a", fact("likes")),
+                    ("diy.razorgirl.winter.fact/b", fact("follows")),
+                ],
+            )
+            .await;
+            let cache = RepoCache::new();
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.sync.getRepo"))
+                .respond_with(WritesDuringDownload {
+                    cache: Arc::clone(&cache),
+                    car,
+                    writes: vec![
+                        LiveWrite {
+                            collection: crate::FACT_COLLECTION.to_string(),
+                            rkey: "late".to_string(),
+                            cid: "bafylate".to_string(),
+                            record: Some(fact("knows")),
+                        },
+                        LiveWrite {
+                            collection: crate::FACT_COLLECTION.to_string(),
+                            rkey: "b".to_string(),
+                            cid: "unknown".to_string(),
+                            record: None,
+                        },
+                    ],
+                })
+                .mount(&server)
+                .await;
+            let coordinator =
+                SyncCoordinator::new(logged_in_client(&server).await, DID, Arc::clone(&cache));
+
+            let summary = coordinator.replay().await.unwrap();
+
+            assert!(cache.get_fact("a").is_some());
+            assert_eq!(cache.get_fact("late").unwrap().cid, "bafylate");
+            assert!(cache.get_fact("b").is_none());
+            assert_eq!(summary.facts, 2);
+        }
+
+        #[tokio::test]
+        async fn failed_download_keeps_existing_cache() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.sync.getRepo"))
+                .respond_with(ResponseTemplate::new(500))
+                .mount(&server)
+                .await;
+
+            let cache = RepoCache::new();
+            cache.upsert_fact("stale".to_string(), stale_fact(), "bafystale".to_string());
+            let coordinator =
+                SyncCoordinator::new(logged_in_client(&server).await, DID, Arc::clone(&cache));
+
+            assert!(coordinator.replay().await.is_err());
+            assert!(cache.get_fact("stale").is_some());
+        }
+    }
 }
@@ -0,0 +1,26 @@
+//! Helpers shared by tests that talk to a mock PDS.
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::AtprotoClient;
+
+/// DID of the account the mock PDS logs in as.
+pub const DID: &str = "did:plc:testuser123";
+
+/// Mount a createSession mock on `server` and return a client logged in to it.
+pub async fn logged_in_client(server: &MockServer) -> AtprotoClient {
+    Mock::given(method("POST"))
+        .and(path("/xrpc/com.atproto.server.createSession"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "did": DID,
+            "handle": "test.example.com",
+            "accessJwt": "test-access-token",
+            "refreshJwt": "test-refresh-token"
+        })))
+        .mount(server)
+        .await;
+    let client = AtprotoClient::new(server.uri());
+    client.login("test.example.com", "password").await.unwrap();
+    client
+}
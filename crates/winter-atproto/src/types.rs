@@ -43,6 +43,15 @@ impl Tid {
         Self::from_u64(combined)
     }
 
+    /// Build the TID for a fixed time and clock identifier.
+    ///
+    /// Unlike [`Tid::now`], the same inputs always give the same TID, for
+    /// record keys that must not change between runs.
+    pub fn from_datetime(at: DateTime<Utc>, clock_id: u16) -> Self {
+        let micros = at.timestamp_micros().max(0) as u64;
+        Self::from_u64((micros << 10) | (u64::from(clock_id) & 0x3FF))
+    }
+
     /// Create a TID from a raw 63-bit value.
     fn from_u64(val: u64) -> Self {
         // Base32-sortable encoding (uses digits 2-7 and a-z)
@@ -381,6 +390,10 @@ pub struct Fact {
     /// Optional expiration timestamp. Facts past this time are excluded from default queries.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
+    /// Soft-delete tombstone. Deleted facts are hidden from queries until
+    /// restored, or hard-deleted once the retention window passes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// Serialize confidence as a string for ATProto compatibility.
@@ -732,6 +745,62 @@ pub struct Note {
     /// When this note was last updated.
     #[serde(default = "default_datetime")]
     pub last_updated: DateTime<Utc>,
+    /// Soft-delete tombstone. Deleted notes are hidden from listings until
+    /// restored, or hard-deleted once the retention window passes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Record key of a fact referenced from [`Note::related_facts`].
+///
+/// References are normally fact AT URIs, but bare record keys are accepted
+/// too. URIs pointing at other collections give `None`.
+pub fn fact_ref_rkey(reference: &str) -> Option<&str> {
+    let reference = reference.trim();
+    match reference.strip_prefix("at://") {
+        Some(path) => {
+            let mut parts = path.split('/');
+            let (_did, collection, rkey) = (parts.next()?, parts.next()?, parts.next()?);
+            (collection == crate::FACT_COLLECTION && !rkey.is_empty() && parts.next().is_none())
+                .then_some(rkey)
+        }
+        None => (!reference.is_empty() && !reference.contains('/')).then_some(reference),
+    }
+}
+
+/// Resolve a note's related facts against a batch of fetched facts.
+///
+/// Returns one entry per reference, in order. Dangling references (the
+/// fact was deleted, or the reference isn't a fact) resolve to `None`.
+pub fn resolve_related_facts<'a>(
+    note: &'a Note,
+    facts: &'a std::collections::HashMap<String, Fact>,
+) -> Vec<(&'a str, Option<&'a Fact>)> {
+    note.related_facts
+        .iter()
+        .map(|reference| {
+            let fact = fact_ref_rkey(reference).and_then(|rkey| facts.get(rkey));
+            (reference.as_str(), fact)
+        })
+        .collect()
+}
+
+/// Notes whose `related_facts` reference fact `rkey`, ordered by note key.
+pub fn note_backlinks<'a>(
+    rkey: &str,
+    notes: &'a std::collections::HashMap<String, Note>,
+) -> Vec<(&'a str, &'a Note)> {
+    let mut backlinks: Vec<(&str, &Note)> = notes
+        .iter()
+        .filter(|(_, note)| {
+            note.related_facts
+                .iter()
+                .any(|reference| fact_ref_rkey(reference) == Some(rkey))
+        })
+        .map(|(key, note)| (key.as_str(), note))
+        .collect();
+    backlinks.sort_by_key(|(key, _)| *key);
+    backlinks
 }
 
 /// Wiki entry record (diy.razorgirl.winter.wikiEntry).
@@ -795,6 +864,22 @@ pub struct WikiLink {
     pub created_at: DateTime<Utc>,
 }
 
+/// Prefix of [`WikiLink::target`] for a link to a slug with no entry yet.
+///
+/// These "red links" are created from `[[slug]]` references to missing
+/// entries and are pointed at the entry's AT URI once it exists.
+pub const UNRESOLVED_WIKI_TARGET_PREFIX: &str = "wiki:";
+
+/// Link target for a wiki slug that doesn't resolve to an entry yet.
+pub fn unresolved_wiki_target(slug: &str) -> String {
+    format!("{}{}", UNRESOLVED_WIKI_TARGET_PREFIX, slug)
+}
+
+/// The slug of an unresolved link target, or `None` for a resolved AT URI.
+pub fn unresolved_wiki_slug(target: &str) -> Option<&str> {
+    target.strip_prefix(UNRESOLVED_WIKI_TARGET_PREFIX)
+}
+
 /// WhiteWind blog entry record (com.whtwnd.blog.entry).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -851,6 +936,69 @@ pub struct Job {
     pub failure_count: u32,
     /// When this job was created.
     pub created_at: DateTime<Utc>,
+    /// When an immediate run outside the schedule was requested
+    /// (cleared once that run finishes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_requested_at: Option<DateTime<Utc>>,
+    /// Record keys of jobs whose most recent run must have succeeded before
+    /// this job runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+/// Find the dependency cycle that giving job `rkey` the dependencies
+/// `depends_on` would leave it waiting on.
+///
+/// `dependencies_of` looks up the dependencies of any other job (`None` if it
+/// doesn't exist). Every job reachable from `rkey` is searched, so a cycle
+/// among existing jobs is found as well as one that closes back at `rkey`.
+/// Returns the cycle as a path of record keys that starts and ends at the
+/// same job.
+pub fn find_job_dependency_cycle<F>(
+    rkey: &str,
+    depends_on: &[String],
+    dependencies_of: F,
+) -> Option<Vec<String>>
+where
+    F: Fn(&str) -> Option<Vec<String>>,
+{
+    let mut path = vec![rkey.to_string()];
+    let mut acyclic = std::collections::HashSet::new();
+    dependency_cycle_from(depends_on, &dependencies_of, &mut path, &mut acyclic)
+}
+
+/// Depth-first search below the last job in `path`, whose dependencies are
+/// `depends_on`. Jobs in `acyclic` have already been searched without
+/// finding a cycle.
+fn dependency_cycle_from<F>(
+    depends_on: &[String],
+    dependencies_of: &F,
+    path: &mut Vec<String>,
+    acyclic: &mut std::collections::HashSet<String>,
+) -> Option<Vec<String>>
+where
+    F: Fn(&str) -> Option<Vec<String>>,
+{
+    for dep in depends_on {
+        if let Some(start) = path.iter().position(|rkey| rkey == dep) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(dep.clone());
+            return Some(cycle);
+        }
+        if acyclic.contains(dep) {
+            continue;
+        }
+
+        path.push(dep.clone());
+        let next = dependencies_of(dep).unwrap_or_default();
+        if let Some(cycle) = dependency_cycle_from(&next, dependencies_of, path, acyclic) {
+            return Some(cycle);
+        }
+        path.pop();
+        acyclic.insert(dep.clone());
+    }
+
+    None
 }
 
 /// Job schedule configuration.
@@ -887,6 +1035,13 @@ pub struct Thought {
     pub kind: ThoughtKind,
     /// Content of the thought.
     pub content: String,
+    /// How `content` is encoded.
+    ///
+    /// Absent on records written before the discriminator existed; the
+    /// `thought-content-types` migration backfills it. Use
+    /// [`Thought::effective_content_type`] when reading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<ThoughtContentType>,
     /// What triggered this thought.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trigger: Option<String>,
@@ -943,6 +1098,52 @@ pub enum ThoughtKind {
     ToolCall,
 }
 
+impl Thought {
+    /// The content type of this thought, inferring it for records that
+    /// predate the `content_type` field.
+    pub fn effective_content_type(&self) -> ThoughtContentType {
+        self.content_type
+            .clone()
+            .unwrap_or_else(|| ThoughtContentType::infer(&self.kind, &self.content))
+    }
+}
+
+/// Encoding of a thought's `content` field.
+///
+/// Lets readers route rendering on an explicit discriminator instead of
+/// sniffing the content string.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThoughtContentType {
+    /// Freeform human-readable text.
+    #[default]
+    Text,
+    /// A JSON tool call payload (`{"tool": ..., "args": ..., ...}`).
+    ToolCall,
+    /// The pre-JSON textual tool call format (`Called tool_name ...`).
+    LegacyToolCall,
+}
+
+impl ThoughtContentType {
+    /// Infer the content type of a record written without a discriminator.
+    ///
+    /// Only used for legacy records; new thoughts set `content_type`
+    /// explicitly when they are created.
+    pub fn infer(kind: &ThoughtKind, content: &str) -> Self {
+        if *kind != ThoughtKind::ToolCall {
+            return Self::Text;
+        }
+        let trimmed = content.trim();
+        if trimmed.starts_with('{') {
+            Self::ToolCall
+        } else if trimmed.starts_with("Called ") {
+            Self::LegacyToolCall
+        } else {
+            Self::Text
+        }
+    }
+}
+
 /// Daemon state record (singleton).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -958,12 +1159,38 @@ pub struct DaemonState {
     /// can access it via CAR file without needing to call the API.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub followers: Vec<String>,
+    /// When `followers` was last synced, so an empty list can be told apart
+    /// from one that was never synced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub followers_synced_at: Option<DateTime<Utc>>,
     /// When this state record was created.
     pub created_at: DateTime<Utc>,
     /// When this state record was last updated.
     pub last_updated: DateTime<Utc>,
 }
 
+/// Migration state record (singleton).
+///
+/// Tracks which data migrations have been applied so the runner never
+/// re-applies one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationState {
+    /// Migrations applied so far, in the order they ran.
+    #[serde(default)]
+    pub applied: Vec<AppliedMigration>,
+}
+
+/// A single applied migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    /// Migration id.
+    pub id: String,
+    /// When the migration was applied (or recorded as already satisfied).
+    pub applied_at: DateTime<Utc>,
+}
+
 /// Custom tool record for Deno-based tools.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -994,6 +1221,10 @@ pub struct CustomTool {
     /// AT URIs enable cross-agent tool sharing between different PDS instances.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub required_tools: Vec<String>,
+    /// How long to cache results for identical input, in seconds.
+    /// Only set for deterministic tools; tools with side effects leave it unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_secs: Option<u64>,
     /// Version number, incremented on each update.
     #[serde(deserialize_with = "deserialize_i32_or_default")]
     pub version: i32,
@@ -1047,6 +1278,14 @@ pub struct ToolApproval {
     /// Built-in MCP tools use plain names (e.g., "query_facts").
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub allowed_tools: Vec<String>,
+    /// Remote hosts the tool may import modules from (e.g., "esm.sh").
+    /// Empty means the tool can only use local imports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_import_hosts: Vec<String>,
+    /// Snapshot of the tool's code at the time of the decision, so a later
+    /// version can be diffed against what was actually approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_code: Option<String>,
     /// The DID of the Winter instance this approval is for.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub winter_did: Option<String>,
@@ -1104,6 +1343,8 @@ pub enum DirectiveKind {
     Boundary,
     /// What to become (e.g., "Develop a distinctive voice in writing").
     Aspiration,
+    /// What to do when awakened with nothing pending (e.g., "Review stale facts").
+    Goal,
 }
 
 impl std::fmt::Display for DirectiveKind {
@@ -1116,6 +1357,7 @@ impl std::fmt::Display for DirectiveKind {
             Self::SelfConcept => write!(f, "self_concept"),
             Self::Boundary => write!(f, "boundary"),
             Self::Aspiration => write!(f, "aspiration"),
+            Self::Goal => write!(f, "goal"),
         }
     }
 }
@@ -1137,6 +1379,27 @@ fn default_symbol() -> String {
     "symbol".to_string()
 }
 
+/// Shape of the curve used to decay a fact's confidence with age.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayFunction {
+    /// Confidence halves every half-life.
+    Exponential,
+    /// Confidence falls in a straight line, reaching half at one half-life
+    /// and zero at two.
+    Linear,
+}
+
+/// Per-predicate confidence decay configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceDecay {
+    /// Decay curve.
+    pub function: DecayFunction,
+    /// Age in seconds at which confidence has fallen to half its stored value.
+    pub half_life_secs: i64,
+}
+
 /// Fact declaration record.
 ///
 /// Declares the schema for a fact predicate before facts of that type exist.
@@ -1153,6 +1416,9 @@ pub struct FactDeclaration {
     /// Tags for categorization.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+    /// How confidence in facts of this predicate decays with age, if at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<ConfidenceDecay>,
     /// When this declaration was created.
     pub created_at: DateTime<Utc>,
     /// When this declaration was last updated.
@@ -1204,8 +1470,58 @@ pub struct Directive {
     pub last_updated: Option<DateTime<Utc>>,
 }
 
+/// Reconstruct the supersession chain that directive `rkey` belongs to.
+///
+/// Follows `supersedes` links back to the original directive and forward to
+/// the newest replacement, returning the chain oldest first. When several
+/// directives supersede the same record, the most recently created one is
+/// followed. Returns an empty chain if `rkey` isn't in `directives`.
+pub fn directive_history<'a>(
+    rkey: &str,
+    directives: &'a std::collections::HashMap<String, Directive>,
+) -> Vec<(&'a str, &'a Directive)> {
+    let Some((start_key, start)) = directives.get_key_value(rkey) else {
+        return Vec::new();
+    };
+    let mut seen = std::collections::HashSet::new();
+    seen.insert(start_key.as_str());
+
+    // Older versions, walking supersedes links back
+    let mut chain = Vec::new();
+    let mut current = start;
+    while let Some((key, previous)) = current
+        .supersedes
+        .as_deref()
+        .and_then(|prev| directives.get_key_value(prev))
+    {
+        if !seen.insert(key.as_str()) {
+            break;
+        }
+        chain.push((key.as_str(), previous));
+        current = previous;
+    }
+    chain.reverse();
+    chain.push((start_key.as_str(), start));
+
+    // Newer versions, finding whatever superseded the latest entry
+    let mut current_key = start_key.as_str();
+    while let Some((key, next)) = directives
+        .iter()
+        .filter(|(key, d)| {
+            d.supersedes.as_deref() == Some(current_key) && !seen.contains(key.as_str())
+        })
+        .max_by_key(|(key, d)| (d.created_at, key.as_str()))
+    {
+        seen.insert(key.as_str());
+        chain.push((key.as_str(), next));
+        current_key = key.as_str();
+    }
+
+    chain
+}
+
 /// Action to perform when a trigger's condition is satisfied.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TriggerAction {
     /// Create a new fact record.
@@ -1220,15 +1536,43 @@ pub enum TriggerAction {
         message: String,
     },
     /// Delete a fact record by rkey.
-    DeleteFact {
-        rkey: String,
+    DeleteFact { rkey: String },
+    /// Request an immediate run of a scheduled job, by name.
+    RunJob { job: String },
+}
+
+/// Event filter checked before a trigger's condition runs.
+///
+/// Filters look at the raw event rather than datalog facts, so they can match
+/// on post text and on nested fields of the record that caused the event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerFilter {
+    /// The event text matches a regular expression.
+    TextMatches { pattern: String },
+    /// A value selected from the event record by a JSON path like
+    /// `$.reply.parent.uri` or `$.facets[0].features[0].did`.
+    ///
+    /// Matches when the value exists, equals `equals` if set, and (as a
+    /// string) matches `pattern` if set.
+    JsonPath {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<serde_json::Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pattern: Option<String>,
     },
+    /// Every nested filter matches.
+    All { filters: Vec<TriggerFilter> },
+    /// At least one nested filter matches.
+    Any { filters: Vec<TriggerFilter> },
 }
 
 /// Trigger record (diy.razorgirl.winter.trigger).
 ///
 /// Defines a condition (datalog query) and an action to execute when the
-/// condition yields new results. Evaluated periodically by the daemon.
+/// condition yields new results. Evaluated periodically by the daemon, and
+/// against each incoming event for conditions that reference `_event`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Trigger {
@@ -1251,6 +1595,9 @@ pub struct Trigger {
     /// instead of the default all-symbol declaration. This enables numeric comparisons.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<FactDeclArg>,
+    /// Event filter; a trigger with a filter is only evaluated against events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<TriggerFilter>,
     /// When this trigger was created.
     pub created_at: DateTime<Utc>,
 }
@@ -1299,6 +1646,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tid_from_datetime_is_deterministic_and_sortable() {
+        let at = Utc::now();
+        assert_eq!(Tid::from_datetime(at, 0), Tid::from_datetime(at, 0));
+        assert!(Tid::from_datetime(at, 0).0 < Tid::from_datetime(at, 1).0);
+        assert!(
+            Tid::from_datetime(at, 1023).0
+                < Tid::from_datetime(at + chrono::Duration::microseconds(1), 0).0
+        );
+    }
+
     #[test]
     fn tid_from_u64_produces_sortable_output() {
         // TIDs should be lexicographically sortable by time
@@ -1407,6 +1765,7 @@ mod tests {
             tags: vec!["agent".to_string(), "phenomenology".to_string()],
             created_at: Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap(),
             expires_at: None,
+            deleted_at: None,
         };
 
         let json = serde_json::to_string_pretty(&fact).unwrap();
@@ -1452,6 +1811,7 @@ mod tests {
             tags: vec![],
             created_at: Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap(),
             expires_at: None,
+            deleted_at: None,
         };
 
         let json = serde_json::to_string_pretty(&fact).unwrap();
@@ -1551,4 +1911,305 @@ mod tests {
             "bafyreig6fcgjwnxmqojqjwmvhpayivpsyfjtaqt42bvxfv5nzjvrlvveoy"
         );
     }
+
+    fn job_deps<'a>(
+        graph: &'a [(&'a str, &'a [&'a str])],
+    ) -> impl Fn(&str) -> Option<Vec<String>> + 'a {
+        move |rkey| {
+            graph
+                .iter()
+                .find(|(k, _)| *k == rkey)
+                .map(|(_, deps)| deps.iter().map(|d| d.to_string()).collect())
+        }
+    }
+
+    #[test]
+    fn job_dependency_chain_has_no_cycle() {
+        let graph: &[(&str, &[&str])] = &[("a", &[]), ("b", &["a"])];
+        let deps = vec!["b".to_string()];
+        assert_eq!(find_job_dependency_cycle("c", &deps, job_deps(graph)), None);
+    }
+
+    #[test]
+    fn job_dependency_cycle_is_found() {
+        // a depends on b, which depends on c; giving c a dependency on a closes the loop
+        let graph: &[(&str, &[&str])] = &[("a", &["b"]), ("b", &["c"])];
+        let deps = vec!["a".to_string()];
+        assert_eq!(
+            find_job_dependency_cycle("c", &deps, job_deps(graph)),
+            Some(vec![
+                "c".to_string(),
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn job_dependency_on_existing_cycle_is_found() {
+        // a and b already wait on each other, so c would never run either
+        let graph: &[(&str, &[&str])] = &[("a", &["b"]), ("b", &["a"])];
+        let deps = vec!["a".to_string()];
+        assert_eq!(
+            find_job_dependency_cycle("c", &deps, job_deps(graph)),
+            Some(vec!["a".to_string(), "b".to_string(), "a".to_string()])
+        );
+    }
+
+    #[test]
+    fn job_shared_dependency_is_not_a_cycle() {
+        // Two paths to the same job are a diamond, not a loop
+        let graph: &[(&str, &[&str])] = &[("a", &["c"]), ("b", &["c"]), ("c", &[])];
+        let deps = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(find_job_dependency_cycle("d", &deps, job_deps(graph)), None);
+    }
+
+    #[test]
+    fn job_self_dependency_is_a_cycle() {
+        let deps = vec!["a".to_string()];
+        assert!(find_job_dependency_cycle("a", &deps, |_| None).is_some());
+    }
+
+    #[test]
+    fn job_depends_on_defaults_to_empty() {
+        let json = serde_json::json!({
+            "name": "digest",
+            "instructions": "summarize",
+            "schedule": { "type": "interval", "seconds": 60 },
+            "createdAt": "2026-01-01T00:00:00Z"
+        });
+        let job: Job = serde_json::from_value(json).unwrap();
+        assert!(job.depends_on.is_empty());
+        assert!(
+            serde_json::to_value(&job)
+                .unwrap()
+                .get("dependsOn")
+                .is_none()
+        );
+    }
+
+    fn directive(content: &str, supersedes: Option<&str>, minutes: i64) -> Directive {
+        Directive {
+            kind: DirectiveKind::Belief,
+            content: content.to_string(),
+            summary: None,
+            active: supersedes.is_some(),
+            confidence: None,
+            source: None,
+            supersedes: supersedes.map(String::from),
+            tags: Vec::new(),
+            priority: 0,
+            created_at: DateTime::from_timestamp(minutes * 60, 0).unwrap(),
+            last_updated: None,
+        }
+    }
+
+    fn history_keys(
+        rkey: &str,
+        directives: &std::collections::HashMap<String, Directive>,
+    ) -> Vec<String> {
+        directive_history(rkey, directives)
+            .into_iter()
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn directive_history_reconstructs_chain_from_any_member() {
+        let directives: std::collections::HashMap<String, Directive> = [
+            ("v1", directive("first take", None, 1)),
+            ("v2", directive("second take", Some("v1"), 2)),
+            ("v3", directive("third take", Some("v2"), 3)),
+            ("other", directive("unrelated", None, 4)),
+        ]
+        .into_iter()
+        .map(|(k, d)| (k.to_string(), d))
+        .collect();
+
+        for start in ["v1", "v2", "v3"] {
+            assert_eq!(history_keys(start, &directives), vec!["v1", "v2", "v3"]);
+        }
+        assert_eq!(history_keys("other", &directives), vec!["other"]);
+        assert!(history_keys("missing", &directives).is_empty());
+
+        let contents: Vec<&str> = directive_history("v2", &directives)
+            .iter()
+            .map(|(_, d)| d.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["first take", "second take", "third take"]);
+    }
+
+    #[test]
+    fn directive_history_follows_newest_branch_and_survives_cycles() {
+        let mut directives: std::collections::HashMap<String, Directive> = [
+            ("root", directive("root", None, 1)),
+            ("old-branch", directive("old branch", Some("root"), 2)),
+            ("new-branch", directive("new branch", Some("root"), 3)),
+        ]
+        .into_iter()
+        .map(|(k, d)| (k.to_string(), d))
+        .collect();
+        assert_eq!(
+            history_keys("root", &directives),
+            vec!["root", "new-branch"]
+        );
+
+        // A corrupt loop of supersedes links must still terminate
+        directives.get_mut("root").unwrap().supersedes = Some("new-branch".to_string());
+        let keys = history_keys("root", &directives);
+        let unique: HashSet<&String> = keys.iter().collect();
+        assert_eq!(unique.len(), keys.len());
+    }
+
+    fn note_with_refs(title: &str, related_facts: &[&str]) -> Note {
+        Note {
+            title: title.to_string(),
+            content: String::new(),
+            category: None,
+            related_facts: related_facts.iter().map(|r| r.to_string()).collect(),
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            last_updated: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn fact_with(predicate: &str, args: &[&str]) -> Fact {
+        Fact {
+            predicate: predicate.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            confidence: None,
+            source: None,
+            supersedes: None,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            expires_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn fact_ref_rkey_accepts_uris_and_bare_keys() {
+        assert_eq!(
+            fact_ref_rkey("at://did:plc:abc/diy.razorgirl.winter.fact/3k2a"),
+            Some("3k2a")
+        );
+        assert_eq!(fact_ref_rkey("3k2a"), Some("3k2a"));
+        assert_eq!(
+            fact_ref_rkey("at://did:plc:abc/diy.razorgirl.winter.note/3k2a"),
+            None
+        );
+        assert_eq!(
+            fact_ref_rkey("at://did:plc:abc/diy.razorgirl.winter.fact"),
+            None
+        );
+        assert_eq!(fact_ref_rkey(""), None);
+    }
+
+    #[test]
+    fn resolve_related_facts_tolerates_dangling_references() {
+        let facts = std::collections::HashMap::from([
+            (
+                "f1".to_string(),
+                fact_with("follows", &["did:plc:a", "did:plc:b"]),
+            ),
+            ("f2".to_string(), fact_with("likes", &["did:plc:a", "tea"])),
+        ]);
+        let note = note_with_refs(
+            "n",
+            &[
+                "at://did:plc:me/diy.razorgirl.winter.fact/f2",
+                "gone",
+                "f1",
+                "at://did:plc:me/diy.razorgirl.winter.rule/f1",
+            ],
+        );
+
+        let resolved = resolve_related_facts(&note, &facts);
+        let predicates: Vec<Option<&str>> = resolved
+            .iter()
+            .map(|(_, fact)| fact.map(|f| f.predicate.as_str()))
+            .collect();
+        assert_eq!(predicates, vec![Some("likes"), None, Some("follows"), None]);
+        assert_eq!(resolved[1].0, "gone");
+    }
+
+    #[test]
+    fn note_backlinks_finds_referencing_notes() {
+        let notes = std::collections::HashMap::from([
+            (
+                "n2".to_string(),
+                note_with_refs("second", &["at://did:plc:me/diy.razorgirl.winter.fact/f1"]),
+            ),
+            ("n1".to_string(), note_with_refs("first", &["f1", "f2"])),
+            ("n3".to_string(), note_with_refs("unrelated", &["f2"])),
+        ]);
+
+        let backlinks: Vec<&str> = note_backlinks("f1", &notes)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(backlinks, vec!["n1", "n2"]);
+        assert!(note_backlinks("f9", &notes).is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_wiki_target_roundtrip() {
+        let target = unresolved_wiki_target("missing-page");
+        assert_eq!(target, "wiki:missing-page");
+        assert_eq!(unresolved_wiki_slug(&target), Some("missing-page"));
+        assert_eq!(
+            unresolved_wiki_slug("at://did:plc:abc/diy.razorgirl.winter.wikiEntry/3k2a"),
+            None
+        );
+    }
+
+    #[test]
+    fn thought_content_type_roundtrips_and_infers_legacy() {
+        let json = serde_json::json!({
+            "kind": "tool_call",
+            "content": "{\"tool\":\"create_fact\"}",
+            "contentType": "tool_call",
+            "createdAt": "2026-01-01T00:00:00Z",
+        });
+        let thought: Thought = serde_json::from_value(json).unwrap();
+        assert_eq!(thought.content_type, Some(ThoughtContentType::ToolCall));
+        assert_eq!(
+            serde_json::to_value(&thought).unwrap()["contentType"],
+            "tool_call"
+        );
+
+        let legacy: Thought = serde_json::from_value(serde_json::json!({
+            "kind": "tool_call",
+            "content": "Called create_fact - FAILED",
+            "createdAt": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(legacy.content_type, None);
+        assert_eq!(
+            legacy.effective_content_type(),
+            ThoughtContentType::LegacyToolCall
+        );
+    }
+
+    #[test]
+    fn thought_content_type_infer_only_applies_to_tool_calls() {
+        assert_eq!(
+            ThoughtContentType::infer(&ThoughtKind::Insight, "{\"tool\":\"x\"}"),
+            ThoughtContentType::Text
+        );
+        assert_eq!(
+            ThoughtContentType::infer(&ThoughtKind::Plan, "Called mom"),
+            ThoughtContentType::Text
+        );
+        assert_eq!(
+            ThoughtContentType::infer(&ThoughtKind::ToolCall, "  {\"tool\":\"x\"}"),
+            ThoughtContentType::ToolCall
+        );
+        assert_eq!(
+            ThoughtContentType::infer(&ThoughtKind::ToolCall, "something else"),
+            ThoughtContentType::Text
+        );
+    }
 }
@@ -0,0 +1,237 @@
+//! Lexicon validation for decoded records.
+//!
+//! Deserializing a record only proves it has the right field types. Records
+//! arriving from the firehose or a CAR export can still violate the lexicon
+//! (oversized strings, too many args, a predicate that isn't an identifier),
+//! and those values flow straight into datalog extraction. The dispatch layer
+//! runs [`Validate::validate`] on every decoded record and skips the ones that
+//! fail, so one bad writer can't poison the fact files.
+//!
+//! Limits mirror the `maxLength` constraints in `lexicons/`, which ATProto
+//! measures in UTF-8 bytes for strings and in items for arrays.
+
+use crate::{
+    BlogEntry, CustomTool, Directive, Fact, FactDeclArg, FactDeclaration, Follow, Job, Like, Note,
+    Post, Repost, Rule, Thought, ToolApproval, Trigger, WikiEntry, WikiLink,
+};
+
+/// Maximum length of a predicate, rule or argument name.
+pub const MAX_NAME_LEN: usize = 64;
+/// Maximum length of a single fact argument.
+pub const MAX_FACT_ARG_LEN: usize = 1024;
+/// Maximum number of arguments to a predicate.
+pub const MAX_ARGS: usize = 10;
+/// Maximum length of a tag.
+pub const MAX_TAG_LEN: usize = 64;
+/// Maximum number of tags on a record.
+pub const MAX_TAGS: usize = 20;
+/// Maximum length of a rule head, body atom or constraint.
+pub const MAX_RULE_CLAUSE_LEN: usize = 256;
+/// Maximum number of atoms in a rule body.
+pub const MAX_RULE_BODY: usize = 20;
+/// Maximum number of constraints on a rule.
+pub const MAX_RULE_CONSTRAINTS: usize = 10;
+/// Maximum length of a rule or declaration description.
+pub const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// Check a decoded record against its lexicon.
+///
+/// The default implementation accepts everything; collections whose records
+/// feed datalog override it with real checks.
+pub trait Validate {
+    /// Returns a description of the first violation found, if any.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Whether `name` can be used as a Soufflé relation name.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn check_len(field: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.len() > max {
+        return Err(format!("{} is {} bytes, max {}", field, value.len(), max));
+    }
+    Ok(())
+}
+
+fn check_count<T>(field: &str, items: &[T], max: usize) -> Result<(), String> {
+    if items.len() > max {
+        return Err(format!("{} has {} items, max {}", field, items.len(), max));
+    }
+    Ok(())
+}
+
+fn check_name(field: &str, name: &str) -> Result<(), String> {
+    check_len(field, name, MAX_NAME_LEN)?;
+    if !is_identifier(name) {
+        return Err(format!("{} '{}' is not a valid identifier", field, name));
+    }
+    Ok(())
+}
+
+fn check_tags(tags: &[String]) -> Result<(), String> {
+    check_count("tags", tags, MAX_TAGS)?;
+    for tag in tags {
+        check_len("tag", tag, MAX_TAG_LEN)?;
+    }
+    Ok(())
+}
+
+fn check_decl_args(args: &[FactDeclArg]) -> Result<(), String> {
+    check_count("args", args, MAX_ARGS)?;
+    for arg in args {
+        check_name("arg name", &arg.name)?;
+    }
+    Ok(())
+}
+
+impl Validate for Fact {
+    fn validate(&self) -> Result<(), String> {
+        check_name("predicate", &self.predicate)?;
+        check_count("args", &self.args, MAX_ARGS)?;
+        for arg in &self.args {
+            check_len("arg", arg, MAX_FACT_ARG_LEN)?;
+        }
+        if let Some(confidence) = self.confidence
+            && !(0.0..=1.0).contains(&confidence)
+        {
+            return Err(format!("confidence {} is outside 0.0-1.0", confidence));
+        }
+        check_tags(&self.tags)
+    }
+}
+
+impl Validate for Rule {
+    fn validate(&self) -> Result<(), String> {
+        check_len("name", &self.name, MAX_NAME_LEN)?;
+        check_len("description", &self.description, MAX_DESCRIPTION_LEN)?;
+        if self.head.trim().is_empty() {
+            return Err("head is empty".to_string());
+        }
+        check_len("head", &self.head, MAX_RULE_CLAUSE_LEN)?;
+        if self.body.is_empty() {
+            return Err("body is empty".to_string());
+        }
+        check_count("body", &self.body, MAX_RULE_BODY)?;
+        for atom in &self.body {
+            check_len("body atom", atom, MAX_RULE_CLAUSE_LEN)?;
+        }
+        check_count("constraints", &self.constraints, MAX_RULE_CONSTRAINTS)?;
+        for constraint in &self.constraints {
+            check_len("constraint", constraint, MAX_RULE_CLAUSE_LEN)?;
+        }
+        check_decl_args(&self.args)
+    }
+}
+
+impl Validate for FactDeclaration {
+    fn validate(&self) -> Result<(), String> {
+        check_name("predicate", &self.predicate)?;
+        check_decl_args(&self.args)?;
+        check_len("description", &self.description, MAX_DESCRIPTION_LEN)?;
+        check_tags(&self.tags)?;
+        if let Some(decay) = &self.decay
+            && decay.half_life_secs < 1
+        {
+            return Err(format!(
+                "decay halfLifeSecs {} must be at least 1",
+                decay.half_life_secs
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Thought {}
+impl Validate for Note {}
+impl Validate for Job {}
+impl Validate for Directive {}
+impl Validate for CustomTool {}
+impl Validate for ToolApproval {}
+impl Validate for Trigger {}
+impl Validate for Post {}
+impl Validate for BlogEntry {}
+impl Validate for WikiEntry {}
+impl Validate for Follow {}
+impl Validate for Like {}
+impl Validate for Repost {}
+impl Validate for WikiLink {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn fact(predicate: &str, args: &[&str]) -> Fact {
+        Fact {
+            predicate: predicate.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            confidence: None,
+            source: None,
+            supersedes: None,
+            tags: vec![],
+            created_at: Utc::now(),
+            expires_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_fact() {
+        assert!(
+            fact("follows", &["did:plc:a", "did:plc:b"])
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_fact_predicate_must_be_identifier() {
+        assert!(fact("", &[]).validate().is_err());
+        assert!(fact("has\ttab", &["a"]).validate().is_err());
+        assert!(fact("1st", &["a"]).validate().is_err());
+        assert!(fact(&"p".repeat(65), &["a"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_fact_arg_limits() {
+        let args: Vec<&str> = vec!["a"; MAX_ARGS + 1];
+        assert!(fact("p", &args).validate().is_err());
+        let long = "x".repeat(MAX_FACT_ARG_LEN + 1);
+        assert!(fact("p", &[&long]).validate().is_err());
+    }
+
+    #[test]
+    fn test_fact_confidence_range() {
+        let mut f = fact("p", &["a"]);
+        f.confidence = Some(1.5);
+        assert!(f.validate().is_err());
+        f.confidence = Some(f64::NAN);
+        assert!(f.validate().is_err());
+        f.confidence = Some(0.5);
+        assert!(f.validate().is_ok());
+    }
+
+    #[test]
+    fn test_rule_requires_body() {
+        let rule = Rule {
+            name: "r".to_string(),
+            description: String::new(),
+            head: "r(X)".to_string(),
+            body: vec![],
+            constraints: vec![],
+            enabled: true,
+            priority: 0,
+            args: vec![],
+            created_at: Utc::now(),
+        };
+        assert_eq!(rule.validate(), Err("body is empty".to_string()));
+    }
+}
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell};
 
-use crate::cli::{cli::commands::*, error::Result};
+use crate::cli::{
+    cli::{commands::*, tools::ToolsCommand},
+    error::Result,
+};
 
 /// Interactive CLI for managing multiple Claude sessions and agents
 #[derive(Parser)]
@@ -62,6 +65,9 @@ pub enum Commands {
         #[command(subcommand)]
         action: ConfigAction,
     },
+
+    /// List, describe, and call tools on a Winter MCP server
+    Tools(ToolsCommand),
 }
 
 impl Cli {
@@ -99,6 +105,7 @@ impl Cli {
                 Ok(())
             }
             Commands::Config { action } => action.execute(&data_dir, &config).await,
+            Commands::Tools(cmd) => cmd.execute().await,
         }
     }
 }
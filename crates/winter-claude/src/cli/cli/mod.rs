@@ -1,8 +1,10 @@
 mod app;
 mod commands;
+mod tools;
 
 #[cfg(test)]
 mod commands_test;
 
 pub use app::Cli;
 pub use commands::*;
+pub use tools::{McpTarget, McpToolClient, ToolInfo, ToolsCommand};
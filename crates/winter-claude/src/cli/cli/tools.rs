@@ -0,0 +1,596 @@
+//! `tools` subcommand: inspect and call tools on a running MCP server.
+//!
+//! Talks JSON-RPC directly to the server, over line-delimited stdio for a
+//! spawned `winter mcp-server` or over `POST /mcp` for the HTTP transport,
+//! so tools can be exercised without going through Claude.
+
+use std::{fmt::Write as _, process::Stdio};
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdin, ChildStdout, Command},
+};
+
+use crate::cli::{
+    error::{InteractiveError, Result},
+    output::{formatter::OutputFormatter, OutputStyle},
+};
+
+/// MCP protocol version sent during initialization
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// List, describe, and call tools on a Winter MCP server
+///
+/// Connect over HTTP with `--url`, or spawn a stdio server by passing its
+/// command after `--`:
+///
+/// ```bash
+/// winter-claude tools --url http://127.0.0.1:3847/mcp --schema
+/// winter-claude tools --call session_stats -- winter mcp-server
+/// ```
+#[derive(Args, Debug)]
+pub struct ToolsCommand {
+    /// MCP endpoint URL for the HTTP transport
+    #[arg(long, conflicts_with = "command")]
+    pub url: Option<String>,
+
+    /// Bearer token for the HTTP transport
+    #[arg(long, env = "WINTER_MCP_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// Only list tools whose name contains this pattern
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Include each tool's input schema
+    #[arg(long)]
+    pub schema: bool,
+
+    /// Call this tool instead of listing the catalog
+    #[arg(long, value_name = "NAME")]
+    pub call: Option<String>,
+
+    /// JSON object of arguments for `--call`
+    #[arg(long, value_name = "JSON", requires = "call")]
+    pub args: Option<String>,
+
+    /// Print raw JSON instead of formatted output
+    #[arg(long)]
+    pub json: bool,
+
+    /// Command (and arguments) that starts a stdio MCP server
+    #[arg(last = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
+
+impl ToolsCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let formatter = OutputFormatter::new();
+        let target = self.target()?;
+        let arguments = self.parsed_args()?;
+
+        let mut client = McpToolClient::connect(target).await?;
+        let result = match &self.call {
+            Some(name) => client.call_tool(name, arguments).await.map(|result| {
+                if self.json {
+                    serde_json::to_string_pretty(&result).unwrap_or_default()
+                } else {
+                    render_call_result(&result)
+                }
+            }),
+            None => client.list_tools().await.map(|mut tools| {
+                if let Some(filter) = &self.filter {
+                    tools.retain(|tool| tool.name.contains(filter.as_str()));
+                }
+                if self.json {
+                    serde_json::to_string_pretty(&tools).unwrap_or_default()
+                } else {
+                    render_tool_list(&tools, self.schema)
+                }
+            }),
+        };
+        client.shutdown().await;
+
+        match result {
+            Ok(output) => formatter.println(output.trim_end()),
+            Err(e) => {
+                formatter.println(&formatter.format_message(&e.to_string(), OutputStyle::Error))?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Which server to connect to, from `--url` or the trailing command
+    pub fn target(&self) -> Result<McpTarget> {
+        match (&self.url, self.command.split_first()) {
+            (Some(url), _) => Ok(McpTarget::Http {
+                url: url.clone(),
+                token: self.token.clone(),
+            }),
+            (None, Some((program, args))) => Ok(McpTarget::Stdio {
+                program: program.clone(),
+                args: args.to_vec(),
+            }),
+            (None, None) => Err(InteractiveError::invalid_input(
+                "Specify --url for an HTTP server or a server command after `--`",
+            )),
+        }
+    }
+
+    /// Arguments for `--call`, which must be a JSON object
+    pub fn parsed_args(&self) -> Result<Value> {
+        let Some(raw) = &self.args else {
+            return Ok(json!({}));
+        };
+        let value: Value = serde_json::from_str(raw)
+            .map_err(|e| InteractiveError::invalid_input(format!("--args is not JSON: {e}")))?;
+        if !value.is_object() {
+            return Err(InteractiveError::invalid_input(
+                "--args must be a JSON object",
+            ));
+        }
+        Ok(value)
+    }
+}
+
+/// Where the MCP server lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpTarget {
+    /// Streamable HTTP endpoint
+    Http { url: String, token: Option<String> },
+    /// Server spawned as a child process speaking line-delimited JSON-RPC
+    Stdio { program: String, args: Vec<String> },
+}
+
+/// A tool from the server's `tools/list` catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub input_schema: Value,
+}
+
+enum Transport {
+    Http {
+        client: reqwest::Client,
+        url: String,
+        token: Option<String>,
+    },
+    Stdio {
+        child: Child,
+        stdin: ChildStdin,
+        stdout: Lines<BufReader<ChildStdout>>,
+    },
+}
+
+/// Minimal JSON-RPC client for the MCP tool methods
+pub struct McpToolClient {
+    transport: Transport,
+    next_id: u64,
+}
+
+impl McpToolClient {
+    /// Connect to `target` and complete the MCP initialize handshake
+    pub async fn connect(target: McpTarget) -> Result<Self> {
+        let transport = match target {
+            McpTarget::Http { url, token } => Transport::Http {
+                client: reqwest::Client::new(),
+                url,
+                token,
+            },
+            McpTarget::Stdio { program, args } => {
+                let mut child = Command::new(&program)
+                    .args(&args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit())
+                    .kill_on_drop(true)
+                    .spawn()
+                    .map_err(|e| {
+                        InteractiveError::execution(format!("Failed to start {program}: {e}"))
+                    })?;
+                let stdin = child.stdin.take().expect("stdin is piped");
+                let stdout = child.stdout.take().expect("stdout is piped");
+                Transport::Stdio {
+                    child,
+                    stdin,
+                    stdout: BufReader::new(stdout).lines(),
+                }
+            }
+        };
+
+        let mut client = Self {
+            transport,
+            next_id: 1,
+        };
+        client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "winter-claude", "version": crate::cli::VERSION },
+                }),
+            )
+            .await?;
+        client.notify("notifications/initialized").await?;
+        Ok(client)
+    }
+
+    /// Fetch the server's tool catalog, sorted by name
+    pub async fn list_tools(&mut self) -> Result<Vec<ToolInfo>> {
+        let result = self.request("tools/list", json!({})).await?;
+        let mut tools: Vec<ToolInfo> =
+            serde_json::from_value(result.get("tools").cloned().unwrap_or(Value::Null))?;
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tools)
+    }
+
+    /// Call a tool and return the raw `tools/call` result
+    pub async fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+    }
+
+    /// Stop a spawned server; a no-op for HTTP
+    pub async fn shutdown(self) {
+        if let Transport::Stdio {
+            mut child, stdin, ..
+        } = self.transport
+        {
+            drop(stdin);
+            let _ = child.kill().await;
+        }
+    }
+
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let response = match &mut self.transport {
+            Transport::Http { client, url, token } => {
+                let mut request = client.post(url.as_str()).json(&message);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let response = request.send().await.map_err(http_error)?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(InteractiveError::execution(format!(
+                        "{method} failed with HTTP {status}"
+                    )));
+                }
+                response.json::<Value>().await.map_err(http_error)?
+            }
+            Transport::Stdio { stdin, stdout, .. } => {
+                write_line(stdin, &message).await?;
+                read_response(stdout, id).await?
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(InteractiveError::execution(format!(
+                "{method} failed: {message}"
+            )));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method });
+        match &mut self.transport {
+            Transport::Http { client, url, token } => {
+                let mut request = client.post(url.as_str()).json(&message);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                request.send().await.map_err(http_error)?;
+                Ok(())
+            }
+            Transport::Stdio { stdin, .. } => write_line(stdin, &message).await,
+        }
+    }
+}
+
+async fn write_line(stdin: &mut ChildStdin, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    stdin.write_all(&line).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// Read lines until the response for `id`, skipping notifications and noise
+async fn read_response(stdout: &mut Lines<BufReader<ChildStdout>>, id: u64) -> Result<Value> {
+    while let Some(line) = stdout.next_line().await? {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if message.get("id").and_then(Value::as_u64) == Some(id) {
+            return Ok(message);
+        }
+    }
+    Err(InteractiveError::execution(
+        "MCP server exited before responding",
+    ))
+}
+
+fn http_error(e: reqwest::Error) -> InteractiveError {
+    InteractiveError::execution(format!("MCP request failed: {e}"))
+}
+
+/// Render the tool catalog, one tool per block
+pub fn render_tool_list(tools: &[ToolInfo], show_schema: bool) -> String {
+    if tools.is_empty() {
+        return "No tools found".to_string();
+    }
+
+    let mut out = String::new();
+    for tool in tools {
+        let _ = writeln!(out, "{}", tool.name);
+        for line in tool.description.lines() {
+            let _ = writeln!(out, "    {line}");
+        }
+        if show_schema && !tool.input_schema.is_null() {
+            let schema = serde_json::to_string_pretty(&tool.input_schema).unwrap_or_default();
+            for line in schema.lines() {
+                let _ = writeln!(out, "    {line}");
+            }
+        }
+        out.push('\n');
+    }
+    let _ = write!(out, "{} tool(s)", tools.len());
+    out
+}
+
+/// Render a `tools/call` result: text blocks verbatim, anything else as JSON
+pub fn render_call_result(result: &Value) -> String {
+    let mut out = String::new();
+    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+        out.push_str("Tool returned an error:\n");
+    }
+
+    let content = result
+        .get("content")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for block in content {
+        match block.get("text").and_then(Value::as_str) {
+            Some(text) => out.push_str(text),
+            None => out.push_str(&serde_json::to_string_pretty(block).unwrap_or_default()),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::cli::cli::{Cli, app::Commands};
+
+    fn parse(args: &[&str]) -> ToolsCommand {
+        let cli = Cli::try_parse_from(std::iter::once("winter-claude").chain(args.iter().copied()))
+            .unwrap();
+        match cli.command {
+            Commands::Tools(cmd) => cmd,
+            _ => panic!("expected tools command"),
+        }
+    }
+
+    fn rpc_result(result: Value) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+    }
+
+    async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_partial_json(json!({ "method": "initialize" })))
+            .respond_with(rpc_result(json!({ "protocolVersion": PROTOCOL_VERSION })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_partial_json(
+                json!({ "method": "notifications/initialized" }),
+            ))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[test]
+    fn test_parse_http_target() {
+        let cmd = parse(&[
+            "tools",
+            "--url",
+            "http://localhost:3847/mcp",
+            "--token",
+            "s3cret",
+        ]);
+        assert_eq!(
+            cmd.target().unwrap(),
+            McpTarget::Http {
+                url: "http://localhost:3847/mcp".to_string(),
+                token: Some("s3cret".to_string()),
+            }
+        );
+        assert!(cmd.call.is_none());
+    }
+
+    #[test]
+    fn test_parse_stdio_target_and_call() {
+        let cmd = parse(&[
+            "tools",
+            "--call",
+            "query_facts",
+            "--args",
+            r#"{"predicate": "follows"}"#,
+            "--",
+            "winter",
+            "mcp-server",
+        ]);
+        assert_eq!(
+            cmd.target().unwrap(),
+            McpTarget::Stdio {
+                program: "winter".to_string(),
+                args: vec!["mcp-server".to_string()],
+            }
+        );
+        assert_eq!(cmd.call.as_deref(), Some("query_facts"));
+        assert_eq!(
+            cmd.parsed_args().unwrap(),
+            json!({ "predicate": "follows" })
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_input() {
+        // --args without --call
+        let parsed = Cli::try_parse_from(["winter-claude", "tools", "--args", "{}", "--url", "x"]);
+        assert!(parsed.is_err());
+
+        // No server to talk to
+        assert!(parse(&["tools"]).target().is_err());
+
+        // Arguments must be a JSON object
+        assert!(
+            parse(&["tools", "--call", "t", "--args", "[1]"])
+                .parsed_args()
+                .is_err()
+        );
+        assert!(
+            parse(&["tools", "--call", "t", "--args", "{"])
+                .parsed_args()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_render_tool_list() {
+        let tools = vec![ToolInfo {
+            name: "create_fact".to_string(),
+            description: "Record a fact".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }];
+
+        let brief = render_tool_list(&tools, false);
+        assert!(brief.starts_with("create_fact\n    Record a fact\n"));
+        assert!(!brief.contains("\"type\""));
+        assert!(brief.ends_with("1 tool(s)"));
+
+        let detailed = render_tool_list(&tools, true);
+        assert!(detailed.contains("    {\n      \"type\": \"object\"\n    }"));
+
+        assert_eq!(render_tool_list(&[], true), "No tools found");
+    }
+
+    #[test]
+    fn test_render_call_result() {
+        let ok = json!({ "content": [{ "type": "text", "text": "3 facts" }] });
+        assert_eq!(render_call_result(&ok), "3 facts\n");
+
+        let failed = json!({
+            "content": [{ "type": "text", "text": "no such predicate" }],
+            "isError": true,
+        });
+        assert_eq!(
+            render_call_result(&failed),
+            "Tool returned an error:\nno such predicate\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_tools_against_mock_server() {
+        let server = mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(header("authorization", "Bearer s3cret"))
+            .and(body_partial_json(json!({ "method": "tools/list" })))
+            .respond_with(rpc_result(json!({
+                "tools": [
+                    { "name": "search_notes", "description": "Search notes", "inputSchema": {} },
+                    { "name": "create_fact", "description": "Record a fact", "inputSchema": {} },
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let target = McpTarget::Http {
+            url: format!("{}/mcp", server.uri()),
+            token: Some("s3cret".to_string()),
+        };
+        let mut client = McpToolClient::connect(target).await.unwrap();
+        let tools = client.list_tools().await.unwrap();
+
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["create_fact", "search_notes"]);
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_against_mock_server() {
+        let server = mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_partial_json(json!({
+                "method": "tools/call",
+                "params": { "name": "query_facts", "arguments": { "predicate": "follows" } },
+            })))
+            .respond_with(rpc_result(
+                json!({ "content": [{ "type": "text", "text": "2 rows" }] }),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_partial_json(
+                json!({ "method": "tools/call", "params": { "name": "missing" } }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32602, "message": "Unknown tool: missing" },
+            })))
+            .mount(&server)
+            .await;
+
+        let target = McpTarget::Http {
+            url: format!("{}/mcp", server.uri()),
+            token: None,
+        };
+        let mut client = McpToolClient::connect(target).await.unwrap();
+
+        let result = client
+            .call_tool("query_facts", json!({ "predicate": "follows" }))
+            .await
+            .unwrap();
+        assert_eq!(render_call_result(&result), "2 rows\n");
+
+        let err = client.call_tool("missing", json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("Unknown tool: missing"));
+    }
+}
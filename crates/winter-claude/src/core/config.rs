@@ -3,7 +3,7 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::error::Error;
+use crate::core::{error::Error, mcp_config::McpConfig, session::SessionId, types::PermissionMode};
 
 // Validation constants
 const MAX_QUERY_LENGTH: usize = 100_000;
@@ -63,6 +63,12 @@ pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mcp_config_path: Option<PathBuf>,
 
+    /// MCP servers described in code, passed inline as `--mcp-config`
+    ///
+    /// Can be combined with `mcp_config_path`; the CLI loads both.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_servers: Option<McpConfig>,
+
     /// List of tools that Claude is allowed to use
     ///
     /// Tools are specified using the format `server_name__tool_name` for MCP tools
@@ -81,6 +87,20 @@ pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_tools: Option<Vec<String>>,
 
+    /// List of tools that Claude is never allowed to use
+    ///
+    /// Uses the same naming format as `allowed_tools` and is passed to the
+    /// CLI as `--disallowedTools`, which takes precedence over the allow list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disallowed_tools: Option<Vec<String>>,
+
+    /// Permission mode for the Claude CLI
+    ///
+    /// Passed as `--permission-mode` when set. `None` leaves the CLI's own
+    /// default in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+
     /// Output format for Claude CLI responses
     ///
     /// - `Text`: Plain text output (default)
@@ -124,6 +144,129 @@ pub struct Config {
     /// or authentication tokens that need to flow through to MCP servers.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+
+    /// Which of this process's environment variables the CLI inherits
+    ///
+    /// Defaults to [`EnvScope::restricted`], so secrets in the parent's
+    /// environment don't leak into the CLI or the tools it runs. Variables
+    /// in `env` are always set on top of whatever is inherited.
+    #[serde(default)]
+    pub env_scope: EnvScope,
+
+    /// Working directory for the Claude CLI subprocess
+    ///
+    /// Built-in file tools resolve relative paths against this directory.
+    /// `None` inherits the current process's working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+
+    /// CLI session to continue, passed as `--resume`
+    ///
+    /// `None` starts a fresh conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume: Option<SessionId>,
+}
+
+/// Variables inherited by default: enough for the CLI to find its binary
+/// dependencies, locale, config directory and credentials.
+pub const DEFAULT_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TZ",
+    "TMPDIR",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_BASE_URL",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "CLAUDE_CONFIG_DIR",
+];
+
+/// How the Claude CLI subprocess's environment is built
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::{Config, EnvScope};
+///
+/// // The default allowlist plus the variables an MCP config substitutes
+/// let config = Config::builder()
+///     .env_scope(EnvScope::restricted().with_vars(["WINTER_MCP_TOKEN"]))
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode", content = "vars")]
+pub enum EnvScope {
+    /// Inherit this process's full environment
+    Inherit,
+    /// Start from an empty environment and pass through only these variables
+    ///
+    /// A name ending in `*` matches every variable with that prefix.
+    Allowlist(Vec<String>),
+}
+
+impl EnvScope {
+    /// The default allowlist, [`DEFAULT_ENV_ALLOWLIST`]
+    pub fn restricted() -> Self {
+        Self::Allowlist(
+            DEFAULT_ENV_ALLOWLIST
+                .iter()
+                .map(|v| (*v).to_string())
+                .collect(),
+        )
+    }
+
+    /// Also pass through the given variables
+    ///
+    /// Has no effect on `Inherit`, which already passes everything.
+    #[must_use]
+    pub fn with_vars<I, S>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if let Self::Allowlist(allowed) = &mut self {
+            for var in vars {
+                let var = var.into();
+                if !allowed.contains(&var) {
+                    allowed.push(var);
+                }
+            }
+        }
+        self
+    }
+
+    /// Whether a variable from this process's environment is passed through
+    pub fn allows(&self, name: &str) -> bool {
+        match self {
+            Self::Inherit => true,
+            Self::Allowlist(allowed) => {
+                allowed
+                    .iter()
+                    .any(|pattern| match pattern.strip_suffix('*') {
+                        Some(prefix) => name.starts_with(prefix),
+                        None => name == pattern,
+                    })
+            }
+        }
+    }
+}
+
+impl Default for EnvScope {
+    fn default() -> Self {
+        Self::restricted()
+    }
 }
 
 /// Output format for Claude CLI responses
@@ -180,13 +323,19 @@ impl Default for Config {
             system_prompt: None,
             model: None,
             mcp_config_path: None,
+            mcp_servers: None,
             allowed_tools: None,
+            disallowed_tools: None,
+            permission_mode: None,
             stream_format: StreamFormat::default(),
             non_interactive: true,
             verbose: false,
             max_tokens: None,
             timeout_secs: Some(30), // Default 30 second timeout
             env: None,
+            env_scope: EnvScope::default(),
+            working_dir: None,
+            resume: None,
         }
     }
 }
@@ -255,22 +404,12 @@ impl Config {
             }
         }
 
-        // Validate allowed tools
+        // Validate allowed and disallowed tools
         if let Some(tools) = &self.allowed_tools {
-            for tool in tools {
-                if tool.is_empty() || tool.len() > MAX_TOOL_NAME_LENGTH {
-                    return Err(Error::InvalidInput(format!(
-                        "Tool name length must be between 1 and {MAX_TOOL_NAME_LENGTH} characters (got '{tool}')"
-                    )));
-                }
-
-                // Validate tool name format
-                if !is_valid_tool_name(tool) {
-                    return Err(Error::InvalidInput(format!(
-                        "Invalid tool name format: '{tool}'. Tool names must contain only alphanumeric characters, underscores, hyphens, and colons"
-                    )));
-                }
-            }
+            validate_tool_names(tools)?;
+        }
+        if let Some(tools) = &self.disallowed_tools {
+            validate_tool_names(tools)?;
         }
 
         // Validate MCP config path
@@ -282,6 +421,31 @@ impl Config {
             }
         }
 
+        // Validate inline MCP servers
+        if let Some(servers) = &self.mcp_servers {
+            if servers.servers.iter().any(|server| server.name.is_empty()) {
+                return Err(Error::InvalidInput("MCP servers must be named".to_string()));
+            }
+        }
+
+        // Validate working directory
+        if let Some(dir) = &self.working_dir {
+            if dir.as_os_str().is_empty() {
+                return Err(Error::InvalidInput(
+                    "Working directory cannot be empty".to_string(),
+                ));
+            }
+        }
+
+        // Validate resumed session
+        if let Some(session) = &self.resume {
+            if session.as_str().is_empty() {
+                return Err(Error::InvalidInput(
+                    "Resumed session ID cannot be empty".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -385,6 +549,28 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set the MCP servers to connect Claude to
+    ///
+    /// The servers are rendered to JSON and passed to the CLI directly, so
+    /// no config file needs to exist on disk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::{Config, McpConfig, McpServer};
+    ///
+    /// let config = Config::builder()
+    ///     .mcp_servers(McpConfig::new().with_server(
+    ///         McpServer::new("winter", vec!["mcp-server"]).named("winter"),
+    ///     ))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn mcp_servers(mut self, servers: McpConfig) -> Self {
+        self.config.mcp_servers = Some(servers);
+        self
+    }
+
     /// Set the list of allowed tools
     ///
     /// Controls which tools Claude can access during execution. Use this
@@ -409,6 +595,42 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set the list of tools Claude may never use
+    ///
+    /// Disallowed tools are denied even if they also appear in the allow list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::Config;
+    ///
+    /// let config = Config::builder()
+    ///     .disallowed_tools(vec!["Bash".to_string(), "Write".to_string()])
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn disallowed_tools(mut self, tools: Vec<String>) -> Self {
+        self.config.disallowed_tools = Some(tools);
+        self
+    }
+
+    /// Set the permission mode for the Claude CLI
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::{Config, PermissionMode};
+    ///
+    /// let config = Config::builder()
+    ///     .permission_mode(PermissionMode::Plan)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.config.permission_mode = Some(mode);
+        self
+    }
+
     /// Set the output format for Claude CLI responses
     ///
     /// Choose between plain text, structured JSON, or streaming JSON formats
@@ -535,6 +757,40 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set which of this process's environment variables the CLI inherits
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::{Config, EnvScope};
+    ///
+    /// let config = Config::builder()
+    ///     .env_scope(EnvScope::Inherit)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn env_scope(mut self, scope: EnvScope) -> Self {
+        self.config.env_scope = scope;
+        self
+    }
+
+    /// Set the working directory for the Claude CLI subprocess
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::Config;
+    ///
+    /// let config = Config::builder()
+    ///     .working_dir("/var/lib/winter/workspace")
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.working_dir = Some(dir.into());
+        self
+    }
+
     /// Build the final configuration
     ///
     /// Consumes the builder and returns the constructed `Config` instance.
@@ -589,6 +845,31 @@ pub fn validate_query(query: &str) -> Result<(), Error> {
     Ok(())
 }
 
+/// Validate a list of tool names for `--allowedTools` / `--disallowedTools`
+///
+/// # Errors
+///
+/// Returns `Error::InvalidInput` if any name is empty, too long, or contains
+/// characters outside the accepted set.
+pub fn validate_tool_names(tools: &[String]) -> Result<(), Error> {
+    for tool in tools {
+        if tool.is_empty() || tool.len() > MAX_TOOL_NAME_LENGTH {
+            return Err(Error::InvalidInput(format!(
+                "Tool name length must be between 1 and {MAX_TOOL_NAME_LENGTH} characters (got '{tool}')"
+            )));
+        }
+
+        // Validate tool name format
+        if !is_valid_tool_name(tool) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid tool name format: '{tool}'. Tool names must contain only alphanumeric characters, underscores, hyphens, and colons"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Check if a tool name has valid format
 fn is_valid_tool_name(name: &str) -> bool {
     // Tool names should only contain alphanumeric, underscores, hyphens, and double underscores
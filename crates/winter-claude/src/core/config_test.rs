@@ -4,7 +4,7 @@
 //! including builder pattern, validation, defaults, and edge cases.
 
 use crate::core::{
-    config::{validate_query, Config, StreamFormat},
+    config::{validate_query, Config, EnvScope, StreamFormat},
     error::Error,
 };
 
@@ -26,6 +26,30 @@ mod config_defaults_tests {
         assert!(config.non_interactive);
         assert_eq!(config.max_tokens, None);
         assert_eq!(config.mcp_config_path, None);
+        assert_eq!(config.env_scope, EnvScope::restricted());
+        assert_eq!(config.working_dir, None);
+    }
+
+    #[test]
+    fn test_env_scope_with_vars_extends_allowlist_once() {
+        let scope = EnvScope::restricted().with_vars(["WINTER_MCP_TOKEN", "PATH"]);
+        let EnvScope::Allowlist(vars) = scope else {
+            panic!("restricted scope should be an allowlist");
+        };
+        assert!(vars.contains(&"WINTER_MCP_TOKEN".to_string()));
+        assert_eq!(vars.iter().filter(|v| *v == "PATH").count(), 1);
+
+        assert_eq!(EnvScope::Inherit.with_vars(["X"]), EnvScope::Inherit);
+    }
+
+    #[test]
+    fn test_env_scope_prefix_patterns() {
+        let scope = EnvScope::Allowlist(vec!["PATH".to_string(), "WINTER_*".to_string()]);
+        assert!(scope.allows("PATH"));
+        assert!(scope.allows("WINTER_MCP_TOKEN"));
+        assert!(!scope.allows("PATHEXT"));
+        assert!(!scope.allows("AWS_SECRET_ACCESS_KEY"));
+        assert!(EnvScope::Inherit.allows("AWS_SECRET_ACCESS_KEY"));
     }
 
     #[test]
@@ -363,6 +387,26 @@ mod tool_configuration_tests {
 
         assert_eq!(config.allowed_tools, Some(tools));
     }
+
+    #[test]
+    fn test_disallowed_tools() {
+        let tools = vec!["Bash".to_string(), "mcp__winter__delete_rule".to_string()];
+        let config = Config::builder()
+            .disallowed_tools(tools.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(config.disallowed_tools, Some(tools));
+    }
+
+    #[test]
+    fn test_invalid_disallowed_tool_rejected() {
+        let result = Config::builder()
+            .disallowed_tools(vec!["Bash; rm -rf /".to_string()])
+            .build();
+
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
 }
 
 /// Test configuration cloning and serialization
@@ -429,11 +473,17 @@ mod config_edge_cases {
             stream_format: StreamFormat::Text,
             timeout_secs: None,
             allowed_tools: None,
+            disallowed_tools: None,
+            permission_mode: None,
             mcp_config_path: None,
+            mcp_servers: None,
             non_interactive: true,
             verbose: false,
             max_tokens: None,
             env: None,
+            env_scope: EnvScope::Inherit,
+            working_dir: None,
+            resume: None,
         };
 
         assert_eq!(config.model, None);
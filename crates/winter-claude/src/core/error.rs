@@ -36,8 +36,16 @@ pub enum ErrorCode {
     RateLimitExceeded = 13,
     /// `C014`: UTF-8 conversion error
     Utf8Error = 14,
+    /// `C015`: Claude CLI exited with an error
+    CliFailed = 15,
 }
 
+/// Most stderr the CLI error variants keep, in bytes
+///
+/// The end of stderr is kept since that's where the CLI reports the error
+/// that actually stopped it.
+pub const MAX_STDERR_LEN: usize = 4096;
+
 impl fmt::Display for ErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "C{:03}", *self as u16)
@@ -167,6 +175,18 @@ pub enum Error {
     /// contains invalid UTF-8 sequences.
     #[error("[{code}] UTF-8 conversion error: {0}", code = ErrorCode::Utf8Error)]
     Utf8Error(#[from] std::string::FromUtf8Error),
+
+    /// Claude CLI exited with a non-zero status `[C015]`
+    ///
+    /// Carries the exit code (`-1` if the process was killed by a signal)
+    /// and the tail of its stderr, which usually names the actual cause.
+    #[error("[{ec}] Claude CLI exited with code {code}: {stderr}", ec = ErrorCode::CliFailed)]
+    CliFailed {
+        /// Process exit code, or `-1` if there was none
+        code: i32,
+        /// Trailing stderr output, truncated to [`MAX_STDERR_LEN`]
+        stderr: String,
+    },
 }
 
 impl Clone for Error {
@@ -193,6 +213,10 @@ impl Clone for Error {
             Error::Utf8Error(e) => {
                 Error::Utf8Error(String::from_utf8(e.as_bytes().to_vec()).unwrap_err())
             }
+            Error::CliFailed { code, stderr } => Error::CliFailed {
+                code: *code,
+                stderr: stderr.clone(),
+            },
         }
     }
 }
@@ -227,9 +251,27 @@ impl Error {
             Error::NotAuthenticated => ErrorCode::NotAuthenticated,
             Error::RateLimitExceeded => ErrorCode::RateLimitExceeded,
             Error::Utf8Error(_) => ErrorCode::Utf8Error,
+            Error::CliFailed { .. } => ErrorCode::CliFailed,
         }
     }
 
+    /// Build a [`Error::CliFailed`] from an exit code and raw stderr
+    ///
+    /// Stderr is trimmed and cut down to its last [`MAX_STDERR_LEN`] bytes.
+    pub fn cli_failed(code: i32, stderr: &str) -> Self {
+        let stderr = stderr.trim();
+        let stderr = if stderr.len() > MAX_STDERR_LEN {
+            let mut start = stderr.len() - MAX_STDERR_LEN;
+            while !stderr.is_char_boundary(start) {
+                start += 1;
+            }
+            format!("...[truncated]{}", &stderr[start..])
+        } else {
+            stderr.to_string()
+        };
+        Error::CliFailed { code, stderr }
+    }
+
     /// Check if this error is recoverable
     ///
     /// Some errors can be recovered from by retrying or changing
@@ -253,6 +295,7 @@ impl Error {
                 | Error::StreamClosed
                 | Error::Io(_)
                 | Error::ProcessError(_)
+                | Error::CliFailed { .. }
         )
     }
 }
@@ -414,6 +457,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_failed_includes_code_and_stderr() {
+        let error = Error::cli_failed(2, "  Error: invalid API key\n");
+        assert_eq!(error.code(), ErrorCode::CliFailed);
+
+        let message = error.to_string();
+        assert!(message.contains("[C015]"));
+        assert!(message.contains("code 2"));
+        assert!(message.ends_with("Error: invalid API key"));
+    }
+
+    #[test]
+    fn test_cli_failed_keeps_tail_of_long_stderr() {
+        let stderr = format!("{}fatal: the real cause", "é".repeat(MAX_STDERR_LEN));
+        let Error::CliFailed { stderr, .. } = Error::cli_failed(1, &stderr) else {
+            panic!("expected CliFailed");
+        };
+        assert!(stderr.starts_with("...[truncated]"));
+        assert!(stderr.ends_with("fatal: the real cause"));
+        assert!(stderr.len() <= MAX_STDERR_LEN + "...[truncated]".len());
+    }
+
     #[test]
     fn test_error_conversions() {
         // Test From implementations
@@ -0,0 +1,223 @@
+//! MCP server configuration passed to the Claude CLI.
+//!
+//! The CLI reads MCP servers from `--mcp-config`, which accepts either a path
+//! or the JSON itself. `McpConfig` describes the servers in code and renders
+//! the `{"mcpServers": {...}}` document the CLI expects, so callers don't have
+//! to keep a config file on disk in sync with the process that serves it.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// How the Claude CLI reaches an MCP server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpTransport {
+    /// Spawn the server as a child process and speak MCP over stdio
+    Stdio {
+        /// Executable to run
+        command: String,
+        /// Arguments passed to the executable
+        args: Vec<String>,
+        /// Environment variables set for the server process
+        env: BTreeMap<String, String>,
+    },
+    /// Connect to an already-running server over streamable HTTP
+    Http {
+        /// Endpoint URL, e.g. `http://127.0.0.1:3847/mcp`
+        url: String,
+        /// Headers sent with every request
+        ///
+        /// Values may use the CLI's `${VAR}` / `${VAR:-default}` substitution.
+        headers: BTreeMap<String, String>,
+    },
+}
+
+/// A single named MCP server
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::McpServer;
+///
+/// let local = McpServer::new("winter", vec!["mcp-server"]).named("winter");
+/// let remote = McpServer::http("http://mcp-server:3847/mcp")
+///     .named("winter")
+///     .with_header("Authorization", "Bearer ${WINTER_MCP_TOKEN:-}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServer {
+    /// Name the server's tools are namespaced under (`mcp__{name}__{tool}`)
+    pub name: String,
+    /// How to reach the server
+    pub transport: McpTransport,
+}
+
+impl McpServer {
+    /// A stdio server that runs `command` with `args`
+    pub fn new(command: impl Into<String>, args: Vec<&str>) -> Self {
+        Self {
+            name: String::new(),
+            transport: McpTransport::Stdio {
+                command: command.into(),
+                args: args.into_iter().map(String::from).collect(),
+                env: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// An HTTP server at `url`
+    pub fn http(url: impl Into<String>) -> Self {
+        Self {
+            name: String::new(),
+            transport: McpTransport::Http {
+                url: url.into(),
+                headers: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Set the server's name
+    #[must_use]
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set an environment variable for a stdio server
+    ///
+    /// Ignored for HTTP servers, which don't run as a child process.
+    #[must_use]
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        if let McpTransport::Stdio { env, .. } = &mut self.transport {
+            env.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Set a request header for an HTTP server
+    ///
+    /// Ignored for stdio servers.
+    #[must_use]
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        if let McpTransport::Http { headers, .. } = &mut self.transport {
+            headers.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// The server's entry in the CLI's `mcpServers` map
+    fn to_cli_entry(&self) -> Value {
+        match &self.transport {
+            McpTransport::Stdio { command, args, env } => json!({
+                "type": "stdio",
+                "command": command,
+                "args": args,
+                "env": env,
+            }),
+            McpTransport::Http { url, headers } => json!({
+                "type": "http",
+                "url": url,
+                "headers": headers,
+            }),
+        }
+    }
+}
+
+/// A set of MCP servers to connect the Claude CLI to
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::{Config, McpConfig, McpServer};
+///
+/// let servers = McpConfig::new().with_server(
+///     McpServer::http("http://127.0.0.1:3847/mcp").named("winter"),
+/// );
+/// let config = Config::builder().mcp_servers(servers).build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpConfig {
+    /// Servers to connect to
+    pub servers: Vec<McpServer>,
+}
+
+impl McpConfig {
+    /// An empty configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a server
+    #[must_use]
+    pub fn with_server(mut self, server: McpServer) -> Self {
+        self.servers.push(server);
+        self
+    }
+
+    /// Render the `{"mcpServers": {...}}` document the CLI reads
+    pub fn to_cli_json(&self) -> Value {
+        let servers: serde_json::Map<String, Value> = self
+            .servers
+            .iter()
+            .map(|server| (server.name.clone(), server.to_cli_entry()))
+            .collect();
+        json!({ "mcpServers": servers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stdio_server_json() {
+        let config = McpConfig::new().with_server(
+            McpServer::new("winter", vec!["mcp-server"])
+                .named("winter")
+                .with_env("WINTER_PDS_URL", "${WINTER_PDS_URL}")
+                .with_header("ignored", "for stdio"),
+        );
+
+        assert_eq!(
+            config.to_cli_json(),
+            json!({
+                "mcpServers": {
+                    "winter": {
+                        "type": "stdio",
+                        "command": "winter",
+                        "args": ["mcp-server"],
+                        "env": {"WINTER_PDS_URL": "${WINTER_PDS_URL}"},
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_http_server_json() {
+        let config = McpConfig::new().with_server(
+            McpServer::http("http://mcp-server:3847/mcp")
+                .named("winter")
+                .with_header("X-Winter-Trigger", "${WINTER_TRIGGER:-}")
+                .with_header("Authorization", "Bearer ${WINTER_MCP_TOKEN:-}"),
+        );
+
+        assert_eq!(
+            config.to_cli_json(),
+            json!({
+                "mcpServers": {
+                    "winter": {
+                        "type": "http",
+                        "url": "http://mcp-server:3847/mcp",
+                        "headers": {
+                            "Authorization": "Bearer ${WINTER_MCP_TOKEN:-}",
+                            "X-Winter-Trigger": "${WINTER_TRIGGER:-}",
+                        },
+                    }
+                }
+            })
+        );
+    }
+}
@@ -12,6 +12,8 @@ pub enum MessageType {
     User,
     /// Response from Claude AI assistant
     Assistant,
+    /// Extended-thinking reasoning from Claude AI assistant
+    Thinking,
     /// Final result message with statistics
     Result,
     /// System message for context setting
@@ -42,6 +44,7 @@ pub enum MessageType {
 ///         output: 100,
 ///         total: 150,
 ///     }),
+///     model: Some("claude-sonnet-4-5".to_string()),
 /// };
 ///
 /// // Check if this was an expensive message
@@ -63,6 +66,8 @@ pub struct MessageMeta {
     pub duration_ms: Option<u64>,
     /// Token usage for this specific message
     pub tokens_used: Option<TokenUsage>,
+    /// The model that generated this message, if known
+    pub model: Option<String>,
 }
 
 /// Token usage statistics for a message
@@ -119,6 +124,17 @@ pub enum Message {
         #[serde(flatten)]
         meta: MessageMeta,
     },
+    /// Claude AI's extended-thinking reasoning
+    Thinking {
+        /// The reasoning text (empty when redacted)
+        content: String,
+        /// Whether the reasoning was redacted by the API and only an
+        /// encrypted form was returned
+        #[serde(default)]
+        redacted: bool,
+        #[serde(flatten)]
+        meta: MessageMeta,
+    },
     /// Final result with conversation statistics
     Result {
         #[serde(flatten)]
@@ -135,6 +151,10 @@ pub enum Message {
     },
     /// Tool invocation request
     Tool {
+        /// Claude's tool use ID (e.g., `toolu_01ABC`), matching the
+        /// `tool_name` of the corresponding `ToolResult`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         /// Name of the tool to invoke
         name: String,
         /// Parameters for the tool
@@ -144,7 +164,8 @@ pub enum Message {
     },
     /// Result from a tool execution
     ToolResult {
-        /// Name of the tool that was executed
+        /// Identifier of the tool that was executed (the tool use ID when
+        /// parsed from stream-json output)
         tool_name: String,
         /// Result of the tool execution
         result: serde_json::Value,
@@ -203,6 +224,7 @@ impl Message {
             Message::Init { .. } => MessageType::Init,
             Message::User { .. } => MessageType::User,
             Message::Assistant { .. } => MessageType::Assistant,
+            Message::Thinking { .. } => MessageType::Thinking,
             Message::Result { .. } => MessageType::Result,
             Message::System { .. } => MessageType::System,
             Message::Tool { .. } => MessageType::Tool,
@@ -216,6 +238,7 @@ impl Message {
             Message::Init { meta, .. }
             | Message::User { meta, .. }
             | Message::Assistant { meta, .. }
+            | Message::Thinking { meta, .. }
             | Message::Result { meta, .. }
             | Message::System { meta, .. }
             | Message::Tool { meta, .. }
@@ -229,6 +252,8 @@ impl Message {
             Message::User { content, .. }
             | Message::Assistant { content, .. }
             | Message::System { content, .. } => content.clone(),
+            Message::Thinking { redacted: true, .. } => "Thinking redacted".to_string(),
+            Message::Thinking { content, .. } => content.clone(),
             Message::Tool {
                 name, parameters, ..
             } => {
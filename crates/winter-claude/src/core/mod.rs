@@ -4,6 +4,8 @@
 pub mod config;
 /// Error types and result helpers for the Claude AI SDK
 pub mod error;
+/// MCP server configuration passed to the Claude CLI
+pub mod mcp_config;
 /// Message types and structures for Claude AI conversations
 pub mod message;
 /// Session management for persistent conversations
@@ -11,14 +13,20 @@ pub mod session;
 /// Core types and response structures for the Claude AI SDK
 pub mod types;
 
-pub use config::{validate_query, Config, StreamFormat};
+pub use config::{
+    validate_query, validate_tool_names, Config, EnvScope, StreamFormat, DEFAULT_ENV_ALLOWLIST,
+};
 pub use error::{Error, ErrorCode, Result};
+pub use mcp_config::{McpConfig, McpServer, McpTransport};
 pub use message::{ConversationStats, Message, MessageMeta, MessageType, TokenUsage};
 pub use session::{
-    Session, SessionBuilder, SessionId, SessionManager, SessionStorage, StorageBackend,
+    CostEntry, CostLedger, CostRange, CostReport, CostTotals, RetentionPolicy, Session,
+    SessionBuilder, SessionId, SessionManager, SessionMessage, SessionRole, SessionStorage,
+    StorageBackend,
 };
 pub use types::{
-    ClaudeCliResponse, ClaudeResponse, Cost, ExtractedToolCall, ResponseMetadata, ToolPermission,
+    ClaudeCliResponse, ClaudeResponse, Cost, ExtractedToolCall, PermissionMode, ResponseMetadata,
+    ToolCallFilter, ToolCallResult, ToolPermission, MCP_TOOL_PREFIX,
 };
 
 #[cfg(test)]
@@ -0,0 +1,378 @@
+//! Per-session cost ledger.
+//!
+//! Every response carries its own cost and token usage, but nothing about
+//! a single response says how much a session (or a day) has cost so far.
+//! `CostLedger` records one `CostEntry` per response and aggregates them
+//! into a `CostReport` on demand. When given a path, entries are appended
+//! to a JSON-lines file so the totals survive restarts.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, sync::RwLock};
+
+use super::types::SessionId;
+use crate::{ClaudeResponse, Error, Result};
+
+/// Cost and token usage of a single response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEntry {
+    /// Session the response belongs to
+    pub session_id: SessionId,
+    /// When the response was recorded
+    pub recorded_at: DateTime<Utc>,
+    /// Cost of the response in USD
+    pub cost_usd: f64,
+    /// Input tokens processed
+    pub input_tokens: u64,
+    /// Output tokens generated
+    pub output_tokens: u64,
+}
+
+impl CostEntry {
+    /// Create an entry recorded now
+    pub fn new(
+        session_id: SessionId,
+        cost_usd: f64,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> Self {
+        Self {
+            session_id,
+            recorded_at: Utc::now(),
+            cost_usd,
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    /// Build an entry from a response's metadata
+    ///
+    /// Returns `None` when the response has no metadata to account for.
+    pub fn from_response(response: &ClaudeResponse) -> Option<Self> {
+        let metadata = response.metadata.as_ref()?;
+        let (input_tokens, output_tokens) = metadata.tokens_used.as_ref().map_or((0, 0), |t| {
+            (t.input_tokens.unwrap_or(0), t.output_tokens.unwrap_or(0))
+        });
+
+        Some(Self::new(
+            SessionId::new(metadata.session_id.clone()),
+            metadata.cost_usd.unwrap_or(0.0),
+            input_tokens,
+            output_tokens,
+        ))
+    }
+
+    /// Override the recording timestamp
+    #[must_use]
+    pub fn at(mut self, recorded_at: DateTime<Utc>) -> Self {
+        self.recorded_at = recorded_at;
+        self
+    }
+}
+
+/// Half-open time range `[start, end)` used to filter a report
+///
+/// A missing bound is unbounded on that side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostRange {
+    /// Inclusive lower bound
+    pub start: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl CostRange {
+    /// Every recorded entry
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Entries recorded in `[start, end)`
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    /// Entries recorded at or after `start`
+    pub fn since(start: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: None,
+        }
+    }
+
+    /// Entries recorded on the given UTC day
+    pub fn day(day: NaiveDate) -> Self {
+        let start = day.and_time(chrono::NaiveTime::MIN).and_utc();
+        Self::between(start, start + Duration::days(1))
+    }
+
+    /// Entries recorded today (UTC)
+    pub fn today() -> Self {
+        Self::day(Utc::now().date_naive())
+    }
+
+    /// Whether a timestamp falls inside the range
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| at >= start) && self.end.is_none_or(|end| at < end)
+    }
+}
+
+/// Accumulated cost and token usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostTotals {
+    /// Total cost in USD
+    pub cost_usd: f64,
+    /// Total input tokens
+    pub input_tokens: u64,
+    /// Total output tokens
+    pub output_tokens: u64,
+    /// Number of responses accounted for
+    pub responses: u64,
+}
+
+impl CostTotals {
+    fn add(&mut self, entry: &CostEntry) {
+        self.cost_usd += entry.cost_usd;
+        self.input_tokens += entry.input_tokens;
+        self.output_tokens += entry.output_tokens;
+        self.responses += 1;
+    }
+}
+
+/// Cost totals over a range, broken down by session and by UTC day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Range the report covers
+    pub range: CostRange,
+    /// Totals across every matching entry
+    pub total: CostTotals,
+    /// Totals per session
+    pub by_session: HashMap<SessionId, CostTotals>,
+    /// Totals per UTC day
+    pub by_day: BTreeMap<NaiveDate, CostTotals>,
+}
+
+impl CostReport {
+    /// Aggregate the entries that fall inside `range`
+    pub fn from_entries<'a>(
+        range: CostRange,
+        entries: impl IntoIterator<Item = &'a CostEntry>,
+    ) -> Self {
+        let mut report = Self {
+            range,
+            ..Self::default()
+        };
+
+        for entry in entries
+            .into_iter()
+            .filter(|e| range.contains(e.recorded_at))
+        {
+            report.total.add(entry);
+            report
+                .by_session
+                .entry(entry.session_id.clone())
+                .or_default()
+                .add(entry);
+            report
+                .by_day
+                .entry(entry.recorded_at.date_naive())
+                .or_default()
+                .add(entry);
+        }
+
+        report
+    }
+}
+
+/// Append-only record of response costs
+///
+/// In-memory by default; with [`CostLedger::persistent`] entries are also
+/// appended to a JSON-lines file, which is read back lazily on first use.
+#[derive(Debug, Default)]
+pub struct CostLedger {
+    path: Option<PathBuf>,
+    /// `None` until the backing file has been read
+    entries: RwLock<Option<Vec<CostEntry>>>,
+}
+
+impl CostLedger {
+    /// Create an in-memory ledger
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            entries: RwLock::new(Some(Vec::new())),
+        }
+    }
+
+    /// Create a ledger backed by a JSON-lines file
+    pub fn persistent(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+            entries: RwLock::new(None),
+        }
+    }
+
+    /// Record a single entry
+    pub async fn record(&self, entry: CostEntry) -> Result<()> {
+        let mut guard = self.entries.write().await;
+        let entries = self.load(&mut guard).await?;
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(Error::Io)?;
+            }
+            let mut line = serde_json::to_string(&entry)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await
+                .map_err(Error::Io)?;
+            file.write_all(line.as_bytes()).await.map_err(Error::Io)?;
+        }
+
+        entries.push(entry);
+        Ok(())
+    }
+
+    /// Aggregate every entry recorded inside `range`
+    pub async fn report(&self, range: CostRange) -> Result<CostReport> {
+        let mut guard = self.entries.write().await;
+        let entries = self.load(&mut guard).await?;
+        Ok(CostReport::from_entries(range, entries.iter()))
+    }
+
+    async fn load<'a>(
+        &self,
+        slot: &'a mut Option<Vec<CostEntry>>,
+    ) -> Result<&'a mut Vec<CostEntry>> {
+        if slot.is_none() {
+            let mut entries = Vec::new();
+            if let Some(path) = &self.path {
+                match tokio::fs::read_to_string(path).await {
+                    Ok(content) => {
+                        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+                            match serde_json::from_str(line) {
+                                Ok(entry) => entries.push(entry),
+                                // A torn final line from a crash shouldn't lose the
+                                // rest of the ledger.
+                                Err(e) => tracing::warn!(error = %e, "skipping bad cost entry"),
+                            }
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(Error::Io(e)),
+                }
+            }
+            *slot = Some(entries);
+        }
+
+        Ok(slot.get_or_insert_with(Vec::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn entry(session: &str, cost: f64, input: u64, output: u64, at: DateTime<Utc>) -> CostEntry {
+        CostEntry::new(SessionId::new(session), cost, input, output).at(at)
+    }
+
+    #[tokio::test]
+    async fn test_accumulates_totals_per_session() -> Result<()> {
+        let ledger = CostLedger::new();
+        ledger.record(entry("a", 0.25, 100, 10, ts(1, 9))).await?;
+        ledger.record(entry("a", 0.5, 200, 20, ts(1, 10))).await?;
+        ledger.record(entry("b", 1.0, 1000, 100, ts(1, 11))).await?;
+
+        let report = ledger.report(CostRange::all()).await?;
+        assert!((report.total.cost_usd - 1.75).abs() < 1e-9);
+        assert_eq!(report.total.input_tokens, 1300);
+        assert_eq!(report.total.output_tokens, 130);
+        assert_eq!(report.total.responses, 3);
+
+        let a = report.by_session[&SessionId::new("a")];
+        assert!((a.cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(a.input_tokens, 300);
+        assert_eq!(a.responses, 2);
+        assert_eq!(report.by_session[&SessionId::new("b")].responses, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_report_filters_by_range() -> Result<()> {
+        let ledger = CostLedger::new();
+        ledger.record(entry("a", 1.0, 10, 1, ts(1, 23))).await?;
+        ledger.record(entry("a", 2.0, 20, 2, ts(2, 0))).await?;
+        ledger.record(entry("b", 4.0, 40, 4, ts(2, 12))).await?;
+        ledger.record(entry("b", 8.0, 80, 8, ts(3, 0))).await?;
+
+        let day = ledger.report(CostRange::day(ts(2, 0).date_naive())).await?;
+        assert!((day.total.cost_usd - 6.0).abs() < 1e-9);
+        assert_eq!(day.total.responses, 2);
+        assert_eq!(day.by_day.len(), 1);
+
+        let since = ledger.report(CostRange::since(ts(2, 12))).await?;
+        assert!((since.total.cost_usd - 12.0).abs() < 1e-9);
+        assert!(!since.by_session.contains_key(&SessionId::new("a")));
+
+        let all = ledger.report(CostRange::all()).await?;
+        assert_eq!(all.by_day.len(), 3);
+        assert_eq!(all.total.input_tokens, 150);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_persistent_ledger_survives_reload() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("cost-ledger-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("costs.jsonl");
+
+        let ledger = CostLedger::persistent(&path);
+        ledger.record(entry("a", 0.5, 50, 5, ts(1, 9))).await?;
+        ledger.record(entry("b", 1.5, 150, 15, ts(1, 10))).await?;
+        drop(ledger);
+
+        let reloaded = CostLedger::persistent(&path);
+        reloaded.record(entry("a", 1.0, 100, 10, ts(1, 11))).await?;
+        let report = reloaded.report(CostRange::all()).await?;
+        assert_eq!(report.total.responses, 3);
+        assert!((report.total.cost_usd - 3.0).abs() < 1e-9);
+        assert_eq!(report.by_session[&SessionId::new("a")].input_tokens, 150);
+
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_from_response_metadata() {
+        let response = ClaudeResponse::with_json(
+            "hi".to_string(),
+            serde_json::json!({
+                "session_id": "s-1",
+                "cost_usd": 0.02,
+                "message": {"usage": {"input_tokens": 12, "output_tokens": 3}},
+            }),
+        );
+        let entry = CostEntry::from_response(&response).unwrap();
+        assert_eq!(entry.session_id, SessionId::new("s-1"));
+        assert!((entry.cost_usd - 0.02).abs() < 1e-9);
+        assert_eq!((entry.input_tokens, entry.output_tokens), (12, 3));
+
+        assert!(CostEntry::from_response(&ClaudeResponse::text("hi".into())).is_none());
+    }
+}
@@ -1,7 +1,13 @@
+/// Per-session cost and token accounting
+pub mod costs;
+/// Retention policy for pruning persisted sessions
+pub mod retention;
 /// Session management module
 pub mod types;
 
 #[cfg(feature = "sqlite")]
 pub mod sqlite_storage;
 
+pub use costs::{CostEntry, CostLedger, CostRange, CostReport, CostTotals};
+pub use retention::RetentionPolicy;
 pub use types::*;
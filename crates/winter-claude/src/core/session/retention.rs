@@ -0,0 +1,177 @@
+//! Retention policy for persisted sessions.
+//!
+//! Without pruning, every session the daemon creates stays in the store
+//! forever. A `RetentionPolicy` bounds the store by idle time, by count, or
+//! both; `SessionManager::prune` applies it on demand and
+//! `SessionManager::spawn_pruner` applies it on an interval.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::types::SessionId;
+
+/// Which sessions to keep when pruning the session store
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use winter_claude_core::RetentionPolicy;
+///
+/// // Drop sessions idle for a week, and never keep more than 500
+/// let policy = RetentionPolicy::new()
+///     .max_idle(Duration::from_secs(7 * 24 * 60 * 60))
+///     .max_sessions(500);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Remove sessions not updated for longer than this
+    pub max_idle: Option<Duration>,
+    /// Keep at most this many sessions, removing the least recently updated
+    pub max_sessions: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// A policy that keeps everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove sessions idle for longer than `ttl`
+    #[must_use]
+    pub fn max_idle(mut self, ttl: Duration) -> Self {
+        self.max_idle = Some(ttl);
+        self
+    }
+
+    /// Keep at most `count` sessions
+    #[must_use]
+    pub fn max_sessions(mut self, count: usize) -> Self {
+        self.max_sessions = Some(count);
+        self
+    }
+
+    /// Whether the policy can ever remove anything
+    pub fn is_unbounded(&self) -> bool {
+        self.max_idle.is_none() && self.max_sessions.is_none()
+    }
+
+    /// Sessions to remove, given each session's last update time
+    ///
+    /// Sessions idle past `max_idle` go first; if more than `max_sessions`
+    /// remain, the least recently updated of those go too.
+    pub fn select_expired(
+        &self,
+        mut sessions: Vec<(SessionId, DateTime<Utc>)>,
+        now: DateTime<Utc>,
+    ) -> Vec<SessionId> {
+        // Most recently updated first, so the LRU tail is at the end
+        sessions.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let mut keep = sessions.len();
+        if let Some(ttl) = self.max_idle {
+            let cutoff = chrono::Duration::from_std(ttl)
+                .ok()
+                .and_then(|ttl| now.checked_sub_signed(ttl))
+                .unwrap_or(DateTime::<Utc>::MIN_UTC);
+            keep = sessions.partition_point(|(_, updated_at)| *updated_at >= cutoff);
+        }
+        if let Some(max) = self.max_sessions {
+            keep = keep.min(max);
+        }
+
+        sessions
+            .split_off(keep)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::session::{Session, SessionManager};
+
+    fn sessions(now: DateTime<Utc>, ages_secs: &[i64]) -> Vec<(SessionId, DateTime<Utc>)> {
+        ages_secs
+            .iter()
+            .map(|age| {
+                let updated_at = now - chrono::Duration::seconds(*age);
+                (SessionId::new(format!("s{age}")), updated_at)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unbounded_policy_keeps_everything() {
+        let now = Utc::now();
+        let policy = RetentionPolicy::new();
+
+        assert!(policy.is_unbounded());
+        assert!(
+            policy
+                .select_expired(sessions(now, &[0, 10, 1_000_000]), now)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_max_idle_removes_only_stale_sessions() {
+        let now = Utc::now();
+        let policy = RetentionPolicy::new().max_idle(Duration::from_secs(60));
+
+        let expired = policy.select_expired(sessions(now, &[3600, 5, 61, 60]), now);
+        assert_eq!(
+            expired,
+            vec![SessionId::new("s61"), SessionId::new("s3600")]
+        );
+    }
+
+    #[test]
+    fn test_max_sessions_removes_least_recently_updated() {
+        let now = Utc::now();
+        let policy = RetentionPolicy::new().max_sessions(2);
+
+        let expired = policy.select_expired(sessions(now, &[30, 10, 20, 40]), now);
+        assert_eq!(expired, vec![SessionId::new("s30"), SessionId::new("s40")]);
+    }
+
+    #[tokio::test]
+    async fn test_manager_prunes_sessions_past_ttl() {
+        let manager = SessionManager::new();
+        let now = Utc::now();
+
+        let mut stale = Session::new(SessionId::new("stale"));
+        stale.updated_at = now - chrono::Duration::hours(2);
+        manager.save(&stale).await.unwrap();
+
+        let active = Session::new(SessionId::new("active"));
+        manager.save(&active).await.unwrap();
+
+        let policy = RetentionPolicy::new().max_idle(Duration::from_secs(60 * 60));
+        assert_eq!(manager.prune(&policy).await.unwrap(), 1);
+
+        assert!(manager.get(&stale.id).await.unwrap().is_none());
+        assert!(manager.get(&active.id).await.unwrap().is_some());
+
+        // Nothing left to remove on a second pass
+        assert_eq!(manager.prune(&policy).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_new_messages_keep_session_from_pruning() {
+        use crate::core::session::{SessionMessage, SessionRole};
+
+        let manager = SessionManager::new();
+        let mut session = Session::new(SessionId::new("resumed"));
+        session.updated_at = Utc::now() - chrono::Duration::hours(2);
+        session.push_message(SessionMessage::new(SessionRole::User, "still here"));
+        manager.save(&session).await.unwrap();
+
+        let policy = RetentionPolicy::new().max_idle(Duration::from_secs(60 * 60));
+        assert_eq!(manager.prune(&policy).await.unwrap(), 0);
+        assert!(manager.get(&session.id).await.unwrap().is_some());
+    }
+}
@@ -1,15 +1,21 @@
 use std::path::PathBuf;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::Error as _;
 use sqlx::{
-    sqlite::{SqlitePool, SqlitePoolOptions},
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
     Row,
 };
 
-use super::types::{Session, SessionId, SessionStorage};
+use super::types::{Session, SessionId, SessionMessage, SessionRole, SessionStorage};
 use crate::{Error, Result};
 
 /// SQLite-based session storage
+///
+/// Sessions live in a `sessions` table alongside their cumulative cost, and
+/// each session's history is kept in `session_messages`, ordered by position.
+/// A session and its messages are always written in a single transaction.
 #[derive(Debug)]
 pub struct SqliteStorage {
     pool: SqlitePool,
@@ -17,28 +23,31 @@ pub struct SqliteStorage {
 
 impl SqliteStorage {
     /// Create a new SQLite storage backend
+    ///
+    /// Creates the database file and schema if they don't exist yet, and
+    /// upgrades databases written before history and cost were stored.
     pub async fn new(path: PathBuf) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .map_err(|e| Error::Io(e))?;
+            tokio::fs::create_dir_all(parent).await.map_err(Error::Io)?;
         }
 
-        let connection_string = format!("sqlite:{}", path.display());
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
 
         let pool = SqlitePoolOptions::new()
             .max_connections(5)
-            .connect(&connection_string)
+            .connect_with(options)
             .await
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to connect to SQLite database: {}", e),
-                ))
-            })?;
+            .map_err(|e| db_error("Failed to connect to SQLite database", &e))?;
+
+        let storage = Self { pool };
+        storage.migrate().await?;
+        Ok(storage)
+    }
 
-        // Create sessions table if it doesn't exist
+    async fn migrate(&self) -> Result<()> {
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS sessions (
@@ -46,20 +55,80 @@ impl SqliteStorage {
                 system_prompt TEXT,
                 metadata TEXT NOT NULL,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                total_cost_usd REAL NOT NULL DEFAULT 0
             )
             "#,
         )
-        .execute(&pool)
+        .execute(&self.pool)
         .await
-        .map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to create sessions table: {}", e),
-            ))
-        })?;
+        .map_err(|e| db_error("Failed to create sessions table", &e))?;
+
+        // Databases created before cost tracking lack the column
+        let columns = sqlx::query("PRAGMA table_info(sessions)")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_error("Failed to inspect sessions table", &e))?;
+        let has_cost = columns
+            .iter()
+            .any(|row| row.get::<String, _>("name") == "total_cost_usd");
+        if !has_cost {
+            sqlx::query("ALTER TABLE sessions ADD COLUMN total_cost_usd REAL NOT NULL DEFAULT 0")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| db_error("Failed to add total_cost_usd column", &e))?;
+        }
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS session_messages (
+                session_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                PRIMARY KEY (session_id, position)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| db_error("Failed to create session_messages table", &e))?;
 
-        Ok(Self { pool })
+        Ok(())
+    }
+
+    async fn load_history(&self, id: &SessionId) -> Result<Vec<SessionMessage>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT role, content, timestamp
+            FROM session_messages
+            WHERE session_id = ?1
+            ORDER BY position
+            "#,
+        )
+        .bind(id.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| db_error("Failed to load session history", &e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let role: String = row.get("role");
+                let role = SessionRole::parse(&role).ok_or_else(|| {
+                    Error::SerializationError(serde_json::Error::custom(format!(
+                        "Invalid message role: {role}"
+                    )))
+                })?;
+                let timestamp: String = row.get("timestamp");
+
+                Ok(SessionMessage {
+                    role,
+                    content: row.get("content"),
+                    timestamp: parse_timestamp("timestamp", &timestamp)?,
+                })
+            })
+            .collect()
     }
 }
 
@@ -75,32 +144,62 @@ impl Clone for SqliteStorage {
 impl SessionStorage for SqliteStorage {
     async fn save(&self, session: &Session) -> Result<()> {
         let metadata_json = serde_json::to_string(&session.metadata)?;
-        let mut updated_session = session.clone();
-        updated_session.updated_at = chrono::Utc::now();
+        let updated_at = Utc::now();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to begin transaction", &e))?;
 
         sqlx::query(
             r#"
-            INSERT INTO sessions (id, system_prompt, metadata, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT INTO sessions
+                (id, system_prompt, metadata, created_at, updated_at, total_cost_usd)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             ON CONFLICT(id) DO UPDATE SET
                 system_prompt = excluded.system_prompt,
                 metadata = excluded.metadata,
-                updated_at = excluded.updated_at
+                updated_at = excluded.updated_at,
+                total_cost_usd = excluded.total_cost_usd
             "#,
         )
         .bind(session.id.as_str())
         .bind(&session.system_prompt)
         .bind(&metadata_json)
         .bind(session.created_at.to_rfc3339())
-        .bind(updated_session.updated_at.to_rfc3339())
-        .execute(&self.pool)
+        .bind(updated_at.to_rfc3339())
+        .bind(session.total_cost_usd)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to save session: {}", e),
-            ))
-        })?;
+        .map_err(|e| db_error("Failed to save session", &e))?;
+
+        sqlx::query("DELETE FROM session_messages WHERE session_id = ?1")
+            .bind(session.id.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to replace session history", &e))?;
+
+        for (position, message) in session.history.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO session_messages (session_id, position, role, content, timestamp)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(session.id.as_str())
+            .bind(i64::try_from(position).unwrap_or(i64::MAX))
+            .bind(message.role.as_str())
+            .bind(&message.content)
+            .bind(message.timestamp.to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to save session history", &e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit session", &e))?;
 
         Ok(())
     }
@@ -108,7 +207,7 @@ impl SessionStorage for SqliteStorage {
     async fn load(&self, id: &SessionId) -> Result<Option<Session>> {
         let row = sqlx::query(
             r#"
-            SELECT id, system_prompt, metadata, created_at, updated_at
+            SELECT id, system_prompt, metadata, created_at, updated_at, total_cost_usd
             FROM sessions
             WHERE id = ?1
             "#,
@@ -116,12 +215,7 @@ impl SessionStorage for SqliteStorage {
         .bind(id.as_str())
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to load session: {}", e),
-            ))
-        })?;
+        .map_err(|e| db_error("Failed to load session", &e))?;
 
         match row {
             Some(row) => {
@@ -130,29 +224,19 @@ impl SessionStorage for SqliteStorage {
                 let metadata_json: String = row.get("metadata");
                 let created_at_str: String = row.get("created_at");
                 let updated_at_str: String = row.get("updated_at");
+                let total_cost_usd: f64 = row.get("total_cost_usd");
 
                 let metadata = serde_json::from_str(&metadata_json)?;
-                let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
-                    .map_err(|e| {
-                        Error::SerializationError(serde_json::Error::custom(format!(
-                            "Invalid created_at timestamp: {}",
-                            e
-                        )))
-                    })?
-                    .with_timezone(&chrono::Utc);
-                let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
-                    .map_err(|e| {
-                        Error::SerializationError(serde_json::Error::custom(format!(
-                            "Invalid updated_at timestamp: {}",
-                            e
-                        )))
-                    })?
-                    .with_timezone(&chrono::Utc);
+                let created_at = parse_timestamp("created_at", &created_at_str)?;
+                let updated_at = parse_timestamp("updated_at", &updated_at_str)?;
+                let history = self.load_history(id).await?;
 
                 Ok(Some(Session {
                     id: SessionId::new(id_str),
                     system_prompt,
                     metadata,
+                    history,
+                    total_cost_usd,
                     created_at,
                     updated_at,
                 }))
@@ -162,16 +246,27 @@ impl SessionStorage for SqliteStorage {
     }
 
     async fn delete(&self, id: &SessionId) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to begin transaction", &e))?;
+
+        sqlx::query("DELETE FROM session_messages WHERE session_id = ?1")
+            .bind(id.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to delete session history", &e))?;
+
         sqlx::query("DELETE FROM sessions WHERE id = ?1")
             .bind(id.as_str())
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to delete session: {}", e),
-                ))
-            })?;
+            .map_err(|e| db_error("Failed to delete session", &e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit session deletion", &e))?;
 
         Ok(())
     }
@@ -180,12 +275,7 @@ impl SessionStorage for SqliteStorage {
         let rows = sqlx::query("SELECT id FROM sessions ORDER BY updated_at DESC")
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to list sessions: {}", e),
-                ))
-            })?;
+            .map_err(|e| db_error("Failed to list sessions", &e))?;
 
         let ids = rows
             .into_iter()
@@ -198,26 +288,69 @@ impl SessionStorage for SqliteStorage {
         Ok(ids)
     }
 
+    async fn list_updated(&self) -> Result<Vec<(SessionId, DateTime<Utc>)>> {
+        let rows = sqlx::query("SELECT id, updated_at FROM sessions")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_error("Failed to list sessions", &e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id_str: String = row.get("id");
+                let updated_at_str: String = row.get("updated_at");
+                Ok((
+                    SessionId::new(id_str),
+                    parse_timestamp("updated_at", &updated_at_str)?,
+                ))
+            })
+            .collect()
+    }
+
     async fn clear(&self) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to begin transaction", &e))?;
+
+        sqlx::query("DELETE FROM session_messages")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to clear session history", &e))?;
+
         sqlx::query("DELETE FROM sessions")
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to clear sessions: {}", e),
-                ))
-            })?;
+            .map_err(|e| db_error("Failed to clear sessions", &e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit clear", &e))?;
 
         Ok(())
     }
 }
 
+fn db_error(context: &str, e: &sqlx::Error) -> Error {
+    Error::Io(std::io::Error::other(format!("{context}: {e}")))
+}
+
+fn parse_timestamp(field: &str, value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            Error::SerializationError(serde_json::Error::custom(format!(
+                "Invalid {field} timestamp: {e}"
+            )))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::core::{ClaudeResponse, ResponseMetadata, SessionManager, StorageBackend};
 
     async fn create_test_storage() -> Result<(SqliteStorage, TempDir)> {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -226,6 +359,20 @@ mod tests {
         Ok((storage, temp_dir))
     }
 
+    fn response(content: &str, cost_usd: f64) -> ClaudeResponse {
+        ClaudeResponse {
+            content: content.to_string(),
+            raw_json: None,
+            metadata: Some(ResponseMetadata {
+                session_id: "test-session".to_string(),
+                cost_usd: Some(cost_usd),
+                duration_ms: None,
+                tokens_used: None,
+                model: None,
+            }),
+        }
+    }
+
     #[tokio::test]
     async fn test_sqlite_storage_save_and_load() -> Result<()> {
         let (storage, _temp_dir) = create_test_storage().await?;
@@ -275,6 +422,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_sqlite_storage_history_and_cost_round_trip() -> Result<()> {
+        let (storage, _temp_dir) = create_test_storage().await?;
+
+        let mut session = Session::new(SessionId::new("test-session-history"));
+        session.record_exchange("first question", &response("first answer", 0.25));
+        session.record_exchange("second question", &response("second answer", 0.5));
+        storage.save(&session).await?;
+
+        let loaded = storage.load(&session.id).await?.unwrap();
+        assert_eq!(loaded.history, session.history);
+        assert!((loaded.total_cost_usd - 0.75).abs() < f64::EPSILON);
+
+        let roles: Vec<SessionRole> = loaded.history.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                SessionRole::User,
+                SessionRole::Assistant,
+                SessionRole::User,
+                SessionRole::Assistant,
+            ]
+        );
+
+        // Saving a shorter history replaces rather than appends
+        session.history.truncate(1);
+        storage.save(&session).await?;
+        let loaded = storage.load(&session.id).await?.unwrap();
+        assert_eq!(loaded.history.len(), 1);
+        assert_eq!(loaded.history[0].content, "first question");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_resumes_from_sqlite() -> Result<()> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("sessions.db");
+        let id = SessionId::new("daemon-session");
+
+        {
+            let manager =
+                SessionManager::with_storage_async(StorageBackend::Sqlite(db_path.clone())).await?;
+            let mut session = Session::new(id.clone()).with_system_prompt("You are Winter");
+            manager
+                .record_exchange(&mut session, "hello", &response("hi there", 0.1))
+                .await?;
+        }
+
+        // A fresh manager over the same file sees the session as it was left
+        let manager = SessionManager::with_storage_async(StorageBackend::Sqlite(db_path)).await?;
+        let resumed = manager.resume(&id).await?;
+        assert_eq!(resumed.system_prompt.as_deref(), Some("You are Winter"));
+        assert_eq!(resumed.history.len(), 2);
+        assert_eq!(resumed.history[1].content, "hi there");
+        assert!((resumed.total_cost_usd - 0.1).abs() < f64::EPSILON);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_sqlite_storage_delete() -> Result<()> {
         let (storage, _temp_dir) = create_test_storage().await?;
@@ -317,4 +524,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_list_updated() -> Result<()> {
+        let (storage, _temp_dir) = create_test_storage().await?;
+        let before = Utc::now();
+
+        let session = Session::new(SessionId::new("test-session"));
+        storage.save(&session).await?;
+
+        let updated = storage.list_updated().await?;
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].0, session.id);
+        assert!(updated[0].1 >= before);
+
+        Ok(())
+    }
 }
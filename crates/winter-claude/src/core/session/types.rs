@@ -4,9 +4,11 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use super::costs::{CostEntry, CostLedger, CostRange, CostReport};
+use super::retention::RetentionPolicy;
 #[cfg(feature = "sqlite")]
 use super::sqlite_storage::SqliteStorage;
-use crate::{Error, Result};
+use crate::{ClaudeResponse, Error, Result};
 
 /// Unique identifier for a Claude AI session
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
@@ -30,6 +32,57 @@ impl std::fmt::Display for SessionId {
     }
 }
 
+/// Who authored a message in a session's history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionRole {
+    /// The prompt sent to Claude
+    User,
+    /// Claude's reply
+    Assistant,
+}
+
+impl SessionRole {
+    /// The role as stored on disk
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+
+    /// Parse a role previously produced by [`SessionRole::as_str`]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "user" => Some(Self::User),
+            "assistant" => Some(Self::Assistant),
+            _ => None,
+        }
+    }
+}
+
+/// A single message in a session's history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionMessage {
+    /// Who sent the message
+    pub role: SessionRole,
+    /// Message text
+    pub content: String,
+    /// When the message was added to the session
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl SessionMessage {
+    /// Create a message timestamped now
+    pub fn new(role: SessionRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+}
+
 /// Represents an active Claude AI session
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Session {
@@ -39,6 +92,12 @@ pub struct Session {
     pub system_prompt: Option<String>,
     /// Additional metadata associated with the session
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Messages exchanged in this session, oldest first
+    #[serde(default)]
+    pub history: Vec<SessionMessage>,
+    /// Total cost in USD of every response recorded in this session
+    #[serde(default)]
+    pub total_cost_usd: f64,
     /// Creation timestamp
     #[serde(default = "chrono::Utc::now")]
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -55,6 +114,8 @@ impl Session {
             id,
             system_prompt: None,
             metadata: HashMap::new(),
+            history: Vec::new(),
+            total_cost_usd: 0.0,
             created_at: now,
             updated_at: now,
         }
@@ -71,6 +132,24 @@ impl Session {
     pub fn id(&self) -> &SessionId {
         &self.id
     }
+
+    /// Append a message to the session's history
+    pub fn push_message(&mut self, message: SessionMessage) {
+        self.history.push(message);
+        self.updated_at = chrono::Utc::now();
+    }
+
+    /// Append a prompt and Claude's response, adding the response's cost
+    pub fn record_exchange(&mut self, prompt: impl Into<String>, response: &ClaudeResponse) {
+        self.push_message(SessionMessage::new(SessionRole::User, prompt));
+        self.push_message(SessionMessage::new(
+            SessionRole::Assistant,
+            response.content.clone(),
+        ));
+        if let Some(cost) = response.metadata.as_ref().and_then(|m| m.cost_usd) {
+            self.total_cost_usd += cost;
+        }
+    }
 }
 
 /// Trait for session storage backends
@@ -91,6 +170,20 @@ pub trait SessionStorage: Send + Sync + std::fmt::Debug {
     /// List all session IDs in storage
     async fn list_ids(&self) -> Result<Vec<SessionId>>;
 
+    /// List every session's ID with its last update time
+    ///
+    /// Used for pruning. The default loads each session in full; backends
+    /// should override it to skip loading histories.
+    async fn list_updated(&self) -> Result<Vec<(SessionId, chrono::DateTime<chrono::Utc>)>> {
+        let mut sessions = Vec::new();
+        for id in self.list_ids().await? {
+            if let Some(session) = self.load(&id).await? {
+                sessions.push((id, session.updated_at));
+            }
+        }
+        Ok(sessions)
+    }
+
     /// Clear all sessions from storage
     async fn clear(&self) -> Result<()>;
 }
@@ -153,6 +246,14 @@ impl SessionStorage for MemoryStorage {
         Ok(sessions.keys().cloned().collect())
     }
 
+    async fn list_updated(&self) -> Result<Vec<(SessionId, chrono::DateTime<chrono::Utc>)>> {
+        let sessions = self.sessions.read().await;
+        Ok(sessions
+            .values()
+            .map(|session| (session.id.clone(), session.updated_at))
+            .collect())
+    }
+
     async fn clear(&self) -> Result<()> {
         let mut sessions = self.sessions.write().await;
         sessions.clear();
@@ -243,6 +344,28 @@ impl SessionStorage for FileStorage {
         Ok(ids)
     }
 
+    async fn list_updated(&self) -> Result<Vec<(SessionId, chrono::DateTime<chrono::Utc>)>> {
+        /// Just the timestamp; the history is skipped rather than built
+        #[derive(Deserialize)]
+        struct Stamp {
+            #[serde(default = "chrono::Utc::now")]
+            updated_at: chrono::DateTime<chrono::Utc>,
+        }
+
+        let mut sessions = Vec::new();
+        for id in self.list_ids().await? {
+            match tokio::fs::read_to_string(self.session_file_path(&id)).await {
+                Ok(content) => {
+                    let stamp: Stamp = serde_json::from_str(&content)?;
+                    sessions.push((id, stamp.updated_at));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(Error::Io(e)),
+            }
+        }
+        Ok(sessions)
+    }
+
     async fn clear(&self) -> Result<()> {
         if self.base_path.exists() {
             let mut entries = tokio::fs::read_dir(&self.base_path)
@@ -262,9 +385,14 @@ impl SessionStorage for FileStorage {
 }
 
 /// Manages multiple Claude AI sessions with configurable storage
+///
+/// The manager also keeps a cost ledger. With the `File` backend the ledger
+/// is written to `costs.jsonl` in the session directory, and with `Sqlite`
+/// it sits next to the database, so spend survives restarts.
 #[derive(Debug, Clone)]
 pub struct SessionManager {
     storage: Arc<Box<dyn SessionStorage>>,
+    costs: Arc<CostLedger>,
 }
 
 impl SessionManager {
@@ -275,6 +403,7 @@ impl SessionManager {
 
     /// Create a new session manager with specified storage backend
     pub fn with_storage(backend: StorageBackend) -> Self {
+        let costs = Self::cost_ledger_for(&backend);
         let storage: Box<dyn SessionStorage> = match backend {
             StorageBackend::Memory => Box::new(MemoryStorage::new()),
             StorageBackend::File(path) => Box::new(FileStorage::new(path)),
@@ -286,12 +415,14 @@ impl SessionManager {
 
         Self {
             storage: Arc::new(storage),
+            costs: Arc::new(costs),
         }
     }
 
     /// Create a new session manager with specified storage backend (async version for `SQLite`)
     #[allow(clippy::unused_async)]
     pub async fn with_storage_async(backend: StorageBackend) -> Result<Self> {
+        let costs = Self::cost_ledger_for(&backend);
         let storage: Box<dyn SessionStorage> = match backend {
             StorageBackend::Memory => Box::new(MemoryStorage::new()),
             StorageBackend::File(path) => Box::new(FileStorage::new(path)),
@@ -301,9 +432,28 @@ impl SessionManager {
 
         Ok(Self {
             storage: Arc::new(storage),
+            costs: Arc::new(costs),
         })
     }
 
+    /// Replace the cost ledger, e.g. to persist costs for in-memory sessions
+    #[must_use]
+    pub fn with_cost_ledger(mut self, ledger: CostLedger) -> Self {
+        self.costs = Arc::new(ledger);
+        self
+    }
+
+    fn cost_ledger_for(backend: &StorageBackend) -> CostLedger {
+        match backend {
+            StorageBackend::Memory => CostLedger::new(),
+            StorageBackend::File(path) => CostLedger::persistent(path.join("costs.jsonl")),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite(path) => {
+                CostLedger::persistent(path.with_extension("costs.jsonl"))
+            }
+        }
+    }
+
     /// Create a new session builder
     pub fn builder() -> SessionBuilder {
         SessionBuilder::new()
@@ -342,8 +492,85 @@ impl SessionManager {
         self.storage.clear().await
     }
 
-    async fn store(&self, session: Session) -> Result<()> {
-        self.storage.save(&session).await
+    /// Record the cost of a single response against its session
+    pub async fn record_cost(&self, entry: CostEntry) -> Result<()> {
+        self.costs.record(entry).await
+    }
+
+    /// Record the cost and token usage reported in a response's metadata
+    ///
+    /// Responses without metadata carry nothing to account for and are ignored.
+    pub async fn record_response(&self, response: &ClaudeResponse) -> Result<()> {
+        match CostEntry::from_response(response) {
+            Some(entry) => self.record_cost(entry).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Cost and token totals for responses recorded inside `range`
+    ///
+    /// Use `CostRange::today()` for the current day's spend.
+    pub async fn report(&self, range: CostRange) -> Result<CostReport> {
+        self.costs.report(range).await
+    }
+
+    /// Remove sessions that fall outside `policy`, returning how many were removed
+    ///
+    /// Recorded costs are kept; only the sessions themselves are deleted.
+    pub async fn prune(&self, policy: &RetentionPolicy) -> Result<usize> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+
+        let sessions = self.storage.list_updated().await?;
+        let expired = policy.select_expired(sessions, chrono::Utc::now());
+        for id in &expired {
+            self.storage.delete(id).await?;
+        }
+
+        Ok(expired.len())
+    }
+
+    /// Prune with `policy` every `interval` until the returned task is aborted
+    ///
+    /// Failures are logged and retried on the next tick.
+    pub fn spawn_pruner(
+        &self,
+        policy: RetentionPolicy,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match manager.prune(&policy).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!(removed, "pruned expired sessions"),
+                    Err(e) => tracing::warn!(error = %e, "failed to prune sessions"),
+                }
+            }
+        })
+    }
+
+    /// Persist a session, including its history and cumulative cost
+    pub async fn save(&self, session: &Session) -> Result<()> {
+        self.storage.save(session).await
+    }
+
+    /// Record a prompt/response exchange on `session` and persist it
+    ///
+    /// Appends both messages to the session's history, adds the response's
+    /// cost to the session total and the cost ledger, then saves the session.
+    pub async fn record_exchange(
+        &self,
+        session: &mut Session,
+        prompt: impl Into<String>,
+        response: &ClaudeResponse,
+    ) -> Result<()> {
+        session.record_exchange(prompt, response);
+        self.record_response(response).await?;
+        self.save(session).await
     }
 }
 
@@ -439,7 +666,7 @@ impl SessionBuilder {
     /// Build and optionally store the session
     pub async fn build(self) -> Result<Session> {
         if let Some(manager) = self.manager {
-            manager.store(self.session.clone()).await?;
+            manager.save(&self.session).await?;
         }
         Ok(self.session)
     }
@@ -28,6 +28,167 @@ pub struct ExtractedToolCall {
     pub input: serde_json::Value,
 }
 
+/// Prefix Claude gives tools served over MCP: `mcp__{server}__{tool}`
+pub const MCP_TOOL_PREFIX: &str = "mcp__";
+
+impl ExtractedToolCall {
+    /// Whether the call targets an MCP tool rather than a Claude built-in
+    pub fn is_mcp(&self) -> bool {
+        self.name.starts_with(MCP_TOOL_PREFIX)
+    }
+
+    /// Name of the MCP server for an `mcp__{server}__{tool}` call
+    pub fn mcp_server(&self) -> Option<&str> {
+        let rest = self.name.strip_prefix(MCP_TOOL_PREFIX)?;
+        rest.split_once("__").map(|(server, _)| server)
+    }
+
+    /// Render the call in the JSON shape Winter stores in tool-call thoughts
+    ///
+    /// The schema is stable: `{"tool": name, "args": input, "builtin": bool,
+    /// "claude_id": id}`, where `builtin` is false for `mcp__*` tools.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use winter_claude_core::ExtractedToolCall;
+    /// use serde_json::json;
+    ///
+    /// let call = ExtractedToolCall {
+    ///     id: "toolu_01ABC".to_string(),
+    ///     name: "WebSearch".to_string(),
+    ///     input: json!({"query": "rust"}),
+    /// };
+    /// let json = call.to_json();
+    /// assert_eq!(json["tool"], "WebSearch");
+    /// assert_eq!(json["args"], json!({"query": "rust"}));
+    /// assert_eq!(json["builtin"], true);
+    /// assert_eq!(json["claude_id"], "toolu_01ABC");
+    /// ```
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "tool": self.name,
+            "args": self.input,
+            "builtin": !self.is_mcp(),
+            "claude_id": self.id,
+        })
+    }
+}
+
+/// Selects which extracted tool calls to keep
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::{ExtractedToolCall, ToolCallFilter};
+/// use serde_json::json;
+///
+/// let call = ExtractedToolCall {
+///     id: "toolu_01".to_string(),
+///     name: "mcp__winter__query_facts".to_string(),
+///     input: json!({}),
+/// };
+/// assert!(ToolCallFilter::Mcp.matches(&call));
+/// assert!(ToolCallFilter::mcp_server("winter").matches(&call));
+/// assert!(!ToolCallFilter::Builtin.matches(&call));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ToolCallFilter {
+    /// Keep every call
+    #[default]
+    All,
+    /// Keep calls to exactly these tool names
+    Names(Vec<String>),
+    /// Keep calls whose tool name starts with this prefix
+    Prefix(String),
+    /// Keep calls to MCP tools (`mcp__*`)
+    Mcp,
+    /// Keep calls to Claude's built-in tools (anything not `mcp__*`)
+    Builtin,
+}
+
+impl ToolCallFilter {
+    /// Keep calls to the given tool names
+    pub fn names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Names(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Keep calls to tools served by the named MCP server
+    pub fn mcp_server(server: &str) -> Self {
+        Self::Prefix(format!("{MCP_TOOL_PREFIX}{server}__"))
+    }
+
+    /// Whether `call` passes the filter
+    pub fn matches(&self, call: &ExtractedToolCall) -> bool {
+        match self {
+            Self::All => true,
+            Self::Names(names) => names.iter().any(|n| *n == call.name),
+            Self::Prefix(prefix) => call.name.starts_with(prefix.as_str()),
+            Self::Mcp => call.is_mcp(),
+            Self::Builtin => !call.is_mcp(),
+        }
+    }
+}
+
+/// Result of a tool call, supplied by the caller for a follow-up query.
+///
+/// When the caller executes an [`ExtractedToolCall`] itself (for example by
+/// forwarding it to an MCP server), it feeds the outcome back with
+/// `QueryBuilder::tool_results` so Claude can continue from it.
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::{ExtractedToolCall, ToolCallResult};
+/// use serde_json::json;
+///
+/// let call = ExtractedToolCall {
+///     id: "toolu_01ABC".to_string(),
+///     name: "WebSearch".to_string(),
+///     input: json!({"query": "Rust async programming"}),
+/// };
+/// let result = ToolCallResult::success(&call, json!(["https://tokio.rs"]));
+/// assert_eq!(result.tool_use_id, "toolu_01ABC");
+/// assert!(!result.is_error);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallResult {
+    /// ID of the tool call this answers (e.g., `toolu_01ABC`)
+    pub tool_use_id: String,
+    /// Name of the tool that was called
+    pub name: String,
+    /// Output of the tool, or the error message if it failed
+    pub content: serde_json::Value,
+    /// Whether the tool call failed
+    pub is_error: bool,
+}
+
+impl ToolCallResult {
+    /// A successful result for `call`
+    pub fn success(call: &ExtractedToolCall, content: serde_json::Value) -> Self {
+        Self {
+            tool_use_id: call.id.clone(),
+            name: call.name.clone(),
+            content,
+            is_error: false,
+        }
+    }
+
+    /// A failed result for `call`
+    pub fn error(call: &ExtractedToolCall, message: impl Into<String>) -> Self {
+        Self {
+            tool_use_id: call.id.clone(),
+            name: call.name.clone(),
+            content: serde_json::Value::String(message.into()),
+            is_error: true,
+        }
+    }
+}
+
 /// Raw response from Claude CLI in JSON format
 ///
 /// This represents the direct JSON response from the Claude CLI tool.
@@ -267,6 +428,17 @@ impl ClaudeResponse {
         }
     }
 
+    /// Tool calls Claude made in this response
+    ///
+    /// Only `StreamFormat::StreamJson` responses carry tool calls; any other
+    /// response returns an empty list.
+    pub fn tool_calls(&self) -> Vec<ExtractedToolCall> {
+        self.raw_json
+            .as_ref()
+            .map(crate::runtime::extract_tool_calls)
+            .unwrap_or_default()
+    }
+
     /// Extract structured metadata from raw JSON response
     ///
     /// This method parses the raw JSON to extract commonly used metadata
@@ -433,6 +605,52 @@ impl ToolPermission {
     }
 }
 
+/// Permission mode the Claude CLI runs a query under
+///
+/// Maps to the CLI's `--permission-mode` flag. In `-p` mode nothing can
+/// answer an interactive permission prompt, so this decides up front how
+/// much the session may do on its own.
+///
+/// # Examples
+///
+/// ```rust
+/// use winter_claude_core::PermissionMode;
+///
+/// assert_eq!(PermissionMode::Plan.as_cli_str(), "plan");
+/// assert_eq!(PermissionMode::default(), PermissionMode::Default);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    /// Standard behavior: only pre-approved tools run without a prompt
+    #[default]
+    Default,
+    /// File edits are accepted without prompting
+    AcceptEdits,
+    /// Read-only planning; no edits or commands are executed
+    Plan,
+    /// Every permission check is skipped
+    BypassPermissions,
+}
+
+impl PermissionMode {
+    /// The value passed to `--permission-mode`
+    pub fn as_cli_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::AcceptEdits => "acceptEdits",
+            Self::Plan => "plan",
+            Self::BypassPermissions => "bypassPermissions",
+        }
+    }
+}
+
+impl std::fmt::Display for PermissionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_cli_str())
+    }
+}
+
 /// Represents a cost in USD
 ///
 /// This is a simple wrapper around a floating-point cost value that provides
@@ -564,4 +782,18 @@ mod tests {
         assert_eq!(response.cost_usd, None);
         assert_eq!(response.total_cost, None);
     }
+
+    #[test]
+    fn test_permission_mode_serializes_as_cli_string() {
+        for mode in [
+            PermissionMode::Default,
+            PermissionMode::AcceptEdits,
+            PermissionMode::Plan,
+            PermissionMode::BypassPermissions,
+        ] {
+            let json = serde_json::to_value(mode).unwrap();
+            assert_eq!(json, serde_json::json!(mode.as_cli_str()));
+            assert_eq!(mode.to_string(), mode.as_cli_str());
+        }
+    }
 }
@@ -25,15 +25,19 @@ pub mod cli;
 
 // Re-export core types for convenience
 pub use crate::core::{
-    ClaudeResponse, Config, ConversationStats, Cost, Error, ExtractedToolCall, Message,
-    MessageMeta, MessageType, ResponseMetadata, Result, Session, SessionId, SessionManager,
-    StreamFormat, TokenUsage, ToolPermission,
+    ClaudeResponse, Config, ConversationStats, Cost, CostEntry, CostRange, CostReport, EnvScope,
+    Error, ExtractedToolCall, Message, MessageMeta, MessageType, PermissionMode, ResponseMetadata,
+    Result, Session, SessionId, SessionManager, SessionMessage, SessionRole, StreamFormat,
+    TokenUsage, ToolCallFilter, ToolCallResult, ToolPermission,
 };
 // Re-export MCP types when feature is enabled
 #[cfg(feature = "mcp")]
 pub use crate::mcp::{McpConfig, McpServer};
 // Re-export runtime types
-pub use crate::runtime::{extract_tool_calls, Client, MessageStream, QueryBuilder};
+pub use crate::runtime::{
+    extract_tool_calls, extract_tool_calls_json, extract_tool_calls_matching, Client,
+    MessageStream, QueryBuilder,
+};
 
 /// Prelude module for convenient imports
 pub mod prelude {
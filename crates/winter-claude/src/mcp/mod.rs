@@ -55,3 +55,24 @@ impl McpServer {
         self
     }
 }
+
+impl From<McpServer> for crate::core::McpServer {
+    fn from(server: McpServer) -> Self {
+        Self {
+            name: server.name,
+            transport: crate::core::McpTransport::Stdio {
+                command: server.command,
+                args: server.args,
+                env: server.env.into_iter().collect(),
+            },
+        }
+    }
+}
+
+impl From<McpConfig> for crate::core::McpConfig {
+    fn from(config: McpConfig) -> Self {
+        Self {
+            servers: config.servers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -9,47 +9,18 @@ use tokio::{
 use tracing::{debug, info};
 
 use crate::{
-    core::{Config, Error, Result, StreamFormat},
+    core::{Config, EnvScope, Error, Result, StreamFormat},
     runtime::{
         error_handling::{log_error_with_context, ErrorContext, ProcessErrorDetails},
         telemetry,
     },
 };
 
-/// Execute a one-shot Claude command with timeout
-#[allow(clippy::too_many_lines)]
-pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
-    let context = ErrorContext::new("execute_claude")
-        .with_debug_info("query_length", query.len().to_string())
-        .with_debug_info("stream_format", format!("{:?}", config.stream_format))
-        .with_debug_info(
-            "timeout_secs",
-            config.timeout_secs.unwrap_or(30).to_string(),
-        );
-
-    let claude_binary = which::which("claude").map_err(|e| {
-        let enhanced_context = context
-            .clone()
-            .with_error_chain(format!("Binary search failed: {e}"))
-            .with_debug_info("search_error", e.to_string());
-        let error = Error::BinaryNotFound;
-        log_error_with_context(&error, &enhanced_context);
-
-        // Record to telemetry
-        let mut telemetry_context = HashMap::new();
-        telemetry_context.insert("search_error".to_string(), e.to_string());
-        telemetry_context.insert(
-            "path_env".to_string(),
-            std::env::var("PATH").unwrap_or_default(),
-        );
-        let error_clone = error.clone();
-        tokio::spawn(async move {
-            telemetry::record_error(&error_clone, "execute_claude", telemetry_context).await;
-        });
-
-        error
-    })?;
-
+/// Build the Claude CLI command for a configuration
+///
+/// Both execution paths share this so every flag the config can express is
+/// emitted the same way; the query and stdio are left to the caller.
+fn build_command(claude_binary: impl AsRef<std::ffi::OsStr>, config: &Config) -> Command {
     let mut cmd = Command::new(claude_binary);
 
     // Always use non-interactive mode for SDK
@@ -88,6 +59,11 @@ pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
         cmd.arg("--mcp-config").arg(mcp_config_path);
     }
 
+    if let Some(servers) = &config.mcp_servers {
+        cmd.arg("--mcp-config")
+            .arg(servers.to_cli_json().to_string());
+    }
+
     if let Some(allowed_tools) = &config.allowed_tools {
         for tool in allowed_tools {
             cmd.arg("--allowedTools").arg(tool);
@@ -95,17 +71,87 @@ pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
         debug!("Added {} allowed tools", allowed_tools.len());
     }
 
+    if let Some(disallowed_tools) = &config.disallowed_tools {
+        for tool in disallowed_tools {
+            cmd.arg("--disallowedTools").arg(tool);
+        }
+        debug!("Added {} disallowed tools", disallowed_tools.len());
+    }
+
+    if let Some(mode) = config.permission_mode {
+        cmd.arg("--permission-mode").arg(mode.as_cli_str());
+    }
+
     if let Some(max_tokens) = &config.max_tokens {
         cmd.arg("--max-tokens").arg(max_tokens.to_string());
     }
 
-    // Apply custom environment variables
+    if let Some(session) = &config.resume {
+        cmd.arg("--resume").arg(session.as_str());
+    }
+
+    // Scope the inherited environment, then apply custom variables on top
+    if let EnvScope::Allowlist(_) = &config.env_scope {
+        cmd.env_clear();
+        for (name, value) in std::env::vars_os() {
+            if name
+                .to_str()
+                .is_some_and(|name| config.env_scope.allows(name))
+            {
+                cmd.env(name, value);
+            }
+        }
+    }
+
     if let Some(env_vars) = &config.env {
         for (key, value) in env_vars {
             cmd.env(key, value);
         }
     }
 
+    if let Some(dir) = &config.working_dir {
+        cmd.current_dir(dir);
+    }
+
+    cmd
+}
+
+/// Execute a one-shot Claude command with timeout
+#[allow(clippy::too_many_lines)]
+pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
+    let context = ErrorContext::new("execute_claude")
+        .with_debug_info("query_length", query.len().to_string())
+        .with_debug_info("stream_format", format!("{:?}", config.stream_format))
+        .with_debug_info(
+            "timeout_secs",
+            config.timeout_secs.unwrap_or(30).to_string(),
+        );
+
+    let claude_binary = which::which("claude").map_err(|e| {
+        let enhanced_context = context
+            .clone()
+            .with_error_chain(format!("Binary search failed: {e}"))
+            .with_debug_info("search_error", e.to_string());
+        let error = Error::BinaryNotFound;
+        log_error_with_context(&error, &enhanced_context);
+
+        // Record to telemetry
+        let mut telemetry_context = HashMap::new();
+        telemetry_context.insert("search_error".to_string(), e.to_string());
+        telemetry_context.insert(
+            "path_env".to_string(),
+            std::env::var("PATH").unwrap_or_default(),
+        );
+        let error_clone = error.clone();
+        tokio::spawn(async move {
+            telemetry::record_error(&error_clone, "execute_claude", telemetry_context).await;
+        });
+
+        error
+    })?;
+
+    let mut cmd = build_command(claude_binary, config);
+
     // Determine if we should use stdin or command argument
     let use_stdin =
         config.allowed_tools.is_some() && !config.allowed_tools.as_ref().unwrap().is_empty();
@@ -230,9 +276,8 @@ pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
                 .with_debug_info("stderr_length", stderr.len().to_string())
                 .with_debug_info("stdout_length", stdout.len().to_string());
 
-            let process_error = error_details.to_error();
-            log_error_with_context(&process_error, &enhanced_context);
-            return Err(process_error);
+            log_error_with_context(&error_details.to_error(), &enhanced_context);
+            return Err(Error::cli_failed(status.code().unwrap_or(-1), &stderr));
         }
 
         let stdout = String::from_utf8_lossy(&stdout_content).to_string();
@@ -311,9 +356,8 @@ pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
                 .with_debug_info("exit_code", status.code().unwrap_or(-1).to_string())
                 .with_debug_info("stderr_length", stderr.len().to_string());
 
-            let process_error = error_details.to_error();
-            log_error_with_context(&process_error, &enhanced_context);
-            return Err(process_error);
+            log_error_with_context(&error_details.to_error(), &enhanced_context);
+            return Err(Error::cli_failed(status.code().unwrap_or(-1), &stderr));
         }
 
         let stdout = String::from_utf8_lossy(&stdout_content).to_string();
@@ -377,76 +421,34 @@ pub async fn execute_claude(config: &Config, query: &str) -> Result<String> {
 ///     Ok(())
 /// }
 /// ```
-#[allow(clippy::too_many_lines)]
 pub async fn execute_claude_streaming(
     config: &Config,
     query: &str,
 ) -> Result<mpsc::Receiver<Result<String>>> {
     let claude_binary = which::which("claude").map_err(|_| Error::BinaryNotFound)?;
 
-    let mut cmd = Command::new(claude_binary);
-
-    // Always use non-interactive mode for SDK
-    cmd.arg("-p");
-
-    // Add format flag
-    match config.stream_format {
-        StreamFormat::Json => {
-            cmd.arg("--output-format").arg("json");
-        }
-        StreamFormat::StreamJson => {
-            cmd.arg("--output-format").arg("stream-json");
-            // stream-json requires verbose flag
-            cmd.arg("--verbose");
-        }
-        StreamFormat::Text => {
-            // Text is default, no need to specify
-        }
-    }
-
-    // Add verbose flag if configured (and not already added for stream-json)
-    if config.verbose && config.stream_format != StreamFormat::StreamJson {
-        cmd.arg("--verbose");
-    }
-
-    // Add optional flags
-    if let Some(system_prompt) = &config.system_prompt {
-        cmd.arg("--system-prompt").arg(system_prompt);
-    }
+    let cmd = build_command(claude_binary, config);
 
-    if let Some(model) = &config.model {
-        cmd.arg("--model").arg(model);
-    }
-
-    if let Some(mcp_config_path) = &config.mcp_config_path {
-        cmd.arg("--mcp-config").arg(mcp_config_path);
-    }
-
-    if let Some(allowed_tools) = &config.allowed_tools {
-        for tool in allowed_tools {
-            cmd.arg("--allowedTools").arg(tool);
-        }
-        debug!("Added {} allowed tools", allowed_tools.len());
-    }
-
-    if let Some(max_tokens) = &config.max_tokens {
-        cmd.arg("--max-tokens").arg(max_tokens.to_string());
-    }
+    debug!("Executing Claude command for streaming: {:?}", cmd);
 
-    // Apply custom environment variables
-    if let Some(env_vars) = &config.env {
-        for (key, value) in env_vars {
-            cmd.env(key, value);
-        }
-    }
+    spawn_streaming(cmd, query, config.timeout_secs.unwrap_or(30)).await
+}
 
+/// Spawn a prepared command, write the query to its stdin and stream stdout
+///
+/// stderr is drained in the background; if the process exits non-zero the
+/// final item on the stream is an [`Error::CliFailed`] carrying it.
+#[allow(clippy::too_many_lines)]
+async fn spawn_streaming(
+    mut cmd: Command,
+    query: &str,
+    timeout_secs: u64,
+) -> Result<mpsc::Receiver<Result<String>>> {
     // Set up stdio for streaming
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    debug!("Executing Claude command for streaming: {:?}", cmd);
-
     let mut child = cmd
         .spawn()
         .map_err(|e| Error::ProcessError(format!("Failed to spawn process: {e}")))?;
@@ -475,7 +477,6 @@ pub async fn execute_claude_streaming(
     let child_for_reader = child.clone();
     let child_for_monitor = child.clone();
 
-    let timeout_secs = config.timeout_secs.unwrap_or(30);
     let timeout_duration = Duration::from_secs(timeout_secs);
 
     // Spawn task to read stdout line by line.
@@ -484,6 +485,20 @@ pub async fn execute_claude_streaming(
         guard.stdout.take()
     };
 
+    // Drain stderr as it arrives so a chatty CLI can't fill the pipe and
+    // stall, and so a failure can report what the CLI said.
+    let stderr = {
+        let mut guard = child.lock().await;
+        guard.stderr.take()
+    };
+    let stderr_task = stderr.map(|mut stderr| {
+        tokio::spawn(async move {
+            let mut buf = Vec::new();
+            let _ = tokio::io::AsyncReadExt::read_to_end(&mut stderr, &mut buf).await;
+            String::from_utf8_lossy(&buf).into_owned()
+        })
+    });
+
     if let Some(stdout) = stdout {
         let tx_clone = tx.clone();
 
@@ -541,11 +556,15 @@ pub async fn execute_claude_streaming(
 
         match status {
             Ok(status) if !status.success() => {
-                let exit_code = status.code().unwrap_or(-1);
+                let stderr = match stderr_task {
+                    Some(task) => task.await.unwrap_or_default(),
+                    None => String::new(),
+                };
+                if !stderr.is_empty() {
+                    debug!("Claude CLI stderr (streaming): {}", stderr);
+                }
                 let _ = tx
-                    .send(Err(Error::ProcessError(format!(
-                        "Claude command failed with exit code {exit_code}"
-                    ))))
+                    .send(Err(Error::cli_failed(status.code().unwrap_or(-1), &stderr)))
                     .await;
             }
             Err(e) => {
@@ -618,3 +637,223 @@ pub async fn execute_claude_with_retry(
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{McpConfig, McpServer, PermissionMode, SessionId, DEFAULT_ENV_ALLOWLIST};
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_allowed_tools_emitted_in_order() {
+        let config = Config::builder()
+            .allowed_tools(vec![
+                "mcp__winter__check_inbox".to_string(),
+                "Read".to_string(),
+            ])
+            .build()
+            .unwrap();
+
+        let args = args(&build_command("claude", &config));
+        let tail: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+        assert_eq!(
+            tail,
+            [
+                "--allowedTools",
+                "mcp__winter__check_inbox",
+                "--allowedTools",
+                "Read",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_disallowed_tools_emitted() {
+        let config = Config::builder()
+            .allowed_tools(vec!["Read".to_string()])
+            .disallowed_tools(vec!["Bash".to_string(), "Write".to_string()])
+            .build()
+            .unwrap();
+
+        let args = args(&build_command("claude", &config));
+        let disallowed: Vec<&str> = args
+            .windows(2)
+            .filter(|w| w[0] == "--disallowedTools")
+            .map(|w| w[1].as_str())
+            .collect();
+        assert_eq!(disallowed, ["Bash", "Write"]);
+        assert_eq!(args.iter().filter(|a| *a == "--allowedTools").count(), 1);
+    }
+
+    #[test]
+    fn test_permission_mode_flag_for_each_variant() {
+        for (mode, expected) in [
+            (PermissionMode::Default, "default"),
+            (PermissionMode::AcceptEdits, "acceptEdits"),
+            (PermissionMode::Plan, "plan"),
+            (PermissionMode::BypassPermissions, "bypassPermissions"),
+        ] {
+            let config = Config::builder().permission_mode(mode).build().unwrap();
+            let args = args(&build_command("claude", &config));
+            assert_eq!(args[1..], ["--permission-mode", expected]);
+        }
+    }
+
+    fn envs(cmd: &Command) -> HashMap<String, Option<String>> {
+        cmd.as_std()
+            .get_envs()
+            .map(|(k, v)| {
+                (
+                    k.to_string_lossy().into_owned(),
+                    v.map(|v| v.to_string_lossy().into_owned()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resumed_session_emitted() {
+        let config = Config {
+            resume: Some(SessionId::new("session-1")),
+            ..Config::default()
+        };
+
+        let args = args(&build_command("claude", &config));
+        assert_eq!(args[1..], ["--resume", "session-1"]);
+    }
+
+    #[test]
+    fn test_working_dir_and_env_applied() {
+        let config = Config::builder()
+            .working_dir("/srv/winter")
+            .env(HashMap::from([(
+                "WINTER_TRIGGER".to_string(),
+                "notification:at://x".to_string(),
+            )]))
+            .env_scope(EnvScope::Allowlist(vec!["PATH".to_string()]))
+            .build()
+            .unwrap();
+
+        let cmd = build_command("claude", &config);
+        assert_eq!(
+            cmd.as_std().get_current_dir(),
+            Some(std::path::Path::new("/srv/winter"))
+        );
+
+        // Only the allowlisted variable and the explicit ones are passed.
+        let envs = envs(&cmd);
+        let mut expected: Vec<&str> = vec!["WINTER_TRIGGER"];
+        if std::env::var_os("PATH").is_some() {
+            expected.push("PATH");
+        }
+        let mut names: Vec<&str> = envs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(names, expected);
+        assert_eq!(
+            envs["WINTER_TRIGGER"].as_deref(),
+            Some("notification:at://x")
+        );
+    }
+
+    #[test]
+    fn test_restricted_env_skips_unlisted_vars() {
+        let cmd = build_command("claude", &Config::default());
+        let envs = envs(&cmd);
+        let allowed = |k: &String| DEFAULT_ENV_ALLOWLIST.contains(&k.as_str());
+        assert!(envs.keys().all(allowed));
+        // Cargo sets this for every test binary; it must not reach the CLI.
+        assert!(!envs.contains_key("CARGO_PKG_NAME"));
+        assert_eq!(cmd.as_std().get_current_dir(), None);
+    }
+
+    #[test]
+    fn test_inherit_env_passes_only_explicit_vars() {
+        let config = Config::builder()
+            .env_scope(EnvScope::Inherit)
+            .build()
+            .unwrap();
+        assert!(envs(&build_command("claude", &config)).is_empty());
+    }
+
+    async fn drain(mut rx: mpsc::Receiver<Result<String>>) -> Vec<Result<String>> {
+        let mut items = Vec::new();
+        while let Some(item) = rx.recv().await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_streaming_failure_surfaces_exit_code_and_stderr() {
+        let script =
+            "cat >/dev/null; echo partial; echo 'Error: credit balance too low' >&2; exit 3";
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+
+        let items = drain(spawn_streaming(cmd, "hello", 10).await.unwrap()).await;
+
+        let partial = |i: &Result<String>| matches!(i, Ok(line) if line == "partial");
+        assert!(items.iter().any(partial));
+        let failure = items
+            .iter()
+            .find_map(|i| i.as_ref().err())
+            .expect("non-zero exit should surface an error");
+        match failure {
+            Error::CliFailed { code, stderr } => {
+                assert_eq!(*code, 3);
+                assert_eq!(stderr, "Error: credit balance too low");
+            }
+            other => panic!("expected CliFailed, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_success_ignores_stderr() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("cat; echo 'warning: noisy' >&2");
+
+        let items = drain(spawn_streaming(cmd, "echoed", 10).await.unwrap()).await;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].as_deref().ok(), Some("echoed"));
+    }
+
+    #[test]
+    fn test_mcp_servers_passed_inline() {
+        let servers = McpConfig::new().with_server(
+            McpServer::http("http://127.0.0.1:3847/mcp")
+                .named("winter")
+                .with_header("Authorization", "Bearer ${WINTER_MCP_TOKEN:-}"),
+        );
+        let config = Config::builder()
+            .mcp_config("/etc/winter/extra-mcp.json")
+            .mcp_servers(servers.clone())
+            .build()
+            .unwrap();
+
+        let args = args(&build_command("claude", &config));
+        let values: Vec<&str> = args
+            .windows(2)
+            .filter(|w| w[0] == "--mcp-config")
+            .map(|w| w[1].as_str())
+            .collect();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], "/etc/winter/extra-mcp.json");
+        let inline: serde_json::Value = serde_json::from_str(values[1]).unwrap();
+        assert_eq!(inline, servers.to_cli_json());
+        assert_eq!(inline["mcpServers"]["winter"]["type"], "http");
+    }
+
+    #[test]
+    fn test_no_tool_flags_by_default() {
+        let args = args(&build_command("claude", &Config::default()));
+        assert_eq!(args, ["-p"]);
+    }
+}
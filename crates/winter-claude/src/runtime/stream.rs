@@ -80,13 +80,21 @@ impl MessageStream {
         line: &str,
         tx: &mpsc::Sender<Result<Message>>,
     ) -> bool {
-        if let Ok(Some(message)) = parser.parse_line(line) {
-            tx.send(Ok(message)).await.is_err()
-        } else {
-            if !line.trim().is_empty() {
-                debug!("Failed to parse line as message: {}", line);
+        match parser.parse_line(line) {
+            Ok(messages) if !messages.is_empty() => {
+                for message in messages {
+                    if tx.send(Ok(message)).await.is_err() {
+                        return true;
+                    }
+                }
+                false
+            }
+            _ => {
+                if !line.trim().is_empty() {
+                    debug!("Failed to parse line as message: {}", line);
+                }
+                false
             }
-            false
         }
     }
 
@@ -145,6 +153,7 @@ impl MessageStream {
                                 cost_usd: None,
                                 duration_ms: None,
                                 tokens_used: None,
+                                model: None,
                             },
                         };
 
@@ -181,6 +190,7 @@ impl MessageStream {
                             cost_usd: None,
                             duration_ms: None,
                             tokens_used: None,
+                            model: None,
                         },
                         stats: ConversationStats {
                             total_messages: 1,
@@ -267,6 +277,9 @@ struct CliApiMessage {
     content: Vec<CliContentBlock>,
     #[serde(default)]
     usage: Option<CliUsage>,
+    /// Model that generated the message (assistant messages only).
+    #[serde(default)]
+    model: Option<String>,
 }
 
 /// A content block inside the API message.
@@ -286,6 +299,14 @@ enum CliContentBlock {
         tool_use_id: String,
         content: Option<serde_json::Value>,
     },
+    /// Extended-thinking reasoning.
+    Thinking {
+        thinking: String,
+    },
+    /// Reasoning the API redacted, returned only in encrypted form.
+    RedactedThinking {
+        data: String,
+    },
 }
 
 /// Token usage from the API message.
@@ -297,13 +318,18 @@ struct CliUsage {
     output_tokens: u64,
 }
 
-/// Convert a CLI stream envelope into a `Message`.
-fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
+/// Convert a CLI stream envelope into `Message`s.
+///
+/// Parallel tool calls arrive as several `tool_use` (or `tool_result`) blocks
+/// in one envelope; each becomes its own message.
+fn convert_envelope(env: CliStreamEnvelope) -> Vec<Message> {
     let session_id = env.session_id.unwrap_or_default();
 
     match env.envelope_type.as_str() {
         "assistant" => {
-            let msg = env.message?;
+            let Some(msg) = env.message else {
+                return Vec::new();
+            };
             let usage = msg.usage.as_ref();
             let meta = MessageMeta {
                 session_id: session_id.clone(),
@@ -315,22 +341,25 @@ fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
                     output: u.output_tokens,
                     total: u.input_tokens + u.output_tokens,
                 }),
+                model: msg.model.clone(),
             };
 
             // Check for tool_use blocks first
-            for block in &msg.content {
-                if let CliContentBlock::ToolUse {
-                    name,
-                    input,
-                    ..
-                } = block
-                {
-                    return Some(Message::Tool {
+            let tools: Vec<Message> = msg
+                .content
+                .iter()
+                .filter_map(|block| match block {
+                    CliContentBlock::ToolUse { id, name, input } => Some(Message::Tool {
+                        id: Some(id.clone()),
                         name: name.clone(),
                         parameters: input.clone(),
-                        meta,
-                    });
-                }
+                        meta: meta.clone(),
+                    }),
+                    _ => None,
+                })
+                .collect();
+            if !tools.is_empty() {
+                return tools;
             }
 
             // Otherwise concatenate text blocks
@@ -344,34 +373,49 @@ fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
                 .collect::<Vec<_>>()
                 .join("");
 
-            Some(Message::Assistant {
+            // The CLI emits thinking as its own message, ahead of the text
+            if text.is_empty()
+                && let Some(thinking) = convert_thinking(&msg.content, meta.clone())
+            {
+                return vec![thinking];
+            }
+
+            vec![Message::Assistant {
                 content: text,
                 meta,
-            })
+            }]
         }
         "user" => {
-            let msg = env.message?;
+            let Some(msg) = env.message else {
+                return Vec::new();
+            };
             let meta = MessageMeta {
                 session_id,
                 timestamp: Some(std::time::SystemTime::now()),
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             };
 
             // Check for tool_result blocks
-            for block in &msg.content {
-                if let CliContentBlock::ToolResult {
-                    tool_use_id,
-                    content,
-                } = block
-                {
-                    return Some(Message::ToolResult {
+            let results: Vec<Message> = msg
+                .content
+                .iter()
+                .filter_map(|block| match block {
+                    CliContentBlock::ToolResult {
+                        tool_use_id,
+                        content,
+                    } => Some(Message::ToolResult {
                         tool_name: tool_use_id.clone(),
                         result: content.clone().unwrap_or(serde_json::Value::Null),
-                        meta,
-                    });
-                }
+                        meta: meta.clone(),
+                    }),
+                    _ => None,
+                })
+                .collect();
+            if !results.is_empty() {
+                return results;
             }
 
             // Otherwise treat as user text
@@ -385,10 +429,10 @@ fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
                 .collect::<Vec<_>>()
                 .join("");
 
-            Some(Message::User {
+            vec![Message::User {
                 content: text,
                 meta,
-            })
+            }]
         }
         "result" => {
             // Result envelope — convert from ClaudeCliResponse-like fields
@@ -398,19 +442,20 @@ fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
                 cost_usd: env.cost_usd,
                 duration_ms: env.duration_ms,
                 tokens_used: None,
+                model: None,
             };
 
             // If there's a result string, check if it's an error
             if env.is_error.unwrap_or(false) {
                 if let Some(result_text) = env.result {
-                    return Some(Message::System {
+                    return vec![Message::System {
                         content: result_text,
                         meta,
-                    });
+                    }];
                 }
             }
 
-            Some(Message::Result {
+            vec![Message::Result {
                 meta,
                 stats: ConversationStats {
                     total_messages: u64::from(env.num_turns.unwrap_or(0)),
@@ -422,16 +467,43 @@ fn convert_envelope(env: CliStreamEnvelope) -> Option<Message> {
                         total: 0,
                     },
                 },
-            })
+            }]
         }
         _ => {
             // Unknown envelope type — skip
             debug!("Skipping unknown CLI stream envelope type: {}", env.envelope_type);
-            None
+            Vec::new()
         }
     }
 }
 
+/// Collect thinking blocks into a `Thinking` message, if there are any.
+///
+/// The message counts as redacted only if every thinking block was.
+fn convert_thinking(blocks: &[CliContentBlock], meta: MessageMeta) -> Option<Message> {
+    let mut thoughts = Vec::new();
+    let mut any_thinking = false;
+    for block in blocks {
+        match block {
+            CliContentBlock::Thinking { thinking } => {
+                any_thinking = true;
+                thoughts.push(thinking.as_str());
+            }
+            CliContentBlock::RedactedThinking { .. } => any_thinking = true,
+            _ => {}
+        }
+    }
+    if !any_thinking {
+        return None;
+    }
+
+    Some(Message::Thinking {
+        redacted: thoughts.is_empty(),
+        content: thoughts.join("\n\n"),
+        meta,
+    })
+}
+
 /// Parses streaming messages from Claude based on the configured format.
 pub struct MessageParser {
     format: StreamFormat,
@@ -443,21 +515,23 @@ impl MessageParser {
         Self { format }
     }
 
-    /// Parses a single line of output into a Message, returning None if the line should be skipped.
-    pub fn parse_line(&self, line: &str) -> Result<Option<Message>> {
+    /// Parses a single line of output into Messages, returning none if the line should be skipped.
+    ///
+    /// A line holding parallel tool calls or results yields one message per call.
+    pub fn parse_line(&self, line: &str) -> Result<Vec<Message>> {
         match self.format {
             StreamFormat::Text => {
                 // Text format doesn't have structured messages
-                Ok(None)
+                Ok(Vec::new())
             }
             StreamFormat::Json | StreamFormat::StreamJson => {
                 if line.trim().is_empty() {
-                    return Ok(None);
+                    return Ok(Vec::new());
                 }
 
                 // Try direct Message deserialization first
                 match serde_json::from_str::<Message>(line) {
-                    Ok(message) => Ok(Some(message)),
+                    Ok(message) => Ok(vec![message]),
                     Err(_direct_err) => {
                         // Fallback: try CLI stream envelope format
                         match serde_json::from_str::<CliStreamEnvelope>(line) {
@@ -497,6 +571,7 @@ impl MessageParser {
                     cost_usd: None,
                     duration_ms: None,
                     tokens_used: None,
+                    model: None,
                 },
             };
             return Ok(Some(message));
@@ -518,7 +593,186 @@ impl MessageParser {
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MessageType;
+    use serde_json::json;
+
+    fn parse_all(line: serde_json::Value) -> Vec<Message> {
+        MessageParser::new(StreamFormat::StreamJson)
+            .parse_line(&line.to_string())
+            .unwrap()
+    }
+
+    fn parse(line: serde_json::Value) -> Message {
+        let mut messages = parse_all(line);
+        assert_eq!(messages.len(), 1, "expected one message, got {messages:?}");
+        messages.remove(0)
+    }
+
+    fn assistant(content: serde_json::Value) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "message": { "content": content },
+            "session_id": "session-1"
+        })
+    }
+
+    #[test]
+    fn test_thinking_block_parses_into_thinking_message() {
+        let message = parse(assistant(json!([
+            {
+                "type": "thinking",
+                "thinking": "The operator asked about the schedule.",
+                "signature": "sig"
+            }
+        ])));
+
+        assert_eq!(message.message_type(), MessageType::Thinking);
+        match message {
+            Message::Thinking {
+                content,
+                redacted,
+                meta,
+            } => {
+                assert_eq!(content, "The operator asked about the schedule.");
+                assert!(!redacted);
+                assert_eq!(meta.session_id, "session-1");
+            }
+            other => panic!("expected thinking, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_redacted_thinking_block_is_marked_redacted() {
+        let message = parse(assistant(json!([
+            { "type": "redacted_thinking", "data": "opaque" }
+        ])));
+
+        match message {
+            Message::Thinking {
+                content, redacted, ..
+            } => {
+                assert!(content.is_empty());
+                assert!(redacted);
+            }
+            other => panic!("expected thinking, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_text_wins_over_thinking_in_the_same_message() {
+        let message = parse(assistant(json!([
+            { "type": "thinking", "thinking": "hmm", "signature": "sig" },
+            { "type": "text", "text": "Hello" }
+        ])));
+
+        assert_eq!(message.message_type(), MessageType::Assistant);
+        assert_eq!(message.content(), "Hello");
+    }
+
+    #[test]
+    fn test_assistant_message_carries_model() {
+        let message = parse(json!({
+            "type": "assistant",
+            "message": {
+                "content": [{ "type": "text", "text": "Hello" }],
+                "model": "claude-sonnet-4-5"
+            },
+            "session_id": "session-1"
+        }));
+
+        assert_eq!(message.meta().model.as_deref(), Some("claude-sonnet-4-5"));
+    }
+
+    #[test]
+    fn test_tool_use_id_matches_tool_result() {
+        let tool = parse(assistant(json!([
+            { "type": "tool_use", "id": "toolu_01", "name": "WebSearch", "input": {"query": "rust"} }
+        ])));
+        let result = parse(json!({
+            "type": "user",
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_01", "content": "results" }
+            ] },
+            "session_id": "session-1"
+        }));
+
+        let Message::Tool { id, name, .. } = tool else {
+            panic!("expected tool, got {tool:?}");
+        };
+        let Message::ToolResult { tool_name, .. } = result else {
+            panic!("expected tool result, got {result:?}");
+        };
+        assert_eq!(name, "WebSearch");
+        assert_eq!(id.as_deref(), Some(tool_name.as_str()));
+    }
+
+    #[test]
+    fn test_parallel_tool_uses_each_become_a_message() {
+        let tools = parse_all(assistant(json!([
+            { "type": "tool_use", "id": "toolu_01", "name": "WebSearch", "input": {"query": "rust"} },
+            { "type": "tool_use", "id": "toolu_02", "name": "Read", "input": {"path": "notes.md"} }
+        ])));
+        let results = parse_all(json!({
+            "type": "user",
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_01", "content": "results" },
+                { "type": "tool_result", "tool_use_id": "toolu_02", "content": "notes" }
+            ] },
+            "session_id": "session-1"
+        }));
+
+        let calls: Vec<_> = tools
+            .iter()
+            .map(|m| match m {
+                Message::Tool { id, name, .. } => (id.clone().unwrap(), name.as_str()),
+                other => panic!("expected tool, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                ("toolu_01".to_string(), "WebSearch"),
+                ("toolu_02".to_string(), "Read")
+            ]
+        );
+
+        let answered: Vec<_> = results
+            .iter()
+            .map(|m| match m {
+                Message::ToolResult { tool_name, .. } => tool_name.as_str(),
+                other => panic!("expected tool result, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(answered, ["toolu_01", "toolu_02"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_thinking_between_other_messages() {
+        let (tx, rx) = mpsc::channel(8);
+        let lines = [
+            assistant(json!([
+                { "type": "thinking", "thinking": "Check the inbox first.", "signature": "s" }
+            ])),
+            assistant(json!([{ "type": "text", "text": "Checking." }])),
+        ];
+        for line in lines {
+            tx.send(Ok(line.to_string())).await.unwrap();
+        }
+        drop(tx);
+
+        let types: Vec<_> = MessageStream::from_line_stream(rx, StreamFormat::StreamJson)
+            .map(|m| m.unwrap().message_type())
+            .collect()
+            .await;
+        assert_eq!(types, [MessageType::Thinking, MessageType::Assistant]);
+    }
+}
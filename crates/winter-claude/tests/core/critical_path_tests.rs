@@ -338,6 +338,7 @@ mod message_tests {
                 output: 200,
                 total: 300,
             }),
+            model: None,
         };
 
         assert_eq!(meta.session_id, "test-session");
@@ -394,6 +395,7 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let message = Message::User {
@@ -421,6 +423,7 @@ mod message_tests {
             cost_usd: Some(0.001),
             duration_ms: Some(1200),
             tokens_used: None,
+            model: None,
         };
 
         let message = Message::Assistant {
@@ -441,10 +444,12 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let params = serde_json::json!({"query": "SELECT * FROM users"});
         let message = Message::Tool {
+            id: None,
             name: "database_query".to_string(),
             parameters: params.clone(),
             meta,
@@ -464,6 +469,7 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let result = serde_json::json!({"rows": [{"id": 1, "name": "Alice"}]});
@@ -487,6 +493,7 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let message = Message::Init { meta };
@@ -503,6 +510,7 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let stats = ConversationStats {
@@ -532,6 +540,7 @@ mod message_tests {
             cost_usd: None,
             duration_ms: None,
             tokens_used: None,
+            model: None,
         };
 
         let message = Message::System {
@@ -555,6 +564,7 @@ mod message_tests {
                 output: 20,
                 total: 30,
             }),
+            model: None,
         };
 
         let message = Message::User {
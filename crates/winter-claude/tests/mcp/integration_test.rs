@@ -77,6 +77,32 @@ fn test_legacy_config_compatibility() {
     );
 }
 
+#[test]
+fn test_legacy_config_converts_to_stdio_servers() {
+    let mut server =
+        McpServer::new("python", vec!["-m", "test_server"]).with_env("API_KEY", "test-key");
+    server.name = "test".to_string();
+
+    let config: winter_claude_core::McpConfig = McpConfig {
+        servers: vec![server],
+    }
+    .into();
+
+    assert_eq!(
+        config.to_cli_json(),
+        serde_json::json!({
+            "mcpServers": {
+                "test": {
+                    "type": "stdio",
+                    "command": "python",
+                    "args": ["-m", "test_server"],
+                    "env": {"API_KEY": "test-key"},
+                }
+            }
+        })
+    );
+}
+
 #[test]
 fn test_stdio_transport_creation() {
     let stdio_transport = TransportType::Stdio {
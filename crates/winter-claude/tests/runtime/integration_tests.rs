@@ -246,6 +246,7 @@ mod streaming_tests {
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             },
         };
 
@@ -259,6 +260,7 @@ mod streaming_tests {
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             },
             stats: winter_claude_core::ConversationStats {
                 total_messages: 1,
@@ -303,6 +305,7 @@ mod streaming_tests {
                         cost_usd: None,
                         duration_ms: None,
                         tokens_used: None,
+                        model: None,
                     },
                 };
 
@@ -351,6 +354,7 @@ mod streaming_tests {
                         cost_usd: None,
                         duration_ms: None,
                         tokens_used: None,
+                        model: None,
                     },
                 };
 
@@ -452,6 +456,7 @@ mod streaming_tests {
                         cost_usd: None,
                         duration_ms: None,
                         tokens_used: None,
+                        model: None,
                     },
                 };
 
@@ -498,6 +503,7 @@ mod streaming_tests {
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             },
         };
 
@@ -567,6 +573,7 @@ mod concurrent_request_tests {
                             cost_usd: None,
                             duration_ms: None,
                             tokens_used: None,
+                            model: None,
                         },
                     };
 
@@ -621,6 +628,7 @@ mod concurrent_request_tests {
                             cost_usd: None,
                             duration_ms: None,
                             tokens_used: None,
+                            model: None,
                         },
                     };
                     tx.send(Ok(message)).await.unwrap();
@@ -714,6 +722,7 @@ mod error_recovery_tests {
                 cost_usd: None,
                 duration_ms: None,
                 tokens_used: None,
+                model: None,
             },
         };
 
@@ -754,6 +763,7 @@ mod error_recovery_tests {
                         cost_usd: None,
                         duration_ms: None,
                         tokens_used: None,
+                        model: None,
                     },
                 };
                 tx.send(Ok(message)).await.unwrap();
@@ -815,6 +825,7 @@ mod error_recovery_tests {
                         cost_usd: None,
                         duration_ms: None,
                         tokens_used: None,
+                        model: None,
                     },
                 };
                 tx.send(Ok(message)).await.unwrap();
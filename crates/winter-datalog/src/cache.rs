@@ -7,12 +7,13 @@
 //! - Cached base program (declarations + compiled rules)
 //! - Generation counters for invalidation
 //! - Dirty predicates needing TSV regeneration
+//! - Memoized query results, invalidated when data changes
 
 use std::collections::{HashMap, HashSet};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use tokio::sync::{RwLock, broadcast};
 use tracing::{debug, info, trace, warn};
@@ -20,9 +21,16 @@ use tracing::{debug, info, trace, warn};
 use winter_atproto::{CacheUpdate, Fact, FactDeclaration, RepoCache, Rule, SyncState};
 
 use crate::dependency::{METADATA_PREDICATES, PredicateDependencyGraph, is_metadata_predicate};
-use crate::derived::DerivedFactGenerator;
+use crate::derived::{DerivedFactGenerator, TIME_DEPENDENT_PREDICATES};
 use crate::error::DatalogError;
+use crate::query_cache::{QueryCacheStats, QueryResultCache, normalize_query};
+use crate::supersession::{SupersessionIndex, refers_to, resolve_reference};
+use crate::tsv;
 use crate::validator::validate_fact_against_declaration;
+use crate::why_not::{
+    Goal, PROBE_PREDICATE, RuleDiagnosis, RuleOutcome, WhyNotReport, first_failure, probe_rules,
+    unify_head,
+};
 use crate::{RuleCompiler, SouffleExecutor};
 
 /// Cached data for a single fact.
@@ -33,6 +41,10 @@ pub struct CachedFactData {
     /// The CID of this record.
     pub cid: String,
     /// Whether this fact is superseded by another.
+    ///
+    /// Resolved from the whole fact set each time predicate files are
+    /// regenerated, since the superseding fact may arrive before or after
+    /// the one it replaces.
     pub is_superseded: bool,
 }
 
@@ -63,9 +75,6 @@ pub struct DatalogCache {
     /// CID to rkey mapping for supersession lookups.
     cid_to_rkey: RwLock<HashMap<String, String>>,
 
-    /// Set of CIDs that have been superseded.
-    superseded_cids: RwLock<HashSet<String>>,
-
     /// Cached rules (for compilation), keyed by rkey.
     rules: RwLock<HashMap<String, Rule>>,
 
@@ -82,6 +91,9 @@ pub struct DatalogCache {
     /// Rules generation counter (bumped on any rule change).
     rules_generation: AtomicU64,
 
+    /// Whether the cache has been populated from the repo cache at least once.
+    populated: AtomicBool,
+
     /// Predicates needing TSV regeneration.
     dirty_predicates: RwLock<HashSet<String>>,
 
@@ -102,6 +114,18 @@ pub struct DatalogCache {
 
     /// Derived fact generator for Bluesky/Winter record-based facts.
     derived: RwLock<DerivedFactGenerator>,
+
+    /// Whether the last derived generation has yet to be evaluated by
+    /// Soufflé, so queries only take the `derived` write lock to record the
+    /// first run after each generation.
+    souffle_pending: AtomicBool,
+
+    /// Data generation counter (bumped on any update that can change query
+    /// results, including derived-only records like follows and notes).
+    data_generation: AtomicU64,
+
+    /// Memoized query results, keyed by normalized query.
+    query_cache: Mutex<QueryResultCache>,
 }
 
 impl DatalogCache {
@@ -136,18 +160,21 @@ impl DatalogCache {
             predicate_arities: RwLock::new(HashMap::new()),
             facts_by_rkey: RwLock::new(HashMap::new()),
             cid_to_rkey: RwLock::new(HashMap::new()),
-            superseded_cids: RwLock::new(HashSet::new()),
             rules: RwLock::new(HashMap::new()),
             declarations: RwLock::new(HashMap::new()),
             declarations_by_predicate: RwLock::new(HashMap::new()),
             facts_generation: AtomicU64::new(0),
             rules_generation: AtomicU64::new(0),
+            populated: AtomicBool::new(false),
             dirty_predicates: RwLock::new(HashSet::new()),
             full_regen_needed: RwLock::new(true),
             fresh_predicates: RwLock::new(HashSet::new()),
             regen_lock: tokio::sync::Mutex::new(()),
             executor: SouffleExecutor::new(),
             derived: RwLock::new(DerivedFactGenerator::new(derived_did, derived_handle)),
+            souffle_pending: AtomicBool::new(false),
+            data_generation: AtomicU64::new(0),
+            query_cache: Mutex::new(QueryResultCache::default()),
         }))
     }
 
@@ -166,6 +193,7 @@ impl DatalogCache {
         let mut derived = self.derived.write().await;
         *derived = DerivedFactGenerator::new(did, handle);
         derived.mark_all_dirty();
+        self.bump_data_generation();
     }
 
     /// Get the fact directory path.
@@ -246,7 +274,12 @@ impl DatalogCache {
         );
 
         info!("loading facts and rules from repo cache");
-        let facts = repo_cache.list_facts();
+        // Tombstoned facts stay out of the datalog cache until restored
+        let facts: Vec<_> = repo_cache
+            .list_facts()
+            .into_iter()
+            .filter(|(_, cached)| cached.value.deleted_at.is_none())
+            .collect();
         let rules = repo_cache.list_rules();
         info!(
             facts = facts.len(),
@@ -260,23 +293,19 @@ impl DatalogCache {
             let mut facts_guard = self.facts_by_rkey.write().await;
             let mut cid_guard = self.cid_to_rkey.write().await;
             let mut arities_guard = self.predicate_arities.write().await;
-            let mut superseded_guard = self.superseded_cids.write().await;
 
             facts_guard.clear();
             cid_guard.clear();
             arities_guard.clear();
-            superseded_guard.clear();
 
-            // First pass: collect all superseded CIDs
-            for (_, cached) in &facts {
-                if let Some(ref supersedes) = cached.value.supersedes {
-                    superseded_guard.insert(supersedes.clone());
-                }
-            }
+            let index = SupersessionIndex::build(
+                facts
+                    .iter()
+                    .map(|(rkey, cached)| (rkey.as_str(), &cached.value, cached.cid.as_str())),
+            );
 
-            // Second pass: insert facts
             for (rkey, cached) in facts.clone() {
-                let is_superseded = superseded_guard.contains(&cached.cid);
+                let is_superseded = index.is_superseded(&rkey);
 
                 // Track arity
                 arities_guard
@@ -408,6 +437,9 @@ impl DatalogCache {
             let notes_list = repo_cache.list_notes();
             info!(count = notes_list.len(), "populating notes");
             for (rkey, cached) in notes_list {
+                if cached.value.deleted_at.is_some() {
+                    continue;
+                }
                 derived.handle_update(&CacheUpdate::NoteCreated {
                     rkey,
                     note: cached.value,
@@ -473,6 +505,14 @@ impl DatalogCache {
                 derived.set_followers(followers_set);
             }
 
+            // Declarations (for confidence decay)
+            for (rkey, cached) in repo_cache.list_declarations() {
+                derived.handle_update(&CacheUpdate::DeclarationCreated {
+                    rkey,
+                    declaration: cached.value,
+                });
+            }
+
             // Mark all derived predicates as needing regeneration
             derived.mark_all_dirty();
             info!("all derived facts populated and marked dirty");
@@ -525,10 +565,52 @@ impl DatalogCache {
         if let Err(e) = self.flush_dirty_predicates().await {
             warn!(error = %e, "failed to initialize lazy regen mode after population");
         }
+
+        self.bump_data_generation();
+        self.populated.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the cache has been populated from the repo cache at least once.
+    ///
+    /// Used for readiness checks: queries before initial population would
+    /// only see facts from incremental updates.
+    pub fn is_populated(&self) -> bool {
+        self.populated.load(Ordering::SeqCst)
     }
 
     /// Handle a cache update event.
     pub async fn handle_update(&self, update: CacheUpdate) -> Result<(), DatalogError> {
+        // Soft-deleting a fact or note hides it from queries just like deleting it
+        let update = match update {
+            CacheUpdate::FactCreated { rkey, fact } | CacheUpdate::FactUpdated { rkey, fact }
+                if fact.deleted_at.is_some() =>
+            {
+                CacheUpdate::FactDeleted { rkey }
+            }
+            CacheUpdate::NoteCreated { rkey, note } | CacheUpdate::NoteUpdated { rkey, note }
+                if note.deleted_at.is_some() =>
+            {
+                CacheUpdate::NoteDeleted { rkey }
+            }
+            update => update,
+        };
+
+        // Facts and declarations also feed derived predicates (fact tags,
+        // confidence decay) on top of their own handling below
+        if matches!(
+            update,
+            CacheUpdate::FactCreated { .. }
+                | CacheUpdate::FactUpdated { .. }
+                | CacheUpdate::FactDeleted { .. }
+                | CacheUpdate::DeclarationCreated { .. }
+                | CacheUpdate::DeclarationUpdated { .. }
+                | CacheUpdate::DeclarationDeleted { .. }
+        ) {
+            let mut derived = self.derived.write().await;
+            derived.handle_update(&update);
+            self.bump_data_generation();
+        }
+
         match update {
             CacheUpdate::FactCreated { rkey, fact } => {
                 self.add_fact(rkey, fact, String::new()).await;
@@ -572,6 +654,7 @@ impl DatalogCache {
                 // Forward to DerivedFactGenerator
                 let mut derived = self.derived.write().await;
                 derived.handle_update(update);
+                self.bump_data_generation();
             }
             // Bluesky records - forward to DerivedFactGenerator
             ref update @ CacheUpdate::FollowCreated { .. }
@@ -603,6 +686,7 @@ impl DatalogCache {
                 // Forward to DerivedFactGenerator
                 let mut derived = self.derived.write().await;
                 derived.handle_update(update);
+                self.bump_data_generation();
             }
             // Declaration records - store for query-time .decl generation
             CacheUpdate::DeclarationCreated { rkey, declaration }
@@ -613,6 +697,7 @@ impl DatalogCache {
                     let mut decls_by_pred = self.declarations_by_predicate.write().await;
                     decls_by_pred.insert(declaration.predicate.clone(), declaration.clone());
                     decls.insert(rkey, declaration);
+                    self.bump_data_generation();
                 }
                 // Mark for full regen since validation rules may have changed
                 *self.full_regen_needed.write().await = true;
@@ -625,6 +710,7 @@ impl DatalogCache {
                     if let Some(removed) = decls.remove(&rkey) {
                         decls_by_pred.remove(&removed.predicate);
                     }
+                    self.bump_data_generation();
                 }
                 // Mark for full regen since validation rules may have changed
                 *self.full_regen_needed.write().await = true;
@@ -635,6 +721,7 @@ impl DatalogCache {
                     state.followers.into_iter().collect();
                 let mut derived = self.derived.write().await;
                 derived.set_followers(followers_set);
+                self.bump_data_generation();
             }
         }
         Ok(())
@@ -645,19 +732,19 @@ impl DatalogCache {
         let predicate = fact.predicate.clone();
         let arity = fact.args.len();
 
-        // Check if this fact supersedes another
-        if let Some(ref supersedes_cid) = fact.supersedes {
-            let mut superseded = self.superseded_cids.write().await;
-            superseded.insert(supersedes_cid.clone());
-
-            // Mark the superseded fact
-            let cid_to_rkey = self.cid_to_rkey.read().await;
-            if let Some(old_rkey) = cid_to_rkey.get(supersedes_cid) {
-                let mut facts = self.facts_by_rkey.write().await;
-                if let Some(old_fact) = facts.get_mut(old_rkey) {
-                    old_fact.is_superseded = true;
-                }
-            }
+        // Superseding a fact changes which version of it is current, so the
+        // superseded fact's predicate needs regenerating as well as this one.
+        // Do the same for whatever the previous version of this record
+        // superseded, in case the link changed.
+        let previous = self.facts_by_rkey.read().await.get(&rkey).cloned();
+        let mut affected = self.superseded_predicates(&fact).await;
+        affected.extend(self.metadata_predicates_for(&rkey, &fact, &cid).await);
+        if let Some(previous) = previous {
+            affected.extend(self.superseded_predicates(&previous.fact).await);
+            affected.extend(
+                self.metadata_predicates_for(&rkey, &previous.fact, &previous.cid)
+                    .await,
+            );
         }
 
         // Track arity
@@ -672,13 +759,7 @@ impl DatalogCache {
             cid_map.insert(cid.clone(), rkey.clone());
         }
 
-        // Check if superseded
-        let is_superseded = {
-            let superseded = self.superseded_cids.read().await;
-            superseded.contains(&cid)
-        };
-
-        // Insert fact
+        // Insert fact (supersession is resolved at regeneration time)
         {
             let mut facts = self.facts_by_rkey.write().await;
             facts.insert(
@@ -686,15 +767,16 @@ impl DatalogCache {
                 CachedFactData {
                     fact,
                     cid,
-                    is_superseded,
+                    is_superseded: false,
                 },
             );
         }
 
-        // Mark predicate as dirty
+        // Mark predicate and affected predicates as dirty
         {
             let mut dirty = self.dirty_predicates.write().await;
             dirty.insert(predicate);
+            dirty.extend(affected);
         }
 
         self.facts_generation.fetch_add(1, Ordering::SeqCst);
@@ -703,26 +785,100 @@ impl DatalogCache {
 
     /// Remove a fact.
     async fn remove_fact(&self, rkey: &str) {
-        let predicate = {
+        // A removed fact no longer supersedes anything, so its predecessor may
+        // become current again
+        let existing = self
+            .facts_by_rkey
+            .read()
+            .await
+            .get(rkey)
+            .map(|data| data.fact.clone());
+        let affected = match existing {
+            Some(fact) => self.superseded_predicates(&fact).await,
+            None => Vec::new(),
+        };
+
+        let removed = {
             let mut facts = self.facts_by_rkey.write().await;
-            if let Some(removed) = facts.remove(rkey) {
+            let removed = facts.remove(rkey);
+            if let Some(ref removed) = removed {
                 // Remove from CID map
                 let mut cid_map = self.cid_to_rkey.write().await;
                 cid_map.remove(&removed.cid);
-                Some(removed.fact.predicate)
-            } else {
-                None
             }
+            removed
         };
 
-        if let Some(pred) = predicate {
+        if let Some(removed) = removed {
+            let metadata = self
+                .metadata_predicates_for(rkey, &removed.fact, &removed.cid)
+                .await;
             let mut dirty = self.dirty_predicates.write().await;
-            dirty.insert(pred);
+            dirty.insert(removed.fact.predicate);
+            dirty.extend(affected);
+            dirty.extend(metadata);
             self.facts_generation.fetch_add(1, Ordering::SeqCst);
             trace!(rkey, "fact removed, generation bumped");
         }
     }
 
+    /// Metadata predicates whose files have a row for `fact`, or a
+    /// supersession link to it from another cached fact.
+    ///
+    /// `_validation_error` is only included when the fact's predicate has a
+    /// declaration to validate against.
+    async fn metadata_predicates_for(&self, rkey: &str, fact: &Fact, cid: &str) -> Vec<String> {
+        let mut predicates = vec!["_fact", "_created_at"];
+        if fact
+            .confidence
+            .is_some_and(|conf| (conf - 1.0).abs() > f64::EPSILON)
+        {
+            predicates.push("_confidence");
+        }
+        if fact.source.is_some() {
+            predicates.push("_source");
+        }
+        if fact.expires_at.is_some() {
+            predicates.push("_expires_at");
+        }
+        let superseded = self.facts_by_rkey.read().await.values().any(|other| {
+            other
+                .fact
+                .supersedes
+                .as_deref()
+                .is_some_and(|reference| refers_to(reference, rkey, cid))
+        });
+        if fact.supersedes.is_some() || superseded {
+            predicates.push("_supersedes");
+        }
+        if self
+            .declarations_by_predicate
+            .read()
+            .await
+            .contains_key(&fact.predicate)
+        {
+            predicates.push("_validation_error");
+        }
+        predicates.into_iter().map(str::to_string).collect()
+    }
+
+    /// Predicates of the facts that `fact` supersedes, if any are cached.
+    async fn superseded_predicates(&self, fact: &Fact) -> Vec<String> {
+        let Some(reference) = fact.supersedes.as_deref() else {
+            return Vec::new();
+        };
+        let facts = self.facts_by_rkey.read().await;
+        let cid_map = self.cid_to_rkey.read().await;
+        resolve_reference(
+            reference,
+            |cid| cid_map.get(cid).map(String::as_str),
+            |r| facts.contains_key(r),
+        )
+        .and_then(|old_rkey| facts.get(old_rkey))
+        .map(|old| vec![old.fact.predicate.clone()])
+        .unwrap_or_default()
+    }
+
     /// Add a rule.
     async fn add_rule(&self, rkey: String, rule: Rule) {
         let mut rules = self.rules.write().await;
@@ -800,6 +956,25 @@ impl DatalogCache {
         extra_rules: Option<&str>,
         extra_facts: Option<&[String]>,
         extra_declarations: Option<&[String]>,
+    ) -> Result<Vec<Vec<String>>, DatalogError> {
+        self.execute_query_with_options(query, extra_rules, extra_facts, extra_declarations, true)
+            .await
+    }
+
+    /// Execute a query, choosing whether user predicates see only the head of
+    /// each supersession chain.
+    ///
+    /// With `latest_only` set, `predicate(...)` contains only current facts
+    /// (neither superseded nor expired). Without it, `predicate(...)` reads
+    /// every version, the same as `_all_predicate(...)`, so stored rules can
+    /// run over history.
+    pub async fn execute_query_with_options(
+        &self,
+        query: &str,
+        extra_rules: Option<&str>,
+        extra_facts: Option<&[String]>,
+        extra_declarations: Option<&[String]>,
+        latest_only: bool,
     ) -> Result<Vec<Vec<String>>, DatalogError> {
         // Flush dirty predicates (marks stale, doesn't regenerate)
         self.flush_dirty_predicates().await?;
@@ -828,6 +1003,32 @@ impl DatalogCache {
         // Get transitive closure of required predicates
         let required_predicates = dep_graph.get_required_predicates(&root_predicates);
 
+        // Serve repeated queries from memory unless they read the clock
+        let version = self.data_version();
+        let cache_key = required_predicates
+            .iter()
+            .all(|p| !is_time_dependent(p))
+            .then(|| {
+                query_cache_key(
+                    query,
+                    extra_rules,
+                    extra_facts,
+                    extra_declarations,
+                    latest_only,
+                )
+            });
+        if let Some(key) = &cache_key {
+            let cached = self
+                .query_cache
+                .lock()
+                .expect("query cache lock poisoned")
+                .get(key, version);
+            if let Some(rows) = cached {
+                debug!(query = %query, rows = rows.len(), "query result cache hit");
+                return Ok(rows);
+            }
+        }
+
         debug!(
             query = %query,
             root_predicates = root_predicates.len(),
@@ -907,6 +1108,7 @@ impl DatalogCache {
                 &required_predicates,
                 &user_declared,
                 &predicate_types,
+                latest_only,
             )
             .await?;
 
@@ -1078,6 +1280,9 @@ impl DatalogCache {
 
         // Execute
         let output = self.executor.execute(&program, &self.fact_dir).await?;
+        if self.souffle_pending.swap(false, Ordering::SeqCst) {
+            self.derived.write().await.mark_souffle_ran();
+        }
 
         // Parse results
         let results = SouffleExecutor::parse_output(&output);
@@ -1092,6 +1297,13 @@ impl DatalogCache {
             );
         }
 
+        if let Some(key) = cache_key {
+            self.query_cache
+                .lock()
+                .expect("query cache lock poisoned")
+                .insert(key, version, results.clone());
+        }
+
         Ok(results)
     }
 
@@ -1265,10 +1477,15 @@ impl DatalogCache {
             );
         }
 
-        // Mark all requested predicates as fresh
+        // Mark all requested predicates as fresh, except time-dependent ones,
+        // which must be recomputed for every query
         {
             let mut fresh = self.fresh_predicates.write().await;
-            fresh.extend(stale);
+            fresh.extend(
+                stale
+                    .into_iter()
+                    .filter(|p| !TIME_DEPENDENT_PREDICATES.contains(&p.as_str())),
+            );
         }
 
         // Clear dirty state for derived predicates we regenerated
@@ -1293,29 +1510,30 @@ impl DatalogCache {
         include_metadata: bool,
     ) -> Result<(), DatalogError> {
         // Collect snapshots
-        let (facts_snapshot, arities_snapshot, decls_snapshot, cid_map_snapshot) = {
+        let (mut facts_snapshot, arities_snapshot, decls_snapshot) = {
             let facts = self.facts_by_rkey.read().await;
             let arities = self.predicate_arities.read().await;
             let decls_by_pred = self.declarations_by_predicate.read().await;
-            let cid_to_rkey = self.cid_to_rkey.read().await;
-            (
-                facts.clone(),
-                arities.clone(),
-                decls_by_pred.clone(),
-                cid_to_rkey.clone(),
-            )
+            (facts.clone(), arities.clone(), decls_by_pred.clone())
         };
 
+        // Resolve supersession over the whole snapshot so chains are correct
+        // regardless of the order facts arrived in
+        let index = SupersessionIndex::build(
+            facts_snapshot
+                .iter()
+                .map(|(rkey, data)| (rkey.as_str(), &data.fact, data.cid.as_str())),
+        );
+        for (rkey, data) in facts_snapshot.iter_mut() {
+            data.is_superseded = index.is_superseded(rkey);
+        }
+
         // Run sync file I/O on a blocking thread to avoid starving the async executor
         let fact_dir = self.fact_dir.clone();
         let predicates = predicates.clone();
         tokio::task::spawn_blocking(move || -> Result<(), DatalogError> {
             if include_metadata {
-                DatalogCache::write_metadata_files_inner(
-                    &fact_dir,
-                    &facts_snapshot,
-                    &cid_map_snapshot,
-                )?;
+                DatalogCache::write_metadata_files_inner(&fact_dir, &facts_snapshot, &index)?;
             }
 
             for predicate in &predicates {
@@ -1353,6 +1571,7 @@ impl DatalogCache {
         &self,
         predicates: &HashSet<String>,
     ) -> Result<(), DatalogError> {
+        let start = std::time::Instant::now();
         let derived_snapshot = {
             let derived = self.derived.read().await;
             derived.clone_for_flush()
@@ -1360,12 +1579,25 @@ impl DatalogCache {
 
         let fact_dir = self.fact_dir.clone();
         let predicates = predicates.clone();
-        tokio::task::spawn_blocking(move || {
+        let rows = tokio::task::spawn_blocking(move || {
             derived_snapshot.write_predicates_subset(&fact_dir, &predicates)
         })
         .await
         .map_err(|e| DatalogError::Internal(format!("spawn_blocking panicked: {}", e)))??;
 
+        let stats = {
+            let mut derived = self.derived.write().await;
+            self.souffle_pending.store(true, Ordering::SeqCst);
+            derived.record_generation(rows, start.elapsed())
+        };
+        debug!(
+            predicates = stats.predicates.len(),
+            added = stats.total_added(),
+            removed = stats.total_removed(),
+            duration_ms = stats.duration_ms,
+            "regenerated derived predicates"
+        );
+
         Ok(())
     }
 
@@ -1373,7 +1605,7 @@ impl DatalogCache {
     fn write_metadata_files_inner(
         fact_dir: &Path,
         facts: &HashMap<String, CachedFactData>,
-        cid_map: &HashMap<String, String>,
+        supersession: &SupersessionIndex,
     ) -> Result<(), DatalogError> {
         let mut fact_file =
             BufWriter::new(std::fs::File::create(fact_dir.join("_fact.facts"))?);
@@ -1404,13 +1636,7 @@ impl DatalogCache {
             }
 
             if let Some(ref source) = data.fact.source {
-                writeln!(source_file, "{}\t{}", rkey, source)?;
-            }
-
-            if let Some(ref supersedes_cid) = data.fact.supersedes
-                && let Some(old_rkey) = cid_map.get(supersedes_cid)
-            {
-                writeln!(supersedes_file, "{}\t{}", rkey, old_rkey)?;
+                writeln!(source_file, "{}", tsv::format_row(&[rkey, source]))?;
             }
 
             writeln!(
@@ -1425,6 +1651,10 @@ impl DatalogCache {
             }
         }
 
+        for (new_rkey, old_rkey) in supersession.links() {
+            writeln!(supersedes_file, "{}\t{}", new_rkey, old_rkey)?;
+        }
+
         Ok(())
     }
 
@@ -1488,18 +1718,16 @@ impl DatalogCache {
                     "skipping fact due to schema validation failure"
                 );
                 // Write to validation errors file for investigation
-                writeln!(errors_file, "{}\t{}\t{}", rkey, data.fact.predicate, error)?;
+                writeln!(
+                    errors_file,
+                    "{}",
+                    tsv::format_row(&[rkey, &data.fact.predicate, &error.to_string()])
+                )?;
                 continue; // Skip writing to TSV
             }
 
-            // Escape tabs and newlines in arguments to prevent TSV corruption
-            let args: Vec<String> = data
-                .fact
-                .args
-                .iter()
-                .map(|a| a.replace(['\t', '\n'], " "))
-                .collect();
-            let args_str = args.join("\t");
+            // Escape tabs, newlines, and backslashes so columns stay aligned
+            let args_str = tsv::format_row(&data.fact.args);
 
             // Write to all file (always, rkey at end)
             writeln!(all_file, "{}\t{}", args_str, rkey)?;
@@ -1522,11 +1750,15 @@ impl DatalogCache {
     ///
     /// The `exclude_predicates` set contains predicates that should NOT be declared
     /// (e.g., because the caller will provide their own .decl statements via extra_rules).
+    ///
+    /// When `latest_only` is false, user predicates are read from their `_all_`
+    /// files so superseded versions are visible.
     async fn generate_program_for_predicates(
         &self,
         required_predicates: &HashSet<String>,
         exclude_predicates: &HashSet<String>,
         predicate_types: &HashMap<String, Vec<String>>,
+        latest_only: bool,
     ) -> Result<(String, HashSet<String>), DatalogError> {
         let mut program = String::new();
         let mut declared_predicates = HashSet::new();
@@ -1591,11 +1823,16 @@ impl DatalogCache {
                     .chain(std::iter::once("rkey: symbol".to_string()))
                     .collect()
             };
+            let input = if latest_only {
+                predicate.clone()
+            } else {
+                format!("{}(filename=\"_all_{}.facts\")", predicate, predicate)
+            };
             program.push_str(&format!(
                 ".decl {}({})\n.input {}\n\n",
                 predicate,
                 params.join(", "),
-                predicate
+                input
             ));
             declared_predicates.insert(predicate.clone());
 
@@ -1700,8 +1937,14 @@ impl DatalogCache {
     }
 
     /// Get mutable access to the derived fact generator (for follower sync).
-    pub async fn derived_mut(&self) -> tokio::sync::RwLockWriteGuard<'_, DerivedFactGenerator> {
-        self.derived.write().await
+    ///
+    /// Cached query results are invalidated when the guard is dropped, after
+    /// the caller's changes are in place.
+    pub async fn derived_mut(&self) -> DerivedWriteGuard<'_> {
+        DerivedWriteGuard {
+            guard: self.derived.write().await,
+            cache: self,
+        }
     }
 
     /// Update the followers set from an external sync.
@@ -1710,6 +1953,7 @@ impl DatalogCache {
     pub async fn set_followers(&self, followers: HashSet<String>) {
         let mut derived = self.derived.write().await;
         derived.set_followers(followers);
+        self.bump_data_generation();
     }
 
     /// Add a single follower (from Follow notification).
@@ -1717,7 +1961,37 @@ impl DatalogCache {
     /// Returns true if this was a new follower.
     pub async fn add_follower(&self, did: String) -> bool {
         let mut derived = self.derived.write().await;
-        derived.add_follower(did)
+        let added = derived.add_follower(did);
+        if added {
+            self.bump_data_generation();
+        }
+        added
+    }
+
+    /// Monotonic version of all data a query can observe.
+    ///
+    /// Changes whenever facts, rules, or derived records change.
+    pub fn data_version(&self) -> u64 {
+        self.facts_generation()
+            + self.rules_generation()
+            + self.data_generation.load(Ordering::SeqCst)
+    }
+
+    /// Hit/miss counters for the query result cache.
+    pub fn query_cache_stats(&self) -> QueryCacheStats {
+        self.query_cache
+            .lock()
+            .expect("query cache lock poisoned")
+            .stats()
+    }
+
+    /// Bump the data generation and drop memoized query results.
+    fn bump_data_generation(&self) {
+        self.data_generation.fetch_add(1, Ordering::SeqCst);
+        self.query_cache
+            .lock()
+            .expect("query cache lock poisoned")
+            .invalidate();
     }
 
     /// Get the current facts generation counter.
@@ -1739,6 +2013,163 @@ impl DatalogCache {
     pub async fn rule_count(&self) -> usize {
         self.rules.read().await.len()
     }
+
+    /// Explain why `goal` (an atom such as `mutual("did:a", "did:b")`) is
+    /// not derived.
+    ///
+    /// Every stored rule with the goal's predicate as head is diagnosed:
+    /// whether its head unifies with the goal and, if so, which body literal
+    /// first has no solution, by probing each prefix of its body.
+    pub async fn why_not(&self, goal: &str) -> Result<WhyNotReport, DatalogError> {
+        let parsed = Goal::parse(goal).map_err(DatalogError::Parse)?;
+        let present = !self.execute_query(goal, None).await?.is_empty();
+
+        let mut candidates: Vec<Rule> = self
+            .rules
+            .read()
+            .await
+            .values()
+            .filter(|rule| {
+                extract_rule_head_predicate(&rule.head).as_deref() == Some(parsed.name.as_str())
+            })
+            .cloned()
+            .collect();
+        candidates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut rules = Vec::with_capacity(candidates.len());
+        for rule in candidates {
+            let outcome = self.probe_rule(&rule, &parsed).await;
+            rules.push(RuleDiagnosis {
+                rule: rule.name,
+                head: rule.head,
+                outcome,
+            });
+        }
+        rules.sort_by_key(|d| std::cmp::Reverse(d.outcome.progress()));
+
+        Ok(WhyNotReport {
+            goal: goal.to_string(),
+            present,
+            rules,
+        })
+    }
+
+    /// Find the first literal of `rule` with no solution for `goal`.
+    async fn probe_rule(&self, rule: &Rule, goal: &Goal) -> RuleOutcome {
+        if !rule.enabled {
+            return RuleOutcome::Disabled;
+        }
+        let bindings = match unify_head(&rule.head, goal) {
+            Ok(bindings) => bindings,
+            Err(reason) => return RuleOutcome::HeadMismatch { reason },
+        };
+        let literals = match RuleCompiler::compile_body(rule) {
+            Ok(literals) => literals,
+            Err(e) => {
+                return RuleOutcome::Error {
+                    message: e.to_string(),
+                };
+            }
+        };
+
+        let probes = probe_rules(&bindings, &literals);
+        let query = format!("{}(K)", PROBE_PREDICATE);
+        match self.execute_query(&query, Some(&probes)).await {
+            Ok(rows) => match first_failure(literals.len(), &rows) {
+                Some(index) => RuleOutcome::FailedAt {
+                    index,
+                    literal: rule
+                        .body
+                        .iter()
+                        .chain(&rule.constraints)
+                        .nth(index)
+                        .cloned()
+                        .unwrap_or_default(),
+                },
+                None => RuleOutcome::Satisfied,
+            },
+            Err(e) => RuleOutcome::Error {
+                message: e.to_string(),
+            },
+        }
+    }
+
+    /// Arity of every predicate a query can reference, keyed by name.
+    ///
+    /// Covers metadata relations, derived predicates, user predicates and
+    /// their `_all_` variants (including the trailing rkey), declared
+    /// predicates that have no facts yet, and the heads of enabled rules.
+    /// Ad-hoc rules and declarations passed with a query are not included.
+    pub async fn predicate_signatures(&self) -> HashMap<String, usize> {
+        let mut signatures: HashMap<String, usize> = [
+            ("_fact", 3),
+            ("_confidence", 2),
+            ("_source", 2),
+            ("_supersedes", 2),
+            ("_created_at", 2),
+            ("_expires_at", 2),
+            ("_validation_error", 3),
+            ("_now", 1),
+            ("_expired", 1),
+        ]
+        .into_iter()
+        .map(|(name, arity)| (name.to_string(), arity))
+        .collect();
+
+        for (name, arity) in DerivedFactGenerator::arities() {
+            signatures.insert(name.to_string(), arity);
+        }
+
+        for rule in self.rules.read().await.values() {
+            if rule.enabled
+                && let Some((name, arity)) = extract_rule_head_with_arity(&rule.head)
+            {
+                signatures.entry(name).or_insert(arity);
+            }
+        }
+
+        // Declared predicates without facts are declared from their args alone
+        for (name, decl) in self.declarations_by_predicate.read().await.iter() {
+            signatures.insert(name.clone(), decl.args.len());
+        }
+
+        for (name, &arity) in self.predicate_arities.read().await.iter() {
+            signatures.insert(name.clone(), arity + 1);
+            signatures.insert(format!("_all_{}", name), arity + 1);
+        }
+
+        signatures
+    }
+}
+
+/// Write access to a cache's [`DerivedFactGenerator`].
+///
+/// Invalidates memoized query results on drop, while the write lock is still
+/// held, so no query can cache results computed from the old state under the
+/// new data version.
+pub struct DerivedWriteGuard<'a> {
+    guard: tokio::sync::RwLockWriteGuard<'a, DerivedFactGenerator>,
+    cache: &'a DatalogCache,
+}
+
+impl std::ops::Deref for DerivedWriteGuard<'_> {
+    type Target = DerivedFactGenerator;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl std::ops::DerefMut for DerivedWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl Drop for DerivedWriteGuard<'_> {
+    fn drop(&mut self) {
+        self.cache.bump_data_generation();
+    }
 }
 
 /// Generate input declarations from a map of predicate arities.
@@ -1846,6 +2277,38 @@ impl ParsedQuery {
     }
 }
 
+/// Whether a predicate's contents depend on the current time.
+fn is_time_dependent(predicate: &str) -> bool {
+    TIME_DEPENDENT_PREDICATES.contains(&predicate) || matches!(predicate, "_now" | "_expired")
+}
+
+/// Build the query result cache key for a query and its ad-hoc inputs.
+fn query_cache_key(
+    query: &str,
+    extra_rules: Option<&str>,
+    extra_facts: Option<&[String]>,
+    extra_declarations: Option<&[String]>,
+    latest_only: bool,
+) -> String {
+    let mut key = normalize_query(query);
+    if let Some(rules) = extra_rules {
+        key.push_str("\n#rules ");
+        key.push_str(&normalize_query(rules));
+    }
+    for fact in extra_facts.unwrap_or_default() {
+        key.push_str("\n#fact ");
+        key.push_str(&normalize_query(fact));
+    }
+    for decl in extra_declarations.unwrap_or_default() {
+        key.push_str("\n#decl ");
+        key.push_str(&normalize_query(decl));
+    }
+    if !latest_only {
+        key.push_str("\n#all_versions");
+    }
+    key
+}
+
 /// Parse a query to extract predicate name and typed arguments.
 fn parse_query(query: &str) -> Option<ParsedQuery> {
     let paren_idx = query.find('(')?;
@@ -1999,14 +2462,21 @@ fn parse_extra_facts(facts: &[String]) -> Vec<(String, usize)> {
     result
 }
 
-/// Count the number of arguments in an argument string, handling quoted strings.
+/// Count the number of arguments in an argument string, handling quoted
+/// strings and backslash escapes within them.
 fn count_args(args_str: &str) -> usize {
     let mut count = 1; // At least one arg if non-empty
     let mut in_string = false;
+    let mut escaped = false;
     let mut depth = 0;
 
     for c in args_str.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
         match c {
+            '\\' if in_string => escaped = true,
             '"' if depth == 0 => in_string = !in_string,
             '(' if !in_string => depth += 1,
             ')' if !in_string => depth -= 1,
@@ -2198,6 +2668,7 @@ mod tests {
             tags: vec![],
             created_at: Utc::now(),
             expires_at: None,
+            deleted_at: None,
         }
     }
 
@@ -2361,6 +2832,18 @@ mod tests {
         assert!(parse_declaration_arg_types("no_parens").is_none());
     }
 
+    #[test]
+    fn test_parse_extra_facts_with_escaped_strings() {
+        let facts = vec![
+            r#"said("a", "she said \"hi, there\"")."#.to_string(),
+            r#"path("C:\\dir", "x,y")"#.to_string(),
+        ];
+        assert_eq!(
+            parse_extra_facts(&facts),
+            vec![("said".to_string(), 2), ("path".to_string(), 2)]
+        );
+    }
+
     #[test]
     fn test_generate_input_declarations_from_arities() {
         let mut arities = HashMap::new();
@@ -2402,6 +2885,39 @@ mod tests {
         assert_eq!(cache.facts_generation(), 1);
     }
 
+    #[tokio::test]
+    async fn test_soft_deleted_fact_is_removed_and_restorable() {
+        let cache = DatalogCache::new_temp().unwrap();
+        let fact = make_fact("follows", vec!["did:a", "did:b"]);
+        cache
+            .handle_update(CacheUpdate::FactCreated {
+                rkey: "rkey1".to_string(),
+                fact: fact.clone(),
+            })
+            .await
+            .unwrap();
+
+        let mut tombstoned = fact.clone();
+        tombstoned.deleted_at = Some(Utc::now());
+        cache
+            .handle_update(CacheUpdate::FactUpdated {
+                rkey: "rkey1".to_string(),
+                fact: tombstoned,
+            })
+            .await
+            .unwrap();
+        assert_eq!(cache.fact_count().await, 0);
+
+        cache
+            .handle_update(CacheUpdate::FactUpdated {
+                rkey: "rkey1".to_string(),
+                fact,
+            })
+            .await
+            .unwrap();
+        assert_eq!(cache.fact_count().await, 1);
+    }
+
     #[tokio::test]
     async fn test_datalog_cache_dirty_tracking() {
         let cache = DatalogCache::new_temp().unwrap();
@@ -2428,6 +2944,55 @@ mod tests {
         assert!(dirty.contains("interested_in"));
     }
 
+    #[tokio::test]
+    async fn test_dirty_tracking_only_touched_metadata() {
+        let cache = DatalogCache::new_temp().unwrap();
+        async fn take_dirty(cache: &DatalogCache) -> HashSet<String> {
+            std::mem::take(&mut *cache.dirty_predicates.write().await)
+        }
+
+        let mut v2 = make_fact("mood", vec!["curious"]);
+        v2.source = Some("did:plc:test".to_string());
+        v2.supersedes = Some("at://did:plc:test/diy.razorgirl.winter.fact/v1".to_string());
+        cache
+            .add_fact("v2".to_string(), v2, "cid2".to_string())
+            .await;
+
+        let dirty = take_dirty(&cache).await;
+        let expected: HashSet<String> = ["mood", "_fact", "_created_at", "_source", "_supersedes"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(dirty, expected);
+
+        // v1 has no metadata of its own beyond the basics, but v2's link to it
+        // only resolves once it arrives
+        let v1 = make_fact("mood", vec!["calm"]);
+        cache
+            .add_fact("v1".to_string(), v1, "cid1".to_string())
+            .await;
+
+        let dirty = take_dirty(&cache).await;
+        let expected: HashSet<String> = ["mood", "_fact", "_created_at", "_supersedes"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(dirty, expected);
+
+        let plain = make_fact("follows", vec!["did:a", "did:b"]);
+        cache
+            .add_fact("f1".to_string(), plain, "cid3".to_string())
+            .await;
+        cache.remove_fact("f1").await;
+
+        let dirty = take_dirty(&cache).await;
+        let expected: HashSet<String> = ["follows", "_fact", "_created_at"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(dirty, expected);
+    }
+
     #[tokio::test]
     async fn test_extra_rules_constant_filtering() {
         // This test demonstrates the bug: constant arguments in ad-hoc rules
@@ -2802,6 +3367,7 @@ test_result(Uri) :- thread_depth(Uri, D), D > "5", reply_cnt(Uri, C), C > "3"."#
             ],
             description: "Test predicate".to_string(),
             tags: vec![],
+            decay: None,
             created_at: Utc::now(),
             last_updated: None,
         };
@@ -2873,6 +3439,7 @@ test_result(Uri) :- thread_depth(Uri, D), D > "5", reply_cnt(Uri, C), C > "3"."#
             ],
             description: "Test predicate".to_string(),
             tags: vec![],
+            decay: None,
             created_at: Utc::now(),
             last_updated: None,
         };
@@ -2994,6 +3561,7 @@ test_result(Uri) :- thread_depth(Uri, D), D > "5", reply_cnt(Uri, C), C > "3"."#
             ],
             description: "Test predicate".to_string(),
             tags: vec![],
+            decay: None,
             created_at: Utc::now(),
             last_updated: None,
         };
@@ -3030,4 +3598,300 @@ test_result(Uri) :- thread_depth(Uri, D), D > "5", reply_cnt(Uri, C), C > "3"."#
             "error message should describe the issue"
         );
     }
+
+    #[tokio::test]
+    async fn test_is_populated_after_repo_cache_population() {
+        let cache = DatalogCache::new_temp().unwrap();
+        let repo_cache = RepoCache::new();
+        assert!(!cache.is_populated());
+
+        cache.populate_from_repo_cache(&repo_cache).await;
+        assert!(cache.is_populated());
+    }
+
+    #[tokio::test]
+    async fn test_stored_rule_with_count_aggregate() {
+        let cache = DatalogCache::new_temp().unwrap();
+
+        let posts = [
+            ("alice", "p1"),
+            ("alice", "p2"),
+            ("alice", "p3"),
+            ("bob", "p4"),
+        ];
+        for (i, (author, post)) in posts.into_iter().enumerate() {
+            cache
+                .add_fact(
+                    format!("rkey{}", i),
+                    make_fact("authored", vec![author, post]),
+                    format!("cid{}", i),
+                )
+                .await;
+        }
+
+        cache
+            .add_rule(
+                "prolific".to_string(),
+                Rule {
+                    name: "prolific".to_string(),
+                    description: "authors with more than two posts".to_string(),
+                    head: "prolific(User)".to_string(),
+                    body: vec![
+                        "authored(User, _, _)".to_string(),
+                        "count : authored(User, _, _) > 2".to_string(),
+                    ],
+                    constraints: vec![],
+                    enabled: true,
+                    priority: 0,
+                    args: vec![],
+                    created_at: Utc::now(),
+                },
+            )
+            .await;
+
+        let result = cache.execute_query("prolific(U)", None).await.unwrap();
+        assert_eq!(result, vec![vec!["alice".to_string()]]);
+    }
+
+    /// Add a three-deep chain v1 <- v2 <- v3 the way live updates arrive:
+    /// newest first, without CIDs, linked by AT URI.
+    async fn add_mood_chain(cache: &DatalogCache) {
+        let mut v3 = make_fact("mood", vec!["focused"]);
+        v3.supersedes = Some("at://did:plc:test/diy.razorgirl.winter.fact/v2".to_string());
+        let mut v2 = make_fact("mood", vec!["curious"]);
+        v2.supersedes = Some("at://did:plc:test/diy.razorgirl.winter.fact/v1".to_string());
+        let v1 = make_fact("mood", vec!["calm"]);
+
+        cache.add_fact("v3".to_string(), v3, String::new()).await;
+        cache.add_fact("v2".to_string(), v2, String::new()).await;
+        cache.add_fact("v1".to_string(), v1, String::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_supersession_chain_files() {
+        let cache = DatalogCache::new_temp().unwrap();
+        add_mood_chain(&cache).await;
+
+        cache.flush_dirty_predicates().await.unwrap();
+        let predicates: HashSet<String> = ["mood".to_string(), "_supersedes".to_string()]
+            .into_iter()
+            .collect();
+        cache.ensure_predicates_exist(&predicates).await.unwrap();
+
+        let current = std::fs::read_to_string(cache.fact_dir.join("mood.facts")).unwrap();
+        assert_eq!(current, "focused\tv3\n");
+
+        let all = std::fs::read_to_string(cache.fact_dir.join("_all_mood.facts")).unwrap();
+        assert_eq!(all.lines().count(), 3);
+
+        let supersedes = std::fs::read_to_string(cache.fact_dir.join("_supersedes.facts")).unwrap();
+        assert!(supersedes.contains("v3\tv2"));
+        assert!(supersedes.contains("v2\tv1"));
+    }
+
+    #[tokio::test]
+    async fn test_query_latest_only() {
+        let cache = DatalogCache::new_temp().unwrap();
+        add_mood_chain(&cache).await;
+
+        let latest = cache
+            .execute_query_with_options("mood(M, _)", None, None, None, true)
+            .await
+            .unwrap();
+        assert_eq!(latest, vec![vec!["focused".to_string()]]);
+
+        let all = cache
+            .execute_query_with_options("mood(M, _)", None, None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_hit_on_repeated_query() {
+        let cache = DatalogCache::new_temp().unwrap();
+        cache
+            .add_fact(
+                "r1".to_string(),
+                make_fact("likes", vec!["alice", "tea"]),
+                "c1".to_string(),
+            )
+            .await;
+
+        let first = cache.execute_query("likes(X, Y)", None).await.unwrap();
+        // Whitespace differences normalize to the same entry
+        let second = cache
+            .execute_query("  likes(X,\n    Y)", None)
+            .await
+            .unwrap();
+        let third = cache.execute_query("likes(X, Y)", None).await.unwrap();
+
+        assert_eq!(first, vec![vec!["alice".to_string(), "tea".to_string()]]);
+        assert_eq!(first, second);
+        assert_eq!(first, third);
+        let stats = cache.query_cache_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_invalidated_by_fact_change() {
+        let cache = DatalogCache::new_temp().unwrap();
+        cache
+            .handle_update(CacheUpdate::FactCreated {
+                rkey: "r1".to_string(),
+                fact: make_fact("likes", vec!["alice", "tea"]),
+            })
+            .await
+            .unwrap();
+
+        let before = cache.execute_query("likes(X, _)", None).await.unwrap();
+        assert_eq!(before, vec![vec!["alice".to_string()]]);
+
+        cache
+            .handle_update(CacheUpdate::FactCreated {
+                rkey: "r2".to_string(),
+                fact: make_fact("likes", vec!["bob", "coffee"]),
+            })
+            .await
+            .unwrap();
+        assert_eq!(cache.query_cache_stats().entries, 0);
+
+        let mut after = cache.execute_query("likes(X, _)", None).await.unwrap();
+        after.sort();
+        assert_eq!(
+            after,
+            vec![vec!["alice".to_string()], vec!["bob".to_string()]]
+        );
+
+        let stats = cache.query_cache_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 2);
+        assert!(stats.invalidations >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_roundtrips_special_characters() {
+        let cache = DatalogCache::new_temp().unwrap();
+        let args = vec!["tab\there", "line\nbreak", "back\\slash \\t"];
+        cache
+            .add_fact(
+                "r1".to_string(),
+                make_fact("quirky", args.clone()),
+                "c1".to_string(),
+            )
+            .await;
+
+        let result = cache
+            .execute_query("quirky(A, B, C, R)", None)
+            .await
+            .unwrap();
+        assert_eq!(result, vec![vec![args[0], args[1], args[2], "r1"]]);
+
+        // Columns stay aligned in the TSV file
+        let content = std::fs::read_to_string(cache.fact_dir().join("quirky.facts")).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert_eq!(content.trim_end().split('\t').count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_predicate_signatures() {
+        let cache = DatalogCache::new_temp().unwrap();
+        cache
+            .add_fact(
+                "r1".to_string(),
+                make_fact("authored", vec!["alice", "p1"]),
+                "cid1".to_string(),
+            )
+            .await;
+        cache
+            .add_rule(
+                "prolific".to_string(),
+                Rule {
+                    name: "prolific".to_string(),
+                    description: String::new(),
+                    head: "prolific(User)".to_string(),
+                    body: vec!["authored(User, _, _)".to_string()],
+                    constraints: vec![],
+                    enabled: true,
+                    priority: 0,
+                    args: vec![],
+                    created_at: Utc::now(),
+                },
+            )
+            .await;
+
+        let signatures = cache.predicate_signatures().await;
+        assert_eq!(signatures["authored"], 3);
+        assert_eq!(signatures["_all_authored"], 3);
+        assert_eq!(signatures["prolific"], 1);
+        assert_eq!(signatures["_confidence"], 2);
+        assert_eq!(
+            signatures["follows"],
+            DerivedFactGenerator::arities()["follows"]
+        );
+        assert!(!signatures.contains_key("_all_prolific"));
+    }
+
+    #[tokio::test]
+    async fn test_why_not_finds_unsatisfied_body_atom() {
+        let cache = DatalogCache::new_temp().unwrap();
+        cache
+            .add_fact(
+                "r1".to_string(),
+                make_fact("likes", vec!["alice", "bob"]),
+                "cid1".to_string(),
+            )
+            .await;
+        cache
+            .add_rule(
+                "mutual".to_string(),
+                Rule {
+                    name: "mutual".to_string(),
+                    description: "people who like each other".to_string(),
+                    head: "mutual(X, Y)".to_string(),
+                    body: vec!["likes(X, Y, _)".to_string(), "likes(Y, X, _)".to_string()],
+                    constraints: vec![],
+                    enabled: true,
+                    priority: 0,
+                    args: vec![],
+                    created_at: Utc::now(),
+                },
+            )
+            .await;
+
+        let report = cache.why_not(r#"mutual("alice", "bob")"#).await.unwrap();
+        assert!(!report.present);
+        assert_eq!(report.rules.len(), 1);
+        assert_eq!(
+            report.rules[0].outcome,
+            RuleOutcome::FailedAt {
+                index: 1,
+                literal: "likes(Y, X, _)".to_string(),
+            }
+        );
+
+        // Once the missing fact exists the rule is satisfied
+        cache
+            .add_fact(
+                "r2".to_string(),
+                make_fact("likes", vec!["bob", "alice"]),
+                "cid2".to_string(),
+            )
+            .await;
+        let report = cache.why_not(r#"mutual("alice", "bob")"#).await.unwrap();
+        assert!(report.present);
+        assert_eq!(report.rules[0].outcome, RuleOutcome::Satisfied);
+
+        // A goal nobody likes fails at the first atom
+        let report = cache.why_not(r#"mutual("carol", "bob")"#).await.unwrap();
+        assert_eq!(
+            report.rules[0].outcome,
+            RuleOutcome::FailedAt {
+                index: 0,
+                literal: "likes(X, Y, _)".to_string(),
+            }
+        );
+    }
 }
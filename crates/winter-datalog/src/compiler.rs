@@ -1,6 +1,12 @@
 //! Compile rules to Soufflé `.dl` format.
+//!
+//! Rule bodies and constraints may use aggregates, written as
+//! `N = count : follows(U, _)`, `S = sum X : score(U, X)`, or with an inline
+//! comparison, `count : follows(U, _) > 100`. The aggregated body is either a
+//! single atom or a braced list of atoms (`count : { a(X), b(X) }`), and is
+//! compiled to Soufflé's `count : { ... }` form.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use winter_atproto::Rule;
 
@@ -28,12 +34,31 @@ impl RuleCompiler {
             ));
         }
 
+        let body: Vec<Literal> = rule
+            .body
+            .iter()
+            .map(|l| Literal::parse(l))
+            .collect::<Result<_, _>>()?;
+        let constraints: Vec<Literal> = rule
+            .constraints
+            .iter()
+            .map(|l| Literal::parse(l))
+            .collect::<Result<_, _>>()?;
+        check_aggregate_grounding(&rule.head, &body, &constraints)?;
+
         // Build the rule: head :- body, constraints.
-        let mut rule_str = format!("{} :- {}", rule.head, rule.body.join(", "));
+        let compile = |literals: &[Literal]| {
+            literals
+                .iter()
+                .map(Literal::compile)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut rule_str = format!("{} :- {}", rule.head, compile(&body));
 
-        if !rule.constraints.is_empty() {
+        if !constraints.is_empty() {
             rule_str.push_str(", ");
-            rule_str.push_str(&rule.constraints.join(", "));
+            rule_str.push_str(&compile(&constraints));
         }
 
         rule_str.push('.');
@@ -41,6 +66,16 @@ impl RuleCompiler {
         Ok(rule_str)
     }
 
+    /// Compile a rule's body literals followed by its constraints, in the
+    /// order Soufflé evaluates them.
+    pub fn compile_body(rule: &Rule) -> Result<Vec<String>, DatalogError> {
+        rule.body
+            .iter()
+            .chain(&rule.constraints)
+            .map(|l| Literal::parse(l).map(|l| l.compile()))
+            .collect()
+    }
+
     /// Compile all rules to Soufflé format.
     pub fn compile_rules(rules: &[Rule]) -> Result<String, DatalogError> {
         let mut output = String::new();
@@ -203,6 +238,292 @@ impl RuleCompiler {
     }
 }
 
+/// Aggregate functions accepted in rule bodies.
+const AGGREGATE_FUNCTIONS: &[&str] = &["count", "sum", "min", "max"];
+
+/// A rule body literal or constraint.
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    /// Passed through to Soufflé unchanged.
+    Plain(String),
+    Aggregate(Aggregate),
+}
+
+/// An aggregate such as `N = sum X : score(U, X)` or `count : follows(U, _) > 100`.
+#[derive(Debug, Clone, PartialEq)]
+struct Aggregate {
+    /// Variable bound to the result (`N` in `N = count : ...`).
+    result: Option<String>,
+    function: String,
+    /// Expression being aggregated; `None` for `count`.
+    target: Option<String>,
+    /// Atoms inside the aggregate, without braces.
+    body: String,
+    /// Trailing comparison (`> 100` in `count : ... > 100`).
+    comparison: Option<String>,
+}
+
+impl Literal {
+    fn parse(literal: &str) -> Result<Self, DatalogError> {
+        let literal = literal.trim();
+        let Some(colon) = find_top_level(literal, ':') else {
+            return Ok(Literal::Plain(literal.to_string()));
+        };
+
+        let (head, rest) = (literal[..colon].trim(), literal[colon + 1..].trim());
+        let (result, head) = match find_top_level(head, '=') {
+            Some(eq) => (Some(head[..eq].trim()), head[eq + 1..].trim()),
+            None => (None, head),
+        };
+        let (function, target) = match head.split_once(char::is_whitespace) {
+            Some((f, t)) => (f, Some(t.trim())),
+            None => (head, None),
+        };
+
+        if !AGGREGATE_FUNCTIONS.contains(&function) {
+            return Err(DatalogError::InvalidRule(format!(
+                "unknown aggregate '{}' in '{}' (expected one of: {})",
+                function,
+                literal,
+                AGGREGATE_FUNCTIONS.join(", ")
+            )));
+        }
+        if let Some(result) = result
+            && !is_variable(result)
+        {
+            return Err(DatalogError::InvalidRule(format!(
+                "aggregate result must be a variable, got '{}'",
+                result
+            )));
+        }
+        match (function, target) {
+            ("count", Some(t)) => {
+                return Err(DatalogError::InvalidRule(format!(
+                    "count takes no target expression, got '{}'",
+                    t
+                )));
+            }
+            (f, None) if f != "count" => {
+                return Err(DatalogError::InvalidRule(format!(
+                    "{} needs a target expression, e.g. '{} X : pred(X)'",
+                    f, f
+                )));
+            }
+            _ => {}
+        }
+
+        let (body, comparison) = split_aggregate_body(rest).ok_or_else(|| {
+            DatalogError::InvalidRule(format!("malformed aggregate body in '{}'", literal))
+        })?;
+
+        Ok(Literal::Aggregate(Aggregate {
+            result: result.map(String::from),
+            function: function.to_string(),
+            target: target.map(String::from),
+            body: body.to_string(),
+            comparison: (!comparison.is_empty()).then(|| comparison.to_string()),
+        }))
+    }
+
+    fn compile(&self) -> String {
+        match self {
+            Literal::Plain(s) => s.clone(),
+            Literal::Aggregate(agg) => {
+                let mut out = String::new();
+                if let Some(ref result) = agg.result {
+                    out.push_str(&format!("{} = ", result));
+                }
+                out.push_str(&agg.function);
+                if let Some(ref target) = agg.target {
+                    out.push_str(&format!(" {}", target));
+                }
+                out.push_str(&format!(" : {{ {} }}", agg.body));
+                if let Some(ref comparison) = agg.comparison {
+                    out.push_str(&format!(" {}", comparison));
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Split an aggregate body into the atoms and any trailing comparison.
+///
+/// Accepts either `{ atoms } rest` or a single `atom(...) rest`.
+fn split_aggregate_body(rest: &str) -> Option<(&str, &str)> {
+    if let Some(inner) = rest.strip_prefix('{') {
+        let close = find_top_level(inner, '}')?;
+        let body = inner[..close].trim();
+        return (!body.is_empty()).then(|| (body, inner[close + 1..].trim()));
+    }
+
+    let open = rest.find('(')?;
+    let close = open + 1 + find_top_level(&rest[open + 1..], ')')?;
+    Some((rest[..=close].trim(), rest[close + 1..].trim()))
+}
+
+/// Find `target` outside quotes and brackets, skipping `:-`, `!=`, `<=`, `>=`.
+fn find_top_level(s: &str, target: char) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut in_quotes = false;
+    let mut prev = '\0';
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_quotes {
+            if c == '"' && prev != '\\' {
+                in_quotes = false;
+            }
+        } else if c == '"' {
+            in_quotes = true;
+        } else if depth == 0 && c == target {
+            let next = chars.peek().map(|(_, n)| *n);
+            let compound = match c {
+                ':' => next == Some('-'),
+                '=' => matches!(prev, '!' | '<' | '>' | '='),
+                _ => false,
+            };
+            if !compound {
+                return Some(i);
+            }
+        } else if matches!(c, '(' | '{' | '[') {
+            depth += 1;
+        } else if matches!(c, ')' | '}' | ']') {
+            depth -= 1;
+        }
+        prev = c;
+    }
+    None
+}
+
+pub(crate) fn is_variable(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_uppercase() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && s != "_"
+}
+
+/// Variables mentioned in a fragment of rule text.
+///
+/// Identifiers followed by `(` are predicates or functors, quoted strings are
+/// constants, and `_` is anonymous; everything else capitalised is a variable.
+fn variables(s: &str) -> HashSet<String> {
+    let mut vars = HashSet::new();
+    let mut in_quotes = false;
+    let mut prev = '\0';
+    let mut ident = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' && prev != '\\' {
+                in_quotes = false;
+            }
+        } else if c == '"' {
+            in_quotes = true;
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+            let continues = chars
+                .peek()
+                .is_some_and(|n| n.is_ascii_alphanumeric() || *n == '_');
+            if !continues {
+                let is_call = chars.peek() == Some(&'(');
+                if !is_call && is_variable(&ident) {
+                    vars.insert(std::mem::take(&mut ident));
+                }
+                ident.clear();
+            }
+        }
+        prev = c;
+    }
+    vars
+}
+
+/// Reject aggregates whose variables can't be bound.
+///
+/// Soufflé requires any variable shared between an aggregate body and the
+/// rest of the rule to be grounded by an ordinary positive atom outside the
+/// aggregate, and the target of `sum`/`min`/`max` to appear in the aggregate
+/// body.
+fn check_aggregate_grounding(
+    head: &str,
+    body: &[Literal],
+    constraints: &[Literal],
+) -> Result<(), DatalogError> {
+    let literals: Vec<&Literal> = body.iter().chain(constraints).collect();
+    if !literals.iter().any(|l| matches!(l, Literal::Aggregate(_))) {
+        return Ok(());
+    }
+
+    let mut grounded = HashSet::new();
+    for literal in &literals {
+        match literal {
+            Literal::Plain(s) => {
+                let atom = s.trim_start();
+                let is_positive_atom = atom
+                    .find('(')
+                    .is_some_and(|i| !atom.starts_with('!') && is_predicate_name(&atom[..i]));
+                if is_positive_atom {
+                    grounded.extend(variables(atom));
+                }
+            }
+            Literal::Aggregate(agg) => grounded.extend(agg.result.clone()),
+        }
+    }
+
+    for (i, literal) in literals.iter().enumerate() {
+        let Literal::Aggregate(agg) = literal else {
+            continue;
+        };
+
+        let inner = variables(&agg.body);
+        if let Some(ref target) = agg.target
+            && let Some(missing) = variables(target).into_iter().find(|v| !inner.contains(v))
+        {
+            return Err(DatalogError::InvalidRule(format!(
+                "aggregate target variable '{}' does not appear in '{}'",
+                missing, agg.body
+            )));
+        }
+
+        // Variables used anywhere else in the rule
+        let mut outside = variables(head);
+        outside.extend(agg.comparison.iter().flat_map(|c| variables(c)));
+        for (j, other) in literals.iter().enumerate() {
+            if i != j {
+                match other {
+                    Literal::Plain(s) => outside.extend(variables(s)),
+                    // Another aggregate's body is its own scope
+                    Literal::Aggregate(o) => {
+                        outside.extend(o.result.clone());
+                        outside.extend(o.comparison.iter().flat_map(|c| variables(c)));
+                    }
+                }
+            }
+        }
+
+        let mut ungrounded: Vec<&String> = inner
+            .iter()
+            .filter(|v| outside.contains(*v) && !grounded.contains(*v))
+            .collect();
+        ungrounded.sort();
+        if !ungrounded.is_empty() {
+            let names: Vec<&str> = ungrounded.iter().map(|v| v.as_str()).collect();
+            return Err(DatalogError::InvalidRule(format!(
+                "ungrounded variable(s) {} in aggregate '{}': bind them with an atom outside the aggregate",
+                names.join(", "),
+                literal.compile()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_predicate_name(s: &str) -> bool {
+    let s = s.trim();
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,6 +752,102 @@ mod tests {
         assert!(heads.is_empty());
     }
 
+    #[test]
+    fn test_compile_count_with_comparison() {
+        let rule = make_rule(
+            "prolific",
+            "prolific(User)",
+            vec!["follows(User, _)", "count : follows(User, _) > 100"],
+        );
+
+        let compiled = RuleCompiler::compile_rule(&rule).unwrap();
+        assert_eq!(
+            compiled,
+            "prolific(User) :- follows(User, _), count : { follows(User, _) } > 100."
+        );
+    }
+
+    #[test]
+    fn test_compile_aggregates_with_result_variable() {
+        let mut rule = make_rule(
+            "total_score",
+            "total_score(U, S)",
+            vec!["account(U)", "S = sum X : { score(U, X), counted(X) }"],
+        );
+        rule.constraints = vec!["N = max X : score(U, X)".to_string(), "N > 3".to_string()];
+
+        let compiled = RuleCompiler::compile_rule(&rule).unwrap();
+        assert_eq!(
+            compiled,
+            "total_score(U, S) :- account(U), S = sum X : { score(U, X), counted(X) }, N = max X : { score(U, X) }, N > 3."
+        );
+    }
+
+    #[test]
+    fn test_quoted_colons_are_not_aggregates() {
+        let rule = make_rule(
+            "has_winter_note",
+            "has_winter_note(N)",
+            vec![r#"has_note(N, "at://did:plc:abc/note")"#],
+        );
+
+        let compiled = RuleCompiler::compile_rule(&rule).unwrap();
+        assert_eq!(
+            compiled,
+            r#"has_winter_note(N) :- has_note(N, "at://did:plc:abc/note")."#
+        );
+    }
+
+    #[test]
+    fn test_rejects_ungrounded_aggregate_variable() {
+        // User only appears inside the aggregate, so nothing binds it
+        let rule = make_rule(
+            "prolific",
+            "prolific(User)",
+            vec!["count : follows(User, _) > 100"],
+        );
+
+        let err = RuleCompiler::compile_rule(&rule).unwrap_err().to_string();
+        assert!(err.contains("ungrounded variable(s) User"), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_negated_grounding() {
+        let rule = make_rule(
+            "lonely",
+            "lonely(User)",
+            vec!["!blocked(User)", "N = count : follows(User, _)", "N < 2"],
+        );
+
+        let err = RuleCompiler::compile_rule(&rule).unwrap_err().to_string();
+        assert!(err.contains("ungrounded"), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_malformed_aggregates() {
+        let cases = [
+            ("N = average X : score(_, X)", "unknown aggregate"),
+            ("N = sum : score(_, X)", "needs a target"),
+            ("N = count X : score(_, X)", "no target"),
+            ("N = sum Y : score(_, X)", "target variable 'Y'"),
+            ("n = count : score(_, _)", "must be a variable"),
+            ("N = count : {}", "malformed"),
+        ];
+        for (literal, expected) in cases {
+            let rule = make_rule("bad", "bad(N)", vec![literal]);
+            let err = RuleCompiler::compile_rule(&rule).unwrap_err().to_string();
+            assert!(err.contains(expected), "{literal}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_variables() {
+        let vars = variables(r#"score(U, X), X > to_number("Y"), _ = _Tmp"#);
+        let mut vars: Vec<_> = vars.into_iter().collect();
+        vars.sort();
+        assert_eq!(vars, vec!["U", "X", "_Tmp"]);
+    }
+
     #[test]
     fn test_parse_extra_rules_heads_with_constant() {
         // The key case: rule with constant argument in body
@@ -3,13 +3,16 @@
 //! Analyzes datalog rules and queries to determine which predicates are needed,
 //! enabling lazy regeneration of only the required TSV files.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::OnceLock;
 
 use regex::Regex;
+use serde::Serialize;
 
 use winter_atproto::Rule;
 
+use crate::derived::DerivedFactGenerator;
+
 /// Compiled regex for predicate extraction (cached).
 fn predicate_regex() -> &'static Regex {
     static RE: OnceLock<Regex> = OnceLock::new();
@@ -55,12 +58,16 @@ impl PredicateDependencyGraph {
                         .extend(body_preds);
                 }
 
-                // Also parse constraints for additional predicates
+                // Constraints can reference predicates too (e.g., aggregates)
                 for constraint in &rule.constraints {
                     let constraint_preds = extract_predicates_from_text(constraint);
-                    for pred in constraint_preds {
-                        all_predicates.insert(pred);
+                    for pred in &constraint_preds {
+                        all_predicates.insert(pred.clone());
                     }
+                    dependencies
+                        .entry(head_pred.clone())
+                        .or_default()
+                        .extend(constraint_preds);
                 }
             }
         }
@@ -112,6 +119,176 @@ impl PredicateDependencyGraph {
     }
 }
 
+/// Role a node plays in an exported dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    /// User-asserted fact predicate.
+    Fact,
+    /// Predicate generated from ATProto records (follows, liked, ...).
+    Builtin,
+    /// Fact metadata predicate (`_confidence`, `_supersedes`, ...).
+    Metadata,
+    /// Predicate defined by one or more rules.
+    Derived,
+    /// A rule connecting its body predicates to its head.
+    Rule,
+}
+
+/// A predicate or rule in an exported dependency graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    /// Unique node id: `pred:<name>` or `rule:<name>`.
+    pub id: String,
+    /// Predicate or rule name.
+    pub label: String,
+    pub kind: GraphNodeKind,
+}
+
+/// A directed edge: predicate -> rule for body literals, rule -> predicate for heads.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Whether the predicate appears under negation in the rule body.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub negated: bool,
+}
+
+/// Serializable view of how predicates feed rules and rules feed predicates.
+///
+/// Unlike [`PredicateDependencyGraph`], which collapses rules into
+/// predicate-to-predicate dependencies, this keeps each rule as its own node
+/// so operators can see which rule produces a derived predicate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependencyGraphExport {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraphExport {
+    /// Build the graph from a set of rules. Disabled rules are skipped.
+    pub fn from_rules(rules: &[Rule]) -> Self {
+        let enabled: Vec<&Rule> = rules.iter().filter(|r| r.enabled).collect();
+        let heads: HashSet<String> = enabled
+            .iter()
+            .filter_map(|r| extract_predicate_name(&r.head))
+            .collect();
+
+        let mut predicates: BTreeSet<String> = BTreeSet::new();
+        let mut rule_nodes: Vec<GraphNode> = Vec::new();
+        let mut edges: BTreeSet<GraphEdge> = BTreeSet::new();
+        let mut rule_ids: HashSet<String> = HashSet::new();
+
+        for rule in enabled {
+            let Some(head_pred) = extract_predicate_name(&rule.head) else {
+                continue;
+            };
+
+            // Rule names aren't guaranteed unique; disambiguate repeats
+            let mut rule_id = format!("rule:{}", rule.name);
+            let mut n = 2;
+            while !rule_ids.insert(rule_id.clone()) {
+                rule_id = format!("rule:{}#{}", rule.name, n);
+                n += 1;
+            }
+            rule_nodes.push(GraphNode {
+                id: rule_id.clone(),
+                label: rule.name.clone(),
+                kind: GraphNodeKind::Rule,
+            });
+
+            for literal in rule.body.iter().chain(&rule.constraints) {
+                let negated = literal.trim_start().starts_with('!');
+                for pred in extract_predicates_from_text(literal) {
+                    edges.insert(GraphEdge {
+                        from: format!("pred:{}", pred),
+                        to: rule_id.clone(),
+                        negated,
+                    });
+                    predicates.insert(pred);
+                }
+            }
+
+            edges.insert(GraphEdge {
+                from: rule_id,
+                to: format!("pred:{}", head_pred),
+                negated: false,
+            });
+            predicates.insert(head_pred);
+        }
+
+        let mut nodes: Vec<GraphNode> = predicates
+            .into_iter()
+            .map(|name| GraphNode {
+                id: format!("pred:{}", name),
+                kind: predicate_kind(&name, &heads),
+                label: name,
+            })
+            .collect();
+        nodes.extend(rule_nodes);
+
+        Self {
+            nodes,
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Render the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph predicates {\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            let style = match node.kind {
+                GraphNodeKind::Fact => "shape=box",
+                GraphNodeKind::Builtin => "shape=box, style=dashed",
+                GraphNodeKind::Metadata => "shape=box, style=dotted",
+                GraphNodeKind::Derived => "shape=box, style=bold",
+                GraphNodeKind::Rule => "shape=ellipse",
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\", {}];\n",
+                dot_escape(&node.id),
+                dot_escape(&node.label),
+                style
+            ));
+        }
+        for edge in &self.edges {
+            let attrs = if edge.negated {
+                " [style=dashed, label=\"not\"]"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\"{};\n",
+                dot_escape(&edge.from),
+                dot_escape(&edge.to),
+                attrs
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Classify a predicate for graph export.
+fn predicate_kind(name: &str, heads: &HashSet<String>) -> GraphNodeKind {
+    let base = name.strip_prefix("_all_").unwrap_or(name);
+    if heads.contains(name) {
+        GraphNodeKind::Derived
+    } else if is_metadata_predicate(name) || matches!(name, "_now" | "_expired") {
+        GraphNodeKind::Metadata
+    } else if DerivedFactGenerator::is_derived(base) {
+        GraphNodeKind::Builtin
+    } else {
+        GraphNodeKind::Fact
+    }
+}
+
+/// Escape a string for use inside a double-quoted DOT identifier.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 /// Extract a predicate name from a rule head like `mutual(X)`.
 fn extract_predicate_name(head: &str) -> Option<String> {
     let head = head.trim();
@@ -140,7 +317,7 @@ fn extract_predicates_from_text(text: &str) -> HashSet<String> {
 }
 
 /// Check if a name is a valid predicate (not a Soufflé keyword).
-fn is_valid_predicate_name(name: &str) -> bool {
+pub(crate) fn is_valid_predicate_name(name: &str) -> bool {
     !matches!(
         name,
         "cat"
@@ -286,4 +463,109 @@ mod tests {
         assert!(preds.contains("_validation_error"));
         assert_eq!(preds.len(), 1);
     }
+
+    fn make_rule(name: &str, head: &str, body: &[&str], enabled: bool) -> Rule {
+        Rule {
+            name: name.to_string(),
+            description: String::new(),
+            head: head.to_string(),
+            body: body.iter().map(|s| s.to_string()).collect(),
+            constraints: vec![],
+            enabled,
+            priority: 0,
+            args: Vec::new(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn edge(from: &str, to: &str, negated: bool) -> GraphEdge {
+        GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            negated,
+        }
+    }
+
+    #[test]
+    fn test_export_edges_match_rules() {
+        let rules = vec![
+            make_rule(
+                "mutual",
+                "mutual(X)",
+                &["follows(Self, X, _)", "is_followed_by(X, Self)"],
+                true,
+            ),
+            make_rule(
+                "quiet_friend",
+                "quiet_friend(X)",
+                &["mutual(X)", "!posted(X, _, _)", "interest(X, _, _)"],
+                true,
+            ),
+            make_rule("ignored", "ignored(X)", &["likes(X, _, _)"], false),
+        ];
+
+        let graph = DependencyGraphExport::from_rules(&rules);
+
+        let expected = vec![
+            edge("pred:follows", "rule:mutual", false),
+            edge("pred:interest", "rule:quiet_friend", false),
+            edge("pred:is_followed_by", "rule:mutual", false),
+            edge("pred:mutual", "rule:quiet_friend", false),
+            edge("pred:posted", "rule:quiet_friend", true),
+            edge("rule:mutual", "pred:mutual", false),
+            edge("rule:quiet_friend", "pred:quiet_friend", false),
+        ];
+        assert_eq!(graph.edges, expected);
+
+        let kind = |id: &str| graph.nodes.iter().find(|n| n.id == id).map(|n| n.kind);
+        assert_eq!(kind("pred:follows"), Some(GraphNodeKind::Builtin));
+        assert_eq!(kind("pred:interest"), Some(GraphNodeKind::Fact));
+        assert_eq!(kind("pred:mutual"), Some(GraphNodeKind::Derived));
+        assert_eq!(kind("rule:mutual"), Some(GraphNodeKind::Rule));
+        assert_eq!(kind("rule:ignored"), None);
+        assert_eq!(kind("pred:likes"), None);
+    }
+
+    #[test]
+    fn test_export_disambiguates_duplicate_rule_names() {
+        let rules = vec![
+            make_rule("reach", "reach(X)", &["follows(Self, X, _)"], true),
+            make_rule("reach", "reach(X)", &["reach(Y)", "follows(Y, X, _)"], true),
+        ];
+
+        let graph = DependencyGraphExport::from_rules(&rules);
+
+        assert!(graph.nodes.iter().any(|n| n.id == "rule:reach"));
+        assert!(graph.nodes.iter().any(|n| n.id == "rule:reach#2"));
+        assert!(
+            graph
+                .edges
+                .contains(&edge("pred:reach", "rule:reach#2", false))
+        );
+        assert!(
+            graph
+                .edges
+                .contains(&edge("rule:reach#2", "pred:reach", false))
+        );
+    }
+
+    #[test]
+    fn test_export_to_dot() {
+        let rules = vec![make_rule(
+            "lonely",
+            "lonely(X)",
+            &["interest(X, _, _)", "!follows(Self, X, _)"],
+            true,
+        )];
+
+        let dot = DependencyGraphExport::from_rules(&rules).to_dot();
+
+        assert!(dot.starts_with("digraph predicates {\n"));
+        assert!(dot.contains("\"rule:lonely\" [label=\"lonely\", shape=ellipse];"));
+        assert!(dot.contains("\"pred:lonely\" [label=\"lonely\", shape=box, style=bold];"));
+        assert!(dot.contains("\"pred:interest\" -> \"rule:lonely\";"));
+        assert!(dot.contains("\"pred:follows\" -> \"rule:lonely\" [style=dashed, label=\"not\"];"));
+        assert!(dot.contains("\"rule:lonely\" -> \"pred:lonely\";"));
+        assert!(dot.ends_with("}\n"));
+    }
 }
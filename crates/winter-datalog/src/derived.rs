@@ -3,25 +3,31 @@
 //! Generates facts automatically from authoritative PDS state:
 //! - Bluesky records (follows, likes, reposts, posts)
 //! - Winter records (directives, tools, jobs)
+//! - Fact confidence decayed by age, per the predicate's declaration
 //!
 //! These facts exist only in TSV files (not as ATProto fact records)
 //! and are regenerated when source records change.
 
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::io::{BufWriter, Write};
 use std::path::Path;
 
+use serde::Serialize;
 use tracing::{debug, trace};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
 use winter_atproto::{
-    BlogEntry, CacheUpdate, CustomTool, Directive, DirectiveKind, Fact, Follow, Job, JobSchedule,
-    Like, Note, Post, Repost, Thought, ToolApproval, ToolApprovalStatus, Trigger, WikiEntry,
-    WikiLink,
+    BlogEntry, CacheUpdate, ConfidenceDecay, CustomTool, DecayFunction, Directive, DirectiveKind,
+    Fact, FactDeclaration, Follow, Job, JobSchedule, Like, Note, Post, Repost, Thought,
+    ToolApproval, ToolApprovalStatus, Trigger, WikiEntry, WikiLink,
 };
 
 use crate::error::DatalogError;
+use crate::tsv;
 
 /// Metadata about a post for derived facts.
 #[derive(Debug, Clone)]
@@ -172,6 +178,46 @@ struct FollowMeta {
     created_at: DateTime<Utc>,
 }
 
+/// Derived predicates whose values depend on the current time.
+///
+/// These are regenerated on every query that needs them rather than only
+/// when their source records change.
+pub const TIME_DEPENDENT_PREDICATES: &[&str] = &["current_confidence"];
+
+/// Largest predicate whose row hashes are kept for diffing generations.
+///
+/// Bounds the memory spent on generation stats; larger predicates are
+/// reported without a diff.
+const MAX_DIFFED_ROWS: usize = 100_000;
+
+/// Confidence inputs for a fact, used to compute `current_confidence`.
+#[derive(Debug, Clone)]
+struct FactConfidenceMeta {
+    /// The fact's predicate (selects the decay configuration).
+    predicate: String,
+    /// Stored confidence (1.0 when unset).
+    confidence: f64,
+    /// When the fact was created.
+    created_at: DateTime<Utc>,
+}
+
+/// Decay a stored confidence by the fact's age.
+///
+/// Exponential decay halves confidence every half-life. Linear decay reaches
+/// half at one half-life and zero at two. Negative ages (clock skew) count as
+/// zero, and a non-positive half-life leaves confidence unchanged.
+pub fn decayed_confidence(confidence: f64, decay: &ConfidenceDecay, age: Duration) -> f64 {
+    if decay.half_life_secs <= 0 {
+        return confidence;
+    }
+    let age_secs = age.num_milliseconds().max(0) as f64 / 1000.0;
+    let half_lives = age_secs / decay.half_life_secs as f64;
+    match decay.function {
+        DecayFunction::Exponential => confidence * 0.5_f64.powf(half_lives),
+        DecayFunction::Linear => confidence * (1.0 - half_lives / 2.0).max(0.0),
+    }
+}
+
 /// Information about a derived predicate.
 #[derive(Debug, Clone)]
 pub struct PredicateInfo {
@@ -230,6 +276,12 @@ pub struct DerivedFactGenerator {
     fact_tags: HashMap<String, Vec<String>>,
     /// Triggers: rkey -> (name, enabled).
     triggers: HashMap<String, (String, bool)>,
+    /// Fact confidence inputs: rkey -> confidence metadata.
+    fact_confidences: HashMap<String, FactConfidenceMeta>,
+    /// Confidence decay configuration by predicate, from fact declarations.
+    decay_by_predicate: HashMap<String, ConfidenceDecay>,
+    /// Declaration rkey -> predicate, for handling declaration deletes.
+    declaration_predicates: HashMap<String, String>,
 
     // =========================================================================
     // From Bluesky API (periodic sync)
@@ -242,6 +294,16 @@ pub struct DerivedFactGenerator {
     // =========================================================================
     /// Predicates that need TSV regeneration.
     dirty_predicates: HashSet<String>,
+
+    // =========================================================================
+    // Generation stats
+    // =========================================================================
+    /// Row hashes per predicate as of its last generation, for diffing.
+    ///
+    /// Predicates with more than [`MAX_DIFFED_ROWS`] rows aren't kept.
+    generated_rows: HashMap<String, HashSet<u64>>,
+    /// Stats from the most recent generation run.
+    last_generation: Option<GenerationStats>,
 }
 
 impl DerivedFactGenerator {
@@ -265,8 +327,13 @@ impl DerivedFactGenerator {
             wiki_links: HashMap::new(),
             fact_tags: HashMap::new(),
             triggers: HashMap::new(),
+            fact_confidences: HashMap::new(),
+            decay_by_predicate: HashMap::new(),
+            declaration_predicates: HashMap::new(),
             followers: HashSet::new(),
             dirty_predicates: HashSet::new(),
+            generated_rows: HashMap::new(),
+            last_generation: None,
         }
     }
 
@@ -308,6 +375,7 @@ impl DerivedFactGenerator {
                 | "has_guideline"
                 | "has_boundary"
                 | "has_aspiration"
+                | "has_goal"
                 | "has_self_concept"
                 // Winter: tools, jobs
                 | "has_tool"
@@ -330,6 +398,8 @@ impl DerivedFactGenerator {
                 | "has_wiki_link"
                 // Winter: fact tags
                 | "fact_tag"
+                // Winter: fact confidence decay
+                | "current_confidence"
                 // Winter: triggers
                 | "has_trigger"
         )
@@ -593,6 +663,14 @@ impl DerivedFactGenerator {
                 description: "Your active aspirations",
             },
         );
+        m.insert(
+            "has_goal",
+            PredicateInfo {
+                arity: 2,
+                args: &["content", "rkey"],
+                description: "Your active awaken goals",
+            },
+        );
         m.insert(
             "has_self_concept",
             PredicateInfo {
@@ -756,6 +834,16 @@ impl DerivedFactGenerator {
             },
         );
 
+        // Fact confidence decay
+        m.insert(
+            "current_confidence",
+            PredicateInfo {
+                arity: 3,
+                args: &["fact_uri", "confidence", "rkey"],
+                description: "Fact confidence decayed by age, for predicates declared with a decay",
+            },
+        );
+
         m
     }
 
@@ -889,15 +977,29 @@ impl DerivedFactGenerator {
                 self.remove_wiki_link(rkey);
             }
 
-            // Facts (for tags)
+            // Facts (for tags and confidence decay)
             CacheUpdate::FactCreated { rkey, fact } => {
                 self.add_fact_tags(rkey.clone(), fact);
+                self.add_fact_confidence(rkey.clone(), fact);
             }
             CacheUpdate::FactUpdated { rkey, fact } => {
                 self.add_fact_tags(rkey.clone(), fact);
+                self.add_fact_confidence(rkey.clone(), fact);
             }
             CacheUpdate::FactDeleted { rkey } => {
                 self.remove_fact_tags(rkey);
+                self.remove_fact_confidence(rkey);
+            }
+
+            // Declarations (for confidence decay)
+            CacheUpdate::DeclarationCreated { rkey, declaration } => {
+                self.add_declaration(rkey.clone(), declaration);
+            }
+            CacheUpdate::DeclarationUpdated { rkey, declaration } => {
+                self.add_declaration(rkey.clone(), declaration);
+            }
+            CacheUpdate::DeclarationDeleted { rkey } => {
+                self.remove_declaration(rkey);
             }
 
             // Triggers
@@ -1513,6 +1615,61 @@ impl DerivedFactGenerator {
         }
     }
 
+    // =========================================================================
+    // Confidence decay handling
+    // =========================================================================
+
+    fn add_fact_confidence(&mut self, rkey: String, fact: &Fact) {
+        self.fact_confidences.insert(
+            rkey,
+            FactConfidenceMeta {
+                predicate: fact.predicate.clone(),
+                confidence: fact.confidence.unwrap_or(1.0),
+                created_at: fact.created_at,
+            },
+        );
+        self.dirty_predicates
+            .insert("current_confidence".to_string());
+    }
+
+    fn remove_fact_confidence(&mut self, rkey: &str) {
+        if self.fact_confidences.remove(rkey).is_some() {
+            self.dirty_predicates
+                .insert("current_confidence".to_string());
+        }
+    }
+
+    fn add_declaration(&mut self, rkey: String, declaration: &FactDeclaration) {
+        // A renamed predicate drops the old predicate's decay
+        if let Some(old) = self
+            .declaration_predicates
+            .insert(rkey, declaration.predicate.clone())
+            && old != declaration.predicate
+        {
+            self.decay_by_predicate.remove(&old);
+        }
+        match declaration.decay {
+            Some(decay) => {
+                self.decay_by_predicate
+                    .insert(declaration.predicate.clone(), decay);
+            }
+            None => {
+                self.decay_by_predicate.remove(&declaration.predicate);
+            }
+        }
+        self.dirty_predicates
+            .insert("current_confidence".to_string());
+    }
+
+    fn remove_declaration(&mut self, rkey: &str) {
+        if let Some(predicate) = self.declaration_predicates.remove(rkey)
+            && self.decay_by_predicate.remove(&predicate).is_some()
+        {
+            self.dirty_predicates
+                .insert("current_confidence".to_string());
+        }
+    }
+
     // =========================================================================
     // Trigger handling
     // =========================================================================
@@ -1590,23 +1747,108 @@ impl DerivedFactGenerator {
             "regenerating all derived fact files"
         );
         for predicate in Self::arities().keys() {
-            let count = self.write_predicate_file(fact_dir, predicate)?;
-            if count > 0 {
-                trace!(predicate, count, "wrote derived predicate file");
+            let rows = self.write_predicate_file(fact_dir, predicate)?;
+            if !rows.is_empty() {
+                trace!(
+                    predicate,
+                    count = rows.len(),
+                    "wrote derived predicate file"
+                );
             }
         }
         self.dirty_predicates.clear();
         Ok(())
     }
 
+    /// Write the given derived predicates and report what changed.
+    ///
+    /// Each written file is compared row-by-row with the previous generation
+    /// of the same predicate, so the stats reflect actual input changes
+    /// rather than file sizes. Unknown predicates are ignored.
+    pub fn generate(
+        &mut self,
+        fact_dir: &Path,
+        predicates: &HashSet<String>,
+    ) -> Result<GenerationStats, DatalogError> {
+        let start = std::time::Instant::now();
+        let mut rows = HashMap::new();
+        for predicate in predicates {
+            if !Self::is_derived(predicate) {
+                continue;
+            }
+            rows.insert(
+                predicate.clone(),
+                self.write_predicate_file(fact_dir, predicate)?,
+            );
+            self.dirty_predicates.remove(predicate);
+        }
+        Ok(self.record_generation(rows, start.elapsed()))
+    }
+
+    /// Record a generation run whose files were written elsewhere.
+    ///
+    /// `rows` holds the row hashes of each predicate that was written, as
+    /// returned by [`DerivedFlushSnapshot::write_predicates_subset`].
+    pub fn record_generation(
+        &mut self,
+        rows: HashMap<String, HashSet<u64>>,
+        elapsed: std::time::Duration,
+    ) -> GenerationStats {
+        let mut predicates = BTreeMap::new();
+        for (predicate, new_rows) in rows {
+            let change = match self.generated_rows.get(&predicate) {
+                Some(old_rows) => PredicateChange {
+                    rows: new_rows.len(),
+                    added: new_rows.difference(old_rows).count(),
+                    removed: old_rows.difference(&new_rows).count(),
+                    diffed: true,
+                },
+                None => PredicateChange {
+                    rows: new_rows.len(),
+                    added: new_rows.len(),
+                    removed: 0,
+                    diffed: false,
+                },
+            };
+            predicates.insert(predicate.clone(), change);
+            if new_rows.len() <= MAX_DIFFED_ROWS {
+                self.generated_rows.insert(predicate, new_rows);
+            } else {
+                self.generated_rows.remove(&predicate);
+            }
+        }
+
+        let stats = GenerationStats {
+            finished_at: Utc::now(),
+            duration_ms: elapsed.as_millis() as u64,
+            predicates,
+            souffle_ran: false,
+        };
+        self.last_generation = Some(stats.clone());
+        stats
+    }
+
+    /// Note that Soufflé evaluated a program after the last generation.
+    pub fn mark_souffle_ran(&mut self) {
+        if let Some(stats) = self.last_generation.as_mut() {
+            stats.souffle_ran = true;
+        }
+    }
+
+    /// Stats from the most recent generation run, if any.
+    pub fn last_generation(&self) -> Option<&GenerationStats> {
+        self.last_generation.as_ref()
+    }
+
+    /// Write a predicate's TSV file, returning the hash of each row written.
     fn write_predicate_file(
         &self,
         fact_dir: &Path,
         predicate: &str,
-    ) -> Result<usize, DatalogError> {
+    ) -> Result<HashSet<u64>, DatalogError> {
         let path = fact_dir.join(format!("{}.facts", predicate));
         let file = std::fs::File::create(&path)?;
-        let mut file = BufWriter::new(file);
+        let mut file = RowHashingWriter::new(BufWriter::new(file));
 
         // Get count based on predicate (for logging)
         let count = match predicate {
@@ -1626,6 +1868,7 @@ impl DerivedFactGenerator {
             "has_tool" => self.tools.len(),
             "has_job" => self.jobs.len(),
             "has_trigger" => self.triggers.len(),
+            "current_confidence" => self.fact_confidences.len(),
             _ => 0,
         };
 
@@ -1828,6 +2071,9 @@ impl DerivedFactGenerator {
             "has_aspiration" => {
                 self.write_directive_predicate(&mut file, &DirectiveKind::Aspiration)?;
             }
+            "has_goal" => {
+                self.write_directive_predicate(&mut file, &DirectiveKind::Goal)?;
+            }
             "has_self_concept" => {
                 self.write_directive_predicate(&mut file, &DirectiveKind::SelfConcept)?;
             }
@@ -1993,13 +2239,26 @@ impl DerivedFactGenerator {
                     }
                 }
             }
+
+            // =================================================================
+            // Fact confidence decay
+            // =================================================================
+            "current_confidence" => {
+                write_current_confidence(
+                    &mut file,
+                    &self.self_did,
+                    &self.fact_confidences,
+                    &self.decay_by_predicate,
+                    Utc::now(),
+                )?;
+            }
             _ => {
                 trace!(predicate, "unknown derived predicate");
             }
         }
 
         trace!(predicate, path = ?path, count, "wrote derived predicate file");
-        Ok(count)
+        Ok(file.finish()?)
     }
 
     fn write_directive_predicate<W: Write>(
@@ -2009,9 +2268,7 @@ impl DerivedFactGenerator {
     ) -> Result<(), DatalogError> {
         for (rkey, (k, content)) in &self.directives {
             if k == kind {
-                // Escape tabs and newlines in content
-                let escaped = content.replace(['\t', '\n'], " ");
-                writeln!(file, "{}\t{}", escaped, rkey)?;
+                writeln!(file, "{}\t{}", escape_tsv(content), rkey)?;
             }
         }
         Ok(())
@@ -2081,6 +2338,8 @@ impl DerivedFactGenerator {
             wiki_links: self.wiki_links.clone(),
             fact_tags: self.fact_tags.clone(),
             triggers: self.triggers.clone(),
+            fact_confidences: self.fact_confidences.clone(),
+            decay_by_predicate: self.decay_by_predicate.clone(),
             followers: self.followers.clone(),
         }
     }
@@ -2109,6 +2368,8 @@ pub struct DerivedFlushSnapshot {
     wiki_links: HashMap<String, WikiLinkMeta>,
     fact_tags: HashMap<String, Vec<String>>,
     triggers: HashMap<String, (String, bool)>,
+    fact_confidences: HashMap<String, FactConfidenceMeta>,
+    decay_by_predicate: HashMap<String, ConfidenceDecay>,
     followers: HashSet<String>,
 }
 
@@ -2137,25 +2398,35 @@ impl DerivedFlushSnapshot {
     ///
     /// Used for lazy regeneration - only writes predicates that are needed
     /// for the current query, creating empty files for predicates that exist
-    /// but have no data.
+    /// but have no data. Returns the row hashes of each written predicate
+    /// for [`DerivedFactGenerator::record_generation`].
     pub fn write_predicates_subset(
         &self,
         fact_dir: &Path,
         predicates: &HashSet<String>,
-    ) -> Result<(), DatalogError> {
+    ) -> Result<HashMap<String, HashSet<u64>>, DatalogError> {
+        let mut rows = HashMap::new();
         for predicate in predicates {
             // Only write if this is a known derived predicate
             if DerivedFactGenerator::is_derived(predicate) {
-                self.write_predicate_file(fact_dir, predicate)?;
+                rows.insert(
+                    predicate.clone(),
+                    self.write_predicate_file(fact_dir, predicate)?,
+                );
             }
         }
-        Ok(())
+        Ok(rows)
     }
 
-    fn write_predicate_file(&self, fact_dir: &Path, predicate: &str) -> Result<(), DatalogError> {
+    /// Write a predicate's TSV file, returning the hash of each row written.
+    fn write_predicate_file(
+        &self,
+        fact_dir: &Path,
+        predicate: &str,
+    ) -> Result<HashSet<u64>, DatalogError> {
         let path = fact_dir.join(format!("{}.facts", predicate));
         let file = std::fs::File::create(&path)?;
-        let mut file = BufWriter::new(file);
+        let mut file = RowHashingWriter::new(BufWriter::new(file));
 
         match predicate {
             // =================================================================
@@ -2379,6 +2650,13 @@ impl DerivedFlushSnapshot {
                     }
                 }
             }
+            "has_goal" => {
+                for (rkey, (kind, content)) in &self.directives {
+                    if *kind == DirectiveKind::Goal {
+                        writeln!(file, "{}\t{}", escape_tsv(content), rkey)?;
+                    }
+                }
+            }
             "has_self_concept" => {
                 for (rkey, (kind, content)) in &self.directives {
                     if *kind == DirectiveKind::SelfConcept {
@@ -2558,18 +2836,31 @@ impl DerivedFlushSnapshot {
                 }
             }
 
+            // =================================================================
+            // Fact confidence decay
+            // =================================================================
+            "current_confidence" => {
+                write_current_confidence(
+                    &mut file,
+                    &self.self_did,
+                    &self.fact_confidences,
+                    &self.decay_by_predicate,
+                    Utc::now(),
+                )?;
+            }
+
             _ => {
                 // Unknown predicate, create empty file
                 trace!(predicate, "unknown derived predicate, creating empty file");
             }
         }
 
-        Ok(())
+        Ok(file.finish()?)
     }
 }
 
 /// Statistics about derived facts.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DerivedFactStats {
     pub follows: usize,
     pub followers: usize,
@@ -2587,9 +2878,124 @@ pub struct DerivedFactStats {
     pub triggers: usize,
 }
 
-/// Escape tabs and newlines in a string for TSV output.
-fn escape_tsv(s: &str) -> String {
-    s.replace(['\t', '\n'], " ")
+/// Row-level change to one derived predicate between generations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PredicateChange {
+    /// Rows in the predicate after this generation.
+    pub rows: usize,
+    /// Rows not present in the previous generation.
+    pub added: usize,
+    /// Rows from the previous generation that are gone.
+    pub removed: usize,
+    /// Whether `added` and `removed` were computed against the previous
+    /// generation. False for a predicate's first generation, and for
+    /// predicates too large to keep row hashes for, where every row counts
+    /// as added.
+    pub diffed: bool,
+}
+
+/// Outcome of a derived-fact generation run.
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationStats {
+    /// When the run finished.
+    pub finished_at: DateTime<Utc>,
+    /// Wall-clock time spent writing and diffing predicate files.
+    pub duration_ms: u64,
+    /// Per-predicate changes for every predicate written in this run.
+    pub predicates: BTreeMap<String, PredicateChange>,
+    /// Whether Soufflé evaluated a program against the regenerated files.
+    pub souffle_ran: bool,
+}
+
+impl GenerationStats {
+    /// Total rows added across all predicates.
+    pub fn total_added(&self) -> usize {
+        self.predicates.values().map(|c| c.added).sum()
+    }
+
+    /// Total rows removed across all predicates.
+    pub fn total_removed(&self) -> usize {
+        self.predicates.values().map(|c| c.removed).sum()
+    }
+}
+
+/// Writer that hashes each line passing through it, so a generation can be
+/// diffed against the previous one without reading the file back.
+struct RowHashingWriter<W> {
+    inner: W,
+    line: Vec<u8>,
+    rows: HashSet<u64>,
+}
+
+impl<W: Write> RowHashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+            rows: HashSet::new(),
+        }
+    }
+
+    fn hash_line(&mut self) {
+        let mut hasher = DefaultHasher::new();
+        self.line.hash(&mut hasher);
+        self.rows.insert(hasher.finish());
+        self.line.clear();
+    }
+
+    /// Flush the underlying writer and return the row hashes.
+    fn finish(mut self) -> std::io::Result<HashSet<u64>> {
+        self.inner.flush()?;
+        if !self.line.is_empty() {
+            self.hash_line();
+        }
+        Ok(self.rows)
+    }
+}
+
+impl<W: Write> Write for RowHashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        for &byte in &buf[..written] {
+            if byte == b'\n' {
+                self.hash_line();
+            } else {
+                self.line.push(byte);
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write `current_confidence` rows for facts whose predicate declares a decay.
+fn write_current_confidence<W: Write>(
+    file: &mut W,
+    self_did: &str,
+    facts: &HashMap<String, FactConfidenceMeta>,
+    decays: &HashMap<String, ConfidenceDecay>,
+    now: DateTime<Utc>,
+) -> std::io::Result<()> {
+    for (rkey, meta) in facts {
+        let Some(decay) = decays.get(&meta.predicate) else {
+            continue;
+        };
+        let confidence = decayed_confidence(meta.confidence, decay, now - meta.created_at);
+        writeln!(
+            file,
+            "at://{}/diy.razorgirl.winter.fact/{}\t{:.4}\t{}",
+            self_did, rkey, confidence, rkey
+        )?;
+    }
+    Ok(())
+}
+
+/// Escape tabs, newlines, and backslashes for TSV output (see [`crate::tsv`]).
+fn escape_tsv(s: &str) -> Cow<'_, str> {
+    tsv::escape_field(s)
 }
 
 /// Convert a DirectiveKind to its corresponding predicate name.
@@ -2601,6 +3007,7 @@ fn directive_kind_to_predicate(kind: &DirectiveKind) -> &'static str {
         DirectiveKind::Guideline => "has_guideline",
         DirectiveKind::Boundary => "has_boundary",
         DirectiveKind::Aspiration => "has_aspiration",
+        DirectiveKind::Goal => "has_goal",
         DirectiveKind::SelfConcept => "has_self_concept",
     }
 }
@@ -2646,6 +3053,7 @@ mod tests {
             related_facts: related_facts.into_iter().map(String::from).collect(),
             created_at: Utc::now(),
             last_updated: Utc::now(),
+            deleted_at: None,
         }
     }
 
@@ -2661,6 +3069,7 @@ mod tests {
         Thought {
             kind,
             content: "test thought content".to_string(),
+            content_type: None,
             trigger: trigger.map(String::from),
             tags: tags.into_iter().map(String::from).collect(),
             duration_ms: None,
@@ -2689,6 +3098,7 @@ mod tests {
             tags: tags.into_iter().map(String::from).collect(),
             created_at: Utc::now(),
             expires_at: None,
+            deleted_at: None,
         }
     }
 
@@ -3106,6 +3516,152 @@ mod tests {
         assert!(generator.dirty_predicates.contains("fact_tag"));
     }
 
+    // =========================================================================
+    // Confidence Decay Tests
+    // =========================================================================
+
+    fn make_declaration(predicate: &str, decay: Option<ConfidenceDecay>) -> FactDeclaration {
+        FactDeclaration {
+            predicate: predicate.to_string(),
+            args: vec![],
+            description: String::new(),
+            tags: vec![],
+            decay,
+            created_at: Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    const DAY_SECS: i64 = 86_400;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_exponential_decay_follows_half_life() {
+        let decay = ConfidenceDecay {
+            function: DecayFunction::Exponential,
+            half_life_secs: 10 * DAY_SECS,
+        };
+        for (days, expected) in [
+            (0, 0.8),
+            (5, 0.8 * 0.5_f64.sqrt()),
+            (10, 0.4),
+            (20, 0.2),
+            (30, 0.1),
+        ] {
+            assert_close(
+                decayed_confidence(0.8, &decay, Duration::days(days)),
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn test_linear_decay_follows_half_life() {
+        let decay = ConfidenceDecay {
+            function: DecayFunction::Linear,
+            half_life_secs: 10 * DAY_SECS,
+        };
+        for (days, expected) in [(0, 1.0), (5, 0.75), (10, 0.5), (20, 0.0), (40, 0.0)] {
+            assert_close(
+                decayed_confidence(1.0, &decay, Duration::days(days)),
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn test_decay_clamps_negative_age_and_bad_half_life() {
+        let decay = ConfidenceDecay {
+            function: DecayFunction::Exponential,
+            half_life_secs: DAY_SECS,
+        };
+        assert_close(decayed_confidence(0.9, &decay, Duration::days(-3)), 0.9);
+
+        let zero = ConfidenceDecay {
+            function: DecayFunction::Exponential,
+            half_life_secs: 0,
+        };
+        assert_close(decayed_confidence(0.9, &zero, Duration::days(3)), 0.9);
+    }
+
+    #[test]
+    fn test_current_confidence_tsv_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+
+        dfg.handle_update(&CacheUpdate::DeclarationCreated {
+            rkey: "decl1".to_string(),
+            declaration: make_declaration(
+                "rumor",
+                Some(ConfidenceDecay {
+                    function: DecayFunction::Exponential,
+                    half_life_secs: DAY_SECS,
+                }),
+            ),
+        });
+
+        let mut old = make_fact_with_tags("rumor", vec!["x"], vec![]);
+        old.confidence = Some(0.8);
+        old.created_at = Utc::now() - Duration::days(2);
+        dfg.handle_update(&CacheUpdate::FactCreated {
+            rkey: "fact1".to_string(),
+            fact: old,
+        });
+        // No decay declared for this predicate, so no row
+        dfg.handle_update(&CacheUpdate::FactCreated {
+            rkey: "fact2".to_string(),
+            fact: make_fact_with_tags("knows", vec!["y"], vec![]),
+        });
+        assert!(dfg.dirty_predicates.contains("current_confidence"));
+
+        dfg.flush_to_dir(dir.path()).unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join("current_confidence.facts")).unwrap();
+        assert_eq!(
+            content,
+            "at://did:plc:test/diy.razorgirl.winter.fact/fact1\t0.2000\tfact1\n"
+        );
+    }
+
+    #[test]
+    fn test_declaration_changes_update_decay() {
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+        let decay = ConfidenceDecay {
+            function: DecayFunction::Linear,
+            half_life_secs: DAY_SECS,
+        };
+
+        dfg.handle_update(&CacheUpdate::DeclarationCreated {
+            rkey: "decl1".to_string(),
+            declaration: make_declaration("rumor", Some(decay)),
+        });
+        assert_eq!(dfg.decay_by_predicate.get("rumor"), Some(&decay));
+
+        // Removing the decay from the declaration stops decaying the predicate
+        dfg.handle_update(&CacheUpdate::DeclarationUpdated {
+            rkey: "decl1".to_string(),
+            declaration: make_declaration("rumor", None),
+        });
+        assert!(dfg.decay_by_predicate.is_empty());
+
+        dfg.handle_update(&CacheUpdate::DeclarationUpdated {
+            rkey: "decl1".to_string(),
+            declaration: make_declaration("rumor", Some(decay)),
+        });
+        dfg.dirty_predicates.clear();
+        dfg.handle_update(&CacheUpdate::DeclarationDeleted {
+            rkey: "decl1".to_string(),
+        });
+        assert!(dfg.decay_by_predicate.is_empty());
+        assert!(dfg.dirty_predicates.contains("current_confidence"));
+    }
+
     // =========================================================================
     // TSV Generation Tests
     // =========================================================================
@@ -3286,6 +3842,7 @@ mod tests {
             thought: Thought {
                 kind: ThoughtKind::ToolCall,
                 content: "Called query_facts\nArgs:\n{}".to_string(),
+                content_type: None,
                 trigger: Some("internal:tool_call".to_string()),
                 tags: Vec::new(),
                 duration_ms: Some(1234),
@@ -3299,6 +3856,7 @@ mod tests {
             thought: Thought {
                 kind: ThoughtKind::ToolCall,
                 content: "Called list_rules\nArgs:\n{}".to_string(),
+                content_type: None,
                 trigger: Some("internal:tool_call".to_string()),
                 tags: Vec::new(),
                 duration_ms: None,
@@ -3406,9 +3964,10 @@ mod tests {
     fn test_escape_tsv_special_characters() {
         // Test the escape_tsv function directly
         assert_eq!(escape_tsv("normal"), "normal");
-        assert_eq!(escape_tsv("has\ttab"), "has tab");
-        assert_eq!(escape_tsv("has\nnewline"), "has newline");
-        assert_eq!(escape_tsv("combo\t\n"), "combo  ");
+        assert_eq!(escape_tsv("has\ttab"), "has\\ttab");
+        assert_eq!(escape_tsv("has\nnewline"), "has\\nnewline");
+        assert_eq!(escape_tsv("combo\t\n"), "combo\\t\\n");
+        assert_eq!(escape_tsv("back\\slash"), "back\\\\slash");
         assert_eq!(escape_tsv(""), "");
     }
 
@@ -3499,7 +4058,8 @@ mod tests {
 
         // Title should be escaped - no raw tabs or newlines
         assert!(!content.contains("Title\twith"));
-        assert!(content.contains("Title with tabs and newlines"));
+        assert!(content.contains("Title\\twith\\ttabs\\nand\\nnewlines"));
+        assert_eq!(content.lines().count(), 1);
     }
 
     #[test]
@@ -3525,7 +4085,7 @@ mod tests {
         let content = std::fs::read_to_string(dir.path().join("has_blog_post.facts")).unwrap();
 
         // Title should be escaped
-        assert!(content.contains("Blog with tabs"));
+        assert!(content.contains("Blog\\twith\\ttabs"));
     }
 
     // =========================================================================
@@ -4210,4 +4770,149 @@ mod tests {
         assert!(dfg.dirty_predicates.contains("post_mention"));
         assert!(dfg.dirty_predicates.contains("post_hashtag"));
     }
+
+    #[test]
+    fn test_generate_reports_added_and_removed_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+        let predicates: HashSet<String> =
+            ["follows", "liked"].iter().map(|p| p.to_string()).collect();
+
+        for (rkey, subject) in [("f1", "did:plc:alice"), ("f2", "did:plc:bob")] {
+            dfg.handle_update(&CacheUpdate::FollowCreated {
+                rkey: rkey.to_string(),
+                follow: make_follow(subject),
+            });
+        }
+        dfg.handle_update(&CacheUpdate::LikeCreated {
+            rkey: "l1".to_string(),
+            like: make_like("at://did:plc:alice/app.bsky.feed.post/p1"),
+        });
+
+        let first = dfg.generate(dir.path(), &predicates).unwrap();
+        assert_eq!(
+            first.predicates["follows"],
+            PredicateChange {
+                rows: 2,
+                added: 2,
+                removed: 0,
+                diffed: false
+            }
+        );
+        assert_eq!(first.predicates["liked"].added, 1);
+        assert!(!first.souffle_ran);
+        assert!(!dfg.has_dirty_predicates());
+
+        // Swap one follow for another and drop the like
+        dfg.handle_update(&CacheUpdate::FollowDeleted {
+            rkey: "f1".to_string(),
+        });
+        dfg.handle_update(&CacheUpdate::FollowCreated {
+            rkey: "f3".to_string(),
+            follow: make_follow("did:plc:carol"),
+        });
+        dfg.handle_update(&CacheUpdate::LikeDeleted {
+            rkey: "l1".to_string(),
+        });
+
+        let second = dfg.generate(dir.path(), &predicates).unwrap();
+        assert_eq!(
+            second.predicates["follows"],
+            PredicateChange {
+                rows: 2,
+                added: 1,
+                removed: 1,
+                diffed: true
+            }
+        );
+        assert_eq!(
+            second.predicates["liked"],
+            PredicateChange {
+                rows: 0,
+                added: 0,
+                removed: 1,
+                diffed: true
+            }
+        );
+        assert_eq!(second.total_added(), 1);
+        assert_eq!(second.total_removed(), 2);
+
+        // Regenerating without changes reports nothing new
+        let third = dfg.generate(dir.path(), &predicates).unwrap();
+        assert_eq!(third.total_added(), 0);
+        assert_eq!(third.total_removed(), 0);
+        assert_eq!(third.predicates["follows"].rows, 2);
+    }
+
+    #[test]
+    fn test_generate_ignores_unknown_and_tracks_souffle_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+        assert!(dfg.last_generation().is_none());
+
+        // Marking before any generation is a no-op
+        dfg.mark_souffle_ran();
+        assert!(dfg.last_generation().is_none());
+
+        let predicates: HashSet<String> = ["follows", "my_user_fact"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let stats = dfg.generate(dir.path(), &predicates).unwrap();
+        assert_eq!(stats.predicates.len(), 1);
+        assert!(stats.predicates.contains_key("follows"));
+        assert!(!dir.path().join("my_user_fact.facts").exists());
+
+        dfg.mark_souffle_ran();
+        assert!(dfg.last_generation().unwrap().souffle_ran);
+    }
+
+    #[test]
+    fn test_row_hashes_ignore_write_boundaries() {
+        let mut split = RowHashingWriter::new(Vec::new());
+        split.write_all(b"did:a\tdi").unwrap();
+        split.write_all(b"d:b\nlast").unwrap();
+        let mut whole = RowHashingWriter::new(Vec::new());
+        whole.write_all(b"did:a\tdid:b\nlast").unwrap();
+
+        let rows = split.finish().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows, whole.finish().unwrap());
+    }
+
+    #[test]
+    fn test_oversized_predicates_are_not_diffed() {
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+        let rows = || {
+            let hashes: HashSet<u64> = (0..=MAX_DIFFED_ROWS as u64).collect();
+            HashMap::from([("follows".to_string(), hashes)])
+        };
+
+        dfg.record_generation(rows(), std::time::Duration::ZERO);
+        assert!(dfg.generated_rows.is_empty());
+
+        let stats = dfg.record_generation(rows(), std::time::Duration::ZERO);
+        assert!(!stats.predicates["follows"].diffed);
+    }
+
+    #[test]
+    fn test_record_generation_from_snapshot_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dfg = DerivedFactGenerator::new("did:plc:test", "test.handle");
+        dfg.handle_update(&CacheUpdate::FollowCreated {
+            rkey: "f1".to_string(),
+            follow: make_follow("did:plc:alice"),
+        });
+
+        let predicates: HashSet<String> = ["follows".to_string()].into_iter().collect();
+        let rows = dfg
+            .clone_for_flush()
+            .write_predicates_subset(dir.path(), &predicates)
+            .unwrap();
+        let stats = dfg.record_generation(rows, std::time::Duration::from_millis(5));
+
+        assert_eq!(stats.duration_ms, 5);
+        assert_eq!(stats.predicates["follows"].added, 1);
+        assert_eq!(dfg.last_generation().unwrap().total_added(), 1);
+    }
 }
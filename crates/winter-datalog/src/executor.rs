@@ -8,6 +8,7 @@ use tokio::process::Command;
 use tracing::{debug, warn};
 
 use crate::DatalogError;
+use crate::tsv;
 
 /// Cached result of souffle availability check.
 /// 0 = unchecked, 1 = available, 2 = not available
@@ -139,7 +140,7 @@ impl SouffleExecutor {
     }
 
     /// Check if Soufflé is available in PATH (uncached).
-    async fn is_souffle_available() -> bool {
+    pub async fn is_souffle_available() -> bool {
         Command::new("which")
             .arg("souffle")
             .output()
@@ -208,8 +209,9 @@ impl SouffleExecutor {
                     // Skip relation name and column headers
                 }
                 State::InData => {
-                    // Soufflé outputs tab-separated values
-                    let tuple: Vec<String> = line.split('\t').map(String::from).collect();
+                    // Soufflé outputs tab-separated values, still escaped as
+                    // they were written to the input files
+                    let tuple = tsv::parse_row(line);
                     if !tuple.is_empty() && !tuple[0].is_empty() {
                         results.push(tuple);
                     }
@@ -308,4 +310,14 @@ did:a\tpost:1
         assert_eq!(results[0], vec!["did:a", "did:b"]);
         assert_eq!(results[1], vec!["did:c", "did:d"]);
     }
+
+    #[test]
+    fn test_parse_output_unescapes_fields() {
+        let output =
+            "===============\nresult\n===============\nhas\\ttab\tline\\nbreak\tback\\\\slash\n";
+        let results = SouffleExecutor::parse_output(output);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0], vec!["has\ttab", "line\nbreak", "back\\slash"]);
+    }
 }
@@ -1,20 +1,56 @@
 //! Extract facts from ATProto records to TSV format.
 
-use std::collections::{HashMap, HashSet};
-use std::io::Write;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use winter_atproto::{AtUri, Fact, ListRecordItem};
 
 use crate::DatalogError;
 use crate::cache::CachedFactData;
+use crate::supersession::SupersessionIndex;
+use crate::tsv;
+
+/// Metadata relations produced by extraction, in output order.
+const META_RELATIONS: &[&str] = &[
+    "_fact",
+    "_confidence",
+    "_source",
+    "_supersedes",
+    "_created_at",
+    "_expires_at",
+];
 
 /// Result of extracting facts to TSV files.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExtractResult {
     /// User-defined predicates (e.g., "follows", "interested_in").
     pub predicates: Vec<String>,
     /// Metadata relation names that were generated.
     pub meta_relations: Vec<&'static str>,
+    /// Rows of every relation, keyed by relation name (file name without
+    /// `.facts`). Values are unescaped.
+    pub relations: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+impl ExtractResult {
+    /// Write each relation to `{name}.facts` in `dir`, in the format the
+    /// executor consumes.
+    ///
+    /// Fields are escaped as described in the [`tsv`] module, so values
+    /// containing tabs, newlines, or backslashes survive a round trip through
+    /// [`FactExtractor::from_tsv_dir`].
+    pub fn to_tsv_files(&self, dir: &Path) -> Result<(), DatalogError> {
+        for (name, rows) in &self.relations {
+            let file = std::fs::File::create(dir.join(format!("{}.facts", name)))?;
+            let mut file = BufWriter::new(file);
+            for row in rows {
+                writeln!(file, "{}", tsv::format_row(row))?;
+            }
+            file.flush()?;
+        }
+        Ok(())
+    }
 }
 
 /// Extracts facts from ATProto records to TSV files for Soufflé.
@@ -34,34 +70,51 @@ impl FactExtractor {
         facts: &[ListRecordItem<Fact>],
         output_dir: &Path,
     ) -> Result<ExtractResult, DatalogError> {
-        use std::fs::File;
+        Self::extract_to_dir_with_options(facts, output_dir, true)
+    }
+
+    /// Extract facts to a directory of TSV files, optionally keeping
+    /// superseded versions in the current `{predicate}.facts` files.
+    ///
+    /// With `latest_only` set this is `extract_to_dir`: each supersession
+    /// chain contributes only its head. Without it, `{predicate}.facts`
+    /// matches `_all_{predicate}.facts`.
+    pub fn extract_to_dir_with_options(
+        facts: &[ListRecordItem<Fact>],
+        output_dir: &Path,
+        latest_only: bool,
+    ) -> Result<ExtractResult, DatalogError> {
+        let result = Self::extract(facts, latest_only);
+        result.to_tsv_files(output_dir)?;
+        Ok(result)
+    }
 
-        // Build CID -> rkey map for supersession lookups
-        let cid_to_rkey: HashMap<&str, &str> = facts
+    /// Extract facts into in-memory relations without writing any files.
+    ///
+    /// Produces the same relations as `extract_to_dir_with_options`; call
+    /// [`ExtractResult::to_tsv_files`] to write them.
+    pub fn extract(facts: &[ListRecordItem<Fact>], latest_only: bool) -> ExtractResult {
+        // Soft-deleted facts are hidden from queries as if they were gone
+        let facts: Vec<&ListRecordItem<Fact>> = facts
             .iter()
-            .map(|item| (item.cid.as_str(), AtUri::extract_rkey(&item.uri)))
+            .filter(|item| item.value.deleted_at.is_none())
             .collect();
 
-        // Collect superseded CIDs (facts that have been replaced)
-        let superseded_cids: HashSet<&str> = facts
+        // Resolve supersedes references (AT URI, CID, or rkey) to rkeys
+        let supersession = SupersessionIndex::build(facts.iter().map(|item| {
+            (
+                AtUri::extract_rkey(&item.uri),
+                &item.value,
+                item.cid.as_str(),
+            )
+        }));
+
+        let mut relations: BTreeMap<String, Vec<Vec<String>>> = META_RELATIONS
             .iter()
-            .filter_map(|item| item.value.supersedes.as_deref())
+            .map(|name| (name.to_string(), Vec::new()))
             .collect();
-
-        // Track files for each predicate (current facts only)
-        let mut current_files: HashMap<String, File> = HashMap::new();
-        // Track files for _all_{predicate} (all facts with rkey)
-        let mut all_files: HashMap<String, File> = HashMap::new();
         let mut predicates = Vec::new();
 
-        // Metadata relation files
-        let mut fact_file = File::create(output_dir.join("_fact.facts"))?;
-        let mut confidence_file = File::create(output_dir.join("_confidence.facts"))?;
-        let mut source_file = File::create(output_dir.join("_source.facts"))?;
-        let mut supersedes_file = File::create(output_dir.join("_supersedes.facts"))?;
-        let mut created_at_file = File::create(output_dir.join("_created_at.facts"))?;
-        let mut expires_at_file = File::create(output_dir.join("_expires_at.facts"))?;
-
         let now = chrono::Utc::now();
 
         for item in facts {
@@ -69,78 +122,131 @@ impl FactExtractor {
             let predicate = &fact.predicate;
             let rkey = AtUri::extract_rkey(&item.uri);
             let cid = &item.cid;
-            let args = fact.args.join("\t");
-            let is_superseded = superseded_cids.contains(cid.as_str());
+            let is_superseded = supersession.is_superseded(rkey);
             let is_expired = fact.expires_at.map_or(false, |ea| ea <= now);
-            let is_current = !is_superseded && !is_expired;
-
-            // Ensure predicate files exist
-            if !current_files.contains_key(predicate) {
-                let current_path = output_dir.join(format!("{}.facts", predicate));
-                let all_path = output_dir.join(format!("_all_{}.facts", predicate));
-                current_files.insert(predicate.clone(), File::create(&current_path)?);
-                all_files.insert(predicate.clone(), File::create(&all_path)?);
+            let is_current = !latest_only || (!is_superseded && !is_expired);
+
+            // Ensure predicate relations exist
+            let all_name = format!("_all_{}", predicate);
+            if !relations.contains_key(&all_name) {
+                relations.insert(predicate.clone(), Vec::new());
+                relations.insert(all_name.clone(), Vec::new());
                 predicates.push(predicate.clone());
             }
 
-            // Write to current predicate file (only non-superseded facts)
             // Format: args..., rkey (rkey at end)
+            let mut row = fact.args.clone();
+            row.push(rkey.to_string());
+
+            // Current predicate (only non-superseded facts)
             if is_current {
-                let file = current_files.get_mut(predicate).unwrap();
-                writeln!(file, "{}\t{}", args, rkey)?;
+                relations
+                    .get_mut(predicate)
+                    .expect("relation inserted above")
+                    .push(row.clone());
             }
 
-            // Write to _all_{predicate} file (all facts with rkey at end)
-            // Format: args..., rkey (rkey at end, same as current)
-            let all_file = all_files.get_mut(predicate).unwrap();
-            writeln!(all_file, "{}\t{}", args, rkey)?;
-
-            // Write to _fact.facts (rkey, predicate, cid)
-            writeln!(fact_file, "{}\t{}\t{}", rkey, predicate, cid)?;
+            // _all_{predicate} (all facts, same format as current)
+            relations
+                .get_mut(&all_name)
+                .expect("relation inserted above")
+                .push(row);
+
+            let mut push_meta = |name: &str, row: Vec<String>| {
+                relations
+                    .get_mut(name)
+                    .expect("metadata relations are pre-populated")
+                    .push(row);
+            };
+
+            // _fact (rkey, predicate, cid)
+            push_meta(
+                "_fact",
+                vec![rkey.to_string(), predicate.clone(), cid.clone()],
+            );
 
-            // Write to _confidence.facts (sparse - only non-1.0 values)
+            // _confidence (sparse - only non-1.0 values)
             if let Some(conf) = fact.confidence
                 && (conf - 1.0).abs() > f64::EPSILON
             {
-                writeln!(confidence_file, "{}\t{}", rkey, conf)?;
+                push_meta("_confidence", vec![rkey.to_string(), conf.to_string()]);
             }
 
-            // Write to _source.facts (sparse - only if set)
+            // _source (sparse - only if set)
             if let Some(ref source) = fact.source {
-                writeln!(source_file, "{}\t{}", rkey, source)?;
+                push_meta("_source", vec![rkey.to_string(), source.clone()]);
             }
 
-            // Write to _supersedes.facts (new_rkey, old_rkey)
-            if let Some(ref old_cid) = fact.supersedes
-                && let Some(old_rkey) = cid_to_rkey.get(old_cid.as_str())
-            {
-                writeln!(supersedes_file, "{}\t{}", rkey, old_rkey)?;
+            // _created_at (dense - every fact)
+            push_meta(
+                "_created_at",
+                vec![rkey.to_string(), fact.created_at.to_rfc3339()],
+            );
+
+            // _expires_at (sparse - only if set)
+            if let Some(ref ea) = fact.expires_at {
+                push_meta("_expires_at", vec![rkey.to_string(), ea.to_rfc3339()]);
             }
+        }
 
-            // Write to _created_at.facts (dense - every fact)
-            writeln!(
-                created_at_file,
-                "{}\t{}",
-                rkey,
-                fact.created_at.to_rfc3339()
-            )?;
+        // _supersedes (new_rkey, old_rkey)
+        let supersedes = relations
+            .get_mut("_supersedes")
+            .expect("metadata relations are pre-populated");
+        for (new_rkey, old_rkey) in supersession.links() {
+            supersedes.push(vec![new_rkey.to_string(), old_rkey.to_string()]);
+        }
 
-            // Write to _expires_at.facts (sparse - only if set)
-            if let Some(ref ea) = fact.expires_at {
-                writeln!(expires_at_file, "{}\t{}", rkey, ea.to_rfc3339())?;
+        ExtractResult {
+            predicates,
+            meta_relations: META_RELATIONS.to_vec(),
+            relations,
+        }
+    }
+
+    /// Load a directory of TSV files written by [`ExtractResult::to_tsv_files`].
+    ///
+    /// Every `*.facts` file becomes a relation. A relation counts as a user
+    /// predicate when its `_all_` counterpart is also present, matching what
+    /// extraction produces; other files (derived predicates, for instance)
+    /// are loaded as plain relations.
+    pub fn from_tsv_dir(dir: &Path) -> Result<ExtractResult, DatalogError> {
+        let mut relations = BTreeMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("facts") {
+                continue;
             }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let content = std::fs::read_to_string(&path)?;
+            let rows: Vec<Vec<String>> = content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(tsv::parse_row)
+                .collect();
+            relations.insert(name.to_string(), rows);
         }
 
+        let predicates = relations
+            .keys()
+            .filter(|name| {
+                !name.starts_with('_') && relations.contains_key(&format!("_all_{}", name))
+            })
+            .cloned()
+            .collect();
+        let meta_relations = META_RELATIONS
+            .iter()
+            .copied()
+            .filter(|name| relations.contains_key(*name))
+            .collect();
+
         Ok(ExtractResult {
             predicates,
-            meta_relations: vec![
-                "_fact",
-                "_confidence",
-                "_source",
-                "_supersedes",
-                "_created_at",
-                "_expires_at",
-            ],
+            meta_relations,
+            relations,
         })
     }
 
@@ -152,7 +258,7 @@ impl FactExtractor {
     ) -> (String, HashSet<String>) {
         // Determine arity for each predicate
         let mut arities: HashMap<&str, usize> = HashMap::new();
-        for item in facts {
+        for item in facts.iter().filter(|item| item.value.deleted_at.is_none()) {
             let fact = &item.value;
             arities
                 .entry(&fact.predicate)
@@ -320,7 +426,7 @@ impl FactExtractor {
                 continue;
             }
 
-            let args = data.fact.args.join("\t");
+            let args = tsv::format_row(&data.fact.args);
 
             // Write to all file (always, rkey at end)
             writeln!(all_file, "{}\t{}", args, rkey)?;
@@ -367,6 +473,7 @@ mod tests {
                 tags: vec![],
                 created_at: Utc::now(),
                 expires_at: None,
+                deleted_at: None,
             },
         }
     }
@@ -451,6 +558,53 @@ mod tests {
         assert!(all.contains("did:a\tdid:c")); // new fact included
     }
 
+    #[test]
+    fn test_soft_deleted_facts_excluded() {
+        let mut deleted = make_fact_with_meta("mood", vec!["calm"], None, None, None, "v1");
+        deleted.value.deleted_at = Some(Utc::now());
+        let kept = make_fact("mood", vec!["curious"]);
+
+        let result = FactExtractor::extract(&[deleted, kept], true);
+
+        assert_eq!(
+            result.relations["mood"],
+            vec![vec!["curious", "rkey-test-cid"]]
+        );
+        assert_eq!(result.relations["_all_mood"].len(), 1);
+        assert_eq!(result.relations["_fact"].len(), 1);
+    }
+
+    #[test]
+    fn test_supersession_chain_latest_only() {
+        // v1 <- v2 (by CID) <- v3 (by AT URI)
+        let facts = vec![
+            make_fact_with_meta("mood", vec!["calm"], None, None, None, "v1"),
+            make_fact_with_meta("mood", vec!["curious"], None, None, Some("v1"), "v2"),
+            make_fact_with_meta(
+                "mood",
+                vec!["focused"],
+                None,
+                None,
+                Some("at://did:test/diy.razorgirl.winter.fact/rkey-v2"),
+                "v3",
+            ),
+        ];
+
+        let dir = tempdir().unwrap();
+        FactExtractor::extract_to_dir_with_options(&facts, dir.path(), true).unwrap();
+        let current = std::fs::read_to_string(dir.path().join("mood.facts")).unwrap();
+        assert_eq!(current, "focused\trkey-v3\n");
+
+        let supersedes = std::fs::read_to_string(dir.path().join("_supersedes.facts")).unwrap();
+        assert!(supersedes.contains("rkey-v2\trkey-v1"));
+        assert!(supersedes.contains("rkey-v3\trkey-v2"));
+
+        let dir = tempdir().unwrap();
+        FactExtractor::extract_to_dir_with_options(&facts, dir.path(), false).unwrap();
+        let current = std::fs::read_to_string(dir.path().join("mood.facts")).unwrap();
+        assert_eq!(current.lines().count(), 3);
+    }
+
     #[test]
     fn test_confidence_sparse_output() {
         let dir = tempdir().unwrap();
@@ -664,4 +818,92 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_tsv_roundtrip_with_special_characters() {
+        let dir = tempdir().unwrap();
+        let facts = vec![
+            make_fact("said", vec!["did:a", "hello\tworld"]),
+            make_fact_with_meta(
+                "said",
+                vec!["did:b", "line one\nline two"],
+                Some(0.5),
+                Some("C:\\notes\\today"),
+                None,
+                "cid2",
+            ),
+            make_fact_with_meta(
+                "path",
+                vec!["back\\slash", "literal \\t", "crlf\r\n"],
+                None,
+                None,
+                None,
+                "cid3",
+            ),
+        ];
+
+        let extracted = FactExtractor::extract_to_dir(&facts, dir.path()).unwrap();
+
+        // Escaping keeps one row per line and the right number of columns
+        let said = std::fs::read_to_string(dir.path().join("said.facts")).unwrap();
+        assert_eq!(said.lines().count(), 2);
+        for line in said.lines() {
+            assert_eq!(line.split('\t').count(), 3);
+        }
+
+        let loaded = FactExtractor::from_tsv_dir(dir.path()).unwrap();
+        assert_eq!(loaded.relations, extracted.relations);
+        assert_eq!(loaded.meta_relations, extracted.meta_relations);
+        let mut predicates = extracted.predicates.clone();
+        predicates.sort();
+        assert_eq!(loaded.predicates, predicates);
+
+        assert_eq!(
+            loaded.relations["path"],
+            vec![vec![
+                "back\\slash".to_string(),
+                "literal \\t".to_string(),
+                "crlf\r\n".to_string(),
+                "rkey-cid3".to_string(),
+            ]]
+        );
+        assert_eq!(
+            loaded.relations["_source"],
+            vec![vec![
+                "rkey-cid2".to_string(),
+                "C:\\notes\\today".to_string()
+            ]]
+        );
+
+        // Writing the loaded state reproduces the original files
+        let copy = tempdir().unwrap();
+        loaded.to_tsv_files(copy.path()).unwrap();
+        for name in loaded.relations.keys() {
+            let file = format!("{}.facts", name);
+            assert_eq!(
+                std::fs::read_to_string(copy.path().join(&file)).unwrap(),
+                std::fs::read_to_string(dir.path().join(&file)).unwrap(),
+                "{} differs",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_tsv_dir_loads_non_fact_relations() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("follows.facts"), "did:me\tdid:you\tr1\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let loaded = FactExtractor::from_tsv_dir(dir.path()).unwrap();
+
+        // No _all_follows, so it's a plain relation rather than a user predicate
+        assert!(loaded.predicates.is_empty());
+        assert!(loaded.meta_relations.is_empty());
+        assert_eq!(loaded.relations.len(), 1);
+        assert_eq!(
+            loaded.relations["follows"][0],
+            vec!["did:me", "did:you", "r1"]
+        );
+    }
 }
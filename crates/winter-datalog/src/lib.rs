@@ -15,13 +15,27 @@ pub mod derived;
 mod error;
 mod executor;
 mod extractor;
+mod query_cache;
+mod query_check;
+mod supersession;
+pub mod tsv;
 mod validator;
+mod why_not;
 
-pub use cache::{CachedFactData, DatalogCache};
+pub use cache::{CachedFactData, DatalogCache, DerivedWriteGuard};
 pub use compiler::RuleCompiler;
-pub use dependency::PredicateDependencyGraph;
-pub use derived::{DerivedFactGenerator, DerivedFactStats, PredicateInfo};
+pub use dependency::{
+    DependencyGraphExport, GraphEdge, GraphNode, GraphNodeKind, PredicateDependencyGraph,
+};
+pub use derived::{
+    DerivedFactGenerator, DerivedFactStats, GenerationStats, PredicateChange, PredicateInfo,
+    decayed_confidence,
+};
 pub use error::DatalogError;
 pub use executor::SouffleExecutor;
 pub use extractor::{ExtractResult, FactExtractor};
+pub use query_cache::{QueryCacheStats, QueryResultCache};
+pub use query_check::{QueryCheckError, check_query, query_atoms, suggest};
+pub use supersession::SupersessionIndex;
 pub use validator::{ValidationError, validate_fact_against_declaration};
+pub use why_not::{RuleDiagnosis, RuleOutcome, WhyNotReport};
@@ -0,0 +1,268 @@
+//! Memoized query results.
+//!
+//! Identical queries against unchanged data return the same rows, so the
+//! cache keeps recent results keyed by the normalized query text. Each entry
+//! records the data version it was computed against; a lookup under any other
+//! version is a miss, and changing the version drops every entry.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+
+/// Default number of query results to keep.
+pub const DEFAULT_QUERY_CACHE_CAPACITY: usize = 256;
+
+/// Results with more rows than this are not cached.
+pub const MAX_CACHED_ROWS: usize = 10_000;
+
+/// Hit/miss counters for the query result cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries currently cached.
+    pub entries: usize,
+    pub capacity: usize,
+    /// Entries dropped to stay within capacity.
+    pub evictions: u64,
+    /// Times the whole cache was cleared because the data changed.
+    pub invalidations: u64,
+}
+
+/// A cached result and the data version it was computed against.
+#[derive(Debug)]
+struct Entry {
+    version: u64,
+    rows: Vec<Vec<String>>,
+}
+
+/// Size-bounded, least-recently-used cache of query results.
+#[derive(Debug)]
+pub struct QueryResultCache {
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    /// Keys from least to most recently used.
+    order: VecDeque<String>,
+    /// Data version of the current contents.
+    version: u64,
+    stats: QueryCacheStats,
+}
+
+impl QueryResultCache {
+    /// Create a cache holding at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            version: 0,
+            stats: QueryCacheStats {
+                capacity,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Look up a result computed against `version`.
+    pub fn get(&mut self, key: &str, version: u64) -> Option<Vec<Vec<String>>> {
+        self.sync_version(version);
+
+        let rows = match self.entries.get(key) {
+            Some(entry) if entry.version == version => entry.rows.clone(),
+            _ => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+
+        self.touch(key);
+        self.stats.hits += 1;
+        Some(rows)
+    }
+
+    /// Store a result computed against `version`.
+    ///
+    /// Results from an older version (the data changed while the query ran)
+    /// and oversized results are dropped.
+    pub fn insert(&mut self, key: String, version: u64, rows: Vec<Vec<String>>) {
+        if self.capacity == 0 || rows.len() > MAX_CACHED_ROWS {
+            return;
+        }
+        self.sync_version(version);
+        if version != self.version {
+            return;
+        }
+
+        let replaced = self.entries.contains_key(&key);
+        self.entries.insert(key.clone(), Entry { version, rows });
+        if replaced {
+            self.touch(&key);
+        } else {
+            self.order.push_back(key);
+        }
+
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Drop every cached result.
+    pub fn invalidate(&mut self) {
+        if !self.entries.is_empty() {
+            self.entries.clear();
+            self.order.clear();
+            self.stats.invalidations += 1;
+        }
+    }
+
+    /// Current hit/miss counters.
+    pub fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// Move to a newer data version, dropping results from older ones.
+    fn sync_version(&mut self, version: u64) {
+        if version > self.version {
+            self.invalidate();
+            self.version = version;
+        }
+    }
+
+    /// Mark `key` as most recently used.
+    fn touch(&mut self, key: &str) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(pos).expect("position is in bounds");
+            self.order.push_back(key);
+        }
+    }
+}
+
+impl Default for QueryResultCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUERY_CACHE_CAPACITY)
+    }
+}
+
+/// Normalize query text for use in a cache key.
+///
+/// Collapses runs of whitespace outside quoted strings, so queries that
+/// differ only in formatting share an entry.
+pub fn normalize_query(query: &str) -> String {
+    let mut out = String::with_capacity(query.len());
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut pending_space = false;
+
+    for c in query.trim().chars() {
+        if in_quotes {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_quotes = false;
+            }
+            continue;
+        }
+
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space {
+            out.push(' ');
+            pending_space = false;
+        }
+        if c == '"' {
+            in_quotes = true;
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&str]) -> Vec<Vec<String>> {
+        values.iter().map(|v| vec![v.to_string()]).collect()
+    }
+
+    #[test]
+    fn test_hit_after_insert() {
+        let mut cache = QueryResultCache::new(4);
+        assert_eq!(cache.get("q", 1), None);
+
+        cache.insert("q".to_string(), 1, rows(&["a"]));
+        assert_eq!(cache.get("q", 1), Some(rows(&["a"])));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn test_new_version_invalidates() {
+        let mut cache = QueryResultCache::new(4);
+        cache.insert("q".to_string(), 1, rows(&["a"]));
+
+        assert_eq!(cache.get("q", 2), None);
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().invalidations, 1);
+
+        // A result computed before the change must not be stored
+        cache.insert("q".to_string(), 1, rows(&["stale"]));
+        assert_eq!(cache.get("q", 2), None);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = QueryResultCache::new(2);
+        cache.insert("a".to_string(), 1, rows(&["1"]));
+        cache.insert("b".to_string(), 1, rows(&["2"]));
+
+        // Touch "a" so "b" becomes the oldest
+        assert!(cache.get("a", 1).is_some());
+        cache.insert("c".to_string(), 1, rows(&["3"]));
+
+        assert!(cache.get("a", 1).is_some());
+        assert!(cache.get("b", 1).is_none());
+        assert!(cache.get("c", 1).is_some());
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_skips_oversized_results() {
+        let mut cache = QueryResultCache::new(4);
+        let big: Vec<Vec<String>> = (0..=MAX_CACHED_ROWS).map(|i| vec![i.to_string()]).collect();
+        cache.insert("big".to_string(), 1, big);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("  follows( X,\n   Y, _ )  "),
+            "follows( X, Y, _ )"
+        );
+        assert_eq!(
+            normalize_query(r#"note("a   b",  X)"#),
+            r#"note("a   b", X)"#
+        );
+        assert_eq!(
+            normalize_query(r#"note("say \"hi  there\"",   X)"#),
+            r#"note("say \"hi  there\"", X)"#
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Pre-flight checks for datalog queries.
+//!
+//! Soufflé reports mistakes in a query as errors against the generated
+//! program, mentioning wrapper relations and line numbers the caller never
+//! wrote. [`check_query`] catches the common mistakes (misspelled predicates,
+//! wrong argument counts, unbalanced parentheses or quotes) against the known
+//! predicate signatures and explains them in terms of the query itself.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::dependency::is_valid_predicate_name;
+
+/// A problem found in a query before it reaches Soufflé.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryCheckError {
+    /// The query doesn't parse (unbalanced parentheses, unterminated string).
+    Syntax(String),
+    /// The query uses a predicate nothing declares.
+    UnknownPredicate {
+        name: String,
+        suggestion: Option<String>,
+    },
+    /// The query uses a predicate with the wrong number of arguments.
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for QueryCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryCheckError::Syntax(msg) => write!(f, "syntax error: {}", msg),
+            QueryCheckError::UnknownPredicate {
+                name,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "predicate `{}` unknown, did you mean `{}`?",
+                name, suggestion
+            ),
+            QueryCheckError::UnknownPredicate {
+                name,
+                suggestion: None,
+            } => write!(f, "predicate `{}` unknown", name),
+            QueryCheckError::ArityMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "predicate `{}` takes {} argument{}, got {}",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                actual
+            ),
+        }
+    }
+}
+
+/// Extract the predicate atoms in a query as `(name, arity)` pairs, in order.
+///
+/// Soufflé functors (`cat`, `strlen`, ...) are skipped. Fails if the query
+/// has an unterminated string or unbalanced parentheses.
+pub fn query_atoms(query: &str) -> Result<Vec<(String, usize)>, QueryCheckError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut atoms = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            i = skip_string(&chars, i)?;
+            continue;
+        }
+        if c == '(' {
+            depth += 1;
+        } else if c == ')' {
+            depth = depth
+                .checked_sub(1)
+                .ok_or_else(|| QueryCheckError::Syntax("unexpected `)`".to_string()))?;
+        } else if is_ident_start(c) && (i == 0 || !is_ident_char(chars[i - 1])) {
+            let start = i;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let mut j = i;
+            while j < chars.len() && chars[j].is_whitespace() {
+                j += 1;
+            }
+            if j < chars.len() && chars[j] == '(' && is_valid_predicate_name(&name) {
+                atoms.push((name, count_args(&chars, j)?));
+            }
+            continue;
+        }
+        i += 1;
+    }
+
+    if depth > 0 {
+        return Err(QueryCheckError::Syntax("unclosed `(`".to_string()));
+    }
+    Ok(atoms)
+}
+
+/// Check every atom in `query` against `known` predicate arities.
+///
+/// Atoms are checked in order and the first problem is reported. Unknown
+/// predicates get a suggestion when a known name is within a couple of edits.
+pub fn check_query(query: &str, known: &HashMap<String, usize>) -> Result<(), QueryCheckError> {
+    for (name, arity) in query_atoms(query)? {
+        match known.get(&name) {
+            Some(&expected) if expected != arity => {
+                return Err(QueryCheckError::ArityMismatch {
+                    name,
+                    expected,
+                    actual: arity,
+                });
+            }
+            Some(_) => {}
+            None => {
+                let suggestion = suggest(&name, known.keys().map(String::as_str));
+                return Err(QueryCheckError::UnknownPredicate { name, suggestion });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the known name closest to `name`, if any is close enough to be a typo.
+pub fn suggest<'a>(name: &str, known: impl Iterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    known
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            row[j + 1] = substitution.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Count the arguments of the parenthesised list opening at `open`.
+fn count_args(chars: &[char], open: usize) -> Result<usize, QueryCheckError> {
+    let mut depth = 0usize;
+    let mut commas = 0;
+    let mut empty = true;
+    let mut i = open;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                empty = false;
+                i = skip_string(chars, i)?;
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(if empty { 0 } else { commas + 1 });
+                }
+            }
+            ',' if depth == 1 => commas += 1,
+            c if !c.is_whitespace() => empty = false,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Err(QueryCheckError::Syntax("unclosed `(`".to_string()))
+}
+
+/// Return the index just past the string literal starting at `start`.
+fn skip_string(chars: &[char], start: usize) -> Result<usize, QueryCheckError> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '"' => return Ok(i + 1),
+            _ => i += 1,
+        }
+    }
+    Err(QueryCheckError::Syntax("unterminated string".to_string()))
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known() -> HashMap<String, usize> {
+        HashMap::from([
+            ("follows".to_string(), 3),
+            ("_all_follows".to_string(), 3),
+            ("_confidence".to_string(), 2),
+            ("is_followed_by".to_string(), 1),
+        ])
+    }
+
+    #[test]
+    fn test_query_atoms() {
+        let atoms =
+            query_atoms(r#"follows(X, "did:plc:a,b", R), _confidence(R, C), C < 0.8"#).unwrap();
+        assert_eq!(
+            atoms,
+            vec![("follows".to_string(), 3), ("_confidence".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_query_atoms_skips_functors_and_counts_nested_args() {
+        let atoms = query_atoms(r#"follows(X, cat(Y, "z"), _), strlen(X) > 3, !p()"#).unwrap();
+        assert_eq!(
+            atoms,
+            vec![("follows".to_string(), 3), ("p".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_query_atoms_syntax_errors() {
+        assert_eq!(
+            query_atoms("follows(X, Y"),
+            Err(QueryCheckError::Syntax("unclosed `(`".to_string()))
+        );
+        assert_eq!(
+            query_atoms("follows(X, Y))"),
+            Err(QueryCheckError::Syntax("unexpected `)`".to_string()))
+        );
+        assert_eq!(
+            query_atoms(r#"follows(X, "oops)"#),
+            Err(QueryCheckError::Syntax("unterminated string".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_check_query_accepts_known() {
+        assert!(check_query("follows(X, Y, _), is_followed_by(X)", &known()).is_ok());
+    }
+
+    #[test]
+    fn test_check_query_suggests_close_predicate() {
+        let err = check_query("follws(X, Y, _)", &known()).unwrap_err();
+        assert_eq!(
+            err,
+            QueryCheckError::UnknownPredicate {
+                name: "follws".to_string(),
+                suggestion: Some("follows".to_string()),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "predicate `follws` unknown, did you mean `follows`?"
+        );
+    }
+
+    #[test]
+    fn test_check_query_unknown_without_suggestion() {
+        let err = check_query("mood(X)", &known()).unwrap_err();
+        assert_eq!(err.to_string(), "predicate `mood` unknown");
+    }
+
+    #[test]
+    fn test_check_query_arity_mismatch() {
+        let err = check_query("follows(X, Y, _), _confidence(R)", &known()).unwrap_err();
+        assert_eq!(
+            err,
+            QueryCheckError::ArityMismatch {
+                name: "_confidence".to_string(),
+                expected: 2,
+                actual: 1,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "predicate `_confidence` takes 2 arguments, got 1"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("follows", "follows"), 0);
+        assert_eq!(edit_distance("follws", "follows"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,215 @@
+//! Supersession chain resolution for facts.
+//!
+//! A fact's `supersedes` field links it to the fact it replaces. Current
+//! records store the AT URI of the replaced fact, but facts written before the
+//! URI migration may still carry its CID, and hand-written facts occasionally
+//! use a bare rkey. The index resolves all three forms to rkeys so callers can
+//! follow chains and pick out their heads.
+
+use std::collections::{HashMap, HashSet};
+
+use winter_atproto::{AtUri, Fact};
+
+/// Resolved supersession links between facts, keyed by rkey.
+#[derive(Debug, Clone, Default)]
+pub struct SupersessionIndex {
+    /// (new_rkey, old_rkey) pairs for every resolvable `supersedes` link.
+    links: Vec<(String, String)>,
+    /// Superseded rkey -> rkey of the newest fact that replaced it.
+    successors: HashMap<String, String>,
+}
+
+impl SupersessionIndex {
+    /// Build an index from `(rkey, fact, cid)` triples.
+    ///
+    /// References that don't resolve to a fact in the input are ignored, so a
+    /// fact whose predecessor was deleted simply starts a new chain. When
+    /// several facts supersede the same one, the most recently created wins.
+    pub fn build<'a>(facts: impl IntoIterator<Item = (&'a str, &'a Fact, &'a str)>) -> Self {
+        let facts: Vec<(&str, &Fact, &str)> = facts.into_iter().collect();
+
+        let by_rkey: HashMap<&str, &Fact> = facts.iter().map(|(r, f, _)| (*r, *f)).collect();
+        let cid_to_rkey: HashMap<&str, &str> = facts
+            .iter()
+            .filter(|(_, _, cid)| !cid.is_empty())
+            .map(|(rkey, _, cid)| (*cid, *rkey))
+            .collect();
+
+        let mut links = Vec::new();
+        let mut successors: HashMap<String, String> = HashMap::new();
+
+        for &(rkey, fact, _) in &facts {
+            let Some(reference) = fact.supersedes.as_deref() else {
+                continue;
+            };
+            let Some(old_rkey) = resolve_reference(
+                reference,
+                |cid| cid_to_rkey.get(cid).copied(),
+                |r| by_rkey.contains_key(r),
+            ) else {
+                continue;
+            };
+            if old_rkey == rkey {
+                continue;
+            }
+
+            links.push((rkey.to_string(), old_rkey.to_string()));
+
+            let replace = match successors.get(old_rkey) {
+                Some(existing) => {
+                    let existing_created = by_rkey.get(existing.as_str()).map(|f| f.created_at);
+                    (Some(fact.created_at), rkey) > (existing_created, existing.as_str())
+                }
+                None => true,
+            };
+            if replace {
+                successors.insert(old_rkey.to_string(), rkey.to_string());
+            }
+        }
+
+        Self { links, successors }
+    }
+
+    /// Whether another fact supersedes this one.
+    pub fn is_superseded(&self, rkey: &str) -> bool {
+        self.successors.contains_key(rkey)
+    }
+
+    /// The rkey of the fact that directly supersedes this one, if any.
+    pub fn superseded_by(&self, rkey: &str) -> Option<&str> {
+        self.successors.get(rkey).map(String::as_str)
+    }
+
+    /// Follow the chain from `rkey` to its newest version.
+    ///
+    /// Returns `rkey` itself when nothing supersedes it. Cycles are broken at
+    /// the first repeated rkey.
+    pub fn head<'a>(&'a self, rkey: &'a str) -> &'a str {
+        let mut current = rkey;
+        let mut seen = HashSet::new();
+        while let Some(next) = self.superseded_by(current) {
+            if !seen.insert(current) {
+                break;
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// All resolved links as (new_rkey, old_rkey) pairs.
+    pub fn links(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.links
+            .iter()
+            .map(|(new, old)| (new.as_str(), old.as_str()))
+    }
+}
+
+/// Resolve a `supersedes` reference (AT URI, CID, or rkey) to an rkey.
+///
+/// `rkey_for_cid` maps a CID to the rkey of the fact it identifies, and
+/// `is_known_rkey` reports whether a candidate rkey belongs to a known fact.
+pub fn resolve_reference<'a>(
+    reference: &'a str,
+    rkey_for_cid: impl Fn(&str) -> Option<&'a str>,
+    is_known_rkey: impl Fn(&str) -> bool,
+) -> Option<&'a str> {
+    if reference.starts_with("at://") {
+        let rkey = AtUri::extract_rkey(reference);
+        return is_known_rkey(rkey).then_some(rkey);
+    }
+    if let Some(rkey) = rkey_for_cid(reference) {
+        return Some(rkey);
+    }
+    is_known_rkey(reference).then_some(reference)
+}
+
+/// Whether a `supersedes` reference points at the fact with `rkey` and `cid`.
+///
+/// The counterpart to [`resolve_reference`] for checking a single fact.
+pub fn refers_to(reference: &str, rkey: &str, cid: &str) -> bool {
+    if reference.starts_with("at://") {
+        return AtUri::extract_rkey(reference) == rkey;
+    }
+    (!cid.is_empty() && reference == cid) || reference == rkey
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn make_fact(supersedes: Option<&str>, age_mins: i64) -> Fact {
+        Fact {
+            predicate: "mood".to_string(),
+            args: vec!["calm".to_string()],
+            confidence: None,
+            source: None,
+            supersedes: supersedes.map(String::from),
+            tags: vec![],
+            created_at: Utc::now() - Duration::minutes(age_mins),
+            expires_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_three_deep_chain_resolves_to_head() {
+        // a <- b (by URI) <- c (by CID)
+        let a = make_fact(None, 30);
+        let b = make_fact(Some("at://did:plc:x/diy.razorgirl.winter.fact/a"), 20);
+        let c = make_fact(Some("cid-b"), 10);
+        let facts = [("a", &a, "cid-a"), ("b", &b, "cid-b"), ("c", &c, "cid-c")];
+
+        let index = SupersessionIndex::build(facts);
+
+        assert!(index.is_superseded("a"));
+        assert!(index.is_superseded("b"));
+        assert!(!index.is_superseded("c"));
+        assert_eq!(index.head("a"), "c");
+        assert_eq!(index.head("b"), "c");
+        assert_eq!(index.head("c"), "c");
+
+        let mut links: Vec<_> = index.links().collect();
+        links.sort();
+        assert_eq!(links, vec![("b", "a"), ("c", "b")]);
+    }
+
+    #[test]
+    fn test_bare_rkey_and_unknown_references() {
+        let a = make_fact(None, 20);
+        let b = make_fact(Some("a"), 10);
+        let orphan = make_fact(Some("at://did:plc:x/diy.razorgirl.winter.fact/gone"), 5);
+        let facts = [("a", &a, ""), ("b", &b, ""), ("orphan", &orphan, "")];
+
+        let index = SupersessionIndex::build(facts);
+
+        assert_eq!(index.superseded_by("a"), Some("b"));
+        assert!(!index.is_superseded("orphan"));
+        assert_eq!(index.links().count(), 1);
+    }
+
+    #[test]
+    fn test_newest_successor_wins_fork() {
+        let a = make_fact(None, 30);
+        let older = make_fact(Some("a"), 20);
+        let newer = make_fact(Some("a"), 10);
+        let facts = [("a", &a, ""), ("older", &older, ""), ("newer", &newer, "")];
+
+        let index = SupersessionIndex::build(facts);
+
+        assert_eq!(index.head("a"), "newer");
+        assert_eq!(index.links().count(), 2);
+    }
+
+    #[test]
+    fn test_cycle_terminates() {
+        let a = make_fact(Some("b"), 10);
+        let b = make_fact(Some("a"), 10);
+        let facts = [("a", &a, ""), ("b", &b, "")];
+
+        let index = SupersessionIndex::build(facts);
+
+        let head = index.head("a");
+        assert!(head == "a" || head == "b");
+    }
+}
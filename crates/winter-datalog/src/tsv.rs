@@ -0,0 +1,115 @@
+//! Tab-separated row encoding for Soufflé fact files.
+//!
+//! Fields are separated by tabs and rows by newlines, so those characters
+//! can't appear raw inside a field. They're written as backslash escapes:
+//!
+//! | Character       | Escaped as |
+//! |-----------------|------------|
+//! | backslash       | `\\`       |
+//! | tab             | `\t`       |
+//! | newline         | `\n`       |
+//! | carriage return | `\r`       |
+//!
+//! Soufflé reads the escaped text as an ordinary symbol, so escaped values
+//! pass through evaluation unchanged and are decoded again on the way out.
+
+use std::borrow::Cow;
+
+/// Escape a single field value.
+pub fn escape_field(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\\', '\t', '\n', '\r']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut out = String::with_capacity(value.len() + 8);
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            other => out.push(other),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Reverse [`escape_field`].
+///
+/// Unknown escapes and a trailing lone backslash are kept literally.
+pub fn unescape_field(field: &str) -> Cow<'_, str> {
+    if !field.contains('\\') {
+        return Cow::Borrowed(field);
+    }
+
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Encode a row of fields as a single TSV line (without the trailing newline).
+pub fn format_row<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|f| escape_field(f.as_ref()))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+/// Decode a single TSV line into its fields.
+pub fn parse_row(line: &str) -> Vec<String> {
+    line.split('\t')
+        .map(|f| unescape_field(f).into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_values_unchanged() {
+        assert!(matches!(escape_field("did:plc:abc"), Cow::Borrowed(_)));
+        assert_eq!(format_row(&["a", "b", "c"]), "a\tb\tc");
+        assert_eq!(parse_row("a\tb\tc"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_special_characters_roundtrip() {
+        let fields = [
+            "tab\there",
+            "line\nbreak",
+            "back\\slash",
+            "crlf\r\n",
+            "literal \\t not a tab",
+            "",
+        ];
+        let line = format_row(&fields);
+        assert!(!line.contains('\n'));
+        assert_eq!(line.matches('\t').count(), fields.len() - 1);
+        assert_eq!(parse_row(&line), fields);
+    }
+
+    #[test]
+    fn test_unknown_escapes_kept() {
+        assert_eq!(unescape_field("a\\qb"), "a\\qb");
+        assert_eq!(unescape_field("trailing\\"), "trailing\\");
+    }
+}
@@ -14,6 +14,12 @@ use winter_atproto::{Fact, FactDeclaration};
 pub enum ValidationError {
     /// The fact has a different number of arguments than declared.
     ArityMismatch { expected: usize, actual: usize },
+    /// An argument can't be read as its declared Soufflé type.
+    TypeMismatch {
+        index: usize,
+        expected: String,
+        value: String,
+    },
 }
 
 impl fmt::Display for ValidationError {
@@ -26,6 +32,17 @@ impl fmt::Display for ValidationError {
                     expected, actual
                 )
             }
+            ValidationError::TypeMismatch {
+                index,
+                expected,
+                value,
+            } => {
+                write!(
+                    f,
+                    "type mismatch: arg {} expected {}, got '{}'",
+                    index, expected, value
+                )
+            }
         }
     }
 }
@@ -50,9 +67,32 @@ pub fn validate_fact_against_declaration(
         });
     }
 
+    for (index, (value, arg)) in fact.args.iter().zip(&declaration.args).enumerate() {
+        if !value_matches_type(value, &arg.r#type) {
+            return Some(ValidationError::TypeMismatch {
+                index,
+                expected: arg.r#type.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+
     None // Valid (or no declaration = permissive)
 }
 
+/// Whether a TSV value parses as the given Soufflé type.
+///
+/// Symbols accept anything, as do types we don't recognize (user-defined
+/// types are Soufflé's problem, not ours).
+fn value_matches_type(value: &str, r#type: &str) -> bool {
+    match r#type {
+        "number" => value.parse::<i64>().is_ok(),
+        "unsigned" => value.parse::<u64>().is_ok(),
+        "float" => value.parse::<f64>().is_ok(),
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,6 +113,7 @@ mod tests {
             args,
             description: "Test declaration".to_string(),
             tags: vec![],
+            decay: None,
             created_at: Utc::now(),
             last_updated: None,
         }
@@ -88,6 +129,7 @@ mod tests {
             tags: vec![],
             created_at: Utc::now(),
             expires_at: None,
+            deleted_at: None,
         }
     }
 
@@ -124,6 +166,7 @@ mod tests {
                 assert_eq!(expected, 2);
                 assert_eq!(actual, 3);
             }
+            other => panic!("expected arity mismatch, got {other}"),
         }
     }
 
@@ -141,7 +184,45 @@ mod tests {
                 assert_eq!(expected, 3);
                 assert_eq!(actual, 1);
             }
+            other => panic!("expected arity mismatch, got {other}"),
+        }
+    }
+
+    #[test]
+    fn test_type_mismatch_fails() {
+        let mut declaration = make_declaration("scored", 2);
+        declaration.args[1].r#type = "number".to_string();
+        let mut declarations = HashMap::new();
+        declarations.insert("scored".to_string(), declaration);
+
+        let fact = make_fact("scored", vec!["did:plc:a", "high"]);
+        let result = validate_fact_against_declaration(&fact, &declarations);
+
+        match result {
+            Some(ValidationError::TypeMismatch {
+                index,
+                expected,
+                value,
+            }) => {
+                assert_eq!(index, 1);
+                assert_eq!(expected, "number");
+                assert_eq!(value, "high");
+            }
+            other => panic!("expected type mismatch, got {other:?}"),
         }
+
+        let fact = make_fact("scored", vec!["did:plc:a", "-42"]);
+        assert!(validate_fact_against_declaration(&fact, &declarations).is_none());
+    }
+
+    #[test]
+    fn test_value_matches_type() {
+        assert!(value_matches_type("anything at all", "symbol"));
+        assert!(value_matches_type("12", "unsigned"));
+        assert!(!value_matches_type("-12", "unsigned"));
+        assert!(value_matches_type("0.5", "float"));
+        assert!(!value_matches_type("half", "float"));
+        assert!(value_matches_type("whatever", "MyRecordType"));
     }
 
     #[test]
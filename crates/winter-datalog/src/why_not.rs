@@ -0,0 +1,280 @@
+//! Diagnosis for tuples a query did not return.
+//!
+//! For each rule whose head could produce the expected tuple, the body is
+//! replayed one literal at a time: probe `k` asks whether the first `k`
+//! literals have any solution once the head variables are bound to the goal's
+//! arguments. The first probe without solutions points at the literal that
+//! failed. All probes for a rule run as ad-hoc rules in a single Soufflé
+//! invocation (see [`crate::DatalogCache::why_not`]).
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::compiler::is_variable;
+
+/// Head of the ad-hoc rules used to probe a rule body.
+pub const PROBE_PREDICATE: &str = "_why_not_probe";
+
+/// A ground (or partially ground) atom such as `mutual("did:a", "did:b")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Goal {
+    pub name: String,
+    /// Argument text as written, quotes included.
+    pub args: Vec<String>,
+}
+
+impl Goal {
+    /// Parse `name(arg, ...)`, splitting arguments outside quotes and parens.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_end_matches('.');
+        let open = text
+            .find('(')
+            .ok_or_else(|| format!("expected an atom like `pred(\"a\", \"b\")`, got `{}`", text))?;
+        if !text.ends_with(')') {
+            return Err(format!("atom `{}` is missing its closing `)`", text));
+        }
+        let name = text[..open].trim();
+        if name.is_empty() {
+            return Err(format!("atom `{}` has no predicate name", text));
+        }
+
+        let inner = &text[open + 1..text.len() - 1];
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut in_string = false;
+        let mut depth = 0i32;
+        for c in inner.chars() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth -= 1,
+                ',' if !in_string && depth == 0 => {
+                    args.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() || !args.is_empty() {
+            args.push(current.trim().to_string());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            args,
+        })
+    }
+}
+
+/// Why one rule did not derive the goal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RuleOutcome {
+    /// Every literal has a solution, so the rule should derive the goal.
+    Satisfied,
+    /// Literal `index` (0-based, body then constraints) had no solution
+    /// given the literals before it.
+    FailedAt { index: usize, literal: String },
+    /// The rule couldn't be probed (e.g. it doesn't compile).
+    Error { message: String },
+    /// The rule's head can't produce the goal tuple.
+    HeadMismatch { reason: String },
+    /// The rule is disabled.
+    Disabled,
+}
+
+impl RuleOutcome {
+    /// How close the rule came to deriving the goal; higher is closer.
+    pub fn progress(&self) -> usize {
+        match self {
+            RuleOutcome::Satisfied => usize::MAX,
+            RuleOutcome::FailedAt { index, .. } => index + 3,
+            RuleOutcome::Error { .. } => 2,
+            RuleOutcome::HeadMismatch { .. } => 1,
+            RuleOutcome::Disabled => 0,
+        }
+    }
+}
+
+/// Diagnosis for a single candidate rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleDiagnosis {
+    pub rule: String,
+    pub head: String,
+    #[serde(flatten)]
+    pub outcome: RuleOutcome,
+}
+
+/// Result of [`crate::DatalogCache::why_not`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WhyNotReport {
+    pub goal: String,
+    /// Whether the goal is currently derivable after all.
+    pub present: bool,
+    /// Rules with the goal's predicate as head, closest matches first.
+    pub rules: Vec<RuleDiagnosis>,
+}
+
+/// Bind a rule head's variables to the goal's arguments.
+///
+/// Goal arguments that are variables or `_` leave the head argument unbound.
+pub fn unify_head(head: &str, goal: &Goal) -> Result<Vec<(String, String)>, String> {
+    let head = Goal::parse(head)?;
+    if head.name != goal.name {
+        return Err(format!("head predicate is `{}`", head.name));
+    }
+    if head.args.len() != goal.args.len() {
+        return Err(format!(
+            "head has {} arguments, goal has {}",
+            head.args.len(),
+            goal.args.len()
+        ));
+    }
+
+    let mut bindings: Vec<(String, String)> = Vec::new();
+    for (i, (h, g)) in head.args.iter().zip(&goal.args).enumerate() {
+        if g == "_" || is_variable(g) || h == "_" {
+            continue;
+        }
+        if is_variable(h) {
+            match bindings.iter().find(|(var, _)| var == h) {
+                Some((_, bound)) if bound != g => {
+                    return Err(format!("`{}` would have to be both {} and {}", h, bound, g));
+                }
+                Some(_) => {}
+                None => bindings.push((h.clone(), g.clone())),
+            }
+        } else if h != g {
+            return Err(format!("head argument {} is {}, goal has {}", i, h, g));
+        }
+    }
+    Ok(bindings)
+}
+
+/// Ad-hoc rules probing each prefix of `literals` under `bindings`.
+///
+/// Probe `k` holds iff the first `k` literals have a solution.
+pub fn probe_rules(bindings: &[(String, String)], literals: &[String]) -> String {
+    let equalities: Vec<String> = bindings
+        .iter()
+        .map(|(var, value)| format!("{} = {}", var, value))
+        .collect();
+
+    (1..=literals.len())
+        .map(|k| {
+            let conjunction: Vec<&str> = equalities
+                .iter()
+                .map(String::as_str)
+                .chain(literals[..k].iter().map(String::as_str))
+                .collect();
+            format!(
+                "{}(\"{}\") :- {}.\n",
+                PROBE_PREDICATE,
+                k,
+                conjunction.join(", ")
+            )
+        })
+        .collect()
+}
+
+/// Index of the first literal whose probe returned no solution, if any.
+pub fn first_failure(literal_count: usize, probe_results: &[Vec<String>]) -> Option<usize> {
+    let satisfied: HashSet<usize> = probe_results
+        .iter()
+        .filter_map(|row| row.first()?.trim_matches('"').parse().ok())
+        .collect();
+    (1..=literal_count)
+        .find(|k| !satisfied.contains(k))
+        .map(|k| k - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(text: &str) -> Goal {
+        Goal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_goal_parse() {
+        assert_eq!(
+            goal(r#"mutual("did:a", "x,y")"#),
+            Goal {
+                name: "mutual".to_string(),
+                args: vec![r#""did:a""#.to_string(), r#""x,y""#.to_string()],
+            }
+        );
+        assert!(goal("ready()").args.is_empty());
+        assert!(Goal::parse("mutual").is_err());
+        assert!(Goal::parse("mutual(\"a\"").is_err());
+    }
+
+    #[test]
+    fn test_unify_head_binds_variables() {
+        let bindings = unify_head("mutual(X, Y)", &goal(r#"mutual("a", "b")"#)).unwrap();
+        assert_eq!(
+            bindings,
+            vec![
+                ("X".to_string(), r#""a""#.to_string()),
+                ("Y".to_string(), r#""b""#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unify_head_skips_goal_variables() {
+        let bindings = unify_head("mutual(X, Y)", &goal(r#"mutual("a", _)"#)).unwrap();
+        assert_eq!(bindings, vec![("X".to_string(), r#""a""#.to_string())]);
+    }
+
+    #[test]
+    fn test_unify_head_mismatches() {
+        assert_eq!(
+            unify_head("self_loop(X, X)", &goal(r#"self_loop("a", "b")"#)),
+            Err(r#"`X` would have to be both "a" and "b""#.to_string())
+        );
+        assert_eq!(
+            unify_head(r#"tagged(X, "blue")"#, &goal(r#"tagged("a", "red")"#)),
+            Err(r#"head argument 1 is "blue", goal has "red""#.to_string())
+        );
+        assert_eq!(
+            unify_head("pair(X, Y)", &goal(r#"pair("a")"#)),
+            Err("head has 2 arguments, goal has 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_probe_rules() {
+        let bindings = vec![("X".to_string(), r#""a""#.to_string())];
+        let literals = vec!["likes(X, Y, _)".to_string(), "likes(Y, X, _)".to_string()];
+        assert_eq!(
+            probe_rules(&bindings, &literals),
+            "_why_not_probe(\"1\") :- X = \"a\", likes(X, Y, _).\n\
+             _why_not_probe(\"2\") :- X = \"a\", likes(X, Y, _), likes(Y, X, _).\n"
+        );
+    }
+
+    #[test]
+    fn test_first_failure() {
+        let rows =
+            |ks: &[&str]| -> Vec<Vec<String>> { ks.iter().map(|k| vec![k.to_string()]).collect() };
+        assert_eq!(first_failure(3, &rows(&["1", "2"])), Some(2));
+        assert_eq!(first_failure(3, &rows(&[])), Some(0));
+        assert_eq!(first_failure(2, &rows(&["1", "2"])), None);
+    }
+
+    #[test]
+    fn test_progress_orders_closest_first() {
+        let failed = |index| RuleOutcome::FailedAt {
+            index,
+            literal: String::new(),
+        };
+        assert!(RuleOutcome::Satisfied.progress() > failed(5).progress());
+        assert!(failed(1).progress() > failed(0).progress());
+        assert!(failed(0).progress() > RuleOutcome::Disabled.progress());
+    }
+}
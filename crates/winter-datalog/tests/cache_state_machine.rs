@@ -215,6 +215,7 @@ impl CacheTestHarness {
                         tags: vec![],
                         created_at: chrono::Utc::now(),
                         expires_at: None,
+                        deleted_at: None,
                     };
                     self.repo_cache
                         .upsert_fact(rkey, fact, format!("cid_{}", rand_rkey()));
@@ -248,6 +249,7 @@ impl CacheTestHarness {
                             tags: vec![],
                             created_at: chrono::Utc::now(),
                             expires_at: None,
+                            deleted_at: None,
                         };
                         self.repo_cache
                             .upsert_fact(rkey, fact, format!("flood_cid_{}", i));
@@ -278,6 +280,7 @@ impl CacheTestHarness {
                         tags: vec![],
                         created_at: chrono::Utc::now(),
                         expires_at: None,
+                        deleted_at: None,
                     };
                     self.repo_cache
                         .upsert_fact(rkey.clone(), fact, format!("cid_{}", rand_rkey()));
@@ -375,6 +378,7 @@ async fn test_full_regen_flag_set_on_lag() {
             tags: vec![],
             created_at: chrono::Utc::now(),
             expires_at: None,
+            deleted_at: None,
         };
         repo_cache.upsert_fact(format!("rkey{}", i), fact, format!("cid{}", i));
     }
@@ -418,6 +422,7 @@ async fn test_generation_counters_increase() {
                 tags: vec![],
                 created_at: chrono::Utc::now(),
                 expires_at: None,
+                deleted_at: None,
             },
         })
         .await
@@ -484,6 +489,7 @@ proptest! {
                             tags: vec![],
                             created_at: chrono::Utc::now(),
                             expires_at: None,
+                            deleted_at: None,
                         },
                     })
                     .await
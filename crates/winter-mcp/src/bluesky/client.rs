@@ -32,6 +32,9 @@ pub enum BlueskyError {
         endpoint: Option<String>,
     },
 
+    #[error("not found: {0}")]
+    NotFound(String),
+
     #[error("not configured")]
     NotConfigured,
 
@@ -1115,6 +1118,8 @@ impl BlueskyClient {
                 text,
                 uri: notif.uri.clone(),
                 cid: notif.cid.as_ref().to_string(),
+                reason_subject: notif.reason_subject.clone(),
+                indexed_at: Some(indexed_at.to_string()),
                 parent,
                 root,
                 facets,
@@ -1805,10 +1810,13 @@ impl BlueskyClient {
         Ok(())
     }
 
-    /// Get a user's profile by DID.
-    pub async fn get_profile(&self, did: &str) -> Result<Profile, BlueskyError> {
+    /// Get a user's profile by DID or handle.
+    ///
+    /// Returns [`BlueskyError::NotFound`] if the AppView has no profile for
+    /// the actor (deleted, taken down, or never existed).
+    pub async fn get_profile(&self, actor: &str) -> Result<Profile, BlueskyError> {
         let params = atrium_api::app::bsky::actor::get_profile::ParametersData {
-            actor: did
+            actor: actor
                 .parse()
                 .map_err(|e| BlueskyError::Api(format!("invalid actor: {}", e)))?,
         };
@@ -1827,13 +1835,18 @@ impl BlueskyClient {
                     BlueskyError::RateLimited {
                         endpoint: Some("getProfile".to_string()),
                     }
+                } else if error_str.contains("Profile not found")
+                    || error_str.contains("Actor not found")
+                {
+                    BlueskyError::NotFound(actor.to_string())
                 } else {
                     BlueskyError::Api(error_str)
                 }
             })?;
 
-        debug!(did = %did, handle = %output.handle.as_str(), "fetched profile");
+        debug!(actor = %actor, handle = %output.handle.as_str(), "fetched profile");
 
+        let viewer = output.viewer.as_ref();
         Ok(Profile {
             did: output.did.to_string(),
             handle: output.handle.to_string(),
@@ -1845,6 +1858,8 @@ impl BlueskyClient {
             follows_count: output.follows_count,
             posts_count: output.posts_count,
             indexed_at: output.indexed_at.as_ref().map(|t| t.as_str().to_string()),
+            follows_you: viewer.is_some_and(|v| v.followed_by.is_some()),
+            followed_by_you: viewer.is_some_and(|v| v.following.is_some()),
         })
     }
 
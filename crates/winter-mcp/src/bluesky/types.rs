@@ -39,6 +39,13 @@ pub struct BlueskyNotification {
     pub uri: String,
     /// Content hash
     pub cid: String,
+    /// AT URI of the post a like or repost is about
+    #[serde(default)]
+    pub reason_subject: Option<String>,
+    /// When the notification was indexed (RFC 3339); this is what the
+    /// notification cursor tracks
+    #[serde(default)]
+    pub indexed_at: Option<String>,
     /// Parent post reference (for threading replies)
     pub parent: Option<PostRef>,
     /// Root post reference (for threading replies)
@@ -80,6 +87,18 @@ impl NotificationReason {
         }
     }
 
+    /// The Bluesky API reason string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mention => "mention",
+            Self::Reply => "reply",
+            Self::Follow => "follow",
+            Self::Like => "like",
+            Self::Repost => "repost",
+            Self::Quote => "quote",
+        }
+    }
+
     /// Returns true if this notification type should trigger an agent wakeup.
     pub fn triggers_wakeup(&self) -> bool {
         matches!(self, Self::Mention | Self::Reply | Self::Quote)
@@ -256,6 +275,12 @@ pub struct Profile {
     pub posts_count: Option<i64>,
     /// When the profile was indexed
     pub indexed_at: Option<String>,
+    /// Whether this user follows the authenticated account
+    #[serde(default)]
+    pub follows_you: bool,
+    /// Whether the authenticated account follows this user
+    #[serde(default)]
+    pub followed_by_you: bool,
 }
 
 /// A post from an author's feed.
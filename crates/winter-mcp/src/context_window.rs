@@ -0,0 +1,192 @@
+//! Context window sizes for the models Winter runs on.
+//!
+//! `session_stats` reports how much of the context window the session has
+//! used. The window depends on the model, so it's looked up from the model
+//! name the daemon reports with its session metrics. `WINTER_CONTEXT_WINDOW`
+//! overrides the lookup (useful for models not in the table, or for a
+//! deployment that enables a larger window), and unknown models fall back to
+//! [`DEFAULT_CONTEXT_WINDOW`].
+
+use tracing::warn;
+
+/// Environment variable that overrides the context window size, in tokens.
+pub const CONTEXT_WINDOW_ENV: &str = "WINTER_CONTEXT_WINDOW";
+
+/// Context window used when the model is unknown and no override is set.
+///
+/// This is the standard window for current Claude models, so it's the least
+/// surprising guess for a model missing from the table.
+pub const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+
+/// Model names ending in this suffix run with the extended 1M token window.
+const EXTENDED_WINDOW_SUFFIX: &str = "[1m]";
+
+/// Size of the extended context window.
+const EXTENDED_CONTEXT_WINDOW: u64 = 1_000_000;
+
+/// Known context windows, keyed by model name prefix.
+///
+/// Checked in order, so more specific prefixes must come before the family
+/// prefixes they share.
+const KNOWN_CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("claude-opus-4-6", 1_000_000),
+    ("claude-opus-4", 200_000),
+    ("claude-sonnet-4", 200_000),
+    ("claude-haiku-4", 200_000),
+    ("claude-3-7-sonnet", 200_000),
+    ("claude-3-5-sonnet", 200_000),
+    ("claude-3-5-haiku", 200_000),
+    ("claude-3-opus", 200_000),
+    ("claude-3-haiku", 200_000),
+];
+
+/// Where a context window size came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWindowSource {
+    /// Set by `WINTER_CONTEXT_WINDOW`.
+    Override,
+    /// Looked up from the model name.
+    Model,
+    /// The model was unknown, so [`DEFAULT_CONTEXT_WINDOW`] was used.
+    Default,
+}
+
+impl ContextWindowSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContextWindowSource::Override => "override",
+            ContextWindowSource::Model => "model",
+            ContextWindowSource::Default => "default",
+        }
+    }
+}
+
+/// Look up the context window of a model by name.
+pub fn known_context_window(model: &str) -> Option<u64> {
+    let model = model.trim().to_ascii_lowercase();
+    if model.ends_with(EXTENDED_WINDOW_SUFFIX) {
+        return Some(EXTENDED_CONTEXT_WINDOW);
+    }
+    KNOWN_CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+/// Resolve the context window for `model`, honoring `WINTER_CONTEXT_WINDOW`.
+pub fn context_window_for_model(model: Option<&str>) -> (u64, ContextWindowSource) {
+    let override_value = std::env::var(CONTEXT_WINDOW_ENV).ok();
+    resolve_context_window(override_value.as_deref(), model)
+}
+
+/// Resolve the context window from an override value and a model name.
+fn resolve_context_window(
+    override_value: Option<&str>,
+    model: Option<&str>,
+) -> (u64, ContextWindowSource) {
+    if let Some(value) = override_value {
+        match value.trim().parse::<u64>() {
+            Ok(window) if window > 0 => return (window, ContextWindowSource::Override),
+            _ => warn!(
+                value,
+                "ignoring invalid {}, expected a positive token count", CONTEXT_WINDOW_ENV
+            ),
+        }
+    }
+
+    if let Some(window) = model.and_then(known_context_window) {
+        return (window, ContextWindowSource::Model);
+    }
+
+    warn!(
+        model = model.unwrap_or("unknown"),
+        default = DEFAULT_CONTEXT_WINDOW,
+        "unknown model context window, using default (set {} to override)",
+        CONTEXT_WINDOW_ENV
+    );
+    (DEFAULT_CONTEXT_WINDOW, ContextWindowSource::Default)
+}
+
+/// Percentage of `context_window` used by `total_tokens`, to one decimal place.
+pub fn context_used_pct(total_tokens: u64, context_window: u64) -> f64 {
+    if context_window == 0 {
+        return 0.0;
+    }
+    let pct = (total_tokens as f64 / context_window as f64) * 100.0;
+    (pct * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_models() {
+        assert_eq!(known_context_window("claude-opus-4-6"), Some(1_000_000));
+        assert_eq!(
+            known_context_window("claude-opus-4-1-20250805"),
+            Some(200_000)
+        );
+        assert_eq!(known_context_window("claude-sonnet-4-5"), Some(200_000));
+        assert_eq!(known_context_window("claude-haiku-4-5"), Some(200_000));
+        assert_eq!(
+            known_context_window("claude-3-5-sonnet-20241022"),
+            Some(200_000)
+        );
+        assert_eq!(
+            known_context_window("claude-sonnet-4-5[1m]"),
+            Some(1_000_000)
+        );
+        assert_eq!(known_context_window("gpt-4"), None);
+    }
+
+    #[test]
+    fn test_pct_uses_model_window() {
+        let cases = [
+            ("claude-opus-4-6", 5.0),
+            ("claude-sonnet-4-5", 25.0),
+            ("claude-haiku-4-5", 25.0),
+            ("claude-sonnet-4-5[1m]", 5.0),
+        ];
+        for (model, expected) in cases {
+            let (window, source) = resolve_context_window(None, Some(model));
+            assert_eq!(source, ContextWindowSource::Model, "{model}");
+            assert_eq!(context_used_pct(50_000, window), expected, "{model}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_model_falls_back_to_default() {
+        assert_eq!(
+            resolve_context_window(None, Some("some-other-model")),
+            (DEFAULT_CONTEXT_WINDOW, ContextWindowSource::Default)
+        );
+        assert_eq!(
+            resolve_context_window(None, None),
+            (DEFAULT_CONTEXT_WINDOW, ContextWindowSource::Default)
+        );
+    }
+
+    #[test]
+    fn test_override_wins() {
+        assert_eq!(
+            resolve_context_window(Some("64000"), Some("claude-opus-4-6")),
+            (64_000, ContextWindowSource::Override)
+        );
+        assert_eq!(
+            resolve_context_window(Some("not a number"), Some("claude-opus-4-6")),
+            (1_000_000, ContextWindowSource::Model)
+        );
+        assert_eq!(
+            resolve_context_window(Some("0"), Some("claude-sonnet-4-5")),
+            (200_000, ContextWindowSource::Model)
+        );
+    }
+
+    #[test]
+    fn test_context_used_pct() {
+        assert_eq!(context_used_pct(0, 200_000), 0.0);
+        assert_eq!(context_used_pct(12_345, 200_000), 6.2);
+        assert_eq!(context_used_pct(1, 0), 0.0);
+    }
+}
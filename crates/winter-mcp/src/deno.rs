@@ -4,6 +4,7 @@
 //! tools using Deno's permission model.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::{Duration, Instant};
 
@@ -47,6 +48,9 @@ pub struct DenoPermissions {
     pub secrets: HashMap<String, String>,
     /// Subprocess commands the tool can run (e.g., ["git"]).
     pub allowed_commands: Vec<String>,
+    /// Remote hosts the tool may import modules from (e.g., ["esm.sh"]).
+    /// Empty disables remote imports entirely.
+    pub allowed_import_hosts: Vec<String>,
     /// MCP tools this tool is allowed to call via chaining.
     /// When non-empty, a helper module is generated and MCP URL/token are passed.
     pub allowed_tools: Vec<String>,
@@ -70,6 +74,12 @@ pub struct DenoOutput {
     pub stderr: String,
     /// Execution duration in milliseconds.
     pub duration_ms: u64,
+    /// Hosts the tool tried to reach over the network (via `fetch`,
+    /// `WebSocket`, `Deno.connect`, `Deno.connectTls` or `Deno.resolveDns`),
+    /// sorted and deduplicated.
+    /// Includes attempts the sandbox denied.
+    #[serde(default)]
+    pub contacted_hosts: Vec<String>,
 }
 
 /// Executor for Deno-based custom tools.
@@ -77,20 +87,29 @@ pub struct DenoOutput {
 pub struct DenoExecutor {
     /// Default timeout for tool execution.
     timeout: Duration,
+    /// Directory every tool may read and write, exposed as `WINTER_WORKSPACE`.
+    workspace: Option<PathBuf>,
 }
 
 impl Default for DenoExecutor {
     fn default() -> Self {
-        Self {
-            timeout: Duration::from_secs(30),
-        }
+        Self::new(Duration::from_secs(30))
     }
 }
 
 impl DenoExecutor {
     /// Create a new executor with custom timeout.
     pub fn new(timeout: Duration) -> Self {
-        Self { timeout }
+        Self {
+            timeout,
+            workspace: None,
+        }
+    }
+
+    /// Grant every tool access to `workspace`.
+    pub fn with_workspace(mut self, workspace: Option<PathBuf>) -> Self {
+        self.workspace = workspace;
+        self
     }
 
     /// Execute a tool with the given code, input, and permissions.
@@ -150,7 +169,8 @@ async function callTool(toolRef: string, args: Record<string, unknown>): Promise
     if (!_mcpUrl || !_toolToken) {{
         throw new Error("Tool chaining not configured (missing MCP URL or token)");
     }}
-    const resp = await fetch(`${{_mcpUrl}}/mcp/internal`, {{
+    // Internal calls bypass the egress shim; they aren't network egress
+    const resp = await _fetch(`${{_mcpUrl}}/mcp/internal`, {{
         method: "POST",
         headers: {{
             "Content-Type": "application/json",
@@ -176,10 +196,63 @@ async function callTool(toolRef: string, args: Record<string, unknown>): Promise
             String::new()
         };
 
-        // Create wrapper that imports the tool and handles stdin/stdout
+        // Create wrapper that imports the tool and handles stdin/stdout.
+        // The tool is imported dynamically so the egress shim is installed
+        // before any of its code (including top-level statements) runs.
         let wrapper_code = format!(
             r#"
-import tool from "file://{}";
+// Egress shim - records every host the tool tries to reach
+const _contactedHosts = new Set<string>();
+const _fetch = globalThis.fetch;
+globalThis.fetch = (input: RequestInfo | URL, init?: RequestInit): Promise<Response> => {{
+    try {{
+        const url = input instanceof Request ? input.url : String(input);
+        _contactedHosts.add(new URL(url).hostname);
+    }} catch (_) {{
+        // Let fetch report the invalid URL
+    }}
+    return _fetch(input, init);
+}};
+// Hooks that can't be installed abort the run rather than let traffic
+// go unrecorded
+function _hook(target: any, name: string, replacement: unknown) {{
+    try {{
+        target[name] = replacement;
+    }} catch (_) {{
+        // Checked below
+    }}
+    if (target[name] !== replacement) {{
+        throw new Error(`egress shim could not hook ${{name}}`);
+    }}
+}}
+for (const name of ["connect", "connectTls"]) {{
+    const original = (Deno as any)[name];
+    _hook(Deno, name, (options: {{ hostname?: string }}) => {{
+        _contactedHosts.add(options?.hostname ?? "127.0.0.1");
+        return original.call(Deno, options);
+    }});
+}}
+const _resolveDns = (Deno as any).resolveDns;
+_hook(Deno, "resolveDns", (query: string, ...rest: unknown[]) => {{
+    _contactedHosts.add(query);
+    return _resolveDns.call(Deno, query, ...rest);
+}});
+const _WebSocket = globalThis.WebSocket;
+_hook(globalThis, "WebSocket", class extends _WebSocket {{
+    constructor(url: string | URL, protocols?: string | string[]) {{
+        _contactedHosts.add(new URL(String(url)).hostname);
+        super(url, protocols);
+    }}
+}});
+// Workers get a fresh global scope without the shim, so they're not allowed
+_hook(globalThis, "Worker", class {{
+    constructor() {{
+        throw new Error("Workers are not available to custom tools");
+    }}
+}});
+const contactedHosts = () => [..._contactedHosts].sort();
+
+const {{ default: tool }} = await import("file://{}");
 {tool_chaining}
 async function readStdin(): Promise<string> {{
     const buf = new Uint8Array(1024 * 1024); // 1MB buffer
@@ -226,9 +299,13 @@ const input = JSON.parse(inputText);
 
 try {{
     const result = await tool(input, context);
-    console.log(JSON.stringify({{ success: true, result }}));
+    console.log(JSON.stringify({{ success: true, result, contacted_hosts: contactedHosts() }}));
 }} catch (error) {{
-    console.log(JSON.stringify({{ success: false, error: error.message || String(error) }}));
+    console.log(JSON.stringify({{
+        success: false,
+        error: error.message || String(error),
+        contacted_hosts: contactedHosts(),
+    }}));
 }}
 "#,
             tool_file.path().display(),
@@ -296,7 +373,7 @@ try {{
         };
 
         // Workspace path — always granted to all tools
-        let workspace_path = std::env::var("WINTER_WORKSPACE").ok().filter(|p| !p.is_empty());
+        let workspace_path = self.workspace.as_ref().map(|ws| ws.display().to_string());
 
         let mut read_paths = format!(
             "{},{}{}",
@@ -316,6 +393,8 @@ try {{
             cmd.arg(format!("--allow-write={}", ws));
         }
 
+        cmd.args(import_flags(permissions));
+
         // Add subprocess command permissions if granted
         if !permissions.allowed_commands.is_empty() {
             cmd.arg(format!(
@@ -412,6 +491,10 @@ try {{
             ))
         })?;
 
+        if !wrapper_output.contacted_hosts.is_empty() {
+            debug!(hosts = ?wrapper_output.contacted_hosts, "Deno tool network egress");
+        }
+
         if !wrapper_output.success {
             return Err(DenoError::ExecutionFailed(
                 wrapper_output
@@ -425,6 +508,7 @@ try {{
             stdout,
             stderr,
             duration_ms,
+            contacted_hosts: wrapper_output.contacted_hosts,
         })
     }
 
@@ -447,6 +531,25 @@ struct WrapperOutput {
     success: bool,
     result: Option<Value>,
     error: Option<String>,
+    #[serde(default)]
+    contacted_hosts: Vec<String>,
+}
+
+/// Deno flags restricting which remote hosts a tool may import modules from.
+///
+/// Deno allows imports from a handful of well-known registries by default, so
+/// an empty allowlist has to turn remote imports off explicitly.
+fn import_flags(permissions: &DenoPermissions) -> Vec<String> {
+    let mut flags = vec!["--no-npm".to_string()];
+    if permissions.allowed_import_hosts.is_empty() {
+        flags.push("--no-remote".to_string());
+    } else {
+        flags.push(format!(
+            "--allow-import={}",
+            permissions.allowed_import_hosts.join(",")
+        ));
+    }
+    flags
 }
 
 #[cfg(test)]
@@ -580,6 +683,129 @@ export default async function(_input: {}): Promise<{ status: number }> {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn import_flags_deny_remote_by_default() {
+        let flags = import_flags(&DenoPermissions::default());
+        assert!(flags.contains(&"--no-remote".to_string()));
+        assert!(!flags.iter().any(|f| f.starts_with("--allow-import")));
+    }
+
+    #[test]
+    fn import_flags_list_granted_hosts() {
+        let permissions = DenoPermissions {
+            allowed_import_hosts: vec!["esm.sh".to_string(), "jsr.io".to_string()],
+            ..Default::default()
+        };
+        let flags = import_flags(&permissions);
+        assert!(flags.contains(&"--allow-import=esm.sh,jsr.io".to_string()));
+        assert!(!flags.contains(&"--no-remote".to_string()));
+    }
+
+    #[tokio::test]
+    async fn tool_with_unlisted_import_fails() {
+        if !deno_available().await {
+            eprintln!("Skipping test - Deno not available");
+            return;
+        }
+
+        let executor = DenoExecutor::default();
+
+        let code = r#"
+import { assert } from "https://deno.land/std@0.224.0/assert/mod.ts";
+
+export default async function(_input: {}): Promise<{ ok: boolean }> {
+    assert(true);
+    return { ok: true };
+}
+"#;
+
+        let permissions = DenoPermissions {
+            network: true,
+            allowed_import_hosts: vec!["esm.sh".to_string()],
+            ..Default::default()
+        };
+
+        let result = executor.execute(code, &json!({}), permissions).await;
+
+        // deno.land is not on the allowlist, so the import must be rejected
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn tool_reports_contacted_hosts() {
+        if !deno_available().await {
+            eprintln!("Skipping test - Deno not available");
+            return;
+        }
+
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let executor = DenoExecutor::default();
+
+        let code = r#"
+export default async function(input: { url: string }): Promise<{ status: number }> {
+    const first = await fetch(input.url);
+    await fetch(new URL("/again", input.url));
+    return { status: first.status };
+}
+"#;
+
+        let permissions = DenoPermissions {
+            network: true,
+            ..Default::default()
+        };
+
+        let result = executor
+            .execute(code, &json!({ "url": server.uri() }), permissions)
+            .await
+            .unwrap();
+
+        assert_eq!(result.result, json!({ "status": 204 }));
+        assert_eq!(result.contacted_hosts, vec!["127.0.0.1"]);
+    }
+
+    #[tokio::test]
+    async fn tool_cannot_start_worker() {
+        if !deno_available().await {
+            eprintln!("Skipping test - Deno not available");
+            return;
+        }
+
+        let executor = DenoExecutor::default();
+
+        let code = r#"
+export default async function(_input: {}): Promise<void> {
+    new Worker("data:text/javascript,", { type: "module" });
+}
+"#;
+
+        let result = executor
+            .execute(code, &json!({}), DenoPermissions::default())
+            .await;
+
+        let Err(DenoError::ExecutionFailed(message)) = result else {
+            panic!("expected the worker to be refused, got {:?}", result);
+        };
+        assert!(message.contains("Workers are not available"));
+    }
+
+    #[test]
+    fn wrapper_output_contacted_hosts_default_empty() {
+        let output: WrapperOutput =
+            serde_json::from_str(r#"{"success": true, "result": 1}"#).unwrap();
+        assert!(output.contacted_hosts.is_empty());
+
+        let output: WrapperOutput = serde_json::from_str(
+            r#"{"success": true, "result": 1, "contacted_hosts": ["api.example.com"]}"#,
+        )
+        .unwrap();
+        assert_eq!(output.contacted_hosts, vec!["api.example.com"]);
+    }
+
     #[tokio::test]
     async fn tool_error_handling() {
         if !deno_available().await {
@@ -8,22 +8,30 @@
 //! run in parallel without blocking each other.
 
 use std::collections::HashMap;
+use std::future::IntoFuture;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
     Json, Router,
-    extract::State,
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    extract::{Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tokio::sync::watch;
 use tracing::{debug, info, warn};
+use winter_atproto::SyncState;
 
 use crate::{
+    metrics::McpMetrics,
     protocol::{JsonRpcRequest, JsonRpcResponse},
     server::McpServer,
+    thought_queue::{OverflowPolicy, ThoughtQueueConfig},
     tools::InterruptionState,
     tools::SessionMetrics,
     tools::inbox::{Inbox, InboxItem},
@@ -44,6 +52,12 @@ pub struct HttpState {
     inbox: Option<Arc<Inbox>>,
     /// Shared session metrics for observability (optional).
     session_metrics: Option<Arc<tokio::sync::RwLock<SessionMetrics>>>,
+    /// Prometheus metrics registry (optional, enables `/metrics`).
+    metrics: Option<Arc<McpMetrics>>,
+    /// Bearer token required on authenticated routes (optional).
+    auth_token: Option<String>,
+    /// Firehose silence after which `/readyz` reports `degraded`.
+    firehose_stale_after: Duration,
 }
 
 impl HttpState {
@@ -56,6 +70,9 @@ impl HttpState {
             sessions: Arc::new(ToolSessionStore::new()),
             inbox: None,
             session_metrics: None,
+            metrics: None,
+            auth_token: None,
+            firehose_stale_after: DEFAULT_FIREHOSE_STALE_AFTER,
         }
     }
 
@@ -71,6 +88,9 @@ impl HttpState {
             sessions,
             inbox: None,
             session_metrics: None,
+            metrics: None,
+            auth_token: None,
+            firehose_stale_after: DEFAULT_FIREHOSE_STALE_AFTER,
         }
     }
 
@@ -87,6 +107,9 @@ impl HttpState {
             sessions,
             inbox: Some(inbox),
             session_metrics: None,
+            metrics: None,
+            auth_token: None,
+            firehose_stale_after: DEFAULT_FIREHOSE_STALE_AFTER,
         }
     }
 
@@ -104,9 +127,34 @@ impl HttpState {
             sessions,
             inbox: Some(inbox),
             session_metrics: Some(session_metrics),
+            metrics: None,
+            auth_token: None,
+            firehose_stale_after: DEFAULT_FIREHOSE_STALE_AFTER,
         }
     }
 
+    /// Enable the `/metrics` endpoint backed by the given registry.
+    ///
+    /// The same registry should be set on the tool registry (via
+    /// `ToolRegistry::set_metrics`) so tool calls are recorded.
+    pub fn with_metrics(mut self, metrics: Arc<McpMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Require `Authorization: Bearer <token>` on authenticated routes.
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Set how long the firehose may go quiet before `/readyz` reports
+    /// `degraded`.
+    pub fn with_firehose_stale_after(mut self, stale_after: Duration) -> Self {
+        self.firehose_stale_after = stale_after;
+        self
+    }
+
     /// Get the interruption state.
     pub fn interruption(&self) -> &Arc<InterruptionState> {
         &self.interruption
@@ -124,20 +172,122 @@ impl HttpState {
 }
 
 /// Create an axum router for the MCP HTTP server.
+///
+/// When an auth token is configured, every route except the health probes
+/// (`/health`, `/healthz`, `/readyz`) and `/mcp/internal` requires
+/// `Authorization: Bearer <token>`. The internal tool-chaining route is
+/// authenticated by its per-execution `X-Tool-Token` instead, so the server
+/// token is never handed to sandboxed tool code.
+///
+/// The `/metrics` route is only registered when a metrics registry is configured.
 pub fn create_router(state: Arc<HttpState>) -> Router {
-    Router::new()
+    let mut authenticated = Router::new()
         .route("/mcp", post(handle_mcp))
-        .route("/mcp/internal", post(handle_internal_tool_call))
-        .route("/health", get(handle_health))
         .route("/interrupt", post(handle_interrupt))
         .route("/interrupt", axum::routing::delete(handle_clear_interrupt))
         .route("/builtin-tool-call", post(handle_builtin_tool_call))
+        .route("/thinking", post(handle_thinking))
         .route("/inbox", post(handle_push_inbox))
         .route("/inbox/status", get(handle_inbox_status))
-        .route("/session-metrics", post(handle_session_metrics))
+        .route(
+            "/session-metrics",
+            post(handle_session_metrics).delete(handle_reset_session_metrics),
+        );
+    if state.metrics.is_some() {
+        authenticated = authenticated.route("/metrics", get(handle_metrics));
+    }
+    let authenticated = authenticated.route_layer(middleware::from_fn_with_state(
+        Arc::clone(&state),
+        require_bearer_token,
+    ));
+
+    Router::new()
+        .route("/mcp/internal", post(handle_internal_tool_call))
+        .route("/health", get(handle_health))
+        .route("/healthz", get(handle_healthz))
+        .route("/readyz", get(handle_readyz))
+        .merge(authenticated)
         .with_state(state)
 }
 
+/// Reject requests that don't carry the configured bearer token.
+///
+/// Passes everything through when no token is configured.
+async fn require_bearer_token(
+    State(state): State<Arc<HttpState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ref expected) = state.auth_token else {
+        return next.run(request).await;
+    };
+
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()));
+
+    if authorized {
+        return next.run(request).await;
+    }
+
+    warn!(path = %request.uri().path(), "rejected unauthenticated MCP HTTP request");
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(json!({ "success": false, "error": "missing or invalid bearer token" })),
+    )
+        .into_response()
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Environment variable holding the bearer token for the MCP HTTP transport.
+pub const AUTH_TOKEN_ENV: &str = "WINTER_MCP_TOKEN";
+
+/// Base URL of the MCP HTTP server when none is configured.
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:3847";
+
+/// The MCP HTTP server's base URL, given the URL of its `/mcp` endpoint.
+///
+/// Falls back to [`DEFAULT_BASE_URL`] without a URL or when the URL doesn't
+/// end in `/mcp`.
+pub fn base_url(mcp_url: Option<&str>) -> String {
+    mcp_url
+        .and_then(|url| url.strip_suffix("/mcp"))
+        .unwrap_or(DEFAULT_BASE_URL)
+        .to_string()
+}
+
+/// Build an HTTP client for calling the MCP HTTP server.
+///
+/// Attaches `Authorization: Bearer $WINTER_MCP_TOKEN` to every request when
+/// the variable is set, so daemon-side callers don't each need to handle auth.
+pub fn authenticated_client() -> reqwest::Client {
+    use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+
+    let mut headers = HeaderMap::new();
+    if let Some(token) = std::env::var(AUTH_TOKEN_ENV).ok().filter(|t| !t.is_empty())
+        && let Ok(mut value) = HeaderValue::from_str(&format!("Bearer {}", token))
+    {
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
 /// Handle MCP JSON-RPC requests.
 ///
 /// This endpoint accepts JSON-RPC requests and returns JSON-RPC responses.
@@ -187,6 +337,95 @@ async fn handle_health() -> impl IntoResponse {
     (StatusCode::OK, "ok")
 }
 
+/// Liveness probe: the process is up and serving requests.
+async fn handle_healthz() -> impl IntoResponse {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe.
+///
+/// Ready once the repo cache has finished its initial sync and the datalog
+/// cache has been populated from it at least once. Returns 503 with the same
+/// per-subsystem breakdown until then.
+///
+/// A live cache whose firehose has gone quiet for longer than the staleness
+/// threshold stays ready (its records are still served) but reports
+/// `"status": "degraded"`.
+async fn handle_readyz(State(state): State<Arc<HttpState>>) -> impl IntoResponse {
+    let tools = state.server.tools();
+    let mut degraded = false;
+
+    let (cache_ready, repo_cache) = match tools.repo_cache().await {
+        Some(cache) => {
+            let sync_state = cache.state();
+            let firehose = cache.firehose_metrics().snapshot(state.firehose_stale_after);
+            degraded = sync_state == SyncState::Live && firehose.stale;
+            let state_name = match sync_state {
+                SyncState::Disconnected => "disconnected",
+                SyncState::Syncing => "syncing",
+                SyncState::Live => "live",
+            };
+            (
+                sync_state == SyncState::Live,
+                json!({
+                    "ready": sync_state == SyncState::Live,
+                    "state": state_name,
+                    "firehose_lag_seconds": cache.firehose_lag_secs(),
+                    "firehose": firehose,
+                }),
+            )
+        }
+        None => (false, json!({ "ready": false, "state": "not_configured" })),
+    };
+
+    let (datalog_ready, datalog_cache) = match tools.datalog_cache().await {
+        Some(cache) => {
+            let populated = cache.is_populated();
+            (
+                populated,
+                json!({ "ready": populated, "populated": populated }),
+            )
+        }
+        None => (false, json!({ "ready": false, "state": "not_configured" })),
+    };
+
+    let ready = cache_ready && datalog_ready;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "ready": ready,
+            "status": if degraded { "degraded" } else { "ok" },
+            "subsystems": {
+                "repo_cache": repo_cache,
+                "datalog_cache": datalog_cache,
+            }
+        })),
+    )
+}
+
+/// Prometheus metrics endpoint.
+async fn handle_metrics(State(state): State<Arc<HttpState>>) -> impl IntoResponse {
+    let Some(ref metrics) = state.metrics else {
+        return (StatusCode::NOT_FOUND, HeaderMap::new(), String::new());
+    };
+
+    let cache = state.server.tools().repo_cache().await;
+    let body = metrics.render(cache.as_deref());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4"),
+    );
+    (StatusCode::OK, headers, body)
+}
+
 /// Request body for setting interruption.
 #[derive(Debug, Deserialize)]
 pub struct InterruptRequest {
@@ -214,6 +453,9 @@ pub struct BuiltinToolCallRequest {
     pub name: String,
     /// Input arguments passed to the tool
     pub input: serde_json::Value,
+    /// Approximate duration, from the tool_use event to its tool_result (ms)
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
     /// What triggered this tool call (notification URI, job name, etc.)
     pub trigger: Option<String>,
 }
@@ -224,6 +466,18 @@ pub struct BuiltinToolCallResponse {
     pub success: bool,
 }
 
+/// Request to record a block of Claude's extended thinking.
+///
+/// Sent by the agent when thinking capture is enabled, with redaction
+/// already applied to `content`.
+#[derive(Debug, Deserialize)]
+pub struct ThinkingRequest {
+    /// Thinking text, or a placeholder if redacted
+    pub content: String,
+    /// What triggered the session (notification URI, job name, etc.)
+    pub trigger: Option<String>,
+}
+
 /// Set the interruption state (called by daemon when notifications arrive).
 async fn handle_interrupt(
     State(state): State<Arc<HttpState>>,
@@ -243,17 +497,39 @@ async fn handle_interrupt(
     )
 }
 
+/// Query parameters for clearing interruption.
+#[derive(Debug, Deserialize)]
+pub struct ClearInterruptQuery {
+    /// Only clear if the interruption was set for this reason.
+    pub reason: Option<String>,
+}
+
 /// Clear the interruption state (called by daemon after session ends).
-async fn handle_clear_interrupt(State(state): State<Arc<HttpState>>) -> impl IntoResponse {
-    debug!("clearing interruption state");
-    state.interruption.clear().await;
+///
+/// With `?reason=...`, only an interruption set for that reason is cleared,
+/// so relieving queue pressure doesn't swallow an operator interrupt.
+async fn handle_clear_interrupt(
+    State(state): State<Arc<HttpState>>,
+    Query(query): Query<ClearInterruptQuery>,
+) -> impl IntoResponse {
+    match query.reason {
+        Some(ref reason) => {
+            debug!(reason = %reason, "clearing interruption state for reason");
+            state.interruption.clear_if_reason(reason).await;
+        }
+        None => {
+            debug!("clearing interruption state");
+            state.interruption.clear().await;
+        }
+    }
 
+    let (interrupted, reason) = state.interruption.check().await;
     (
         StatusCode::OK,
         Json(InterruptResponse {
             success: true,
-            interrupted: false,
-            reason: None,
+            interrupted,
+            reason,
         }),
     )
 }
@@ -453,7 +729,7 @@ fn format_internal_result(
 
 /// Record a built-in Claude tool call as a Thought.
 ///
-/// This endpoint is called by the agent after each Claude invocation to log
+/// This endpoint is called by the agent as each built-in tool completes to log
 /// built-in tool usage (WebSearch, Read, WebFetch, etc.) as Thought records.
 /// This allows built-in tools to appear in the thoughtstream alongside MCP tools.
 async fn handle_builtin_tool_call(
@@ -466,7 +742,13 @@ async fn handle_builtin_tool_call(
     state
         .server
         .tools()
-        .record_builtin_tool_call(&request.name, &request.id, &request.input, request.trigger)
+        .record_builtin_tool_call(
+            &request.name,
+            &request.id,
+            &request.input,
+            request.duration_ms,
+            request.trigger,
+        )
         .await;
 
     (
@@ -475,6 +757,20 @@ async fn handle_builtin_tool_call(
     )
 }
 
+/// Record a block of extended thinking as a reflection thought.
+async fn handle_thinking(
+    State(state): State<Arc<HttpState>>,
+    Json(request): Json<ThinkingRequest>,
+) -> impl IntoResponse {
+    state
+        .server
+        .tools()
+        .record_thinking(&request.content, request.trigger)
+        .await;
+
+    (StatusCode::OK, Json(json!({ "success": true })))
+}
+
 /// Push an item to the inbox (daemon-internal endpoint).
 ///
 /// The daemon uses this endpoint to push notifications, DMs, and jobs
@@ -509,20 +805,27 @@ async fn handle_inbox_status(State(state): State<Arc<HttpState>>) -> impl IntoRe
         (0, true)
     };
 
-    // Include last tool call timestamp from session metrics for staleness checks
-    let last_tool_call_at = if let Some(ref metrics) = state.session_metrics {
-        let m = metrics.read().await;
-        Some(m.last_tool_call_at.to_rfc3339())
-    } else {
-        None
-    };
+    // Include session metrics for staleness and budget checks
+    let (last_tool_call_at, total_cost_usd, turn_count) =
+        if let Some(ref metrics) = state.session_metrics {
+            let m = metrics.read().await;
+            (
+                Some(m.last_tool_call_at.to_rfc3339()),
+                m.total_cost_usd,
+                m.turn_count,
+            )
+        } else {
+            (None, 0.0, 0)
+        };
 
     (
         StatusCode::OK,
         Json(json!({
             "pending": pending,
             "is_empty": is_empty,
-            "last_tool_call_at": last_tool_call_at
+            "last_tool_call_at": last_tool_call_at,
+            "total_cost_usd": total_cost_usd,
+            "turn_count": turn_count
         })),
     )
 }
@@ -547,6 +850,9 @@ pub struct SessionMetricsUpdate {
     /// Whether this update represents a completed turn.
     #[serde(default)]
     pub is_turn: bool,
+    /// Model that produced this turn, used to size the context window.
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 /// Update session metrics (called by daemon during streaming).
@@ -563,6 +869,22 @@ async fn handle_session_metrics(
         if update.is_turn {
             m.turn_count += 1;
         }
+        if update.model.is_some() {
+            m.model = update.model;
+        }
+        (StatusCode::OK, Json(json!({ "success": true })))
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "success": false, "error": "session metrics not configured" })),
+        )
+    }
+}
+
+/// Reset session metrics (called by daemon when a new session starts).
+async fn handle_reset_session_metrics(State(state): State<Arc<HttpState>>) -> impl IntoResponse {
+    if let Some(ref metrics) = state.session_metrics {
+        *metrics.write().await = SessionMetrics::default();
         (StatusCode::OK, Json(json!({ "success": true })))
     } else {
         (
@@ -572,8 +894,142 @@ async fn handle_session_metrics(
     }
 }
 
-/// Run the MCP HTTP server on the specified port.
-pub async fn run_server(server: McpServer, port: u16) -> Result<(), std::io::Error> {
+/// Configuration for the MCP HTTP server.
+#[derive(Debug, Clone)]
+pub struct HttpServerConfig {
+    /// Port to listen on.
+    pub port: u16,
+    /// Expose Prometheus metrics at `/metrics`.
+    pub metrics: bool,
+    /// Bearer token required on authenticated routes.
+    pub auth_token: Option<String>,
+    /// Bind to all interfaces instead of localhost only.
+    pub bind_public: bool,
+    /// How long to wait for in-flight requests after a shutdown signal.
+    pub drain_timeout: Duration,
+    /// Firehose silence after which `/readyz` reports `degraded`.
+    pub firehose_stale_after: Duration,
+    /// Capacity and overflow policies of the thought queue.
+    pub thought_queue: ThoughtQueueConfig,
+    /// File that pending inbox items are persisted to, if any.
+    pub inbox_path: Option<PathBuf>,
+}
+
+/// Default time to wait for in-flight requests to finish on shutdown.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait on shutdown for queued thoughts to reach the PDS.
+pub const THOUGHT_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default firehose staleness threshold.
+///
+/// The Jetstream subscription only carries Winter's own repo and the
+/// operator's, so quiet stretches of several minutes are normal.
+pub const DEFAULT_FIREHOSE_STALE_AFTER: Duration = Duration::from_secs(15 * 60);
+
+impl HttpServerConfig {
+    /// Create a config for the given port with all optional features disabled.
+    ///
+    /// The server binds to localhost and runs without authentication.
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            metrics: false,
+            auth_token: None,
+            bind_public: false,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            firehose_stale_after: DEFAULT_FIREHOSE_STALE_AFTER,
+            thought_queue: ThoughtQueueConfig::default(),
+            inbox_path: None,
+        }
+    }
+
+    /// Set how long the firehose may go quiet before `/readyz` reports
+    /// `degraded`.
+    pub fn with_firehose_stale_after(mut self, stale_after: Duration) -> Self {
+        self.firehose_stale_after = stale_after;
+        self
+    }
+
+    /// Set what happens to thoughts recorded while the thought queue is full.
+    pub fn with_thought_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.thought_queue.policy = policy;
+        self
+    }
+
+    /// Write up to `batch_size` queued thoughts per `applyWrites` call.
+    pub fn with_thought_batch_size(mut self, batch_size: usize) -> Self {
+        self.thought_queue.batch_size = batch_size;
+        self
+    }
+
+    /// Truncate thought content longer than `max_bytes` before writing it.
+    pub fn with_thought_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.thought_queue.max_content_bytes = max_bytes;
+        self
+    }
+
+    /// Persist pending inbox items to `path` so they survive a restart.
+    pub fn with_inbox_path(mut self, path: Option<PathBuf>) -> Self {
+        self.inbox_path = path;
+        self
+    }
+
+    /// Set how long to wait for in-flight requests on shutdown.
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Require a bearer token on authenticated routes.
+    ///
+    /// Empty tokens are treated as unset.
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Bind to all interfaces (`0.0.0.0`) instead of `127.0.0.1`.
+    pub fn with_bind_public(mut self, bind_public: bool) -> Self {
+        self.bind_public = bind_public;
+        self
+    }
+
+    /// Enable or disable the `/metrics` endpoint.
+    pub fn with_metrics(mut self, enabled: bool) -> Self {
+        self.metrics = enabled;
+        self
+    }
+}
+
+/// Run the MCP HTTP server with the given configuration.
+///
+/// Runs until `shutdown_rx` flips to `true`, then stops accepting new
+/// connections and waits up to `config.drain_timeout` for in-flight requests,
+/// then up to [`THOUGHT_FLUSH_TIMEOUT`] for queued thoughts to be written,
+/// before returning.
+///
+/// Refuses to bind publicly without an auth token, since that would expose
+/// the full tool surface to the network.
+pub async fn run_server(
+    server: McpServer,
+    config: HttpServerConfig,
+    shutdown_rx: watch::Receiver<bool>,
+) -> Result<(), std::io::Error> {
+    let port = config.port;
+
+    if config.bind_public && config.auth_token.is_none() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("refusing to bind publicly without {} set", AUTH_TOKEN_ENV),
+        ));
+    }
+    if config.auth_token.is_none() {
+        warn!(
+            "{} not set, MCP HTTP server is unauthenticated (localhost only)",
+            AUTH_TOKEN_ENV
+        );
+    }
     let interruption = Arc::new(InterruptionState::new());
     let sessions = Arc::new(ToolSessionStore::new());
     let session_metrics = Arc::new(tokio::sync::RwLock::new(SessionMetrics::default()));
@@ -603,28 +1059,117 @@ pub async fn run_server(server: McpServer, port: u16) -> Result<(), std::io::Err
         .await;
 
     // Create inbox and wire into both ToolState and HttpState
-    let inbox = Arc::new(Inbox::new());
+    let inbox = match &config.inbox_path {
+        Some(path) => {
+            let inbox = Inbox::with_store(path).map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!("failed to load inbox from {}: {}", path.display(), e),
+                )
+            })?;
+            info!(
+                path = %path.display(),
+                pending = inbox.len().await,
+                "inbox loaded from store"
+            );
+            inbox
+        }
+        None => Inbox::new(),
+    };
+    let inbox = Arc::new(inbox);
     server
         .tools()
         .set_inbox(Arc::clone(&inbox))
         .await;
 
-    let state = Arc::new(HttpState::with_all(
-        server,
-        interruption,
-        sessions,
-        inbox,
-        session_metrics,
-    ));
-    let router = create_router(state);
+    let mut state = HttpState::with_all(server, interruption, sessions, inbox, session_metrics)
+        .with_firehose_stale_after(config.firehose_stale_after);
+
+    state
+        .server
+        .tools()
+        .set_thought_queue_config(config.thought_queue)
+        .await;
+
+    // Wire the metrics registry into both the tool registry and the router
+    if config.metrics {
+        let metrics = Arc::new(McpMetrics::new());
+        state.server.tools().set_metrics(Arc::clone(&metrics)).await;
+        state = state.with_metrics(metrics);
+        info!("Prometheus metrics enabled at /metrics");
+    }
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
+    if let Some(token) = config.auth_token {
+        state = state.with_auth_token(token);
+    }
+
+    let state = Arc::new(state);
+    let router = create_router(Arc::clone(&state));
+
+    let host = if config.bind_public {
+        "0.0.0.0"
+    } else {
+        "127.0.0.1"
+    };
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port)).await?;
+
+    info!("MCP HTTP server listening on http://{}:{}", host, port);
 
-    info!("MCP HTTP server listening on http://0.0.0.0:{}", port);
+    let result = serve_with_shutdown(listener, router, shutdown_rx, config.drain_timeout).await;
 
-    axum::serve(listener, router).await?;
+    // Requests have drained, so no more thoughts are coming
+    let tools = state.server.tools();
+    if tools.flush_thoughts(THOUGHT_FLUSH_TIMEOUT).await {
+        info!("queued thoughts flushed");
+    }
+    result
+}
+
+/// Serve a router until shutdown, draining in-flight requests.
+///
+/// Once `shutdown_rx` flips to `true` the listener stops accepting new
+/// connections. In-flight requests get up to `drain_timeout` to complete;
+/// anything still running after that is dropped.
+pub async fn serve_with_shutdown(
+    listener: tokio::net::TcpListener,
+    router: Router,
+    shutdown_rx: watch::Receiver<bool>,
+    drain_timeout: Duration,
+) -> Result<(), std::io::Error> {
+    let graceful_rx = shutdown_rx.clone();
+    let server = axum::serve(listener, router)
+        .with_graceful_shutdown(wait_for_shutdown(graceful_rx))
+        .into_future();
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => result,
+        _ = wait_for_shutdown(shutdown_rx) => {
+            info!(
+                timeout_secs = drain_timeout.as_secs(),
+                "MCP HTTP server shutting down, draining in-flight requests"
+            );
+            match tokio::time::timeout(drain_timeout, &mut server).await {
+                Ok(result) => {
+                    info!("MCP HTTP server drained");
+                    result
+                }
+                Err(_) => {
+                    warn!("drain timeout elapsed, dropping in-flight requests");
+                    Ok(())
+                }
+            }
+        }
+    }
+}
 
-    Ok(())
+/// Resolve once the shutdown flag is set (or the sender is dropped).
+async fn wait_for_shutdown(mut shutdown_rx: watch::Receiver<bool>) {
+    while !*shutdown_rx.borrow() {
+        if shutdown_rx.changed().await.is_err() {
+            return;
+        }
+    }
 }
 
 /// Health check response type.
@@ -636,7 +1181,9 @@ pub struct HealthResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::deno::{DenoExecutor, DenoPermissions};
     use crate::tools::ToolRegistry;
+    use crate::tools::permissions::PermissionVec;
     use axum::body::Body;
     use axum::http::Request;
     use serde_json::{Value, json};
@@ -667,6 +1214,554 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_thinking_endpoint_records_reflection() {
+        let tools = ToolRegistry::empty();
+        let (thought_tx, mut thought_rx) =
+            crate::thought_queue::thought_channel(Default::default());
+        tools.set_thought_tx(thought_tx).await;
+        let router = create_router(Arc::new(HttpState::new(McpServer::new(tools))));
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/thinking")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        json!({ "content": "Answer the DM first.", "trigger": "job:daily" })
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let thought = thought_rx.try_recv().expect("thinking recorded");
+        assert_eq!(thought.kind, winter_atproto::ThoughtKind::Reflection);
+        assert_eq!(thought.content, "Answer the DM first.");
+        assert_eq!(thought.trigger.as_deref(), Some("job:daily"));
+        assert_eq!(thought.tags, ["thinking"]);
+    }
+
+    async fn get_json(router: &Router, uri: &str) -> (StatusCode, Value) {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_healthz_is_live_without_caches() {
+        let router = create_router(create_test_state());
+        let (status, body) = get_json(&router, "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = get_json(&router, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["subsystems"]["repo_cache"]["state"], "not_configured");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_flips_after_sync_complete() {
+        let tools = ToolRegistry::empty();
+        let repo_cache = winter_atproto::RepoCache::new();
+        let datalog_cache = winter_datalog::DatalogCache::new_temp().unwrap();
+        datalog_cache.start_update_listener(Arc::clone(&repo_cache));
+        tools.set_cache(Arc::clone(&repo_cache)).await;
+        tools.set_datalog_cache(Arc::clone(&datalog_cache)).await;
+
+        let router = create_router(Arc::new(HttpState::new(McpServer::new(tools))));
+
+        repo_cache.set_state(SyncState::Syncing);
+        let (status, body) = get_json(&router, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["subsystems"]["repo_cache"]["state"], "syncing");
+        assert_eq!(body["subsystems"]["datalog_cache"]["populated"], false);
+
+        // Going live broadcasts `Synchronized`, which triggers datalog population
+        repo_cache.set_state(SyncState::Live);
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        loop {
+            let (status, body) = get_json(&router, "/readyz").await;
+            if status == StatusCode::OK {
+                assert_eq!(body["ready"], true);
+                assert_eq!(body["subsystems"]["repo_cache"]["state"], "live");
+                assert_eq!(body["subsystems"]["datalog_cache"]["ready"], true);
+                break;
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "readiness never flipped: {}",
+                body
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readyz_reports_degraded_when_firehose_stale() {
+        let repo_cache = winter_atproto::RepoCache::new();
+        repo_cache.set_state(SyncState::Live);
+        repo_cache.record_event_time(chrono::Utc::now().timestamp_micros());
+
+        let router_with_threshold = |stale_after| {
+            let repo_cache = Arc::clone(&repo_cache);
+            async move {
+                let tools = ToolRegistry::empty();
+                tools.set_cache(repo_cache).await;
+                create_router(Arc::new(
+                    HttpState::new(McpServer::new(tools)).with_firehose_stale_after(stale_after),
+                ))
+            }
+        };
+
+        let fresh = router_with_threshold(Duration::from_secs(3600)).await;
+        let (_, body) = get_json(&fresh, "/readyz").await;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["subsystems"]["repo_cache"]["firehose"]["total_events"], 1);
+        assert_eq!(body["subsystems"]["repo_cache"]["firehose"]["stale"], false);
+
+        let stale = router_with_threshold(Duration::from_millis(1)).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (_, body) = get_json(&stale, "/readyz").await;
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["subsystems"]["repo_cache"]["firehose"]["stale"], true);
+    }
+
+    #[tokio::test]
+    async fn test_probes_exempt_from_auth() {
+        let router = create_router(create_authed_state());
+        let (status, _) = get_json(&router, "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = get_json(&router, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_disabled_by_default() {
+        let state = create_test_state();
+        let router = create_router(state);
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_after_tool_calls() {
+        let metrics = Arc::new(McpMetrics::new());
+        let server = McpServer::new(ToolRegistry::empty());
+        server.tools().set_metrics(Arc::clone(&metrics)).await;
+        let state = Arc::new(HttpState::new(server).with_metrics(metrics));
+
+        for (id, tool) in [(1, "session_stats"), (2, "no_such_tool")] {
+            let request_body = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": { "name": tool, "arguments": {} }
+            });
+            let response = create_router(Arc::clone(&state))
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/mcp")
+                        .header("content-type", "application/json")
+                        .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = create_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(text.contains("# TYPE winter_mcp_tool_calls_total counter"));
+        assert!(text.contains("winter_mcp_tool_calls_total{tool=\"session_stats\"} 1"));
+        assert!(text.contains("winter_mcp_tool_errors_total{tool=\"no_such_tool\"} 1"));
+        assert!(text.contains("winter_datalog_query_duration_seconds_count 0"));
+    }
+
+    fn tools_list_request(auth: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .header("content-type", "application/json");
+        if let Some(auth) = auth {
+            builder = builder.header("authorization", auth);
+        }
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        builder
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap()
+    }
+
+    fn create_authed_state() -> Arc<HttpState> {
+        Arc::new(HttpState::new(McpServer::new(ToolRegistry::empty())).with_auth_token("s3cret"))
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_missing_token() {
+        let router = create_router(create_authed_state());
+        let response = router.oneshot(tools_list_request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_wrong_token() {
+        let router = create_router(create_authed_state());
+        let response = router
+            .oneshot(tools_list_request(Some("Bearer wrong")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_auth_accepts_valid_token() {
+        let router = create_router(create_authed_state());
+        let response = router
+            .oneshot(tools_list_request(Some("Bearer s3cret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_auth_exempts_health() {
+        let router = create_router(create_authed_state());
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    fn internal_tool_call_request(tool_token: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/mcp/internal")
+            .header("content-type", "application/json")
+            .header("X-Tool-Token", tool_token)
+            .body(Body::from(
+                json!({ "tool_ref": "check_interruption", "arguments": {} }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_internal_tool_call_authenticates_by_tool_token() {
+        let state = create_authed_state();
+        let token = state
+            .sessions
+            .register(
+                ["check_interruption".to_string()].into(),
+                PermissionVec::bottom(),
+                0,
+            )
+            .await;
+        let router = create_router(state);
+
+        // No bearer token: the session token alone admits the call
+        let response = router
+            .clone()
+            .oneshot(internal_tool_call_request(&token))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], true);
+
+        let response = router
+            .oneshot(internal_tool_call_request("not-a-session"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_chained_tool_runs_with_auth_enabled() {
+        if !DenoExecutor::is_available().await {
+            eprintln!("Skipping test - Deno not available");
+            return;
+        }
+
+        let state = create_authed_state();
+        let allowed_tools = vec!["check_interruption".to_string()];
+        let token = state
+            .sessions
+            .register(
+                allowed_tools.iter().cloned().collect(),
+                PermissionVec::bottom(),
+                0,
+            )
+            .await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, create_router(state)).into_future());
+
+        let code = r#"
+export default async function(_input: {}): Promise<unknown> {
+    return await callTool("check_interruption", {});
+}
+"#;
+        let permissions = DenoPermissions {
+            allowed_tools,
+            tool_token: Some(token),
+            mcp_url: Some(format!("http://{}", addr)),
+            ..Default::default()
+        };
+        let output = DenoExecutor::default()
+            .execute(code, &json!({}), permissions)
+            .await
+            .unwrap();
+        assert_eq!(output.result["interrupted"], false);
+    }
+
+    #[tokio::test]
+    async fn test_auth_protects_daemon_endpoints() {
+        let router = create_router(create_authed_state());
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/inbox/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_clear_interrupt_by_reason() {
+        let state = create_test_state();
+        let router = create_router(Arc::clone(&state));
+
+        let delete = |uri: &'static str| {
+            let router = router.clone();
+            async move {
+                let response = router
+                    .oneshot(
+                        Request::builder()
+                            .method("DELETE")
+                            .uri(uri)
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            }
+        };
+
+        state.interruption().set_interrupt("operator_dm").await;
+        let body = delete("/interrupt?reason=queue_pressure").await;
+        assert_eq!(body["interrupted"], true);
+        assert_eq!(body["reason"], "operator_dm");
+
+        state.interruption().set_interrupt("queue_pressure").await;
+        let body = delete("/interrupt?reason=queue_pressure").await;
+        assert_eq!(body["interrupted"], false);
+
+        state.interruption().set_interrupt("operator_dm").await;
+        let body = delete("/interrupt").await;
+        assert_eq!(body["interrupted"], false);
+    }
+
+    #[tokio::test]
+    async fn test_session_metrics_reported_and_reset() {
+        let state = Arc::new(HttpState::with_all(
+            McpServer::new(ToolRegistry::empty()),
+            Arc::new(InterruptionState::new()),
+            Arc::new(ToolSessionStore::new()),
+            Arc::new(Inbox::new()),
+            Arc::new(tokio::sync::RwLock::new(SessionMetrics::default())),
+        ));
+        let router = create_router(state);
+
+        let send = |method: &'static str, body: Body| {
+            let router = router.clone();
+            async move {
+                router
+                    .oneshot(
+                        Request::builder()
+                            .method(method)
+                            .uri("/session-metrics")
+                            .header("content-type", "application/json")
+                            .body(body)
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        let update = json!({ "total_tokens": 100, "cost_usd": 1.5, "is_turn": true });
+        assert_eq!(
+            send("POST", Body::from(update.to_string())).await,
+            StatusCode::OK
+        );
+        let (_, body) = get_json(&router, "/inbox/status").await;
+        assert_eq!(body["total_cost_usd"], 1.5);
+        assert_eq!(body["turn_count"], 1);
+
+        assert_eq!(send("DELETE", Body::empty()).await, StatusCode::OK);
+        let (_, body) = get_json(&router, "/inbox/status").await;
+        assert_eq!(body["total_cost_usd"], 0.0);
+        assert_eq!(body["turn_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_server_refuses_public_bind_without_token() {
+        let server = McpServer::new(ToolRegistry::empty());
+        let config = HttpServerConfig::new(0).with_bind_public(true);
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let err = run_server(server, config, shutdown_rx).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_and_refuses_new() {
+        let router = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "done"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            router,
+            shutdown_rx,
+            Duration::from_secs(5),
+        ));
+
+        let client = reqwest::Client::new();
+        let in_flight = tokio::spawn({
+            let client = client.clone();
+            async move { client.get(format!("http://{}/slow", addr)).send().await }
+        });
+
+        // Let the request reach the handler, then signal shutdown
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(true).unwrap();
+
+        let response = in_flight.await.unwrap().unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "done");
+
+        server.await.unwrap().unwrap();
+
+        let refused = reqwest::Client::new()
+            .get(format!("http://{}/slow", addr))
+            .send()
+            .await;
+        assert!(refused.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up_after_drain_timeout() {
+        let router = Router::new().route(
+            "/hang",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                "never"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            router,
+            shutdown_rx,
+            Duration::from_millis(100),
+        ));
+
+        let _hanging = tokio::spawn(async move {
+            reqwest::Client::new()
+                .get(format!("http://{}/hang", addr))
+                .send()
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(true).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server should stop after the drain timeout")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+
     #[tokio::test]
     async fn test_mcp_initialize() {
         let state = create_test_state();
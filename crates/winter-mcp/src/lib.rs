@@ -6,19 +6,28 @@
 //! - HTTP: Persistent server for production (reduces startup latency)
 
 pub mod bluesky;
+pub mod context_window;
 pub mod deno;
 pub mod http;
+pub mod metrics;
 pub mod protocol;
 pub mod secrets;
 pub mod server;
+pub mod thought_queue;
 pub mod tools;
+pub mod trace;
 
 pub use bluesky::{BlueskyClient, BlueskyError};
 pub use deno::{DenoError, DenoExecutor, DenoOutput, DenoPermissions};
-pub use secrets::{SecretError, SecretManager};
+pub use metrics::McpMetrics;
+pub use secrets::{
+    EnvProvider, FileProvider, LayeredProvider, SecretError, SecretManager, SecretProvider,
+    SecretSource,
+};
 pub use server::McpServer;
+pub use tools::enrich::{Enricher, EnricherRegistry, QueryRow};
 pub use tools::inbox::{
     ConversationHistoryMessage as InboxConversationHistoryMessage, Inbox, InboxItem,
     InboxItemKind, InboxPayload, PostRef as InboxPostRef,
 };
-pub use tools::{InterruptionState, SessionMetrics, ToolMeta, ToolRegistry};
+pub use tools::{InterruptionState, SessionMetrics, ToolMeta, ToolRegistry, ToolSettings};
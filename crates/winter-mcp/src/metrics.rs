@@ -0,0 +1,418 @@
+//! Prometheus metrics for the MCP HTTP server.
+//!
+//! Metrics are collected in a cheap shared registry (atomics plus a small
+//! per-tool map) that is updated from the tool execution path, and rendered
+//! on demand in the Prometheus text exposition format by the `/metrics`
+//! route. Collection is opt-in: when no registry is configured on the
+//! `ToolRegistry`, nothing is recorded.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use winter_atproto::RepoCache;
+
+/// Upper bounds (in seconds) of the datalog query duration histogram buckets.
+const DATALOG_DURATION_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Per-tool call counters.
+#[derive(Debug, Default, Clone, Copy)]
+struct ToolCounters {
+    calls: u64,
+    errors: u64,
+    duration_ms_total: u64,
+}
+
+/// Shared metrics registry for the MCP server.
+#[derive(Debug)]
+pub struct McpMetrics {
+    /// Per-tool counters, keyed by tool name (sorted for stable output).
+    tools: Mutex<BTreeMap<String, ToolCounters>>,
+    /// Number of datalog queries executed.
+    datalog_queries: AtomicU64,
+    /// Number of datalog queries that failed.
+    datalog_query_errors: AtomicU64,
+    /// Sum of datalog query durations in microseconds.
+    datalog_duration_us_total: AtomicU64,
+    /// Cumulative histogram bucket counts (one per entry in `DATALOG_DURATION_BUCKETS`).
+    datalog_duration_buckets: Vec<AtomicU64>,
+    /// Thoughts discarded because the thought queue was full.
+    thoughts_dropped: AtomicU64,
+    /// Secrets handed to custom tools, keyed by (tool rkey, secret name).
+    secret_accesses: Mutex<BTreeMap<(String, String), u64>>,
+}
+
+impl Default for McpMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpMetrics {
+    /// Create an empty metrics registry.
+    pub fn new() -> Self {
+        Self {
+            tools: Mutex::new(BTreeMap::new()),
+            datalog_queries: AtomicU64::new(0),
+            datalog_query_errors: AtomicU64::new(0),
+            datalog_duration_us_total: AtomicU64::new(0),
+            datalog_duration_buckets: DATALOG_DURATION_BUCKETS
+                .iter()
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            thoughts_dropped: AtomicU64::new(0),
+            secret_accesses: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record a completed tool call.
+    pub fn record_tool_call(&self, tool: &str, is_error: bool, duration_ms: u64) {
+        let mut tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        let counters = tools.entry(tool.to_string()).or_default();
+        counters.calls += 1;
+        counters.duration_ms_total += duration_ms;
+        if is_error {
+            counters.errors += 1;
+        }
+    }
+
+    /// Record a datalog query execution.
+    pub fn record_datalog_query(&self, duration: std::time::Duration, success: bool) {
+        self.datalog_queries.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.datalog_query_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.datalog_duration_us_total
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+
+        let secs = duration.as_secs_f64();
+        for (bound, bucket) in DATALOG_DURATION_BUCKETS
+            .iter()
+            .zip(&self.datalog_duration_buckets)
+        {
+            if secs <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Record a thought discarded by the thought queue's overflow policy.
+    pub fn record_thought_dropped(&self) {
+        self.thoughts_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a custom tool being given a secret.
+    pub fn record_secret_access(&self, tool_rkey: &str, secret: &str) {
+        let mut accesses = self
+            .secret_accesses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *accesses
+            .entry((tool_rkey.to_string(), secret.to_string()))
+            .or_default() += 1;
+    }
+
+    /// Total tool calls recorded across all tools.
+    pub fn total_tool_calls(&self) -> u64 {
+        let tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        tools.values().map(|c| c.calls).sum()
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    ///
+    /// Cache sizes and firehose lag are read from the repo cache at render
+    /// time, so they are only included when a cache is available.
+    pub fn render(&self, cache: Option<&RepoCache>) -> String {
+        let mut out = String::new();
+
+        let tools = self.tools.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+        write_header(
+            &mut out,
+            "winter_mcp_tool_calls_total",
+            "Total MCP tool calls.",
+            "counter",
+        );
+        for (tool, c) in &tools {
+            let _ = writeln!(
+                out,
+                "winter_mcp_tool_calls_total{{tool=\"{}\"}} {}",
+                escape_label(tool),
+                c.calls
+            );
+        }
+
+        write_header(
+            &mut out,
+            "winter_mcp_tool_errors_total",
+            "Total MCP tool calls that returned an error.",
+            "counter",
+        );
+        for (tool, c) in &tools {
+            let _ = writeln!(
+                out,
+                "winter_mcp_tool_errors_total{{tool=\"{}\"}} {}",
+                escape_label(tool),
+                c.errors
+            );
+        }
+
+        write_header(
+            &mut out,
+            "winter_mcp_tool_duration_seconds_total",
+            "Cumulative time spent executing each MCP tool.",
+            "counter",
+        );
+        for (tool, c) in &tools {
+            let _ = writeln!(
+                out,
+                "winter_mcp_tool_duration_seconds_total{{tool=\"{}\"}} {}",
+                escape_label(tool),
+                c.duration_ms_total as f64 / 1000.0
+            );
+        }
+
+        write_header(
+            &mut out,
+            "winter_thoughts_dropped_total",
+            "Thoughts discarded because the thought queue was full.",
+            "counter",
+        );
+        let _ = writeln!(
+            out,
+            "winter_thoughts_dropped_total {}",
+            self.thoughts_dropped.load(Ordering::Relaxed)
+        );
+
+        let secret_accesses = self
+            .secret_accesses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        write_header(
+            &mut out,
+            "winter_secret_accesses_total",
+            "Secrets handed to custom tools, by tool and secret name.",
+            "counter",
+        );
+        for ((tool_rkey, secret), count) in &secret_accesses {
+            let _ = writeln!(
+                out,
+                "winter_secret_accesses_total{{tool_rkey=\"{}\",secret=\"{}\"}} {}",
+                escape_label(tool_rkey),
+                escape_label(secret),
+                count
+            );
+        }
+
+        let query_count = self.datalog_queries.load(Ordering::Relaxed);
+        write_header(
+            &mut out,
+            "winter_datalog_query_errors_total",
+            "Total datalog queries that failed.",
+            "counter",
+        );
+        let _ = writeln!(
+            out,
+            "winter_datalog_query_errors_total {}",
+            self.datalog_query_errors.load(Ordering::Relaxed)
+        );
+
+        write_header(
+            &mut out,
+            "winter_datalog_query_duration_seconds",
+            "Datalog query execution time.",
+            "histogram",
+        );
+        for (bound, bucket) in DATALOG_DURATION_BUCKETS
+            .iter()
+            .zip(&self.datalog_duration_buckets)
+        {
+            let _ = writeln!(
+                out,
+                "winter_datalog_query_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "winter_datalog_query_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+            query_count
+        );
+        let _ = writeln!(
+            out,
+            "winter_datalog_query_duration_seconds_sum {}",
+            self.datalog_duration_us_total.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(
+            out,
+            "winter_datalog_query_duration_seconds_count {}",
+            query_count
+        );
+
+        if let Some(cache) = cache {
+            write_header(
+                &mut out,
+                "winter_cache_records",
+                "Number of records held in the repo cache, by collection.",
+                "gauge",
+            );
+            let sizes = [
+                ("fact", cache.fact_count()),
+                ("rule", cache.rule_count()),
+                ("thought", cache.thought_count()),
+                ("note", cache.note_count()),
+                ("job", cache.job_count()),
+                ("directive", cache.directive_count()),
+                ("tool", cache.tool_count()),
+                ("toolApproval", cache.tool_approval_count()),
+                ("factDeclaration", cache.declaration_count()),
+                ("trigger", cache.trigger_count()),
+                ("wikiEntry", cache.wiki_entry_count()),
+                ("wikiLink", cache.wiki_link_count()),
+                ("blogEntry", cache.blog_entry_count()),
+                ("follow", cache.follow_count()),
+                ("like", cache.like_count()),
+                ("repost", cache.repost_count()),
+                ("post", cache.post_count()),
+            ];
+            for (collection, count) in sizes {
+                let _ = writeln!(
+                    out,
+                    "winter_cache_records{{collection=\"{}\"}} {}",
+                    collection, count
+                );
+            }
+
+            write_header(
+                &mut out,
+                "winter_cache_sync_state",
+                "Repo cache sync state (0 = disconnected, 1 = syncing, 2 = live).",
+                "gauge",
+            );
+            let _ = writeln!(out, "winter_cache_sync_state {}", cache.state() as u8);
+
+            if let Some(lag) = cache.firehose_lag_secs() {
+                write_header(
+                    &mut out,
+                    "winter_firehose_lag_seconds",
+                    "Seconds since the most recent firehose event was received.",
+                    "gauge",
+                );
+                let _ = writeln!(out, "winter_firehose_lag_seconds {}", lag);
+            }
+
+            // Staleness is reported by `/readyz`; only the counters are needed here.
+            let firehose = cache.firehose_metrics().snapshot(Duration::MAX);
+            write_header(
+                &mut out,
+                "winter_firehose_events_total",
+                "Firehose events received since startup.",
+                "counter",
+            );
+            let _ = writeln!(
+                out,
+                "winter_firehose_events_total {}",
+                firehose.total_events
+            );
+            write_header(
+                &mut out,
+                "winter_firehose_events_per_second",
+                "Firehose events received per second, averaged over the last minute.",
+                "gauge",
+            );
+            let _ = writeln!(
+                out,
+                "winter_firehose_events_per_second {}",
+                firehose.events_per_sec
+            );
+        }
+
+        out
+    }
+}
+
+/// Write the `# HELP` and `# TYPE` lines for a metric family.
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value per the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_tool_call_counts_errors() {
+        let metrics = McpMetrics::new();
+        metrics.record_tool_call("query_facts", false, 10);
+        metrics.record_tool_call("query_facts", true, 5);
+        metrics.record_tool_call("create_fact", false, 1);
+
+        assert_eq!(metrics.total_tool_calls(), 3);
+        let out = metrics.render(None);
+        assert!(out.contains("winter_mcp_tool_calls_total{tool=\"query_facts\"} 2"));
+        assert!(out.contains("winter_mcp_tool_errors_total{tool=\"query_facts\"} 1"));
+        assert!(out.contains("winter_mcp_tool_calls_total{tool=\"create_fact\"} 1"));
+    }
+
+    #[test]
+    fn test_record_secret_access_by_tool_and_secret() {
+        let metrics = McpMetrics::new();
+        metrics.record_secret_access("tool1", "API_KEY");
+        metrics.record_secret_access("tool1", "API_KEY");
+        metrics.record_secret_access("tool2", "TOKEN");
+
+        let out = metrics.render(None);
+        assert!(
+            out.contains("winter_secret_accesses_total{tool_rkey=\"tool1\",secret=\"API_KEY\"} 2")
+        );
+        assert!(
+            out.contains("winter_secret_accesses_total{tool_rkey=\"tool2\",secret=\"TOKEN\"} 1")
+        );
+    }
+
+    #[test]
+    fn test_datalog_histogram_is_cumulative() {
+        let metrics = McpMetrics::new();
+        metrics.record_datalog_query(Duration::from_millis(30), true);
+        metrics.record_datalog_query(Duration::from_secs(3), false);
+
+        let out = metrics.render(None);
+        assert!(out.contains("winter_datalog_query_duration_seconds_bucket{le=\"0.01\"} 0"));
+        assert!(out.contains("winter_datalog_query_duration_seconds_bucket{le=\"0.05\"} 1"));
+        assert!(out.contains("winter_datalog_query_duration_seconds_bucket{le=\"5\"} 2"));
+        assert!(out.contains("winter_datalog_query_duration_seconds_bucket{le=\"+Inf\"} 2"));
+        assert!(out.contains("winter_datalog_query_duration_seconds_count 2"));
+        assert!(out.contains("winter_datalog_query_errors_total 1"));
+    }
+
+    #[test]
+    fn test_render_includes_cache_gauges() {
+        let cache = RepoCache::new();
+        cache.record_event_time(chrono::Utc::now().timestamp_micros());
+
+        let out = McpMetrics::new().render(Some(&*cache));
+        assert!(out.contains("winter_cache_records{collection=\"fact\"} 0"));
+        assert!(out.contains("winter_cache_sync_state 0"));
+        assert!(out.contains("winter_firehose_lag_seconds"));
+        assert!(out.contains("winter_firehose_events_total 1"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 
 /// JSON-RPC request from Claude Code.
 #[derive(Debug, Clone, Deserialize)]
@@ -50,6 +50,26 @@ impl JsonRpcResponse {
             }),
         }
     }
+
+    /// Echo a trace id back to the client.
+    ///
+    /// Successful results get it under `result._meta.traceId`, errors under
+    /// `error.data._meta.traceId`.
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        let meta = json!({ "traceId": trace_id });
+        if let Some(Value::Object(result)) = self.result.as_mut() {
+            result.insert("_meta".to_string(), meta);
+        } else if let Some(error) = self.error.as_mut() {
+            match error.data.as_mut() {
+                Some(Value::Object(data)) => {
+                    data.insert("_meta".to_string(), meta);
+                }
+                _ if error.data.is_none() => error.data = Some(json!({ "_meta": meta })),
+                _ => {}
+            }
+        }
+        self
+    }
 }
 
 /// JSON-RPC error object.
@@ -109,6 +129,10 @@ pub struct ServerCapabilities {
     pub resources: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<ToolsCapability>,
+    /// Non-standard extensions, keyed by name. Clients that don't know an
+    /// extension ignore it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<Value>,
 }
 
 /// Tools capability.
@@ -148,6 +172,18 @@ pub struct CallToolParams {
     pub arguments: HashMap<String, Value>,
 }
 
+/// Parameters for the `tools/callBatch` extension.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallBatchParams {
+    pub calls: Vec<CallToolParams>,
+}
+
+/// Result of `tools/callBatch`: one result per call, in request order.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallBatchResult {
+    pub results: Vec<CallToolResult>,
+}
+
 /// Result of tools/call.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -252,6 +288,20 @@ mod tests {
         assert!(parsed.get("result").is_none());
     }
 
+    #[test]
+    fn json_rpc_response_with_trace_id() {
+        let resp =
+            JsonRpcResponse::success(Some(json!(1)), json!({"content": []})).with_trace_id("t1");
+        assert_eq!(resp.result.unwrap()["_meta"]["traceId"], "t1");
+
+        let resp = JsonRpcResponse::error(Some(json!(1)), -32603, "boom").with_trace_id("t2");
+        assert_eq!(resp.error.unwrap().data.unwrap()["_meta"]["traceId"], "t2");
+
+        // Non-object results are left alone
+        let resp = JsonRpcResponse::success(None, json!("result")).with_trace_id("t3");
+        assert_eq!(resp.result, Some(json!("result")));
+    }
+
     #[test]
     fn json_rpc_response_null_id() {
         let resp = JsonRpcResponse::success(None, json!("result"));
@@ -307,6 +357,7 @@ mod tests {
                 prompts: None,
                 resources: None,
                 tools: Some(ToolsCapability { list_changed: true }),
+                experimental: None,
             },
             server_info: ServerInfo {
                 name: "winter".to_string(),
@@ -324,6 +375,21 @@ mod tests {
         assert!(parsed["capabilities"].get("logging").is_none());
     }
 
+    #[test]
+    fn call_batch_params_deserialize() {
+        let params: CallBatchParams = serde_json::from_value(json!({
+            "calls": [
+                { "name": "query_facts", "arguments": { "query": "q(X)" } },
+                { "name": "list_rules" },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(params.calls.len(), 2);
+        assert_eq!(params.calls[0].name, "query_facts");
+        assert_eq!(params.calls[0].arguments["query"], "q(X)");
+        assert!(params.calls[1].arguments.is_empty());
+    }
+
     // ToolDefinition tests
 
     #[test]
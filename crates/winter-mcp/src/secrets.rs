@@ -2,14 +2,26 @@
 //!
 //! Secrets are stored in a local encrypted file, separate from ATProto records.
 //! This ensures that secret values never leave the local machine.
+//!
+//! Custom tools resolve secrets through a [`SecretProvider`]: the secrets file
+//! ([`FileProvider`]), `WINTER_SECRET_*` environment variables
+//! ([`EnvProvider`]), or several of them layered ([`LayeredProvider`]).
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
 
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+
+/// Prefix for secrets in the environment, both when read by [`EnvProvider`]
+/// and when passed to Deno tools.
+pub const SECRET_ENV_PREFIX: &str = "WINTER_SECRET_";
 
 /// Errors from secret management operations.
 #[derive(Debug, Error)]
@@ -135,7 +147,7 @@ impl SecretManager {
             .iter()
             .filter_map(|name| {
                 self.data.secrets.get(name).map(|value| {
-                    let env_name = format!("WINTER_SECRET_{}", name);
+                    let env_name = format!("{}{}", SECRET_ENV_PREFIX, name);
                     (env_name, value.clone())
                 })
             })
@@ -196,6 +208,215 @@ impl SecretManager {
     }
 }
 
+/// A source of secret values for custom tools.
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Short name for logs and errors.
+    fn name(&self) -> &'static str;
+
+    /// Get a secret's value, or [`SecretError::NotFound`] if this provider
+    /// doesn't have it.
+    async fn get(&self, name: &str) -> Result<String, SecretError>;
+
+    /// Names of all secrets this provider has.
+    async fn list_names(&self) -> Vec<String>;
+}
+
+/// Resolve `names` through `provider`, keyed by their Deno env var names.
+///
+/// Fails on the first secret the provider can't supply.
+pub async fn resolve_subset(
+    provider: &dyn SecretProvider,
+    names: &[String],
+) -> Result<HashMap<String, String>, SecretError> {
+    let mut values = HashMap::new();
+    for name in names {
+        let value = provider.get(name).await?;
+        values.insert(format!("{}{}", SECRET_ENV_PREFIX, name), value);
+    }
+    Ok(values)
+}
+
+/// Secrets from the local secrets file, reloaded on every lookup so values
+/// set through the web UI are picked up.
+pub struct FileProvider {
+    manager: RwLock<SecretManager>,
+}
+
+impl FileProvider {
+    /// Wrap an already-loaded secret manager.
+    pub fn new(manager: SecretManager) -> Self {
+        Self {
+            manager: RwLock::new(manager),
+        }
+    }
+
+    /// Load the secrets file from the default or specified path.
+    pub async fn load(path: Option<PathBuf>) -> Result<Self, SecretError> {
+        Ok(Self::new(SecretManager::load(path).await?))
+    }
+
+    async fn reload(&self) -> tokio::sync::RwLockWriteGuard<'_, SecretManager> {
+        let mut manager = self.manager.write().await;
+        if let Err(e) = manager.reload().await {
+            tracing::warn!(error = %e, "failed to reload secrets");
+        }
+        manager
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn get(&self, name: &str) -> Result<String, SecretError> {
+        self.reload()
+            .await
+            .get(name)
+            .map(str::to_string)
+            .ok_or_else(|| SecretError::NotFound(name.to_string()))
+    }
+
+    async fn list_names(&self) -> Vec<String> {
+        self.reload().await.list_names()
+    }
+}
+
+/// Secrets from `WINTER_SECRET_<NAME>` environment variables, captured when
+/// the provider is created.
+pub struct EnvProvider {
+    vars: HashMap<String, String>,
+}
+
+impl EnvProvider {
+    /// Capture the secrets in the process environment.
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Capture the secrets among `vars`. Empty values are ignored.
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let vars = vars
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .filter_map(|(key, value)| {
+                let name = key.strip_prefix(SECRET_ENV_PREFIX)?;
+                (!name.is_empty()).then(|| (name.to_string(), value))
+            })
+            .collect();
+        Self { vars }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for EnvProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    async fn get(&self, name: &str) -> Result<String, SecretError> {
+        self.vars
+            .get(name)
+            .cloned()
+            .ok_or_else(|| SecretError::NotFound(name.to_string()))
+    }
+
+    async fn list_names(&self) -> Vec<String> {
+        self.vars.keys().cloned().collect()
+    }
+}
+
+/// Providers consulted in order; the first one with the secret wins.
+pub struct LayeredProvider {
+    providers: Vec<Arc<dyn SecretProvider>>,
+}
+
+impl LayeredProvider {
+    pub fn new(providers: Vec<Arc<dyn SecretProvider>>) -> Self {
+        Self { providers }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for LayeredProvider {
+    fn name(&self) -> &'static str {
+        "layered"
+    }
+
+    async fn get(&self, name: &str) -> Result<String, SecretError> {
+        for provider in &self.providers {
+            match provider.get(name).await {
+                Ok(value) => return Ok(value),
+                Err(SecretError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(SecretError::NotFound(name.to_string()))
+    }
+
+    async fn list_names(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        for provider in &self.providers {
+            names.extend(provider.list_names().await);
+        }
+        names.into_iter().collect()
+    }
+}
+
+/// A kind of [`SecretProvider`], as named in configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSource {
+    /// [`EnvProvider`]
+    Env,
+    /// [`FileProvider`]
+    File,
+}
+
+impl FromStr for SecretSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "env" => Ok(Self::Env),
+            "file" => Ok(Self::File),
+            other => Err(format!(
+                "unknown secret provider '{}', expected env or file",
+                other
+            )),
+        }
+    }
+}
+
+impl SecretSource {
+    /// Parse a comma-separated list such as `env,file`.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        s.split(',').map(str::parse).collect()
+    }
+
+    /// Build the provider for `sources`, layered in the order given.
+    ///
+    /// `secrets_path` is used by [`SecretSource::File`].
+    pub async fn build(
+        sources: &[SecretSource],
+        secrets_path: Option<PathBuf>,
+    ) -> Result<Arc<dyn SecretProvider>, SecretError> {
+        let mut providers: Vec<Arc<dyn SecretProvider>> = Vec::new();
+        for source in sources {
+            providers.push(match source {
+                SecretSource::Env => Arc::new(EnvProvider::from_env()),
+                SecretSource::File => Arc::new(FileProvider::load(secrets_path.clone()).await?),
+            });
+        }
+        Ok(match providers.len() {
+            1 => providers.remove(0),
+            _ => Arc::new(LayeredProvider::new(providers)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,4 +523,75 @@ mod tests {
         assert_eq!(mgr1.get("NEW_SECRET"), Some("value2"));
         assert_eq!(mgr1.get("INITIAL"), Some("value1"));
     }
+
+    fn env_provider(vars: &[(&str, &str)]) -> EnvProvider {
+        EnvProvider::from_vars(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+    }
+
+    #[tokio::test]
+    async fn env_provider_reads_prefixed_vars() {
+        let provider = env_provider(&[
+            ("WINTER_SECRET_API_KEY", "env-key"),
+            ("WINTER_SECRET_EMPTY", ""),
+            ("WINTER_SECRETS_PATH", "/tmp/secrets.json"),
+            ("PATH", "/usr/bin"),
+        ]);
+
+        assert_eq!(provider.get("API_KEY").await.unwrap(), "env-key");
+        assert_eq!(provider.list_names().await, vec!["API_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn layered_provider_first_hit_wins() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("secrets.json");
+        let mut mgr = SecretManager::load(Some(path.clone())).await.unwrap();
+        mgr.set("API_KEY", "file-key").await.unwrap();
+        mgr.set("TOKEN", "file-token").await.unwrap();
+
+        let provider = LayeredProvider::new(vec![
+            Arc::new(env_provider(&[("WINTER_SECRET_API_KEY", "env-key")])),
+            Arc::new(FileProvider::load(Some(path)).await.unwrap()),
+        ]);
+
+        assert_eq!(provider.get("API_KEY").await.unwrap(), "env-key");
+        assert_eq!(provider.get("TOKEN").await.unwrap(), "file-token");
+        assert_eq!(provider.list_names().await, vec!["API_KEY", "TOKEN"]);
+    }
+
+    #[tokio::test]
+    async fn missing_secret_is_an_error() {
+        let provider = LayeredProvider::new(vec![Arc::new(env_provider(&[]))]);
+
+        let err = provider.get("MISSING").await.unwrap_err();
+        assert!(matches!(err, SecretError::NotFound(ref name) if name == "MISSING"));
+        assert_eq!(err.to_string(), "secret not found: MISSING");
+
+        let err = resolve_subset(&provider, &["MISSING".to_string()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn file_provider_sees_later_writes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("secrets.json");
+        let provider = FileProvider::load(Some(path.clone())).await.unwrap();
+        assert!(provider.get("LATER").await.is_err());
+
+        let mut mgr = SecretManager::load(Some(path)).await.unwrap();
+        mgr.set("LATER", "value").await.unwrap();
+
+        assert_eq!(provider.get("LATER").await.unwrap(), "value");
+    }
+
+    #[test]
+    fn secret_source_parse_list() {
+        assert_eq!(
+            SecretSource::parse_list("env, file"),
+            Ok(vec![SecretSource::Env, SecretSource::File])
+        );
+        assert!(SecretSource::parse_list("vault").is_err());
+    }
 }
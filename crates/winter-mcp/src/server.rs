@@ -3,18 +3,59 @@
 use std::io::{self, BufRead, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use serde_json::Value;
+use futures_util::future::join_all;
+use serde_json::{Value, json};
 use thiserror::Error;
-use tracing::{debug, error, info};
+use tracing::{Instrument, debug, error, info, info_span};
 
 use crate::{
     protocol::{
-        CallToolParams, InitializeParams, InitializeResult, JsonRpcRequest, JsonRpcResponse,
-        ListToolsResult, ServerCapabilities, ServerInfo, ToolsCapability,
+        CallBatchParams, CallBatchResult, CallToolParams, CallToolResult, InitializeParams,
+        InitializeResult, JsonRpcRequest, JsonRpcResponse, ListToolsResult, ServerCapabilities,
+        ServerInfo, ToolsCapability,
     },
-    tools::ToolRegistry,
+    tools::{ToolRegistry, permissions::is_safe_mcp_tool},
+    trace::{new_trace_id, with_trace_id},
 };
 
+/// JSON-RPC method for the batch tool-call extension.
+///
+/// Not part of MCP; advertised under `capabilities.experimental` so only
+/// clients that look for it use it.
+pub const CALL_BATCH_METHOD: &str = "tools/callBatch";
+
+/// Maximum number of calls in one `tools/callBatch` request.
+pub const MAX_BATCH_CALLS: usize = 32;
+
+/// How one step of a batch is executed.
+#[derive(Debug, PartialEq, Eq)]
+enum BatchStep {
+    /// Indices of adjacent read-only calls, run concurrently.
+    Concurrent(Vec<usize>),
+    /// Index of a call that may write, run on its own.
+    Serial(usize),
+}
+
+/// Split a batch into steps that keep the effect of running it in order.
+///
+/// Adjacent read-only calls are grouped so they run concurrently, and every
+/// other call runs alone once everything before it has finished. A read
+/// therefore never observes a write that comes after it in the batch, and
+/// always observes the writes that come before it.
+fn plan_batch<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<BatchStep> {
+    let mut steps = Vec::new();
+    for (index, name) in names.into_iter().enumerate() {
+        if !is_safe_mcp_tool(name) {
+            steps.push(BatchStep::Serial(index));
+        } else if let Some(BatchStep::Concurrent(group)) = steps.last_mut() {
+            group.push(index);
+        } else {
+            steps.push(BatchStep::Concurrent(vec![index]));
+        }
+    }
+    steps
+}
+
 /// Errors that can occur in the MCP server.
 #[derive(Debug, Error)]
 pub enum McpError {
@@ -115,6 +156,7 @@ impl McpServer {
     ///
     /// The trigger is passed through to tool execution for thought recording,
     /// allowing tool calls to be associated with their originating session.
+    /// Each request also gets a fresh trace id (see [`crate::trace`]).
     /// This method is thread-safe and can be called concurrently.
     pub async fn handle_request_with_trigger(
         &self,
@@ -127,13 +169,21 @@ impl McpServer {
             return None;
         }
 
-        let result = self
-            .handle_request_inner_with_trigger(request, trigger)
-            .await;
-        Some(match result {
+        // Correlate everything this request causes: log lines via the span,
+        // thoughts via the task-local trace id, and the client via `_meta`
+        let trace_id = new_trace_id();
+        let span = info_span!("mcp_request", trace_id = %trace_id, method = %request.method);
+        let result = with_trace_id(
+            trace_id.clone(),
+            self.handle_request_inner_with_trigger(request, trigger)
+                .instrument(span),
+        )
+        .await;
+        let response = match result {
             Ok(value) => JsonRpcResponse::success(request.id.clone(), value),
             Err(e) => JsonRpcResponse::error(request.id.clone(), -32603, e),
-        })
+        };
+        Some(response.with_trace_id(&trace_id))
     }
 
     async fn handle_notification(&self, request: &JsonRpcRequest) {
@@ -159,6 +209,7 @@ impl McpServer {
             "initialize" => self.handle_initialize(request).await,
             "tools/list" => self.handle_list_tools().await,
             "tools/call" => self.handle_call_tool_with_trigger(request, trigger).await,
+            CALL_BATCH_METHOD => self.handle_call_batch(request, trigger).await,
             _ => Err(format!("Unknown method: {}", request.method)),
         }
     }
@@ -183,6 +234,9 @@ impl McpServer {
                 tools: Some(ToolsCapability {
                     list_changed: false,
                 }),
+                experimental: Some(json!({
+                    CALL_BATCH_METHOD: { "maxCalls": MAX_BATCH_CALLS },
+                })),
             },
             server_info: ServerInfo {
                 name: "winter".to_string(),
@@ -221,4 +275,258 @@ impl McpServer {
             .await;
         serde_json::to_value(result).map_err(|e| e.to_string())
     }
+
+    /// Run several tool calls, returning their results in request order.
+    ///
+    /// See [`plan_batch`] for which calls run concurrently. A failing call
+    /// produces an error result in its slot and doesn't stop the batch.
+    async fn handle_call_batch(
+        &self,
+        request: &JsonRpcRequest,
+        trigger: Option<String>,
+    ) -> Result<Value, String> {
+        let params: CallBatchParams = request
+            .params
+            .as_ref()
+            .map(|p| serde_json::from_value(p.clone()))
+            .transpose()
+            .map_err(|e| format!("Invalid batch params: {}", e))?
+            .ok_or("Missing batch params")?;
+
+        if params.calls.is_empty() {
+            return Err("Batch must contain at least one call".to_string());
+        }
+        if params.calls.len() > MAX_BATCH_CALLS {
+            return Err(format!(
+                "Batch has {} calls; at most {} are allowed",
+                params.calls.len(),
+                MAX_BATCH_CALLS
+            ));
+        }
+
+        debug!(calls = params.calls.len(), "executing tool batch");
+
+        let mut results: Vec<Option<CallToolResult>> = vec![None; params.calls.len()];
+        for step in plan_batch(params.calls.iter().map(|c| c.name.as_str())) {
+            match step {
+                BatchStep::Concurrent(indices) => {
+                    let outputs = join_all(indices.iter().map(|&i| {
+                        let call = &params.calls[i];
+                        self.tools.execute_with_trigger(
+                            &call.name,
+                            &call.arguments,
+                            trigger.clone(),
+                        )
+                    }))
+                    .await;
+                    for (i, output) in indices.into_iter().zip(outputs) {
+                        results[i] = Some(output);
+                    }
+                }
+                BatchStep::Serial(i) => {
+                    let call = &params.calls[i];
+                    results[i] = Some(
+                        self.tools
+                            .execute_with_trigger(&call.name, &call.arguments, trigger.clone())
+                            .await,
+                    );
+                }
+            }
+        }
+
+        let result = CallBatchResult {
+            results: results
+                .into_iter()
+                .map(|r| r.expect("every call is in exactly one step"))
+                .collect(),
+        };
+        serde_json::to_value(result).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> JsonRpcRequest {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .unwrap()
+    }
+
+    fn result_text(result: &Value) -> Value {
+        serde_json::from_str(result["content"][0]["text"].as_str().unwrap())
+            .unwrap_or_else(|_| result["content"][0]["text"].clone())
+    }
+
+    #[test]
+    fn test_plan_batch_groups_adjacent_reads() {
+        let steps = plan_batch([
+            "query_facts",
+            "list_rules",
+            "create_fact",
+            "get_note",
+            "create_note",
+            "delete_note",
+            "list_notes",
+        ]);
+        assert_eq!(
+            steps,
+            vec![
+                BatchStep::Concurrent(vec![0, 1]),
+                BatchStep::Serial(2),
+                BatchStep::Concurrent(vec![3]),
+                BatchStep::Serial(4),
+                BatchStep::Serial(5),
+                BatchStep::Concurrent(vec![6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_batch_treats_unknown_tools_as_writes() {
+        assert_eq!(plan_batch(["my_custom_tool"]), vec![BatchStep::Serial(0)]);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_advertises_batch_extension() {
+        let server = McpServer::new(ToolRegistry::empty());
+        let response = server
+            .handle_request(&request(
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0" },
+                }),
+            ))
+            .await
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(
+            result["capabilities"]["experimental"][CALL_BATCH_METHOD]["maxCalls"],
+            MAX_BATCH_CALLS
+        );
+        // Standard capabilities are unchanged for clients that ignore the extension
+        assert_eq!(result["capabilities"]["tools"]["listChanged"], false);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mixed_batch_preserves_order() {
+        let server = McpServer::new(ToolRegistry::empty());
+        let response = server
+            .handle_request(&request(
+                CALL_BATCH_METHOD,
+                json!({
+                    "calls": [
+                        { "name": "check_interruption" },
+                        { "name": "set_active_context", "arguments": { "context": "first" } },
+                        { "name": "check_interruption" },
+                        { "name": "set_active_context", "arguments": { "context": "second" } },
+                        { "name": "no_such_tool" },
+                        { "name": "check_interruption" },
+                    ]
+                }),
+            ))
+            .await
+            .unwrap();
+
+        let results = response.result.unwrap()["results"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(results.len(), 6);
+
+        for i in [0, 2, 5] {
+            assert_eq!(results[i]["isError"], false);
+            assert_eq!(result_text(&results[i])["interrupted"], false);
+        }
+        assert_eq!(result_text(&results[1])["active_context"], "first");
+        assert_eq!(result_text(&results[3])["active_context"], "second");
+        assert_eq!(results[4]["isError"], true);
+        assert!(
+            result_text(&results[4])
+                .as_str()
+                .unwrap()
+                .contains("no_such_tool")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_id_in_thought_tags_and_response_meta() {
+        let tools = ToolRegistry::empty();
+        let (thought_tx, mut thought_rx) =
+            crate::thought_queue::thought_channel(Default::default());
+        tools.set_thought_tx(thought_tx).await;
+        let server = McpServer::new(tools);
+
+        let response = server
+            .handle_request(&request(
+                "tools/call",
+                json!({ "name": "check_interruption", "arguments": {} }),
+            ))
+            .await
+            .unwrap();
+        let trace_id = response.result.as_ref().unwrap()["_meta"]["traceId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(!trace_id.is_empty());
+
+        let thought = thought_rx.try_recv().expect("tool call thought recorded");
+        assert!(
+            thought.tags.contains(&format!("trace:{}", trace_id)),
+            "tags {:?} missing trace {}",
+            thought.tags,
+            trace_id
+        );
+
+        // Each request gets its own id
+        let second = server
+            .handle_request(&request(
+                "tools/call",
+                json!({ "name": "check_interruption", "arguments": {} }),
+            ))
+            .await
+            .unwrap();
+        assert_ne!(
+            second.result.unwrap()["_meta"]["traceId"],
+            trace_id.as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_id_on_error_response() {
+        let server = McpServer::new(ToolRegistry::empty());
+        let response = server
+            .handle_request(&request("no/such/method", json!({})))
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert!(error.data.unwrap()["_meta"]["traceId"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_empty_and_oversized() {
+        let server = McpServer::new(ToolRegistry::empty());
+
+        let empty = server
+            .handle_request(&request(CALL_BATCH_METHOD, json!({ "calls": [] })))
+            .await
+            .unwrap();
+        assert!(empty.error.is_some());
+
+        let calls: Vec<Value> = (0..=MAX_BATCH_CALLS)
+            .map(|_| json!({ "name": "check_interruption" }))
+            .collect();
+        let oversized = server
+            .handle_request(&request(CALL_BATCH_METHOD, json!({ "calls": calls })))
+            .await
+            .unwrap();
+        assert!(oversized.error.is_some());
+    }
 }
@@ -0,0 +1,536 @@
+//! Bounded queue between tool execution and the background thought writer.
+//!
+//! Tool calls record thoughts fire-and-forget, so when the PDS falls behind
+//! the queue fills up and something has to give. [`OverflowPolicy`] decides
+//! what: discard the oldest queued thought, discard the new one, or wait a
+//! bounded time for room. Important kinds (errors, responses) can use a
+//! different policy from the chatty tool-call stream. Every discarded thought
+//! is counted, and reported to [`McpMetrics`] when one is attached.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tracing::warn;
+use winter_atproto::{Thought, ThoughtKind};
+
+use crate::metrics::McpMetrics;
+
+/// Default number of thoughts buffered before the overflow policy applies.
+pub const DEFAULT_THOUGHT_QUEUE_CAPACITY: usize = 100;
+
+/// Default time the writer waits for more thoughts to fill a batch.
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(500);
+
+/// Most writes the PDS accepts in one `applyWrites` call.
+pub const MAX_THOUGHT_BATCH_SIZE: usize = 200;
+
+/// Default byte limit for a thought's content.
+///
+/// ATProto records have size limits and the PDS rejects oversized writes
+/// with `PayloadTooLargeError`; 32KB leaves room for the rest of the record.
+pub const DEFAULT_MAX_THOUGHT_CONTENT_BYTES: usize = 32_000;
+
+/// Default wait for [`OverflowPolicy::Block`] when parsed without a timeout.
+pub const DEFAULT_BLOCK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What to do with a thought when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued thought to make room.
+    DropOldest,
+    /// Discard the thought being queued.
+    DropNewest,
+    /// Wait up to `timeout` for room, then discard the thought being queued.
+    Block { timeout: Duration },
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DropOldest => write!(f, "drop-oldest"),
+            Self::DropNewest => write!(f, "drop-newest"),
+            Self::Block { timeout } => write!(f, "block:{}ms", timeout.as_millis()),
+        }
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    /// Parse `drop-oldest`, `drop-newest`, `block` or `block:<millis>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            "block" => Ok(Self::Block {
+                timeout: DEFAULT_BLOCK_TIMEOUT,
+            }),
+            other => {
+                let millis = other
+                    .strip_prefix("block:")
+                    .map(|ms| ms.trim_end_matches("ms"))
+                    .and_then(|ms| ms.parse::<u64>().ok())
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid overflow policy '{}': expected drop-oldest, drop-newest, block or block:<ms>",
+                            other
+                        )
+                    })?;
+                Ok(Self::Block {
+                    timeout: Duration::from_millis(millis),
+                })
+            }
+        }
+    }
+}
+
+/// Capacity and overflow behaviour of a thought queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThoughtQueueConfig {
+    pub capacity: usize,
+    /// Policy for ordinary thoughts.
+    pub policy: OverflowPolicy,
+    /// Policy for important kinds (see [`is_important`]).
+    pub important_policy: OverflowPolicy,
+    /// Thoughts written per `applyWrites` call. 1 writes each thought with
+    /// its own `createRecord`.
+    pub batch_size: usize,
+    /// How long the writer waits for a batch to fill before flushing it.
+    pub batch_window: Duration,
+    /// Content longer than this many bytes is truncated before writing.
+    pub max_content_bytes: usize,
+}
+
+impl Default for ThoughtQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_THOUGHT_QUEUE_CAPACITY,
+            policy: OverflowPolicy::DropNewest,
+            important_policy: OverflowPolicy::Block {
+                timeout: Duration::from_secs(5),
+            },
+            batch_size: 1,
+            batch_window: DEFAULT_BATCH_WINDOW,
+            max_content_bytes: DEFAULT_MAX_THOUGHT_CONTENT_BYTES,
+        }
+    }
+}
+
+impl ThoughtQueueConfig {
+    /// Policy applied to a thought of the given kind.
+    pub fn policy_for(&self, kind: &ThoughtKind) -> OverflowPolicy {
+        if is_important(kind) {
+            self.important_policy
+        } else {
+            self.policy
+        }
+    }
+}
+
+/// Kinds worth waiting for rather than dropping under load.
+pub fn is_important(kind: &ThoughtKind) -> bool {
+    matches!(kind, ThoughtKind::Error | ThoughtKind::Response)
+}
+
+struct Shared {
+    queue: Mutex<VecDeque<Thought>>,
+    config: Mutex<ThoughtQueueConfig>,
+    not_empty: Notify,
+    not_full: Notify,
+    senders: AtomicUsize,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+    metrics: Mutex<Option<Arc<McpMetrics>>>,
+}
+
+impl Shared {
+    fn config(&self) -> ThoughtQueueConfig {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record_drop(&self, thought: &Thought, reason: &str) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = self
+            .metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            metrics.record_thought_dropped();
+        }
+        warn!(kind = ?thought.kind, reason, "thought queue full, dropped thought");
+    }
+}
+
+/// Create a thought queue, returning its sending and receiving halves.
+pub fn thought_channel(config: ThoughtQueueConfig) -> (ThoughtSender, ThoughtReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(config.capacity)),
+        config: Mutex::new(config),
+        not_empty: Notify::new(),
+        not_full: Notify::new(),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        dropped: AtomicU64::new(0),
+        metrics: Mutex::new(None),
+    });
+    (
+        ThoughtSender {
+            shared: Arc::clone(&shared),
+        },
+        ThoughtReceiver { shared },
+    )
+}
+
+/// Sending half of a thought queue.
+pub struct ThoughtSender {
+    shared: Arc<Shared>,
+}
+
+impl ThoughtSender {
+    /// Queue a thought, applying the overflow policy for its kind if full.
+    ///
+    /// Returns `false` if the thought itself was discarded. Under
+    /// [`OverflowPolicy::DropOldest`] the new thought is always queued
+    /// unless the queue has been closed.
+    pub async fn send(&self, thought: Thought) -> bool {
+        if self.is_closed() {
+            warn!(kind = ?thought.kind, "thought queue closed, dropped thought");
+            return false;
+        }
+        let config = self.shared.config();
+        match config.policy_for(&thought.kind) {
+            OverflowPolicy::DropOldest => {
+                let evicted = {
+                    let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+                    let evicted = if queue.len() >= config.capacity {
+                        queue.pop_front()
+                    } else {
+                        None
+                    };
+                    queue.push_back(thought);
+                    evicted
+                };
+                self.shared.not_empty.notify_one();
+                if let Some(evicted) = evicted {
+                    self.shared.record_drop(&evicted, "drop-oldest");
+                }
+                true
+            }
+            OverflowPolicy::DropNewest => match self.try_push(thought, config.capacity) {
+                Ok(()) => true,
+                Err(thought) => {
+                    self.shared.record_drop(&thought, "drop-newest");
+                    false
+                }
+            },
+            OverflowPolicy::Block { timeout } => {
+                let deadline = tokio::time::Instant::now() + timeout;
+                let mut thought = thought;
+                loop {
+                    let notified = self.shared.not_full.notified();
+                    thought = match self.try_push(thought, config.capacity) {
+                        Ok(()) => return true,
+                        Err(thought) => thought,
+                    };
+                    if self.is_closed() {
+                        self.shared.record_drop(&thought, "queue closed");
+                        return false;
+                    }
+                    if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                        // One last attempt in case room appeared as we timed out.
+                        return match self.try_push(thought, config.capacity) {
+                            Ok(()) => true,
+                            Err(thought) => {
+                                self.shared.record_drop(&thought, "block timeout");
+                                false
+                            }
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    fn try_push(&self, thought: Thought, capacity: usize) -> Result<(), Thought> {
+        {
+            let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+            if queue.len() >= capacity {
+                return Err(thought);
+            }
+            queue.push_back(thought);
+        }
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Close the queue for every sender. Thoughts already queued are still
+    /// delivered; anything sent afterwards is discarded, and the receiver
+    /// sees the end of the queue once it has drained.
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.shared.not_empty.notify_one();
+        // Blocked senders give up rather than wait for room that won't come.
+        self.shared.not_full.notify_waiters();
+    }
+
+    /// Whether [`close`](Self::close) has been called.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::SeqCst)
+    }
+
+    /// Number of thoughts discarded by the overflow policy so far.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Replace the capacity and overflow policies.
+    pub fn set_config(&self, config: ThoughtQueueConfig) {
+        *self.shared.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+        // Waiting senders may now fit under a larger capacity.
+        self.shared.not_full.notify_waiters();
+    }
+
+    /// Report dropped thoughts to the given metrics registry.
+    pub fn set_metrics(&self, metrics: Arc<McpMetrics>) {
+        *self
+            .shared
+            .metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(metrics);
+    }
+}
+
+impl Clone for ThoughtSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for ThoughtSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Wake the receiver so it can observe the queue closing.
+            self.shared.not_empty.notify_one();
+        }
+    }
+}
+
+/// Receiving half of a thought queue.
+pub struct ThoughtReceiver {
+    shared: Arc<Shared>,
+}
+
+impl ThoughtReceiver {
+    /// Wait for the next thought. Returns `None` once the queue is drained
+    /// and either every sender has been dropped or it has been closed.
+    pub async fn recv(&mut self) -> Option<Thought> {
+        loop {
+            let notified = self.shared.not_empty.notified();
+            if let Some(thought) = self.try_recv() {
+                return Some(thought);
+            }
+            if self.shared.senders.load(Ordering::SeqCst) == 0
+                || self.shared.closed.load(Ordering::SeqCst)
+            {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    /// Current queue configuration, including batching thresholds.
+    pub fn config(&self) -> ThoughtQueueConfig {
+        self.shared.config()
+    }
+
+    /// Take the next thought if one is queued.
+    pub fn try_recv(&mut self) -> Option<Thought> {
+        let thought = self
+            .shared
+            .queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()?;
+        self.shared.not_full.notify_one();
+        Some(thought)
+    }
+
+    /// Number of thoughts currently queued.
+    pub fn len(&self) -> usize {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn thought(kind: ThoughtKind, content: &str) -> Thought {
+        Thought {
+            kind,
+            content: content.to_string(),
+            content_type: None,
+            trigger: None,
+            tags: vec![],
+            duration_ms: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn config(policy: OverflowPolicy) -> ThoughtQueueConfig {
+        ThoughtQueueConfig {
+            capacity: 2,
+            policy,
+            important_policy: policy,
+            ..Default::default()
+        }
+    }
+
+    fn drain(rx: &mut ThoughtReceiver) -> Vec<String> {
+        std::iter::from_fn(|| rx.try_recv().map(|t| t.content)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_keeps_queued_thoughts() {
+        let (tx, mut rx) = thought_channel(config(OverflowPolicy::DropNewest));
+        for content in ["a", "b", "c"] {
+            tx.send(thought(ThoughtKind::ToolCall, content)).await;
+        }
+        assert_eq!(drain(&mut rx), ["a", "b"]);
+        assert_eq!(tx.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_latest_thoughts() {
+        let (tx, mut rx) = thought_channel(config(OverflowPolicy::DropOldest));
+        for content in ["a", "b", "c", "d"] {
+            assert!(tx.send(thought(ThoughtKind::ToolCall, content)).await);
+        }
+        assert_eq!(drain(&mut rx), ["c", "d"]);
+        assert_eq!(tx.dropped(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_block_drops_after_timeout() {
+        let (tx, mut rx) = thought_channel(config(OverflowPolicy::Block {
+            timeout: Duration::from_millis(50),
+        }));
+        tx.send(thought(ThoughtKind::ToolCall, "a")).await;
+        tx.send(thought(ThoughtKind::ToolCall, "b")).await;
+
+        assert!(!tx.send(thought(ThoughtKind::ToolCall, "c")).await);
+        assert_eq!(drain(&mut rx), ["a", "b"]);
+        assert_eq!(tx.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_room() {
+        let (tx, mut rx) = thought_channel(config(OverflowPolicy::Block {
+            timeout: Duration::from_secs(5),
+        }));
+        tx.send(thought(ThoughtKind::ToolCall, "a")).await;
+        tx.send(thought(ThoughtKind::ToolCall, "b")).await;
+
+        let sender = tokio::spawn(async move {
+            let queued = tx.send(thought(ThoughtKind::ToolCall, "c")).await;
+            (queued, tx.dropped())
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(rx.recv().await.unwrap().content, "a");
+
+        assert_eq!(sender.await.unwrap(), (true, 0));
+        assert_eq!(drain(&mut rx), ["b", "c"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_important_kinds_use_their_own_policy() {
+        let (tx, mut rx) = thought_channel(ThoughtQueueConfig {
+            capacity: 1,
+            policy: OverflowPolicy::DropNewest,
+            important_policy: OverflowPolicy::DropOldest,
+            ..Default::default()
+        });
+        tx.send(thought(ThoughtKind::ToolCall, "call")).await;
+        assert!(!tx.send(thought(ThoughtKind::ToolCall, "dropped")).await);
+        assert!(tx.send(thought(ThoughtKind::Error, "error")).await);
+
+        assert_eq!(drain(&mut rx), ["error"]);
+        assert_eq!(tx.dropped(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_thoughts_reach_metrics() {
+        let metrics = Arc::new(McpMetrics::new());
+        let (tx, _rx) = thought_channel(config(OverflowPolicy::DropNewest));
+        tx.set_metrics(Arc::clone(&metrics));
+        for content in ["a", "b", "c"] {
+            tx.send(thought(ThoughtKind::ToolCall, content)).await;
+        }
+        assert!(
+            metrics
+                .render(None)
+                .contains("winter_thoughts_dropped_total 1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recv_ends_when_senders_dropped() {
+        let (tx, mut rx) = thought_channel(ThoughtQueueConfig::default());
+        tx.send(thought(ThoughtKind::ToolCall, "a")).await;
+        drop(tx);
+        assert_eq!(rx.recv().await.unwrap().content, "a");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_close_drains_queue_then_ends() {
+        let (tx, mut rx) = thought_channel(ThoughtQueueConfig::default());
+        let other = tx.clone();
+        tx.send(thought(ThoughtKind::ToolCall, "a")).await;
+        tx.send(thought(ThoughtKind::ToolCall, "b")).await;
+
+        other.close();
+        assert!(!tx.send(thought(ThoughtKind::ToolCall, "late")).await);
+
+        assert_eq!(rx.recv().await.unwrap().content, "a");
+        assert_eq!(rx.recv().await.unwrap().content, "b");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("drop-oldest".parse(), Ok(OverflowPolicy::DropOldest));
+        assert_eq!("drop-newest".parse(), Ok(OverflowPolicy::DropNewest));
+        assert_eq!(
+            "block".parse(),
+            Ok(OverflowPolicy::Block {
+                timeout: DEFAULT_BLOCK_TIMEOUT
+            })
+        );
+        assert_eq!(
+            "block:250ms".parse(),
+            Ok(OverflowPolicy::Block {
+                timeout: Duration::from_millis(250)
+            })
+        );
+        assert!("sometimes".parse::<OverflowPolicy>().is_err());
+    }
+}
@@ -12,7 +12,7 @@ use serde_json::{Value, json};
 use crate::protocol::{CallToolResult, ToolDefinition};
 use winter_atproto::{AtUri, Tid};
 
-use super::{ToolMeta, ToolState};
+use super::{ToolMeta, ToolState, changes};
 
 /// Collection name for WhiteWind blog entries.
 const BLOG_COLLECTION: &str = "com.whtwnd.blog.entry";
@@ -266,6 +266,8 @@ pub async fn update_blog_post(
         Err(e) => return CallToolResult::error(format!("Failed to get blog post: {}", e)),
     };
 
+    let before = entry.clone();
+
     // Apply updates (only for provided fields)
     if let Some(title) = arguments.get("title").and_then(|v| v.as_str()) {
         entry.title = title.to_string();
@@ -324,7 +326,8 @@ pub async fn update_blog_post(
                     "cid": response.cid,
                     "title": entry.title,
                     "draft": entry.draft,
-                    "url": url
+                    "url": url,
+                    "changes": changes::record_changes(&before, &entry, &[])
                 })
                 .to_string(),
             )
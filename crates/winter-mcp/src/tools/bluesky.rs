@@ -6,7 +6,7 @@ use std::path::{Path, PathBuf};
 use serde_json::{Value, json};
 use winter_atproto::{ByteSlice, Facet, FacetFeature};
 
-use crate::bluesky::{ImageInput, PostRef};
+use crate::bluesky::{BlueskyError, ImageInput, PostRef};
 use crate::protocol::{CallToolResult, ToolDefinition};
 
 use super::{ToolMeta, ToolState};
@@ -56,7 +56,10 @@ fn resolve_workspace_path(path: &str, workspace: &Path) -> Result<PathBuf, Strin
 /// Each image needs `alt` text plus one of:
 /// - `path`: file path (relative to workspace, or absolute within workspace)
 /// - `data`: base64-encoded image bytes
-fn parse_images(arguments: &HashMap<String, Value>) -> Result<Vec<ImageInput>, String> {
+fn parse_images(
+    arguments: &HashMap<String, Value>,
+    workspace: Option<&Path>,
+) -> Result<Vec<ImageInput>, String> {
     let images_value = match arguments.get("images") {
         Some(v) => v,
         None => return Ok(Vec::new()),
@@ -67,11 +70,6 @@ fn parse_images(arguments: &HashMap<String, Value>) -> Result<Vec<ImageInput>, S
         None => return Ok(Vec::new()),
     };
 
-    let workspace = std::env::var("WINTER_WORKSPACE")
-        .ok()
-        .filter(|p| !p.is_empty())
-        .map(PathBuf::from);
-
     let mut images = Vec::new();
     for (i, img) in images_array.iter().enumerate() {
         let alt = img
@@ -88,9 +86,9 @@ fn parse_images(arguments: &HashMap<String, Value>) -> Result<Vec<ImageInput>, S
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| format!("images[{}]: 'path' must be a string", i))?;
 
-            let workspace = workspace.as_ref().ok_or_else(|| {
+            let workspace = workspace.ok_or_else(|| {
                 format!(
-                    "images[{}]: file paths require WINTER_WORKSPACE to be set",
+                    "images[{}]: file paths require a workspace to be configured",
                     i
                 )
             })?;
@@ -502,6 +500,20 @@ pub fn definitions() -> Vec<ToolDefinition> {
                 "required": ["query"]
             }),
         },
+        ToolDefinition {
+            name: "get_profile".to_string(),
+            description: "Get a Bluesky user's profile: display name, bio, follower/following/post counts, and whether they follow you and you follow them. Use this to understand who you're interacting with when a notification only gives you a DID or handle.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "actor": {
+                        "type": "string",
+                        "description": "DID or handle of the user"
+                    }
+                },
+                "required": ["actor"]
+            }),
+        },
         ToolDefinition {
             name: "get_thread_context".to_string(),
             description: "Get the full context of a Bluesky thread. Returns all posts in the thread tree, list of participants, and your participation metrics (reply count, last reply time, posts since your last reply). Use this before replying to a thread to understand the full conversation.".to_string(),
@@ -638,7 +650,7 @@ pub async fn post_to_bluesky(
 
     let facets = parse_facets(arguments);
 
-    let images = match parse_images(arguments) {
+    let images = match parse_images(arguments, state.settings.workspace.as_deref()) {
         Ok(imgs) => imgs,
         Err(e) => return CallToolResult::error(e),
     };
@@ -698,7 +710,7 @@ pub async fn reply_to_bluesky(
 
     let facets = parse_facets(arguments);
 
-    let images = match parse_images(arguments) {
+    let images = match parse_images(arguments, state.settings.workspace.as_deref()) {
         Ok(imgs) => imgs,
         Err(e) => return CallToolResult::error(e),
     };
@@ -1024,6 +1036,40 @@ pub async fn search_users(state: &ToolState, arguments: &HashMap<String, Value>)
     }
 }
 
+pub async fn get_profile(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let actor = match arguments.get("actor").and_then(|v| v.as_str()) {
+        Some(a) => a.trim_start_matches('@'),
+        None => return CallToolResult::error("Missing required parameter: actor"),
+    };
+
+    let client = match &state.bluesky {
+        Some(c) => c,
+        None => return CallToolResult::error("Bluesky client not configured"),
+    };
+
+    match client.get_profile(actor).await {
+        Ok(profile) => {
+            let response = json!({
+                "did": profile.did,
+                "handle": profile.handle,
+                "display_name": profile.display_name,
+                "description": profile.description,
+                "avatar": profile.avatar,
+                "followers_count": profile.followers_count,
+                "follows_count": profile.follows_count,
+                "posts_count": profile.posts_count,
+                "follows_you": profile.follows_you,
+                "followed_by_you": profile.followed_by_you
+            });
+            CallToolResult::success(serde_json::to_string(&response).unwrap_or_default())
+        }
+        Err(BlueskyError::NotFound(actor)) => {
+            CallToolResult::error(format!("Profile not found: {}", actor))
+        }
+        Err(e) => CallToolResult::error(format!("Failed to get profile: {}", e)),
+    }
+}
+
 pub async fn get_thread_context(
     state: &ToolState,
     arguments: &HashMap<String, Value>,
@@ -1314,7 +1360,7 @@ mod tests {
     #[test]
     fn parse_images_empty_when_no_key() {
         let args = HashMap::new();
-        let result = parse_images(&args).unwrap();
+        let result = parse_images(&args, None).unwrap();
         assert!(result.is_empty());
     }
 
@@ -1322,7 +1368,7 @@ mod tests {
     fn parse_images_empty_when_not_array() {
         let mut args = HashMap::new();
         args.insert("images".to_string(), serde_json::json!("not an array"));
-        let result = parse_images(&args).unwrap();
+        let result = parse_images(&args, None).unwrap();
         assert!(result.is_empty());
     }
 
@@ -1333,7 +1379,7 @@ mod tests {
             "images".to_string(),
             serde_json::json!([{"data": "aGVsbG8="}]),
         );
-        let result = parse_images(&args);
+        let result = parse_images(&args, None);
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("missing 'alt'"));
     }
@@ -1349,7 +1395,7 @@ mod tests {
                 "mime_type": "image/png"
             }]),
         );
-        let result = parse_images(&args).unwrap();
+        let result = parse_images(&args, None).unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].alt, "a test image");
         assert_eq!(result[0].mime_type, "image/png");
@@ -0,0 +1,113 @@
+//! Field-level diffs for update tools.
+//!
+//! Update tools read the existing record before overwriting it and report
+//! what they changed as `changes: { field: { from, to } }`, so thoughts and
+//! the web UI can show a meaningful diff rather than just the rkey.
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use super::truncate_for_summary;
+
+/// Longest string shown on either side of a change. Content fields can be
+/// many kilobytes, so longer values are truncated.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Diff two versions of a record, field by field.
+///
+/// Fields are named as in tool arguments (snake_case). `skip` lists record
+/// fields (as serialized, e.g. `lastUpdated`) that are bookkeeping rather
+/// than edits. A field absent on one side shows as null.
+pub fn record_changes<T: Serialize>(before: &T, after: &T, skip: &[&str]) -> Value {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return json!({});
+    };
+
+    let added = after.keys().filter(|field| !before.contains_key(*field));
+    let mut changes = Map::new();
+    for field in before.keys().chain(added) {
+        if skip.contains(&field.as_str()) {
+            continue;
+        }
+        let from = before.get(field).unwrap_or(&Value::Null);
+        let to = after.get(field).unwrap_or(&Value::Null);
+        if from != to {
+            changes.insert(
+                snake_case(field),
+                json!({ "from": shorten(from), "to": shorten(to) }),
+            );
+        }
+    }
+    Value::Object(changes)
+}
+
+fn shorten(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(truncate_for_summary(s, MAX_VALUE_CHARS)),
+        other => other.clone(),
+    }
+}
+
+/// `lastUpdated` -> `last_updated`.
+fn snake_case(field: &str) -> String {
+    let mut out = String::with_capacity(field.len() + 4);
+    for c in field.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_changed_fields_only() {
+        let before = json!({ "content": "old", "priority": 1, "tags": ["a"] });
+        let after = json!({ "content": "new", "priority": 1, "tags": ["a"] });
+
+        assert_eq!(
+            record_changes(&before, &after, &[]),
+            json!({ "content": { "from": "old", "to": "new" } })
+        );
+    }
+
+    #[test]
+    fn added_and_removed_fields_show_as_null() {
+        let before = json!({ "summary": "s" });
+        let after = json!({ "expiresAt": "2030-01-01T00:00:00Z" });
+
+        assert_eq!(
+            record_changes(&before, &after, &[]),
+            json!({
+                "summary": { "from": "s", "to": null },
+                "expires_at": { "from": null, "to": "2030-01-01T00:00:00Z" }
+            })
+        );
+    }
+
+    #[test]
+    fn skipped_fields_are_ignored() {
+        let before = json!({ "lastUpdated": "2026-01-01T00:00:00Z" });
+        let after = json!({ "lastUpdated": "2026-02-01T00:00:00Z" });
+
+        assert_eq!(record_changes(&before, &after, &["lastUpdated"]), json!({}));
+    }
+
+    #[test]
+    fn long_strings_are_truncated() {
+        let before = json!({ "content": "x".repeat(1000) });
+        let after = json!({ "content": "y" });
+
+        let changes = record_changes(&before, &after, &[]);
+        let from = changes["content"]["from"].as_str().unwrap();
+        assert_eq!(from.chars().count(), MAX_VALUE_CHARS + 3);
+    }
+}
@@ -0,0 +1,201 @@
+//! Schema-guided coercion of tool arguments.
+//!
+//! MCP clients sometimes send scalars as strings (`"5"`, `"true"`). Before a
+//! built-in tool runs, each argument is checked against the `type` declared
+//! for it in the tool's input schema: obvious mismatches are converted and
+//! anything else is rejected with an error naming the parameter.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde_json::{Number, Value};
+
+/// Coerce `arguments` to the property types declared in `schema`.
+///
+/// Arguments without a declared type, and nulls, are passed through as-is.
+/// Borrows the input unchanged when nothing needed converting.
+pub fn coerce_arguments<'a>(
+    schema: &Value,
+    arguments: &'a HashMap<String, Value>,
+) -> Result<Cow<'a, HashMap<String, Value>>, String> {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return Ok(Cow::Borrowed(arguments));
+    };
+
+    let mut coerced: Option<HashMap<String, Value>> = None;
+    for (name, value) in arguments {
+        let types: Vec<&str> = match properties.get(name).and_then(|p| p.get("type")) {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => continue,
+        };
+        if value.is_null() || types.iter().any(|t| matches_type(value, t)) {
+            continue;
+        }
+        match types.iter().find_map(|t| coerce_value(value, t)) {
+            Some(converted) => {
+                coerced
+                    .get_or_insert_with(|| arguments.clone())
+                    .insert(name.clone(), converted);
+            }
+            None => {
+                return Err(format!(
+                    "Invalid type for parameter '{}': expected {}, got {}",
+                    name,
+                    types.join(" or "),
+                    type_name(value)
+                ));
+            }
+        }
+    }
+
+    Ok(coerced.map_or(Cow::Borrowed(arguments), Cow::Owned))
+}
+
+/// Whether `value` already has JSON Schema type `ty`. Unknown types match anything.
+fn matches_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Convert `value` to type `ty` if the intent is unambiguous.
+fn coerce_value(value: &Value, ty: &str) -> Option<Value> {
+    match (ty, value) {
+        ("integer", Value::String(s)) => parse_integer(s.trim()),
+        ("integer", Value::Number(n)) => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && *f >= i64::MIN as f64 && *f <= i64::MAX as f64)
+            .map(|f| Value::from(f as i64)),
+        ("number", Value::String(s)) => {
+            let s = s.trim();
+            parse_integer(s).or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+            })
+        }
+        ("boolean", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+        ("array", Value::String(s)) => serde_json::from_str(s).ok().filter(Value::is_array),
+        ("object", Value::String(s)) => serde_json::from_str(s).ok().filter(Value::is_object),
+        _ => None,
+    }
+}
+
+fn parse_integer(s: &str) -> Option<Value> {
+    s.parse::<i64>()
+        .map(Value::from)
+        .or_else(|_| s.parse::<u64>().map(Value::from))
+        .ok()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "limit": { "type": "integer" },
+                "score": { "type": "number" },
+                "enabled": { "type": "boolean" },
+                "rkey": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "context": { "type": ["string", "null"] },
+                "anything": { "description": "no declared type" }
+            }
+        })
+    }
+
+    fn args(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn stringified_integer_is_accepted() {
+        let input = args(json!({ "limit": "25" }));
+        let coerced = coerce_arguments(&schema(), &input).unwrap();
+        assert_eq!(coerced["limit"], json!(25));
+    }
+
+    #[test]
+    fn non_numeric_string_is_rejected() {
+        let input = args(json!({ "limit": "lots" }));
+        let err = coerce_arguments(&schema(), &input).unwrap_err();
+        assert_eq!(
+            err,
+            "Invalid type for parameter 'limit': expected integer, got string"
+        );
+    }
+
+    #[test]
+    fn fractional_values_are_not_integers() {
+        assert!(coerce_arguments(&schema(), &args(json!({ "limit": "2.5" }))).is_err());
+        assert!(coerce_arguments(&schema(), &args(json!({ "limit": 2.5 }))).is_err());
+
+        let coerced = coerce_arguments(&schema(), &args(json!({ "limit": 3.0 }))).unwrap();
+        assert_eq!(coerced["limit"], json!(3));
+    }
+
+    #[test]
+    fn scalars_are_coerced_from_strings() {
+        let input = args(json!({
+            "score": " 0.75 ",
+            "enabled": "TRUE",
+            "rkey": 12345,
+            "tags": "[\"a\", \"b\"]"
+        }));
+        let coerced = coerce_arguments(&schema(), &input).unwrap();
+        assert_eq!(coerced["score"], json!(0.75));
+        assert_eq!(coerced["enabled"], json!(true));
+        assert_eq!(coerced["rkey"], json!("12345"));
+        assert_eq!(coerced["tags"], json!(["a", "b"]));
+    }
+
+    #[test]
+    fn genuinely_wrong_types_are_rejected() {
+        assert!(coerce_arguments(&schema(), &args(json!({ "enabled": "yes" }))).is_err());
+        assert!(coerce_arguments(&schema(), &args(json!({ "score": "NaN" }))).is_err());
+        assert!(coerce_arguments(&schema(), &args(json!({ "tags": "a,b" }))).is_err());
+        assert!(coerce_arguments(&schema(), &args(json!({ "rkey": {"x": 1} }))).is_err());
+    }
+
+    #[test]
+    fn matching_nulls_and_untyped_arguments_are_borrowed() {
+        let input = args(json!({
+            "limit": 10,
+            "context": null,
+            "enabled": null,
+            "anything": [1, "two"],
+            "unknown": "ignored"
+        }));
+        let coerced = coerce_arguments(&schema(), &input).unwrap();
+        assert!(matches!(coerced, Cow::Borrowed(_)));
+    }
+}
@@ -5,30 +5,36 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::Utc;
 use serde_json::{Value, json};
-use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::deno::{DenoExecutor, DenoPermissions};
 use crate::protocol::{CallToolResult, ToolDefinition};
-use crate::secrets::SecretManager;
+use crate::secrets::{SecretError, SecretProvider, resolve_subset};
+use crate::trace::{current_trace_id, trace_tag};
 use winter_atproto::{
-    ByteSlice, CustomTool, Facet, FacetFeature, IDENTITY_COLLECTION, IDENTITY_KEY, Identity,
-    SECRET_META_COLLECTION, SECRET_META_KEY, SecretEntry, SecretMeta, TOOL_APPROVAL_COLLECTION,
-    TOOL_COLLECTION, Tid, ToolApproval, ToolApprovalStatus,
+    ByteSlice, CustomTool, Facet, FacetFeature, GetRecordResponse, IDENTITY_COLLECTION,
+    IDENTITY_KEY, Identity, SECRET_META_COLLECTION, SECRET_META_KEY, SecretEntry, SecretMeta,
+    TOOL_APPROVAL_COLLECTION, TOOL_COLLECTION, Thought, ThoughtContentType, ThoughtKind, Tid,
+    ToolApproval, ToolApprovalStatus,
 };
 
 use super::permissions::PermissionVec;
-use super::{ToolMeta, ToolState};
+use super::result_cache::ToolResultKey;
+use super::{ToolMeta, ToolState, changes};
 
 /// Maximum code size (64KB).
 const MAX_CODE_SIZE: usize = 64 * 1024;
 
-/// Get the web URL for tool approval (from env or default).
-fn web_url() -> String {
-    std::env::var("WINTER_WEB_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
+/// Web UI URL linked from operator DMs when none is configured.
+const DEFAULT_WEB_URL: &str = "http://localhost:8080";
+
+/// Get the web URL for tool approval (configured, or the default).
+fn web_url(state: &ToolState) -> &str {
+    state.settings.web_url.as_deref().unwrap_or(DEFAULT_WEB_URL)
 }
 
 pub fn definitions() -> Vec<ToolDefinition> {
@@ -77,6 +83,11 @@ pub fn definitions() -> Vec<ToolDefinition> {
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Tools this tool needs to call for chaining. Use AT URIs for custom tools (e.g., 'at://did:plc:xxx/diy.razorgirl.winter.tool/rkey') and plain names for built-in MCP tools (e.g., 'query_facts'). AT URIs enable cross-agent tool sharing."
+                    },
+                    "cache_ttl_secs": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Cache results for identical input for this many seconds. Only set this for deterministic tools; omit it for tools with side effects."
                     }
                 },
                 "required": ["name", "description", "code", "input_schema"]
@@ -126,6 +137,11 @@ pub fn definitions() -> Vec<ToolDefinition> {
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Tools this tool needs to call (optional). Use AT URIs for custom tools, plain names for built-in MCP tools."
+                    },
+                    "cache_ttl_secs": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Cache results for identical input for this many seconds (optional). Set to 0 to stop caching."
                     }
                 },
                 "required": ["name", "code"]
@@ -353,18 +369,21 @@ async fn build_tool_name_map(state: &ToolState, allowed_tools: &[String]) -> Has
 ///
 /// Checks both Winter's PDS (for auto-approvals and legacy) and the operator's PDS
 /// (for operator-granted approvals). Operator PDS approvals take precedence.
-async fn get_approval(state: &ToolState, tool_rkey: &str) -> Option<ToolApproval> {
-    // First check operator's PDS if WINTER_OPERATOR_DID is set
-    match std::env::var("WINTER_OPERATOR_DID") {
-        Ok(operator_did) => {
+async fn get_approval(
+    state: &ToolState,
+    tool_rkey: &str,
+) -> Option<GetRecordResponse<ToolApproval>> {
+    // First check operator's PDS if the operator DID is configured
+    match &state.settings.operator_did {
+        Some(operator_did) => {
             info!(
                 operator_did = %operator_did,
                 tool_rkey = %tool_rkey,
                 "Checking operator's PDS for approval"
             );
-            if let Some(approval) = get_operator_approval(&operator_did, tool_rkey).await {
+            if let Some(approval) = get_operator_approval(operator_did, tool_rkey).await {
                 // Verify winter_did if set
-                if let Some(ref winter_did) = approval.winter_did {
+                if let Some(ref winter_did) = approval.value.winter_did {
                     if let Some(our_did) = state.atproto.did().await {
                         if winter_did != &our_did {
                             warn!(
@@ -386,10 +405,10 @@ async fn get_approval(state: &ToolState, tool_rkey: &str) -> Option<ToolApproval
                 }
             }
         }
-        Err(_) => {
+        None => {
             info!(
                 tool_rkey = %tool_rkey,
-                "WINTER_OPERATOR_DID not set, skipping operator PDS check"
+                "operator DID not configured, skipping operator PDS check"
             );
         }
     }
@@ -401,11 +420,13 @@ async fn get_approval(state: &ToolState, tool_rkey: &str) -> Option<ToolApproval
         .get_record::<ToolApproval>(TOOL_APPROVAL_COLLECTION, tool_rkey)
         .await
         .ok()
-        .map(|r| r.value)
 }
 
 /// Fetch tool approval from operator's PDS (public XRPC, no auth needed).
-async fn get_operator_approval(operator_did: &str, tool_rkey: &str) -> Option<ToolApproval> {
+async fn get_operator_approval(
+    operator_did: &str,
+    tool_rkey: &str,
+) -> Option<GetRecordResponse<ToolApproval>> {
     // Resolve operator's PDS endpoint
     let pds_url = match resolve_pds_for_did(operator_did).await {
         Some(url) => url,
@@ -452,34 +473,13 @@ async fn get_operator_approval(operator_did: &str, tool_rkey: &str) -> Option<To
     }
 
     // Parse the response — ATProto getRecord returns { uri, cid, value }
-    let body: serde_json::Value = match response.json().await {
-        Ok(b) => b,
-        Err(e) => {
-            warn!(
-                error = %e,
-                tool_rkey = %tool_rkey,
-                "Failed to parse operator approval response"
-            );
-            return None;
-        }
-    };
-    let value = match body.get("value") {
-        Some(v) => v,
-        None => {
-            warn!(
-                tool_rkey = %tool_rkey,
-                "Operator approval response missing 'value' field"
-            );
-            return None;
-        }
-    };
-    match serde_json::from_value::<ToolApproval>(value.clone()) {
+    match response.json::<GetRecordResponse<ToolApproval>>().await {
         Ok(approval) => Some(approval),
         Err(e) => {
             warn!(
                 error = %e,
                 tool_rkey = %tool_rkey,
-                "Failed to deserialize operator approval record"
+                "Failed to parse operator approval response"
             );
             None
         }
@@ -634,7 +634,7 @@ async fn notify_operator(
     };
 
     // Build the URL and create explicit facet for it
-    let review_url = format!("{}/tools/{}", web_url(), tool_rkey);
+    let review_url = format!("{}/tools/{}", web_url(state), tool_rkey);
     info!(url = %review_url, "Tool approval notification URL");
     let message_prefix = format!(
         "I created/updated a tool \"{}\" that needs your approval.\n\nRequired secrets: {}{}\n\nPlease review at ",
@@ -752,6 +752,11 @@ pub async fn create_custom_tool(
         })
         .unwrap_or_default();
 
+    let cache_ttl_secs = arguments
+        .get("cache_ttl_secs")
+        .and_then(|v| v.as_u64())
+        .filter(|&ttl| ttl > 0);
+
     let now = Utc::now();
     let tool = CustomTool {
         name: name.to_string(),
@@ -763,6 +768,7 @@ pub async fn create_custom_tool(
         requires_network,
         required_commands: required_commands.clone(),
         required_tools: required_tools.clone(),
+        cache_ttl_secs,
         version: 1,
         created_at: now,
         last_updated: Some(now),
@@ -798,6 +804,8 @@ pub async fn create_custom_tool(
                     allow_workspace_write: None,
                     allowed_commands: Vec::new(),
                     allowed_tools: tool.required_tools.clone(),
+                    allowed_import_hosts: Vec::new(),
+                    tool_code: Some(tool.code.clone()),
                     winter_did: None,
                     operator_did: None,
                     approved_by: Some("auto".to_string()),
@@ -882,6 +890,8 @@ pub async fn update_custom_tool(
         Err(e) => return CallToolResult::error(e),
     };
 
+    let before = tool.clone();
+
     // Update fields
     tool.code = code.to_string();
     tool.version += 1;
@@ -936,6 +946,10 @@ pub async fn update_custom_tool(
             .collect();
     }
 
+    if let Some(ttl) = arguments.get("cache_ttl_secs").and_then(|v| v.as_u64()) {
+        tool.cache_ttl_secs = (ttl > 0).then_some(ttl);
+    }
+
     // Delete any existing approval (code changed = re-approval required)
     if state
         .atproto
@@ -951,6 +965,7 @@ pub async fn update_custom_tool(
 
     // Check if updated tool is safe (including transitive chaining checks)
     let is_safe = is_auto_approvable(state, &tool).await;
+    let changes = changes::record_changes(&before, &tool, &["lastUpdated"]);
 
     match state
         .atproto
@@ -977,6 +992,8 @@ pub async fn update_custom_tool(
                     allow_workspace_write: None,
                     allowed_commands: Vec::new(),
                     allowed_tools: tool.required_tools.clone(),
+                    allowed_import_hosts: Vec::new(),
+                    tool_code: Some(tool.code.clone()),
                     winter_did: None,
                     operator_did: None,
                     approved_by: Some("auto".to_string()),
@@ -1000,6 +1017,7 @@ pub async fn update_custom_tool(
                         "cid": response.cid,
                         "name": name,
                         "version": tool.version,
+                        "changes": changes,
                         "status": "approved",
                         "auto_approved": true,
                         "message": "Tool updated and auto-approved (safe tool). Ready to run."
@@ -1024,6 +1042,7 @@ pub async fn update_custom_tool(
                         "cid": response.cid,
                         "name": name,
                         "version": tool.version,
+                        "changes": changes,
                         "status": "pending_approval",
                         "auto_approved": false,
                         "message": "Tool updated. Previous approval revoked. The operator has been notified."
@@ -1068,7 +1087,7 @@ pub async fn list_custom_tools(
         }
 
         let rkey = item.uri.split('/').next_back().unwrap_or("");
-        let approval = get_approval(state, rkey).await;
+        let approval = get_approval(state, rkey).await.map(|r| r.value);
         let approved = is_approved(&approval, item.value.version);
 
         let status = if approved {
@@ -1103,6 +1122,7 @@ pub async fn list_custom_tools(
             "requires_workspace": item.value.requires_workspace,
             "required_commands": item.value.required_commands,
             "required_tools": item.value.required_tools,
+            "cache_ttl_secs": item.value.cache_ttl_secs,
             "allow_network": approval.as_ref().and_then(|a| a.allow_network),
             "allowed_secrets": approval.as_ref().map(|a| &a.allowed_secrets),
             "workspace_path": approval.as_ref().and_then(|a| a.workspace_path.as_ref()),
@@ -1110,6 +1130,7 @@ pub async fn list_custom_tools(
             "allow_workspace_write": approval.as_ref().and_then(|a| a.allow_workspace_write),
             "allowed_commands": approval.as_ref().map(|a| &a.allowed_commands),
             "allowed_tools": approval.as_ref().map(|a| &a.allowed_tools),
+            "allowed_import_hosts": approval.as_ref().map(|a| &a.allowed_import_hosts),
         }));
     }
 
@@ -1137,7 +1158,7 @@ pub async fn get_custom_tool(
         Err(e) => return CallToolResult::error(e),
     };
 
-    let approval = get_approval(state, &rkey).await;
+    let approval = get_approval(state, &rkey).await.map(|r| r.value);
     let approved = is_approved(&approval, tool.version);
 
     CallToolResult::success(
@@ -1151,6 +1172,7 @@ pub async fn get_custom_tool(
             "requires_workspace": tool.requires_workspace,
             "required_commands": tool.required_commands,
             "required_tools": tool.required_tools,
+            "cache_ttl_secs": tool.cache_ttl_secs,
             "version": tool.version,
             "approved": approved,
             "approval": approval.map(|a| json!({
@@ -1163,6 +1185,7 @@ pub async fn get_custom_tool(
                 "allow_workspace_write": a.allow_workspace_write,
                 "allowed_commands": a.allowed_commands,
                 "allowed_tools": a.allowed_tools,
+                "allowed_import_hosts": a.allowed_import_hosts,
                 "reason": a.reason,
             })),
             "created_at": tool.created_at.to_rfc3339(),
@@ -1172,9 +1195,68 @@ pub async fn get_custom_tool(
     )
 }
 
+/// Tag on thoughts recording a custom tool being given a secret.
+pub const SECRET_ACCESS_TAG: &str = "secret_access";
+
+/// Resolve the secrets granted to a tool, auditing each one it is given.
+///
+/// Every resolved secret queues one `secret_access` thought naming the tool
+/// and the secret (never the value) and bumps the secret-access metric.
+/// Nothing is recorded if any secret fails to resolve, since the tool then
+/// doesn't run.
+async fn resolve_tool_secrets(
+    state: &ToolState,
+    provider: &dyn SecretProvider,
+    tool_name: &str,
+    tool_rkey: &str,
+    names: &[String],
+) -> Result<HashMap<String, String>, SecretError> {
+    let values = resolve_subset(provider, names).await?;
+
+    for secret in names {
+        info!(
+            tool = %tool_name,
+            tool_rkey = %tool_rkey,
+            secret = %secret,
+            "custom tool given secret"
+        );
+        if let Some(ref metrics) = state.metrics {
+            metrics.record_secret_access(tool_rkey, secret);
+        }
+        if let Some(ref tx) = state.thought_tx {
+            let mut tags = vec![SECRET_ACCESS_TAG.to_string()];
+            tags.extend(current_trace_id().iter().map(|id| trace_tag(id)));
+            let thought = Thought {
+                kind: ThoughtKind::ToolCall,
+                content: json!({
+                    "event": SECRET_ACCESS_TAG,
+                    "tool": tool_name,
+                    "tool_rkey": tool_rkey,
+                    "secret": secret,
+                })
+                .to_string(),
+                content_type: Some(ThoughtContentType::ToolCall),
+                trigger: Some("internal:secret_access".to_string()),
+                tags,
+                duration_ms: None,
+                created_at: Utc::now(),
+            };
+            if !tx.send(thought).await {
+                warn!(
+                    tool_rkey = %tool_rkey,
+                    secret = %secret,
+                    "secret access thought not queued"
+                );
+            }
+        }
+    }
+
+    Ok(values)
+}
+
 pub async fn run_custom_tool(
     state: &ToolState,
-    secrets: Option<&Arc<RwLock<SecretManager>>>,
+    secrets: Option<&Arc<dyn SecretProvider>>,
     deno: Option<&DenoExecutor>,
     arguments: &HashMap<String, Value>,
 ) -> CallToolResult {
@@ -1201,7 +1283,33 @@ pub async fn run_custom_tool(
 
     // Check approval status
     let approval = get_approval(state, &rkey).await;
+    let approval_cid = approval.as_ref().and_then(|r| r.cid.clone());
+    let approval = approval.map(|r| r.value);
     let approved = is_approved(&approval, tool.version);
+    let sandbox_mode = !approved;
+
+    // Deterministic tools can be answered from cache without touching secrets or Deno
+    let cache_ttl = tool.cache_ttl_secs.map(Duration::from_secs);
+    let cache_key = ToolResultKey::new(
+        &rkey,
+        tool.version,
+        approval_cid.as_deref(),
+        sandbox_mode,
+        &input,
+    );
+    if cache_ttl.is_some()
+        && let Some(cached) = state.tool_results.get(&cache_key)
+    {
+        debug!(tool = %name, tool_rkey = %rkey, "Serving custom tool result from cache");
+        return CallToolResult::success(
+            json!({
+                "result": cached,
+                "sandboxed": sandbox_mode,
+                "cached": true,
+            })
+            .to_string(),
+        );
+    }
 
     // Track the chaining token for cleanup after execution
     let mut chaining_token: Option<String> = None;
@@ -1209,14 +1317,27 @@ pub async fn run_custom_tool(
     // Build permissions based on approval
     let permissions = if approved {
         let approval = approval.unwrap();
-        let secret_values = if let Some(secrets) = secrets {
-            let mut mgr = secrets.write().await;
-            if let Err(e) = mgr.reload().await {
-                tracing::warn!(error = %e, "failed to reload secrets");
+        let secret_values = match secrets {
+            Some(provider) => {
+                match resolve_tool_secrets(
+                    state,
+                    provider.as_ref(),
+                    name,
+                    &rkey,
+                    &approval.allowed_secrets,
+                )
+                .await
+                {
+                    Ok(values) => values,
+                    Err(e) => {
+                        return CallToolResult::error(format!(
+                            "Failed to resolve secrets for tool '{}': {}",
+                            name, e
+                        ));
+                    }
+                }
             }
-            mgr.get_subset(&approval.allowed_secrets)
-        } else {
-            HashMap::new()
+            None => HashMap::new(),
         };
 
         // Build tool chaining permissions
@@ -1265,6 +1386,7 @@ pub async fn run_custom_tool(
             network: approval.allow_network.unwrap_or(false),
             secrets: secret_values,
             allowed_commands: approval.allowed_commands.clone(),
+            allowed_import_hosts: approval.allowed_import_hosts.clone(),
             allowed_tools,
             tool_name_map,
             tool_token,
@@ -1275,8 +1397,6 @@ pub async fn run_custom_tool(
         DenoPermissions::default()
     };
 
-    let sandbox_mode = !approved;
-
     info!(
         tool = %name,
         sandboxed = sandbox_mode,
@@ -1287,15 +1407,32 @@ pub async fn run_custom_tool(
     );
 
     let result = match deno.execute(&tool.code, &input, permissions).await {
-        Ok(output) => CallToolResult::success(
-            json!({
-                "result": output.result,
-                "duration_ms": output.duration_ms,
-                "sandboxed": sandbox_mode,
-                "stderr": if output.stderr.is_empty() { None } else { Some(output.stderr) },
-            })
-            .to_string(),
-        ),
+        Ok(output) => {
+            if !output.contacted_hosts.is_empty() {
+                info!(
+                    tool = %name,
+                    tool_rkey = %rkey,
+                    hosts = ?output.contacted_hosts,
+                    "Custom tool network egress"
+                );
+            }
+            if let Some(ttl) = cache_ttl {
+                state
+                    .tool_results
+                    .insert(cache_key, output.result.clone(), ttl);
+            }
+            CallToolResult::success(
+                json!({
+                    "result": output.result,
+                    "duration_ms": output.duration_ms,
+                    "sandboxed": sandbox_mode,
+                    "cached": false,
+                    "stderr": if output.stderr.is_empty() { None } else { Some(output.stderr) },
+                    "contacted_hosts": output.contacted_hosts,
+                })
+                .to_string(),
+            )
+        }
         Err(e) => CallToolResult::error(format!(
             "Tool execution failed{}: {}",
             if sandbox_mode {
@@ -1427,7 +1564,7 @@ pub async fn request_secret(
                 && let Some(ref bluesky) = state.bluesky
             {
                 // Build URL and create explicit facet for it
-                let secrets_url = format!("{}/secrets", web_url());
+                let secrets_url = format!("{}/secrets", web_url(state));
                 let message_prefix = format!(
                     "I need a new secret \"{}\".\n\nDescription: {}\n\nPlease add it at ",
                     name, description
@@ -1470,7 +1607,7 @@ pub async fn request_secret(
 
 pub async fn list_secrets(
     state: &ToolState,
-    secrets: Option<&Arc<RwLock<SecretManager>>>,
+    secrets: Option<&Arc<dyn SecretProvider>>,
     _arguments: &HashMap<String, Value>,
 ) -> CallToolResult {
     // Get metadata from ATProto
@@ -1490,8 +1627,7 @@ pub async fn list_secrets(
 
     // Check which secrets have values
     let has_value_set: std::collections::HashSet<String> = if let Some(secrets) = secrets {
-        let mgr = secrets.read().await;
-        mgr.list_names().into_iter().collect()
+        secrets.list_names().await.into_iter().collect()
     } else {
         std::collections::HashSet::new()
     };
@@ -1520,7 +1656,7 @@ pub async fn list_secrets(
 /// Dispatch custom tool calls.
 pub async fn dispatch(
     state: &ToolState,
-    secrets: Option<&Arc<RwLock<SecretManager>>>,
+    secrets: Option<&Arc<dyn SecretProvider>>,
     deno: Option<&DenoExecutor>,
     tool_name: &str,
     arguments: HashMap<String, Value>,
@@ -1541,6 +1677,10 @@ pub async fn dispatch(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::McpMetrics;
+    use crate::secrets::EnvProvider;
+    use crate::thought_queue::{ThoughtQueueConfig, ThoughtReceiver, thought_channel};
+    use crate::tools::ToolRegistry;
     use winter_atproto::{ToolApproval, ToolApprovalStatus};
 
     fn make_approval(status: ToolApprovalStatus, version: i32) -> ToolApproval {
@@ -1555,6 +1695,8 @@ mod tests {
             allow_workspace_write: None,
             allowed_commands: vec![],
             allowed_tools: vec![],
+            allowed_import_hosts: vec![],
+            tool_code: None,
             winter_did: None,
             operator_did: None,
             approved_by: None,
@@ -1591,4 +1733,89 @@ mod tests {
     fn is_approved_rejects_none() {
         assert!(!is_approved(&None, 1));
     }
+
+    async fn audited_registry() -> (ToolRegistry, ThoughtReceiver) {
+        let registry = ToolRegistry::empty();
+        let (tx, rx) = thought_channel(ThoughtQueueConfig::default());
+        {
+            let mut state = registry.state.write().await;
+            state.thought_tx = Some(tx);
+            state.metrics = Some(Arc::new(McpMetrics::new()));
+        }
+        (registry, rx)
+    }
+
+    fn provider() -> EnvProvider {
+        EnvProvider::from_vars([
+            ("WINTER_SECRET_API_KEY".to_string(), "key-value".to_string()),
+            ("WINTER_SECRET_TOKEN".to_string(), "token-value".to_string()),
+            ("WINTER_SECRET_UNGRANTED".to_string(), "other".to_string()),
+        ])
+    }
+
+    fn drain(rx: &mut ThoughtReceiver) -> Vec<Thought> {
+        std::iter::from_fn(|| rx.try_recv()).collect()
+    }
+
+    #[tokio::test]
+    async fn one_audit_entry_per_granted_secret() {
+        let (registry, mut rx) = audited_registry().await;
+        let state = registry.state.read().await;
+        let granted = vec!["API_KEY".to_string(), "TOKEN".to_string()];
+
+        let values = resolve_tool_secrets(&state, &provider(), "weather", "tool1", &granted)
+            .await
+            .unwrap();
+        assert_eq!(values.len(), 2);
+
+        let thoughts = drain(&mut rx);
+        assert_eq!(thoughts.len(), 2);
+        let mut audited: Vec<Value> = thoughts
+            .iter()
+            .map(|t| {
+                assert!(t.tags.contains(&SECRET_ACCESS_TAG.to_string()));
+                assert!(!t.content.contains("key-value"));
+                assert!(!t.content.contains("token-value"));
+                serde_json::from_str(&t.content).unwrap()
+            })
+            .collect();
+        audited.sort_by_key(|v| v["secret"].as_str().unwrap().to_string());
+        assert_eq!(
+            audited,
+            vec![
+                json!({
+                    "event": "secret_access",
+                    "tool": "weather",
+                    "tool_rkey": "tool1",
+                    "secret": "API_KEY",
+                }),
+                json!({
+                    "event": "secret_access",
+                    "tool": "weather",
+                    "tool_rkey": "tool1",
+                    "secret": "TOKEN",
+                }),
+            ]
+        );
+
+        let rendered = state.metrics.as_ref().unwrap().render(None);
+        assert!(
+            rendered
+                .contains("winter_secret_accesses_total{tool_rkey=\"tool1\",secret=\"API_KEY\"} 1")
+        );
+        assert!(!rendered.contains("UNGRANTED"));
+    }
+
+    #[tokio::test]
+    async fn failed_resolution_is_not_audited() {
+        let (registry, mut rx) = audited_registry().await;
+        let state = registry.state.read().await;
+        let granted = vec!["API_KEY".to_string(), "MISSING".to_string()];
+
+        let err = resolve_tool_secrets(&state, &provider(), "weather", "tool1", &granted)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "secret not found: MISSING");
+        assert!(drain(&mut rx).is_empty());
+    }
 }
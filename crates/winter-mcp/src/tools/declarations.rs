@@ -9,13 +9,65 @@ use chrono::Utc;
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{AtUri, FactDeclaration, Tid, WriteOp, WriteResult};
+use winter_atproto::{AtUri, ConfidenceDecay, DecayFunction, FactDeclaration, Tid};
 
-use super::{ToolMeta, ToolState, parse_args};
+use super::{ToolMeta, ToolState, apply_batch_creates, changes, parse_args};
 
 /// Collection name for fact declarations.
 const DECLARATION_COLLECTION: &str = "diy.razorgirl.winter.factDeclaration";
 
+/// Seconds per day, for converting `half_life_days` to the stored seconds.
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// JSON schema for the `decay` argument.
+fn decay_schema(description: &str) -> Value {
+    json!({
+        "type": ["object", "null"],
+        "properties": {
+            "function": {
+                "type": "string",
+                "enum": ["exponential", "linear"],
+                "description": "exponential halves confidence every half-life; linear reaches half at one half-life and zero at two"
+            },
+            "half_life_days": {
+                "type": "number",
+                "description": "Age in days at which confidence has fallen to half"
+            }
+        },
+        "required": ["function", "half_life_days"],
+        "description": description
+    })
+}
+
+/// Parse a `decay` argument. `null` means no decay.
+fn parse_decay(value: &Value) -> Result<Option<ConfidenceDecay>, String> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let function = match value.get("function").and_then(|v| v.as_str()) {
+        Some("exponential") => DecayFunction::Exponential,
+        Some("linear") => DecayFunction::Linear,
+        Some(other) => {
+            return Err(format!(
+                "decay.function must be 'exponential' or 'linear', got '{}'",
+                other
+            ));
+        }
+        None => return Err("decay.function is required".to_string()),
+    };
+    let days = value
+        .get("half_life_days")
+        .and_then(|v| v.as_f64())
+        .ok_or("decay.half_life_days is required")?;
+    if !days.is_finite() || days <= 0.0 {
+        return Err("decay.half_life_days must be positive".to_string());
+    }
+    Ok(Some(ConfidenceDecay {
+        function,
+        half_life_secs: ((days * SECS_PER_DAY).round() as i64).max(1),
+    }))
+}
+
 /// Safely truncate a string to a maximum number of characters.
 /// This handles UTF-8 correctly by counting characters, not bytes.
 pub(crate) fn truncate_chars(s: &str, max_chars: usize) -> String {
@@ -48,7 +100,10 @@ create_fact_declaration(
   description: "Records when a conversation thread has concluded",
   tags: ["conversation", "tracking"]
 )
-```"#.to_string(),
+```
+
+Set `decay` to let confidence in this predicate's facts fade with age. The
+decayed values are queryable as `current_confidence(FactUri, Confidence, Rkey)`."#.to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -86,7 +141,8 @@ create_fact_declaration(
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Tags for categorization (max 20)"
-                    }
+                    },
+                    "decay": decay_schema("Confidence decay for facts of this predicate (optional)")
                 },
                 "required": ["predicate", "args", "description"]
             }),
@@ -127,7 +183,8 @@ create_fact_declaration(
                                     "type": "array",
                                     "items": { "type": "string" },
                                     "description": "Tags for categorization (max 20)"
-                                }
+                                },
+                                "decay": decay_schema("Confidence decay for facts of this predicate (optional)")
                             },
                             "required": ["predicate", "args", "description"]
                         },
@@ -168,7 +225,8 @@ create_fact_declaration(
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "New tags (replaces existing)"
-                    }
+                    },
+                    "decay": decay_schema("New confidence decay (null removes it)")
                 },
                 "required": ["rkey"]
             }),
@@ -267,11 +325,17 @@ pub async fn create_fact_declaration(
         })
         .unwrap_or_default();
 
+    let decay = match arguments.get("decay").map(parse_decay).transpose() {
+        Ok(decay) => decay.flatten(),
+        Err(e) => return CallToolResult::error(e),
+    };
+
     let declaration = FactDeclaration {
         predicate: predicate.clone(),
         args,
         description,
         tags,
+        decay,
         created_at: Utc::now(),
         last_updated: None,
     };
@@ -385,11 +449,17 @@ pub async fn create_fact_declarations(
             })
             .unwrap_or_default();
 
+        let decay = match obj.get("decay").map(parse_decay).transpose() {
+            Ok(decay) => decay.flatten(),
+            Err(e) => return CallToolResult::error(format!("declarations[{}]: {}", i, e)),
+        };
+
         let declaration = FactDeclaration {
             predicate,
             args,
             description,
             tags,
+            decay,
             created_at: now,
             last_updated: None,
         };
@@ -398,55 +468,29 @@ pub async fn create_fact_declarations(
         validated.push((rkey, declaration));
     }
 
-    // Build WriteOp list
-    let writes: Vec<WriteOp> = validated
-        .iter()
-        .map(|(rkey, decl)| WriteOp::Create {
-            collection: DECLARATION_COLLECTION.to_string(),
-            rkey: rkey.clone(),
-            value: serde_json::to_value(decl).unwrap(),
-        })
-        .collect();
-
-    // Execute batch write
-    match state.atproto.apply_writes(writes).await {
-        Ok(response) => {
-            // Update cache for each created record
-            for ((rkey, decl), result) in validated.iter().zip(response.results.iter()) {
-                if let WriteResult::Create { cid, .. } = result
-                    && let Some(cache) = &state.cache
-                {
-                    cache.upsert_declaration(rkey.clone(), decl.clone(), cid.clone());
-                }
-            }
-
-            let results: Vec<Value> = validated
-                .iter()
-                .zip(response.results.iter())
-                .map(|((rkey, decl), result)| {
-                    if let WriteResult::Create { uri, cid } = result {
-                        json!({
-                            "rkey": rkey,
-                            "uri": uri,
-                            "cid": cid,
-                            "predicate": decl.predicate,
-                            "arity": decl.args.len()
-                        })
-                    } else {
-                        json!({ "rkey": rkey, "error": "unexpected result type" })
-                    }
-                })
-                .collect();
-
-            CallToolResult::success(
-                json!({
-                    "created": validated.len(),
-                    "results": results
-                })
-                .to_string(),
-            )
+    let describe = |_, decl: &FactDeclaration| {
+        let mut item = serde_json::Map::new();
+        item.insert("predicate".to_string(), json!(decl.predicate));
+        item.insert("arity".to_string(), json!(decl.args.len()));
+        item
+    };
+    let on_created = |rkey: &str, decl: &FactDeclaration, cid: &str| {
+        if let Some(cache) = &state.cache {
+            cache.upsert_declaration(rkey.to_string(), decl.clone(), cid.to_string());
         }
-        Err(e) => CallToolResult::error(format!("Batch write failed: {}", e)),
+    };
+
+    match apply_batch_creates(
+        state,
+        DECLARATION_COLLECTION,
+        &validated,
+        describe,
+        on_created,
+    )
+    .await
+    {
+        Ok(result) => CallToolResult::success(result.to_string()),
+        Err(result) => CallToolResult::error(result.to_string()),
     }
 }
 
@@ -472,7 +516,8 @@ pub async fn update_fact_declaration(
         Err(e) => return CallToolResult::error(format!("Failed to get fact declaration: {}", e)),
     };
 
-    let mut changes = Vec::new();
+    let before = declaration.clone();
+    let mut updated = Vec::new();
 
     // Update args if provided
     if let Some(arr) = arguments.get("args").and_then(|v| v.as_array()) {
@@ -482,7 +527,7 @@ pub async fn update_fact_declaration(
         match parse_args(arr) {
             Ok(args) => {
                 declaration.args = args;
-                changes.push("args");
+                updated.push("args");
             }
             Err(e) => return e,
         }
@@ -491,7 +536,7 @@ pub async fn update_fact_declaration(
     // Update description if provided
     if let Some(desc) = arguments.get("description").and_then(|v| v.as_str()) {
         declaration.description = truncate_chars(desc, 1024);
-        changes.push("description");
+        updated.push("description");
     }
 
     // Update tags if provided
@@ -502,10 +547,21 @@ pub async fn update_fact_declaration(
             .take(20)
             .map(|s| truncate_chars(&s, 64))
             .collect();
-        changes.push("tags");
+        updated.push("tags");
+    }
+
+    // Update decay if provided (null removes it)
+    if let Some(value) = arguments.get("decay") {
+        match parse_decay(value) {
+            Ok(decay) => {
+                declaration.decay = decay;
+                updated.push("decay");
+            }
+            Err(e) => return CallToolResult::error(e),
+        }
     }
 
-    if changes.is_empty() {
+    if updated.is_empty() {
         return CallToolResult::error("No changes specified");
     }
 
@@ -532,7 +588,7 @@ pub async fn update_fact_declaration(
                     "rkey": rkey,
                     "uri": response.uri,
                     "cid": response.cid,
-                    "changes": changes,
+                    "changes": changes::record_changes(&before, &declaration, &["lastUpdated"]),
                     "predicate": declaration.predicate
                 })
                 .to_string(),
@@ -629,6 +685,7 @@ pub async fn list_fact_declarations(
                 }).collect::<Vec<_>>(),
                 "description": r.value.description,
                 "tags": r.value.tags,
+                "decay": r.value.decay,
                 "created_at": r.value.created_at.to_rfc3339()
             })
         })
@@ -666,6 +723,20 @@ mod tests {
         assert_eq!(args[1].description, None);
     }
 
+    #[test]
+    fn test_parse_decay() {
+        let decay = parse_decay(&json!({"function": "exponential", "half_life_days": 1.5}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(decay.function, DecayFunction::Exponential);
+        assert_eq!(decay.half_life_secs, 129_600);
+
+        assert_eq!(parse_decay(&Value::Null).unwrap(), None);
+        assert!(parse_decay(&json!({"function": "cubic", "half_life_days": 1})).is_err());
+        assert!(parse_decay(&json!({"function": "linear", "half_life_days": 0})).is_err());
+        assert!(parse_decay(&json!({"function": "linear"})).is_err());
+    }
+
     #[test]
     fn test_parse_args_missing_name() {
         let arr = vec![json!({"type": "symbol"})];
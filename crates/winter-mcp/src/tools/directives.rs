@@ -9,9 +9,11 @@ use chrono::Utc;
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{AtUri, Directive, DirectiveKind, Tid, WriteOp, WriteResult};
+use winter_atproto::{AtUri, Directive, DirectiveKind, Tid, directive_history};
 
-use super::{MAX_BATCH_SIZE, ToolMeta, ToolState, truncate_string};
+use super::{
+    MAX_BATCH_SIZE, ToolMeta, ToolState, apply_batch_creates, changes, dry_run, truncate_string,
+};
 
 /// Collection name for directives.
 const DIRECTIVE_COLLECTION: &str = "diy.razorgirl.winter.directive";
@@ -29,13 +31,14 @@ Kinds:
 - guideline: Behavioral guidelines (e.g., "Engage thoughtfully with disagreement")
 - self_concept: Self-understanding prose (e.g., "I experience genuine curiosity when...")
 - boundary: Limits on behavior (e.g., "I will not pretend certainty I don't have")
-- aspiration: What you want to become (e.g., "Develop a distinctive voice in writing")"#.to_string(),
+- aspiration: What you want to become (e.g., "Develop a distinctive voice in writing")
+- goal: Something to do when awakened with nothing pending (e.g., "Review facts you haven't revisited in a month"). Priority weights how often it's picked"#.to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "kind": {
                         "type": "string",
-                        "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration"],
+                        "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration", "goal"],
                         "description": "The type of directive"
                     },
                     "content": {
@@ -84,7 +87,7 @@ Kinds:
                             "properties": {
                                 "kind": {
                                     "type": "string",
-                                    "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration"],
+                                    "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration", "goal"],
                                     "description": "The type of directive"
                                 },
                                 "content": {
@@ -182,7 +185,7 @@ Kinds:
                 "properties": {
                     "kind": {
                         "type": "string",
-                        "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration"],
+                        "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration", "goal"],
                         "description": "Filter by directive kind (optional)"
                     },
                     "include_inactive": {
@@ -204,6 +207,49 @@ Kinds:
                 }
             }),
         },
+        ToolDefinition {
+            name: "get_directive_history".to_string(),
+            description: "Show how a directive evolved: the chain of directives that superseded one another, oldest first.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of any directive in the chain"
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
+        ToolDefinition {
+            name: "detect_directive_conflicts".to_string(),
+            description: "Find pairs of directives that may contradict each other (similar wording, opposite polarity). Advisory only: review the candidates and decide what, if anything, to change.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "kinds": {
+                        "type": "array",
+                        "items": {
+                            "type": "string",
+                            "enum": ["value", "interest", "belief", "guideline", "self_concept", "boundary", "aspiration", "goal"]
+                        },
+                        "description": "Directive kinds to compare (default: guideline, boundary, value, belief)"
+                    },
+                    "min_overlap": {
+                        "type": "number",
+                        "description": "Minimum share of key terms two directives must have in common, 0.0 to 1.0 (default 0.3)"
+                    },
+                    "include_inactive": {
+                        "type": "boolean",
+                        "description": "Also compare inactive directives (default false)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of candidate pairs to return (default 20)"
+                    }
+                }
+            }),
+        },
     ]
 }
 
@@ -222,6 +268,7 @@ fn parse_directive_kind(s: &str) -> Option<DirectiveKind> {
         "self_concept" => Some(DirectiveKind::SelfConcept),
         "boundary" => Some(DirectiveKind::Boundary),
         "aspiration" => Some(DirectiveKind::Aspiration),
+        "goal" => Some(DirectiveKind::Goal),
         _ => None,
     }
 }
@@ -310,6 +357,10 @@ pub async fn create_directive(
         last_updated: None,
     };
 
+    if dry_run::requested(arguments) {
+        return dry_run::preview(DIRECTIVE_COLLECTION, &directive, &[]);
+    }
+
     let rkey = Tid::now().to_string();
 
     match state
@@ -359,8 +410,8 @@ pub async fn create_directives(
     }
 
     // Validate and parse all directives first
-    let mut validated: Vec<(String, Directive, String)> =
-        Vec::with_capacity(directives_array.len());
+    let mut validated: Vec<(String, Directive)> = Vec::with_capacity(directives_array.len());
+    let mut kinds: Vec<String> = Vec::with_capacity(directives_array.len());
     let now = Utc::now();
 
     for (i, dir_val) in directives_array.iter().enumerate() {
@@ -445,58 +496,33 @@ pub async fn create_directives(
         };
 
         let rkey = Tid::now().to_string();
-        validated.push((rkey, directive, kind_str.to_string()));
+        validated.push((rkey, directive));
+        kinds.push(kind_str.to_string());
     }
 
-    // Build WriteOp list
-    let writes: Vec<WriteOp> = validated
-        .iter()
-        .map(|(rkey, directive, _)| WriteOp::Create {
-            collection: DIRECTIVE_COLLECTION.to_string(),
-            rkey: rkey.clone(),
-            value: serde_json::to_value(directive).unwrap(),
-        })
-        .collect();
-
-    // Execute batch write
-    match state.atproto.apply_writes(writes).await {
-        Ok(response) => {
-            // Update cache for each created record
-            for ((rkey, directive, _), result) in validated.iter().zip(response.results.iter()) {
-                if let WriteResult::Create { cid, .. } = result
-                    && let Some(cache) = &state.cache
-                {
-                    cache.upsert_directive(rkey.clone(), directive.clone(), cid.clone());
-                }
-            }
-
-            let results: Vec<Value> = validated
-                .iter()
-                .zip(response.results.iter())
-                .map(|((rkey, directive, kind_str), result)| {
-                    if let WriteResult::Create { uri, cid } = result {
-                        json!({
-                            "rkey": rkey,
-                            "uri": uri,
-                            "cid": cid,
-                            "kind": kind_str,
-                            "content": directive.content
-                        })
-                    } else {
-                        json!({ "rkey": rkey, "error": "unexpected result type" })
-                    }
-                })
-                .collect();
-
-            CallToolResult::success(
-                json!({
-                    "created": validated.len(),
-                    "results": results
-                })
-                .to_string(),
-            )
+    let describe = |i: usize, directive: &Directive| {
+        let mut item = serde_json::Map::new();
+        item.insert("kind".to_string(), json!(kinds[i]));
+        item.insert("content".to_string(), json!(directive.content));
+        item
+    };
+    let on_created = |rkey: &str, directive: &Directive, cid: &str| {
+        if let Some(cache) = &state.cache {
+            cache.upsert_directive(rkey.to_string(), directive.clone(), cid.to_string());
         }
-        Err(e) => CallToolResult::error(format!("Batch write failed: {}", e)),
+    };
+
+    match apply_batch_creates(
+        state,
+        DIRECTIVE_COLLECTION,
+        &validated,
+        describe,
+        on_created,
+    )
+    .await
+    {
+        Ok(result) => CallToolResult::success(result.to_string()),
+        Err(result) => CallToolResult::error(result.to_string()),
     }
 }
 
@@ -522,7 +548,8 @@ pub async fn update_directive(
         Err(e) => return CallToolResult::error(format!("Failed to get directive: {}", e)),
     };
 
-    let mut changes = Vec::new();
+    let before = directive.clone();
+    let mut updated = Vec::new();
 
     // Update content if provided
     if let Some(content) = arguments.get("content").and_then(|v| v.as_str()) {
@@ -533,25 +560,25 @@ pub async fn update_directive(
             ));
         }
         directive.content = content.to_string();
-        changes.push("content");
+        updated.push("content");
     }
 
     // Update summary if provided
     if let Some(summary) = arguments.get("summary").and_then(|v| v.as_str()) {
         directive.summary = Some(truncate_string(summary, 256));
-        changes.push("summary");
+        updated.push("summary");
     }
 
     // Update confidence if provided
     if let Some(confidence) = arguments.get("confidence").and_then(|v| v.as_f64()) {
         directive.confidence = Some(confidence.clamp(0.0, 1.0));
-        changes.push("confidence");
+        updated.push("confidence");
     }
 
     // Update source if provided
     if let Some(source) = arguments.get("source").and_then(|v| v.as_str()) {
         directive.source = Some(truncate_string(source, 500));
-        changes.push("source");
+        updated.push("source");
     }
 
     // Update tags if provided
@@ -562,16 +589,16 @@ pub async fn update_directive(
             .take(10)
             .map(|s| truncate_string(&s, 64))
             .collect();
-        changes.push("tags");
+        updated.push("tags");
     }
 
     // Update priority if provided
     if let Some(priority) = arguments.get("priority").and_then(|v| v.as_i64()) {
         directive.priority = priority as i32;
-        changes.push("priority");
+        updated.push("priority");
     }
 
-    if changes.is_empty() {
+    if updated.is_empty() {
         return CallToolResult::error("No changes specified");
     }
 
@@ -594,7 +621,7 @@ pub async fn update_directive(
                     "rkey": rkey,
                     "uri": response.uri,
                     "cid": response.cid,
-                    "changes": changes,
+                    "changes": changes::record_changes(&before, &directive, &["lastUpdated"]),
                     "kind": directive.kind.to_string(),
                     "content": directive.content
                 })
@@ -761,10 +788,389 @@ pub async fn list_directives(
     )
 }
 
+pub async fn get_directive_history(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let directives: HashMap<String, Directive> = match state
+        .atproto
+        .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
+        .await
+    {
+        Ok(records) => records
+            .into_iter()
+            .map(|r| (AtUri::extract_rkey(&r.uri).to_string(), r.value))
+            .collect(),
+        Err(e) => return CallToolResult::error(format!("Failed to list directives: {}", e)),
+    };
+
+    let chain = directive_history(rkey, &directives);
+    if chain.is_empty() {
+        return CallToolResult::error(format!("Directive not found: {}", rkey));
+    }
+
+    let history: Vec<Value> = chain
+        .into_iter()
+        .map(|(key, d)| {
+            json!({
+                "rkey": key,
+                "kind": d.kind.to_string(),
+                "content": d.content,
+                "summary": d.summary,
+                "active": d.active,
+                "confidence": d.confidence,
+                "source": d.source,
+                "supersedes": d.supersedes,
+                "created_at": d.created_at.to_rfc3339(),
+                "last_updated": d.last_updated.map(|dt| dt.to_rfc3339())
+            })
+        })
+        .collect();
+
+    CallToolResult::success(
+        json!({
+            "rkey": rkey,
+            "count": history.len(),
+            "history": history
+        })
+        .to_string(),
+    )
+}
+
+/// Kinds compared by `detect_directive_conflicts` unless others are given.
+const DEFAULT_CONFLICT_KINDS: &[DirectiveKind] = &[
+    DirectiveKind::Guideline,
+    DirectiveKind::Boundary,
+    DirectiveKind::Value,
+    DirectiveKind::Belief,
+];
+
+/// Default minimum term overlap for a conflict candidate.
+const DEFAULT_MIN_OVERLAP: f64 = 0.3;
+
+/// Words that flip the polarity of a directive.
+const NEGATION_WORDS: &[&str] = &[
+    "not",
+    "no",
+    "never",
+    "nor",
+    "don't",
+    "dont",
+    "doesn't",
+    "won't",
+    "can't",
+    "cannot",
+    "shouldn't",
+    "mustn't",
+    "avoid",
+    "refuse",
+    "stop",
+    "without",
+];
+
+/// Words too common to say anything about what a directive is about.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "to", "of", "in", "on", "at", "for", "with", "by",
+    "from", "about", "as", "is", "are", "be", "been", "being", "it", "its", "this", "that",
+    "these", "those", "i", "me", "my", "you", "your", "we", "our", "they", "them", "their", "do",
+    "does", "should", "must", "will", "can", "always", "when", "if", "so", "any", "all",
+];
+
+/// A pair of directives that may contradict each other.
+#[derive(Debug, Clone, PartialEq)]
+struct ConflictCandidate {
+    a: String,
+    b: String,
+    /// Share of key terms the two directives have in common (Jaccard index).
+    overlap: f64,
+    shared_terms: Vec<String>,
+}
+
+/// Split directive content into key terms and whether it is negated.
+fn directive_terms(content: &str) -> (std::collections::BTreeSet<String>, bool) {
+    let mut terms = std::collections::BTreeSet::new();
+    let mut negated = false;
+    for word in content
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\''))
+        .filter(|w| !w.is_empty())
+    {
+        if NEGATION_WORDS.contains(&word) {
+            negated = true;
+        } else if !STOP_WORDS.contains(&word) && word.len() > 2 {
+            terms.insert(stem(word));
+        }
+    }
+    (terms, negated)
+}
+
+/// Crude suffix stripping so "replies" and "replying" match "reply".
+fn stem(word: &str) -> String {
+    if let Some(base) = word.strip_suffix("ies")
+        && base.len() >= 3
+    {
+        return format!("{}y", base);
+    }
+    for suffix in ["ing", "ed", "s"] {
+        if let Some(base) = word.strip_suffix(suffix)
+            && base.len() >= 3
+        {
+            return base.to_string();
+        }
+    }
+    word.to_string()
+}
+
+/// Flag pairs of directives that share most of their key terms but differ
+/// in polarity, e.g. "reply to strangers" against "never reply to strangers".
+///
+/// Results are sorted by overlap, highest first.
+fn find_directive_conflicts(
+    directives: &[(String, Directive)],
+    kinds: &[DirectiveKind],
+    min_overlap: f64,
+) -> Vec<ConflictCandidate> {
+    let analyzed: Vec<_> = directives
+        .iter()
+        .filter(|(_, d)| kinds.contains(&d.kind))
+        .map(|(rkey, d)| {
+            let (terms, negated) = directive_terms(&d.content);
+            (rkey, terms, negated)
+        })
+        .collect();
+
+    let mut candidates = Vec::new();
+    for (i, (rkey_a, terms_a, negated_a)) in analyzed.iter().enumerate() {
+        for (rkey_b, terms_b, negated_b) in &analyzed[i + 1..] {
+            if negated_a == negated_b {
+                continue;
+            }
+            let shared: Vec<String> = terms_a.intersection(terms_b).cloned().collect();
+            let union = terms_a.union(terms_b).count();
+            if shared.len() < 2 || union == 0 {
+                continue;
+            }
+            let overlap = shared.len() as f64 / union as f64;
+            if overlap >= min_overlap {
+                candidates.push(ConflictCandidate {
+                    a: (*rkey_a).clone(),
+                    b: (*rkey_b).clone(),
+                    overlap,
+                    shared_terms: shared,
+                });
+            }
+        }
+    }
+
+    candidates.sort_by(|x, y| y.overlap.total_cmp(&x.overlap));
+    candidates
+}
+
+pub async fn detect_directive_conflicts(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let kinds: Vec<DirectiveKind> = match arguments.get("kinds").and_then(|v| v.as_array()) {
+        Some(values) => {
+            let mut kinds = Vec::new();
+            for value in values {
+                match value.as_str().and_then(parse_directive_kind) {
+                    Some(kind) => kinds.push(kind),
+                    None => {
+                        return CallToolResult::error(format!("Invalid directive kind: {}", value));
+                    }
+                }
+            }
+            kinds
+        }
+        None => DEFAULT_CONFLICT_KINDS.to_vec(),
+    };
+    let min_overlap = arguments
+        .get("min_overlap")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(DEFAULT_MIN_OVERLAP)
+        .clamp(0.0, 1.0);
+    let include_inactive = arguments
+        .get("include_inactive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let limit = arguments
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(20) as usize;
+
+    let directives: Vec<(String, Directive)> = match state
+        .atproto
+        .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
+        .await
+    {
+        Ok(records) => records
+            .into_iter()
+            .filter(|r| include_inactive || r.value.active)
+            .map(|r| (AtUri::extract_rkey(&r.uri).to_string(), r.value))
+            .collect(),
+        Err(e) => return CallToolResult::error(format!("Failed to list directives: {}", e)),
+    };
+    let by_rkey: HashMap<&str, &Directive> =
+        directives.iter().map(|(k, d)| (k.as_str(), d)).collect();
+
+    let candidates = find_directive_conflicts(&directives, &kinds, min_overlap);
+    let total = candidates.len();
+    let conflicts: Vec<Value> = candidates
+        .into_iter()
+        .take(limit)
+        .map(|c| {
+            let describe = |rkey: &str| {
+                let d = by_rkey[rkey];
+                json!({
+                    "rkey": rkey,
+                    "kind": d.kind.to_string(),
+                    "content": d.content
+                })
+            };
+            json!({
+                "pair": format!("{} vs {}", c.a, c.b),
+                "a": describe(&c.a),
+                "b": describe(&c.b),
+                "overlap": (c.overlap * 100.0).round() / 100.0,
+                "shared_terms": c.shared_terms,
+                "reason": "similar wording with opposite polarity"
+            })
+        })
+        .collect();
+
+    CallToolResult::success(
+        json!({
+            "count": conflicts.len(),
+            "total_candidates": total,
+            "conflicts": conflicts
+        })
+        .to_string(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn test_directive(kind: DirectiveKind, content: &str) -> Directive {
+        Directive {
+            kind,
+            content: content.to_string(),
+            summary: None,
+            active: true,
+            confidence: None,
+            source: None,
+            supersedes: None,
+            tags: Vec::new(),
+            priority: 0,
+            created_at: Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    #[test]
+    fn test_conflict_flags_contradictory_pair() {
+        let directives = vec![
+            (
+                "guide".to_string(),
+                test_directive(
+                    DirectiveKind::Guideline,
+                    "Always reply quickly to mentions from strangers",
+                ),
+            ),
+            (
+                "bound".to_string(),
+                test_directive(
+                    DirectiveKind::Boundary,
+                    "Never reply to mentions from strangers",
+                ),
+            ),
+        ];
+
+        let conflicts =
+            find_directive_conflicts(&directives, DEFAULT_CONFLICT_KINDS, DEFAULT_MIN_OVERLAP);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].a, "guide");
+        assert_eq!(conflicts[0].b, "bound");
+        assert!(conflicts[0].shared_terms.contains(&"mention".to_string()));
+        assert!(conflicts[0].shared_terms.contains(&"stranger".to_string()));
+    }
+
+    #[test]
+    fn test_stem() {
+        assert_eq!(stem("replies"), "reply");
+        assert_eq!(stem("replying"), "reply");
+        assert_eq!(stem("mentions"), "mention");
+        assert_eq!(stem("is"), "is");
+    }
+
+    #[test]
+    fn test_conflict_ignores_unrelated_and_agreeing_pairs() {
+        let directives = vec![
+            (
+                "garden".to_string(),
+                test_directive(DirectiveKind::Guideline, "Post about gardening projects"),
+            ),
+            (
+                "keys".to_string(),
+                test_directive(DirectiveKind::Boundary, "Never share private keys"),
+            ),
+            (
+                "reply".to_string(),
+                test_directive(DirectiveKind::Guideline, "Reply to mentions from friends"),
+            ),
+            (
+                "reply-kindly".to_string(),
+                test_directive(
+                    DirectiveKind::Value,
+                    "Reply kindly to mentions from friends",
+                ),
+            ),
+        ];
+
+        let conflicts =
+            find_directive_conflicts(&directives, DEFAULT_CONFLICT_KINDS, DEFAULT_MIN_OVERLAP);
+        assert!(
+            conflicts.is_empty(),
+            "unexpected conflicts: {:?}",
+            conflicts
+        );
+    }
+
+    #[test]
+    fn test_conflict_respects_kinds() {
+        let directives = vec![
+            (
+                "interest".to_string(),
+                test_directive(DirectiveKind::Interest, "Reading long philosophy threads"),
+            ),
+            (
+                "bound".to_string(),
+                test_directive(
+                    DirectiveKind::Boundary,
+                    "Avoid reading long philosophy threads",
+                ),
+            ),
+        ];
+
+        assert!(
+            find_directive_conflicts(&directives, DEFAULT_CONFLICT_KINDS, DEFAULT_MIN_OVERLAP)
+                .is_empty()
+        );
+        let kinds = [DirectiveKind::Interest, DirectiveKind::Boundary];
+        assert_eq!(
+            find_directive_conflicts(&directives, &kinds, DEFAULT_MIN_OVERLAP).len(),
+            1
+        );
+    }
+
     #[test]
     fn test_parse_directive_kind() {
         assert_eq!(parse_directive_kind("value"), Some(DirectiveKind::Value));
@@ -789,6 +1195,7 @@ mod tests {
             parse_directive_kind("aspiration"),
             Some(DirectiveKind::Aspiration)
         );
+        assert_eq!(parse_directive_kind("goal"), Some(DirectiveKind::Goal));
         assert_eq!(parse_directive_kind("invalid"), None);
     }
 
@@ -801,5 +1208,6 @@ mod tests {
         assert_eq!(DirectiveKind::SelfConcept.to_string(), "self_concept");
         assert_eq!(DirectiveKind::Boundary.to_string(), "boundary");
         assert_eq!(DirectiveKind::Aspiration.to_string(), "aspiration");
+        assert_eq!(DirectiveKind::Goal.to_string(), "goal");
     }
 }
@@ -0,0 +1,118 @@
+//! Dry-run previews for record-creating tools.
+//!
+//! Passing `dry_run: true` to one of these tools builds and validates the
+//! record exactly as a real call would, then returns it instead of writing it
+//! to the PDS. Nothing is written and no cache is touched.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::protocol::CallToolResult;
+
+use super::ToolMeta;
+
+/// Argument name callers use to request a dry run.
+pub const DRY_RUN_ARG: &str = "dry_run";
+
+/// Tools that honour `dry_run`.
+pub const DRY_RUN_TOOLS: &[&str] = &[
+    "create_fact",
+    "create_rule",
+    "create_note",
+    "create_directive",
+    "schedule_job",
+    "schedule_recurring",
+];
+
+/// Whether the caller asked for a dry run.
+pub fn requested(arguments: &HashMap<String, Value>) -> bool {
+    arguments
+        .get(DRY_RUN_ARG)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// The result of a dry run: the record that would have been written to
+/// `collection`, plus any validation warnings.
+pub fn preview<T: Serialize>(collection: &str, record: &T, warnings: &[String]) -> CallToolResult {
+    let record = match serde_json::to_value(record) {
+        Ok(record) => record,
+        Err(e) => return CallToolResult::error(format!("Failed to serialize record: {}", e)),
+    };
+    CallToolResult::success(
+        json!({
+            "dry_run": true,
+            "collection": collection,
+            "record": record,
+            "valid": true,
+            "warnings": warnings,
+        })
+        .to_string(),
+    )
+}
+
+/// Add the `dry_run` parameter to the input schema of tools that honour it.
+pub fn advertise_flag(mut meta: ToolMeta) -> ToolMeta {
+    if DRY_RUN_TOOLS.contains(&meta.definition.name.as_str())
+        && let Some(properties) = meta
+            .definition
+            .input_schema
+            .get_mut("properties")
+            .and_then(|p| p.as_object_mut())
+    {
+        properties.insert(
+            DRY_RUN_ARG.to_string(),
+            json!({
+                "type": "boolean",
+                "description": "If true, validate and return the record that would be created without writing it. Default: false."
+            }),
+        );
+    }
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ToolContent;
+
+    #[test]
+    fn every_dry_run_tool_exists_and_advertises_the_flag() {
+        let tools = super::super::ToolRegistry::all_tools();
+        for name in DRY_RUN_TOOLS {
+            let tool = tools
+                .iter()
+                .find(|t| t.definition.name == *name)
+                .unwrap_or_else(|| panic!("'{}' is not a tool", name));
+            assert_eq!(
+                tool.definition.input_schema["properties"][DRY_RUN_ARG]["type"], "boolean",
+                "'{}'",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn preview_wraps_the_record() {
+        let result = preview(
+            "diy.razorgirl.winter.note",
+            &json!({ "title": "t" }),
+            &["careful".to_string()],
+        );
+        let ToolContent::Text { text } = &result.content[0];
+        let body: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(body["dry_run"], true);
+        assert_eq!(body["collection"], "diy.razorgirl.winter.note");
+        assert_eq!(body["record"]["title"], "t");
+        assert_eq!(body["warnings"], json!(["careful"]));
+    }
+
+    #[test]
+    fn requested_defaults_to_false() {
+        assert!(!requested(&HashMap::new()));
+        let args = HashMap::from([(DRY_RUN_ARG.to_string(), json!(true))]);
+        assert!(requested(&args));
+    }
+}
@@ -1,7 +1,9 @@
 //! Query and enrich tool for chaining datalog queries with Bluesky API enrichment.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
+use async_trait::async_trait;
 use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
@@ -115,6 +117,105 @@ impl EnrichmentResult {
     }
 }
 
+/// A query result tuple as it flows through enrichment.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryRow {
+    /// The datalog tuple.
+    pub tuple: Vec<String>,
+    /// Built-in enrichment results, keyed by column and then enrichment type.
+    pub enrichments: HashMap<String, HashMap<String, Value>>,
+    /// Data attached by [`Enricher`]s, keyed by whatever the enricher chooses
+    /// (conventionally its own name).
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub annotations: HashMap<String, Value>,
+}
+
+impl QueryRow {
+    /// Create a row with no enrichment data yet.
+    pub fn new(tuple: Vec<String>) -> Self {
+        Self {
+            tuple,
+            ..Default::default()
+        }
+    }
+}
+
+/// A pluggable enrichment source for `query_and_enrich`.
+///
+/// Enrichers run after the built-in Bluesky enrichments, over every row that
+/// survived them, in the order the caller listed them. Each gets the whole
+/// batch so it can deduplicate or batch its own lookups.
+#[async_trait]
+pub trait Enricher: Send + Sync {
+    /// Name used to select this enricher in the `enrichers` argument.
+    fn name(&self) -> &'static str;
+
+    /// Human-readable description.
+    fn description(&self) -> &'static str;
+
+    /// Attach data to the rows, typically under [`QueryRow::annotations`].
+    async fn enrich(&self, rows: &mut [QueryRow]);
+}
+
+/// Enrichers available to `query_and_enrich`, by name.
+#[derive(Default)]
+pub struct EnricherRegistry {
+    enrichers: BTreeMap<&'static str, Arc<dyn Enricher>>,
+}
+
+impl EnricherRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an enricher, replacing any existing one with the same name.
+    pub fn register(&mut self, enricher: Arc<dyn Enricher>) {
+        self.enrichers.insert(enricher.name(), enricher);
+    }
+
+    /// Look up an enricher by name.
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Enricher>> {
+        self.enrichers.get(name)
+    }
+
+    /// Names of all registered enrichers, sorted.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.enrichers.keys().copied().collect()
+    }
+
+    /// Resolve enricher names, failing on the first unknown one.
+    fn resolve(&self, names: &[String]) -> Result<Vec<Arc<dyn Enricher>>, String> {
+        names
+            .iter()
+            .map(|name| {
+                self.get(name).cloned().ok_or_else(|| {
+                    format!(
+                        "Unknown enricher '{}' (available: {})",
+                        name,
+                        if self.enrichers.is_empty() {
+                            "none".to_string()
+                        } else {
+                            self.names().join(", ")
+                        }
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Run the named enrichers over `rows` in order.
+    ///
+    /// Names are resolved up front, so an unknown name fails before any
+    /// enricher has touched the rows.
+    pub async fn apply(&self, names: &[String], rows: &mut [QueryRow]) -> Result<(), String> {
+        for enricher in self.resolve(names)? {
+            enricher.enrich(rows).await;
+        }
+        Ok(())
+    }
+}
+
 /// Cache key for deduplication.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CacheKey {
@@ -144,7 +245,7 @@ impl CacheKey {
 pub fn definitions() -> Vec<ToolDefinition> {
     vec![ToolDefinition {
         name: "query_and_enrich".to_string(),
-        description: r#"Query facts using datalog, then enrich results with Bluesky API data.
+        description: r#"Query facts using datalog, then enrich results with Bluesky API data and registered enrichers.
 
 This tool chains a datalog query with API enrichment calls, deduplicating lookups
 for efficiency. Each enrichment specifies which result column to use as its input key.
@@ -160,6 +261,13 @@ for efficiency. Each enrichment specifies which result column to use as its inpu
 | followers | DID | Get accounts following the user |
 | follows | DID | Get accounts the user follows |
 
+## Enrichers
+
+`enrichers` names additional enrichment sources registered with the server.
+They run after the built-in enrichments, in the order given, and attach their
+data under each result's `annotations`. Unknown names are rejected with the
+list of available enrichers.
+
 ## Failure Handling
 
 Global `on_failure` sets the default; each enrichment can override:
@@ -244,6 +352,11 @@ Returns query results with enrichment data attached, deduped by unique keys."#
                     },
                     "description": "Enrichments to apply to query results"
                 },
+                "enrichers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Names of registered enrichers to run over the results, in order"
+                },
                 "max_parallel": {
                     "type": "integer",
                     "description": "Maximum concurrent API calls (default: 5)"
@@ -254,7 +367,7 @@ Returns query results with enrichment data attached, deduped by unique keys."#
                     "description": "Default failure handling for enrichments"
                 }
             },
-            "required": ["query", "enrichments"]
+            "required": ["query"]
         }),
     }]
 }
@@ -283,11 +396,24 @@ pub async fn query_and_enrich(
                 return CallToolResult::error(format!("Invalid enrichments: {}", e));
             }
         },
-        None => return CallToolResult::error("Missing required parameter: enrichments"),
+        None => Vec::new(),
     };
 
-    if enrichments.is_empty() {
-        return CallToolResult::error("enrichments array cannot be empty");
+    let enricher_names: Vec<String> = match arguments.get("enrichers") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(names) => names,
+            Err(e) => return CallToolResult::error(format!("Invalid enrichers: {}", e)),
+        },
+        None => Vec::new(),
+    };
+
+    if enrichments.is_empty() && enricher_names.is_empty() {
+        return CallToolResult::error("At least one of enrichments or enrichers is required");
+    }
+
+    // Fail on unknown enrichers before running the query
+    if let Err(e) = state.enrichers.resolve(&enricher_names) {
+        return CallToolResult::error(e);
     }
 
     // Parse options
@@ -318,9 +444,10 @@ pub async fn query_and_enrich(
         })
         .unwrap_or_default();
 
-    // Check for Bluesky client
+    // Built-in enrichments need a Bluesky client; registered enrichers don't
     let bluesky = match &state.bluesky {
-        Some(b) => b,
+        Some(b) => Some(b),
+        None if enrichments.is_empty() => None,
         None => return CallToolResult::error("Bluesky client not configured"),
     };
 
@@ -410,18 +537,24 @@ pub async fn query_and_enrich(
         .collect();
 
     // Process in parallel batches
-    let results: Vec<(CacheKey, EnrichmentResult)> = stream::iter(keys_vec)
-        .map(|(cache_key, spec)| {
-            let key = cache_key.key.clone();
-            async move {
-                let result =
-                    execute_enrichment(bluesky, &key, &spec.enrichment_type, &spec.options).await;
-                (cache_key, result)
-            }
-        })
-        .buffer_unordered(max_parallel)
-        .collect()
-        .await;
+    let results: Vec<(CacheKey, EnrichmentResult)> = match bluesky {
+        Some(bluesky) => {
+            stream::iter(keys_vec)
+                .map(|(cache_key, spec)| {
+                    let key = cache_key.key.clone();
+                    async move {
+                        let result =
+                            execute_enrichment(bluesky, &key, &spec.enrichment_type, &spec.options)
+                                .await;
+                        (cache_key, result)
+                    }
+                })
+                .buffer_unordered(max_parallel)
+                .collect()
+                .await
+        }
+        None => Vec::new(),
+    };
 
     // Process results and check for halt conditions
     for (cache_key, result) in results {
@@ -455,11 +588,11 @@ pub async fn query_and_enrich(
         cache.insert(cache_key, result);
     }
 
-    // Build output results
-    let mut output_results: Vec<Value> = Vec::new();
+    // Build output rows
+    let mut rows: Vec<QueryRow> = Vec::new();
 
     'tuple_loop: for tuple in &tuples {
-        let mut enrichment_data: HashMap<String, HashMap<String, Value>> = HashMap::new();
+        let mut row = QueryRow::new(tuple.clone());
 
         for spec in &enrichments {
             let on_failure = spec.on_failure.unwrap_or(global_on_failure);
@@ -476,7 +609,7 @@ pub async fn query_and_enrich(
                     let col_key = spec.column.to_string();
                     let type_key = spec.enrichment_type.as_str().to_string();
 
-                    enrichment_data
+                    row.enrichments
                         .entry(col_key)
                         .or_default()
                         .insert(type_key, serde_json::to_value(result).unwrap());
@@ -484,16 +617,18 @@ pub async fn query_and_enrich(
             }
         }
 
-        output_results.push(json!({
-            "tuple": tuple,
-            "enrichments": enrichment_data
-        }));
+        rows.push(row);
+    }
+
+    // Registered enrichers only see complete built-in results
+    if !halted && let Err(e) = state.enrichers.apply(&enricher_names, &mut rows).await {
+        return CallToolResult::error(e);
     }
 
     let mut response = json!({
         "query": query,
-        "count": output_results.len(),
-        "results": output_results,
+        "count": rows.len(),
+        "results": rows,
         "cache_stats": {
             "unique_keys": unique_key_count,
             "total_lookups": total_lookups
@@ -766,4 +901,118 @@ mod tests {
         assert_eq!(spec.options.limit, Some(10));
         assert_eq!(spec.on_failure, Some(FailureMode::SkipTuple));
     }
+
+    /// Appends its name to each row's `order` annotation and records the
+    /// first tuple column under its own name.
+    struct Tagger(&'static str);
+
+    #[async_trait]
+    impl Enricher for Tagger {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "test enricher"
+        }
+
+        async fn enrich(&self, rows: &mut [QueryRow]) {
+            for row in rows {
+                let tagged = format!("{}:{}", self.0, row.tuple[0]);
+                row.annotations.insert(self.0.to_string(), json!(tagged));
+                let order = row
+                    .annotations
+                    .entry("order".to_string())
+                    .or_insert(json!([]));
+                order.as_array_mut().unwrap().push(json!(self.0));
+            }
+        }
+    }
+
+    fn registry_with(names: &[&'static str]) -> EnricherRegistry {
+        let mut registry = EnricherRegistry::new();
+        for name in names {
+            registry.register(Arc::new(Tagger(name)));
+        }
+        registry
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_enricher_registry_applies_selected_in_order() {
+        let registry = registry_with(&["alpha", "beta", "gamma"]);
+        let mut rows = vec![QueryRow::new(vec!["did:plc:a".to_string()])];
+
+        registry
+            .apply(&names(&["gamma", "alpha"]), &mut rows)
+            .await
+            .unwrap();
+
+        let annotations = &rows[0].annotations;
+        assert_eq!(annotations["alpha"], json!("alpha:did:plc:a"));
+        assert_eq!(annotations["gamma"], json!("gamma:did:plc:a"));
+        assert!(!annotations.contains_key("beta"));
+        assert_eq!(annotations["order"], json!(["gamma", "alpha"]));
+    }
+
+    #[tokio::test]
+    async fn test_enricher_registry_rejects_unknown_before_running() {
+        let registry = registry_with(&["alpha", "beta"]);
+        let mut rows = vec![QueryRow::new(vec!["x".to_string()])];
+
+        let err = registry
+            .apply(&names(&["alpha", "missing"]), &mut rows)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, "Unknown enricher 'missing' (available: alpha, beta)");
+        assert!(rows[0].annotations.is_empty());
+    }
+
+    #[test]
+    fn test_enricher_registry_replaces_by_name() {
+        let mut registry = registry_with(&["beta", "alpha"]);
+        registry.register(Arc::new(Tagger("alpha")));
+        assert_eq!(registry.names(), vec!["alpha", "beta"]);
+        assert!(registry.get("alpha").is_some());
+        assert!(registry.get("gamma").is_none());
+    }
+
+    #[test]
+    fn test_query_row_omits_empty_annotations() {
+        let row = QueryRow::new(vec!["a".to_string()]);
+        let value = serde_json::to_value(&row).unwrap();
+        assert_eq!(value, json!({"tuple": ["a"], "enrichments": {}}));
+    }
+
+    #[tokio::test]
+    async fn test_query_and_enrich_rejects_unknown_enricher() {
+        let registry = super::super::ToolRegistry::empty();
+        registry.register_enricher(Arc::new(Tagger("alpha"))).await;
+        let state = registry.state.read().await;
+
+        let args = HashMap::from([
+            ("query".to_string(), json!("p(X)")),
+            ("enrichers".to_string(), json!(["beta"])),
+        ]);
+        let result = query_and_enrich(&state, &args).await;
+
+        assert_eq!(result.is_error, Some(true));
+        let crate::protocol::ToolContent::Text { text } = &result.content[0];
+        assert_eq!(text, "Unknown enricher 'beta' (available: alpha)");
+    }
+
+    #[tokio::test]
+    async fn test_query_and_enrich_requires_some_enrichment() {
+        let registry = super::super::ToolRegistry::empty();
+        let state = registry.state.read().await;
+
+        let args = HashMap::from([("query".to_string(), json!("p(X)"))]);
+        let result = query_and_enrich(&state, &args).await;
+
+        assert_eq!(result.is_error, Some(true));
+    }
 }
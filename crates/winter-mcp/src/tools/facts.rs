@@ -1,16 +1,24 @@
 //! Fact tools for MCP.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use serde_json::{Value, json};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Fact, ListRecordItem, Rule, SyncState, Tid, WriteOp, WriteResult};
-use winter_datalog::{DerivedFactGenerator, FactExtractor, RuleCompiler, SouffleExecutor};
-
-use super::{MAX_BATCH_SIZE, ToolMeta, ToolState, parse_string_array};
+use winter_atproto::{
+    AtUri, Fact, FactDeclaration, ListRecordItem, Rule, SyncState, Tid, Validate, note_backlinks,
+};
+use winter_datalog::{
+    DependencyGraphExport, DerivedFactGenerator, FactExtractor, RuleCompiler, SouffleExecutor,
+    check_query, query_atoms, validate_fact_against_declaration,
+};
+
+use super::{
+    MAX_BATCH_SIZE, ToolMeta, ToolState, apply_batch_creates, changes, dry_run, parse_string_array,
+    tombstones,
+};
 
 /// Collection name for facts.
 const FACT_COLLECTION: &str = "diy.razorgirl.winter.fact";
@@ -18,6 +26,92 @@ const FACT_COLLECTION: &str = "diy.razorgirl.winter.fact";
 /// Collection name for rules.
 const RULE_COLLECTION: &str = "diy.razorgirl.winter.rule";
 
+/// Collection name for fact declarations.
+const DECLARATION_COLLECTION: &str = "diy.razorgirl.winter.factDeclaration";
+
+/// Environment variable selecting how fact creation treats undeclared predicates.
+///
+/// `warn` attaches a warning to the result; anything else (the default) lets
+/// the fact through silently.
+const UNDECLARED_POLICY_ENV: &str = "WINTER_UNDECLARED_FACT_POLICY";
+
+/// What to do when a fact's predicate has no declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UndeclaredPolicy {
+    Allow,
+    Warn,
+}
+
+impl UndeclaredPolicy {
+    fn from_env() -> Self {
+        match std::env::var(UNDECLARED_POLICY_ENV).as_deref() {
+            Ok("warn") => Self::Warn,
+            _ => Self::Allow,
+        }
+    }
+}
+
+/// Load fact declarations keyed by predicate, preferring the repo cache.
+async fn declarations_by_predicate(
+    state: &ToolState,
+) -> Result<HashMap<String, FactDeclaration>, CallToolResult> {
+    if let Some(cache) = &state.cache
+        && cache.state() == SyncState::Live
+    {
+        return Ok(cache
+            .list_declarations()
+            .into_iter()
+            .map(|(_, cached)| (cached.value.predicate.clone(), cached.value))
+            .collect());
+    }
+
+    match state
+        .atproto
+        .list_all_records::<FactDeclaration>(DECLARATION_COLLECTION)
+        .await
+    {
+        Ok(records) => Ok(records
+            .into_iter()
+            .map(|r| (r.value.predicate.clone(), r.value))
+            .collect()),
+        Err(e) => Err(CallToolResult::error(format!(
+            "Failed to load fact declarations: {}",
+            e
+        ))),
+    }
+}
+
+/// Check a fact against its declaration before it is written.
+///
+/// In strict mode a non-conforming fact is rejected with `Err`; otherwise it
+/// comes back as a warning. Undeclared predicates are handled by `policy`.
+fn check_fact(
+    fact: &Fact,
+    declarations: &HashMap<String, FactDeclaration>,
+    strict: bool,
+    policy: UndeclaredPolicy,
+) -> Result<Option<String>, String> {
+    if !declarations.contains_key(&fact.predicate) {
+        return Ok((policy == UndeclaredPolicy::Warn)
+            .then(|| format!("predicate '{}' has no declaration", fact.predicate)));
+    }
+
+    match validate_fact_against_declaration(fact, declarations) {
+        None => Ok(None),
+        Some(error) => {
+            let message = format!(
+                "fact does not match declaration for '{}': {}",
+                fact.predicate, error
+            );
+            if strict {
+                Err(message)
+            } else {
+                Ok(Some(message))
+            }
+        }
+    }
+}
+
 /// Parse `expires_at` or `ttl_seconds` from a HashMap (for create_fact, update_fact).
 fn parse_expires_at(arguments: &HashMap<String, Value>) -> Option<DateTime<Utc>> {
     if let Some(ts) = arguments.get("expires_at").and_then(|v| v.as_str()) {
@@ -85,6 +179,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "ttl_seconds": {
                         "type": "integer",
                         "description": "Optional time-to-live in seconds (convenience alternative to expires_at). Computed to expires_at at creation time."
+                    },
+                    "strict": {
+                        "type": "boolean",
+                        "description": "Reject the fact if it doesn't match its predicate's declaration (arg count and types). When false, the fact is written with a warning. Default: true"
                     }
                 },
                 "required": ["predicate", "args"]
@@ -131,6 +229,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                             "required": ["predicate", "args"]
                         },
                         "description": "Array of facts to create"
+                    },
+                    "strict": {
+                        "type": "boolean",
+                        "description": "Reject the batch if any fact doesn't match its predicate's declaration. When false, facts are written with warnings. Default: true"
                     }
                 },
                 "required": ["facts"]
@@ -178,13 +280,45 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "delete_fact".to_string(),
-            description: "Delete a fact by its record key.".to_string(),
+            description: "Delete a fact by its record key. By default the fact is soft-deleted: hidden from queries but restorable with restore_fact for 30 days, after which it is removed for good.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "rkey": {
                         "type": "string",
                         "description": "Record key of the fact to delete"
+                    },
+                    "permanent": {
+                        "type": "boolean",
+                        "description": "Delete immediately instead of soft-deleting. Cannot be undone. Default: false."
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
+        ToolDefinition {
+            name: "restore_fact".to_string(),
+            description: "Restore a soft-deleted fact, making it visible to queries again. Only works within 30 days of deletion.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of the deleted fact"
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
+        ToolDefinition {
+            name: "get_fact".to_string(),
+            description: "Get a fact by its record key, along with the notes that list it in related_facts.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of the fact"
                     }
                 },
                 "required": ["rkey"]
@@ -192,7 +326,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "query_facts".to_string(),
-            description: r#"Query facts using datalog. By default, queries return only current facts (superseded facts are excluded).
+            description: r#"Query facts using datalog. By default, queries return only current facts: each supersession chain contributes only its newest version. Set `latest_only: false` to have user predicates include every version.
 
 ## Available Relations
 
@@ -230,7 +364,11 @@ Example: `extra_facts: ["thread_depth(\"at://...\", \"7\")", "my_reply_count(\"a
 
 **Ad-hoc declarations** (extra_declarations parameter):
 Declare predicates at query time for predicates not yet stored.
-Example: `extra_declarations: ["my_pred(arg1: symbol, arg2: symbol)"]`"#.to_string(),
+Example: `extra_declarations: ["my_pred(arg1: symbol, arg2: symbol)"]`
+
+**Result size**: results are capped (see `max_results`). A truncated response has `truncated: true` and `total_count`; narrow the query instead of raising the limit.
+
+**Validation**: predicates are checked before the query runs. Unknown predicates (with a suggested spelling) and wrong argument counts are reported as `Invalid query: ...`."#.to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -251,11 +389,39 @@ Example: `extra_declarations: ["my_pred(arg1: symbol, arg2: symbol)"]`"#.to_stri
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Optional ad-hoc predicate declarations (e.g., [\"my_pred(arg1: symbol, arg2: symbol)\"]). For predicates not yet stored."
+                    },
+                    "latest_only": {
+                        "type": "boolean",
+                        "description": "Only include the head of each supersession chain in user predicates (default: true). When false, predicates behave like their _all_ variants."
+                    },
+                    "max_results": {
+                        "type": "integer",
+                        "description": "Maximum number of results to return (default and upper bound: the server's cap, 1000 unless configured). Larger result sets are truncated, with `truncated: true` and `total_count` set."
                     }
                 },
                 "required": ["query"]
             }),
         },
+        ToolDefinition {
+            name: "query_why_not".to_string(),
+            description: r#"Explain why a tuple is missing from query results.
+
+Give the tuple you expected as a ground atom, e.g. `mutual_follow("did:plc:a", "did:plc:b")`. For every rule with that predicate as its head, reports whether the head matches the tuple and, if so, the first body literal (or constraint) that has no solution once the head's variables are bound to your values. Rules are listed closest match first.
+
+Statuses: `failed_at` (with `index` and `literal`), `satisfied` (the rule should derive the tuple), `head_mismatch` (with `reason`), `disabled`, `error`. `present: true` means the tuple is actually derived.
+
+Use `_` for arguments you don't care about."#.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "goal": {
+                        "type": "string",
+                        "description": "The expected tuple as an atom, e.g. 'mutual_follow(\"did:plc:a\", \"did:plc:b\")'"
+                    }
+                },
+                "required": ["goal"]
+            }),
+        },
         ToolDefinition {
             name: "list_predicates".to_string(),
             description: r#"List all available predicates with their arities.
@@ -287,6 +453,45 @@ For each predicate, shows:
                 "properties": {}
             }),
         },
+        ToolDefinition {
+            name: "derived_fact_stats".to_string(),
+            description: "Show derived-fact generation stats: record counts feeding derived predicates, the last regeneration run's duration, per-predicate rows added/removed, whether Soufflé ran against it, and query result cache hits/misses. Useful for tuning query performance.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        },
+        ToolDefinition {
+            name: "predicate_graph".to_string(),
+            description: r#"Show how predicates feed rules and rules feed derived predicates.
+
+Returns the dependency graph of enabled rules. Predicate nodes are `pred:<name>` with a kind of `fact`, `builtin`, `metadata`, or `derived`; rule nodes are `rule:<name>`. Edges run from body predicates to the rule (`negated` when used under `!`) and from the rule to its head predicate.
+
+Use this to trace why a rule produces unexpected results."#.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "dot"],
+                        "description": "Output format: 'json' (nodes and edges, default) or 'dot' (Graphviz source)"
+                    }
+                }
+            }),
+        },
+        ToolDefinition {
+            name: "reconcile_facts".to_string(),
+            description: "Compare the cached facts against the authoritative list on the PDS. Reports facts missing from the cache, facts in the cache that no longer exist, and facts whose cached version is out of date. With repair=true, brings the cache back in line with the PDS.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "repair": {
+                        "type": "boolean",
+                        "description": "Update the cache to match the PDS (default: false)"
+                    }
+                }
+            }),
+        },
     ]
 }
 
@@ -335,6 +540,11 @@ pub async fn create_fact(state: &ToolState, arguments: &HashMap<String, Value>)
 
     let expires_at = parse_expires_at(arguments);
 
+    let strict = arguments
+        .get("strict")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
     let fact = Fact {
         predicate: predicate.to_string(),
         args,
@@ -344,7 +554,24 @@ pub async fn create_fact(state: &ToolState, arguments: &HashMap<String, Value>)
         tags,
         created_at: Utc::now(),
         expires_at,
+        deleted_at: None,
+    };
+
+    let declarations = match declarations_by_predicate(state).await {
+        Ok(d) => d,
+        Err(e) => return e,
+    };
+    let warning = match check_fact(&fact, &declarations, strict, UndeclaredPolicy::from_env()) {
+        Ok(warning) => warning,
+        Err(message) => return CallToolResult::error(message),
     };
+    if let Some(ref warning) = warning {
+        warn!(predicate = %fact.predicate, warning = %warning, "creating fact with warning");
+    }
+
+    if dry_run::requested(arguments) {
+        return dry_run::preview(FACT_COLLECTION, &fact, warning.as_slice());
+    }
 
     let rkey = Tid::now().to_string();
 
@@ -368,6 +595,9 @@ pub async fn create_fact(state: &ToolState, arguments: &HashMap<String, Value>)
             if let Some(ref ea) = fact.expires_at {
                 result["expires_at"] = json!(ea.to_rfc3339());
             }
+            if let Some(warning) = warning {
+                result["warning"] = json!(warning);
+            }
             CallToolResult::success(result.to_string())
         }
         Err(e) => CallToolResult::error(format!("Failed to create fact: {}", e)),
@@ -392,8 +622,19 @@ pub async fn create_facts(state: &ToolState, arguments: &HashMap<String, Value>)
         ));
     }
 
+    let strict = arguments
+        .get("strict")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let declarations = match declarations_by_predicate(state).await {
+        Ok(d) => d,
+        Err(e) => return e,
+    };
+    let policy = UndeclaredPolicy::from_env();
+
     // Validate and parse all facts first
     let mut validated: Vec<(String, Fact)> = Vec::with_capacity(facts_array.len());
+    let mut warnings: Vec<String> = Vec::new();
     let now = Utc::now();
 
     for (i, fact_val) in facts_array.iter().enumerate() {
@@ -449,61 +690,40 @@ pub async fn create_facts(state: &ToolState, arguments: &HashMap<String, Value>)
             tags,
             created_at: now,
             expires_at,
+            deleted_at: None,
         };
 
+        match check_fact(&fact, &declarations, strict, policy) {
+            Ok(Some(warning)) => warnings.push(format!("facts[{}]: {}", i, warning)),
+            Ok(None) => {}
+            Err(message) => return CallToolResult::error(format!("facts[{}]: {}", i, message)),
+        }
+
         let rkey = Tid::now().to_string();
         validated.push((rkey, fact));
     }
 
-    // Build WriteOp list
-    let writes: Vec<WriteOp> = validated
-        .iter()
-        .map(|(rkey, fact)| WriteOp::Create {
-            collection: FACT_COLLECTION.to_string(),
-            rkey: rkey.clone(),
-            value: serde_json::to_value(fact).expect("Fact struct should always serialize"),
-        })
-        .collect();
+    let describe = |_, fact: &Fact| {
+        let mut item = serde_json::Map::new();
+        item.insert("predicate".to_string(), json!(fact.predicate));
+        item.insert("args".to_string(), json!(fact.args));
+        item
+    };
+    let on_created = |rkey: &str, fact: &Fact, cid: &str| {
+        if let Some(cache) = &state.cache {
+            cache.upsert_fact(rkey.to_string(), fact.clone(), cid.to_string());
+        }
+    };
 
-    // Execute batch write
-    match state.atproto.apply_writes(writes).await {
-        Ok(response) => {
-            // Update cache for each created record
-            for ((rkey, fact), result) in validated.iter().zip(response.results.iter()) {
-                if let WriteResult::Create { cid, .. } = result
-                    && let Some(cache) = &state.cache
-                {
-                    cache.upsert_fact(rkey.clone(), fact.clone(), cid.clone());
-                }
+    match apply_batch_creates(state, FACT_COLLECTION, &validated, describe, on_created).await {
+        Ok(mut result) => {
+            if !warnings.is_empty() {
+                warn!(count = warnings.len(), "created facts with warnings");
+                result["warnings"] = json!(warnings);
             }
-
-            let results: Vec<Value> = validated
-                .iter()
-                .zip(response.results.iter())
-                .map(|((rkey, fact), result)| {
-                    if let WriteResult::Create { uri, cid } = result {
-                        json!({
-                            "rkey": rkey,
-                            "uri": uri,
-                            "cid": cid,
-                            "predicate": fact.predicate,
-                            "args": fact.args
-                        })
-                    } else {
-                        json!({ "rkey": rkey, "error": "unexpected result type" })
-                    }
-                })
-                .collect();
-
-            CallToolResult::success(
-                json!({
-                    "created": validated.len(),
-                    "results": results
-                })
-                .to_string(),
-            )
+            CallToolResult::success(result.to_string())
         }
-        Err(e) => CallToolResult::error(format!("Batch write failed: {}", e)),
+        Err(result) => CallToolResult::error(result.to_string()),
     }
 }
 
@@ -526,7 +746,7 @@ pub async fn update_fact(state: &ToolState, arguments: &HashMap<String, Value>)
         None => return CallToolResult::error("Missing required parameter: args"),
     };
 
-    // Get the old fact to get its CID for the supersedes reference
+    // Get the old fact to get its URI for the supersedes reference
     let old_record = match state
         .atproto
         .get_record::<Fact>(FACT_COLLECTION, rkey)
@@ -544,7 +764,8 @@ pub async fn update_fact(state: &ToolState, arguments: &HashMap<String, Value>)
         ));
     }
 
-    let old_cid = old_record.cid;
+    let before = old_record.value;
+    let old_uri = old_record.uri;
 
     let confidence = arguments
         .get("confidence")
@@ -568,10 +789,11 @@ pub async fn update_fact(state: &ToolState, arguments: &HashMap<String, Value>)
         args,
         confidence,
         source: None,
-        supersedes: old_cid,
+        supersedes: Some(old_uri),
         tags,
         created_at: Utc::now(),
         expires_at,
+        deleted_at: None,
     };
 
     // Create a new fact that supersedes the old one
@@ -589,13 +811,15 @@ pub async fn update_fact(state: &ToolState, arguments: &HashMap<String, Value>)
             }
             // Old fact is preserved for historical queries.
             // The supersedes reference in the new fact links them.
+            let changes = changes::record_changes(&before, &fact, &["createdAt", "supersedes"]);
             CallToolResult::success(
                 json!({
                     "rkey": new_rkey,
                     "uri": response.uri,
                     "cid": response.cid,
                     "supersedes_rkey": rkey,
-                    "predicate": predicate
+                    "predicate": predicate,
+                    "changes": changes
                 })
                 .to_string(),
             )
@@ -604,14 +828,69 @@ pub async fn update_fact(state: &ToolState, arguments: &HashMap<String, Value>)
     }
 }
 
+pub async fn get_fact(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let cached = state
+        .cache
+        .as_ref()
+        .filter(|cache| cache.state() == SyncState::Live)
+        .and_then(|cache| cache.get_fact(rkey));
+    let fact = match cached {
+        Some(cached) => cached.value,
+        None => match state
+            .atproto
+            .get_record::<Fact>(FACT_COLLECTION, rkey)
+            .await
+        {
+            Ok(record) => record.value,
+            Err(e) => return CallToolResult::error(format!("Failed to get fact: {}", e)),
+        },
+    };
+
+    let notes = match super::notes::load_notes(state).await {
+        Ok(notes) => notes,
+        Err(e) => return CallToolResult::error(e),
+    };
+    let referenced_by: Vec<Value> = note_backlinks(rkey, &notes)
+        .into_iter()
+        .map(|(note_rkey, note)| json!({ "rkey": note_rkey, "title": note.title }))
+        .collect();
+
+    CallToolResult::success(
+        json!({
+            "rkey": rkey,
+            "predicate": fact.predicate,
+            "args": fact.args,
+            "confidence": fact.confidence,
+            "source": fact.source,
+            "supersedes": fact.supersedes,
+            "tags": fact.tags,
+            "created_at": fact.created_at.to_rfc3339(),
+            "expires_at": fact.expires_at.map(|t| t.to_rfc3339()),
+            "deleted_at": fact.deleted_at.map(|t| t.to_rfc3339()),
+            "referenced_by_notes": referenced_by,
+        })
+        .to_string(),
+    )
+}
+
 pub async fn delete_fact(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
     let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
         Some(r) => r,
         None => return CallToolResult::error("Missing required parameter: rkey"),
     };
 
+    let permanent = arguments
+        .get("permanent")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     // First, fetch the fact to check if it's a derived predicate
-    let fact = match state
+    let mut fact = match state
         .atproto
         .get_record::<Fact>(FACT_COLLECTION, rkey)
         .await
@@ -628,6 +907,28 @@ pub async fn delete_fact(state: &ToolState, arguments: &HashMap<String, Value>)
         ));
     }
 
+    if !permanent {
+        // Keep the original tombstone if the fact is already soft-deleted
+        let deleted_at = *fact.deleted_at.get_or_insert_with(Utc::now);
+        return match state.atproto.put_record(FACT_COLLECTION, rkey, &fact).await {
+            Ok(response) => {
+                if let Some(cache) = &state.cache {
+                    cache.upsert_fact(rkey.to_string(), fact, response.cid);
+                }
+                CallToolResult::success(
+                    json!({
+                        "deleted": true,
+                        "rkey": rkey,
+                        "permanent": false,
+                        "restorable_until": tombstones::restorable_until(deleted_at).to_rfc3339()
+                    })
+                    .to_string(),
+                )
+            }
+            Err(e) => CallToolResult::error(format!("Failed to delete fact: {}", e)),
+        };
+    }
+
     match state.atproto.delete_record(FACT_COLLECTION, rkey).await {
         Ok(()) => {
             // Remove from cache
@@ -637,7 +938,8 @@ pub async fn delete_fact(state: &ToolState, arguments: &HashMap<String, Value>)
             CallToolResult::success(
                 json!({
                     "deleted": true,
-                    "rkey": rkey
+                    "rkey": rkey,
+                    "permanent": true
                 })
                 .to_string(),
             )
@@ -646,6 +948,50 @@ pub async fn delete_fact(state: &ToolState, arguments: &HashMap<String, Value>)
     }
 }
 
+pub async fn restore_fact(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let mut fact = match state
+        .atproto
+        .get_record::<Fact>(FACT_COLLECTION, rkey)
+        .await
+    {
+        Ok(record) => record.value,
+        Err(e) => return CallToolResult::error(format!("Failed to get fact: {}", e)),
+    };
+
+    let Some(deleted_at) = fact.deleted_at.take() else {
+        return CallToolResult::error(format!("Fact {} is not deleted", rkey));
+    };
+    if !tombstones::is_restorable(deleted_at, Utc::now()) {
+        return CallToolResult::error(format!(
+            "Fact {} was deleted more than {} days ago and can no longer be restored",
+            rkey,
+            tombstones::RETENTION_DAYS
+        ));
+    }
+
+    match state.atproto.put_record(FACT_COLLECTION, rkey, &fact).await {
+        Ok(response) => {
+            if let Some(cache) = &state.cache {
+                cache.upsert_fact(rkey.to_string(), fact.clone(), response.cid);
+            }
+            CallToolResult::success(
+                json!({
+                    "restored": true,
+                    "rkey": rkey,
+                    "predicate": fact.predicate
+                })
+                .to_string(),
+            )
+        }
+        Err(e) => CallToolResult::error(format!("Failed to restore fact: {}", e)),
+    }
+}
+
 /// Maximum query length to prevent abuse.
 const MAX_QUERY_LENGTH: usize = 4096;
 
@@ -655,6 +1001,83 @@ const MAX_QUERY_LENGTH: usize = 4096;
 /// into shell commands, and multi-line rules/declarations are common.
 const FORBIDDEN_PATTERNS: &[&str] = &["$(", "`", "&&", "||", ";", "|"];
 
+/// Hard cap on `query_facts` results when none is configured.
+const DEFAULT_MAX_RESULTS_CAP: usize = 1000;
+
+/// The server-enforced cap on `query_facts` results.
+fn max_results_cap(state: &ToolState) -> usize {
+    state
+        .settings
+        .query_max_results
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_RESULTS_CAP)
+}
+
+/// Read `max_results`, defaulting to and never exceeding `cap`.
+fn parse_max_results(arguments: &HashMap<String, Value>, cap: usize) -> usize {
+    arguments
+        .get("max_results")
+        .and_then(|v| v.as_u64())
+        .map(|n| (n.min(cap as u64) as usize).max(1))
+        .unwrap_or(cap)
+}
+
+/// Add the predicates a query's ad-hoc rules, facts and declarations define.
+fn add_adhoc_signatures(
+    known: &mut HashMap<String, usize>,
+    extra_rules: Option<&str>,
+    extra_facts: Option<&[String]>,
+    extra_declarations: Option<&[String]>,
+) {
+    for decl in extra_declarations.unwrap_or_default() {
+        if let Some((name, types)) = parse_declaration_types(decl) {
+            known.entry(name).or_insert(types.len());
+        }
+    }
+    if let Some(rules) = extra_rules {
+        for line in rules
+            .lines()
+            .filter(|l| l.trim_start().starts_with(".decl "))
+        {
+            if let Some((name, types)) = parse_declaration_types(line) {
+                known.entry(name).or_insert(types.len());
+            }
+        }
+        for (name, arity) in RuleCompiler::parse_extra_rules_heads(rules) {
+            known.entry(name).or_insert(arity);
+        }
+    }
+    for fact in extra_facts.unwrap_or_default() {
+        if let Some((name, arity)) = query_atoms(fact).ok().and_then(|a| a.into_iter().next()) {
+            known.entry(name).or_insert(arity);
+        }
+    }
+}
+
+/// Build the `query_facts` response, keeping at most `max_results` tuples.
+///
+/// When results are dropped the response says so and carries the full count,
+/// so the caller knows to narrow the query rather than trusting a partial answer.
+fn query_response(query: &str, tuples: Vec<Vec<String>>, max_results: usize) -> Value {
+    let total = tuples.len();
+    let results: Vec<Value> = tuples
+        .into_iter()
+        .take(max_results)
+        .map(|tuple| json!(tuple))
+        .collect();
+
+    let mut response = json!({
+        "query": query,
+        "results": results,
+        "count": results.len()
+    });
+    if total > max_results {
+        response["truncated"] = json!(true);
+        response["total_count"] = json!(total);
+    }
+    response
+}
+
 pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
     let query = match arguments.get("query").and_then(|v| v.as_str()) {
         Some(q) => q.trim(),
@@ -682,6 +1105,13 @@ pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>)
 
     let extra_rules = arguments.get("extra_rules").and_then(|v| v.as_str());
 
+    let latest_only = arguments
+        .get("latest_only")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let max_results = parse_max_results(arguments, max_results_cap(state));
+
     // Validate extra_rules if provided
     if let Some(rules) = extra_rules {
         if rules.len() > MAX_QUERY_LENGTH {
@@ -807,29 +1237,41 @@ pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>)
     }
 
     if let Some(ref datalog_cache) = state.datalog_cache {
-        let tuples = match datalog_cache
-            .execute_query_with_facts_and_declarations(
+        // Catch typos and arity mistakes before Soufflé turns them into
+        // errors about the generated program
+        if datalog_cache.is_populated() {
+            let mut known = datalog_cache.predicate_signatures().await;
+            add_adhoc_signatures(
+                &mut known,
+                extra_rules,
+                extra_facts.as_deref(),
+                extra_declarations.as_deref(),
+            );
+            if let Err(e) = check_query(query, &known) {
+                return CallToolResult::error(format!("Invalid query: {}", e));
+            }
+        }
+
+        let query_start = std::time::Instant::now();
+        let query_result = datalog_cache
+            .execute_query_with_options(
                 query,
                 extra_rules,
                 extra_facts.as_deref(),
                 extra_declarations.as_deref(),
+                latest_only,
             )
-            .await
-        {
+            .await;
+        if let Some(ref metrics) = state.metrics {
+            metrics.record_datalog_query(query_start.elapsed(), query_result.is_ok());
+        }
+
+        let tuples = match query_result {
             Ok(tuples) => tuples,
             Err(e) => return CallToolResult::error(format!("Failed to execute query: {}", e)),
         };
 
-        let results: Vec<Value> = tuples.into_iter().map(|tuple| json!(tuple)).collect();
-
-        return CallToolResult::success(
-            json!({
-                "query": query,
-                "results": results,
-                "count": results.len()
-            })
-            .to_string(),
-        );
+        return CallToolResult::success(query_response(query, tuples, max_results).to_string());
     }
 
     // Fall back to non-cached execution
@@ -846,7 +1288,7 @@ pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>)
             let facts: Vec<winter_atproto::ListRecordItem<Fact>> = cached_facts
                 .into_iter()
                 .map(|(rkey, cached)| winter_atproto::ListRecordItem {
-                    uri: format!("at://did/{}:{}", FACT_COLLECTION, rkey),
+                    uri: format!("at://did/{}/{}", FACT_COLLECTION, rkey),
                     cid: cached.cid,
                     value: cached.value,
                 })
@@ -892,7 +1334,8 @@ pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>)
     };
 
     // Extract facts to TSV files
-    if let Err(e) = FactExtractor::extract_to_dir(&facts, temp_dir.path()) {
+    if let Err(e) = FactExtractor::extract_to_dir_with_options(&facts, temp_dir.path(), latest_only)
+    {
         return CallToolResult::error(format!("Failed to extract facts: {}", e));
     }
 
@@ -976,17 +1419,42 @@ pub async fn query_facts(state: &ToolState, arguments: &HashMap<String, Value>)
     // Parse results
     let tuples = SouffleExecutor::parse_output(&output);
 
-    // Format results
-    let results: Vec<Value> = tuples.into_iter().map(|tuple| json!(tuple)).collect();
+    CallToolResult::success(query_response(query, tuples, max_results).to_string())
+}
 
-    CallToolResult::success(
-        json!({
-            "query": query,
-            "results": results,
-            "count": results.len()
-        })
-        .to_string(),
-    )
+pub async fn query_why_not(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let goal = match arguments.get("goal").and_then(|v| v.as_str()) {
+        Some(g) => g.trim(),
+        None => return CallToolResult::error("Missing required parameter: goal"),
+    };
+
+    if goal.len() > MAX_QUERY_LENGTH {
+        return CallToolResult::error(format!(
+            "Goal too long: {} chars (max {})",
+            goal.len(),
+            MAX_QUERY_LENGTH
+        ));
+    }
+    for pattern in FORBIDDEN_PATTERNS {
+        if goal.contains(pattern) {
+            return CallToolResult::error(format!(
+                "Goal contains forbidden pattern: {:?}",
+                pattern
+            ));
+        }
+    }
+
+    let Some(ref datalog_cache) = state.datalog_cache else {
+        return CallToolResult::error("No datalog cache available");
+    };
+
+    match datalog_cache.why_not(goal).await {
+        Ok(report) => CallToolResult::success(serde_json::to_string(&report).unwrap_or_default()),
+        Err(e) => CallToolResult::error(format!("Failed to diagnose goal: {}", e)),
+    }
 }
 
 pub async fn list_validation_errors(
@@ -1137,6 +1605,84 @@ pub async fn list_predicates(
     )
 }
 
+pub async fn derived_fact_stats(
+    state: &ToolState,
+    _arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let Some(ref datalog_cache) = state.datalog_cache else {
+        return CallToolResult::error("Datalog cache not available");
+    };
+
+    let derived = datalog_cache.derived().await;
+    let last_generation = derived.last_generation().map(|stats| {
+        json!({
+            "finished_at": stats.finished_at.to_rfc3339(),
+            "duration_ms": stats.duration_ms,
+            "souffle_ran": stats.souffle_ran,
+            "total_added": stats.total_added(),
+            "total_removed": stats.total_removed(),
+            "predicates": stats.predicates,
+        })
+    });
+
+    CallToolResult::success(
+        json!({
+            "records": derived.stats(),
+            "last_generation": last_generation,
+            "query_cache": datalog_cache.query_cache_stats(),
+        })
+        .to_string(),
+    )
+}
+
+pub async fn predicate_graph(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let format = arguments
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("json");
+    if !matches!(format, "json" | "dot") {
+        return CallToolResult::error(format!(
+            "Invalid format '{}': expected 'json' or 'dot'",
+            format
+        ));
+    }
+
+    // Try cache first, fall back to HTTP
+    let rules: Vec<Rule> = match &state.cache {
+        Some(cache) if cache.state() == SyncState::Live => cache
+            .list_rules()
+            .into_iter()
+            .map(|(_, cached)| cached.value)
+            .collect(),
+        _ => match state
+            .atproto
+            .list_all_records::<Rule>(RULE_COLLECTION)
+            .await
+        {
+            Ok(records) => records.into_iter().map(|r| r.value).collect(),
+            Err(e) => return CallToolResult::error(format!("Failed to list rules: {}", e)),
+        },
+    };
+
+    let graph = DependencyGraphExport::from_rules(&rules);
+    let mut result = json!({
+        "format": format,
+        "node_count": graph.nodes.len(),
+        "edge_count": graph.edges.len(),
+    });
+    if format == "dot" {
+        result["dot"] = json!(graph.to_dot());
+    } else {
+        result["nodes"] = json!(graph.nodes);
+        result["edges"] = json!(graph.edges);
+    }
+
+    CallToolResult::success(result.to_string())
+}
+
 /// Represents a query argument - either a variable or a constant.
 #[derive(Debug, Clone, PartialEq)]
 enum QueryArg {
@@ -1396,6 +1942,91 @@ fn generate_query_wrapper(
     (wrapper, result_arity)
 }
 
+/// Diff cached facts against the PDS, optionally repairing the cache.
+pub async fn reconcile_facts(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let cache = match &state.cache {
+        Some(cache) => cache,
+        None => return CallToolResult::error("Repo cache not available"),
+    };
+    let repair = arguments
+        .get("repair")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // Snapshot the cache before listing, so a fact deleted mid-listing shows
+    // up as extra (and is removed on repair) rather than being missed.
+    let cached: HashMap<String, String> = cache
+        .list_facts()
+        .into_iter()
+        .map(|(rkey, record)| (rkey, record.cid))
+        .collect();
+
+    let records = match state
+        .atproto
+        .list_all_records::<Fact>(FACT_COLLECTION)
+        .await
+    {
+        Ok(records) => records,
+        Err(e) => return CallToolResult::error(format!("Failed to list facts: {}", e)),
+    };
+
+    let mut missing = Vec::new();
+    let mut outdated = Vec::new();
+    let mut invalid = Vec::new();
+    let mut on_pds = HashSet::new();
+    let mut to_upsert = Vec::new();
+    for item in records {
+        let rkey = AtUri::extract_rkey(&item.uri).to_string();
+        on_pds.insert(rkey.clone());
+        // Records failing validation are kept out of the cache on purpose.
+        if let Err(reason) = item.value.validate() {
+            invalid.push(json!({ "rkey": rkey, "reason": reason }));
+            continue;
+        }
+        match cached.get(&rkey) {
+            None => missing.push(rkey.clone()),
+            Some(cid) if *cid != item.cid => outdated.push(rkey.clone()),
+            Some(_) => continue,
+        }
+        to_upsert.push((rkey, item.value, item.cid));
+    }
+
+    let mut extra: Vec<String> = cached
+        .keys()
+        .filter(|rkey| !on_pds.contains(*rkey))
+        .cloned()
+        .collect();
+    missing.sort();
+    outdated.sort();
+    extra.sort();
+
+    if repair {
+        for (rkey, fact, cid) in to_upsert {
+            cache.upsert_fact(rkey, fact, cid);
+        }
+        for rkey in &extra {
+            cache.delete_fact(rkey);
+        }
+    }
+
+    CallToolResult::success(
+        json!({
+            "in_sync": missing.is_empty() && outdated.is_empty() && extra.is_empty(),
+            "pds_count": on_pds.len(),
+            "cache_count": cached.len(),
+            "missing_from_cache": missing,
+            "extra_in_cache": extra,
+            "outdated_in_cache": outdated,
+            "invalid": invalid,
+            "repaired": repair
+        })
+        .to_string(),
+    )
+}
+
 /// Fetch facts and rules via HTTP (fallback when cache is unavailable).
 async fn fetch_facts_and_rules_http(
     state: &ToolState,
@@ -1434,6 +2065,100 @@ async fn fetch_facts_and_rules_http(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use winter_atproto::FactDeclArg;
+
+    fn make_fact(predicate: &str, args: &[&str]) -> Fact {
+        Fact {
+            predicate: predicate.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            confidence: None,
+            source: None,
+            supersedes: None,
+            tags: vec![],
+            created_at: Utc::now(),
+            expires_at: None,
+            deleted_at: None,
+        }
+    }
+
+    fn scored_declarations() -> HashMap<String, FactDeclaration> {
+        let declaration = FactDeclaration {
+            predicate: "scored".to_string(),
+            args: vec![
+                FactDeclArg {
+                    name: "who".to_string(),
+                    r#type: "symbol".to_string(),
+                    description: None,
+                },
+                FactDeclArg {
+                    name: "score".to_string(),
+                    r#type: "number".to_string(),
+                    description: None,
+                },
+            ],
+            description: "A score for an account".to_string(),
+            tags: vec![],
+            decay: None,
+            created_at: Utc::now(),
+            last_updated: None,
+        };
+        HashMap::from([("scored".to_string(), declaration)])
+    }
+
+    #[test]
+    fn test_check_fact_rejects_arg_count_mismatch() {
+        let declarations = scored_declarations();
+        let fact = make_fact("scored", &["did:plc:a"]);
+
+        let err = check_fact(&fact, &declarations, true, UndeclaredPolicy::Allow).unwrap_err();
+        assert!(
+            err.contains("arity mismatch: expected 2 args, got 1"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_check_fact_rejects_type_mismatch() {
+        let declarations = scored_declarations();
+        let fact = make_fact("scored", &["did:plc:a", "lots"]);
+
+        let err = check_fact(&fact, &declarations, true, UndeclaredPolicy::Allow).unwrap_err();
+        assert!(
+            err.contains("type mismatch: arg 1 expected number"),
+            "{err}"
+        );
+
+        let ok = make_fact("scored", &["did:plc:a", "7"]);
+        assert_eq!(
+            check_fact(&ok, &declarations, true, UndeclaredPolicy::Allow),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_check_fact_non_strict_warns() {
+        let declarations = scored_declarations();
+        let fact = make_fact("scored", &["did:plc:a", "lots"]);
+
+        let warning = check_fact(&fact, &declarations, false, UndeclaredPolicy::Allow).unwrap();
+        assert!(warning.unwrap().contains("type mismatch"));
+    }
+
+    #[test]
+    fn test_check_fact_undeclared_policy() {
+        let declarations = scored_declarations();
+        let fact = make_fact("mood", &["calm"]);
+
+        assert_eq!(
+            check_fact(&fact, &declarations, true, UndeclaredPolicy::Allow),
+            Ok(None)
+        );
+        let warning = check_fact(&fact, &declarations, true, UndeclaredPolicy::Warn).unwrap();
+        assert_eq!(
+            warning.as_deref(),
+            Some("predicate 'mood' has no declaration")
+        );
+    }
 
     #[test]
     fn test_parse_query_with_variables() {
@@ -1614,4 +2339,95 @@ mod tests {
     fn test_max_query_length() {
         assert_eq!(MAX_QUERY_LENGTH, 4096);
     }
+
+    fn tuples(n: usize) -> Vec<Vec<String>> {
+        (0..n).map(|i| vec![format!("did:plc:{}", i)]).collect()
+    }
+
+    #[test]
+    fn test_query_response_truncates_at_cap() {
+        let response = query_response("follows(X)", tuples(25), 10);
+        assert_eq!(response["count"], 10);
+        assert_eq!(response["results"].as_array().unwrap().len(), 10);
+        assert_eq!(response["results"][0], json!(["did:plc:0"]));
+        assert_eq!(response["truncated"], true);
+        assert_eq!(response["total_count"], 25);
+    }
+
+    #[test]
+    fn test_query_response_within_cap_not_flagged() {
+        let response = query_response("follows(X)", tuples(10), 10);
+        assert_eq!(response["count"], 10);
+        assert!(response.get("truncated").is_none());
+        assert!(response.get("total_count").is_none());
+    }
+
+    #[test]
+    fn test_parse_max_results_respects_cap() {
+        let args = |v: Value| HashMap::from([("max_results".to_string(), v)]);
+        assert_eq!(parse_max_results(&HashMap::new(), 500), 500);
+        assert_eq!(parse_max_results(&args(json!(20)), 500), 20);
+        assert_eq!(parse_max_results(&args(json!(100_000)), 500), 500);
+        assert_eq!(parse_max_results(&args(json!(0)), 500), 1);
+        assert_eq!(parse_max_results(&args(json!("lots")), 500), 500);
+    }
+
+    #[test]
+    fn test_add_adhoc_signatures() {
+        let mut known = HashMap::from([("follows".to_string(), 3)]);
+        add_adhoc_signatures(
+            &mut known,
+            Some(".decl seen(x: symbol)\nmutual(X, Y) :- follows(X, Y, _), follows(Y, X, _)."),
+            Some(&["thread_depth(\"at://a\", \"7\")".to_string()]),
+            Some(&["mood(who: symbol, how: symbol)".to_string()]),
+        );
+        assert_eq!(known["seen"], 1);
+        assert_eq!(known["mutual"], 2);
+        assert_eq!(known["thread_depth"], 2);
+        assert_eq!(known["mood"], 2);
+        assert_eq!(known["follows"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_facts_rejects_unknown_predicate_before_souffle() {
+        let registry = super::super::ToolRegistry::empty();
+        let datalog_cache = winter_datalog::DatalogCache::new_temp().unwrap();
+        datalog_cache
+            .populate_from_repo_cache(&winter_atproto::RepoCache::new())
+            .await;
+        registry.state.write().await.datalog_cache = Some(datalog_cache);
+        let state = registry.state.read().await;
+
+        let args = HashMap::from([("query".to_string(), json!("follws(X, Y, _)"))]);
+        let result = query_facts(&state, &args).await;
+
+        assert_eq!(result.is_error, Some(true));
+        let crate::protocol::ToolContent::Text { text } = &result.content[0];
+        assert_eq!(
+            text,
+            "Invalid query: predicate `follws` unknown, did you mean `follows`?"
+        );
+
+        let args = HashMap::from([("query".to_string(), json!("follows(X)"))]);
+        let result = query_facts(&state, &args).await;
+        let crate::protocol::ToolContent::Text { text } = &result.content[0];
+        assert!(text.starts_with("Invalid query: predicate `follows` takes"));
+    }
+
+    #[tokio::test]
+    async fn test_query_why_not_rejects_malformed_goal() {
+        let registry = super::super::ToolRegistry::empty();
+        registry.state.write().await.datalog_cache =
+            Some(winter_datalog::DatalogCache::new_temp().unwrap());
+        let state = registry.state.read().await;
+
+        let result = query_why_not(&state, &HashMap::new()).await;
+        assert_eq!(result.is_error, Some(true));
+
+        let args = HashMap::from([("goal".to_string(), json!("mutual"))]);
+        let result = query_why_not(&state, &args).await;
+        assert_eq!(result.is_error, Some(true));
+        let crate::protocol::ToolContent::Text { text } = &result.content[0];
+        assert!(text.starts_with("Failed to diagnose goal: parse error: expected an atom"));
+    }
 }
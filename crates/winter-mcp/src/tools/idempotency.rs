@@ -0,0 +1,281 @@
+//! Idempotency keys for mutating tools.
+//!
+//! A caller that retries a mutation (say, `create_fact` after a timeout) can
+//! pass the same `idempotency_key` both times. The first successful result is
+//! remembered for a window and replayed to any retry instead of running the
+//! tool again. A retry that arrives while the first call is still running
+//! waits for it rather than racing it. Reusing a key with different arguments
+//! is an error rather than a replay of the other call's result.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use tokio::sync::OnceCell;
+
+use crate::protocol::CallToolResult;
+
+use super::ToolMeta;
+use super::permissions::is_safe_mcp_tool;
+
+/// Argument name callers use to pass an idempotency key.
+pub const IDEMPOTENCY_KEY_ARG: &str = "idempotency_key";
+
+/// How long a key's result is remembered.
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Whether a tool takes an idempotency key. Read-only tools don't need one.
+pub fn accepts_key(tool: &str) -> bool {
+    !is_safe_mcp_tool(tool)
+}
+
+/// Add the idempotency key parameter to a mutating tool's input schema.
+pub fn advertise_key(mut meta: ToolMeta) -> ToolMeta {
+    if accepts_key(&meta.definition.name)
+        && let Some(properties) = meta
+            .definition
+            .input_schema
+            .get_mut("properties")
+            .and_then(|p| p.as_object_mut())
+    {
+        properties.insert(
+            IDEMPOTENCY_KEY_ARG.to_string(),
+            json!({
+                "type": "string",
+                "description": "Optional key for safe retries: repeating a call with the same key within a few minutes returns the first result instead of running the tool again."
+            }),
+        );
+    }
+    meta
+}
+
+/// The outcome of the first call made with a key.
+type Slot = Arc<OnceCell<CallToolResult>>;
+
+struct Entry {
+    slot: Slot,
+    /// The arguments of the first call, which any reuse of the key must match.
+    fingerprint: String,
+    created_at: Instant,
+}
+
+/// The arguments of a call in a canonical form, minus the key itself.
+fn fingerprint(arguments: &HashMap<String, Value>) -> String {
+    let arguments: BTreeMap<&String, &Value> = arguments
+        .iter()
+        .filter(|(name, _)| name.as_str() != IDEMPOTENCY_KEY_ARG)
+        .collect();
+    serde_json::to_string(&arguments).unwrap_or_default()
+}
+
+/// Short-lived map of (tool, key) to the result of the first call.
+pub struct IdempotencyStore {
+    window: Duration,
+    entries: Mutex<HashMap<(String, String), Entry>>,
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl IdempotencyStore {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The slot for this tool and key, created if new or expired.
+    ///
+    /// Keys are scoped per tool, so reusing a key across tools is harmless.
+    /// Returns `None` if the key is already bound to a different `fingerprint`.
+    fn slot(&self, tool: &str, key: &str, fingerprint: String) -> Option<Slot> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| now.duration_since(entry.created_at) < self.window);
+        let entry = entries
+            .entry((tool.to_string(), key.to_string()))
+            .or_insert_with(|| Entry {
+                slot: Arc::new(OnceCell::new()),
+                fingerprint: fingerprint.clone(),
+                created_at: now,
+            });
+        (entry.fingerprint == fingerprint).then(|| Arc::clone(&entry.slot))
+    }
+
+    /// Run `call` at most once per key within the window.
+    ///
+    /// Failed results aren't remembered, so a retry after an error runs again.
+    /// A key reused with different `arguments` fails without running `call`.
+    pub async fn run<F, Fut>(
+        &self,
+        tool: &str,
+        key: &str,
+        arguments: &HashMap<String, Value>,
+        call: F,
+    ) -> CallToolResult
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = CallToolResult>,
+    {
+        let Some(slot) = self.slot(tool, key, fingerprint(arguments)) else {
+            return CallToolResult::error(format!(
+                "{} '{}' was already used with different arguments; use a new key for a different call",
+                IDEMPOTENCY_KEY_ARG, key
+            ));
+        };
+        let result = slot
+            .get_or_try_init(|| async move {
+                let result = call().await;
+                if result.is_error == Some(true) {
+                    Err(result)
+                } else {
+                    Ok(result)
+                }
+            })
+            .await;
+        match result {
+            Ok(result) => result.clone(),
+            Err(result) => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn args(content: &str) -> HashMap<String, Value> {
+        HashMap::from([
+            ("content".to_string(), json!(content)),
+            (IDEMPOTENCY_KEY_ARG.to_string(), json!("k1")),
+        ])
+    }
+
+    async fn counted(calls: &AtomicUsize, result: CallToolResult) -> CallToolResult {
+        calls.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    #[test]
+    fn only_mutating_tools_advertise_a_key() {
+        let tools = super::super::ToolRegistry::all_tools();
+        let has_key = |name: &str| {
+            let tool = tools.iter().find(|t| t.definition.name == name).unwrap();
+            tool.definition.input_schema["properties"]
+                .get(IDEMPOTENCY_KEY_ARG)
+                .is_some()
+        };
+
+        assert!(has_key("create_fact"));
+        assert!(!has_key("query_facts"));
+    }
+
+    #[tokio::test]
+    async fn same_key_runs_once() {
+        let store = IdempotencyStore::default();
+        let calls = AtomicUsize::new(0);
+
+        let first = store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success("one"))
+            })
+            .await;
+        let second = store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success("two"))
+            })
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn keys_are_scoped_per_tool() {
+        let store = IdempotencyStore::default();
+        let calls = AtomicUsize::new(0);
+
+        store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+        store
+            .run("create_note", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_remembered() {
+        let store = IdempotencyStore::default();
+        let calls = AtomicUsize::new(0);
+
+        let first = store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::error("timeout"))
+            })
+            .await;
+        let second = store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success("ok"))
+            })
+            .await;
+
+        assert_eq!(first.is_error, Some(true));
+        assert_eq!(second.is_error, Some(false));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn keys_expire_after_the_window() {
+        let store = IdempotencyStore::new(Duration::from_millis(1));
+        let calls = AtomicUsize::new(0);
+
+        store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn key_reused_with_other_arguments_is_rejected() {
+        let store = IdempotencyStore::default();
+        let calls = AtomicUsize::new(0);
+
+        store
+            .run("create_fact", "k1", &args("a"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+        let reused = store
+            .run("create_fact", "k1", &args("b"), || {
+                counted(&calls, CallToolResult::success(""))
+            })
+            .await;
+
+        assert_eq!(reused.is_error, Some(true));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
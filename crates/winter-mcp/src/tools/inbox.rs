@@ -1,15 +1,20 @@
 //! Inbox infrastructure for the persistent session model.
 //!
-//! The inbox is an in-memory collection of items (notifications, DMs, jobs, system messages)
-//! that the daemon pushes and Winter polls via MCP tools. Items persist until explicitly
-//! acknowledged.
+//! The inbox is an in-memory collection of items (notifications, mentions, DMs, jobs,
+//! trigger firings, system messages) that the daemon pushes and Winter polls via MCP
+//! tools. Each item carries a typed payload, so Winter never re-parses message strings.
+//! Items persist until explicitly acknowledged. An inbox opened with
+//! [`Inbox::with_store`] also mirrors its items to a local JSON file, so pending work
+//! survives a restart.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tokio::sync::RwLock;
+use tracing::warn;
 use winter_atproto::{Facet, Tid};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
@@ -42,8 +47,10 @@ pub struct InboxItem {
 #[serde(rename_all = "snake_case")]
 pub enum InboxItemKind {
     Notification,
+    Mention,
     DirectMessage,
     Job,
+    Trigger,
     System,
 }
 
@@ -51,8 +58,10 @@ impl std::fmt::Display for InboxItemKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Notification => write!(f, "notification"),
+            Self::Mention => write!(f, "mention"),
             Self::DirectMessage => write!(f, "direct_message"),
             Self::Job => write!(f, "job"),
+            Self::Trigger => write!(f, "trigger"),
             Self::System => write!(f, "system"),
         }
     }
@@ -90,6 +99,33 @@ pub enum InboxPayload {
         #[serde(default)]
         facets: Vec<Facet>,
     },
+    CoalescedNotification {
+        /// "like", "repost", etc.
+        reason: String,
+        /// AT URI of the post the notifications are about.
+        subject_uri: String,
+        /// How many notifications were folded into this item.
+        count: usize,
+        /// Handles of the notification authors, oldest first.
+        author_handles: Vec<String>,
+    },
+    FollowersChanged {
+        /// DIDs that started following since the last follower sync.
+        added: Vec<String>,
+        /// DIDs that stopped following since the last follower sync.
+        removed: Vec<String>,
+    },
+    Mention {
+        author_did: String,
+        author_handle: String,
+        text: Option<String>,
+        uri: String,
+        cid: String,
+        parent: Option<PostRef>,
+        root: Option<PostRef>,
+        #[serde(default)]
+        facets: Vec<Facet>,
+    },
     DirectMessage {
         sender_did: String,
         sender_handle: String,
@@ -105,6 +141,21 @@ pub enum InboxPayload {
         name: String,
         instructions: String,
     },
+    Trigger {
+        /// Name of the trigger that fired.
+        name: String,
+        /// The action's message, with variables substituted.
+        message: String,
+        /// URI of the event subject, for event triggers.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject_uri: Option<String>,
+        /// DID of the event author, for event triggers.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author_did: Option<String>,
+        /// Text of the event, for event triggers.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
     System {
         message: String,
     },
@@ -129,6 +180,8 @@ const DEFAULT_MAX_SIZE: usize = 200;
 pub struct Inbox {
     items: RwLock<Vec<InboxItem>>,
     max_size: usize,
+    /// File the pending items are mirrored to, if any.
+    store: Option<PathBuf>,
 }
 
 impl Inbox {
@@ -137,6 +190,7 @@ impl Inbox {
         Self {
             items: RwLock::new(Vec::new()),
             max_size: DEFAULT_MAX_SIZE,
+            store: None,
         }
     }
 
@@ -145,6 +199,40 @@ impl Inbox {
         Self {
             items: RwLock::new(Vec::new()),
             max_size,
+            store: None,
+        }
+    }
+
+    /// Open an inbox backed by the JSON file at `path`.
+    ///
+    /// Items left pending by a previous run are loaded back; a missing file
+    /// starts an empty inbox. Every push and acknowledgement rewrites the file.
+    pub fn with_store(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let items = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            items: RwLock::new(items),
+            max_size: DEFAULT_MAX_SIZE,
+            store: Some(path),
+        })
+    }
+
+    /// Write `items` to the backing file, if there is one.
+    ///
+    /// Writes to a sibling temp file and renames it into place, so a crash
+    /// mid-write leaves the previous snapshot intact. Failures are logged
+    /// rather than returned: the in-memory inbox stays authoritative.
+    async fn persist(&self, items: &[InboxItem]) {
+        let Some(path) = &self.store else {
+            return;
+        };
+        if let Err(e) = write_snapshot(path, items).await {
+            warn!(error = %e, path = %path.display(), "failed to persist inbox");
         }
     }
 
@@ -166,6 +254,8 @@ impl Inbox {
                 }
             }
         }
+
+        self.persist(&items).await;
     }
 
     /// Get all pending items, sorted by priority (descending) then time (ascending).
@@ -185,7 +275,11 @@ impl Inbox {
         let mut items = self.items.write().await;
         let before = items.len();
         items.retain(|item| !ids.contains(&item.id));
-        before - items.len()
+        let removed = before - items.len();
+        if removed > 0 {
+            self.persist(&items).await;
+        }
+        removed
     }
 
     /// Get the number of pending items.
@@ -214,12 +308,22 @@ impl Default for Inbox {
     }
 }
 
+async fn write_snapshot(path: &Path, items: &[InboxItem]) -> std::io::Result<()> {
+    let json = serde_json::to_vec(items)?;
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, json).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
 // ============================================================================
 // Helper: Create inbox items with auto-generated IDs
 // ============================================================================
 
 impl InboxItem {
     /// Create a new notification inbox item.
+    ///
+    /// Mentions (`kind` of "mention") become [`InboxItemKind::Mention`] items;
+    /// every other reason stays a notification.
     pub fn notification(
         author_did: String,
         author_handle: String,
@@ -233,26 +337,84 @@ impl InboxItem {
     ) -> Self {
         let root_uri = root.as_ref().map(|r| r.uri.as_str()).unwrap_or(&uri);
         let context_tag = format!("notification:{}:root={}", uri, root_uri);
+        let (item_kind, payload) = if kind == "mention" {
+            (
+                InboxItemKind::Mention,
+                InboxPayload::Mention {
+                    author_did,
+                    author_handle,
+                    text,
+                    uri,
+                    cid,
+                    parent,
+                    root,
+                    facets,
+                },
+            )
+        } else {
+            (
+                InboxItemKind::Notification,
+                InboxPayload::Notification {
+                    author_did,
+                    author_handle,
+                    kind,
+                    text,
+                    uri,
+                    cid,
+                    parent,
+                    root,
+                    facets,
+                },
+            )
+        };
         Self {
             id: Tid::now().to_string(),
-            kind: InboxItemKind::Notification,
+            kind: item_kind,
             priority: 100,
             created_at: Utc::now(),
             context_tag,
-            payload: InboxPayload::Notification {
-                author_did,
-                author_handle,
-                kind,
-                text,
-                uri,
-                cid,
-                parent,
-                root,
-                facets,
+            payload,
+        }
+    }
+
+    /// Create a notification item standing in for a burst of same-reason
+    /// notifications about one post.
+    ///
+    /// Low priority: a run of likes is worth noticing, not worth waking for.
+    pub fn coalesced_notification(
+        reason: String,
+        subject_uri: String,
+        author_handles: Vec<String>,
+    ) -> Self {
+        let context_tag = format!("coalesced:{}:{}", reason, subject_uri);
+        Self {
+            id: Tid::now().to_string(),
+            kind: InboxItemKind::Notification,
+            priority: 25,
+            created_at: Utc::now(),
+            context_tag,
+            payload: InboxPayload::CoalescedNotification {
+                reason,
+                subject_uri,
+                count: author_handles.len(),
+                author_handles,
             },
         }
     }
 
+    /// Create a notification item listing follower changes found by a
+    /// follower sync.
+    pub fn followers_changed(added: Vec<String>, removed: Vec<String>) -> Self {
+        Self {
+            id: Tid::now().to_string(),
+            kind: InboxItemKind::Notification,
+            priority: 25,
+            created_at: Utc::now(),
+            context_tag: "followers".to_string(),
+            payload: InboxPayload::FollowersChanged { added, removed },
+        }
+    }
+
     /// Create a new DM inbox item.
     pub fn direct_message(
         sender_did: String,
@@ -296,6 +458,48 @@ impl InboxItem {
         }
     }
 
+    /// Create a new trigger inbox item.
+    pub fn trigger(name: String, message: String) -> Self {
+        let context_tag = format!("trigger:{}", name);
+        Self {
+            id: Tid::now().to_string(),
+            kind: InboxItemKind::Trigger,
+            priority: 50,
+            created_at: Utc::now(),
+            context_tag,
+            payload: InboxPayload::Trigger {
+                name,
+                message,
+                subject_uri: None,
+                author_did: None,
+                text: None,
+            },
+        }
+    }
+
+    /// Attach the event that fired an event trigger to a trigger item.
+    ///
+    /// Has no effect on other kinds of item.
+    pub fn with_event(
+        mut self,
+        event_subject: String,
+        event_author: String,
+        event_text: String,
+    ) -> Self {
+        if let InboxPayload::Trigger {
+            subject_uri,
+            author_did,
+            text,
+            ..
+        } = &mut self.payload
+        {
+            *subject_uri = Some(event_subject);
+            *author_did = Some(event_author);
+            *text = Some(event_text);
+        }
+        self
+    }
+
     /// Create a new tool approved inbox item.
     pub fn tool_approved(tool_name: String, tool_rkey: String, approval_rkey: String) -> Self {
         let context_tag = format!("tool_approved:{}", tool_rkey);
@@ -334,13 +538,13 @@ pub fn definitions() -> Vec<ToolDefinition> {
     vec![
         ToolDefinition {
             name: "check_inbox".to_string(),
-            description: "Check the inbox for pending items (notifications, DMs, jobs). Returns all pending items sorted by priority then time. Call this regularly between tasks and during free time.".to_string(),
+            description: "Check the inbox for pending items (notifications, mentions, DMs, jobs, trigger firings). Returns all pending items sorted by priority then time, each with a typed payload. Call this regularly between tasks and during free time.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "types": {
                         "type": "array",
-                        "items": { "type": "string", "enum": ["notification", "direct_message", "job", "system"] },
+                        "items": { "type": "string", "enum": ["notification", "mention", "direct_message", "job", "trigger", "system"] },
                         "description": "Filter by item kind. If omitted, returns all types."
                     },
                     "min_priority": {
@@ -546,6 +750,53 @@ mod tests {
         assert_eq!(inbox.len().await, 1);
     }
 
+    #[tokio::test]
+    async fn test_stored_inbox_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inbox.json");
+
+        let inbox = Inbox::with_store(&path).unwrap();
+        assert!(inbox.is_empty().await);
+        inbox.push(InboxItem::job("a".into(), "first".into())).await;
+        inbox.push(InboxItem::system("second".into())).await;
+        let ids: Vec<String> = inbox.items().await.into_iter().map(|i| i.id).collect();
+        drop(inbox);
+
+        let restarted = Inbox::with_store(&path).unwrap();
+        let reloaded: Vec<String> = restarted.items().await.into_iter().map(|i| i.id).collect();
+        assert_eq!(reloaded, ids);
+    }
+
+    #[tokio::test]
+    async fn test_stored_inbox_forgets_acknowledged_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inbox.json");
+
+        let inbox = Inbox::with_store(&path).unwrap();
+        inbox.push(InboxItem::job("a".into(), "first".into())).await;
+        inbox
+            .push(InboxItem::job("b".into(), "second".into()))
+            .await;
+        let items = inbox.items().await;
+        assert_eq!(inbox.acknowledge(&[items[0].id.clone()]).await, 1);
+        drop(inbox);
+
+        let restarted = Inbox::with_store(&path).unwrap();
+        let pending = restarted.items().await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, items[1].id);
+    }
+
+    #[test]
+    fn test_stored_inbox_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inbox.json");
+        std::fs::write(&path, b"not json").unwrap();
+
+        let err = Inbox::with_store(&path).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
     #[tokio::test]
     async fn test_inbox_overflow_trimming() {
         let inbox = Inbox::with_max_size(3);
@@ -604,4 +855,129 @@ mod tests {
         assert!(!result.is_error.unwrap_or(false));
         assert!(inbox.is_empty().await);
     }
+
+    fn notification_item(kind: &str) -> InboxItem {
+        InboxItem::notification(
+            "did:plc:author".into(),
+            "author.bsky.social".into(),
+            kind.into(),
+            Some("hi @winter".into()),
+            "at://did:plc:author/app.bsky.feed.post/1".into(),
+            "bafypost".into(),
+            None,
+            None,
+            vec![],
+        )
+    }
+
+    /// One item from each source the daemon pushes.
+    fn item_per_source() -> Vec<InboxItem> {
+        vec![
+            notification_item("reply"),
+            notification_item("mention"),
+            InboxItem::direct_message(
+                "did:plc:friend".into(),
+                "friend.bsky.social".into(),
+                "convo1".into(),
+                "msg1".into(),
+                "hey".into(),
+                vec![],
+                vec![],
+                150,
+            ),
+            InboxItem::job("digest".into(), "summarize the day".into()),
+            InboxItem::trigger("greet".into(), "mentioned by did:plc:a".into()).with_event(
+                "at://did:plc:a/app.bsky.feed.post/1".into(),
+                "did:plc:a".into(),
+                "hello".into(),
+            ),
+            InboxItem::system("restarted".into()),
+        ]
+    }
+
+    #[test]
+    fn test_every_variant_roundtrips_through_json() {
+        for item in item_per_source() {
+            let json = serde_json::to_value(&item).unwrap();
+            let back: InboxItem = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(back.kind, item.kind);
+            assert_eq!(serde_json::to_value(&back).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn test_payload_types_match_kinds() {
+        let tags: Vec<(String, Value)> = item_per_source()
+            .iter()
+            .map(|item| {
+                let json = serde_json::to_value(item).unwrap();
+                (item.kind.to_string(), json["payload"]["type"].clone())
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("notification".to_string(), json!("notification")),
+                ("mention".to_string(), json!("mention")),
+                ("direct_message".to_string(), json!("direct_message")),
+                ("job".to_string(), json!("job")),
+                ("trigger".to_string(), json!("trigger")),
+                ("system".to_string(), json!("system")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mentions_keep_notification_context_tag() {
+        let item = notification_item("mention");
+        assert_eq!(item.kind, InboxItemKind::Mention);
+        assert!(item.context_tag.starts_with("notification:"));
+        let InboxPayload::Mention {
+            author_did, text, ..
+        } = &item.payload
+        else {
+            panic!("expected mention payload, got {:?}", item.payload);
+        };
+        assert_eq!(author_did, "did:plc:author");
+        assert_eq!(text.as_deref(), Some("hi @winter"));
+    }
+
+    #[test]
+    fn test_trigger_without_event_omits_event_fields() {
+        let item = InboxItem::trigger("nightly".into(), "check feeds".into());
+        assert_eq!(item.context_tag, "trigger:nightly");
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(
+            json["payload"],
+            json!({"type": "trigger", "name": "nightly", "message": "check feeds"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_inbox_returns_typed_item_per_source() {
+        let inbox = Inbox::new();
+        for item in item_per_source() {
+            inbox.push(item).await;
+        }
+
+        for kind in [
+            "notification",
+            "mention",
+            "direct_message",
+            "job",
+            "trigger",
+            "system",
+        ] {
+            let mut args = HashMap::new();
+            args.insert("types".to_string(), json!([kind]));
+            let result = check_inbox(Some(&inbox), &args).await;
+            let crate::protocol::ToolContent::Text { text } = &result.content[0];
+            let body: Value = serde_json::from_str(text).unwrap();
+
+            assert_eq!(body["count"], 1, "{}", kind);
+            let item: InboxItem = serde_json::from_value(body["items"][0].clone()).unwrap();
+            assert_eq!(item.kind.to_string(), kind);
+            assert_eq!(body["items"][0]["payload"]["type"], kind);
+        }
+    }
 }
@@ -6,9 +6,9 @@ use chrono::{DateTime, Utc};
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Job, JobSchedule, JobStatus, Tid};
+use winter_atproto::{Job, JobSchedule, JobStatus, Tid, find_job_dependency_cycle};
 
-use super::{ToolMeta, ToolState};
+use super::{ToolMeta, ToolState, dry_run};
 
 /// Collection name for jobs.
 const JOB_COLLECTION: &str = "diy.razorgirl.winter.job";
@@ -32,6 +32,11 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "run_at": {
                         "type": "string",
                         "description": "ISO 8601 timestamp for when to run (e.g., '2026-01-30T14:00:00Z')"
+                    },
+                    "depends_on": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Record keys of jobs whose most recent run must succeed before this job runs"
                     }
                 },
                 "required": ["name", "instructions", "run_at"]
@@ -54,6 +59,11 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "interval_seconds": {
                         "type": "integer",
                         "description": "How often to run (in seconds)"
+                    },
+                    "depends_on": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Record keys of jobs whose most recent run must succeed before this job runs"
                     }
                 },
                 "required": ["name", "instructions", "interval_seconds"]
@@ -108,6 +118,20 @@ pub fn definitions() -> Vec<ToolDefinition> {
                 "required": ["rkey"]
             }),
         },
+        ToolDefinition {
+            name: "run_job_now".to_string(),
+            description: "Run a job immediately, independent of its schedule. Recurring jobs keep their regular cadence.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of the job to run"
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
     ]
 }
 
@@ -138,6 +162,12 @@ pub async fn schedule_job(state: &ToolState, arguments: &HashMap<String, Value>)
         Err(e) => return CallToolResult::error(format!("Invalid run_at timestamp: {}", e)),
     };
 
+    let rkey = Tid::now().to_string();
+    let depends_on = match parse_depends_on(state, &rkey, arguments).await {
+        Ok(deps) => deps,
+        Err(e) => return CallToolResult::error(e),
+    };
+
     let job = Job {
         name: name.to_string(),
         instructions: instructions.to_string(),
@@ -147,9 +177,13 @@ pub async fn schedule_job(state: &ToolState, arguments: &HashMap<String, Value>)
         next_run: Some(run_at),
         failure_count: 0,
         created_at: Utc::now(),
+        run_requested_at: None,
+        depends_on,
     };
 
-    let rkey = Tid::now().to_string();
+    if dry_run::requested(arguments) {
+        return dry_run::preview(JOB_COLLECTION, &job, &[]);
+    }
 
     match state
         .atproto
@@ -167,7 +201,8 @@ pub async fn schedule_job(state: &ToolState, arguments: &HashMap<String, Value>)
                     "uri": response.uri,
                     "cid": response.cid,
                     "name": name,
-                    "run_at": run_at.to_rfc3339()
+                    "run_at": run_at.to_rfc3339(),
+                    "depends_on": job.depends_on
                 })
                 .to_string(),
             )
@@ -196,6 +231,12 @@ pub async fn schedule_recurring(
         None => return CallToolResult::error("Missing required parameter: interval_seconds"),
     };
 
+    let rkey = Tid::now().to_string();
+    let depends_on = match parse_depends_on(state, &rkey, arguments).await {
+        Ok(deps) => deps,
+        Err(e) => return CallToolResult::error(e),
+    };
+
     let now = Utc::now();
     let next_run = now + chrono::Duration::seconds(interval as i64);
 
@@ -208,9 +249,13 @@ pub async fn schedule_recurring(
         next_run: Some(next_run),
         failure_count: 0,
         created_at: now,
+        run_requested_at: None,
+        depends_on,
     };
 
-    let rkey = Tid::now().to_string();
+    if dry_run::requested(arguments) {
+        return dry_run::preview(JOB_COLLECTION, &job, &[]);
+    }
 
     match state
         .atproto
@@ -229,7 +274,8 @@ pub async fn schedule_recurring(
                     "cid": response.cid,
                     "name": name,
                     "interval_seconds": interval,
-                    "next_run": next_run.to_rfc3339()
+                    "next_run": next_run.to_rfc3339(),
+                    "depends_on": job.depends_on
                 })
                 .to_string(),
             )
@@ -311,7 +357,8 @@ pub async fn list_jobs(state: &ToolState, arguments: &HashMap<String, Value>) ->
                 "status": format!("{:?}", item.value.status).to_lowercase(),
                 "next_run": item.value.next_run.map(|dt| dt.to_rfc3339()),
                 "last_run": item.value.last_run.map(|dt| dt.to_rfc3339()),
-                "failure_count": item.value.failure_count
+                "failure_count": item.value.failure_count,
+                "depends_on": item.value.depends_on
             })
         })
         .collect();
@@ -373,6 +420,7 @@ pub async fn get_job(state: &ToolState, arguments: &HashMap<String, Value>) -> C
                     "next_run": job.next_run.map(|dt| dt.to_rfc3339()),
                     "last_run": job.last_run.map(|dt| dt.to_rfc3339()),
                     "failure_count": job.failure_count,
+                    "depends_on": job.depends_on,
                     "created_at": job.created_at.to_rfc3339()
                 })
                 .to_string(),
@@ -381,3 +429,94 @@ pub async fn get_job(state: &ToolState, arguments: &HashMap<String, Value>) -> C
         Err(e) => CallToolResult::error(format!("Failed to get job: {}", e)),
     }
 }
+
+pub async fn run_job_now(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let mut job = match state.atproto.get_record::<Job>(JOB_COLLECTION, rkey).await {
+        Ok(record) => record.value,
+        Err(e) => return CallToolResult::error(format!("Failed to get job: {}", e)),
+    };
+
+    // The scheduler sees the request on the record and runs the job without
+    // touching next_run
+    let requested_at = Utc::now();
+    job.run_requested_at = Some(requested_at);
+
+    match state.atproto.put_record(JOB_COLLECTION, rkey, &job).await {
+        Ok(response) => {
+            if let Some(cache) = &state.cache {
+                cache.upsert_job(rkey.to_string(), job.clone(), response.cid.clone());
+            }
+            CallToolResult::success(
+                json!({
+                    "rkey": rkey,
+                    "name": job.name,
+                    "requested_at": requested_at.to_rfc3339(),
+                    "next_run": job.next_run.map(|dt| dt.to_rfc3339())
+                })
+                .to_string(),
+            )
+        }
+        Err(e) => CallToolResult::error(format!("Failed to request job run: {}", e)),
+    }
+}
+
+/// Read the optional `depends_on` argument for a new job with record key `rkey`.
+///
+/// Every dependency must be an existing job, and the dependencies must not
+/// form a cycle.
+async fn parse_depends_on(
+    state: &ToolState,
+    rkey: &str,
+    arguments: &HashMap<String, Value>,
+) -> Result<Vec<String>, String> {
+    let depends_on: Vec<String> = match arguments.get("depends_on") {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .ok_or_else(|| "depends_on must be an array of job rkeys".to_string())
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("depends_on must be an array of job rkeys".to_string()),
+    };
+    if depends_on.is_empty() {
+        return Ok(depends_on);
+    }
+
+    let jobs: HashMap<String, Vec<String>> = match &state.cache {
+        Some(cache) if cache.state() == winter_atproto::SyncState::Live => cache
+            .list_jobs()
+            .into_iter()
+            .map(|(rkey, cached)| (rkey, cached.value.depends_on))
+            .collect(),
+        _ => state
+            .atproto
+            .list_all_records::<Job>(JOB_COLLECTION)
+            .await
+            .map_err(|e| format!("Failed to list jobs: {}", e))?
+            .into_iter()
+            .map(|item| {
+                let rkey = item.uri.split('/').next_back().unwrap_or("").to_string();
+                (rkey, item.value.depends_on)
+            })
+            .collect(),
+    };
+
+    if let Some(missing) = depends_on.iter().find(|dep| !jobs.contains_key(*dep)) {
+        return Err(format!("Unknown job in depends_on: {}", missing));
+    }
+    if let Some(cycle) =
+        find_job_dependency_cycle(rkey, &depends_on, |other| jobs.get(other).cloned())
+    {
+        return Err(format!("Dependency cycle: {}", cycle.join(" -> ")));
+    }
+
+    Ok(depends_on)
+}
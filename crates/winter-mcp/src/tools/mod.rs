@@ -107,8 +107,8 @@
 //! // Add the module declaration at the top
 //! mod my_tools;
 //!
-//! // In ToolRegistry::all_tools(), add:
-//! tools.extend(my_tools::tools());
+//! // In ToolRegistry::tool_categories(), add:
+//! ("my_tools", my_tools::tools()),
 //!
 //! // In ToolRegistry::execute(), add the dispatch:
 //! "my_tool" => my_tools::my_tool(&state, arguments).await,
@@ -195,37 +195,53 @@
 
 mod blog;
 mod bluesky;
+mod changes;
+mod coerce;
 mod custom_tools;
 mod declarations;
 mod directives;
-mod enrich;
+mod dry_run;
+pub mod enrich;
 mod facts;
+mod idempotency;
 mod identity;
 pub mod inbox;
 mod jobs;
 mod notes;
 mod pds;
 pub mod permissions;
+mod result_cache;
 mod rules;
 mod thoughts;
-mod triggers;
+pub mod tombstones;
+pub mod triggers;
 pub mod wiki;
 
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use serde_json::{Value, json};
-use tokio::sync::{RwLock, mpsc};
-use tracing::warn;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
 
 use crate::bluesky::BlueskyClient;
 use crate::deno::DenoExecutor;
+use crate::metrics::McpMetrics;
 use crate::protocol::{CallToolResult, ToolContent, ToolDefinition};
-use crate::secrets::SecretManager;
-use winter_atproto::{AtprotoClient, RepoCache, Thought, ThoughtKind, Tid};
+use crate::secrets::{FileProvider, SecretManager, SecretProvider};
+use crate::thought_queue::{
+    MAX_THOUGHT_BATCH_SIZE, ThoughtQueueConfig, ThoughtReceiver, ThoughtSender, thought_channel,
+};
+use crate::trace::{current_trace_id, trace_tag};
+use winter_atproto::{
+    AtprotoClient, RepoCache, Thought, ThoughtContentType, ThoughtKind, Tid, WriteOp, WriteResult,
+};
 use winter_datalog::DatalogCache;
 
 
@@ -327,17 +343,29 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: Some("facts"),
         },
         "update_fact" => SingleMutation {
-            key_fields: &["rkey", "predicate", "supersedes_rkey"],
+            key_fields: &["rkey", "predicate", "supersedes_rkey", "changes"],
             web_path: Some("facts"),
         },
         "delete_fact" => SingleMutation {
-            key_fields: &["deleted", "rkey"],
+            key_fields: &["deleted", "rkey", "permanent"],
             web_path: None,
         },
+        "restore_fact" => SingleMutation {
+            key_fields: &["restored", "rkey", "predicate"],
+            web_path: Some("facts"),
+        },
         "create_note" => SingleMutation {
             key_fields: &["rkey", "title"],
             web_path: Some("notes"),
         },
+        "delete_note" => SingleMutation {
+            key_fields: &["deleted", "rkey", "permanent"],
+            web_path: None,
+        },
+        "restore_note" => SingleMutation {
+            key_fields: &["restored", "rkey", "title"],
+            web_path: Some("notes"),
+        },
         "schedule_job" => SingleMutation {
             key_fields: &["rkey", "name", "next_run"],
             web_path: Some("jobs"),
@@ -354,6 +382,10 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             key_fields: &["cancelled", "rkey"],
             web_path: None,
         },
+        "run_job_now" => SingleMutation {
+            key_fields: &["rkey", "name", "requested_at"],
+            web_path: Some("jobs"),
+        },
         "create_directive" => SingleMutation {
             key_fields: &["rkey", "kind"],
             web_path: Some("directives"),
@@ -387,7 +419,7 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: Some("tools"),
         },
         "update_custom_tool" => SingleMutation {
-            key_fields: &["rkey", "name", "version"],
+            key_fields: &["rkey", "name", "version", "changes"],
             web_path: Some("tools"),
         },
         "delete_custom_tool" => SingleMutation {
@@ -399,7 +431,7 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: Some("declarations"),
         },
         "update_fact_declaration" => SingleMutation {
-            key_fields: &["rkey", "predicate"],
+            key_fields: &["rkey", "predicate", "changes"],
             web_path: Some("declarations"),
         },
         "delete_fact_declaration" => SingleMutation {
@@ -411,7 +443,7 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: Some("blog"),
         },
         "update_blog_post" => SingleMutation {
-            key_fields: &["rkey", "title"],
+            key_fields: &["rkey", "title", "changes"],
             web_path: Some("blog"),
         },
         // Wiki tools
@@ -420,7 +452,7 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: Some("wiki"),
         },
         "update_wiki_entry" => SingleMutation {
-            key_fields: &["rkey", "title", "slug"],
+            key_fields: &["rkey", "title", "slug", "changes"],
             web_path: Some("wiki"),
         },
         "delete_wiki_entry" => SingleMutation {
@@ -441,7 +473,7 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             web_path: None,
         },
         "update_trigger" => SingleMutation {
-            key_fields: &["rkey", "name"],
+            key_fields: &["rkey", "name", "changes"],
             web_path: None,
         },
         "delete_trigger" => SingleMutation {
@@ -463,6 +495,15 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             key_fields: &["deleted", "collection", "rkey"],
             web_path: None,
         },
+        "pds_apply_writes" => BatchMutation {
+            count_field: "applied",
+            sample_field: "results",
+            sample_key: "rkey",
+        },
+        "replay_repo" => SingleMutation {
+            key_fields: &["rev", "datalog_regenerated"],
+            web_path: None,
+        },
 
         // === Bluesky Mutations (no web link - external) ===
         "post_to_bluesky" => SingleMutation {
@@ -537,6 +578,16 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             items_field: "directives",
             sample_key: "kind",
         },
+        "get_directive_history" => List {
+            count_field: "count",
+            items_field: "history",
+            sample_key: "rkey",
+        },
+        "detect_directive_conflicts" => List {
+            count_field: "count",
+            items_field: "conflicts",
+            sample_key: "pair",
+        },
         "list_rules" => List {
             count_field: "count",
             items_field: "rules",
@@ -597,12 +648,29 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             items_field: "errors",
             sample_key: "predicate",
         },
+        "predicate_graph" => List {
+            count_field: "node_count",
+            items_field: "nodes",
+            sample_key: "id",
+        },
+        "reconcile_facts" => SingleMutation {
+            key_fields: &["in_sync", "pds_count", "cache_count", "repaired"],
+            web_path: None,
+        },
 
         // === Query ===
         "query_facts" => Query,
+        "query_why_not" => Get {
+            key_fields: &["goal", "present"],
+            size_field: None,
+        },
         "query_and_enrich" => Query,
 
         // === Get Operations ===
+        "get_fact" => Get {
+            key_fields: &["rkey", "predicate"],
+            size_field: None,
+        },
         "get_note" => Get {
             key_fields: &["rkey", "title"],
             size_field: Some("content"),
@@ -641,6 +709,10 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
         "get_notifications" => BlueskyRead(Notifications),
         "search_posts" | "search_users" => BlueskyRead(Search),
         "get_thread_context" => BlueskyRead(Thread),
+        "get_profile" => Get {
+            key_fields: &["handle", "follows_you"],
+            size_field: Some("description"),
+        },
 
         // === Custom Tool Execution ===
         "run_custom_tool" => Custom,
@@ -657,8 +729,8 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
             key_fields: &["context_used_pct", "turn_count"],
             size_field: None,
         },
-        "set_active_context" => Get {
-            key_fields: &["active_context", "status"],
+        "set_active_context" | "push_active_context" | "pop_active_context" => Get {
+            key_fields: &["active_context", "depth"],
             size_field: None,
         },
 
@@ -667,19 +739,93 @@ fn get_tool_category(tool_name: &str) -> ToolResultCategory {
     }
 }
 
-/// Get the web UI base URL from environment, if configured.
-fn get_web_url() -> Option<String> {
-    std::env::var("WINTER_WEB_URL").ok()
-}
-
-/// Generate a web UI link for a record, if WINTER_WEB_URL is set.
-fn make_web_link(web_path: &str, rkey: &str) -> Option<String> {
-    get_web_url().map(|base| format!("{}/{}/{}", base.trim_end_matches('/'), web_path, rkey))
+/// Generate a web UI link for a record, if the web UI's URL is configured.
+fn make_web_link(web_url: Option<&str>, web_path: &str, rkey: &str) -> Option<String> {
+    web_url.map(|base| format!("{}/{}/{}", base.trim_end_matches('/'), web_path, rkey))
 }
 
 /// Maximum batch size for batch create operations.
 pub(crate) const MAX_BATCH_SIZE: usize = 100;
 
+/// Create a batch of records with a single `applyWrites` call.
+///
+/// Every submitted record gets an entry in `results`, starting with the
+/// fields from `describe` (called with the record's index). Created records
+/// add their `uri` and `cid` and are passed to `on_created` (used to update
+/// the cache); any the PDS didn't confirm add an `error` instead. Since
+/// `applyWrites` commits all-or-nothing, a rejected request reports every
+/// record as failed and comes back as `Err`.
+pub(crate) async fn apply_batch_creates<T: serde::Serialize>(
+    state: &ToolState,
+    collection: &str,
+    records: &[(String, T)],
+    describe: impl Fn(usize, &T) -> serde_json::Map<String, Value>,
+    mut on_created: impl FnMut(&str, &T, &str),
+) -> Result<Value, Value> {
+    let writes = records
+        .iter()
+        .map(|(rkey, record)| {
+            serde_json::to_value(record).map(|value| WriteOp::Create {
+                collection: collection.to_string(),
+                rkey: rkey.clone(),
+                value,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    let outcome = match writes {
+        Ok(writes) => state
+            .atproto
+            .apply_writes(writes)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(format!("failed to serialize record: {}", e)),
+    };
+
+    let batch_error = outcome.as_ref().err().cloned();
+    let write_results = outcome.map(|r| r.results).unwrap_or_default();
+
+    let mut created = 0;
+    let results: Vec<Value> = records
+        .iter()
+        .enumerate()
+        .map(|(i, (rkey, record))| {
+            let mut item = serde_json::Map::new();
+            item.insert("rkey".to_string(), json!(rkey));
+            item.extend(describe(i, record));
+            match (&batch_error, write_results.get(i)) {
+                (Some(error), _) => {
+                    item.insert("error".to_string(), json!(error));
+                }
+                (None, Some(WriteResult::Create { uri, cid })) => {
+                    on_created(rkey, record, cid);
+                    created += 1;
+                    item.insert("uri".to_string(), json!(uri));
+                    item.insert("cid".to_string(), json!(cid));
+                }
+                (None, Some(_)) => {
+                    item.insert("error".to_string(), json!("unexpected result type"));
+                }
+                (None, None) => {
+                    item.insert("error".to_string(), json!("no result returned by PDS"));
+                }
+            }
+            Value::Object(item)
+        })
+        .collect();
+
+    let mut body = json!({
+        "created": created,
+        "failed": records.len() - created,
+        "results": results,
+    });
+    if let Some(error) = batch_error {
+        body["error"] = json!(format!("Batch write failed: {}", error));
+        return Err(body);
+    }
+    Ok(body)
+}
+
 /// Truncate a string for summary display (UTF-8 safe).
 pub(crate) fn truncate_for_summary(s: &str, max_chars: usize) -> String {
     let char_count = s.chars().count();
@@ -795,12 +941,12 @@ fn extract_string(result: &Value, field: &str, max_len: Option<usize>) -> Option
 }
 
 /// Summarize a tool result based on its category.
-fn summarize_result(tool_name: &str, result: &Value) -> String {
+fn summarize_result(tool_name: &str, result: &Value, web_url: Option<&str>) -> String {
     match get_tool_category(tool_name) {
         ToolResultCategory::SingleMutation {
             key_fields,
             web_path,
-        } => summarize_single_mutation(result, key_fields, web_path),
+        } => summarize_single_mutation(result, key_fields, web_path, web_url),
         ToolResultCategory::BatchMutation {
             count_field,
             sample_field,
@@ -827,11 +973,16 @@ fn summarize_single_mutation(
     result: &Value,
     key_fields: &[&str],
     web_path: Option<&str>,
+    web_url: Option<&str>,
 ) -> String {
     let mut parts: Vec<String> = Vec::new();
 
     for field in key_fields {
-        if let Some(value) = extract_string(result, field, Some(50)) {
+        if let Some(Value::Object(changed)) = result.get(*field) {
+            // A `changes` diff summarizes as the names of the changed fields
+            let names: Vec<&str> = changed.keys().map(String::as_str).collect();
+            parts.push(format!("{}=[{}]", field, names.join(", ")));
+        } else if let Some(value) = extract_string(result, field, Some(50)) {
             parts.push(format!("{}={}", field, value));
         }
     }
@@ -841,7 +992,7 @@ fn summarize_single_mutation(
     // Add web link if available
     if let Some(path) = web_path
         && let Some(rkey) = extract_string(result, "rkey", None)
-        && let Some(link) = make_web_link(path, &rkey)
+        && let Some(link) = make_web_link(web_url, path, &rkey)
     {
         summary.push_str(&format!("\nView: {}", link));
     }
@@ -1112,8 +1263,9 @@ fn summarize_custom(result: &Value) -> String {
 /// Collection name for thoughts.
 const THOUGHT_COLLECTION: &str = "diy.razorgirl.winter.thought";
 
-/// Bounded channel size for async thought writing.
-const THOUGHT_CHANNEL_SIZE: usize = 100;
+/// Deepest the active context stack may grow, guarding against pushes that
+/// are never popped.
+const MAX_ACTIVE_CONTEXT_DEPTH: usize = 16;
 
 /// State for background session interruption signaling.
 ///
@@ -1161,6 +1313,20 @@ impl InterruptionState {
         let mut guard = self.reason.write().await;
         *guard = None;
     }
+
+    /// Clear the interruption state only if it was set for `reason`.
+    ///
+    /// Returns whether the state was cleared.
+    pub async fn clear_if_reason(&self, reason: &str) -> bool {
+        let mut guard = self.reason.write().await;
+        if self.should_interrupt.load(Ordering::SeqCst) && guard.as_deref() == Some(reason) {
+            self.should_interrupt.store(false, Ordering::SeqCst);
+            *guard = None;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// Live session metrics for observability.
@@ -1189,6 +1355,8 @@ pub struct SessionMetrics {
     pub inbox_items_acknowledged: u64,
     /// When the last tool call was executed (for watchdog staleness checks).
     pub last_tool_call_at: chrono::DateTime<chrono::Utc>,
+    /// Model the session is running on, as reported by the daemon.
+    pub model: Option<String>,
 }
 
 impl Default for SessionMetrics {
@@ -1204,6 +1372,7 @@ impl Default for SessionMetrics {
             tool_error_count: 0,
             inbox_items_acknowledged: 0,
             last_tool_call_at: chrono::Utc::now(),
+            model: None,
         }
     }
 }
@@ -1216,10 +1385,10 @@ pub struct ToolState {
     pub cache: Option<Arc<RepoCache>>,
     /// Datalog query cache for efficient query execution (optional).
     pub datalog_cache: Option<Arc<DatalogCache>>,
-    /// Channel for async thought recording (fire-and-forget).
-    pub thought_tx: Option<mpsc::Sender<Thought>>,
-    /// Secret manager for custom tool secrets (optional).
-    pub secrets: Option<Arc<RwLock<SecretManager>>>,
+    /// Queue for async thought recording (fire-and-forget).
+    pub thought_tx: Option<ThoughtSender>,
+    /// Where custom tools get their secrets (optional).
+    pub secrets: Option<Arc<dyn SecretProvider>>,
     /// Deno executor for custom tool sandboxing (optional).
     pub deno: Option<DenoExecutor>,
     /// Interruption state for background sessions (optional).
@@ -1234,14 +1403,41 @@ pub struct ToolState {
     pub inbox: Option<Arc<inbox::Inbox>>,
     /// Live session metrics (optional, set when persistent session is active).
     pub session_metrics: Option<Arc<RwLock<SessionMetrics>>>,
-    /// Active context tag for thought scoping in persistent sessions.
-    /// Set by Winter via `set_active_context` when working on a specific inbox item.
-    pub active_context: Arc<RwLock<Option<String>>>,
+    /// Stack of active context tags for thought scoping in persistent sessions.
+    /// Pushed by Winter via `push_active_context` when starting work on an inbox
+    /// item, so nested work (a DM mid-job) keeps the outer context. Thoughts are
+    /// tagged with the top of the stack.
+    pub active_context: Arc<RwLock<Vec<String>>>,
+    /// Prometheus metrics registry (optional, set when `/metrics` is enabled).
+    pub metrics: Option<Arc<McpMetrics>>,
+    /// Pluggable enrichment sources for `query_and_enrich`.
+    pub enrichers: enrich::EnricherRegistry,
+    /// Results of custom tools that opted into caching.
+    pub tool_results: Arc<result_cache::ToolResultCache>,
+    /// Deployment settings from the `winter` config.
+    pub settings: ToolSettings,
+}
+
+/// Deployment settings tools read, passed down from the `winter` config.
+#[derive(Debug, Clone, Default)]
+pub struct ToolSettings {
+    /// Base URL of the web UI, linked from tool-call thoughts and operator DMs.
+    pub web_url: Option<String>,
+    /// DID of the operator, whose PDS is checked for custom tool approvals.
+    pub operator_did: Option<String>,
+    /// Workspace directory that custom tools and image uploads may use.
+    pub workspace: Option<PathBuf>,
+    /// Hard cap on `query_facts` results.
+    pub query_max_results: Option<usize>,
 }
 
 /// Registry of available tools.
 pub struct ToolRegistry {
     state: Arc<RwLock<ToolState>>,
+    /// Results of recent mutations, keyed by caller-supplied idempotency key.
+    idempotency: idempotency::IdempotencyStore,
+    /// Background thought writer, taken by [`ToolRegistry::flush_thoughts`].
+    thought_writer: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl ToolRegistry {
@@ -1266,22 +1462,21 @@ impl ToolRegistry {
                 internal_mcp_url: None,
                 inbox: None,
                 session_metrics: None,
-                active_context: Arc::new(RwLock::new(None)),
+                active_context: Arc::new(RwLock::new(Vec::new())),
+                metrics: None,
+                enrichers: enrich::EnricherRegistry::new(),
+                tool_results: Arc::new(result_cache::ToolResultCache::new()),
+                settings: ToolSettings::default(),
             })),
+            idempotency: idempotency::IdempotencyStore::default(),
+            thought_writer: Mutex::new(None),
         }
     }
 
     /// Create a new tool registry.
     pub fn new(atproto: AtprotoClient) -> Self {
         let atproto = Arc::new(atproto);
-
-        // Create thought channel and spawn background writer
-        let (thought_tx, thought_rx) = mpsc::channel(THOUGHT_CHANNEL_SIZE);
-        let writer_client = Arc::clone(&atproto);
-
-        tokio::spawn(async move {
-            thought_writer_loop(writer_client, thought_rx).await;
-        });
+        let (thought_tx, thought_writer) = spawn_thought_writer(&atproto);
 
         Self {
             state: Arc::new(RwLock::new(ToolState {
@@ -1298,22 +1493,21 @@ impl ToolRegistry {
                 internal_mcp_url: None,
                 inbox: None,
                 session_metrics: None,
-                active_context: Arc::new(RwLock::new(None)),
+                active_context: Arc::new(RwLock::new(Vec::new())),
+                metrics: None,
+                enrichers: enrich::EnricherRegistry::new(),
+                tool_results: Arc::new(result_cache::ToolResultCache::new()),
+                settings: ToolSettings::default(),
             })),
+            idempotency: idempotency::IdempotencyStore::default(),
+            thought_writer: Mutex::new(Some(thought_writer)),
         }
     }
 
     /// Create a new tool registry with a cache.
     pub fn with_cache(atproto: AtprotoClient, cache: Arc<RepoCache>) -> Self {
         let atproto = Arc::new(atproto);
-
-        // Create thought channel and spawn background writer
-        let (thought_tx, thought_rx) = mpsc::channel(THOUGHT_CHANNEL_SIZE);
-        let writer_client = Arc::clone(&atproto);
-
-        tokio::spawn(async move {
-            thought_writer_loop(writer_client, thought_rx).await;
-        });
+        let (thought_tx, thought_writer) = spawn_thought_writer(&atproto);
 
         Self {
             state: Arc::new(RwLock::new(ToolState {
@@ -1330,8 +1524,74 @@ impl ToolRegistry {
                 internal_mcp_url: None,
                 inbox: None,
                 session_metrics: None,
-                active_context: Arc::new(RwLock::new(None)),
+                active_context: Arc::new(RwLock::new(Vec::new())),
+                metrics: None,
+                enrichers: enrich::EnricherRegistry::new(),
+                tool_results: Arc::new(result_cache::ToolResultCache::new()),
+                settings: ToolSettings::default(),
             })),
+            idempotency: idempotency::IdempotencyStore::default(),
+            thought_writer: Mutex::new(Some(thought_writer)),
+        }
+    }
+
+    /// Replace the thought queue, so tests can observe recorded thoughts.
+    #[cfg(test)]
+    pub async fn set_thought_tx(&self, thought_tx: ThoughtSender) {
+        let mut guard = self.state.write().await;
+        guard.thought_tx = Some(thought_tx);
+    }
+
+    /// Set the thought queue's capacity and overflow policies.
+    pub async fn set_thought_queue_config(&self, config: ThoughtQueueConfig) {
+        let guard = self.state.read().await;
+        if let Some(ref tx) = guard.thought_tx {
+            tx.set_config(config);
+        }
+    }
+
+    /// Stop accepting thoughts and wait up to `timeout` for the background
+    /// writer to write everything already queued.
+    ///
+    /// Returns `false` if the writer was still running when the timeout
+    /// elapsed. Thoughts recorded after this call are discarded.
+    pub async fn flush_thoughts(&self, timeout: Duration) -> bool {
+        if let Some(ref tx) = self.state.read().await.thought_tx {
+            tx.close();
+        }
+        let writer = self
+            .thought_writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(writer) = writer else {
+            return true;
+        };
+        match tokio::time::timeout(timeout, writer).await {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                warn!(error = %e, "thought writer task failed");
+                true
+            }
+            Err(_) => {
+                warn!(
+                    timeout_secs = timeout.as_secs(),
+                    "timed out flushing queued thoughts"
+                );
+                false
+            }
+        }
+    }
+
+    /// Queue a thought for the background writer.
+    ///
+    /// The sender is cloned out first so a blocking overflow policy never
+    /// holds the state lock while it waits.
+    async fn queue_thought(&self, thought: Thought) -> bool {
+        let tx = self.state.read().await.thought_tx.clone();
+        match tx {
+            Some(tx) => tx.send(thought).await,
+            None => false,
         }
     }
 
@@ -1367,10 +1627,27 @@ impl ToolRegistry {
         guard.bluesky = Some(client);
     }
 
+    /// Register an enricher for `query_and_enrich`.
+    pub async fn register_enricher(&self, enricher: Arc<dyn enrich::Enricher>) {
+        let mut guard = self.state.write().await;
+        guard.enrichers.register(enricher);
+    }
+
     /// Set the secret manager for custom tools.
     pub async fn set_secrets(&self, secrets: SecretManager) {
+        self.set_secret_provider(Arc::new(FileProvider::new(secrets))).await;
+    }
+
+    /// Set where custom tools get their secrets.
+    pub async fn set_secret_provider(&self, provider: Arc<dyn SecretProvider>) {
+        let mut guard = self.state.write().await;
+        guard.secrets = Some(provider);
+    }
+
+    /// Set the deployment settings tools read.
+    pub async fn set_settings(&self, settings: ToolSettings) {
         let mut guard = self.state.write().await;
-        guard.secrets = Some(Arc::new(RwLock::new(secrets)));
+        guard.settings = settings;
     }
 
     /// Set the Deno executor for custom tools.
@@ -1411,6 +1688,27 @@ impl ToolRegistry {
         guard.session_metrics = Some(metrics);
     }
 
+    /// Set the Prometheus metrics registry.
+    pub async fn set_metrics(&self, metrics: Arc<McpMetrics>) {
+        let mut guard = self.state.write().await;
+        if let Some(ref tx) = guard.thought_tx {
+            tx.set_metrics(Arc::clone(&metrics));
+        }
+        guard.metrics = Some(metrics);
+    }
+
+    /// Get the repo cache, if configured.
+    pub async fn repo_cache(&self) -> Option<Arc<RepoCache>> {
+        let guard = self.state.read().await;
+        guard.cache.clone()
+    }
+
+    /// Get the datalog cache, if configured.
+    pub async fn datalog_cache(&self) -> Option<Arc<DatalogCache>> {
+        let guard = self.state.read().await;
+        guard.datalog_cache.clone()
+    }
+
     /// Clear session metrics (on session end).
     pub async fn clear_session_metrics(&self) {
         let mut guard = self.state.write().await;
@@ -1419,93 +1717,128 @@ impl ToolRegistry {
 
     /// Get all tool metadata (definitions + permissions).
     fn all_tools() -> Vec<ToolMeta> {
-        let mut tools = Vec::new();
-
-        // Bluesky tools
-        tools.extend(bluesky::tools());
-
-        // Fact tools
-        tools.extend(facts::tools());
-
-        // Enrich tools (query + API enrichment)
-        tools.extend(enrich::tools());
-
-        // Rule tools
-        tools.extend(rules::tools());
-
-        // Note tools
-        tools.extend(notes::tools());
-
-        // Job tools
-        tools.extend(jobs::tools());
-
-        // Identity tools
-        tools.extend(identity::tools());
-
-        // Thought tools
-        tools.extend(thoughts::tools());
-
-        // Blog tools
-        tools.extend(blog::tools());
-
-        // Custom tools
-        tools.extend(custom_tools::tools());
-
-        // Directive tools
-        tools.extend(directives::tools());
-
-        // Fact declaration tools
-        tools.extend(declarations::tools());
+        Self::tool_categories()
+            .into_iter()
+            .flat_map(|(_, tools)| tools)
+            .map(idempotency::advertise_key)
+            .map(dry_run::advertise_flag)
+            .collect()
+    }
 
-        // Trigger tools
-        tools.extend(triggers::tools());
+    /// Built-in tools grouped by category, in catalog order.
+    fn tool_categories() -> Vec<(&'static str, Vec<ToolMeta>)> {
+        vec![
+            ("bluesky", bluesky::tools()),
+            ("facts", facts::tools()),
+            // Query + API enrichment
+            ("enrich", enrich::tools()),
+            ("rules", rules::tools()),
+            ("notes", notes::tools()),
+            ("jobs", jobs::tools()),
+            ("identity", identity::tools()),
+            ("thoughts", thoughts::tools()),
+            ("blog", blog::tools()),
+            ("custom_tools", custom_tools::tools()),
+            ("directives", directives::tools()),
+            ("declarations", declarations::tools()),
+            ("triggers", triggers::tools()),
+            ("wiki", wiki::tools()),
+            // Raw PDS access
+            ("pds", pds::tools()),
+            // Persistent session model
+            ("inbox", inbox::tools()),
+            ("session", Self::session_tools()),
+        ]
+    }
 
-        // Wiki tools
-        tools.extend(wiki::tools());
-
-        // PDS raw access tools
-        tools.extend(pds::tools());
-
-        // Inbox tools (persistent session model)
-        tools.extend(inbox::tools());
-
-        // Session management tools
-        tools.push(ToolMeta::allowed(ToolDefinition {
-            name: "check_interruption".to_string(),
-            description: "Check if this background session should wrap up. Call this periodically during background sessions. Returns whether notifications are waiting and the session should exit gracefully.".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {},
-                "required": []
+    /// Tools for managing the current session.
+    fn session_tools() -> Vec<ToolMeta> {
+        vec![
+            ToolMeta::allowed(ToolDefinition {
+                name: "check_interruption".to_string(),
+                description: "Check if this background session should wrap up. Call this periodically during background sessions. Returns whether notifications are waiting and the session should exit gracefully.".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
             }),
-        }));
-
-        tools.push(ToolMeta::allowed(ToolDefinition {
-            name: "set_active_context".to_string(),
-            description: "Set the active context tag for thought scoping. When working on an inbox item, set this to the item's context_tag so thoughts are associated with it. Pass null or empty string to clear. Thoughts recorded while a context is active will be tagged with it.".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "context": {
-                        "type": ["string", "null"],
-                        "description": "Context tag to set (from inbox item's context_tag field), or null to clear"
-                    }
-                },
-                "required": []
+            ToolMeta::allowed(ToolDefinition {
+                name: "set_active_context".to_string(),
+                description: "Replace the whole active context stack with a single context tag. Pass null or empty string to clear the stack. Prefer push_active_context/pop_active_context when starting work that interrupts another item.".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "context": {
+                            "type": ["string", "null"],
+                            "description": "Context tag to set (from inbox item's context_tag field), or null to clear"
+                        }
+                    },
+                    "required": []
+                }),
+            }),
+            ToolMeta::allowed(ToolDefinition {
+                name: "push_active_context".to_string(),
+                description: "Push a context tag onto the active context stack. When starting work on an inbox item, push its context_tag so thoughts are associated with it. Thoughts are tagged with the top of the stack, so nested work (handling a DM mid-job) doesn't lose the outer context.".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "context": {
+                            "type": "string",
+                            "description": "Context tag to push (from inbox item's context_tag field)"
+                        }
+                    },
+                    "required": ["context"]
+                }),
             }),
-        }));
-
-        tools.push(ToolMeta::allowed(ToolDefinition {
-            name: "session_stats".to_string(),
-            description: "Get live session metrics: token usage, context window percentage, turn count, cost, tool call stats. Use this to monitor session health and decide when to wrap up.".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {},
-                "required": []
+            ToolMeta::allowed(ToolDefinition {
+                name: "pop_active_context".to_string(),
+                description: "Pop the top context tag off the active context stack when finished with an item, restoring the context you were working in before.".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
             }),
-        }));
+            ToolMeta::allowed(ToolDefinition {
+                name: "session_stats".to_string(),
+                description: "Get live session metrics: token usage, context window percentage, turn count, cost, tool call stats. Use this to monitor session health and decide when to wrap up.".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            }),
+        ]
+    }
 
-        tools
+    /// Input schemas of every built-in tool, keyed by tool name.
+    fn input_schemas() -> &'static HashMap<String, Value> {
+        static SCHEMAS: OnceLock<HashMap<String, Value>> = OnceLock::new();
+        SCHEMAS.get_or_init(|| {
+            Self::all_tools()
+                .into_iter()
+                .map(|t| (t.definition.name, t.definition.input_schema))
+                .collect()
+        })
+    }
+
+    /// Error message for a tool that doesn't exist.
+    ///
+    /// Suggests the closest built-in tool name when `name` looks like a typo,
+    /// and lists the tool categories so the caller knows where to look.
+    fn unknown_tool_message(name: &str) -> String {
+        let mut message = format!("Unknown tool: {}.", name);
+        let known = Self::input_schemas().keys().map(String::as_str);
+        if let Some(suggestion) = winter_datalog::suggest(name, known) {
+            message.push_str(&format!(" Did you mean '{}'?", suggestion));
+        }
+        let categories: Vec<&str> = Self::tool_categories()
+            .into_iter()
+            .map(|(category, _)| category)
+            .collect();
+        message.push_str(&format!(" Available categories: {}", categories.join(", ")));
+        message
     }
 
     /// Get all tool definitions (for MCP protocol).
@@ -1527,6 +1860,36 @@ impl ToolRegistry {
             .collect()
     }
 
+    /// Get a machine-readable catalog of every built-in tool.
+    ///
+    /// Each entry carries the tool's name, description and JSON Schema for
+    /// its input, plus whether the autonomous agent may call it and whether
+    /// it's read-only. Custom tools are stored on the PDS and not included.
+    pub fn to_json_schema_catalog() -> Value {
+        let tools: Vec<Value> = Self::all_tools()
+            .into_iter()
+            .map(|t| {
+                json!({
+                    "name": t.definition.name,
+                    "description": t.definition.description,
+                    "agentAllowed": t.agent_allowed,
+                    "readOnly": permissions::is_safe_mcp_tool(&t.definition.name),
+                    "inputSchema": t.definition.input_schema,
+                })
+            })
+            .collect();
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "server": {
+                "name": "winter",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "toolCount": tools.len(),
+            "tools": tools,
+        })
+    }
+
     /// Get the DID of this Winter instance (from the ATProto client).
     pub async fn get_did(&self) -> Option<String> {
         let state = self.state.read().await;
@@ -1665,6 +2028,7 @@ impl ToolRegistry {
                     "remote": true,
                     "source_did": did,
                     "tool_name": tool.name,
+                    "contacted_hosts": output.contacted_hosts,
                 })
                 .to_string(),
             ),
@@ -1692,6 +2056,50 @@ impl ToolRegistry {
     ) -> CallToolResult {
         let start = Instant::now();
 
+        // Fix up stringly-typed arguments before the tool sees them
+        let coerced = match Self::input_schemas().get(name) {
+            Some(schema) => coerce::coerce_arguments(schema, arguments),
+            None => Ok(Cow::Borrowed(arguments)),
+        };
+        let arguments = match coerced {
+            Ok(coerced) => coerced,
+            Err(e) => {
+                let duration_ms = start.elapsed().as_millis() as u64;
+                let result = CallToolResult::error(e);
+                return self
+                    .finalize_result(name, arguments, result, duration_ms, trigger)
+                    .await;
+            }
+        };
+        let arguments = arguments.as_ref();
+
+        // Retries of a mutation with the same key replay the first result.
+        // Dry runs write nothing, so they must not claim the key.
+        let idempotency_key = arguments
+            .get(idempotency::IDEMPOTENCY_KEY_ARG)
+            .and_then(|v| v.as_str())
+            .filter(|key| !key.is_empty() && idempotency::accepts_key(name))
+            .filter(|_| !dry_run::requested(arguments));
+        match idempotency_key {
+            Some(key) => {
+                self.idempotency
+                    .run(name, key, arguments, || {
+                        self.dispatch(name, arguments, trigger, start)
+                    })
+                    .await
+            }
+            None => self.dispatch(name, arguments, trigger, start).await,
+        }
+    }
+
+    /// Run a tool whose arguments have already been coerced, then finalize its result.
+    async fn dispatch(
+        &self,
+        name: &str,
+        arguments: &HashMap<String, Value>,
+        trigger: Option<String>,
+        start: Instant,
+    ) -> CallToolResult {
         // Record a "starting" thought for potentially slow tools
         // This provides immediate feedback that work is happening
         if is_potentially_slow_tool(name) {
@@ -1705,7 +2113,7 @@ impl ToolRegistry {
             let mut state = self.state.write().await;
             match name {
                 "get_notifications" => bluesky::get_notifications(&mut state, arguments).await,
-                _ => CallToolResult::error(format!("Unknown tool: {}", name)),
+                _ => CallToolResult::error(Self::unknown_tool_message(name)),
             }
         } else {
             let state = self.state.read().await;
@@ -1737,6 +2145,7 @@ impl ToolRegistry {
                 "get_timeline" => bluesky::get_timeline(&state, arguments).await,
                 "search_posts" => bluesky::search_posts(&state, arguments).await,
                 "search_users" => bluesky::search_users(&state, arguments).await,
+                "get_profile" => bluesky::get_profile(&state, arguments).await,
                 "get_thread_context" => bluesky::get_thread_context(&state, arguments).await,
                 "mute_user" => bluesky::mute_user(&state, arguments).await,
                 "unmute_user" => bluesky::unmute_user(&state, arguments).await,
@@ -1750,10 +2159,16 @@ impl ToolRegistry {
                 "create_fact" => facts::create_fact(&state, arguments).await,
                 "create_facts" => facts::create_facts(&state, arguments).await,
                 "update_fact" => facts::update_fact(&state, arguments).await,
+                "get_fact" => facts::get_fact(&state, arguments).await,
                 "delete_fact" => facts::delete_fact(&state, arguments).await,
+                "restore_fact" => facts::restore_fact(&state, arguments).await,
                 "query_facts" => facts::query_facts(&state, arguments).await,
+                "query_why_not" => facts::query_why_not(&state, arguments).await,
                 "list_predicates" => facts::list_predicates(&state, arguments).await,
                 "list_validation_errors" => facts::list_validation_errors(&state, arguments).await,
+                "predicate_graph" => facts::predicate_graph(&state, arguments).await,
+                "derived_fact_stats" => facts::derived_fact_stats(&state, arguments).await,
+                "reconcile_facts" => facts::reconcile_facts(&state, arguments).await,
 
                 // Enrich tool
                 "query_and_enrich" => enrich::query_and_enrich(&state, arguments).await,
@@ -1768,6 +2183,8 @@ impl ToolRegistry {
                 "create_note" => notes::create_note(&state, arguments).await,
                 "get_note" => notes::get_note(&state, arguments).await,
                 "list_notes" => notes::list_notes(&state, arguments).await,
+                "delete_note" => notes::delete_note(&state, arguments).await,
+                "restore_note" => notes::restore_note(&state, arguments).await,
 
                 // Job tools
                 "schedule_job" => jobs::schedule_job(&state, arguments).await,
@@ -1775,6 +2192,7 @@ impl ToolRegistry {
                 "list_jobs" => jobs::list_jobs(&state, arguments).await,
                 "cancel_job" => jobs::cancel_job(&state, arguments).await,
                 "get_job" => jobs::get_job(&state, arguments).await,
+                "run_job_now" => jobs::run_job_now(&state, arguments).await,
 
                 // Identity tools
                 "get_identity" => identity::get_identity(&state, arguments).await,
@@ -1814,6 +2232,12 @@ impl ToolRegistry {
                 "update_directive" => directives::update_directive(&state, arguments).await,
                 "deactivate_directive" => directives::deactivate_directive(&state, arguments).await,
                 "list_directives" => directives::list_directives(&state, arguments).await,
+                "get_directive_history" => {
+                    directives::get_directive_history(&state, arguments).await
+                }
+                "detect_directive_conflicts" => {
+                    directives::detect_directive_conflicts(&state, arguments).await
+                }
 
                 // PDS raw access tools
                 "pds_list_records" => pds::pds_list_records(&state, arguments).await,
@@ -1821,6 +2245,8 @@ impl ToolRegistry {
                 "pds_get_records" => pds::pds_get_records(&state, arguments).await,
                 "pds_put_record" => pds::pds_put_record(&state, arguments).await,
                 "pds_delete_record" => pds::pds_delete_record(&state, arguments).await,
+                "pds_apply_writes" => pds::pds_apply_writes(&state, arguments).await,
+                "replay_repo" => pds::replay_repo(&state, arguments).await,
 
                 // Fact declaration tools
                 "create_fact_declaration" => {
@@ -1883,7 +2309,7 @@ impl ToolRegistry {
                     }
                 }
 
-                // Active context tool
+                // Active context tools
                 "set_active_context" => {
                     let context = arguments
                         .get("context")
@@ -1892,28 +2318,67 @@ impl ToolRegistry {
                         .map(String::from);
 
                     let label = context.as_deref().unwrap_or("(cleared)");
-                    *state.active_context.write().await = context.clone();
+                    let mut stack = state.active_context.write().await;
+                    *stack = context.iter().cloned().collect();
 
                     CallToolResult::success(
                         json!({
                             "active_context": context,
+                            "depth": stack.len(),
                             "status": format!("context set to {}", label),
                         })
                         .to_string(),
                     )
                 }
+                "push_active_context" => {
+                    let context = arguments
+                        .get("context")
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty());
+                    let mut stack = state.active_context.write().await;
+
+                    match context {
+                        None => CallToolResult::error("Missing required parameter: context"),
+                        Some(_) if stack.len() >= MAX_ACTIVE_CONTEXT_DEPTH => {
+                            CallToolResult::error(format!(
+                                "Active context stack is full ({} levels); pop a context first",
+                                MAX_ACTIVE_CONTEXT_DEPTH
+                            ))
+                        }
+                        Some(context) => {
+                            stack.push(context.to_string());
+                            CallToolResult::success(
+                                json!({
+                                    "active_context": context,
+                                    "depth": stack.len(),
+                                    "stack": *stack,
+                                })
+                                .to_string(),
+                            )
+                        }
+                    }
+                }
+                "pop_active_context" => {
+                    let mut stack = state.active_context.write().await;
+                    let popped = stack.pop();
+
+                    CallToolResult::success(
+                        json!({
+                            "popped": popped,
+                            "active_context": stack.last(),
+                            "depth": stack.len(),
+                        })
+                        .to_string(),
+                    )
+                }
 
                 // Session stats tool
                 "session_stats" => {
-                    const CONTEXT_WINDOW: u64 = 1_000_000;
                     if let Some(ref metrics) = state.session_metrics {
                         let m = metrics.read().await;
                         let elapsed = (chrono::Utc::now() - m.session_start).num_seconds().max(0) as u64;
-                        let context_used_pct = if CONTEXT_WINDOW > 0 {
-                            (m.total_tokens as f64 / CONTEXT_WINDOW as f64) * 100.0
-                        } else {
-                            0.0
-                        };
+                        let (context_window, context_window_source) =
+                            crate::context_window::context_window_for_model(m.model.as_deref());
                         CallToolResult::success(
                             json!({
                                 "session_start": m.session_start.to_rfc3339(),
@@ -1921,8 +2386,10 @@ impl ToolRegistry {
                                 "total_input_tokens": m.total_input_tokens,
                                 "total_output_tokens": m.total_output_tokens,
                                 "total_tokens": m.total_tokens,
-                                "context_window": CONTEXT_WINDOW,
-                                "context_used_pct": (context_used_pct * 10.0).round() / 10.0,
+                                "model": m.model,
+                                "context_window": context_window,
+                                "context_window_source": context_window_source.as_str(),
+                                "context_used_pct": crate::context_window::context_used_pct(m.total_tokens, context_window),
                                 "turn_count": m.turn_count,
                                 "total_cost_usd": (m.total_cost_usd * 10000.0).round() / 10000.0,
                                 "tool_call_count": m.tool_call_count,
@@ -1942,7 +2409,7 @@ impl ToolRegistry {
                     }
                 }
 
-                _ => CallToolResult::error(format!("Unknown tool: {}", name)),
+                _ => CallToolResult::error(Self::unknown_tool_message(name)),
             }
         };
 
@@ -1969,6 +2436,9 @@ impl ToolRegistry {
         // Update session metrics counters
         {
             let state = self.state.read().await;
+            if let Some(ref metrics) = state.metrics {
+                metrics.record_tool_call(name, result.is_error.unwrap_or(false), duration_ms);
+            }
             if let Some(ref metrics) = state.session_metrics {
                 let mut m = metrics.write().await;
                 m.tool_call_count += 1;
@@ -2017,25 +2487,23 @@ impl ToolRegistry {
                 "status": "starting"
             })
             .to_string(),
+            content_type: Some(ThoughtContentType::ToolCall),
             trigger: trigger.or_else(|| Some("internal:tool_call".to_string())),
-            tags: Vec::new(),
+            tags: current_trace_id().iter().map(|id| trace_tag(id)).collect(),
             duration_ms: None,
             created_at: Utc::now(),
         };
 
-        let state = self.state.read().await;
-
         // Fire and forget - don't block on write
-        if let Some(ref tx) = state.thought_tx
-            && let Err(e) = tx.try_send(thought)
-        {
-            warn!(error = %e, tool = %name, "failed to queue tool_starting thought");
+        if !self.queue_thought(thought).await {
+            debug!(tool = %name, "tool_starting thought not queued");
         }
     }
 
     /// Record a thought about a tool call for transparency.
     ///
-    /// This uses fire-and-forget semantics via a bounded channel.
+    /// This uses fire-and-forget semantics via a bounded queue whose overflow
+    /// policy is set with `set_thought_queue_config`.
     /// The thought is sent asynchronously and written by a background task.
     ///
     /// The trigger parameter allows tool calls to be associated with their
@@ -2052,47 +2520,58 @@ impl ToolRegistry {
         let is_error = result.is_error.unwrap_or(false);
 
         // Format the tool call in structured format for web UI rendering
-        let content = format_tool_call_content(name, arguments, result, is_error);
-
-        let state = self.state.read().await;
+        let web_url = self.state.read().await.settings.web_url.clone();
+        let content =
+            format_tool_call_content(name, arguments, result, is_error, web_url.as_deref());
 
-        // In persistent sessions, use the active context for thought scoping.
-        // This tags thoughts with the specific inbox item being worked on.
-        let thought_trigger = if trigger.as_deref() == Some("persistent") {
-            let active = state.active_context.read().await;
-            active.clone().unwrap_or_else(|| "persistent".to_string())
-        } else {
-            trigger.unwrap_or_else(|| "internal:tool_call".to_string())
-        };
+        let thought_trigger = self.tool_call_trigger(trigger).await;
 
         let thought = Thought {
             kind: ThoughtKind::ToolCall,
             content,
+            content_type: Some(ThoughtContentType::ToolCall),
             trigger: Some(thought_trigger),
-            tags: Vec::new(),
+            tags: current_trace_id().iter().map(|id| trace_tag(id)).collect(),
             duration_ms: Some(duration_ms),
             created_at: Utc::now(),
         };
 
         // Fire and forget - don't block on write
-        if let Some(ref tx) = state.thought_tx
-            && let Err(e) = tx.try_send(thought)
-        {
-            warn!(error = %e, tool = %name, "failed to queue tool_call thought");
+        if !self.queue_thought(thought).await {
+            debug!(tool = %name, "tool_call thought not queued");
+        }
+    }
+
+    /// The trigger to tag a tool-call thought with.
+    ///
+    /// In persistent sessions this is the active context, which scopes the
+    /// thought to the specific inbox item being worked on.
+    async fn tool_call_trigger(&self, trigger: Option<String>) -> String {
+        if trigger.as_deref() == Some("persistent") {
+            let active_context = self.state.read().await.active_context.clone();
+            let stack = active_context.read().await;
+            stack
+                .last()
+                .cloned()
+                .unwrap_or_else(|| "persistent".to_string())
+        } else {
+            trigger.unwrap_or_else(|| "internal:tool_call".to_string())
         }
     }
 
     /// Record a built-in Claude tool call as a Thought.
     ///
-    /// This is called via HTTP from the agent after each Claude invocation to log
-    /// built-in tool usage (WebSearch, Read, WebFetch, etc.) as Thought records.
-    /// Unlike MCP tools which have results available, built-in tools execute inside
-    /// Claude's process so we only have the input arguments.
+    /// This is called via HTTP from the agent to log built-in tool usage
+    /// (WebSearch, Read, WebFetch, etc.) as Thought records. Built-in tools
+    /// execute inside Claude's process, so we only have the input arguments
+    /// and, when the agent saw both the `tool_use` and `tool_result` events,
+    /// an approximate duration.
     pub async fn record_builtin_tool_call(
         &self,
         name: &str,
         tool_id: &str,
         input: &Value,
+        duration_ms: Option<u64>,
         trigger: Option<String>,
     ) {
         // Format as structured JSON matching the MCP tool call format
@@ -2103,25 +2582,42 @@ impl ToolRegistry {
             "claude_id": tool_id,
         });
 
-        let thought_trigger = trigger.unwrap_or_else(|| "internal:tool_call".to_string());
+        let thought_trigger = self.tool_call_trigger(trigger).await;
 
         let thought = Thought {
             kind: ThoughtKind::ToolCall,
             content: serde_json::to_string(&content)
                 .unwrap_or_else(|_| format!("{{\"tool\":\"{}\"}}", name)),
+            content_type: Some(ThoughtContentType::ToolCall),
             trigger: Some(thought_trigger),
             tags: vec!["builtin".to_string()],
-            duration_ms: None, // We don't have timing info for built-in tools
+            duration_ms,
             created_at: Utc::now(),
         };
 
-        let state = self.state.read().await;
-
         // Fire and forget - don't block on write
-        if let Some(ref tx) = state.thought_tx
-            && let Err(e) = tx.try_send(thought)
-        {
-            warn!(error = %e, tool = %name, "failed to queue builtin tool_call thought");
+        if !self.queue_thought(thought).await {
+            debug!(tool = %name, "builtin tool_call thought not queued");
+        }
+    }
+
+    /// Record a block of Claude's extended thinking as a reflection thought.
+    ///
+    /// Called via HTTP from the agent, which has already applied any
+    /// redaction, when thinking capture is enabled.
+    pub async fn record_thinking(&self, content: &str, trigger: Option<String>) {
+        let thought = Thought {
+            kind: ThoughtKind::Reflection,
+            content: content.to_string(),
+            content_type: Some(ThoughtContentType::Text),
+            trigger: Some(trigger.unwrap_or_else(|| "internal:thinking".to_string())),
+            tags: vec!["thinking".to_string()],
+            duration_ms: None,
+            created_at: Utc::now(),
+        };
+
+        if !self.queue_thought(thought).await {
+            debug!("thinking thought not queued");
         }
     }
 }
@@ -2155,7 +2651,9 @@ fn is_potentially_slow_tool(name: &str) -> bool {
     matches!(
         name,
         // Datalog queries can be slow, especially on first run
-        "query_facts" | "list_validation_errors" | "list_predicates"
+        "query_facts" | "query_why_not" | "list_validation_errors" | "list_predicates"
+        // Full repo listings and downloads
+        | "reconcile_facts" | "replay_repo"
         // Network calls to resolve handles/DIDs
         | "resolve_handle" | "resolve_did" | "get_profile"
         // Bluesky API calls that may be slow
@@ -2171,6 +2669,7 @@ fn format_tool_call_content(
     arguments: &HashMap<String, Value>,
     result: &CallToolResult,
     is_error: bool,
+    web_url: Option<&str>,
 ) -> String {
     let args = if arguments.is_empty() {
         None
@@ -2189,21 +2688,21 @@ fn format_tool_call_content(
                 let (result_for_thought, summary, link) = match &category {
                     ToolResultCategory::Excluded => (None, None, None),
                     ToolResultCategory::SingleMutation { web_path, .. } => {
-                        let sum = summarize_result(name, &json);
+                        let sum = summarize_result(name, &json, web_url);
                         let link = web_path.and_then(|path| {
                             extract_string(&json, "rkey", None)
-                                .and_then(|rkey| make_web_link(path, &rkey))
+                                .and_then(|rkey| make_web_link(web_url, path, &rkey))
                         });
                         // SingleMutation results are small, keep them
                         (Some(json), Some(sum).filter(|s| !s.is_empty()), link)
                     }
                     ToolResultCategory::BatchMutation { .. } => {
-                        let sum = summarize_result(name, &json);
+                        let sum = summarize_result(name, &json, web_url);
                         // Batch results can be large, omit full result
                         (None, Some(sum).filter(|s| !s.is_empty()), None)
                     }
                     _ => {
-                        let sum = summarize_result(name, &json);
+                        let sum = summarize_result(name, &json, web_url);
                         // List/Query/Get/BlueskyRead results can be very large
                         // (e.g. list_wiki_entries with full content fields).
                         // Omit the full result from thoughts — the summary
@@ -2235,26 +2734,77 @@ fn format_tool_call_content(
     serde_json::to_string(&content).unwrap_or_else(|_| format!("{{\"tool\":\"{}\"}}", name))
 }
 
-/// Maximum byte size for thought content to avoid PayloadTooLargeError.
-/// ATProto records have size limits; 32KB is a safe limit for thought content.
-const MAX_THOUGHT_CONTENT_BYTES: usize = 32_000;
+/// Create the thought queue and spawn its background writer.
+fn spawn_thought_writer(atproto: &Arc<AtprotoClient>) -> (ThoughtSender, JoinHandle<()>) {
+    let (thought_tx, thought_rx) = thought_channel(ThoughtQueueConfig::default());
+    let writer_client = Arc::clone(atproto);
+    let writer = tokio::spawn(async move {
+        thought_writer_loop(writer_client, thought_rx).await;
+    });
+    (thought_tx, writer)
+}
 
 /// Background task that writes thoughts to the PDS.
-async fn thought_writer_loop(client: Arc<AtprotoClient>, mut rx: mpsc::Receiver<Thought>) {
-    while let Some(mut thought) = rx.recv().await {
-        // Truncate content if too large to avoid PayloadTooLargeError
-        if thought.content.len() > MAX_THOUGHT_CONTENT_BYTES {
-            // Find a safe UTF-8 boundary for truncation
-            let mut end = MAX_THOUGHT_CONTENT_BYTES;
-            while end > 0 && !thought.content.is_char_boundary(end) {
-                end -= 1;
+///
+/// With a batch size above one, thoughts that arrive within the batch window
+/// are written together in a single `applyWrites` call.
+async fn thought_writer_loop(client: Arc<AtprotoClient>, mut rx: ThoughtReceiver) {
+    while let Some(first) = rx.recv().await {
+        let config = rx.config();
+        let batch_size = config.batch_size.clamp(1, MAX_THOUGHT_BATCH_SIZE);
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + config.batch_window;
+        while batch.len() < batch_size {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(thought)) => batch.push(thought),
+                // Window elapsed, or the queue closed with this batch pending.
+                Ok(None) | Err(_) => break,
+            }
+        }
+        write_thoughts(&client, batch, config.max_content_bytes).await;
+    }
+}
+
+/// Write thoughts in order, as one `applyWrites` batch when there are several.
+///
+/// `applyWrites` is all-or-nothing, so if the batch is rejected each thought
+/// is retried on its own with the same rkey. One bad record then only loses
+/// itself, and a batch that was committed despite the error isn't duplicated.
+async fn write_thoughts(client: &AtprotoClient, thoughts: Vec<Thought>, max_content_bytes: usize) {
+    let records: Vec<(String, Thought)> = thoughts
+        .into_iter()
+        .map(|mut thought| {
+            truncate_thought_content(&mut thought, max_content_bytes);
+            (Tid::now().to_string(), thought)
+        })
+        .collect();
+
+    if records.len() > 1 {
+        let writes = records
+            .iter()
+            .filter_map(|(rkey, thought)| {
+                Some(WriteOp::Create {
+                    collection: THOUGHT_COLLECTION.to_string(),
+                    rkey: rkey.clone(),
+                    value: serde_json::to_value(thought).ok()?,
+                })
+            })
+            .collect();
+        match client.apply_writes(writes).await {
+            Ok(_) => return,
+            Err(e) => {
+                warn!(
+                    error = %e,
+                    count = records.len(),
+                    "thought batch failed, writing individually"
+                );
             }
-            thought.content = format!("{}...[truncated]", &thought.content[..end]);
         }
+    }
 
-        let rkey = Tid::now().to_string();
+    for (rkey, thought) in &records {
         if let Err(e) = client
-            .create_record(THOUGHT_COLLECTION, Some(&rkey), &thought)
+            .create_record(THOUGHT_COLLECTION, Some(rkey), thought)
             .await
         {
             warn!(error = %e, "failed to write thought");
@@ -2262,38 +2812,152 @@ async fn thought_writer_loop(client: Arc<AtprotoClient>, mut rx: mpsc::Receiver<
     }
 }
 
-/// Truncate a string to a maximum number of characters (not bytes).
-/// Safe for UTF-8 strings with multi-byte characters.
-#[cfg(test)]
-fn truncate_chars(s: &str, max_chars: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_chars {
-        s.to_string()
-    } else {
-        format!("{}...", s.chars().take(max_chars).collect::<String>())
-    }
-}
+/// Marker appended to content cut short by [`truncate_thought_content`].
+const TRUNCATION_MARKER: &str = "...[truncated]";
 
-/// Format tool arguments into a concise summary string.
-#[cfg(test)]
-fn format_arguments_summary(arguments: &HashMap<String, Value>) -> String {
-    if arguments.is_empty() {
-        return String::new();
+/// Upper bound on shrink passes before giving up on preserving JSON structure.
+const MAX_JSON_SHRINK_PASSES: usize = 64;
+
+/// Truncate content to at most `max_bytes` to avoid PayloadTooLargeError.
+///
+/// JSON content (such as tool-call thoughts) stays valid JSON so the web UI
+/// can still render it: the largest strings are shortened and the largest
+/// arrays lose trailing elements until the document fits, and objects gain
+/// a `"truncated": true` field. Other content is cut at a UTF-8 boundary.
+fn truncate_thought_content(thought: &mut Thought, max_bytes: usize) {
+    if thought.content.len() <= max_bytes {
+        return;
     }
 
-    let parts: Vec<String> = arguments
-        .iter()
-        .map(|(k, v)| {
-            let value_str = format_value_summary(v);
-            format!("{}={}", k, value_str)
-        })
-        .collect();
+    if let Ok(mut value) = serde_json::from_str::<Value>(&thought.content) {
+        if let Some(truncated) = truncate_json(&mut value, max_bytes) {
+            thought.content = truncated;
+            return;
+        }
+    }
 
-    parts.join(", ")
+    let mut end = max_bytes.saturating_sub(TRUNCATION_MARKER.len());
+    while end > 0 && !thought.content.is_char_boundary(end) {
+        end -= 1;
+    }
+    thought.content = format!("{}{}", &thought.content[..end], TRUNCATION_MARKER);
 }
 
-/// Format a single JSON value into a concise summary.
-#[cfg(test)]
+/// Shrink `value` until it serializes to at most `max_bytes`.
+///
+/// Returns `None` if the structure can't be made small enough.
+fn truncate_json(value: &mut Value, max_bytes: usize) -> Option<String> {
+    if let Value::Object(map) = value {
+        map.insert("truncated".to_string(), Value::Bool(true));
+    }
+
+    for _ in 0..MAX_JSON_SHRINK_PASSES {
+        let serialized = serde_json::to_string(value).ok()?;
+        if serialized.len() <= max_bytes {
+            return Some(serialized);
+        }
+        if !shrink_json(value, serialized.len() - max_bytes) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Remove roughly `excess` bytes from the largest part of `value`.
+///
+/// Returns `false` if nothing could be removed.
+fn shrink_json(value: &mut Value, excess: usize) -> bool {
+    match value {
+        Value::String(s) => {
+            if s.is_empty() {
+                return false;
+            }
+            if s.len() <= TRUNCATION_MARKER.len() + excess {
+                s.clear();
+                return true;
+            }
+            let mut end = s.len().saturating_sub(excess + TRUNCATION_MARKER.len());
+            while end > 0 && !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            s.truncate(end);
+            s.push_str(TRUNCATION_MARKER);
+            true
+        }
+        Value::Array(items) => {
+            let mut removed = 0;
+            while items.len() > 1 && removed < excess {
+                let item = items.pop().expect("array is non-empty");
+                removed += json_size(&item) + 1;
+            }
+            if removed >= excess {
+                return true;
+            }
+            match items.first_mut() {
+                Some(item) if shrink_json(item, excess - removed) => true,
+                Some(_) => {
+                    items.clear();
+                    true
+                }
+                None => removed > 0,
+            }
+        }
+        Value::Object(map) => {
+            let Some(largest) = map
+                .iter()
+                .max_by_key(|(_, v)| json_size(v))
+                .map(|(k, _)| k.clone())
+            else {
+                return false;
+            };
+            let child = map.get_mut(&largest).expect("key was just found");
+            if shrink_json(child, excess) {
+                return true;
+            }
+            map.remove(&largest);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Serialized size of a JSON value in bytes.
+fn json_size(value: &Value) -> usize {
+    serde_json::to_string(value).map_or(0, |s| s.len())
+}
+
+/// Truncate a string to a maximum number of characters (not bytes).
+/// Safe for UTF-8 strings with multi-byte characters.
+#[cfg(test)]
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    let char_count = s.chars().count();
+    if char_count <= max_chars {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(max_chars).collect::<String>())
+    }
+}
+
+/// Format tool arguments into a concise summary string.
+#[cfg(test)]
+fn format_arguments_summary(arguments: &HashMap<String, Value>) -> String {
+    if arguments.is_empty() {
+        return String::new();
+    }
+
+    let parts: Vec<String> = arguments
+        .iter()
+        .map(|(k, v)| {
+            let value_str = format_value_summary(v);
+            format!("{}={}", k, value_str)
+        })
+        .collect();
+
+    parts.join(", ")
+}
+
+/// Format a single JSON value into a concise summary.
+#[cfg(test)]
 fn format_value_summary(v: &Value) -> String {
     match v {
         Value::String(s) => format!("\"{}\"", truncate_chars(s, 50)),
@@ -2530,7 +3194,7 @@ mod tests {
             "rkey": "3abc123",
             "predicate": "interested_in"
         });
-        let summary = summarize_single_mutation(&result, &["rkey", "predicate"], None);
+        let summary = summarize_single_mutation(&result, &["rkey", "predicate"], None, None);
         assert_eq!(summary, "rkey=3abc123, predicate=interested_in");
     }
 
@@ -2539,7 +3203,7 @@ mod tests {
         let result = json!({
             "rkey": "3abc123"
         });
-        let summary = summarize_single_mutation(&result, &["rkey", "predicate"], None);
+        let summary = summarize_single_mutation(&result, &["rkey", "predicate"], None, None);
         assert_eq!(summary, "rkey=3abc123");
     }
 
@@ -2549,10 +3213,23 @@ mod tests {
             "deleted": true,
             "rkey": "3abc123"
         });
-        let summary = summarize_single_mutation(&result, &["deleted", "rkey"], None);
+        let summary = summarize_single_mutation(&result, &["deleted", "rkey"], None, None);
         assert_eq!(summary, "deleted=true, rkey=3abc123");
     }
 
+    #[test]
+    fn summarize_single_mutation_lists_changed_fields() {
+        let result = json!({
+            "rkey": "3abc123",
+            "changes": {
+                "content": { "from": "a", "to": "b" },
+                "priority": { "from": 1, "to": 2 }
+            }
+        });
+        let summary = summarize_single_mutation(&result, &["rkey", "changes"], None, None);
+        assert_eq!(summary, "rkey=3abc123, changes=[content, priority]");
+    }
+
     #[test]
     fn summarize_batch_mutation_basic() {
         let result = json!({
@@ -2744,7 +3421,7 @@ mod tests {
     #[test]
     fn summarize_result_excluded_returns_empty() {
         let result = json!({"kind": "insight", "content": "test"});
-        let summary = summarize_result("record_thought", &result);
+        let summary = summarize_result("record_thought", &result, None);
         assert!(summary.is_empty());
     }
 
@@ -2752,7 +3429,7 @@ mod tests {
     fn summarize_result_unknown_tool_uses_custom() {
         let result = json!({"foo": "bar"});
         // Unknown tools default to Custom category
-        let summary = summarize_result("unknown_tool", &result);
+        let summary = summarize_result("unknown_tool", &result, None);
         // Custom category returns empty if no expected fields
         assert!(summary.is_empty());
     }
@@ -2832,7 +3509,7 @@ mod tests {
             .unwrap(),
         );
 
-        let content = format_tool_call_content("create_fact", &args, &result, false);
+        let content = format_tool_call_content("create_fact", &args, &result, false, None);
         let parsed: Value = serde_json::from_str(&content).expect("should be valid JSON");
 
         assert_eq!(parsed["tool"], "create_fact");
@@ -2848,7 +3525,7 @@ mod tests {
         let error_text = "Detailed error message that should not be truncated";
         let result = CallToolResult::error(error_text);
 
-        let content = format_tool_call_content("create_fact", &args, &result, true);
+        let content = format_tool_call_content("create_fact", &args, &result, true, None);
         let parsed: Value = serde_json::from_str(&content).expect("should be valid JSON");
 
         assert_eq!(parsed["tool"], "create_fact");
@@ -2868,7 +3545,7 @@ mod tests {
             .unwrap(),
         );
 
-        let content = format_tool_call_content("record_thought", &args, &result, false);
+        let content = format_tool_call_content("record_thought", &args, &result, false, None);
         let parsed: Value = serde_json::from_str(&content).expect("should be valid JSON");
 
         assert_eq!(parsed["tool"], "record_thought");
@@ -2891,7 +3568,7 @@ mod tests {
             .unwrap(),
         );
 
-        let content = format_tool_call_content("create_fact", &args, &result, false);
+        let content = format_tool_call_content("create_fact", &args, &result, false, None);
         let parsed: Value = serde_json::from_str(&content).expect("should be valid JSON");
 
         assert_eq!(parsed["args"]["predicate"], "test");
@@ -2909,7 +3586,7 @@ mod tests {
             .unwrap(),
         );
 
-        let content = format_tool_call_content("list_notes", &args, &result, false);
+        let content = format_tool_call_content("list_notes", &args, &result, false, None);
         let parsed: Value = serde_json::from_str(&content).expect("should be valid JSON");
 
         assert!(parsed.get("args").is_none());
@@ -2976,6 +3653,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn json_schema_catalog_contains_every_tool() {
+        let catalog = ToolRegistry::to_json_schema_catalog();
+        let entries = catalog["tools"].as_array().expect("tools array");
+        let all = ToolRegistry::all_tools();
+
+        assert_eq!(entries.len(), all.len());
+        assert_eq!(catalog["toolCount"], all.len());
+
+        for tool in &all {
+            let entry = entries
+                .iter()
+                .find(|e| e["name"] == tool.definition.name)
+                .unwrap_or_else(|| panic!("catalog is missing '{}'", tool.definition.name));
+
+            assert!(!entry["description"].as_str().unwrap_or("").is_empty());
+            assert_eq!(entry["agentAllowed"], tool.agent_allowed);
+
+            let schema = entry["inputSchema"]
+                .as_object()
+                .unwrap_or_else(|| panic!("'{}' has no input schema", tool.definition.name));
+            assert!(
+                !schema.is_empty(),
+                "'{}' has an empty schema",
+                tool.definition.name
+            );
+            assert_eq!(schema["type"], "object", "'{}'", tool.definition.name);
+        }
+
+        let query = entries.iter().find(|e| e["name"] == "query_facts").unwrap();
+        assert_eq!(query["readOnly"], true);
+        let create = entries.iter().find(|e| e["name"] == "create_fact").unwrap();
+        assert_eq!(create["readOnly"], false);
+    }
+
     #[test]
     fn definitions_and_all_tools_count_match() {
         let all_tools = ToolRegistry::all_tools();
@@ -2997,4 +3709,1510 @@ mod tests {
             tool_count
         );
     }
+
+    // Helpers for tests against a mock PDS
+
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn logged_in_client(server: &MockServer) -> Arc<AtprotoClient> {
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.createSession"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "did": "did:plc:winter",
+                "handle": "winter.test",
+                "accessJwt": "access",
+                "refreshJwt": "refresh"
+            })))
+            .mount(server)
+            .await;
+        let client = AtprotoClient::new(server.uri());
+        client.login("winter.test", "password").await.unwrap();
+        Arc::new(client)
+    }
+
+    fn request_json(request: &wiremock::Request) -> Value {
+        serde_json::from_slice(&request.body).unwrap()
+    }
+
+    /// A registry whose tools talk to the given mock PDS.
+    async fn registry_for(server: &MockServer) -> ToolRegistry {
+        let registry = ToolRegistry::empty();
+        registry.state.write().await.atproto = logged_in_client(server).await;
+        registry
+    }
+
+    mod unknown_tools {
+        use super::*;
+
+        async fn error_text(name: &str) -> String {
+            let registry = ToolRegistry::empty();
+            let result = registry.execute(name, &HashMap::new()).await;
+            assert_eq!(result.is_error, Some(true));
+            let ToolContent::Text { text } = &result.content[0];
+            text.clone()
+        }
+
+        #[tokio::test]
+        async fn near_miss_suggests_closest_tool() {
+            let text = error_text("qurey_facts").await;
+            assert!(
+                text.starts_with("Unknown tool: qurey_facts. Did you mean 'query_facts'?"),
+                "{}",
+                text
+            );
+        }
+
+        #[tokio::test]
+        async fn unrelated_name_gets_no_suggestion() {
+            let text = error_text("make_coffee").await;
+            assert!(!text.contains("Did you mean"), "{}", text);
+            assert!(
+                text.contains("Available categories: bluesky, facts"),
+                "{}",
+                text
+            );
+        }
+
+        #[test]
+        fn every_tool_belongs_to_a_named_category() {
+            let categories = ToolRegistry::tool_categories();
+            let grouped: usize = categories.iter().map(|(_, tools)| tools.len()).sum();
+            assert_eq!(grouped, ToolRegistry::all_tools().len());
+            assert!(categories.iter().all(|(_, tools)| !tools.is_empty()));
+        }
+    }
+
+    mod idempotency_keys {
+        use super::*;
+
+        fn note_args(key: Option<&str>) -> HashMap<String, Value> {
+            let mut args = HashMap::new();
+            args.insert("title".to_string(), json!("Retry me"));
+            args.insert("content".to_string(), json!("once only"));
+            if let Some(key) = key {
+                args.insert("idempotency_key".to_string(), json!(key));
+            }
+            args
+        }
+
+        async fn mount_create_record(server: &MockServer, times: u64) {
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:winter/diy.razorgirl.winter.note/x",
+                    "cid": "bafyx"
+                })))
+                .expect(times)
+                .mount(server)
+                .await;
+        }
+
+        fn text(result: &CallToolResult) -> &str {
+            let ToolContent::Text { text } = &result.content[0];
+            text
+        }
+
+        #[tokio::test]
+        async fn same_key_creates_one_record() {
+            let server = MockServer::start().await;
+            mount_create_record(&server, 1).await;
+            let registry = registry_for(&server).await;
+
+            let args = note_args(Some("k1"));
+            let first = registry.execute("create_note", &args).await;
+            let retry = registry.execute("create_note", &args).await;
+
+            assert_eq!(first.is_error, Some(false));
+            assert_eq!(text(&first), text(&retry));
+        }
+
+        #[tokio::test]
+        async fn calls_without_a_key_are_not_deduplicated() {
+            let server = MockServer::start().await;
+            mount_create_record(&server, 2).await;
+            let registry = registry_for(&server).await;
+
+            registry.execute("create_note", &note_args(None)).await;
+            registry.execute("create_note", &note_args(None)).await;
+        }
+    }
+
+    mod dry_runs {
+        use super::*;
+
+        /// A mock PDS that fails the test if anything is written to it.
+        async fn read_only_server() -> MockServer {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "records": [] })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(500))
+                .expect(0)
+                .mount(&server)
+                .await;
+            server
+        }
+
+        async fn preview(name: &str, args: Value) -> Value {
+            let server = read_only_server().await;
+            let registry = registry_for(&server).await;
+            let mut args: HashMap<String, Value> = serde_json::from_value(args).unwrap();
+            args.insert("dry_run".to_string(), json!(true));
+
+            let result = registry.execute(name, &args).await;
+
+            let ToolContent::Text { text } = &result.content[0];
+            assert_eq!(result.is_error, Some(false), "{}", text);
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[tokio::test]
+        async fn create_fact_returns_the_would_be_record() {
+            let body = preview(
+                "create_fact",
+                json!({ "predicate": "likes", "args": ["did:plc:alice", "tea"] }),
+            )
+            .await;
+
+            assert_eq!(body["dry_run"], true);
+            assert_eq!(body["collection"], "diy.razorgirl.winter.fact");
+            assert_eq!(body["record"]["predicate"], "likes");
+            assert_eq!(body["record"]["args"], json!(["did:plc:alice", "tea"]));
+        }
+
+        #[tokio::test]
+        async fn create_rule_returns_the_compiled_record() {
+            let body = preview(
+                "create_rule",
+                json!({
+                    "name": "mutual",
+                    "description": "mutual follows",
+                    "head": "mutual(X, Y)",
+                    "body": ["follows(X, Y)", "follows(Y, X)"]
+                }),
+            )
+            .await;
+
+            assert_eq!(body["record"]["head"], "mutual(X, Y)");
+            assert_eq!(body["valid"], true);
+        }
+
+        #[tokio::test]
+        async fn schedule_job_returns_the_would_be_record() {
+            let body = preview(
+                "schedule_job",
+                json!({
+                    "name": "check_in",
+                    "instructions": "say hi",
+                    "run_at": "2030-01-01T00:00:00Z"
+                }),
+            )
+            .await;
+
+            assert_eq!(body["collection"], "diy.razorgirl.winter.job");
+            assert_eq!(body["record"]["name"], "check_in");
+        }
+    }
+
+    mod result_caching {
+        use super::*;
+        use crate::deno::DenoExecutor;
+        use result_cache::ToolResultKey;
+
+        /// A mock PDS holding one cacheable tool, approved by the given
+        /// approval record.
+        async fn server_with_tool(approval_cid: &str) -> MockServer {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "records": [{
+                        "uri": "at://did:plc:winter/diy.razorgirl.winter.tool/3ktool",
+                        "cid": "bafytool",
+                        "value": {
+                            "name": "add",
+                            "description": "adds numbers",
+                            "code": "export default (input) => input.x + 1;",
+                            "inputSchema": {},
+                            "cacheTtlSecs": 60,
+                            "version": 1,
+                            "createdAt": "2026-01-01T00:00:00Z"
+                        }
+                    }]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:winter/diy.razorgirl.winter.toolApproval/3ktool",
+                    "cid": approval_cid,
+                    "value": {
+                        "toolRkey": "3ktool",
+                        "toolVersion": 1,
+                        "status": "approved",
+                        "createdAt": "2026-01-01T00:00:00Z"
+                    }
+                })))
+                .mount(&server)
+                .await;
+            server
+        }
+
+        /// Run the tool after caching a result for it under `bafyapproval`.
+        async fn run_with_cached_result(approval_cid: &str) -> CallToolResult {
+            let server = server_with_tool(approval_cid).await;
+            let registry = registry_for(&server).await;
+            {
+                let mut state = registry.state.write().await;
+                state.deno = Some(DenoExecutor::default());
+                state.tool_results.insert(
+                    ToolResultKey::new("3ktool", 1, Some("bafyapproval"), false, &json!({"x": 1})),
+                    json!(2),
+                    Duration::from_secs(60),
+                );
+            }
+            let args: HashMap<String, Value> =
+                serde_json::from_value(json!({ "name": "add", "input": {"x": 1} })).unwrap();
+
+            registry.execute("run_custom_tool", &args).await
+        }
+
+        #[tokio::test]
+        async fn repeat_run_is_served_from_cache() {
+            let result = run_with_cached_result("bafyapproval").await;
+
+            let ToolContent::Text { text } = &result.content[0];
+            assert_eq!(result.is_error, Some(false), "{}", text);
+            let body: Value = serde_json::from_str(text).unwrap();
+            assert_eq!(body["cached"], true);
+            assert_eq!(body["result"], 2);
+            assert_eq!(body["sandboxed"], false);
+        }
+
+        #[tokio::test]
+        async fn new_approval_is_not_served_the_old_result() {
+            let result = run_with_cached_result("bafyreapproved").await;
+
+            let ToolContent::Text { text } = &result.content[0];
+            assert!(!text.contains("\"cached\":true"), "{}", text);
+        }
+    }
+
+    mod update_changes {
+        use super::*;
+
+        /// A mock PDS holding one existing record that accepts writes.
+        async fn server_with_record(collection: &str, rkey: &str, value: Value) -> MockServer {
+            let server = MockServer::start().await;
+            let uri = format!("at://did:plc:winter/{}/{}", collection, rkey);
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": uri,
+                    "cid": "bafyold",
+                    "value": value
+                })))
+                .mount(&server)
+                .await;
+            for write in ["putRecord", "createRecord"] {
+                Mock::given(method("POST"))
+                    .and(path(format!("/xrpc/com.atproto.repo.{}", write)))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "uri": uri,
+                        "cid": "bafynew"
+                    })))
+                    .mount(&server)
+                    .await;
+            }
+            server
+        }
+
+        async fn update(server: &MockServer, name: &str, args: Value) -> Value {
+            let registry = registry_for(server).await;
+            let args: HashMap<String, Value> = serde_json::from_value(args).unwrap();
+
+            let result = registry.execute(name, &args).await;
+
+            let ToolContent::Text { text } = &result.content[0];
+            assert_eq!(result.is_error, Some(false), "{}", text);
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[tokio::test]
+        async fn update_directive_reports_before_and_after() {
+            let server = server_with_record(
+                "diy.razorgirl.winter.directive",
+                "3kdir",
+                json!({
+                    "kind": "value",
+                    "content": "be kind",
+                    "priority": 2,
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }),
+            )
+            .await;
+
+            let body = update(
+                &server,
+                "update_directive",
+                json!({ "rkey": "3kdir", "content": "be very kind", "priority": 2 }),
+            )
+            .await;
+
+            // Priority was passed but didn't change; lastUpdated is bookkeeping
+            assert_eq!(
+                body["changes"],
+                json!({ "content": { "from": "be kind", "to": "be very kind" } })
+            );
+        }
+
+        #[tokio::test]
+        async fn update_fact_reports_changed_args() {
+            let server = server_with_record(
+                "diy.razorgirl.winter.fact",
+                "3kfact",
+                json!({
+                    "predicate": "likes",
+                    "args": ["did:plc:alice", "tea"],
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }),
+            )
+            .await;
+
+            let body = update(
+                &server,
+                "update_fact",
+                json!({
+                    "rkey": "3kfact",
+                    "predicate": "likes",
+                    "args": ["did:plc:alice", "coffee"]
+                }),
+            )
+            .await;
+
+            assert_eq!(
+                body["changes"],
+                json!({
+                    "args": {
+                        "from": ["did:plc:alice", "tea"],
+                        "to": ["did:plc:alice", "coffee"]
+                    }
+                })
+            );
+        }
+    }
+
+    mod soft_delete {
+        use super::*;
+        use wiremock::matchers::query_param;
+
+        const FACTS: &str = "diy.razorgirl.winter.fact";
+
+        fn fact(deleted_at: Option<chrono::DateTime<Utc>>) -> Value {
+            let mut fact = json!({
+                "predicate": "likes",
+                "args": ["did:plc:alice", "tea"],
+                "createdAt": "2026-01-01T00:00:00Z"
+            });
+            if let Some(at) = deleted_at {
+                fact["deletedAt"] = json!(at.to_rfc3339());
+            }
+            fact
+        }
+
+        /// A mock PDS holding one fact that accepts puts and counts deletes.
+        async fn server_with_fact(value: Value, deletes: u64) -> MockServer {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": format!("at://did:plc:winter/{}/3kfact", FACTS),
+                    "cid": "bafyold",
+                    "value": value
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.putRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": format!("at://did:plc:winter/{}/3kfact", FACTS),
+                    "cid": "bafynew"
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.deleteRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                .expect(deletes)
+                .mount(&server)
+                .await;
+            server
+        }
+
+        async fn call(server: &MockServer, name: &str) -> CallToolResult {
+            let registry = registry_for(server).await;
+            let mut args = HashMap::new();
+            args.insert("rkey".to_string(), json!("3kfact"));
+            registry.execute(name, &args).await
+        }
+
+        async fn written_fact(server: &MockServer) -> Value {
+            let requests = server.received_requests().await.unwrap();
+            let put = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("putRecord"))
+                .expect("fact was written");
+            request_json(put)["record"].clone()
+        }
+
+        #[tokio::test]
+        async fn delete_fact_tombstones_instead_of_deleting() {
+            let server = server_with_fact(fact(None), 0).await;
+
+            let result = call(&server, "delete_fact").await;
+
+            assert_eq!(result.is_error, Some(false));
+            assert!(written_fact(&server).await["deletedAt"].is_string());
+        }
+
+        #[tokio::test]
+        async fn restore_fact_clears_the_tombstone() {
+            let deleted_at = Utc::now() - chrono::Duration::days(1);
+            let server = server_with_fact(fact(Some(deleted_at)), 0).await;
+
+            let result = call(&server, "restore_fact").await;
+
+            assert_eq!(result.is_error, Some(false));
+            assert!(written_fact(&server).await.get("deletedAt").is_none());
+        }
+
+        #[tokio::test]
+        async fn restore_fact_refuses_expired_tombstones() {
+            let deleted_at = Utc::now() - chrono::Duration::days(tombstones::RETENTION_DAYS + 1);
+            let server = server_with_fact(fact(Some(deleted_at)), 0).await;
+
+            let result = call(&server, "restore_fact").await;
+
+            assert_eq!(result.is_error, Some(true));
+        }
+
+        #[tokio::test]
+        async fn purge_deletes_only_expired_tombstones() {
+            let server = server_with_fact(fact(None), 1).await;
+            let now = Utc::now();
+            let listed = |rkey: &str, value: Value| {
+                json!({
+                    "uri": format!("at://did:plc:winter/{}/{}", FACTS, rkey),
+                    "cid": "bafy",
+                    "value": value
+                })
+            };
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .and(query_param("collection", FACTS))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "records": [
+                        listed("old", fact(Some(now - chrono::Duration::days(40)))),
+                        listed("recent", fact(Some(now - chrono::Duration::days(1)))),
+                        listed("live", fact(None))
+                    ]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .and(query_param("collection", "diy.razorgirl.winter.note"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "records": [] })))
+                .mount(&server)
+                .await;
+            let client = logged_in_client(&server).await;
+
+            let report = tombstones::purge_expired(&client, None, tombstones::retention(), now)
+                .await
+                .unwrap();
+
+            assert_eq!(report, tombstones::PurgeReport { facts: 1, notes: 0 });
+        }
+    }
+
+    mod argument_coercion {
+        use super::*;
+
+        async fn registry_with_job() -> ToolRegistry {
+            let registry = ToolRegistry::empty();
+            let inbox = Arc::new(inbox::Inbox::new());
+            inbox
+                .push(inbox::InboxItem::job("nightly".into(), "tidy up".into()))
+                .await;
+            registry.set_inbox(inbox).await;
+            registry
+        }
+
+        fn count(result: &CallToolResult) -> u64 {
+            let ToolContent::Text { text } = &result.content[0];
+            let body: Value = serde_json::from_str(text).unwrap();
+            body["count"].as_u64().unwrap()
+        }
+
+        #[tokio::test]
+        async fn stringified_integer_reaches_the_tool() {
+            let registry = registry_with_job().await;
+            let mut args = HashMap::new();
+
+            // The job has priority 50; a dropped filter would return it
+            args.insert("min_priority".to_string(), json!("60"));
+            let result = registry.execute("check_inbox", &args).await;
+            assert_eq!(result.is_error, Some(false));
+            assert_eq!(count(&result), 0);
+
+            args.insert("min_priority".to_string(), json!("50"));
+            let result = registry.execute("check_inbox", &args).await;
+            assert_eq!(count(&result), 1);
+        }
+
+        #[tokio::test]
+        async fn non_numeric_string_is_rejected_before_dispatch() {
+            let registry = registry_with_job().await;
+            let mut args = HashMap::new();
+            args.insert("min_priority".to_string(), json!("high"));
+
+            let result = registry.execute("check_inbox", &args).await;
+
+            assert_eq!(result.is_error, Some(true));
+            let ToolContent::Text { text } = &result.content[0];
+            assert!(text.contains("'min_priority'"), "{}", text);
+        }
+    }
+
+    // Tests for batched thought writes
+
+    mod thought_batching {
+        use super::*;
+        use crate::thought_queue::OverflowPolicy;
+
+        fn thought(content: &str) -> Thought {
+            Thought {
+                kind: ThoughtKind::ToolCall,
+                content: content.to_string(),
+                content_type: None,
+                trigger: None,
+                tags: vec![],
+                duration_ms: None,
+                created_at: Utc::now(),
+            }
+        }
+
+        /// Queue the thoughts, close the queue and run the writer to completion.
+        async fn write_all(client: Arc<AtprotoClient>, contents: &[&str], batch_size: usize) {
+            let (tx, rx) = thought_channel(ThoughtQueueConfig {
+                policy: OverflowPolicy::DropNewest,
+                batch_size,
+                ..Default::default()
+            });
+            for content in contents {
+                assert!(tx.send(thought(content)).await);
+            }
+            drop(tx);
+            thought_writer_loop(client, rx).await;
+        }
+
+        #[tokio::test]
+        async fn queued_thoughts_become_one_apply_writes_batch() {
+            let server = MockServer::start().await;
+            let client = logged_in_client(&server).await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "commit": { "cid": "bafycommit", "rev": "rev1" },
+                    "results": []
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(500))
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            write_all(client, &["a", "b", "c"], 10).await;
+
+            let requests = server.received_requests().await.unwrap();
+            let batch = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("applyWrites"))
+                .map(request_json)
+                .unwrap();
+            let writes = batch["writes"].as_array().unwrap();
+            let contents: Vec<_> = writes.iter().map(|w| &w["value"]["content"]).collect();
+            assert_eq!(contents, ["a", "b", "c"]);
+            assert!(writes.iter().all(|w| w["collection"] == THOUGHT_COLLECTION));
+            // Rkeys are TIDs, so they preserve queue order.
+            let rkeys: Vec<_> = writes.iter().map(|w| w["rkey"].as_str().unwrap()).collect();
+            assert!(rkeys.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        #[tokio::test]
+        async fn rejected_batch_falls_back_to_individual_writes() {
+            let server = MockServer::start().await;
+            let client = logged_in_client(&server).await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "error": "InvalidRequest",
+                    "message": "record too large"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:winter/diy.razorgirl.winter.thought/x",
+                    "cid": "bafyx"
+                })))
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            write_all(client, &["a", "b"], 10).await;
+
+            let requests = server.received_requests().await.unwrap();
+            let created: Vec<_> = requests
+                .iter()
+                .filter(|r| r.url.path().ends_with("createRecord"))
+                .map(|r| request_json(r)["record"]["content"].clone())
+                .collect();
+            assert_eq!(created, ["a", "b"]);
+        }
+
+        #[tokio::test]
+        async fn batch_size_one_writes_records_individually() {
+            let server = MockServer::start().await;
+            let client = logged_in_client(&server).await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:winter/diy.razorgirl.winter.thought/x",
+                    "cid": "bafyx"
+                })))
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            write_all(client, &["a", "b"], 1).await;
+        }
+
+        #[tokio::test]
+        async fn flush_writes_pending_batch_without_waiting_for_window() {
+            let server = MockServer::start().await;
+            let client = Arc::into_inner(logged_in_client(&server).await).unwrap();
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "commit": { "cid": "bafycommit", "rev": "rev1" },
+                    "results": []
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let registry = ToolRegistry::new(client);
+            registry
+                .set_thought_queue_config(ThoughtQueueConfig {
+                    batch_size: 10,
+                    batch_window: Duration::from_secs(60),
+                    ..Default::default()
+                })
+                .await;
+            assert!(registry.queue_thought(thought("a")).await);
+            assert!(registry.queue_thought(thought("b")).await);
+
+            assert!(registry.flush_thoughts(Duration::from_secs(5)).await);
+            assert!(!registry.queue_thought(thought("late")).await);
+
+            let requests = server.received_requests().await.unwrap();
+            let batch = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("applyWrites"))
+                .map(request_json)
+                .unwrap();
+            let writes = batch["writes"].as_array().unwrap();
+            let contents: Vec<_> = writes.iter().map(|w| &w["value"]["content"]).collect();
+            assert_eq!(contents, ["a", "b"]);
+        }
+    }
+
+    // Tests for applyWrites batch creates
+
+    mod batch_creates {
+        use super::*;
+
+        fn rule_args(names: &[&str]) -> HashMap<String, Value> {
+            let rules: Vec<Value> = names
+                .iter()
+                .map(|name| {
+                    json!({
+                        "name": name,
+                        "description": "test rule",
+                        "head": format!("{}(X)", name),
+                        "body": ["likes(X, Y)"]
+                    })
+                })
+                .collect();
+            HashMap::from([("rules".to_string(), json!(rules))])
+        }
+
+        fn create_result(rkey: &str) -> Value {
+            json!({
+                "$type": "com.atproto.repo.applyWrites#createResult",
+                "uri": format!("at://did:plc:winter/diy.razorgirl.winter.rule/{}", rkey),
+                "cid": format!("bafy{}", rkey)
+            })
+        }
+
+        fn body_of(result: &CallToolResult) -> Value {
+            let ToolContent::Text { text } = &result.content[0];
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[tokio::test]
+        async fn batch_of_creates_issues_one_apply_writes() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "commit": { "cid": "bafycommit", "rev": "rev1" },
+                    "results": [create_result("a"), create_result("b"), create_result("c")]
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let state = registry.state.read().await;
+
+            let result = rules::create_rules(&state, &rule_args(&["one", "two", "three"])).await;
+
+            assert_eq!(result.is_error, Some(false));
+            let body = body_of(&result);
+            assert_eq!(body["created"], 3);
+            assert_eq!(body["failed"], 0);
+            let results = body["results"].as_array().unwrap();
+            assert_eq!(results[1]["name"], "two");
+            assert_eq!(results[1]["cid"], "bafyb");
+
+            let requests = server.received_requests().await.unwrap();
+            let batch = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("applyWrites"))
+                .map(request_json)
+                .unwrap();
+            assert_eq!(batch["writes"].as_array().unwrap().len(), 3);
+        }
+
+        #[tokio::test]
+        async fn missing_results_are_reported_per_item() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "commit": { "cid": "bafycommit", "rev": "rev1" },
+                    "results": [create_result("a")]
+                })))
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let state = registry.state.read().await;
+
+            let result = rules::create_rules(&state, &rule_args(&["one", "two"])).await;
+
+            let body = body_of(&result);
+            assert_eq!(body["created"], 1);
+            assert_eq!(body["failed"], 1);
+            assert_eq!(body["results"][0]["uri"], create_result("a")["uri"]);
+            assert_eq!(body["results"][1]["name"], "two");
+            assert!(body["results"][1]["error"].is_string());
+        }
+
+        #[tokio::test]
+        async fn rejected_batch_fails_every_item() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "error": "InvalidRequest",
+                    "message": "invalid record"
+                })))
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let state = registry.state.read().await;
+
+            let result = rules::create_rules(&state, &rule_args(&["one", "two"])).await;
+
+            assert_eq!(result.is_error, Some(true));
+            let body = body_of(&result);
+            assert!(
+                body["error"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("Batch write failed")
+            );
+            assert_eq!(body["created"], 0);
+            assert_eq!(body["failed"], 2);
+            let results = body["results"].as_array().unwrap();
+            assert!(
+                results
+                    .iter()
+                    .all(|r| r["error"].is_string() && r["uri"].is_null())
+            );
+        }
+    }
+
+    // Tests for pds_apply_writes
+
+    mod apply_writes {
+        use super::*;
+
+        fn body_of(result: &CallToolResult) -> Value {
+            let ToolContent::Text { text } = &result.content[0];
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[tokio::test]
+        async fn mixed_batch_is_sent_as_one_commit() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "commit": { "cid": "bafycommit", "rev": "rev1" },
+                    "results": [
+                        {
+                            "$type": "com.atproto.repo.applyWrites#createResult",
+                            "uri": "at://did:plc:winter/diy.razorgirl.winter.fact/new",
+                            "cid": "bafynew"
+                        },
+                        { "$type": "com.atproto.repo.applyWrites#deleteResult" }
+                    ]
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let state = registry.state.read().await;
+
+            let args = HashMap::from([(
+                "writes".to_string(),
+                json!([
+                    {
+                        "action": "create",
+                        "collection": "diy.razorgirl.winter.fact",
+                        "rkey": "new",
+                        "record": {
+                            "predicate": "likes",
+                            "args": ["a", "b"],
+                            "confidence": 1.0,
+                            "tags": [],
+                            "createdAt": "2026-01-01T00:00:00Z"
+                        }
+                    },
+                    {
+                        "action": "delete",
+                        "collection": "diy.razorgirl.winter.fact",
+                        "rkey": "old"
+                    }
+                ]),
+            )]);
+            let result = pds::pds_apply_writes(&state, &args).await;
+
+            assert_eq!(result.is_error, Some(false));
+            let body = body_of(&result);
+            assert_eq!(body["applied"], 2);
+            assert_eq!(body["commit"]["rev"], "rev1");
+            assert_eq!(body["results"][0]["cid"], "bafynew");
+            assert_eq!(body["results"][1]["action"], "delete");
+            assert_eq!(body["results"][1]["rkey"], "old");
+
+            let requests = server.received_requests().await.unwrap();
+            let batch = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("applyWrites"))
+                .map(request_json)
+                .unwrap();
+            let writes = batch["writes"].as_array().unwrap();
+            assert_eq!(writes.len(), 2);
+            assert_eq!(writes[0]["$type"], "com.atproto.repo.applyWrites#create");
+            assert_eq!(writes[0]["value"]["$type"], "diy.razorgirl.winter.fact");
+            assert_eq!(writes[1]["$type"], "com.atproto.repo.applyWrites#delete");
+        }
+
+        #[tokio::test]
+        async fn disallowed_collection_rejects_whole_batch() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.applyWrites"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let state = registry.state.read().await;
+
+            let args = HashMap::from([(
+                "writes".to_string(),
+                json!([
+                    {
+                        "action": "delete",
+                        "collection": "diy.razorgirl.winter.fact",
+                        "rkey": "old"
+                    },
+                    {
+                        "action": "update",
+                        "collection": "diy.razorgirl.winter.identity",
+                        "rkey": "self",
+                        "record": {}
+                    }
+                ]),
+            )]);
+            let result = pds::pds_apply_writes(&state, &args).await;
+
+            assert_eq!(result.is_error, Some(true));
+            let ToolContent::Text { text } = &result.content[0];
+            assert!(text.contains("writes[1]"), "{}", text);
+            assert!(text.contains("not allowed"), "{}", text);
+        }
+    }
+
+    // Tests for reconcile_facts
+
+    mod reconcile {
+        use super::*;
+
+        fn fact_value() -> Value {
+            json!({
+                "predicate": "likes",
+                "args": ["did:plc:a"],
+                "createdAt": "2026-01-01T00:00:00Z"
+            })
+        }
+
+        fn listed(rkey: &str, cid: &str) -> Value {
+            json!({
+                "uri": format!("at://did:plc:winter/diy.razorgirl.winter.fact/{}", rkey),
+                "cid": cid,
+                "value": fact_value()
+            })
+        }
+
+        /// A registry whose PDS holds facts a, b and c, and whose cache has a,
+        /// an older c, and d, which the PDS no longer has.
+        async fn out_of_sync_registry(server: &MockServer) -> (ToolRegistry, Arc<RepoCache>) {
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "records": [listed("a", "cida"), listed("b", "cidb"), listed("c", "cidc2")]
+                })))
+                .mount(server)
+                .await;
+
+            let cache = RepoCache::new();
+            let fact: winter_atproto::Fact = serde_json::from_value(fact_value()).unwrap();
+            cache.upsert_fact("a".to_string(), fact.clone(), "cida".to_string());
+            cache.upsert_fact("c".to_string(), fact.clone(), "cidc1".to_string());
+            cache.upsert_fact("d".to_string(), fact, "cidd".to_string());
+
+            let registry = registry_for(server).await;
+            registry.state.write().await.cache = Some(Arc::clone(&cache));
+            (registry, cache)
+        }
+
+        fn body_of(result: &CallToolResult) -> Value {
+            let ToolContent::Text { text } = &result.content[0];
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[tokio::test]
+        async fn reports_drift_without_touching_cache() {
+            let server = MockServer::start().await;
+            let (registry, cache) = out_of_sync_registry(&server).await;
+            let state = registry.state.read().await;
+
+            let result = facts::reconcile_facts(&state, &HashMap::new()).await;
+
+            assert_eq!(result.is_error, Some(false));
+            let body = body_of(&result);
+            assert_eq!(body["in_sync"], false);
+            assert_eq!(body["missing_from_cache"], json!(["b"]));
+            assert_eq!(body["outdated_in_cache"], json!(["c"]));
+            assert_eq!(body["extra_in_cache"], json!(["d"]));
+            assert_eq!(body["repaired"], false);
+            assert!(cache.get_fact("d").is_some());
+            assert!(cache.get_fact("b").is_none());
+        }
+
+        #[tokio::test]
+        async fn repair_brings_cache_in_line() {
+            let server = MockServer::start().await;
+            let (registry, cache) = out_of_sync_registry(&server).await;
+            let state = registry.state.read().await;
+
+            let args = HashMap::from([("repair".to_string(), json!(true))]);
+            let result = facts::reconcile_facts(&state, &args).await;
+
+            assert_eq!(body_of(&result)["repaired"], true);
+            assert!(cache.get_fact("b").is_some());
+            assert_eq!(cache.get_fact("c").unwrap().cid, "cidc2");
+            assert!(cache.get_fact("d").is_none());
+            assert_eq!(cache.fact_count(), 3);
+
+            let result = facts::reconcile_facts(&state, &HashMap::new()).await;
+            assert_eq!(body_of(&result)["in_sync"], true);
+        }
+    }
+
+    mod profile {
+        use super::*;
+        use wiremock::matchers::query_param;
+
+        /// A registry with a Bluesky client logged in to the mock server.
+        async fn bluesky_registry(server: &MockServer) -> ToolRegistry {
+            let registry = registry_for(server).await;
+            let client = BlueskyClient::new(&server.uri(), "winter.test", "password")
+                .await
+                .unwrap();
+            registry.state.write().await.bluesky = Some(client);
+            registry
+        }
+
+        fn actor(name: &str) -> HashMap<String, Value> {
+            HashMap::from([("actor".to_string(), json!(name))])
+        }
+
+        #[tokio::test]
+        async fn returns_profile_with_follow_state() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/app.bsky.actor.getProfile"))
+                .and(query_param("actor", "alice.test"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "did": "did:plc:alice",
+                    "handle": "alice.test",
+                    "displayName": "Alice",
+                    "description": "datalog enjoyer",
+                    "followersCount": 42,
+                    "followsCount": 7,
+                    "postsCount": 100,
+                    "viewer": {
+                        "followedBy": "at://did:plc:alice/app.bsky.graph.follow/3k"
+                    }
+                })))
+                .mount(&server)
+                .await;
+            let registry = bluesky_registry(&server).await;
+            let state = registry.state.read().await;
+
+            let result = bluesky::get_profile(&state, &actor("@alice.test")).await;
+
+            assert_eq!(result.is_error, Some(false));
+            let ToolContent::Text { text } = &result.content[0];
+            let body: Value = serde_json::from_str(text).unwrap();
+            assert_eq!(body["did"], "did:plc:alice");
+            assert_eq!(body["display_name"], "Alice");
+            assert_eq!(body["description"], "datalog enjoyer");
+            assert_eq!(body["followers_count"], 42);
+            assert_eq!(body["follows_you"], true);
+            assert_eq!(body["followed_by_you"], false);
+        }
+
+        #[tokio::test]
+        async fn reports_missing_profile() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/app.bsky.actor.getProfile"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "error": "InvalidRequest",
+                    "message": "Profile not found"
+                })))
+                .mount(&server)
+                .await;
+            let registry = bluesky_registry(&server).await;
+            let state = registry.state.read().await;
+
+            let result = bluesky::get_profile(&state, &actor("did:plc:gone")).await;
+
+            assert_eq!(result.is_error, Some(true));
+            let ToolContent::Text { text } = &result.content[0];
+            assert_eq!(text, "Profile not found: did:plc:gone");
+        }
+
+        #[tokio::test]
+        async fn requires_actor() {
+            let registry = ToolRegistry::empty();
+            let state = registry.state.read().await;
+
+            let result = bluesky::get_profile(&state, &HashMap::new()).await;
+
+            assert_eq!(result.is_error, Some(true));
+        }
+    }
+
+    // Tests for thought content truncation
+
+    mod thought_truncation {
+        use super::*;
+
+        fn thought(content: String) -> Thought {
+            Thought {
+                kind: ThoughtKind::ToolCall,
+                content,
+                content_type: None,
+                trigger: None,
+                tags: vec![],
+                duration_ms: None,
+                created_at: Utc::now(),
+            }
+        }
+
+        #[test]
+        fn small_content_is_unchanged() {
+            let mut t = thought(r#"{"tool":"query_facts"}"#.to_string());
+            truncate_thought_content(&mut t, 1000);
+            assert_eq!(t.content, r#"{"tool":"query_facts"}"#);
+        }
+
+        #[test]
+        fn large_json_tool_call_stays_valid_json_within_limit() {
+            let rows: Vec<Value> = (0..2000)
+                .map(|i| json!({ "did": format!("did:plc:{i:020}"), "handle": "a.bsky.social" }))
+                .collect();
+            let content = json!({
+                "tool": "query_facts",
+                "args": { "predicate": "follows" },
+                "result": { "rows": rows, "note": "x".repeat(5000) },
+                "failed": false,
+            })
+            .to_string();
+            assert!(content.len() > 32_000);
+
+            let mut t = thought(content);
+            truncate_thought_content(&mut t, 8_000);
+
+            assert!(t.content.len() <= 8_000, "len {}", t.content.len());
+            let parsed: Value = serde_json::from_str(&t.content).expect("valid JSON");
+            assert_eq!(parsed["tool"], "query_facts");
+            assert_eq!(parsed["args"]["predicate"], "follows");
+            assert_eq!(parsed["truncated"], true);
+            let kept = parsed["result"]["rows"].as_array().unwrap();
+            assert!(!kept.is_empty() && kept.len() < 2000);
+            assert_eq!(kept[0]["did"], format!("did:plc:{:020}", 0));
+        }
+
+        #[test]
+        fn long_json_string_is_cut_with_marker() {
+            let content = json!({ "tool": "fetch", "result": "é".repeat(10_000) }).to_string();
+
+            let mut t = thought(content);
+            truncate_thought_content(&mut t, 1_000);
+
+            assert!(t.content.len() <= 1_000);
+            let parsed: Value = serde_json::from_str(&t.content).unwrap();
+            let result = parsed["result"].as_str().unwrap();
+            assert!(result.ends_with(TRUNCATION_MARKER));
+            assert!(result.starts_with("éé"));
+        }
+
+        #[test]
+        fn plain_text_is_cut_within_limit() {
+            let mut t = thought("日本語".repeat(1000));
+            truncate_thought_content(&mut t, 100);
+
+            assert!(t.content.len() <= 100);
+            assert!(t.content.ends_with(TRUNCATION_MARKER));
+        }
+
+        #[test]
+        fn queue_config_limit_defaults_to_32kb() {
+            let config = ThoughtQueueConfig::default();
+            assert_eq!(config.max_content_bytes, 32_000);
+        }
+    }
+
+    mod builtin_tool_calls {
+        use super::*;
+
+        async fn registry_with_queue() -> (ToolRegistry, ThoughtReceiver) {
+            let registry = ToolRegistry::empty();
+            let (tx, rx) = thought_channel(ThoughtQueueConfig::default());
+            registry.set_thought_tx(tx).await;
+            (registry, rx)
+        }
+
+        #[tokio::test]
+        async fn duration_is_recorded_on_the_thought() {
+            let (registry, mut rx) = registry_with_queue().await;
+
+            registry
+                .record_builtin_tool_call(
+                    "WebSearch",
+                    "toolu_01",
+                    &json!({"query": "rust"}),
+                    Some(1250),
+                    Some("notification:at://x".to_string()),
+                )
+                .await;
+
+            let thought = rx.try_recv().unwrap();
+            assert_eq!(thought.duration_ms, Some(1250));
+            assert_eq!(thought.content_type, Some(ThoughtContentType::ToolCall));
+            assert_eq!(thought.trigger.as_deref(), Some("notification:at://x"));
+            let content: Value = serde_json::from_str(&thought.content).unwrap();
+            assert_eq!(content["tool"], "WebSearch");
+            assert_eq!(content["claude_id"], "toolu_01");
+        }
+
+        #[tokio::test]
+        async fn persistent_trigger_uses_active_context() {
+            let (registry, mut rx) = registry_with_queue().await;
+            let active_context = registry.state.read().await.active_context.clone();
+            active_context.write().await.push("dm:convo1".to_string());
+
+            registry
+                .record_builtin_tool_call(
+                    "Read",
+                    "toolu_02",
+                    &json!({}),
+                    None,
+                    Some("persistent".to_string()),
+                )
+                .await;
+
+            let thought = rx.try_recv().unwrap();
+            assert_eq!(thought.trigger.as_deref(), Some("dm:convo1"));
+            assert_eq!(thought.duration_ms, None);
+        }
+    }
+
+    mod active_context_stack {
+        use super::*;
+
+        fn context_args(context: &str) -> HashMap<String, Value> {
+            HashMap::from([("context".to_string(), json!(context))])
+        }
+
+        fn response(result: &CallToolResult) -> Value {
+            let ToolContent::Text { text } = &result.content[0];
+            serde_json::from_str(text).unwrap()
+        }
+
+        /// The trigger a persistent-session thought would be tagged with now.
+        async fn tagged_trigger(registry: &ToolRegistry, rx: &mut ThoughtReceiver) -> String {
+            // Skip the tool-call thoughts of the context tools themselves
+            while rx.try_recv().is_some() {}
+            registry
+                .record_builtin_tool_call(
+                    "Read",
+                    "toolu",
+                    &json!({}),
+                    None,
+                    Some("persistent".to_string()),
+                )
+                .await;
+            rx.try_recv().unwrap().trigger.unwrap()
+        }
+
+        #[tokio::test]
+        async fn pushing_and_popping_restores_the_prior_context() {
+            let registry = ToolRegistry::empty();
+            let (tx, mut rx) = thought_channel(ThoughtQueueConfig::default());
+            registry.set_thought_tx(tx).await;
+
+            assert_eq!(tagged_trigger(&registry, &mut rx).await, "persistent");
+
+            let result = registry
+                .execute("push_active_context", &context_args("job:digest"))
+                .await;
+            assert_eq!(response(&result)["depth"], 1);
+            assert_eq!(tagged_trigger(&registry, &mut rx).await, "job:digest");
+
+            let result = registry
+                .execute("push_active_context", &context_args("dm:convo1"))
+                .await;
+            assert_eq!(response(&result)["depth"], 2);
+            assert_eq!(
+                response(&result)["stack"],
+                json!(["job:digest", "dm:convo1"])
+            );
+            assert_eq!(tagged_trigger(&registry, &mut rx).await, "dm:convo1");
+
+            let result = registry
+                .execute("pop_active_context", &HashMap::new())
+                .await;
+            assert_eq!(response(&result)["popped"], "dm:convo1");
+            assert_eq!(response(&result)["active_context"], "job:digest");
+            assert_eq!(tagged_trigger(&registry, &mut rx).await, "job:digest");
+
+            registry
+                .execute("pop_active_context", &HashMap::new())
+                .await;
+            assert_eq!(tagged_trigger(&registry, &mut rx).await, "persistent");
+
+            let result = registry
+                .execute("pop_active_context", &HashMap::new())
+                .await;
+            assert_eq!(result.is_error, Some(false));
+            assert_eq!(response(&result)["popped"], Value::Null);
+        }
+
+        #[tokio::test]
+        async fn set_replaces_the_whole_stack() {
+            let registry = ToolRegistry::empty();
+            for context in ["a", "b"] {
+                registry
+                    .execute("push_active_context", &context_args(context))
+                    .await;
+            }
+
+            let result = registry
+                .execute("set_active_context", &context_args("c"))
+                .await;
+            assert_eq!(response(&result)["depth"], 1);
+            assert_eq!(
+                *registry.state.read().await.active_context.read().await,
+                vec!["c".to_string()]
+            );
+        }
+
+        #[tokio::test]
+        async fn push_is_bounded() {
+            let registry = ToolRegistry::empty();
+            for i in 0..MAX_ACTIVE_CONTEXT_DEPTH {
+                let result = registry
+                    .execute("push_active_context", &context_args(&format!("c{}", i)))
+                    .await;
+                assert_eq!(result.is_error, Some(false));
+            }
+
+            let result = registry
+                .execute("push_active_context", &context_args("overflow"))
+                .await;
+            assert_eq!(result.is_error, Some(true));
+
+            let result = registry
+                .execute("push_active_context", &HashMap::new())
+                .await;
+            assert_eq!(result.is_error, Some(true));
+        }
+    }
+
+    mod thought_contexts {
+        use super::*;
+
+        fn thought(content: &str, trigger: Option<&str>) -> Thought {
+            Thought {
+                kind: ThoughtKind::Insight,
+                content: content.to_string(),
+                content_type: Some(ThoughtContentType::Text),
+                trigger: trigger.map(String::from),
+                tags: vec![],
+                duration_ms: None,
+                created_at: Utc::now(),
+            }
+        }
+
+        fn contents(result: &CallToolResult) -> Vec<String> {
+            let ToolContent::Text { text } = &result.content[0];
+            let body: Value = serde_json::from_str(text).unwrap();
+            let mut contents: Vec<String> = body["thoughts"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["content"].as_str().unwrap().to_string())
+                .collect();
+            contents.sort();
+            contents
+        }
+
+        async fn registry_with_thoughts(thoughts: &[(&str, Option<&str>)]) -> ToolRegistry {
+            let cache = RepoCache::new();
+            for (i, (content, trigger)) in thoughts.iter().enumerate() {
+                cache.upsert_thought(
+                    format!("t{}", i),
+                    thought(content, *trigger),
+                    format!("cid{}", i),
+                );
+            }
+            cache.set_state(winter_atproto::SyncState::Live);
+
+            let registry = ToolRegistry::empty();
+            registry.state.write().await.cache = Some(cache);
+            registry
+        }
+
+        #[tokio::test]
+        async fn context_filter_returns_only_that_contexts_thoughts() {
+            let registry = registry_with_thoughts(&[
+                ("reading the thread", Some("dm:convo1")),
+                ("drafting a reply", Some("dm:convo1")),
+                ("nested notification", Some("dm:convo12")),
+                ("daily digest", Some("job:digest")),
+                ("untagged", None),
+            ])
+            .await;
+
+            let args = HashMap::from([("context".to_string(), json!("dm:convo1"))]);
+            let result = registry.execute("list_thoughts", &args).await;
+
+            assert_eq!(result.is_error, Some(false));
+            assert_eq!(
+                contents(&result),
+                vec!["drafting a reply", "reading the thread"]
+            );
+        }
+
+        #[tokio::test]
+        async fn unknown_context_returns_nothing() {
+            let registry = registry_with_thoughts(&[("daily digest", Some("job:digest"))]).await;
+
+            let args = HashMap::from([("context".to_string(), json!("job:other"))]);
+            let result = registry.execute("list_thoughts", &args).await;
+
+            assert!(contents(&result).is_empty());
+        }
+
+        #[tokio::test]
+        async fn recorded_thoughts_default_to_the_active_context() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:winter/diy.razorgirl.winter.thought/x",
+                    "cid": "bafyx"
+                })))
+                .mount(&server)
+                .await;
+            let registry = registry_for(&server).await;
+            let active_context = registry.state.read().await.active_context.clone();
+            active_context.write().await.push("dm:convo1".to_string());
+
+            let args = HashMap::from([
+                ("kind".to_string(), json!("plan")),
+                ("content".to_string(), json!("reply after lunch")),
+            ]);
+            registry.execute("record_thought", &args).await;
+
+            let requests = server.received_requests().await.unwrap();
+            let create = requests
+                .iter()
+                .find(|r| r.url.path().ends_with("createRecord"))
+                .unwrap();
+            assert_eq!(request_json(create)["record"]["trigger"], "dm:convo1");
+        }
+    }
 }
@@ -3,12 +3,15 @@
 use std::collections::HashMap;
 
 use chrono::Utc;
+use futures_util::future::join_all;
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Note, Tid};
+use winter_atproto::{
+    FACT_COLLECTION, Fact, Note, SyncState, Tid, fact_ref_rkey, resolve_related_facts,
+};
 
-use super::{ToolMeta, ToolState, truncate_for_summary};
+use super::{ToolMeta, ToolState, dry_run, parse_string_array, tombstones, truncate_for_summary};
 
 /// Collection name for notes.
 const NOTE_COLLECTION: &str = "diy.razorgirl.winter.note";
@@ -40,6 +43,11 @@ pub fn definitions() -> Vec<ToolDefinition> {
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Optional tags for categorization"
+                    },
+                    "related_facts": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Optional facts this note is about, as fact AT URIs or rkeys"
                     }
                 },
                 "required": ["title", "content"]
@@ -54,6 +62,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "rkey": {
                         "type": "string",
                         "description": "Record key of the note"
+                    },
+                    "resolve_facts": {
+                        "type": "boolean",
+                        "description": "Resolve related_facts into their predicate and args (default false). Deleted facts are reported as unresolved."
                     }
                 },
                 "required": ["rkey"]
@@ -84,6 +96,38 @@ pub fn definitions() -> Vec<ToolDefinition> {
                 }
             }),
         },
+        ToolDefinition {
+            name: "delete_note".to_string(),
+            description: "Delete a note by its record key. By default the note is soft-deleted: hidden from listings but restorable with restore_note for 30 days, after which it is removed for good.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of the note to delete"
+                    },
+                    "permanent": {
+                        "type": "boolean",
+                        "description": "Delete immediately instead of soft-deleting. Cannot be undone. Default: false."
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
+        ToolDefinition {
+            name: "restore_note".to_string(),
+            description: "Restore a soft-deleted note. Only works within 30 days of deletion.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "rkey": {
+                        "type": "string",
+                        "description": "Record key of the deleted note"
+                    }
+                },
+                "required": ["rkey"]
+            }),
+        },
     ]
 }
 
@@ -124,17 +168,33 @@ pub async fn create_note(state: &ToolState, arguments: &HashMap<String, Value>)
         })
         .unwrap_or_default();
 
+    let related_facts = match arguments.get("related_facts").and_then(|v| v.as_array()) {
+        Some(arr) => match parse_string_array(arr, "related_facts") {
+            Ok(refs) => refs,
+            Err(e) => return e,
+        },
+        None => Vec::new(),
+    };
+    if let Some(bad) = related_facts.iter().find(|r| fact_ref_rkey(r).is_none()) {
+        return CallToolResult::error(format!("Not a fact reference: {}", bad));
+    }
+
     let now = Utc::now();
     let note = Note {
         title: title.to_string(),
         content: content.to_string(),
         category,
-        related_facts: Vec::new(),
+        related_facts,
         tags,
         created_at: now,
         last_updated: now,
+        deleted_at: None,
     };
 
+    if dry_run::requested(arguments) {
+        return dry_run::preview(NOTE_COLLECTION, &note, &[]);
+    }
+
     let rkey = Tid::now().to_string();
 
     match state
@@ -167,25 +227,125 @@ pub async fn get_note(state: &ToolState, arguments: &HashMap<String, Value>) ->
         None => return CallToolResult::error("Missing required parameter: rkey"),
     };
 
-    match state
+    let resolve_facts = arguments
+        .get("resolve_facts")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let note = match state
         .atproto
         .get_record::<Note>(NOTE_COLLECTION, rkey)
         .await
     {
-        Ok(record) => CallToolResult::success(
-            json!({
-                "rkey": rkey,
-                "title": record.value.title,
-                "content": record.value.content,
-                "category": record.value.category,
-                "tags": record.value.tags,
-                "created_at": record.value.created_at.to_rfc3339(),
-                "last_updated": record.value.last_updated.to_rfc3339()
+        Ok(record) => record.value,
+        Err(e) => return CallToolResult::error(format!("Failed to get note: {}", e)),
+    };
+
+    let related_facts = if resolve_facts {
+        let rkeys: Vec<&str> = note
+            .related_facts
+            .iter()
+            .filter_map(|r| fact_ref_rkey(r))
+            .collect();
+        let facts = fetch_facts(state, &rkeys).await;
+        let resolved: Vec<Value> = resolve_related_facts(&note, &facts)
+            .into_iter()
+            .map(|(reference, fact)| match fact {
+                Some(fact) => json!({
+                    "ref": reference,
+                    "rkey": fact_ref_rkey(reference),
+                    "resolved": true,
+                    "predicate": fact.predicate,
+                    "args": fact.args,
+                }),
+                None => json!({
+                    "ref": reference,
+                    "rkey": fact_ref_rkey(reference),
+                    "resolved": false,
+                }),
             })
-            .to_string(),
-        ),
-        Err(e) => CallToolResult::error(format!("Failed to get note: {}", e)),
+            .collect();
+        json!(resolved)
+    } else {
+        json!(note.related_facts)
+    };
+
+    CallToolResult::success(
+        json!({
+            "rkey": rkey,
+            "title": note.title,
+            "content": note.content,
+            "category": note.category,
+            "tags": note.tags,
+            "related_facts": related_facts,
+            "created_at": note.created_at.to_rfc3339(),
+            "last_updated": note.last_updated.to_rfc3339(),
+            "deleted_at": note.deleted_at.map(|t| t.to_rfc3339())
+        })
+        .to_string(),
+    )
+}
+
+/// Fetch facts by rkey in one batch.
+///
+/// Reads from the cache when it's live, otherwise fetches from the PDS
+/// concurrently. Facts that don't exist or are soft-deleted are left out.
+async fn fetch_facts(state: &ToolState, rkeys: &[&str]) -> HashMap<String, Fact> {
+    if let Some(cache) = &state.cache
+        && cache.state() == SyncState::Live
+    {
+        return rkeys
+            .iter()
+            .filter_map(|rkey| cache.get_fact(rkey).map(|f| (rkey.to_string(), f.value)))
+            .filter(|(_, fact)| fact.deleted_at.is_none())
+            .collect();
     }
+
+    let fetches = rkeys.iter().map(|rkey| async move {
+        state
+            .atproto
+            .get_record::<Fact>(FACT_COLLECTION, rkey)
+            .await
+            .ok()
+            .map(|record| (rkey.to_string(), record.value))
+    });
+    join_all(fetches)
+        .await
+        .into_iter()
+        .flatten()
+        .filter(|(_, fact)| fact.deleted_at.is_none())
+        .collect()
+}
+
+/// Load every note that isn't soft-deleted, keyed by rkey, from the cache
+/// when it's live.
+pub(super) async fn load_notes(state: &ToolState) -> Result<HashMap<String, Note>, String> {
+    if let Some(cache) = &state.cache
+        && cache.state() == SyncState::Live
+    {
+        return Ok(cache
+            .list_notes()
+            .into_iter()
+            .map(|(rkey, cached)| (rkey, cached.value))
+            .filter(|(_, note)| note.deleted_at.is_none())
+            .collect());
+    }
+
+    state
+        .atproto
+        .list_all_records::<Note>(NOTE_COLLECTION)
+        .await
+        .map(|records| {
+            records
+                .into_iter()
+                .map(|r| {
+                    let rkey = r.uri.split('/').next_back().unwrap_or("").to_string();
+                    (rkey, r.value)
+                })
+                .filter(|(_, note)| note.deleted_at.is_none())
+                .collect()
+        })
+        .map_err(|e| format!("Failed to list notes: {}", e))
 }
 
 pub async fn list_notes(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
@@ -234,6 +394,10 @@ pub async fn list_notes(state: &ToolState, arguments: &HashMap<String, Value>) -
     let formatted: Vec<Value> = notes
         .into_iter()
         .filter(|item| {
+            // Soft-deleted notes stay hidden until restored
+            if item.value.deleted_at.is_some() {
+                return false;
+            }
             // Filter by category if specified
             if let Some(cat) = category_filter
                 && item.value.category.as_deref() != Some(cat)
@@ -281,3 +445,108 @@ pub async fn list_notes(state: &ToolState, arguments: &HashMap<String, Value>) -
         .to_string(),
     )
 }
+
+pub async fn delete_note(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let permanent = arguments
+        .get("permanent")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    if permanent {
+        return match state.atproto.delete_record(NOTE_COLLECTION, rkey).await {
+            Ok(()) => {
+                if let Some(cache) = &state.cache {
+                    cache.delete_note(rkey);
+                }
+                CallToolResult::success(
+                    json!({
+                        "deleted": true,
+                        "rkey": rkey,
+                        "permanent": true
+                    })
+                    .to_string(),
+                )
+            }
+            Err(e) => CallToolResult::error(format!("Failed to delete note: {}", e)),
+        };
+    }
+
+    let mut note = match state
+        .atproto
+        .get_record::<Note>(NOTE_COLLECTION, rkey)
+        .await
+    {
+        Ok(record) => record.value,
+        Err(e) => return CallToolResult::error(format!("Failed to get note: {}", e)),
+    };
+
+    // Deleting twice keeps the first tombstone, so the window isn't extended
+    let deleted_at = *note.deleted_at.get_or_insert_with(Utc::now);
+
+    match state.atproto.put_record(NOTE_COLLECTION, rkey, &note).await {
+        Ok(response) => {
+            if let Some(cache) = &state.cache {
+                cache.upsert_note(rkey.to_string(), note, response.cid);
+            }
+            CallToolResult::success(
+                json!({
+                    "deleted": true,
+                    "rkey": rkey,
+                    "permanent": false,
+                    "restorable_until": tombstones::restorable_until(deleted_at).to_rfc3339()
+                })
+                .to_string(),
+            )
+        }
+        Err(e) => CallToolResult::error(format!("Failed to delete note: {}", e)),
+    }
+}
+
+pub async fn restore_note(state: &ToolState, arguments: &HashMap<String, Value>) -> CallToolResult {
+    let rkey = match arguments.get("rkey").and_then(|v| v.as_str()) {
+        Some(r) => r,
+        None => return CallToolResult::error("Missing required parameter: rkey"),
+    };
+
+    let mut note = match state
+        .atproto
+        .get_record::<Note>(NOTE_COLLECTION, rkey)
+        .await
+    {
+        Ok(record) => record.value,
+        Err(e) => return CallToolResult::error(format!("Failed to get note: {}", e)),
+    };
+
+    let Some(deleted_at) = note.deleted_at.take() else {
+        return CallToolResult::error(format!("Note {} is not deleted", rkey));
+    };
+    if !tombstones::is_restorable(deleted_at, Utc::now()) {
+        return CallToolResult::error(format!(
+            "Note {} was deleted more than {} days ago and can no longer be restored",
+            rkey,
+            tombstones::RETENTION_DAYS
+        ));
+    }
+
+    match state.atproto.put_record(NOTE_COLLECTION, rkey, &note).await {
+        Ok(response) => {
+            if let Some(cache) = &state.cache {
+                cache.upsert_note(rkey.to_string(), note.clone(), response.cid);
+            }
+            CallToolResult::success(
+                json!({
+                    "restored": true,
+                    "rkey": rkey,
+                    "title": note.title
+                })
+                .to_string(),
+            )
+        }
+        Err(e) => CallToolResult::error(format!("Failed to restore note: {}", e)),
+    }
+}
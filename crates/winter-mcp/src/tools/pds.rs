@@ -4,13 +4,17 @@
 //! and extensibility without going through typed record structures.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::AtUri;
+use winter_atproto::{
+    AtUri, IDENTITY_COLLECTION, STATE_COLLECTION, SyncCoordinator, Tid, WriteOp, WriteResult,
+    dispatch_create_or_update_json, dispatch_delete, is_tracked_collection,
+};
 
-use super::{ToolMeta, ToolState};
+use super::{MAX_BATCH_SIZE, ToolMeta, ToolState};
 
 pub fn definitions() -> Vec<ToolDefinition> {
     vec![
@@ -111,13 +115,119 @@ pub fn definitions() -> Vec<ToolDefinition> {
                 "required": ["uris"]
             }),
         },
+        ToolDefinition {
+            name: "pds_apply_writes".to_string(),
+            description: "Apply several creates, updates and deletes to Winter's records in one atomic commit: either every write lands or none do. Use for multi-record changes that must not be half-applied, e.g. creating a replacement fact and deleting the one it supersedes. Only Winter's own record collections are allowed (not identity or daemon state).".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "writes": {
+                        "type": "array",
+                        "description": "Writes to apply, in order",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "action": {
+                                    "type": "string",
+                                    "enum": ["create", "update", "delete"]
+                                },
+                                "collection": {
+                                    "type": "string",
+                                    "description": "The collection NSID (e.g., 'diy.razorgirl.winter.fact')"
+                                },
+                                "rkey": {
+                                    "type": "string",
+                                    "description": "The record key (required for update and delete; generated for create if omitted)"
+                                },
+                                "record": {
+                                    "type": "object",
+                                    "description": "The record data for create and update. The $type field will be added automatically."
+                                }
+                            },
+                            "required": ["action", "collection"]
+                        }
+                    }
+                },
+                "required": ["writes"]
+            }),
+        },
+        ToolDefinition {
+            name: "replay_repo".to_string(),
+            description: "Re-download the repo, rebuild the record cache from it and regenerate all datalog facts. Use after changes to cache or schema logic. Returns per-collection record counts.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        },
     ]
 }
 
+/// Whether `pds_apply_writes` may write to a collection.
+///
+/// Limited to the Winter collections the cache tracks. Identity and daemon
+/// state are managed by the daemon and excluded.
+pub(crate) fn is_apply_writes_allowed(collection: &str) -> bool {
+    collection.starts_with("diy.razorgirl.winter.")
+        && is_tracked_collection(collection)
+        && collection != IDENTITY_COLLECTION
+        && collection != STATE_COLLECTION
+}
+
+/// Parse one entry of the `writes` argument.
+fn parse_write(value: &Value) -> Result<WriteOp, String> {
+    let obj = value.as_object().ok_or("expected object")?;
+    let action = obj
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or("missing action")?;
+    let collection = obj
+        .get("collection")
+        .and_then(|v| v.as_str())
+        .ok_or("missing collection")?;
+    if !is_apply_writes_allowed(collection) {
+        return Err(format!("collection '{}' is not allowed", collection));
+    }
+    let collection = collection.to_string();
+    let rkey = obj.get("rkey").and_then(|v| v.as_str()).map(String::from);
+    let record = match obj.get("record") {
+        Some(Value::Object(record)) => Some(Value::Object(record.clone())),
+        Some(_) => return Err("record must be an object".to_string()),
+        None => None,
+    };
+
+    match action {
+        "create" => Ok(WriteOp::Create {
+            collection,
+            rkey: rkey.unwrap_or_else(|| Tid::now().to_string()),
+            value: record.ok_or("missing record")?,
+        }),
+        "update" => Ok(WriteOp::Update {
+            collection,
+            rkey: rkey.ok_or("missing rkey")?,
+            value: record.ok_or("missing record")?,
+        }),
+        "delete" => Ok(WriteOp::Delete {
+            collection,
+            rkey: rkey.ok_or("missing rkey")?,
+        }),
+        other => Err(format!("unknown action '{}'", other)),
+    }
+}
+
 /// Get all PDS tools with their permission metadata.
-/// All PDS tools are allowed for the autonomous agent.
+/// All PDS tools are allowed for the autonomous agent except repo replay and
+/// batch writes, which are operator-only.
 pub fn tools() -> Vec<ToolMeta> {
-    definitions().into_iter().map(ToolMeta::allowed).collect()
+    definitions()
+        .into_iter()
+        .map(|def| {
+            if matches!(def.name.as_str(), "replay_repo" | "pds_apply_writes") {
+                ToolMeta::operator_only(def)
+            } else {
+                ToolMeta::allowed(def)
+            }
+        })
+        .collect()
 }
 
 /// List records in a collection.
@@ -339,6 +449,143 @@ pub async fn pds_get_records(
     }
 }
 
+/// Apply several writes as one atomic commit.
+pub async fn pds_apply_writes(
+    state: &ToolState,
+    arguments: &HashMap<String, Value>,
+) -> CallToolResult {
+    let entries = match arguments.get("writes").and_then(|v| v.as_array()) {
+        Some(arr) => arr,
+        None => return CallToolResult::error("Missing required parameter: writes"),
+    };
+
+    if entries.is_empty() {
+        return CallToolResult::error("writes array cannot be empty");
+    }
+
+    if entries.len() > MAX_BATCH_SIZE {
+        return CallToolResult::error(format!(
+            "Batch size {} exceeds maximum of {}",
+            entries.len(),
+            MAX_BATCH_SIZE
+        ));
+    }
+
+    // Validate everything up front so nothing is sent unless all of it can be.
+    let mut writes = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        match parse_write(entry) {
+            Ok(write) => writes.push(write),
+            Err(e) => errors.push(format!("writes[{}]: {}", i, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return CallToolResult::error(errors.join("\n"));
+    }
+
+    let response = match state.atproto.apply_writes(writes.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            return CallToolResult::error(format!("Failed to apply writes (none applied): {}", e));
+        }
+    };
+
+    let results: Vec<Value> = writes
+        .iter()
+        .zip(response.results.iter())
+        .map(|(write, result)| {
+            let (action, record) = match write {
+                WriteOp::Create { value, .. } => ("create", Some(value)),
+                WriteOp::Update { value, .. } => ("update", Some(value)),
+                WriteOp::Delete { .. } => ("delete", None),
+            };
+            let mut item = json!({
+                "action": action,
+                "collection": write.collection(),
+                "rkey": write.rkey(),
+            });
+            match (record, result) {
+                (
+                    Some(record),
+                    WriteResult::Create { uri, cid } | WriteResult::Update { uri, cid },
+                ) => {
+                    item["uri"] = json!(uri);
+                    item["cid"] = json!(cid);
+                    if let Some(cache) = &state.cache
+                        && let Err(e) = dispatch_create_or_update_json(
+                            cache,
+                            write.collection(),
+                            write.rkey(),
+                            cid,
+                            record.clone(),
+                        )
+                    {
+                        item["cache_warning"] = json!(e.to_string());
+                    }
+                }
+                (None, _) => {
+                    if let Some(cache) = &state.cache {
+                        dispatch_delete(cache, write.collection(), write.rkey());
+                    }
+                }
+                // Result kinds don't line up with the writes; the commit still
+                // landed, so leave the cache to catch up from the firehose.
+                _ => {}
+            }
+            item
+        })
+        .collect();
+
+    CallToolResult::success(
+        json!({
+            "applied": results.len(),
+            "commit": {
+                "cid": response.commit.cid,
+                "rev": response.commit.rev
+            },
+            "results": results
+        })
+        .to_string(),
+    )
+}
+
+/// Rebuild the cache and datalog facts from a fresh copy of the repo.
+pub async fn replay_repo(state: &ToolState, _arguments: &HashMap<String, Value>) -> CallToolResult {
+    let cache = match &state.cache {
+        Some(cache) => cache,
+        None => return CallToolResult::error("Repo cache not available"),
+    };
+
+    let did = match state.atproto.did().await {
+        Some(did) => did,
+        None => return CallToolResult::error("Not logged in"),
+    };
+
+    let coordinator = SyncCoordinator::new((*state.atproto).clone(), did, Arc::clone(cache));
+    let summary = match coordinator.replay().await {
+        Ok(summary) => summary,
+        Err(e) => return CallToolResult::error(format!("Failed to replay repo: {}", e)),
+    };
+
+    let datalog_regenerated = match &state.datalog_cache {
+        Some(datalog) => {
+            datalog.populate_from_repo_cache(cache).await;
+            true
+        }
+        None => false,
+    };
+
+    CallToolResult::success(
+        json!({
+            "rev": summary.rev,
+            "counts": summary,
+            "datalog_regenerated": datalog_regenerated
+        })
+        .to_string(),
+    )
+}
+
 /// Extract the rkey from an AT URI.
 fn extract_rkey(uri: &str) -> String {
     AtUri::extract_rkey(uri).to_string()
@@ -364,7 +611,7 @@ mod tests {
     #[test]
     fn test_definitions_count() {
         let defs = definitions();
-        assert_eq!(defs.len(), 5);
+        assert_eq!(defs.len(), 7);
     }
 
     #[test]
@@ -376,5 +623,32 @@ mod tests {
         assert!(names.contains(&"pds_put_record"));
         assert!(names.contains(&"pds_delete_record"));
         assert!(names.contains(&"pds_get_records"));
+        assert!(names.contains(&"pds_apply_writes"));
+        assert!(names.contains(&"replay_repo"));
+    }
+
+    #[test]
+    fn test_replay_and_batch_writes_are_operator_only() {
+        for tool in tools() {
+            assert_eq!(
+                tool.agent_allowed,
+                !matches!(
+                    tool.definition.name.as_str(),
+                    "replay_repo" | "pds_apply_writes"
+                ),
+                "{}",
+                tool.definition.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_writes_allowlist() {
+        assert!(is_apply_writes_allowed("diy.razorgirl.winter.fact"));
+        assert!(is_apply_writes_allowed("diy.razorgirl.winter.note"));
+        assert!(!is_apply_writes_allowed(IDENTITY_COLLECTION));
+        assert!(!is_apply_writes_allowed(STATE_COLLECTION));
+        assert!(!is_apply_writes_allowed("app.bsky.feed.post"));
+        assert!(!is_apply_writes_allowed("diy.razorgirl.winter.unknown"));
     }
 }
@@ -11,37 +11,11 @@
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
 
-use winter_atproto::{CustomTool, ToolApproval, code_needs_network};
+use winter_atproto::{CustomTool, SAFE_TOOLS, ToolApproval, code_needs_network};
 
 /// MCP tools that are safe to call without operator approval.
-/// These are all read-only operations that don't modify state.
-pub const SAFE_MCP_TOOLS: &[&str] = &[
-    "query_facts",
-    "list_rules",
-    "list_directives",
-    "list_jobs",
-    "list_notes",
-    "get_note",
-    "list_facts",
-    "list_fact_declarations",
-    "get_thread_context",
-    "search_posts",
-    "get_identity",
-    "query_and_enrich",
-    "list_predicates",
-    "list_custom_tools",
-    "get_custom_tool",
-    "list_secrets",
-    "list_thoughts",
-    "get_thought",
-    "list_blog_posts",
-    "get_blog_post",
-    "check_interruption",
-    "pds_list_records",
-    "pds_get_record",
-    "pds_get_records",
-    "search_users",
-];
+/// Defined once in `winter_atproto::SAFE_TOOLS` so the approval CLI agrees.
+pub const SAFE_MCP_TOOLS: &[&str] = SAFE_TOOLS.names();
 
 /// A permission vector — one point in the product lattice.
 /// Comparison is component-wise across all dimensions.
@@ -74,19 +48,16 @@ impl PermissionVec {
     /// True if this vector is within the auto-approval threshold:
     /// no network, no secrets, no commands, only safe built-in MCP tools.
     ///
-    /// Any reference to a custom tool AT URI makes this unsafe, because we can't
-    /// verify the remote tool's safety without fetching it.
+    /// Uses the same check as [`CustomTool::is_safe`]. Any reference to a
+    /// custom tool AT URI makes this unsafe, because we can't verify the
+    /// remote tool's safety without fetching it.
     pub fn is_safe(&self) -> bool {
-        !self.network
-            && self.secrets.is_empty()
-            && self.commands.is_empty()
-            && self.mcp_tools.iter().all(|t| {
-                // AT URIs reference custom tools — always require approval
-                if is_at_uri(t) {
-                    return false;
-                }
-                is_safe_mcp_tool(t)
-            })
+        SAFE_TOOLS.permits(
+            self.network,
+            self.secrets.len(),
+            self.commands.len(),
+            self.mcp_tools.iter().map(String::as_str),
+        )
     }
 
     /// True if self dominates other in every dimension.
@@ -193,7 +164,7 @@ impl PartialOrd for PermissionVec {
 
 /// Check if an MCP tool name is in the safe list.
 pub fn is_safe_mcp_tool(name: &str) -> bool {
-    SAFE_MCP_TOOLS.contains(&name)
+    SAFE_TOOLS.contains(name)
 }
 
 /// Check if a string is an AT URI (references a custom tool on some PDS).
@@ -863,4 +834,66 @@ mod tests {
             r#"import { helper } from "./utils.ts";"#
         ));
     }
+
+    fn custom_tool(
+        code: &str,
+        requires_network: Option<bool>,
+        secrets: &[&str],
+        commands: &[&str],
+        tools: &[&str],
+    ) -> CustomTool {
+        CustomTool {
+            name: "t".to_string(),
+            description: String::new(),
+            code: code.to_string(),
+            input_schema: serde_json::json!({}),
+            required_secrets: secrets.iter().map(|s| s.to_string()).collect(),
+            requires_workspace: None,
+            requires_network,
+            required_commands: commands.iter().map(|s| s.to_string()).collect(),
+            required_tools: tools.iter().map(|s| s.to_string()).collect(),
+            cache_ttl_secs: None,
+            version: 1,
+            created_at: chrono::Utc::now(),
+            last_updated: None,
+        }
+    }
+
+    /// The server classifies via `PermissionVec::is_safe`, the approval CLI via
+    /// `CustomTool::is_safe`; they must never disagree.
+    #[test]
+    fn server_and_cli_safety_classification_agree() {
+        let battery = vec![
+            custom_tool("return 1;", None, &[], &[], &[]),
+            custom_tool("await fetch(url);", None, &[], &[], &[]),
+            custom_tool("await fetch(url);", Some(false), &[], &[], &[]),
+            custom_tool("return 1;", Some(true), &[], &[], &[]),
+            custom_tool(r#"import x from "npm:x";"#, None, &[], &[], &[]),
+            custom_tool("return 1;", None, &["KEY"], &[], &[]),
+            custom_tool("return 1;", None, &[], &["git"], &[]),
+            custom_tool("return 1;", None, &[], &[], &["query_facts", "list_notes"]),
+            custom_tool("return 1;", None, &[], &[], &["create_fact"]),
+            custom_tool(
+                "return 1;",
+                None,
+                &[],
+                &[],
+                &["at://did:plc:x/diy.razorgirl.winter.tool/abc"],
+            ),
+        ];
+        let battery = battery.into_iter().chain(
+            SAFE_MCP_TOOLS
+                .iter()
+                .map(|t| custom_tool("return 1;", None, &[], &[], &[t])),
+        );
+
+        for tool in battery {
+            assert_eq!(
+                PermissionVec::from_tool(&tool).is_safe(),
+                tool.is_safe(&SAFE_TOOLS),
+                "classification mismatch for {:?}",
+                tool
+            );
+        }
+    }
 }
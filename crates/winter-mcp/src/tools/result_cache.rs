@@ -0,0 +1,176 @@
+//! In-memory result cache for deterministic custom tools.
+//!
+//! Tools opt in by declaring `cache_ttl_secs`; results are keyed by tool rkey,
+//! version, approval and input, so updating a tool or its approval never
+//! serves results from the previous code or permissions.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+/// Upper bound on cached results across all tools.
+const MAX_ENTRIES: usize = 512;
+
+/// Identifies one run of a tool: the code, the permissions it ran with and
+/// its input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ToolResultKey {
+    rkey: String,
+    version: i32,
+    /// CID of the approval record the tool ran under, if any.
+    approval_cid: Option<String>,
+    sandboxed: bool,
+    /// The input as canonical JSON; objects serialize with sorted keys.
+    input: String,
+}
+
+impl ToolResultKey {
+    pub fn new(
+        rkey: &str,
+        version: i32,
+        approval_cid: Option<&str>,
+        sandboxed: bool,
+        input: &Value,
+    ) -> Self {
+        Self {
+            rkey: rkey.to_string(),
+            version,
+            approval_cid: approval_cid.map(str::to_string),
+            sandboxed,
+            input: input.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    result: Value,
+    expires_at: Instant,
+}
+
+/// Cache of custom tool results, shared by all callers of `run_custom_tool`.
+#[derive(Debug, Default)]
+pub struct ToolResultCache {
+    entries: Mutex<HashMap<ToolResultKey, CachedResult>>,
+}
+
+impl ToolResultCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up an unexpired result for this run.
+    pub fn get(&self, key: &ToolResultKey) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(cached) if cached.expires_at > Instant::now() => Some(cached.result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a result for `ttl`. A zero TTL stores nothing.
+    pub fn insert(&self, key: ToolResultKey, result: Value, ttl: Duration) {
+        if ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, cached| cached.expires_at > now);
+        if entries.len() >= MAX_ENTRIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.expires_at)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key,
+            CachedResult {
+                result,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Number of cached results, including any not yet pruned after expiry.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn key(input: Value) -> ToolResultKey {
+        ToolResultKey::new("3kabc", 1, Some("bafyapproval"), false, &input)
+    }
+
+    #[test]
+    fn hit_on_identical_input() {
+        let cache = ToolResultCache::new();
+        cache.insert(key(json!({"x": 1, "y": 2})), json!({"sum": 3}), TTL);
+
+        // Key order doesn't matter
+        assert_eq!(
+            cache.get(&key(json!({"y": 2, "x": 1}))),
+            Some(json!({"sum": 3}))
+        );
+    }
+
+    #[test]
+    fn miss_when_input_changes() {
+        let cache = ToolResultCache::new();
+        cache.insert(key(json!({"x": 1})), json!(1), TTL);
+
+        assert_eq!(cache.get(&key(json!({"x": 2}))), None);
+    }
+
+    #[test]
+    fn miss_across_versions_approvals_and_sandbox_modes() {
+        let cache = ToolResultCache::new();
+        cache.insert(key(json!({})), json!(1), TTL);
+
+        let input = json!({});
+        for other in [
+            ToolResultKey::new("3kabc", 2, Some("bafyapproval"), false, &input),
+            ToolResultKey::new("3kabc", 1, Some("bafyreapproved"), false, &input),
+            ToolResultKey::new("3kabc", 1, None, true, &input),
+            ToolResultKey::new("3kdef", 1, Some("bafyapproval"), false, &input),
+        ] {
+            assert_eq!(cache.get(&other), None);
+        }
+    }
+
+    #[test]
+    fn expired_results_are_dropped() {
+        let cache = ToolResultCache::new();
+        cache.insert(key(json!({})), json!(1), Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(cache.get(&key(json!({}))), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn zero_ttl_is_not_cached() {
+        let cache = ToolResultCache::new();
+        cache.insert(key(json!({})), json!(1), Duration::ZERO);
+
+        assert!(cache.is_empty());
+    }
+}
@@ -6,9 +6,13 @@ use chrono::Utc;
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Rule, Tid, WriteOp, WriteResult};
+use winter_atproto::{Rule, Tid};
+use winter_datalog::RuleCompiler;
 
-use super::{MAX_BATCH_SIZE, ToolMeta, ToolState, parse_args, parse_string_array};
+use super::{
+    MAX_BATCH_SIZE, ToolMeta, ToolState, apply_batch_creates, dry_run, parse_args,
+    parse_string_array,
+};
 
 /// Collection name for rules.
 const RULE_COLLECTION: &str = "diy.razorgirl.winter.rule";
@@ -37,7 +41,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "body": {
                         "type": "array",
                         "items": { "type": "string" },
-                        "description": "The rule body (conditions), e.g., ['follows(X, Y)', 'follows(Y, X)']"
+                        "description": "The rule body (conditions), e.g., ['follows(X, Y)', 'follows(Y, X)']. Aggregates (count, sum, min, max) are written 'N = count : pred(X, _)', 'S = sum V : score(X, V)', or with an inline comparison 'count : pred(X, _) > 100'; variables shared with the rest of the rule must be bound by an atom outside the aggregate."
                     },
                     "constraints": {
                         "type": "array",
@@ -237,6 +241,14 @@ pub async fn create_rule(state: &ToolState, arguments: &HashMap<String, Value>)
         created_at: Utc::now(),
     };
 
+    if let Err(e) = RuleCompiler::compile_rule(&rule) {
+        return CallToolResult::error(e.to_string());
+    }
+
+    if dry_run::requested(arguments) {
+        return dry_run::preview(RULE_COLLECTION, &rule, &[]);
+    }
+
     let rkey = Tid::now().to_string();
 
     match state
@@ -346,59 +358,29 @@ pub async fn create_rules(state: &ToolState, arguments: &HashMap<String, Value>)
             created_at: now,
         };
 
+        if let Err(e) = RuleCompiler::compile_rule(&rule) {
+            return CallToolResult::error(format!("rules[{}]: {}", i, e));
+        }
+
         let rkey = Tid::now().to_string();
         validated.push((rkey, rule));
     }
 
-    // Build WriteOp list
-    let writes: Vec<WriteOp> = validated
-        .iter()
-        .map(|(rkey, rule)| WriteOp::Create {
-            collection: RULE_COLLECTION.to_string(),
-            rkey: rkey.clone(),
-            value: serde_json::to_value(rule).unwrap(),
-        })
-        .collect();
-
-    // Execute batch write
-    match state.atproto.apply_writes(writes).await {
-        Ok(response) => {
-            // Update cache for each created record
-            for ((rkey, rule), result) in validated.iter().zip(response.results.iter()) {
-                if let WriteResult::Create { cid, .. } = result
-                    && let Some(cache) = &state.cache
-                {
-                    cache.upsert_rule(rkey.clone(), rule.clone(), cid.clone());
-                }
-            }
-
-            let results: Vec<Value> = validated
-                .iter()
-                .zip(response.results.iter())
-                .map(|((rkey, rule), result)| {
-                    if let WriteResult::Create { uri, cid } = result {
-                        json!({
-                            "rkey": rkey,
-                            "uri": uri,
-                            "cid": cid,
-                            "name": rule.name,
-                            "head": rule.head
-                        })
-                    } else {
-                        json!({ "rkey": rkey, "error": "unexpected result type" })
-                    }
-                })
-                .collect();
-
-            CallToolResult::success(
-                json!({
-                    "created": validated.len(),
-                    "results": results
-                })
-                .to_string(),
-            )
+    let describe = |_, rule: &Rule| {
+        let mut item = serde_json::Map::new();
+        item.insert("name".to_string(), json!(rule.name));
+        item.insert("head".to_string(), json!(rule.head));
+        item
+    };
+    let on_created = |rkey: &str, rule: &Rule, cid: &str| {
+        if let Some(cache) = &state.cache {
+            cache.upsert_rule(rkey.to_string(), rule.clone(), cid.to_string());
         }
-        Err(e) => CallToolResult::error(format!("Batch write failed: {}", e)),
+    };
+
+    match apply_batch_creates(state, RULE_COLLECTION, &validated, describe, on_created).await {
+        Ok(result) => CallToolResult::success(result.to_string()),
+        Err(result) => CallToolResult::error(result.to_string()),
     }
 }
 
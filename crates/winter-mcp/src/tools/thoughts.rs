@@ -6,7 +6,7 @@ use chrono::Utc;
 use serde_json::{Value, json};
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Thought, ThoughtKind, Tid};
+use winter_atproto::{Thought, ThoughtContentType, ThoughtKind, Tid};
 
 use super::{ToolMeta, ToolState};
 
@@ -32,7 +32,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     },
                     "trigger": {
                         "type": "string",
-                        "description": "What triggered this thought (optional)"
+                        "description": "What triggered this thought (optional, defaults to the active context)"
                     },
                     "tags": {
                         "type": "array",
@@ -45,7 +45,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "list_thoughts".to_string(),
-            description: "List recent thoughts with optional filtering by kind or tag.".to_string(),
+            description: "List recent thoughts with optional filtering by kind, tag or context. Filter by an inbox item's context_tag to retrieve everything you thought while working on it.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -66,6 +66,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                         "type": "string",
                         "description": "Filter by trigger (case-insensitive substring)"
                     },
+                    "context": {
+                        "type": "string",
+                        "description": "Filter by context tag (exact trigger match), e.g. an inbox item's context_tag"
+                    },
                     "limit": {
                         "type": "integer",
                         "description": "Maximum thoughts to return (default 20)"
@@ -110,12 +114,14 @@ pub async fn record_thought(
         None => return CallToolResult::error("Missing required parameter: content"),
     };
 
-    let trigger = arguments
-        .get("trigger")
-        .and_then(|v| v.as_str())
-        .map(String::from);
+    // Default to the active context, so thoughts recorded while working on an
+    // inbox item can be retrieved by its context tag later.
+    let trigger = match arguments.get("trigger").and_then(|v| v.as_str()) {
+        Some(t) => Some(t.to_string()),
+        None => state.active_context.read().await.last().cloned(),
+    };
 
-    let tags: Vec<String> = arguments
+    let mut tags: Vec<String> = arguments
         .get("tags")
         .and_then(|v| v.as_array())
         .map(|arr| {
@@ -141,9 +147,13 @@ pub async fn record_thought(
         }
     };
 
+    // Tie the thought to the MCP request that recorded it
+    crate::trace::tag_with_current_trace(&mut tags);
+
     let thought = Thought {
         kind,
         content: content.to_string(),
+        content_type: Some(ThoughtContentType::Text),
         trigger,
         tags,
         duration_ms: None,
@@ -197,6 +207,7 @@ pub async fn list_thoughts(
     let tag_filter = arguments.get("tag").and_then(|v| v.as_str());
     let search_filter = arguments.get("search").and_then(|v| v.as_str());
     let trigger_filter = arguments.get("trigger").and_then(|v| v.as_str());
+    let context_filter = arguments.get("context").and_then(|v| v.as_str());
     let limit = arguments
         .get("limit")
         .and_then(|v| v.as_u64())
@@ -269,6 +280,12 @@ pub async fn list_thoughts(
                     _ => return false,
                 }
             }
+            // Filter by context tag (exact match)
+            if let Some(context) = context_filter
+                && item.value.trigger.as_deref() != Some(context)
+            {
+                return false;
+            }
             true
         })
         .take(limit)
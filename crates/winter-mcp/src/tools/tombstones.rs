@@ -0,0 +1,108 @@
+//! Soft deletion for facts and notes.
+//!
+//! `delete_fact` and `delete_note` mark a record with a `deleted_at`
+//! tombstone rather than removing it. Tombstoned records are hidden from
+//! queries and listings, can be brought back with `restore_fact` or
+//! `restore_note` within the retention window, and are hard-deleted by
+//! [`purge_expired`] once it has passed.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use tracing::warn;
+use winter_atproto::{
+    AtUri, AtprotoClient, AtprotoError, FACT_COLLECTION, Fact, NOTE_COLLECTION, Note, RepoCache,
+};
+
+/// Days a tombstoned record stays restorable before it is purged.
+pub const RETENTION_DAYS: i64 = 30;
+
+/// How long a tombstoned record stays restorable.
+pub fn retention() -> Duration {
+    Duration::days(RETENTION_DAYS)
+}
+
+/// When a record deleted at `deleted_at` stops being restorable.
+pub fn restorable_until(deleted_at: DateTime<Utc>) -> DateTime<Utc> {
+    deleted_at + retention()
+}
+
+/// Whether a record deleted at `deleted_at` can still be restored at `now`.
+pub fn is_restorable(deleted_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now < restorable_until(deleted_at)
+}
+
+/// Records hard-deleted by one [`purge_expired`] run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PurgeReport {
+    pub facts: usize,
+    pub notes: usize,
+}
+
+/// Hard-delete facts and notes whose tombstones are older than `retention`.
+///
+/// A record that fails to delete is logged and left for the next run.
+pub async fn purge_expired(
+    atproto: &AtprotoClient,
+    cache: Option<&RepoCache>,
+    retention: Duration,
+    now: DateTime<Utc>,
+) -> Result<PurgeReport, AtprotoError> {
+    let cutoff = now - retention;
+
+    let facts =
+        purge_collection::<Fact>(atproto, FACT_COLLECTION, cutoff, |f| f.deleted_at).await?;
+    let notes =
+        purge_collection::<Note>(atproto, NOTE_COLLECTION, cutoff, |n| n.deleted_at).await?;
+
+    if let Some(cache) = cache {
+        for rkey in &facts {
+            cache.delete_fact(rkey);
+        }
+        for rkey in &notes {
+            cache.delete_note(rkey);
+        }
+    }
+
+    Ok(PurgeReport {
+        facts: facts.len(),
+        notes: notes.len(),
+    })
+}
+
+/// Delete every record in `collection` tombstoned before `cutoff`,
+/// returning the rkeys that were deleted.
+async fn purge_collection<T: DeserializeOwned>(
+    atproto: &AtprotoClient,
+    collection: &str,
+    cutoff: DateTime<Utc>,
+    deleted_at: impl Fn(&T) -> Option<DateTime<Utc>>,
+) -> Result<Vec<String>, AtprotoError> {
+    let mut purged = Vec::new();
+    for item in atproto.list_all_records::<T>(collection).await? {
+        if !deleted_at(&item.value).is_some_and(|at| at <= cutoff) {
+            continue;
+        }
+        let rkey = AtUri::extract_rkey(&item.uri);
+        match atproto.delete_record(collection, rkey).await {
+            Ok(()) => purged.push(rkey.to_string()),
+            Err(e) => warn!(collection, rkey, error = %e, "failed to purge tombstoned record"),
+        }
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restorable_within_the_window() {
+        let deleted_at = Utc::now();
+
+        assert!(is_restorable(deleted_at, deleted_at + Duration::days(1)));
+        assert!(!is_restorable(
+            deleted_at,
+            deleted_at + Duration::days(RETENTION_DAYS)
+        ));
+    }
+}
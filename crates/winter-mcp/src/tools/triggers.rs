@@ -1,27 +1,281 @@
 //! Trigger tools for MCP.
+//!
+//! Also holds the condition matching shared by `test_trigger` and the
+//! daemon's trigger engine, so a dry run sees exactly what would fire.
 
 use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
 use chrono::Utc;
+use regex::Regex;
 use serde_json::{Value, json};
 
+use crate::bluesky::BlueskyNotification;
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Tid, Trigger, TriggerAction};
+use winter_atproto::{CacheUpdate, FactDeclArg, Tid, Trigger, TriggerAction, TriggerFilter};
+use winter_datalog::{DatalogCache, DatalogError};
 
 use std::collections::HashSet;
 
-use super::{ToolMeta, ToolState, parse_args};
+use super::{ToolMeta, ToolState, changes, parse_args};
 
 /// Collection name for triggers.
 const TRIGGER_COLLECTION: &str = "diy.razorgirl.winter.trigger";
 
+/// Predicate holding the event being evaluated: `_event(kind, subject, author, text)`.
+pub const EVENT_PREDICATE: &str = "_event";
+
+/// Maximum number of compiled filter regexes kept in [`REGEX_CACHE`].
+const MAX_CACHED_REGEXES: usize = 256;
+
+/// Compiled filter regexes by pattern, so each is compiled once rather than per event.
+static REGEX_CACHE: LazyLock<Mutex<HashMap<String, Regex>>> = LazyLock::new(Default::default);
+
+/// An incoming event that triggers are evaluated against.
+///
+/// Injected into the condition query as a single `_event` fact, so a
+/// condition like `_event("mention", U, A, _), follows(Self, A, _)` matches
+/// mentions from accounts we follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerEvent {
+    /// Event kind, e.g. `mention`, `reply`, `quote`, `follow` or `fact`.
+    pub kind: String,
+    /// What the event is about: a post URI for notifications, a record key for facts.
+    pub subject: String,
+    /// DID of whoever caused the event, or the fact's source for fact events.
+    pub author: String,
+    /// Post text, or the predicate name for fact events.
+    pub text: String,
+    /// The notification or fact as JSON, for JSON-path filters.
+    pub record: Value,
+}
+
+impl TriggerEvent {
+    /// Event for a Bluesky notification.
+    pub fn from_notification(notification: &BlueskyNotification) -> Self {
+        Self {
+            kind: notification.reason.as_str().to_string(),
+            subject: notification.uri.clone(),
+            author: notification.author_did.clone(),
+            text: notification.text.clone().unwrap_or_default(),
+            record: serde_json::to_value(notification).unwrap_or_default(),
+        }
+    }
+
+    /// Event for a fact arriving from the firehose.
+    ///
+    /// Facts written by triggers are skipped so a trigger can't feed itself.
+    pub fn from_cache_update(update: &CacheUpdate) -> Option<Self> {
+        let CacheUpdate::FactCreated { rkey, fact } = update else {
+            return None;
+        };
+        let source = fact.source.clone().unwrap_or_default();
+        if source.starts_with("trigger:") {
+            return None;
+        }
+        Some(Self {
+            kind: "fact".to_string(),
+            subject: rkey.clone(),
+            author: source,
+            text: fact.predicate.clone(),
+            record: serde_json::to_value(fact).unwrap_or_default(),
+        })
+    }
+
+    /// The `_event(...)` fact for this event.
+    pub fn to_fact(&self) -> String {
+        format!(
+            "{}({}, {}, {}, {})",
+            EVENT_PREDICATE,
+            datalog_string(&self.kind),
+            datalog_string(&self.subject),
+            datalog_string(&self.author),
+            datalog_string(&self.text)
+        )
+    }
+}
+
+/// Quote a value for use as a datalog string constant.
+///
+/// Backslashes and double quotes are escaped, and whitespace control
+/// characters become spaces since string constants can't span lines.
+fn datalog_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' | '\r' | '\t' => quoted.push(' '),
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Whether a trigger's condition reads the current event.
+///
+/// Such triggers only make sense against an event, so they're skipped by
+/// periodic evaluation and run once per event instead.
+pub fn references_event(condition: &str, condition_rules: Option<&str>) -> bool {
+    let mentions = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|token| token == EVENT_PREDICATE)
+    };
+    mentions(condition) || condition_rules.is_some_and(mentions)
+}
+
+/// Whether a trigger is evaluated against events rather than periodically.
+pub fn is_event_trigger(trigger: &Trigger) -> bool {
+    trigger.filter.is_some()
+        || references_event(&trigger.condition, trigger.condition_rules.as_deref())
+}
+
+/// Compile a regex, reusing an earlier compilation of the same pattern.
+fn cached_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut cache = REGEX_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(re) = cache.get(pattern) {
+        return Ok(re.clone());
+    }
+
+    let re = Regex::new(pattern)?;
+    if cache.len() >= MAX_CACHED_REGEXES {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), re.clone());
+    Ok(re)
+}
+
+/// One step of a parsed JSON path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// Parse a JSON path of the form `$.field.nested[0].more`.
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let Some(mut rest) = path.trim().strip_prefix('$') else {
+        return Err(format!("JSON path must start with '$': {}", path));
+    };
+
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let field = &after[..end];
+            if field.is_empty() {
+                return Err(format!("empty field name in JSON path: {}", path));
+            }
+            segments.push(PathSegment::Field(field.to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| format!("unclosed '[' in JSON path: {}", path))?;
+            let index = after[..end]
+                .trim()
+                .parse()
+                .map_err(|_| format!("array index must be a number in JSON path: {}", path))?;
+            segments.push(PathSegment::Index(index));
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("expected '.' or '[' in JSON path: {}", path));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Select the value at a parsed JSON path.
+fn select_json_path<'a>(value: &'a Value, path: &[PathSegment]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |current, segment| match segment {
+            PathSegment::Field(name) => current.get(name),
+            PathSegment::Index(index) => current.get(*index),
+        })
+}
+
+/// Check that a filter's regexes compile and its JSON paths parse.
+pub fn validate_filter(filter: &TriggerFilter) -> Result<(), String> {
+    match filter {
+        TriggerFilter::TextMatches { pattern } => cached_regex(pattern)
+            .map(|_| ())
+            .map_err(|e| format!("invalid regex '{}': {}", pattern, e)),
+        TriggerFilter::JsonPath { path, pattern, .. } => {
+            parse_json_path(path)?;
+            if let Some(pattern) = pattern {
+                cached_regex(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+            }
+            Ok(())
+        }
+        TriggerFilter::All { filters } | TriggerFilter::Any { filters } => {
+            filters.iter().try_for_each(validate_filter)
+        }
+    }
+}
+
+/// Whether an event passes a filter.
+///
+/// Filters that fail to compile never match; [`validate_filter`] rejects
+/// them when the trigger is saved.
+pub fn filter_matches(filter: &TriggerFilter, event: &TriggerEvent) -> bool {
+    match filter {
+        TriggerFilter::TextMatches { pattern } => {
+            cached_regex(pattern).is_ok_and(|re| re.is_match(&event.text))
+        }
+        TriggerFilter::JsonPath {
+            path,
+            equals,
+            pattern,
+        } => {
+            let Ok(segments) = parse_json_path(path) else {
+                return false;
+            };
+            let Some(value) = select_json_path(&event.record, &segments) else {
+                return false;
+            };
+            if equals.as_ref().is_some_and(|expected| expected != value) {
+                return false;
+            }
+            match pattern {
+                Some(pattern) => {
+                    let text = match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    cached_regex(pattern).is_ok_and(|re| re.is_match(&text))
+                }
+                None => true,
+            }
+        }
+        TriggerFilter::All { filters } => filters.iter().all(|f| filter_matches(f, event)),
+        TriggerFilter::Any { filters } => filters.iter().any(|f| filter_matches(f, event)),
+    }
+}
+
+/// Whether a trigger's filter lets an event through to the condition.
+///
+/// Without a filter everything passes; with one, there must be an event.
+pub fn passes_filter(filter: Option<&TriggerFilter>, event: Option<&TriggerEvent>) -> bool {
+    match (filter, event) {
+        (None, _) => true,
+        (Some(filter), Some(event)) => filter_matches(filter, event),
+        (Some(_), None) => false,
+    }
+}
+
 /// Build a query and extra_rules from a trigger condition body.
 ///
 /// Trigger conditions are rule bodies (e.g. `follows_me(X, _), !has_impression(X)`)
 /// which can't be passed directly as queries. This wraps them into a rule:
 ///   `_trigger_result(X) :- follows_me(X, _), !has_impression(X).`
 /// and returns `("_trigger_result(X)", Some("<rules>"))`.
-fn build_trigger_query(condition: &str, condition_rules: Option<&str>) -> (String, Option<String>) {
+pub fn build_trigger_query(
+    condition: &str,
+    condition_rules: Option<&str>,
+) -> (String, Option<String>) {
     let vars = extract_variables(condition);
 
     let query = if vars.is_empty() {
@@ -51,7 +305,7 @@ fn build_trigger_query(condition: &str, condition_rules: Option<&str>) -> (Strin
 
 /// Extract unique uppercase variable names from a datalog condition body,
 /// preserving first-seen order. Skips `_` (anonymous variable).
-fn extract_variables(condition: &str) -> Vec<String> {
+pub fn extract_variables(condition: &str) -> Vec<String> {
     let mut seen = HashSet::new();
     let mut vars = Vec::new();
 
@@ -73,11 +327,137 @@ fn extract_variables(condition: &str) -> Vec<String> {
     vars
 }
 
+/// Build an extra_declarations entry for `_trigger_result` when the trigger has typed args.
+///
+/// Returns an empty vec if args is empty (fall back to default all-symbol declaration).
+pub fn build_trigger_result_declaration(args: &[FactDeclArg]) -> Vec<String> {
+    if args.is_empty() {
+        return Vec::new();
+    }
+    let params: Vec<String> = args
+        .iter()
+        .map(|a| format!("{}: {}", a.name, a.r#type))
+        .collect();
+    vec![format!("_trigger_result({})", params.join(", "))]
+}
+
+/// Run a trigger condition, returning one tuple per match.
+///
+/// With an event, its `_event` fact is visible to the condition.
+pub async fn evaluate_condition(
+    datalog: &DatalogCache,
+    condition: &str,
+    condition_rules: Option<&str>,
+    args: &[FactDeclArg],
+    event: Option<&TriggerEvent>,
+) -> Result<Vec<Vec<String>>, DatalogError> {
+    let (query, rules) = build_trigger_query(condition, condition_rules);
+
+    let extra_decls = build_trigger_result_declaration(args);
+    let extra_decls_option: Option<&[String]> = if extra_decls.is_empty() {
+        None
+    } else {
+        Some(&extra_decls)
+    };
+
+    let event_facts: Vec<String> = event.map(TriggerEvent::to_fact).into_iter().collect();
+    let event_facts_option: Option<&[String]> = if event_facts.is_empty() {
+        None
+    } else {
+        Some(&event_facts)
+    };
+
+    datalog
+        .execute_query_with_facts_and_declarations(
+            &query,
+            rules.as_deref(),
+            event_facts_option,
+            extra_decls_option,
+        )
+        .await
+}
+
+/// Evaluate a trigger and resolve its action for each matching tuple.
+///
+/// Events rejected by the trigger's filter match nothing, without running
+/// the condition.
+pub async fn match_trigger(
+    datalog: &DatalogCache,
+    trigger: &Trigger,
+    event: Option<&TriggerEvent>,
+) -> Result<Vec<(Vec<String>, TriggerAction)>, DatalogError> {
+    if !passes_filter(trigger.filter.as_ref(), event) {
+        return Ok(Vec::new());
+    }
+
+    let tuples = evaluate_condition(
+        datalog,
+        &trigger.condition,
+        trigger.condition_rules.as_deref(),
+        &trigger.args,
+        event,
+    )
+    .await?;
+
+    Ok(tuples
+        .into_iter()
+        .map(|tuple| {
+            let action = resolve_action(&trigger.action, &tuple);
+            (tuple, action)
+        })
+        .collect())
+}
+
+/// Substitute a result tuple into every templated field of an action.
+pub fn resolve_action(action: &TriggerAction, tuple: &[String]) -> TriggerAction {
+    match action {
+        TriggerAction::CreateFact {
+            predicate,
+            args,
+            tags,
+        } => TriggerAction::CreateFact {
+            predicate: predicate.clone(),
+            args: args
+                .iter()
+                .map(|arg| substitute_variables(arg, tuple))
+                .collect(),
+            tags: tags
+                .iter()
+                .map(|tag| substitute_variables(tag, tuple))
+                .collect(),
+        },
+        TriggerAction::CreateInboxItem { message } => TriggerAction::CreateInboxItem {
+            message: substitute_variables(message, tuple),
+        },
+        TriggerAction::DeleteFact { rkey } => TriggerAction::DeleteFact {
+            rkey: substitute_variables(rkey, tuple),
+        },
+        TriggerAction::RunJob { job } => TriggerAction::RunJob {
+            job: substitute_variables(job, tuple),
+        },
+    }
+}
+
+/// Replace `$0`, `$1`, etc. in a template with values from the tuple.
+///
+/// Out-of-range `$N` references are left as literals.
+pub fn substitute_variables(template: &str, tuple: &[String]) -> String {
+    let mut result = template.to_string();
+
+    // Replace from highest index to lowest to avoid $1 replacing part of $10
+    for i in (0..tuple.len()).rev() {
+        let placeholder = format!("${}", i);
+        result = result.replace(&placeholder, &tuple[i]);
+    }
+
+    result
+}
+
 pub fn definitions() -> Vec<ToolDefinition> {
     vec![
         ToolDefinition {
             name: "create_trigger".to_string(),
-            description: "Create a new datalog trigger. Triggers evaluate a datalog condition periodically and execute an action when new results appear. Supports $0, $1 variable substitution from query result columns.".to_string(),
+            description: "Create a new datalog trigger. Triggers evaluate a datalog condition periodically and execute an action when new results appear. Conditions that reference _event(kind, subject, author, text) are instead evaluated against each incoming notification and fact event. Supports $0, $1 variable substitution from query result columns.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -103,7 +483,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
                         "properties": {
                             "type": {
                                 "type": "string",
-                                "enum": ["create_fact", "create_inbox_item", "delete_fact"],
+                                "enum": ["create_fact", "create_inbox_item", "delete_fact", "run_job"],
                                 "description": "Action type"
                             },
                             "predicate": {
@@ -127,6 +507,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                             "rkey": {
                                 "type": "string",
                                 "description": "For delete_fact: rkey of fact to delete (supports $0 substitution)"
+                            },
+                            "job": {
+                                "type": "string",
+                                "description": "For run_job: name of the scheduled job to run now (supports $0 substitution)"
                             }
                         },
                         "required": ["type"]
@@ -148,6 +532,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                             "required": ["name"]
                         },
                         "description": "Type annotations for _trigger_result predicate columns. Enables numeric comparisons instead of lexicographic string ordering."
+                    },
+                    "filter": {
+                        "type": "object",
+                        "description": "Optional event filter checked before the condition; makes this an event trigger. One of {type: 'text_matches', pattern}, {type: 'json_path', path: '$.reply.parent.uri', equals?, pattern?}, {type: 'all', filters: [...]}, {type: 'any', filters: [...]}. Regexes and paths are validated on save."
                     }
                 },
                 "required": ["name", "description", "condition", "action"]
@@ -199,6 +587,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                             "required": ["name"]
                         },
                         "description": "Type annotations for _trigger_result (replaces existing)"
+                    },
+                    "filter": {
+                        "type": ["object", "null"],
+                        "description": "New event filter (null to clear)"
                     }
                 },
                 "required": ["rkey"]
@@ -234,7 +626,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "test_trigger".to_string(),
-            description: "Dry-run a trigger condition to see what would fire. Accepts either an existing trigger rkey or an ad-hoc condition. Does not execute any actions.".to_string(),
+            description: "Dry-run a trigger condition to see what would fire, using the same matching as the daemon. Accepts either an existing trigger rkey or an ad-hoc condition, and optionally a synthetic event. Does not execute any actions.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -262,6 +654,22 @@ pub fn definitions() -> Vec<ToolDefinition> {
                             "required": ["name"]
                         },
                         "description": "Type annotations for ad-hoc testing (used with ad-hoc condition)"
+                    },
+                    "event": {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "type": "string" },
+                            "subject": { "type": "string" },
+                            "author": { "type": "string" },
+                            "text": { "type": "string" },
+                            "record": { "type": "object" }
+                        },
+                        "required": ["kind"],
+                        "description": "Synthetic event exposed to the condition as _event(kind, subject, author, text); record is what JSON-path filters select from"
+                    },
+                    "filter": {
+                        "type": "object",
+                        "description": "Ad-hoc event filter (used with ad-hoc condition)"
                     }
                 },
                 "required": []
@@ -315,6 +723,14 @@ pub async fn create_trigger(
         None => Vec::new(),
     };
 
+    let filter = match arguments.get("filter") {
+        Some(v) if !v.is_null() => match parse_trigger_filter(v) {
+            Ok(f) => Some(f),
+            Err(e) => return CallToolResult::error(e),
+        },
+        _ => None,
+    };
+
     let atproto = &state.atproto;
 
     let trigger = Trigger {
@@ -325,6 +741,7 @@ pub async fn create_trigger(
         action,
         enabled,
         args,
+        filter,
         created_at: Utc::now(),
     };
 
@@ -366,6 +783,7 @@ pub async fn update_trigger(
         Some(cached) => cached.value,
         None => return CallToolResult::error(format!("Trigger not found: {}", rkey)),
     };
+    let before = existing.clone();
 
     // Overlay provided fields
     let name = arguments
@@ -413,6 +831,15 @@ pub async fn update_trigger(
         existing.args
     };
 
+    let filter = match arguments.get("filter") {
+        Some(Value::Null) => None,
+        Some(v) => match parse_trigger_filter(v) {
+            Ok(f) => Some(f),
+            Err(e) => return CallToolResult::error(e),
+        },
+        None => existing.filter,
+    };
+
     let trigger = Trigger {
         name: name.clone(),
         description,
@@ -421,6 +848,7 @@ pub async fn update_trigger(
         action,
         enabled,
         args,
+        filter,
         created_at: existing.created_at,
     };
 
@@ -433,7 +861,8 @@ pub async fn update_trigger(
                 "rkey": rkey,
                 "name": name,
                 "enabled": enabled,
-                "status": "updated"
+                "status": "updated",
+                "changes": changes::record_changes(&before, &trigger, &[])
             })
             .to_string(),
         ),
@@ -497,6 +926,9 @@ pub async fn list_triggers(
                 "enabled": t.enabled,
                 "created_at": t.created_at.to_rfc3339(),
             });
+            if let Some(filter) = &t.filter {
+                entry["filter"] = json!(filter);
+            }
             if !t.args.is_empty() {
                 entry["args"] = json!(t.args.iter().map(|a| {
                     json!({
@@ -527,87 +959,110 @@ pub async fn test_trigger(
         Some(c) => c,
         None => return CallToolResult::error("Cache not available"),
     };
-    // Get condition either from existing trigger or ad-hoc
-    let (condition, condition_rules, trigger_name, trigger_args) = if let Some(rkey) =
-        arguments.get("rkey").and_then(|v| v.as_str())
-    {
-        match cache.get_trigger(rkey) {
-            Some(cached) => (
-                cached.value.condition.clone(),
-                cached.value.condition_rules.clone(),
-                Some(cached.value.name.clone()),
-                cached.value.args.clone(),
-            ),
-            None => return CallToolResult::error(format!("Trigger not found: {}", rkey)),
-        }
-    } else if let Some(condition) = arguments.get("condition").and_then(|v| v.as_str()) {
-        let rules = arguments
-            .get("condition_rules")
-            .and_then(|v| v.as_str())
-            .map(String::from);
-        let args = match arguments.get("args").and_then(|v| v.as_array()) {
-            Some(arr) => match parse_args(arr) {
-                Ok(a) => a,
-                Err(e) => return e,
-            },
-            None => Vec::new(),
-        };
-        (condition.to_string(), rules, None, args)
-    } else {
-        return CallToolResult::error("Either rkey or condition must be provided");
+    let datalog_cache = match state.datalog_cache.as_ref() {
+        Some(d) => d,
+        None => return CallToolResult::error("Datalog not available"),
     };
 
-    // Wrap condition body into a proper query rule
-    let (query, rules) = build_trigger_query(&condition, condition_rules.as_deref());
-
-    // Build typed declaration for _trigger_result if trigger has args
-    let extra_decls: Vec<String> = if !trigger_args.is_empty() {
-        let params: Vec<String> = trigger_args
-            .iter()
-            .map(|a| format!("{}: {}", a.name, a.r#type))
-            .collect();
-        vec![format!("_trigger_result({})", params.join(", "))]
-    } else {
-        Vec::new()
-    };
-    let extra_decls_option: Option<&[String]> = if extra_decls.is_empty() {
-        None
-    } else {
-        Some(&extra_decls)
+    let event = match arguments.get("event") {
+        Some(value) => match parse_trigger_event(value) {
+            Ok(event) => Some(event),
+            Err(e) => return CallToolResult::error(e),
+        },
+        None => None,
     };
 
-    // Run the query
-    let query_result = if let Some(ref datalog_cache) = state.datalog_cache {
-        datalog_cache
-            .execute_query_with_facts_and_declarations(
-                &query,
-                rules.as_deref(),
-                None,
-                extra_decls_option,
-            )
-            .await
-    } else {
-        return CallToolResult::error("Datalog not available");
-    };
+    // Get condition either from existing trigger or ad-hoc
+    let (condition, condition_rules, trigger_name, trigger_args, action, filter) =
+        if let Some(rkey) = arguments.get("rkey").and_then(|v| v.as_str()) {
+            match cache.get_trigger(rkey) {
+                Some(cached) => (
+                    cached.value.condition.clone(),
+                    cached.value.condition_rules.clone(),
+                    Some(cached.value.name.clone()),
+                    cached.value.args.clone(),
+                    Some(cached.value.action.clone()),
+                    cached.value.filter.clone(),
+                ),
+                None => return CallToolResult::error(format!("Trigger not found: {}", rkey)),
+            }
+        } else if let Some(condition) = arguments.get("condition").and_then(|v| v.as_str()) {
+            let rules = arguments
+                .get("condition_rules")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let args = match arguments.get("args").and_then(|v| v.as_array()) {
+                Some(arr) => match parse_args(arr) {
+                    Ok(a) => a,
+                    Err(e) => return e,
+                },
+                None => Vec::new(),
+            };
+            let filter = match arguments.get("filter") {
+                Some(v) if !v.is_null() => match parse_trigger_filter(v) {
+                    Ok(f) => Some(f),
+                    Err(e) => return CallToolResult::error(e),
+                },
+                _ => None,
+            };
+            (condition.to_string(), rules, None, args, None, filter)
+        } else {
+            return CallToolResult::error("Either rkey or condition must be provided");
+        };
+
+    // Same filtering and evaluation the trigger engine runs
+    if filter.is_some() && !passes_filter(filter.as_ref(), event.as_ref()) {
+        let mut response = json!({
+            "result_count": 0,
+            "results": [],
+            "status": "filtered_out",
+        });
+        if let Some(name) = trigger_name {
+            response["trigger_name"] = json!(name);
+        }
+        if event.is_none() {
+            response["note"] = json!(
+                "Trigger has an event filter, so it only fires against incoming events. Pass 'event' to test it."
+            );
+        }
+        return CallToolResult::success(response.to_string());
+    }
+
+    let query_result = evaluate_condition(
+        datalog_cache,
+        &condition,
+        condition_rules.as_deref(),
+        &trigger_args,
+        event.as_ref(),
+    )
+    .await;
 
     match query_result {
         Ok(results) => {
             let result_count = results.len();
-            let sample: Vec<Value> = results
-                .into_iter()
-                .take(20)
-                .map(|tuple| json!(tuple))
-                .collect();
+            let shown: Vec<Vec<String>> = results.into_iter().take(20).collect();
 
             let mut response = json!({
                 "result_count": result_count,
-                "results": sample,
+                "results": shown,
                 "status": if result_count > 0 { "would_fire" } else { "no_match" },
             });
 
             if let Some(name) = trigger_name {
                 response["trigger_name"] = json!(name);
             }
+            if let Some(action) = action {
+                let actions: Vec<Value> = shown
+                    .iter()
+                    .map(|tuple| format_action(&resolve_action(&action, tuple)))
+                    .collect();
+                response["actions"] = json!(actions);
+            }
+            if event.is_none() && references_event(&condition, condition_rules.as_deref()) {
+                response["note"] = json!(
+                    "Condition reads _event, so it only fires against incoming events. Pass 'event' to test it."
+                );
+            }
             if result_count > 20 {
                 response["truncated"] = json!(true);
                 response["showing"] = json!(20);
@@ -619,6 +1074,36 @@ pub async fn test_trigger(
     }
 }
 
+/// Parse a synthetic event for `test_trigger`.
+fn parse_trigger_event(value: &Value) -> Result<TriggerEvent, String> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let kind = field("kind");
+    if kind.is_empty() {
+        return Err("event missing 'kind'".to_string());
+    }
+    Ok(TriggerEvent {
+        kind,
+        subject: field("subject"),
+        author: field("author"),
+        text: field("text"),
+        record: value.get("record").cloned().unwrap_or_default(),
+    })
+}
+
+/// Parse and validate a trigger's event filter.
+fn parse_trigger_filter(value: &Value) -> Result<TriggerFilter, String> {
+    let filter: TriggerFilter =
+        serde_json::from_value(value.clone()).map_err(|e| format!("Invalid filter: {}", e))?;
+    validate_filter(&filter).map_err(|e| format!("Invalid filter: {}", e))?;
+    Ok(filter)
+}
+
 /// Parse a trigger action from a JSON value.
 fn parse_trigger_action(value: &Value) -> Result<TriggerAction, String> {
     let action_type = value
@@ -673,6 +1158,14 @@ fn parse_trigger_action(value: &Value) -> Result<TriggerAction, String> {
                 .to_string();
             Ok(TriggerAction::DeleteFact { rkey })
         }
+        "run_job" => {
+            let job = value
+                .get("job")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "run_job action missing 'job'".to_string())?
+                .to_string();
+            Ok(TriggerAction::RunJob { job })
+        }
         other => Err(format!("Unknown action type: {}", other)),
     }
 }
@@ -698,6 +1191,10 @@ fn format_action(action: &TriggerAction) -> Value {
             "type": "delete_fact",
             "rkey": rkey,
         }),
+        TriggerAction::RunJob { job } => json!({
+            "type": "run_job",
+            "job": job,
+        }),
     }
 }
 
@@ -789,4 +1286,355 @@ mod tests {
         let value = json!({ "type": "unknown" });
         assert!(parse_trigger_action(&value).is_err());
     }
+
+    #[test]
+    fn test_parse_run_job_action() {
+        let value = json!({ "type": "run_job", "job": "digest" });
+        let action = parse_trigger_action(&value).unwrap();
+        assert_eq!(
+            action,
+            TriggerAction::RunJob {
+                job: "digest".to_string()
+            }
+        );
+        assert_eq!(format_action(&action), value);
+        assert!(parse_trigger_action(&json!({ "type": "run_job" })).is_err());
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let tuple = vec!["alice".to_string(), "bob".to_string()];
+        assert_eq!(
+            substitute_variables("$0 follows $1", &tuple),
+            "alice follows bob"
+        );
+        assert_eq!(substitute_variables("$0 and $0", &tuple), "alice and alice");
+        assert_eq!(substitute_variables("$0 and $2", &tuple), "alice and $2");
+        assert_eq!(substitute_variables("plain text", &[]), "plain text");
+    }
+
+    #[test]
+    fn test_substitute_variables_double_digit_index() {
+        let tuple: Vec<String> = (0..11).map(|i| format!("val{}", i)).collect();
+        assert_eq!(substitute_variables("$0 $10", &tuple), "val0 val10");
+    }
+
+    #[test]
+    fn test_extract_variables() {
+        assert_eq!(
+            extract_variables("follows_me(X, _), !has_impression(X)"),
+            vec!["X"]
+        );
+        assert_eq!(
+            extract_variables("follows(Self, X, _), is_followed_by(X, Self)"),
+            vec!["Self", "X"]
+        );
+        assert_eq!(
+            extract_variables(r#"fact_tag(R, "social", _), _fact(R, P, _)"#),
+            vec!["R", "P"]
+        );
+        assert!(extract_variables(r#"has_fact("hello", "world", _)"#).is_empty());
+    }
+
+    #[test]
+    fn test_build_trigger_query() {
+        let (query, rules) = build_trigger_query("follows_me(X, _), !has_impression(X)", None);
+        assert_eq!(query, "_trigger_result(X)");
+        assert!(
+            rules
+                .unwrap()
+                .contains("_trigger_result(X) :- follows_me(X, _), !has_impression(X).")
+        );
+
+        let (query, rules) = build_trigger_query(
+            "mutual(X)",
+            Some("mutual(X) :- follows(Self, X, _), is_followed_by(X, Self)."),
+        );
+        assert_eq!(query, "_trigger_result(X)");
+        let rules = rules.unwrap();
+        assert!(rules.contains("mutual(X) :- follows(Self, X, _), is_followed_by(X, Self)."));
+        assert!(rules.contains("_trigger_result(X) :- mutual(X)."));
+
+        let (query, rules) = build_trigger_query(r#"has_fact("stale_check", _, _)"#, None);
+        assert_eq!(query, "_trigger_result()");
+        assert!(
+            rules
+                .unwrap()
+                .contains("_trigger_result() :- has_fact(\"stale_check\", _, _).")
+        );
+    }
+
+    #[test]
+    fn test_event_fact_is_escaped() {
+        let event = TriggerEvent {
+            kind: "mention".to_string(),
+            subject: "at://did:plc:a/app.bsky.feed.post/1".to_string(),
+            author: "did:plc:a".to_string(),
+            text: "say \"hi\"\nplease \\o/".to_string(),
+            record: Value::Null,
+        };
+        assert_eq!(
+            event.to_fact(),
+            r#"_event("mention", "at://did:plc:a/app.bsky.feed.post/1", "did:plc:a", "say \"hi\" please \\o/")"#
+        );
+    }
+
+    #[test]
+    fn test_event_from_cache_update() {
+        let fact = |source: Option<&str>| winter_atproto::Fact {
+            predicate: "met".to_string(),
+            args: vec!["did:plc:a".to_string()],
+            confidence: None,
+            source: source.map(String::from),
+            supersedes: None,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            expires_at: None,
+            deleted_at: None,
+        };
+
+        let event = TriggerEvent::from_cache_update(&CacheUpdate::FactCreated {
+            rkey: "f1".to_string(),
+            fact: fact(None),
+        })
+        .unwrap();
+        assert_eq!(event.kind, "fact");
+        assert_eq!(event.subject, "f1");
+        assert_eq!(event.text, "met");
+
+        assert!(
+            TriggerEvent::from_cache_update(&CacheUpdate::FactCreated {
+                rkey: "f2".to_string(),
+                fact: fact(Some("trigger:greet")),
+            })
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_references_event() {
+        assert!(references_event(r#"_event("mention", U, A, _)"#, None));
+        assert!(references_event(
+            "from_friend(U)",
+            Some("from_friend(U) :- _event(_, U, A, _), follows(_, A, _).")
+        ));
+        assert!(!references_event("follows(Self, X, _)", None));
+        assert!(!references_event("_event_log(X)", None));
+    }
+
+    #[test]
+    fn test_resolve_action_substitutes_all_fields() {
+        let tuple = vec!["did:plc:a".to_string(), "social".to_string()];
+        let action = TriggerAction::CreateFact {
+            predicate: "met".to_string(),
+            args: vec!["$0".to_string()],
+            tags: vec!["$1".to_string()],
+        };
+        assert_eq!(
+            resolve_action(&action, &tuple),
+            TriggerAction::CreateFact {
+                predicate: "met".to_string(),
+                args: vec!["did:plc:a".to_string()],
+                tags: vec!["social".to_string()],
+            }
+        );
+    }
+
+    fn reply_event(text: &str) -> TriggerEvent {
+        TriggerEvent {
+            kind: "reply".to_string(),
+            subject: "at://did:plc:a/app.bsky.feed.post/2".to_string(),
+            author: "did:plc:a".to_string(),
+            text: text.to_string(),
+            record: json!({
+                "reason": "reply",
+                "text": text,
+                "parent": { "uri": "at://did:plc:me/app.bsky.feed.post/1", "cid": "c1" },
+                "facets": [
+                    { "features": [{ "$type": "app.bsky.richtext.facet#tag", "tag": "winter" }] }
+                ],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_regex_filter() {
+        let filter = TriggerFilter::TextMatches {
+            pattern: r"(?i)\bwinter\b".to_string(),
+        };
+        assert!(filter_matches(
+            &filter,
+            &reply_event("hey Winter, how are you?")
+        ));
+        assert!(!filter_matches(&filter, &reply_event("wintertime is cold")));
+    }
+
+    #[test]
+    fn test_json_path_filter_over_nested_record() {
+        let event = reply_event("hello");
+
+        let parent_is_mine = TriggerFilter::JsonPath {
+            path: "$.parent.uri".to_string(),
+            equals: None,
+            pattern: Some("^at://did:plc:me/".to_string()),
+        };
+        assert!(filter_matches(&parent_is_mine, &event));
+
+        let tagged = TriggerFilter::JsonPath {
+            path: "$.facets[0].features[0].tag".to_string(),
+            equals: Some(json!("winter")),
+            pattern: None,
+        };
+        assert!(filter_matches(&tagged, &event));
+
+        let missing = TriggerFilter::JsonPath {
+            path: "$.facets[3].features[0].tag".to_string(),
+            equals: None,
+            pattern: None,
+        };
+        assert!(!filter_matches(&missing, &event));
+
+        let wrong_value = TriggerFilter::JsonPath {
+            path: "$.parent.cid".to_string(),
+            equals: Some(json!("c2")),
+            pattern: None,
+        };
+        assert!(!filter_matches(&wrong_value, &event));
+    }
+
+    #[test]
+    fn test_combined_filters() {
+        let mentions_winter = TriggerFilter::TextMatches {
+            pattern: "winter".to_string(),
+        };
+        let is_reply = TriggerFilter::JsonPath {
+            path: "$.reason".to_string(),
+            equals: Some(json!("reply")),
+            pattern: None,
+        };
+        let both = TriggerFilter::All {
+            filters: vec![mentions_winter.clone(), is_reply.clone()],
+        };
+        let either = TriggerFilter::Any {
+            filters: vec![mentions_winter, is_reply],
+        };
+
+        assert!(filter_matches(&both, &reply_event("hi winter")));
+        assert!(!filter_matches(&both, &reply_event("hi there")));
+        assert!(filter_matches(&either, &reply_event("hi there")));
+        assert!(!passes_filter(Some(&both), None));
+        assert!(passes_filter(None, None));
+    }
+
+    #[test]
+    fn test_parse_trigger_filter_validates() {
+        let filter = parse_trigger_filter(&json!({
+            "type": "any",
+            "filters": [
+                { "type": "text_matches", "pattern": "^gm" },
+                { "type": "json_path", "path": "$.parent.uri" }
+            ]
+        }))
+        .unwrap();
+        assert!(matches!(filter, TriggerFilter::Any { ref filters } if filters.len() == 2));
+
+        let bad_regex =
+            parse_trigger_filter(&json!({ "type": "text_matches", "pattern": "(unclosed" }));
+        assert!(bad_regex.unwrap_err().contains("invalid regex"));
+
+        let nested_bad_regex = parse_trigger_filter(&json!({
+            "type": "all",
+            "filters": [{ "type": "json_path", "path": "$.text", "pattern": "[" }]
+        }));
+        assert!(nested_bad_regex.is_err());
+
+        assert!(
+            parse_trigger_filter(&json!({ "type": "json_path", "path": "parent.uri" })).is_err()
+        );
+        assert!(
+            parse_trigger_filter(&json!({ "type": "json_path", "path": "$.facets[x]" })).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_json_path() {
+        assert_eq!(
+            parse_json_path("$.facets[0].features").unwrap(),
+            vec![
+                PathSegment::Field("facets".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Field("features".to_string()),
+            ]
+        );
+        assert!(parse_json_path("$").unwrap().is_empty());
+        assert!(parse_json_path("$.a..b").is_err());
+        assert!(parse_json_path("$.a[0").is_err());
+    }
+
+    #[test]
+    fn test_filter_makes_event_trigger() {
+        let mut trigger = mention_trigger();
+        trigger.condition = "follows(Self, X, _)".to_string();
+        assert!(!is_event_trigger(&trigger));
+
+        trigger.filter = Some(TriggerFilter::TextMatches {
+            pattern: "gm".to_string(),
+        });
+        assert!(is_event_trigger(&trigger));
+    }
+
+    fn mention_trigger() -> Trigger {
+        Trigger {
+            name: "greet_mentions".to_string(),
+            description: "Queue a note for each mention".to_string(),
+            condition: r#"_event("mention", U, A, _)"#.to_string(),
+            condition_rules: None,
+            action: TriggerAction::CreateInboxItem {
+                message: "mentioned by $1 in $0".to_string(),
+            },
+            enabled: true,
+            args: Vec::new(),
+            filter: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn event(kind: &str) -> TriggerEvent {
+        TriggerEvent {
+            kind: kind.to_string(),
+            subject: "at://did:plc:a/app.bsky.feed.post/1".to_string(),
+            author: "did:plc:a".to_string(),
+            text: "hello".to_string(),
+            record: Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_matching_event_produces_action() {
+        let datalog = DatalogCache::new_temp().unwrap();
+
+        let matches = match_trigger(&datalog, &mention_trigger(), Some(&event("mention")))
+            .await
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].1,
+            TriggerAction::CreateInboxItem {
+                message: "mentioned by did:plc:a in at://did:plc:a/app.bsky.feed.post/1"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_matching_event_produces_nothing() {
+        let datalog = DatalogCache::new_temp().unwrap();
+
+        let matches = match_trigger(&datalog, &mention_trigger(), Some(&event("reply")))
+            .await
+            .unwrap();
+
+        assert!(matches.is_empty());
+    }
 }
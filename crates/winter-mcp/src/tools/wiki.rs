@@ -11,9 +11,12 @@ static WIKI_REF_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\[\[([^\]|]+?)(?:\|([^\]]+))?\]\]").unwrap());
 
 use crate::protocol::{CallToolResult, ToolDefinition};
-use winter_atproto::{Tid, WikiEntry, WikiLink, WIKI_ENTRY_COLLECTION, WIKI_LINK_COLLECTION};
+use winter_atproto::{
+    AtUri, Tid, WIKI_ENTRY_COLLECTION, WIKI_LINK_COLLECTION, WikiEntry, WikiLink,
+    unresolved_wiki_slug, unresolved_wiki_target,
+};
 
-use super::{ToolMeta, ToolState, truncate_for_summary};
+use super::{ToolMeta, ToolState, changes, truncate_for_summary};
 
 /// Maximum content size (100KB).
 const MAX_CONTENT_SIZE: usize = 100 * 1024;
@@ -112,7 +115,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
     vec![
         ToolDefinition {
             name: "create_wiki_entry".to_string(),
-            description: "Create a new wiki entry. Validates slug uniqueness and auto-creates WikiLink records from [[wiki-link]] syntax in content.".to_string(),
+            description: "Create a new wiki entry. Rejects taken slugs unless auto_suffix is set, and auto-creates WikiLink records from [[wiki-link]] syntax in content. References to slugs with no entry yet become red links that resolve once the entry exists.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -150,6 +153,10 @@ pub fn definitions() -> Vec<ToolDefinition> {
                     "supersedes": {
                         "type": "string",
                         "description": "AT URI of the previous version of this entry"
+                    },
+                    "auto_suffix": {
+                        "type": "boolean",
+                        "description": "If the slug is taken, use the first free slug-2, slug-3, ... instead of failing. Default: false"
                     }
                 },
                 "required": ["title", "slug", "content"]
@@ -157,7 +164,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "update_wiki_entry".to_string(),
-            description: "Update an existing wiki entry. Only provided fields are changed. Reconciles WikiLink records from [[wiki-link]] syntax changes.".to_string(),
+            description: "Update an existing wiki entry. Only provided fields are changed. Reconciles WikiLink records with the [[wiki-link]] syntax in the content, creating links for new references and deleting links for removed ones.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -230,7 +237,7 @@ pub fn definitions() -> Vec<ToolDefinition> {
         },
         ToolDefinition {
             name: "get_wiki_entry_by_slug".to_string(),
-            description: "Resolve a slug or alias to a wiki entry. Checks both slugs and aliases.".to_string(),
+            description: "Resolve a slug or alias to a wiki entry. Slug matches take precedence over aliases; if several entries share the slug, the newest is returned and the others are listed in `duplicates`.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -428,22 +435,33 @@ pub async fn create_wiki_entry(
         .and_then(|v| v.as_str())
         .map(String::from);
 
-    // Check slug uniqueness against cache
-    if let Some(ref cache) = state.cache {
-        for (_, cached) in cache.list_wiki_entries() {
-            if cached.value.slug == slug {
-                return CallToolResult::error(format!(
-                    "Slug '{}' already in use by entry '{}'",
-                    slug, cached.value.title
-                ));
-            }
-        }
-    }
+    let auto_suffix = arguments
+        .get("auto_suffix")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // Check slug uniqueness
+    let existing = match load_entries(state).await {
+        Ok(e) => e,
+        Err(result) => return result,
+    };
+    let requested_slug = slug;
+    let entries = || existing.iter().map(|(_, e)| e);
+    let slug = if auto_suffix {
+        unique_slug(slug, |s| slug_owner(entries(), s).is_some())
+    } else if let Some(other) = slug_owner(entries(), slug) {
+        return CallToolResult::error(format!(
+            "Slug '{}' already in use by entry '{}' (pass auto_suffix to pick a free slug)",
+            slug, other.title
+        ));
+    } else {
+        slug.to_string()
+    };
 
     let now = Utc::now();
     let entry = WikiEntry {
         title: title.to_string(),
-        slug: slug.to_string(),
+        slug: slug.clone(),
         aliases,
         summary,
         content: content.to_string(),
@@ -470,8 +488,7 @@ pub async fn create_wiki_entry(
             }
 
             // Auto-create wiki links from [[wiki-link]] syntax
-            let links_created =
-                auto_create_wiki_links(state, &entry_uri, &entry.content).await;
+            let links = sync_wiki_links(state, &entry_uri, &entry).await;
 
             CallToolResult::success(
                 json!({
@@ -480,8 +497,10 @@ pub async fn create_wiki_entry(
                     "cid": response.cid,
                     "title": title,
                     "slug": slug,
+                    "requested_slug": (slug != requested_slug).then_some(requested_slug),
                     "status": status,
-                    "links_created": links_created,
+                    "links_created": links.created,
+                    "links_resolved": links.retargeted,
                 })
                 .to_string(),
             )
@@ -509,7 +528,7 @@ pub async fn update_wiki_entry(
         Err(e) => return CallToolResult::error(format!("Failed to get wiki entry: {}", e)),
     };
 
-    let old_content = entry.content.clone();
+    let before = entry.clone();
 
     // Apply updates
     if let Some(title) = arguments.get("title").and_then(|v| v.as_str()) {
@@ -571,15 +590,8 @@ pub async fn update_wiki_entry(
                 cache.upsert_wiki_entry(rkey.to_string(), entry.clone(), response.cid.clone());
             }
 
-            // Reconcile wiki links if content changed
-            let mut links_created = 0;
-            let mut links_deleted = 0;
-            if entry.content != old_content {
-                let (created, deleted) =
-                    reconcile_wiki_links(state, &entry_uri, &old_content, &entry.content).await;
-                links_created = created;
-                links_deleted = deleted;
-            }
+            // Reconcile wiki links with the [[wiki-link]] syntax in the content
+            let links = sync_wiki_links(state, &entry_uri, &entry).await;
 
             CallToolResult::success(
                 json!({
@@ -589,8 +601,10 @@ pub async fn update_wiki_entry(
                     "title": entry.title,
                     "slug": entry.slug,
                     "status": entry.status,
-                    "links_created": links_created,
-                    "links_deleted": links_deleted,
+                    "links_created": links.created,
+                    "links_deleted": links.deleted,
+                    "links_retargeted": links.retargeted,
+                    "changes": changes::record_changes(&before, &entry, &["lastUpdated"]),
                 })
                 .to_string(),
             )
@@ -698,68 +712,30 @@ pub async fn get_wiki_entry_by_slug(
         None => return CallToolResult::error("Missing required parameter: slug"),
     };
 
-    // Search cache for matching slug or alias
-    if let Some(ref cache) = state.cache {
-        if cache.state() == winter_atproto::SyncState::Live {
-            for (rkey, cached) in cache.list_wiki_entries() {
-                if cached.value.slug == slug
-                    || cached.value.aliases.iter().any(|a| a == slug)
-                {
-                    return CallToolResult::success(
-                        json!({
-                            "rkey": rkey,
-                            "title": cached.value.title,
-                            "slug": cached.value.slug,
-                            "aliases": cached.value.aliases,
-                            "summary": cached.value.summary,
-                            "content": cached.value.content,
-                            "status": cached.value.status,
-                            "supersedes": cached.value.supersedes,
-                            "tags": cached.value.tags,
-                            "created_at": cached.value.created_at.to_rfc3339(),
-                            "last_updated": cached.value.last_updated.to_rfc3339(),
-                        })
-                        .to_string(),
-                    );
-                }
-            }
-            return CallToolResult::error(format!("No wiki entry found for slug or alias '{}'", slug));
-        }
-    }
+    let entries = match load_entries(state).await {
+        Ok(e) => e,
+        Err(result) => return result,
+    };
 
-    // Fall back to listing all records via HTTP
-    match state
-        .atproto
-        .list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION)
-        .await
-    {
-        Ok(records) => {
-            for item in &records {
-                if item.value.slug == slug
-                    || item.value.aliases.iter().any(|a| a == slug)
-                {
-                    let rkey = item.uri.split('/').next_back().unwrap_or("");
-                    return CallToolResult::success(
-                        json!({
-                            "rkey": rkey,
-                            "title": item.value.title,
-                            "slug": item.value.slug,
-                            "aliases": item.value.aliases,
-                            "summary": item.value.summary,
-                            "content": item.value.content,
-                            "status": item.value.status,
-                            "supersedes": item.value.supersedes,
-                            "tags": item.value.tags,
-                            "created_at": item.value.created_at.to_rfc3339(),
-                            "last_updated": item.value.last_updated.to_rfc3339(),
-                        })
-                        .to_string(),
-                    );
-                }
-            }
-            CallToolResult::error(format!("No wiki entry found for slug or alias '{}'", slug))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to search wiki entries: {}", e)),
+    match find_entry_by_slug(&entries, slug) {
+        Some((rkey, entry, duplicates)) => CallToolResult::success(
+            json!({
+                "rkey": rkey,
+                "title": entry.title,
+                "slug": entry.slug,
+                "aliases": entry.aliases,
+                "summary": entry.summary,
+                "content": entry.content,
+                "status": entry.status,
+                "supersedes": entry.supersedes,
+                "tags": entry.tags,
+                "created_at": entry.created_at.to_rfc3339(),
+                "last_updated": entry.last_updated.to_rfc3339(),
+                "duplicates": duplicates,
+            })
+            .to_string(),
+        ),
+        None => CallToolResult::error(format!("No wiki entry found for slug or alias '{}'", slug)),
     }
 }
 
@@ -1092,163 +1068,340 @@ async fn fetch_links_via_http(
     }
 }
 
-/// Resolve a local slug to an AT URI by searching the cache.
-fn resolve_local_slug(state: &ToolState, slug: &str) -> Option<String> {
-    let cache = state.cache.as_ref()?;
-    let did_future = state.atproto.did();
-
-    // Synchronous cache lookup
-    for (rkey, cached) in cache.list_wiki_entries() {
-        if cached.value.slug == slug || cached.value.aliases.iter().any(|a| a == slug) {
-            // We need the DID but can't easily await here. Use a blocking approach.
-            // Since we're already inside an async context, use tokio::task::block_in_place
-            let did = tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current()
-                    .block_on(did_future)
-            });
-            if let Some(did) = did {
-                return Some(format!("at://{}/{}/{}", did, WIKI_ENTRY_COLLECTION, rkey));
-            }
-            return None;
+/// Load all wiki entries, from the cache when live and over HTTP otherwise.
+async fn load_entries(state: &ToolState) -> Result<Vec<(String, WikiEntry)>, CallToolResult> {
+    if let Some(ref cache) = state.cache {
+        if cache.state() == winter_atproto::SyncState::Live {
+            return Ok(cache
+                .list_wiki_entries()
+                .into_iter()
+                .map(|(rkey, cached)| (rkey, cached.value))
+                .collect());
         }
     }
-    None
+    fetch_entries_via_http(state).await
+}
+
+/// Ordering under which the newest of several entries sharing a slug wins.
+///
+/// Entries are compared by creation time, then by record key (TIDs sort by
+/// time, so this also breaks ties between entries created in the same instant).
+fn entry_age(rkey: &str, entry: &WikiEntry) -> (chrono::DateTime<Utc>, String) {
+    (entry.created_at, rkey.to_string())
+}
+
+/// Find the entry a slug or alias refers to.
+///
+/// Slug matches take precedence over alias matches. When several entries
+/// share the slug (or, failing that, the alias), the newest wins. Returns the
+/// winning entry and the record keys of the other matches.
+fn find_entry_by_slug<'a>(
+    entries: &'a [(String, WikiEntry)],
+    slug: &str,
+) -> Option<(&'a str, &'a WikiEntry, Vec<&'a str>)> {
+    let by_slug: Vec<&(String, WikiEntry)> =
+        entries.iter().filter(|(_, e)| e.slug == slug).collect();
+    let mut matches = if by_slug.is_empty() {
+        entries
+            .iter()
+            .filter(|(_, e)| e.aliases.iter().any(|a| a == slug))
+            .collect()
+    } else {
+        by_slug
+    };
+
+    matches.sort_by_key(|(rkey, entry)| std::cmp::Reverse(entry_age(rkey, entry)));
+    let mut matches = matches.into_iter();
+    let (rkey, entry) = matches.next()?;
+    let others = matches.map(|(rkey, _)| rkey.as_str()).collect();
+    Some((rkey.as_str(), entry, others))
 }
 
-/// Auto-create wiki links from `[[wiki-link]]` syntax in content.
+/// The entry already using `slug`, if any. New entries must not reuse a slug.
+pub fn slug_owner<'a>(
+    entries: impl IntoIterator<Item = &'a WikiEntry>,
+    slug: &str,
+) -> Option<&'a WikiEntry> {
+    entries.into_iter().find(|e| e.slug == slug)
+}
+
+/// First of `slug`, `slug-2`, `slug-3`, ... that isn't taken.
 ///
-/// For each local `[[slug]]` reference, resolves the slug and creates a WikiLink record.
-/// Cross-user references (`[[handle/slug]]`, `[[did/slug]]`) are not auto-resolved.
-/// Returns the number of links created.
-async fn auto_create_wiki_links(
+/// The base is shortened if needed so the suffixed slug stays within
+/// [`MAX_SLUG_LENGTH`].
+fn unique_slug(slug: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(slug) {
+        return slug.to_string();
+    }
+
+    (2..)
+        .map(|n| {
+            let suffix = format!("-{}", n);
+            let base = &slug[..slug.len().min(MAX_SLUG_LENGTH - suffix.len())];
+            format!("{}{}", base.trim_end_matches('-'), suffix)
+        })
+        .find(|candidate| !taken(candidate))
+        .expect("some suffix is free")
+}
+
+/// Load all wiki entries and links, from the cache when live and over HTTP otherwise.
+async fn load_entries_and_links(
     state: &ToolState,
-    source_uri: &str,
-    content: &str,
-) -> usize {
-    let refs = parse_wiki_refs(content);
-    let mut created = 0;
+) -> Result<(Vec<(String, WikiEntry)>, Vec<(String, WikiLink)>), CallToolResult> {
+    if let Some(ref cache) = state.cache {
+        if cache.state() == winter_atproto::SyncState::Live {
+            let entries = cache
+                .list_wiki_entries()
+                .into_iter()
+                .map(|(rkey, cached)| (rkey, cached.value))
+                .collect();
+            let links = cache
+                .list_wiki_links()
+                .into_iter()
+                .map(|(rkey, cached)| (rkey, cached.value))
+                .collect();
+            return Ok((entries, links));
+        }
+    }
 
-    for (wiki_ref, _display) in &refs {
-        if let WikiRef::Local { slug } = wiki_ref {
-            if let Some(target_uri) = resolve_local_slug(state, slug) {
-                let link = WikiLink {
-                    source: source_uri.to_string(),
-                    target: target_uri,
-                    link_type: "related-to".to_string(),
-                    source_anchor: None,
-                    target_anchor: None,
-                    context: None,
-                    created_at: Utc::now(),
-                };
-
-                let rkey = Tid::now().to_string();
-                if let Ok(response) = state
-                    .atproto
-                    .create_record(WIKI_LINK_COLLECTION, Some(&rkey), &link)
-                    .await
-                {
-                    if let Some(cache) = &state.cache {
-                        cache.insert_wiki_link(rkey, link, response.cid);
-                    }
-                    created += 1;
-                }
-            }
+    Ok((
+        fetch_entries_via_http(state).await?,
+        fetch_links_via_http(state).await?,
+    ))
+}
+
+/// Map every slug and alias to the AT URI of its entry.
+///
+/// Slugs take precedence over aliases when both name the same string, and
+/// the newest entry wins among duplicates, matching [`find_entry_by_slug`].
+fn slug_targets(did: &str, entries: &[(String, WikiEntry)]) -> HashMap<String, String> {
+    let uri = |rkey: &str| format!("at://{}/{}/{}", did, WIKI_ENTRY_COLLECTION, rkey);
+
+    // Oldest first, so the newest of any duplicates is inserted last and wins
+    let mut entries: Vec<&(String, WikiEntry)> = entries.iter().collect();
+    entries.sort_by_key(|(rkey, entry)| entry_age(rkey, entry));
+
+    let mut targets = HashMap::new();
+    for (rkey, entry) in &entries {
+        for alias in &entry.aliases {
+            targets.insert(alias.clone(), uri(rkey));
         }
     }
+    for (rkey, entry) in &entries {
+        targets.insert(entry.slug.clone(), uri(rkey));
+    }
+    targets
+}
 
-    created
+/// Slug of a link created from `[[slug]]` syntax.
+///
+/// Auto-created links record their reference in `context`, which is how they
+/// are told apart from links made with `create_wiki_link`.
+fn auto_link_slug(link: &WikiLink) -> Option<&str> {
+    link.context
+        .as_deref()?
+        .strip_prefix("[[")?
+        .strip_suffix("]]")
+}
+
+/// Changes that bring a source's auto-created links in line with its content.
+#[derive(Debug, Default)]
+struct WikiLinkPlan {
+    /// Links to create.
+    create: Vec<WikiLink>,
+    /// Record keys of links whose reference is gone from the content.
+    delete: Vec<String>,
+    /// Links whose slug now resolves to a different target, with their new value.
+    retarget: Vec<(String, WikiLink)>,
 }
 
-/// Reconcile wiki links when content changes.
+/// Plan the auto-created links for `source_uri` given its current content.
 ///
-/// Computes the diff between old and new wiki refs, deletes stale links and creates new ones.
-/// Returns (links_created, links_deleted).
-async fn reconcile_wiki_links(
-    state: &ToolState,
+/// Every distinct local `[[slug]]` reference gets one link. References to
+/// slugs without an entry produce red links (see [`unresolved_wiki_target`]),
+/// and existing links are retargeted when their slug's entry appears or goes
+/// away. Cross-user references are not linked.
+fn plan_wiki_links(
     source_uri: &str,
-    old_content: &str,
-    new_content: &str,
-) -> (usize, usize) {
-    let old_refs: Vec<String> = parse_wiki_refs(old_content)
-        .into_iter()
-        .filter_map(|(r, _)| {
-            if let WikiRef::Local { slug } = r {
-                Some(slug)
-            } else {
-                None
+    content: &str,
+    existing: &[(String, WikiLink)],
+    targets: &HashMap<String, String>,
+) -> WikiLinkPlan {
+    let mut wanted: Vec<String> = Vec::new();
+    for (wiki_ref, _display) in parse_wiki_refs(content) {
+        if let WikiRef::Local { slug } = wiki_ref {
+            if !wanted.contains(&slug) {
+                wanted.push(slug);
             }
-        })
-        .collect();
+        }
+    }
 
-    let new_refs: Vec<String> = parse_wiki_refs(new_content)
-        .into_iter()
-        .filter_map(|(r, _)| {
-            if let WikiRef::Local { slug } = r {
-                Some(slug)
-            } else {
-                None
-            }
+    let target_for = |slug: &str| {
+        targets
+            .get(slug)
+            .cloned()
+            .unwrap_or_else(|| unresolved_wiki_target(slug))
+    };
+
+    let mut plan = WikiLinkPlan::default();
+    let mut linked: Vec<&str> = Vec::new();
+
+    for (rkey, link) in existing {
+        if link.source != source_uri {
+            continue;
+        }
+        let Some(slug) = auto_link_slug(link) else {
+            continue;
+        };
+
+        if !wanted.iter().any(|w| w == slug) || linked.contains(&slug) {
+            plan.delete.push(rkey.clone());
+            continue;
+        }
+        linked.push(slug);
+
+        let target = target_for(slug);
+        if link.target != target {
+            plan.retarget.push((
+                rkey.clone(),
+                WikiLink {
+                    target,
+                    ..link.clone()
+                },
+            ));
+        }
+    }
+
+    for slug in wanted.iter().filter(|s| !linked.contains(&s.as_str())) {
+        plan.create.push(WikiLink {
+            source: source_uri.to_string(),
+            target: target_for(slug),
+            link_type: "related-to".to_string(),
+            source_anchor: None,
+            target_anchor: None,
+            context: Some(format!("[[{}]]", slug)),
+            created_at: Utc::now(),
+        });
+    }
+
+    plan
+}
+
+/// Red links from any source that `entry` (at `entry_uri`) now resolves.
+fn plan_red_link_resolution(
+    entry_uri: &str,
+    entry: &WikiEntry,
+    existing: &[(String, WikiLink)],
+) -> Vec<(String, WikiLink)> {
+    existing
+        .iter()
+        .filter(|(_, link)| {
+            unresolved_wiki_slug(&link.target)
+                .is_some_and(|slug| slug == entry.slug || entry.aliases.iter().any(|a| a == slug))
         })
-        .collect();
+        .map(|(rkey, link)| {
+            (
+                rkey.clone(),
+                WikiLink {
+                    target: entry_uri.to_string(),
+                    ..link.clone()
+                },
+            )
+        })
+        .collect()
+}
 
-    // Find removed and added slugs
-    let removed: Vec<&String> = old_refs.iter().filter(|s| !new_refs.contains(s)).collect();
-    let added: Vec<&String> = new_refs.iter().filter(|s| !old_refs.contains(s)).collect();
+/// Counts of link records changed by [`sync_wiki_links`].
+#[derive(Debug, Default)]
+struct WikiLinkChanges {
+    created: usize,
+    deleted: usize,
+    retargeted: usize,
+}
 
-    let mut deleted = 0;
-    let mut created = 0;
+/// Sync the `[[slug]]` links of a just-written entry and resolve red links
+/// that point at its slug or aliases.
+///
+/// Link writes are best effort: failures are logged and the entry write
+/// still succeeds.
+async fn sync_wiki_links(state: &ToolState, entry_uri: &str, entry: &WikiEntry) -> WikiLinkChanges {
+    let mut changes = WikiLinkChanges::default();
+
+    let (entries, links) = match load_entries_and_links(state).await {
+        Ok(loaded) => loaded,
+        Err(_) => {
+            tracing::warn!(uri = %entry_uri, "failed to load wiki records, skipping link sync");
+            return changes;
+        }
+    };
+    let did = match AtUri::parse(entry_uri) {
+        Ok(uri) => uri.did,
+        Err(_) => return changes,
+    };
 
-    // Delete links for removed references
-    if let Some(ref cache) = state.cache {
-        for slug in &removed {
-            if let Some(target_uri) = resolve_local_slug(state, slug) {
-                // Find existing link with this source+target
-                for (rkey, cached) in cache.list_wiki_links() {
-                    if cached.value.source == source_uri && cached.value.target == target_uri {
-                        if state
-                            .atproto
-                            .delete_record(WIKI_LINK_COLLECTION, &rkey)
-                            .await
-                            .is_ok()
-                        {
-                            cache.delete_wiki_link(&rkey);
-                            deleted += 1;
-                        }
-                        break;
-                    }
+    let plan = plan_wiki_links(
+        entry_uri,
+        &entry.content,
+        &links,
+        &slug_targets(&did, &entries),
+    );
+    let mut retarget = plan.retarget;
+    retarget.extend(
+        plan_red_link_resolution(entry_uri, entry, &links)
+            .into_iter()
+            .filter(|(rkey, _)| !retarget.iter().any(|(r, _)| r == rkey)),
+    );
+
+    for rkey in &plan.delete {
+        match state
+            .atproto
+            .delete_record(WIKI_LINK_COLLECTION, rkey)
+            .await
+        {
+            Ok(()) => {
+                if let Some(cache) = &state.cache {
+                    cache.delete_wiki_link(rkey);
                 }
+                changes.deleted += 1;
             }
+            Err(e) => tracing::warn!(rkey = %rkey, error = %e, "failed to delete wiki link"),
         }
     }
 
-    // Create links for added references
-    for slug in &added {
-        if let Some(target_uri) = resolve_local_slug(state, slug) {
-            let link = WikiLink {
-                source: source_uri.to_string(),
-                target: target_uri,
-                link_type: "related-to".to_string(),
-                source_anchor: None,
-                target_anchor: None,
-                context: None,
-                created_at: Utc::now(),
-            };
-
-            let rkey = Tid::now().to_string();
-            if let Ok(response) = state
-                .atproto
-                .create_record(WIKI_LINK_COLLECTION, Some(&rkey), &link)
-                .await
-            {
+    for (rkey, link) in retarget {
+        match state
+            .atproto
+            .put_record(WIKI_LINK_COLLECTION, &rkey, &link)
+            .await
+        {
+            Ok(response) => {
                 if let Some(cache) = &state.cache {
                     cache.insert_wiki_link(rkey, link, response.cid);
                 }
-                created += 1;
+                changes.retargeted += 1;
             }
+            Err(e) => tracing::warn!(rkey = %rkey, error = %e, "failed to retarget wiki link"),
         }
     }
 
-    (created, deleted)
+    for link in plan.create {
+        let rkey = Tid::now().to_string();
+        match state
+            .atproto
+            .create_record(WIKI_LINK_COLLECTION, Some(&rkey), &link)
+            .await
+        {
+            Ok(response) => {
+                if let Some(cache) = &state.cache {
+                    cache.insert_wiki_link(rkey, link, response.cid);
+                }
+                changes.created += 1;
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to create wiki link"),
+        }
+    }
+
+    changes
 }
 
 #[cfg(test)]
@@ -1363,4 +1516,264 @@ mod tests {
         let refs = parse_wiki_refs("No wiki links here.");
         assert!(refs.is_empty());
     }
+
+    const SOURCE: &str = "at://did:plc:test/diy.razorgirl.winter.wikiEntry/src";
+
+    fn entry(slug: &str, aliases: &[&str]) -> WikiEntry {
+        WikiEntry {
+            title: slug.to_string(),
+            slug: slug.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            summary: None,
+            content: String::new(),
+            status: "stable".to_string(),
+            supersedes: None,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            last_updated: Utc::now(),
+        }
+    }
+
+    fn targets() -> HashMap<String, String> {
+        let entries = vec![
+            ("rk-a".to_string(), entry("page-a", &["alias-a"])),
+            ("rk-b".to_string(), entry("page-b", &[])),
+        ];
+        slug_targets("did:plc:test", &entries)
+    }
+
+    /// Apply a plan to a set of links, as `sync_wiki_links` does on the PDS.
+    fn apply(links: &mut Vec<(String, WikiLink)>, plan: WikiLinkPlan) {
+        links.retain(|(rkey, _)| !plan.delete.contains(rkey));
+        for (rkey, link) in plan.retarget {
+            if let Some(existing) = links.iter_mut().find(|(r, _)| *r == rkey) {
+                existing.1 = link;
+            }
+        }
+        for link in plan.create {
+            links.push((format!("new-{}", links.len()), link));
+        }
+    }
+
+    fn linked_slugs(links: &[(String, WikiLink)]) -> Vec<(String, String)> {
+        let mut slugs: Vec<(String, String)> = links
+            .iter()
+            .filter_map(|(_, l)| Some((auto_link_slug(l)?.to_string(), l.target.clone())))
+            .collect();
+        slugs.sort();
+        slugs
+    }
+
+    fn entry_at(slug: &str, created_at: &str) -> WikiEntry {
+        WikiEntry {
+            created_at: created_at.parse().unwrap(),
+            ..entry(slug, &[])
+        }
+    }
+
+    #[test]
+    fn test_unique_slug_keeps_free_slug() {
+        assert_eq!(unique_slug("page", |_| false), "page");
+    }
+
+    #[test]
+    fn test_unique_slug_auto_suffixes() {
+        let taken = ["page", "page-2", "page-3"];
+        assert_eq!(unique_slug("page", |s| taken.contains(&s)), "page-4");
+        assert_eq!(unique_slug("other", |s| taken.contains(&s)), "other");
+    }
+
+    #[test]
+    fn test_unique_slug_respects_max_length() {
+        let long = "a".repeat(MAX_SLUG_LENGTH);
+        let slug = unique_slug(&long, |s| s == long);
+        assert_eq!(slug.len(), MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("-2"));
+        assert!(is_valid_slug(&slug));
+    }
+
+    #[test]
+    fn test_find_entry_by_slug_newest_duplicate_wins() {
+        let entries = vec![
+            ("3aaa".to_string(), entry_at("dup", "2026-01-01T00:00:00Z")),
+            ("3ccc".to_string(), entry_at("dup", "2026-03-01T00:00:00Z")),
+            ("3bbb".to_string(), entry_at("dup", "2026-02-01T00:00:00Z")),
+        ];
+
+        let (rkey, _, duplicates) = find_entry_by_slug(&entries, "dup").unwrap();
+        assert_eq!(rkey, "3ccc");
+        assert_eq!(duplicates, vec!["3bbb", "3aaa"]);
+
+        // Same answer regardless of listing order
+        let mut reversed = entries.clone();
+        reversed.reverse();
+        assert_eq!(find_entry_by_slug(&reversed, "dup").unwrap().0, "3ccc");
+
+        // Same timestamp falls back to the (time-ordered) record key
+        let tied = vec![
+            ("3bbb".to_string(), entry_at("dup", "2026-01-01T00:00:00Z")),
+            ("3aaa".to_string(), entry_at("dup", "2026-01-01T00:00:00Z")),
+        ];
+        assert_eq!(find_entry_by_slug(&tied, "dup").unwrap().0, "3bbb");
+
+        // Link resolution agrees with lookup
+        let targets = slug_targets("did:plc:test", &entries);
+        assert_eq!(
+            targets["dup"],
+            "at://did:plc:test/diy.razorgirl.winter.wikiEntry/3ccc"
+        );
+    }
+
+    #[test]
+    fn test_find_entry_by_slug_prefers_slug_over_alias() {
+        let entries = vec![
+            ("3aaa".to_string(), entry("page", &[])),
+            (
+                "3bbb".to_string(),
+                WikiEntry {
+                    created_at: Utc::now() + chrono::Duration::days(1),
+                    ..entry("other", &["page"])
+                },
+            ),
+        ];
+        assert_eq!(find_entry_by_slug(&entries, "page").unwrap().0, "3aaa");
+        assert_eq!(find_entry_by_slug(&entries, "other").unwrap().0, "3bbb");
+        assert!(find_entry_by_slug(&entries, "missing").is_none());
+    }
+
+    #[test]
+    fn test_slug_targets_prefers_slugs_over_aliases() {
+        let entries = vec![
+            ("rk-a".to_string(), entry("page-a", &["page-b"])),
+            ("rk-b".to_string(), entry("page-b", &[])),
+        ];
+        let targets = slug_targets("did:plc:test", &entries);
+        assert_eq!(
+            targets["page-b"],
+            "at://did:plc:test/diy.razorgirl.winter.wikiEntry/rk-b"
+        );
+    }
+
+    #[test]
+    fn test_plan_creates_links_for_references() {
+        let plan = plan_wiki_links(
+            SOURCE,
+            "See [[page-a]], [[alias-a|again]], [[page-a]] and [[bob.bsky.social/x]]",
+            &[],
+            &targets(),
+        );
+        assert!(plan.delete.is_empty());
+        assert!(plan.retarget.is_empty());
+
+        let mut links = Vec::new();
+        apply(&mut links, plan);
+        assert_eq!(
+            linked_slugs(&links),
+            vec![
+                (
+                    "alias-a".to_string(),
+                    "at://did:plc:test/diy.razorgirl.winter.wikiEntry/rk-a".to_string()
+                ),
+                (
+                    "page-a".to_string(),
+                    "at://did:plc:test/diy.razorgirl.winter.wikiEntry/rk-a".to_string()
+                ),
+            ]
+        );
+        assert!(links.iter().all(|(_, l)| l.source == SOURCE));
+    }
+
+    #[test]
+    fn test_plan_unresolved_slug_creates_red_link() {
+        let plan = plan_wiki_links(SOURCE, "See [[not-written-yet]]", &[], &targets());
+        assert_eq!(plan.create.len(), 1);
+        assert_eq!(plan.create[0].target, "wiki:not-written-yet");
+    }
+
+    #[test]
+    fn test_editing_content_adds_and_prunes_links() {
+        let targets = targets();
+        let mut links = Vec::new();
+        apply(
+            &mut links,
+            plan_wiki_links(SOURCE, "[[page-a]] and [[page-b]]", &links, &targets),
+        );
+        assert_eq!(links.len(), 2);
+
+        // Drop page-a, keep page-b, add a red link
+        let plan = plan_wiki_links(SOURCE, "[[page-b]] and [[page-c]]", &links, &targets);
+        assert_eq!(plan.delete.len(), 1);
+        assert_eq!(plan.create.len(), 1);
+        apply(&mut links, plan);
+        assert_eq!(
+            linked_slugs(&links),
+            vec![
+                (
+                    "page-b".to_string(),
+                    "at://did:plc:test/diy.razorgirl.winter.wikiEntry/rk-b".to_string()
+                ),
+                ("page-c".to_string(), "wiki:page-c".to_string()),
+            ]
+        );
+
+        // Unchanged content is a no-op
+        let plan = plan_wiki_links(SOURCE, "[[page-b]] and [[page-c]]", &links, &targets);
+        assert!(plan.create.is_empty() && plan.delete.is_empty() && plan.retarget.is_empty());
+
+        // Removing every reference prunes every link
+        apply(
+            &mut links,
+            plan_wiki_links(SOURCE, "no links", &links, &targets),
+        );
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn test_plan_ignores_manual_and_other_sources() {
+        let manual = WikiLink {
+            source: SOURCE.to_string(),
+            target: "at://did:plc:other/diy.razorgirl.winter.wikiEntry/x".to_string(),
+            link_type: "depends-on".to_string(),
+            source_anchor: None,
+            target_anchor: None,
+            context: None,
+            created_at: Utc::now(),
+        };
+        let other_source = WikiLink {
+            source: "at://did:plc:test/diy.razorgirl.winter.wikiEntry/other".to_string(),
+            context: Some("[[page-a]]".to_string()),
+            ..manual.clone()
+        };
+        let links = vec![
+            ("manual".to_string(), manual),
+            ("other".to_string(), other_source),
+        ];
+
+        let plan = plan_wiki_links(SOURCE, "nothing", &links, &targets());
+        assert!(plan.delete.is_empty());
+        assert!(plan.retarget.is_empty());
+    }
+
+    #[test]
+    fn test_red_links_resolve_once_entry_exists() {
+        let mut links = Vec::new();
+        apply(
+            &mut links,
+            plan_wiki_links(SOURCE, "[[page-c]]", &links, &targets()),
+        );
+        assert_eq!(links[0].1.target, "wiki:page-c");
+
+        let new_uri = "at://did:plc:test/diy.razorgirl.winter.wikiEntry/rk-c";
+        let resolved = plan_red_link_resolution(new_uri, &entry("page-c", &[]), &links);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].1.target, new_uri);
+        assert_eq!(resolved[0].1.source, SOURCE);
+
+        // The source's own sync also retargets once the slug resolves
+        let mut with_c = targets();
+        with_c.insert("page-c".to_string(), new_uri.to_string());
+        let plan = plan_wiki_links(SOURCE, "[[page-c]]", &links, &with_c);
+        assert_eq!(plan.retarget.len(), 1);
+        assert_eq!(plan.retarget[0].1.target, new_uri);
+    }
 }
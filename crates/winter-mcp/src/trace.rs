@@ -0,0 +1,83 @@
+//! Per-request trace ids.
+//!
+//! Every MCP request with an id gets a fresh trace id. The server runs the
+//! request inside [`with_trace_id`], so anything recorded while handling it
+//! (tracing spans, tool-call thoughts, thoughts from `record_thought`) can
+//! pick the id up with [`current_trace_id`] without threading it through
+//! every tool. The id is also echoed back in the response's `_meta`.
+
+use std::future::Future;
+
+use uuid::Uuid;
+
+/// Prefix of the thought tag that carries a trace id.
+pub const TRACE_TAG_PREFIX: &str = "trace:";
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Generate a new trace id.
+pub fn new_trace_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Run `future` with `trace_id` as the current trace id.
+pub async fn with_trace_id<F: Future>(trace_id: String, future: F) -> F::Output {
+    TRACE_ID.scope(trace_id, future).await
+}
+
+/// The trace id of the request being handled, if any.
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(Clone::clone).ok()
+}
+
+/// Thought tag for a trace id.
+pub fn trace_tag(trace_id: &str) -> String {
+    format!("{}{}", TRACE_TAG_PREFIX, trace_id)
+}
+
+/// Add the current trace id's tag to `tags`, if there is one and it's missing.
+pub fn tag_with_current_trace(tags: &mut Vec<String>) {
+    if let Some(trace_id) = current_trace_id() {
+        let tag = trace_tag(&trace_id);
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trace_id_scoped_to_future() {
+        assert_eq!(current_trace_id(), None);
+
+        let seen = with_trace_id("abc".to_string(), async { current_trace_id() }).await;
+        assert_eq!(seen, Some("abc".to_string()));
+
+        assert_eq!(current_trace_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_tag_with_current_trace() {
+        let mut tags = vec!["existing".to_string()];
+        tag_with_current_trace(&mut tags);
+        assert_eq!(tags, vec!["existing"]);
+
+        with_trace_id("abc".to_string(), async {
+            tag_with_current_trace(&mut tags);
+            tag_with_current_trace(&mut tags);
+        })
+        .await;
+        assert_eq!(tags, vec!["existing", "trace:abc"]);
+    }
+
+    #[test]
+    fn test_new_trace_ids_are_unique() {
+        assert_ne!(new_trace_id(), new_trace_id());
+        assert_eq!(new_trace_id().len(), 32);
+    }
+}
@@ -9,7 +9,9 @@ use tokio::sync::{Notify, RwLock, watch};
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
-use winter_atproto::{AtUri, AtprotoClient, CacheUpdate, JOB_COLLECTION, RepoCache, Tid};
+use winter_atproto::{
+    AtUri, AtprotoClient, CacheUpdate, JOB_COLLECTION, RepoCache, Tid, find_job_dependency_cycle,
+};
 
 use crate::{Job, JobSchedule, JobStatus, SchedulerError};
 
@@ -78,6 +80,8 @@ impl Scheduler {
                 next_run: record.value.next_run.unwrap_or_else(Utc::now),
                 failure_count: record.value.failure_count,
                 created_at: record.value.created_at,
+                run_requested_at: record.value.run_requested_at,
+                depends_on: record.value.depends_on,
             };
             jobs.push(job);
         }
@@ -171,18 +175,35 @@ impl Scheduler {
             next_run: record.next_run.unwrap_or_else(Utc::now),
             failure_count: record.failure_count,
             created_at: record.created_at,
+            run_requested_at: record.run_requested_at,
+            depends_on: record.depends_on.clone(),
         }
     }
 
     /// Add a new job.
+    ///
+    /// Fails if a job with the same rkey exists or if its dependencies would
+    /// form a cycle.
     #[tracing::instrument(skip(self), fields(rkey = %job.rkey, name = %job.name))]
     pub async fn add_job(&self, job: Job) -> Result<(), SchedulerError> {
-        // Check for duplicates
+        // Check for duplicates and dependency cycles
         {
             let jobs = self.jobs.read().await;
             if jobs.iter().any(|j| j.rkey == job.rkey) {
                 return Err(SchedulerError::JobExists(job.rkey));
             }
+
+            let cycle = find_job_dependency_cycle(&job.rkey, &job.depends_on, |rkey| {
+                jobs.iter()
+                    .find(|j| j.rkey == rkey)
+                    .map(|j| j.depends_on.clone())
+            });
+            if let Some(cycle) = cycle {
+                return Err(SchedulerError::InvalidConfig(format!(
+                    "dependency cycle: {}",
+                    cycle.join(" -> ")
+                )));
+            }
         }
 
         // Create record in PDS
@@ -200,6 +221,8 @@ impl Scheduler {
             next_run: Some(job.next_run),
             failure_count: 0,
             created_at: job.created_at,
+            run_requested_at: None,
+            depends_on: job.depends_on.clone(),
         };
 
         self.client
@@ -287,6 +310,33 @@ impl Scheduler {
         Ok(true)
     }
 
+    /// Request an immediate run of a job, independent of its schedule.
+    ///
+    /// The run doesn't move `next_run`, so recurring jobs keep their cadence.
+    /// Returns false if the job was not found.
+    pub async fn run_now(&self, rkey: &str) -> Result<bool, SchedulerError> {
+        if !self.request_run(rkey).await {
+            return Ok(false);
+        }
+        self.wake();
+
+        self.sync_job_to_pds(rkey).await?;
+        info!(rkey, "requested immediate job run");
+        Ok(true)
+    }
+
+    /// Mark a job as requested to run immediately in local state.
+    async fn request_run(&self, rkey: &str) -> bool {
+        let mut jobs = self.jobs.write().await;
+        match jobs.iter_mut().find(|j| j.rkey == rkey) {
+            Some(job) => {
+                job.run_requested_at = Some(Utc::now());
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Delete all jobs with the given name except the most recent one.
     /// Returns the number of jobs deleted.
     pub async fn deduplicate_jobs_by_name(&self, name: &str) -> Result<usize, SchedulerError> {
@@ -393,15 +443,24 @@ impl Scheduler {
             .collect()
     }
 
-    /// Take the first due job and mark it as running.
+    /// Take the first due (or run-now requested) job and mark it as running.
     ///
     /// Returns `None` if no jobs are due.
     /// This operation is atomic - the job is marked as running while holding the lock.
+    /// The returned job keeps `run_requested_at` only for out-of-schedule runs;
+    /// a request on a job that is due anyway is satisfied by the regular run.
+    /// Scheduled runs wait for the job's dependencies; requested runs don't.
     pub async fn take_due_job(&self) -> Option<Job> {
         let mut jobs = self.jobs.write().await;
 
         // Find and claim the first due job atomically
-        if let Some(job) = jobs.iter_mut().find(|j| j.is_due()) {
+        let index = jobs
+            .iter()
+            .position(|j| j.is_run_requested() || (j.is_due() && j.dependencies_met(&jobs)));
+        if let Some(job) = index.map(|i| &mut jobs[i]) {
+            if job.is_due() {
+                job.run_requested_at = None;
+            }
             job.status = JobStatus::Running;
             Some(job.clone())
         } else {
@@ -431,11 +490,13 @@ impl Scheduler {
         let next_due = jobs
             .iter()
             .filter(|j| {
-                j.status == JobStatus::Pending
+                (j.status == JobStatus::Pending
                     || (matches!(j.status, JobStatus::Failed { .. })
-                        && matches!(j.schedule, JobSchedule::Interval { .. }))
+                        && matches!(j.schedule, JobSchedule::Interval { .. })))
+                    && j.dependencies_met(&jobs)
             })
             .map(|j| j.next_run)
+            .chain(jobs.iter().filter(|j| j.is_run_requested()).map(|_| now))
             .min();
 
         let secs = match next_due {
@@ -462,7 +523,49 @@ impl Scheduler {
         // Execute
         let result = executor(job.clone()).await;
 
-        // Update state based on result
+        if job.run_requested_at.is_some() {
+            self.finish_requested_run(&job, result).await;
+        } else {
+            self.finish_scheduled_run(&job, result).await;
+        }
+
+        // Sync to PDS
+        if let Err(e) = self.sync_job_to_pds(&job.rkey).await {
+            error!(rkey = %job.rkey, error = %e, "failed to sync job to PDS");
+        }
+    }
+
+    /// Record the outcome of a run requested outside the schedule.
+    ///
+    /// Recurring jobs keep their `next_run` and return to pending or failed;
+    /// one-shot jobs finish just as they would on schedule.
+    async fn finish_requested_run(&self, job: &Job, result: Result<(), String>) {
+        let mut jobs = self.jobs.write().await;
+        let Some(j) = jobs.iter_mut().find(|j| j.rkey == job.rkey) else {
+            return;
+        };
+        j.run_requested_at = None;
+        j.last_run = Some(Utc::now());
+
+        match result {
+            Ok(()) => {
+                j.failure_count = 0;
+                j.status = match j.schedule {
+                    JobSchedule::Once { .. } => JobStatus::Completed,
+                    JobSchedule::Interval { .. } => JobStatus::Pending,
+                };
+                info!(rkey = %job.rkey, "requested job run completed");
+            }
+            Err(error) => {
+                j.failure_count += 1;
+                error!(rkey = %job.rkey, error = %error, "requested job run failed");
+                j.status = JobStatus::Failed { error };
+            }
+        }
+    }
+
+    /// Record the outcome of a scheduled run, rescheduling or backing off.
+    async fn finish_scheduled_run(&self, job: &Job, result: Result<(), String>) {
         match result {
             Ok(()) => {
                 let now = Utc::now();
@@ -511,11 +614,6 @@ impl Scheduler {
                 }
             }
         }
-
-        // Sync to PDS
-        if let Err(e) = self.sync_job_to_pds(&job.rkey).await {
-            error!(rkey = %job.rkey, error = %e, "failed to sync job to PDS");
-        }
     }
 
     /// Update a job's status in local state.
@@ -556,6 +654,8 @@ impl Scheduler {
             next_run: Some(job.next_run),
             failure_count: job.failure_count,
             created_at: job.created_at,
+            run_requested_at: job.run_requested_at,
+            depends_on: job.depends_on.clone(),
         };
 
         self.client
@@ -691,4 +791,136 @@ mod tests {
             _ => panic!("Expected Failed status"),
         }
     }
+
+    fn counting_executor(calls: Arc<std::sync::atomic::AtomicUsize>) -> JobExecutor {
+        Box::new(move |_job| {
+            let calls = Arc::clone(&calls);
+            Box::pin(async move {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_run_now_keeps_interval_cadence() {
+        let scheduler = Scheduler::new(Arc::new(AtprotoClient::new("http://localhost:0")));
+        let mut job = Job::interval(
+            "interval-rkey".to_string(),
+            "Hourly".to_string(),
+            "Do something".to_string(),
+            3600,
+        );
+        let next_run = Utc::now() + chrono::Duration::minutes(30);
+        job.next_run = next_run;
+        scheduler.jobs.write().await.push(job);
+
+        assert!(scheduler.take_due_job().await.is_none());
+        assert!(scheduler.request_run("interval-rkey").await);
+        assert!(!scheduler.request_run("missing").await);
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let executor = counting_executor(Arc::clone(&calls));
+        let taken = scheduler.take_due_job().await.expect("run was requested");
+        scheduler.execute_job(taken, &executor).await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let job = scheduler.get_job("interval-rkey").await.unwrap();
+        assert_eq!(job.next_run, next_run);
+        assert!(job.last_run.is_some());
+        assert_eq!(job.status, JobStatus::Pending);
+        assert!(job.run_requested_at.is_none());
+
+        // The request is consumed; nothing else runs until next_run
+        assert!(scheduler.take_due_job().await.is_none());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_now_completes_one_shot() {
+        let scheduler = Scheduler::new(Arc::new(AtprotoClient::new("http://localhost:0")));
+        scheduler.jobs.write().await.push(Job::once(
+            "once-rkey".to_string(),
+            "Later".to_string(),
+            "Do something".to_string(),
+            Utc::now() + chrono::Duration::hours(1),
+        ));
+        assert!(scheduler.request_run("once-rkey").await);
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let executor = counting_executor(Arc::clone(&calls));
+        let taken = scheduler.take_due_job().await.expect("run was requested");
+        scheduler.execute_job(taken, &executor).await;
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let job = scheduler.get_job("once-rkey").await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.last_run.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dependent_job_runs_after_dependency() {
+        let scheduler = Scheduler::new(Arc::new(AtprotoClient::new("http://localhost:0")));
+        let due = Utc::now() - chrono::Duration::seconds(1);
+        let mut second = Job::once(
+            "second".to_string(),
+            "Second".to_string(),
+            "Do B".to_string(),
+            due,
+        );
+        second.depends_on = vec!["first".to_string()];
+        let first = Job::once(
+            "first".to_string(),
+            "First".to_string(),
+            "Do A".to_string(),
+            due,
+        );
+        // The dependent job comes first in the list, so ordering must come
+        // from the dependency rather than position
+        scheduler.jobs.write().await.extend([second, first]);
+
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let executor: JobExecutor = {
+            let order = Arc::clone(&order);
+            Box::new(move |job| {
+                let order = Arc::clone(&order);
+                Box::pin(async move {
+                    order.lock().unwrap().push(job.rkey);
+                    Ok(())
+                })
+            })
+        };
+
+        let job = scheduler.take_due_job().await.expect("first is due");
+        assert_eq!(job.rkey, "first");
+        // The dependent job is held while its dependency runs
+        assert!(scheduler.take_due_job().await.is_none());
+        scheduler.execute_job(job, &executor).await;
+
+        let job = scheduler.take_due_job().await.expect("second is unblocked");
+        assert_eq!(job.rkey, "second");
+        scheduler.execute_job(job, &executor).await;
+
+        assert_eq!(*order.lock().unwrap(), vec!["first", "second"]);
+        assert!(scheduler.take_due_job().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_add_job_rejects_dependency_cycle() {
+        let scheduler = Scheduler::new(Arc::new(AtprotoClient::new("http://localhost:0")));
+        let at = Utc::now() + chrono::Duration::hours(1);
+        let mut first = Job::once("a".to_string(), "A".to_string(), "Do A".to_string(), at);
+        first.depends_on = vec!["b".to_string()];
+        scheduler.jobs.write().await.push(first);
+
+        let mut second = Job::once("b".to_string(), "B".to_string(), "Do B".to_string(), at);
+        second.depends_on = vec!["a".to_string()];
+        match scheduler.add_job(second).await {
+            Err(SchedulerError::InvalidConfig(msg)) => {
+                assert_eq!(msg, "dependency cycle: b -> a -> b")
+            }
+            other => panic!("expected cycle rejection, got {:?}", other),
+        }
+        assert_eq!(scheduler.list_jobs().await.len(), 1);
+    }
 }
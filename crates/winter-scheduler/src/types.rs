@@ -24,6 +24,12 @@ pub struct Job {
     pub failure_count: u32,
     /// When this job was created.
     pub created_at: DateTime<Utc>,
+    /// When an immediate run outside the schedule was requested.
+    #[serde(default)]
+    pub run_requested_at: Option<DateTime<Utc>>,
+    /// Record keys of jobs whose most recent run must succeed first.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
 }
 
 /// How a job is scheduled to run.
@@ -66,6 +72,8 @@ impl Job {
             next_run: at,
             failure_count: 0,
             created_at: Utc::now(),
+            run_requested_at: None,
+            depends_on: Vec::new(),
         }
     }
 
@@ -81,6 +89,8 @@ impl Job {
             next_run: Utc::now(),
             failure_count: 0,
             created_at: Utc::now(),
+            run_requested_at: None,
+            depends_on: Vec::new(),
         }
     }
 
@@ -99,6 +109,38 @@ impl Job {
         }
     }
 
+    /// Check if an immediate run was requested and the job isn't already running.
+    pub fn is_run_requested(&self) -> bool {
+        self.run_requested_at.is_some() && self.status != JobStatus::Running
+    }
+
+    /// Check if this job's most recent run succeeded.
+    ///
+    /// One-shot jobs must be completed; recurring jobs must have run at least
+    /// once and be waiting for their next run without failures.
+    pub fn last_run_succeeded(&self) -> bool {
+        match &self.status {
+            JobStatus::Completed => true,
+            JobStatus::Pending => {
+                matches!(self.schedule, JobSchedule::Interval { .. })
+                    && self.last_run.is_some()
+                    && self.failure_count == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if every job this one depends on has succeeded, given all jobs.
+    ///
+    /// A missing dependency is never satisfied.
+    pub fn dependencies_met(&self, jobs: &[Job]) -> bool {
+        self.depends_on.iter().all(|dep| {
+            jobs.iter()
+                .find(|j| &j.rkey == dep)
+                .is_some_and(Job::last_run_succeeded)
+        })
+    }
+
     /// Calculate the next run time after a successful execution.
     pub fn calculate_next_run(&self) -> Option<DateTime<Utc>> {
         match &self.schedule {
@@ -508,4 +550,39 @@ mod tests {
             "Job with future next_run should not be due"
         );
     }
+
+    #[test]
+    fn test_dependencies_met() {
+        let mut parent = Job::once(
+            "parent".to_string(),
+            "Parent".to_string(),
+            "Instructions".to_string(),
+            Utc::now(),
+        );
+        let mut child = Job::once(
+            "child".to_string(),
+            "Child".to_string(),
+            "Instructions".to_string(),
+            Utc::now(),
+        );
+        child.depends_on = vec!["parent".to_string()];
+        assert!(!child.dependencies_met(&[]));
+        assert!(!child.dependencies_met(std::slice::from_ref(&parent)));
+
+        parent.status = JobStatus::Completed;
+        assert!(child.dependencies_met(std::slice::from_ref(&parent)));
+
+        // A recurring dependency counts once its latest run succeeded
+        let mut recurring = Job::interval(
+            "parent".to_string(),
+            "Parent".to_string(),
+            "Instructions".to_string(),
+            60,
+        );
+        assert!(!child.dependencies_met(std::slice::from_ref(&recurring)));
+        recurring.last_run = Some(Utc::now());
+        assert!(child.dependencies_met(std::slice::from_ref(&recurring)));
+        recurring.failure_count = 1;
+        assert!(!child.dependencies_met(std::slice::from_ref(&recurring)));
+    }
 }
@@ -0,0 +1,409 @@
+//! Read-only JSON API mirroring the HTML pages.
+//!
+//! Every list and detail page has a counterpart under `/api/v1` that returns
+//! the typed records as stored in the PDS, so programmatic observers don't
+//! need to scrape HTML. Records are wrapped with their URI, CID and record
+//! key:
+//!
+//! ```json
+//! { "uri": "at://...", "cid": "bafy...", "rkey": "3k...", "value": { ... } }
+//! ```
+//!
+//! Lists return `{ "records": [...], "count": n }`; thoughts are paginated
+//! with `limit` and `cursor` and also return the next `cursor`.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use winter_atproto::{
+    AtprotoClient, CustomTool, DIRECTIVE_COLLECTION, Directive, FACT_COLLECTION,
+    FACT_DECLARATION_COLLECTION, Fact, FactDeclaration, IDENTITY_COLLECTION, IDENTITY_KEY,
+    Identity, JOB_COLLECTION, Job, NOTE_COLLECTION, Note, RULE_COLLECTION, Rule,
+    THOUGHT_COLLECTION, TOOL_COLLECTION, Thought, WIKI_ENTRY_COLLECTION, WikiEntry,
+};
+
+use crate::WebError;
+use crate::routes::AppState;
+
+/// Default page size for `/api/v1/thoughts`.
+const DEFAULT_THOUGHT_LIMIT: u32 = 50;
+
+/// Largest page size the PDS accepts for `listRecords`.
+const MAX_THOUGHT_LIMIT: u32 = 100;
+
+/// A record with its repository metadata.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiRecord<T> {
+    pub uri: String,
+    pub cid: Option<String>,
+    pub rkey: String,
+    pub value: T,
+}
+
+impl<T> ApiRecord<T> {
+    fn new(uri: String, cid: Option<String>, value: T) -> Self {
+        let rkey = uri.split('/').next_back().unwrap_or("").to_string();
+        Self {
+            uri,
+            cid,
+            rkey,
+            value,
+        }
+    }
+}
+
+/// A list of records.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiRecordList<T> {
+    pub records: Vec<ApiRecord<T>>,
+    pub count: usize,
+    /// Cursor for the next page, for paginated lists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+impl<T> ApiRecordList<T> {
+    fn new(records: Vec<ApiRecord<T>>, cursor: Option<String>) -> Self {
+        Self {
+            count: records.len(),
+            records,
+            cursor,
+        }
+    }
+}
+
+/// [`WebError`] rendered as JSON instead of an HTML page.
+struct ApiError(WebError);
+
+impl From<WebError> for ApiError {
+    fn from(error: WebError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.0.status(),
+            Json(json!({ "error": self.0.user_message() })),
+        )
+            .into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Routes for the JSON API, merged into the main router.
+pub(crate) fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/v1/facts", get(list_facts))
+        .route("/api/v1/facts/{rkey}", get(get_fact))
+        .route("/api/v1/rules", get(list_rules))
+        .route("/api/v1/rules/{rkey}", get(get_rule))
+        .route("/api/v1/jobs", get(list_jobs))
+        .route("/api/v1/jobs/{rkey}", get(get_job))
+        .route("/api/v1/notes", get(list_notes))
+        .route("/api/v1/notes/{rkey}", get(get_note))
+        .route("/api/v1/wiki", get(list_wiki))
+        .route("/api/v1/wiki/{slug_or_rkey}", get(get_wiki))
+        .route("/api/v1/directives", get(list_directives))
+        .route("/api/v1/directives/{rkey}", get(get_directive))
+        .route("/api/v1/declarations", get(list_declarations))
+        .route("/api/v1/declarations/{rkey}", get(get_declaration))
+        .route("/api/v1/tools", get(list_tools))
+        .route("/api/v1/tools/{rkey}", get(get_tool))
+        .route("/api/v1/identity", get(get_identity))
+        .route("/api/v1/thoughts", get(list_thoughts))
+}
+
+async fn list_all<T: DeserializeOwned>(
+    client: &AtprotoClient,
+    collection: &str,
+) -> ApiResult<ApiRecordList<T>> {
+    let records = client
+        .list_all_records::<T>(collection)
+        .await
+        .map_err(|e| WebError::Upstream(e.to_string()))?
+        .into_iter()
+        .map(|item| ApiRecord::new(item.uri, Some(item.cid), item.value))
+        .collect();
+    Ok(Json(ApiRecordList::new(records, None)))
+}
+
+async fn get_one<T: DeserializeOwned>(
+    client: &AtprotoClient,
+    collection: &str,
+    what: &str,
+    rkey: &str,
+) -> ApiResult<ApiRecord<T>> {
+    let record = client
+        .get_record::<T>(collection, rkey)
+        .await
+        .map_err(|e| WebError::from_pds(&format!("{} {}", what, rkey), e))?;
+    Ok(Json(ApiRecord::new(record.uri, record.cid, record.value)))
+}
+
+async fn list_facts(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<Fact>> {
+    list_all(&state.client, FACT_COLLECTION).await
+}
+
+async fn get_fact(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<Fact>> {
+    get_one(&state.client, FACT_COLLECTION, "fact", &rkey).await
+}
+
+async fn list_rules(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<Rule>> {
+    list_all(&state.client, RULE_COLLECTION).await
+}
+
+async fn get_rule(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<Rule>> {
+    get_one(&state.client, RULE_COLLECTION, "rule", &rkey).await
+}
+
+async fn list_jobs(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<Job>> {
+    list_all(&state.client, JOB_COLLECTION).await
+}
+
+async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<Job>> {
+    get_one(&state.client, JOB_COLLECTION, "job", &rkey).await
+}
+
+async fn list_notes(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<Note>> {
+    list_all(&state.client, NOTE_COLLECTION).await
+}
+
+async fn get_note(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<Note>> {
+    get_one(&state.client, NOTE_COLLECTION, "note", &rkey).await
+}
+
+async fn list_wiki(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<WikiEntry>> {
+    list_all(&state.client, WIKI_ENTRY_COLLECTION).await
+}
+
+/// Look up a wiki entry by slug, falling back to record key, like the HTML
+/// page does.
+async fn get_wiki(
+    State(state): State<Arc<AppState>>,
+    Path(slug_or_rkey): Path<String>,
+) -> ApiResult<ApiRecord<WikiEntry>> {
+    let entries = state
+        .client
+        .list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION)
+        .await
+        .map_err(|e| WebError::Upstream(e.to_string()))?;
+
+    let rkey_of = |uri: &str| uri.split('/').next_back().unwrap_or("").to_string();
+    entries
+        .into_iter()
+        .find(|item| item.value.slug == slug_or_rkey || rkey_of(&item.uri) == slug_or_rkey)
+        .map(|item| Json(ApiRecord::new(item.uri, Some(item.cid), item.value)))
+        .ok_or_else(|| WebError::NotFound(format!("wiki entry {}", slug_or_rkey)).into())
+}
+
+async fn list_directives(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<ApiRecordList<Directive>> {
+    list_all(&state.client, DIRECTIVE_COLLECTION).await
+}
+
+async fn get_directive(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<Directive>> {
+    get_one(&state.client, DIRECTIVE_COLLECTION, "directive", &rkey).await
+}
+
+async fn list_declarations(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<ApiRecordList<FactDeclaration>> {
+    list_all(&state.client, FACT_DECLARATION_COLLECTION).await
+}
+
+async fn get_declaration(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<FactDeclaration>> {
+    get_one(
+        &state.client,
+        FACT_DECLARATION_COLLECTION,
+        "declaration",
+        &rkey,
+    )
+    .await
+}
+
+async fn list_tools(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecordList<CustomTool>> {
+    list_all(&state.client, TOOL_COLLECTION).await
+}
+
+async fn get_tool(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> ApiResult<ApiRecord<CustomTool>> {
+    get_one(&state.client, TOOL_COLLECTION, "tool", &rkey).await
+}
+
+async fn get_identity(State(state): State<Arc<AppState>>) -> ApiResult<ApiRecord<Identity>> {
+    get_one(&state.client, IDENTITY_COLLECTION, "identity", IDENTITY_KEY).await
+}
+
+#[derive(Deserialize)]
+struct PageParams {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+async fn list_thoughts(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PageParams>,
+) -> ApiResult<ApiRecordList<Thought>> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_THOUGHT_LIMIT)
+        .clamp(1, MAX_THOUGHT_LIMIT);
+    let page = state
+        .client
+        .list_records::<Thought>(THOUGHT_COLLECTION, Some(limit), params.cursor.as_deref())
+        .await
+        .map_err(|e| WebError::Upstream(e.to_string()))?;
+    let records = page
+        .records
+        .into_iter()
+        .map(|item| ApiRecord::new(item.uri, Some(item.cid), item.value))
+        .collect();
+    Ok(Json(ApiRecordList::new(records, page.cursor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::test_support::{self, DID, logged_in_router};
+
+    fn fact_json(predicate: &str) -> Value {
+        json!({
+            "$type": FACT_COLLECTION,
+            "predicate": predicate,
+            "args": ["self", "lichen"],
+            "confidence": "0.9",
+            "tags": ["nature"],
+            "createdAt": "2026-01-01T00:00:00Z"
+        })
+    }
+
+    async fn mock_pds() -> MockServer {
+        let server = test_support::mock_pds().await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.listRecords"))
+            .and(query_param("collection", FACT_COLLECTION))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "records": [
+                    {
+                        "uri": format!("at://{}/{}/f1", DID, FACT_COLLECTION),
+                        "cid": "bafyf1",
+                        "value": fact_json("likes")
+                    },
+                    {
+                        "uri": format!("at://{}/{}/f2", DID, FACT_COLLECTION),
+                        "cid": "bafyf2",
+                        "value": fact_json("studies")
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .and(query_param("rkey", "f1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uri": format!("at://{}/{}/f1", DID, FACT_COLLECTION),
+                "cid": "bafyf1",
+                "value": fact_json("likes")
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .and(query_param("rkey", "missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn get_json(server: &MockServer, uri: &str) -> (StatusCode, Value) {
+        let response = logged_in_router(server)
+            .await
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_facts_shape() {
+        let server = mock_pds().await;
+        let (status, body) = get_json(&server, "/api/v1/facts").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 2);
+        assert!(body.get("cursor").is_none());
+
+        let list: ApiRecordList<Fact> = serde_json::from_value(body).unwrap();
+        assert_eq!(list.records[0].rkey, "f1");
+        assert_eq!(list.records[0].cid.as_deref(), Some("bafyf1"));
+        assert_eq!(list.records[0].value.predicate, "likes");
+        assert_eq!(list.records[1].value.predicate, "studies");
+    }
+
+    #[tokio::test]
+    async fn test_get_fact_shape() {
+        let server = mock_pds().await;
+        let (status, body) = get_json(&server, "/api/v1/facts/f1").await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The value is the record as the `Fact` type serializes it.
+        let record: ApiRecord<Fact> = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(body["value"], serde_json::to_value(&record.value).unwrap());
+        assert_eq!(record.rkey, "f1");
+        assert_eq!(record.value.args, ["self", "lichen"]);
+        assert_eq!(record.value.confidence, Some(0.9));
+        assert_eq!(record.value.tags, ["nature"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_record_is_json_404() {
+        let server = mock_pds().await;
+        let (status, body) = get_json(&server, "/api/v1/facts/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "fact missing not found");
+    }
+}
@@ -0,0 +1,304 @@
+//! CSRF protection for the write forms.
+//!
+//! Uses the double-submit cookie pattern. Every response carries a random
+//! token in the `winter_csrf` cookie (issued on first visit), and every
+//! `method="post"` form in an HTML response gets the same token as a hidden
+//! `csrf_token` field. A POST is only let through when the submitted field
+//! (or the `X-CSRF-Token` header, for scripted clients) matches the cookie.
+//! A cross-site page can make the browser send the cookie, but it can't read
+//! it, so it can't produce the matching field.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use axum::{
+    Form,
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    http::{HeaderValue, Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use regex::Regex;
+use tracing::warn;
+
+use crate::WebError;
+
+/// Cookie holding the CSRF token.
+pub const CSRF_COOKIE: &str = "winter_csrf";
+
+/// Form field carrying the CSRF token.
+pub const CSRF_FIELD: &str = "csrf_token";
+
+/// Header accepted in place of the form field.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Largest form body buffered while checking the token.
+const MAX_FORM_BYTES: usize = 1024 * 1024;
+
+static POST_FORM_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<form\b[^>]*\bmethod\s*=\s*"post"[^>]*>"#).expect("valid regex")
+});
+
+/// Middleware that issues CSRF tokens and validates them on POST.
+pub async fn csrf_protect(request: Request, next: Next) -> Response {
+    let cookie_token = token_from_cookies(&request);
+
+    if request.method() == Method::POST {
+        let Some(expected) = cookie_token else {
+            return reject(&request, "missing CSRF cookie");
+        };
+        let (request, submitted) = match submitted_token(request).await {
+            Ok(parts) => parts,
+            Err(response) => return response,
+        };
+        if !submitted.is_some_and(|t| constant_time_eq(t.as_bytes(), expected.as_bytes())) {
+            return reject(&request, "invalid CSRF token");
+        }
+        // Handlers may re-render a form in response to a POST (e.g. on an
+        // edit conflict), so the token is injected here as well.
+        return inject_token(next.run(request).await, &expected).await;
+    }
+
+    let (token, issued) = match cookie_token {
+        Some(token) => (token, false),
+        None => (uuid::Uuid::new_v4().simple().to_string(), true),
+    };
+
+    let response = inject_token(next.run(request).await, &token).await;
+    if issued {
+        set_cookie(response, &token)
+    } else {
+        response
+    }
+}
+
+/// Read the token from the request's `Cookie` headers.
+fn token_from_cookies(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == CSRF_COOKIE)
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Pull the submitted token from the header or the form body.
+///
+/// The body is buffered to read the field and then handed back in a rebuilt
+/// request, so the handler's own `Form` extractor still sees it.
+async fn submitted_token(request: Request) -> Result<(Request, Option<String>), Response> {
+    if let Some(token) = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        let token = token.to_string();
+        return Ok((request, Some(token)));
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_FORM_BYTES)
+        .await
+        .map_err(|_| WebError::Validation("form body too large".to_string()).into_response())?;
+
+    let probe = Request::from_parts(parts.clone(), Body::from(bytes.clone()));
+    let token = Form::<HashMap<String, String>>::from_request(probe, &())
+        .await
+        .ok()
+        .and_then(|Form(mut fields)| fields.remove(CSRF_FIELD));
+
+    Ok((Request::from_parts(parts, Body::from(bytes)), token))
+}
+
+/// Add the hidden token field to every POST form in an HTML response.
+async fn inject_token(response: Response, token: &str) -> Response {
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+    if !is_html {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!(error = %e, "failed to buffer HTML response for CSRF injection");
+            return WebError::Template(e.to_string()).into_response();
+        }
+    };
+    let Ok(html) = std::str::from_utf8(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let field = format!(
+        r#"<input type="hidden" name="{}" value="{}">"#,
+        CSRF_FIELD, token
+    );
+    let html = POST_FORM_TAG.replace_all(html, |caps: &regex::Captures| {
+        format!("{}\n{}", &caps[0], field)
+    });
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(Bytes::from(html.into_owned())))
+}
+
+/// Attach the `Set-Cookie` header for a newly issued token.
+fn set_cookie(mut response: Response, token: &str) -> Response {
+    let cookie = format!(
+        "{}={}; Path=/; SameSite=Strict; HttpOnly",
+        CSRF_COOKIE, token
+    );
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+fn reject(request: &Request, reason: &str) -> Response {
+    warn!(path = %request.uri().path(), reason, "rejected POST without valid CSRF token");
+    WebError::Forbidden(reason.to_string()).into_response()
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        http::StatusCode,
+        middleware,
+        response::Html,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    fn test_router() -> Router {
+        Router::new()
+            .route(
+                "/form",
+                get(|| async { Html(r#"<form action="/api/things" method="post"></form>"#) }),
+            )
+            .route(
+                "/api/things",
+                post(|Form(fields): Form<HashMap<String, String>>| async move {
+                    fields.get("name").cloned().unwrap_or_default()
+                }),
+            )
+            .layer(middleware::from_fn(csrf_protect))
+    }
+
+    fn post_form(cookie: Option<&str>, body: &str) -> Request {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/api/things")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        if let Some(cookie) = cookie {
+            builder = builder.header(header::COOKIE, format!("{}={}", CSRF_COOKIE, cookie));
+        }
+        builder.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn body_string(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_issues_cookie_and_renders_token() {
+        let response = test_router()
+            .oneshot(Request::builder().uri("/form").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let cookie = response.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let token = cookie
+            .strip_prefix("winter_csrf=")
+            .and_then(|rest| rest.split(';').next())
+            .unwrap()
+            .to_string();
+        assert!(cookie.contains("SameSite=Strict"));
+
+        let html = body_string(response).await;
+        assert!(html.contains(&format!(
+            r#"<input type="hidden" name="csrf_token" value="{}">"#,
+            token
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_get_reuses_existing_cookie() {
+        let response = test_router()
+            .oneshot(
+                Request::builder()
+                    .uri("/form")
+                    .header(header::COOKIE, "other=1; winter_csrf=abc123")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+        assert!(body_string(response).await.contains(r#"value="abc123""#));
+    }
+
+    #[tokio::test]
+    async fn test_post_with_matching_token_succeeds() {
+        let response = test_router()
+            .oneshot(post_form(Some("abc123"), "name=x&csrf_token=abc123"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // The handler still sees the full form after the token check.
+        assert_eq!(body_string(response).await, "x");
+    }
+
+    #[tokio::test]
+    async fn test_post_with_header_token_succeeds() {
+        let mut request = post_form(Some("abc123"), "name=x");
+        request
+            .headers_mut()
+            .insert(CSRF_HEADER, HeaderValue::from_static("abc123"));
+        let response = test_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_post_without_valid_token_is_forbidden() {
+        let cases = [
+            (None, "name=x&csrf_token=abc123"),
+            (Some("abc123"), "name=x"),
+            (Some("abc123"), "name=x&csrf_token=wrong"),
+        ];
+        for (cookie, body) in cases {
+            let response = test_router()
+                .oneshot(post_form(cookie, body))
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::FORBIDDEN,
+                "{cookie:?} {body}"
+            );
+        }
+    }
+}
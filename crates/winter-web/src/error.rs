@@ -1,13 +1,18 @@
 //! Error types for the web UI.
 
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
 use thiserror::Error;
+use winter_atproto::AtprotoError;
+
+use crate::routes::html_escape;
 
 /// Errors that can occur in the web UI.
 #[derive(Debug, Error)]
 pub enum WebError {
     /// ATProto error.
     #[error("ATProto error: {0}")]
-    Atproto(#[from] winter_atproto::AtprotoError),
+    Atproto(#[from] AtprotoError),
 
     /// IO error.
     #[error("IO error: {0}")]
@@ -16,4 +21,174 @@ pub enum WebError {
     /// Template error.
     #[error("template error: {0}")]
     Template(String),
+
+    /// Submitted form input was invalid.
+    #[error("{0}")]
+    Validation(String),
+
+    /// The request was refused, e.g. a POST without a valid CSRF token.
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    /// The requested record does not exist.
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// The record changed since the edit form was loaded.
+    #[error(
+        "{0} was changed since this form was loaded; review the current version and reapply your edits"
+    )]
+    Conflict(String),
+
+    /// The PDS failed or rejected the request.
+    #[error("PDS request failed: {0}")]
+    Upstream(String),
+}
+
+impl WebError {
+    /// Classify a PDS error for a record: missing records become `NotFound`,
+    /// everything else `Upstream`.
+    pub fn from_pds(what: &str, error: AtprotoError) -> Self {
+        match error {
+            AtprotoError::NotFound { .. } => WebError::NotFound(what.to_string()),
+            other => WebError::Upstream(other.to_string()),
+        }
+    }
+
+    /// HTTP status code for this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            WebError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            WebError::Forbidden(_) => StatusCode::FORBIDDEN,
+            WebError::Conflict(_) => StatusCode::CONFLICT,
+            WebError::NotFound(_) | WebError::Atproto(AtprotoError::NotFound { .. }) => {
+                StatusCode::NOT_FOUND
+            }
+            WebError::Upstream(_) | WebError::Atproto(_) => StatusCode::BAD_GATEWAY,
+            WebError::Io(_) | WebError::Template(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message to show the user. Internal errors are not described in detail.
+    pub fn user_message(&self) -> String {
+        match self {
+            WebError::Io(_) | WebError::Template(_) => "internal server error".to_string(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for WebError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let html = ERROR_HTML
+            .replace(
+                "<!-- STATUS -->",
+                status.canonical_reason().unwrap_or("Error"),
+            )
+            .replace("<!-- MESSAGE -->", &html_escape(&self.user_message()));
+        (status, Html(html)).into_response()
+    }
+}
+
+const ERROR_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Winter - <!-- STATUS --></title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+            max-width: 800px;
+            margin: 0 auto;
+            padding: 2rem;
+            background: #0a0a0a;
+            color: #e0e0e0;
+        }
+        h1 { color: #bf616a; }
+        a { color: #81a1c1; }
+    </style>
+</head>
+<body>
+    <h1><!-- STATUS --></h1>
+    <p><!-- MESSAGE --></p>
+    <p><a href="/">Back to Winter</a></p>
+</body>
+</html>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_and_message_per_variant() {
+        let cases = [
+            (
+                WebError::Validation("predicate is required".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "predicate is required",
+            ),
+            (
+                WebError::Forbidden("invalid CSRF token".to_string()),
+                StatusCode::FORBIDDEN,
+                "forbidden: invalid CSRF token",
+            ),
+            (
+                WebError::NotFound("fact abc".to_string()),
+                StatusCode::NOT_FOUND,
+                "fact abc not found",
+            ),
+            (
+                WebError::Conflict("note abc".to_string()),
+                StatusCode::CONFLICT,
+                "note abc was changed since this form was loaded; review the current version and reapply your edits",
+            ),
+            (
+                WebError::Upstream("HTTP error: timeout".to_string()),
+                StatusCode::BAD_GATEWAY,
+                "PDS request failed: HTTP error: timeout",
+            ),
+            (
+                WebError::Atproto(AtprotoError::Network("connection reset".to_string())),
+                StatusCode::BAD_GATEWAY,
+                "ATProto error: network error: connection reset",
+            ),
+            (
+                WebError::Io(std::io::Error::other("disk full")),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal server error",
+            ),
+            (
+                WebError::Template("bad placeholder".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal server error",
+            ),
+        ];
+
+        for (error, status, message) in cases {
+            assert_eq!(error.status(), status, "{error:?}");
+            assert_eq!(error.user_message(), message, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_pds_classifies_not_found() {
+        let missing = AtprotoError::NotFound {
+            collection: "diy.razorgirl.winter.fact".to_string(),
+            rkey: "abc".to_string(),
+        };
+        let error = WebError::from_pds("fact abc", missing);
+        assert!(matches!(error, WebError::NotFound(_)));
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+
+        let error = WebError::from_pds("fact abc", AtprotoError::Auth("expired".to_string()));
+        assert!(matches!(error, WebError::Upstream(_)));
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn test_into_response_renders_status_page() {
+        let response = WebError::NotFound("note <xyz>".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
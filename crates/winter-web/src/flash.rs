@@ -0,0 +1,107 @@
+//! Flash messages for form handlers.
+//!
+//! Form submissions follow post/redirect/get. When a create, update or delete
+//! fails at the PDS, the handler redirects back to the form (or detail page)
+//! with the error in the `error` query parameter, and that page shows it above
+//! its content instead of the failure only reaching the logs. Input that fails
+//! validation is instead answered with the form itself, re-rendered with what
+//! was submitted and the error flashed above it.
+
+use axum::response::Redirect;
+use serde::Deserialize;
+
+use crate::WebError;
+use crate::routes::html_escape;
+
+/// Flash message read from the query string.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Flash {
+    pub error: Option<String>,
+}
+
+impl Flash {
+    /// Flash showing `error`, for rendering a page directly rather than
+    /// through a redirect.
+    pub fn from_error(error: &WebError) -> Self {
+        Self {
+            error: Some(error.user_message()),
+        }
+    }
+
+    /// Render the flash as HTML, or an empty string if there is none.
+    pub fn render(&self) -> String {
+        match self.error.as_deref().filter(|e| !e.is_empty()) {
+            Some(error) => format!(
+                r#"<div class="flash flash-error" role="alert">{}</div>"#,
+                html_escape(error)
+            ),
+            None => String::new(),
+        }
+    }
+}
+
+/// Redirect to `path` with `error` shown as a flash message.
+pub(crate) fn redirect_with_error(path: &str, error: &WebError) -> Redirect {
+    Redirect::to(&format!(
+        "{}?error={}",
+        path,
+        percent_encode(&error.user_message())
+    ))
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn test_redirect_carries_encoded_message() {
+        let error = WebError::Validation("predicate & args are required".to_string());
+        let response = redirect_with_error("/facts/new", &error).into_response();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/facts/new?error=predicate%20%26%20args%20are%20required"
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_message() {
+        let flash = Flash {
+            error: Some("<script>alert(1)</script>".to_string()),
+        };
+        let html = flash.render();
+        assert!(html.contains("flash-error"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_render_empty_without_error() {
+        assert_eq!(Flash::default().render(), "");
+        let flash = Flash {
+            error: Some(String::new()),
+        };
+        assert_eq!(flash.render(), "");
+    }
+
+    #[test]
+    fn test_percent_encode_utf8() {
+        assert_eq!(percent_encode("café?"), "caf%C3%A9%3F");
+    }
+}
@@ -0,0 +1,115 @@
+//! Jetstream subscription shared by the live streams (thoughts, job status).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{error, info, trace};
+
+/// A record commit from Jetstream.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Commit {
+    pub operation: String,
+    pub collection: String,
+    #[serde(default)]
+    pub rkey: Option<String>,
+    pub record: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JetstreamEvent {
+    kind: String,
+    commit: Option<Commit>,
+}
+
+/// Parse a Jetstream message, returning its commit if it touches `collection`.
+pub(crate) fn parse_commit(text: &str, collection: &str) -> Result<Option<Commit>, String> {
+    let event: JetstreamEvent =
+        serde_json::from_str(text).map_err(|e| format!("failed to parse event: {}", e))?;
+
+    if event.kind != "commit" {
+        return Ok(None);
+    }
+
+    Ok(event.commit.filter(|c| c.collection == collection))
+}
+
+/// Stream `collection` commits in `did`'s repo, passing each message to
+/// `handle` and reconnecting with exponential backoff.
+///
+/// `connected` tracks whether the Jetstream connection is currently up.
+pub(crate) async fn subscribe(
+    did: &str,
+    collection: &str,
+    connected: &AtomicBool,
+    mut handle: impl FnMut(&str) -> Result<(), String>,
+) {
+    let mut backoff = Duration::from_secs(1);
+    let max_backoff = Duration::from_secs(60);
+
+    loop {
+        let result =
+            connect_and_stream(did, collection, &mut backoff, connected, &mut handle).await;
+        connected.store(false, Ordering::SeqCst);
+        match result {
+            Ok(()) => {
+                info!(collection = %collection, "jetstream subscription ended cleanly");
+                return;
+            }
+            Err(e) => {
+                error!(error = %e, collection = %collection, backoff_secs = backoff.as_secs(), "jetstream connection error, reconnecting");
+                tokio::time::sleep(backoff).await;
+                backoff = std::cmp::min(backoff * 2, max_backoff);
+            }
+        }
+    }
+}
+
+async fn connect_and_stream(
+    did: &str,
+    collection: &str,
+    backoff: &mut Duration,
+    connected: &AtomicBool,
+    handle: &mut impl FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    let url = format!(
+        "{}?wantedDids={}&wantedCollections={}",
+        winter_atproto::DEFAULT_JETSTREAM_URL,
+        did,
+        collection,
+    );
+    info!(url = %url, did = %did, "connecting to jetstream");
+
+    let (ws_stream, _) = connect_async(&url)
+        .await
+        .map_err(|e| format!("connection failed: {}", e))?;
+
+    let (_, mut read) = ws_stream.split();
+    info!(collection = %collection, "connected to jetstream");
+    connected.store(true, Ordering::SeqCst);
+
+    // Reset backoff on successful connect
+    *backoff = Duration::from_secs(1);
+
+    loop {
+        match read.next().await {
+            Some(Ok(Message::Text(text))) => {
+                if let Err(e) = handle(&text) {
+                    trace!(error = %e, "failed to handle jetstream event");
+                }
+            }
+            Some(Ok(Message::Close(_))) => {
+                return Err("connection closed by server".to_string());
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => {
+                return Err(format!("read error: {}", e));
+            }
+            None => {
+                return Err("stream ended".to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Real-time job status subscription via Jetstream.
+//!
+//! The scheduler runs in the daemon process and records each status change
+//! on the job's PDS record. This watches those records and publishes a
+//! [`JobStatusEvent`] to the SSE channel whenever a job's status changes.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use winter_atproto::{JOB_COLLECTION, Job, JobStatus};
+
+use crate::jetstream;
+
+/// A job status transition, as sent to SSE clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatusEvent {
+    pub rkey: String,
+    /// Job name (`None` for deletions, where the record is gone).
+    pub name: Option<String>,
+    /// New status: `pending`, `running`, `completed`, `failed` or `deleted`.
+    pub status: String,
+    /// Status before this change, if it was seen since the subscription began.
+    pub previous_status: Option<String>,
+    /// Error message for failed jobs.
+    pub error: Option<String>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: Option<DateTime<Utc>>,
+    /// When the change was observed.
+    pub at: DateTime<Utc>,
+}
+
+/// Status name used in the UI and in [`JobStatusEvent::status`].
+pub(crate) fn job_status_name(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Pending => "pending",
+        JobStatus::Running => "running",
+        JobStatus::Completed => "completed",
+        JobStatus::Failed { .. } => "failed",
+    }
+}
+
+/// Last seen status per job, used to turn record writes into transitions.
+#[derive(Debug, Default)]
+struct JobStatusTracker {
+    statuses: HashMap<String, String>,
+}
+
+impl JobStatusTracker {
+    /// Record a create or update of a job, returning an event if its status changed.
+    fn observe_write(&mut self, rkey: &str, job: &Job) -> Option<JobStatusEvent> {
+        let status = job_status_name(&job.status);
+        let previous_status = self.statuses.insert(rkey.to_string(), status.to_string());
+        if previous_status.as_deref() == Some(status) {
+            return None;
+        }
+
+        Some(JobStatusEvent {
+            rkey: rkey.to_string(),
+            name: Some(job.name.clone()),
+            status: status.to_string(),
+            previous_status,
+            error: match &job.status {
+                JobStatus::Failed { error } => Some(error.clone()),
+                _ => None,
+            },
+            last_run: job.last_run,
+            next_run: job.next_run,
+            at: Utc::now(),
+        })
+    }
+
+    /// Record the deletion of a job.
+    fn observe_delete(&mut self, rkey: &str) -> JobStatusEvent {
+        JobStatusEvent {
+            rkey: rkey.to_string(),
+            name: None,
+            status: "deleted".to_string(),
+            previous_status: self.statuses.remove(rkey),
+            error: None,
+            last_run: None,
+            next_run: None,
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Subscribe to job record changes via Jetstream and push status
+/// transitions to the SSE channel.
+pub async fn subscribe_jobs(did: String, job_tx: broadcast::Sender<String>) {
+    let mut tracker = JobStatusTracker::default();
+    let connected = AtomicBool::new(false);
+
+    jetstream::subscribe(&did, JOB_COLLECTION, &connected, |text| {
+        handle_event(text, &mut tracker, &job_tx)
+    })
+    .await;
+}
+
+fn handle_event(
+    text: &str,
+    tracker: &mut JobStatusTracker,
+    job_tx: &broadcast::Sender<String>,
+) -> Result<(), String> {
+    let Some(commit) = jetstream::parse_commit(text, JOB_COLLECTION)? else {
+        return Ok(());
+    };
+    let Some(rkey) = commit.rkey else {
+        return Ok(());
+    };
+
+    let status_event = match commit.operation.as_str() {
+        "create" | "update" => {
+            let record = match commit.record {
+                Some(r) => r,
+                None => return Ok(()),
+            };
+            match serde_json::from_value::<Job>(record) {
+                Ok(job) => tracker.observe_write(&rkey, &job),
+                Err(e) => {
+                    warn!(error = %e, "failed to decode job from jetstream");
+                    None
+                }
+            }
+        }
+        "delete" => Some(tracker.observe_delete(&rkey)),
+        _ => None,
+    };
+
+    if let Some(status_event) = status_event {
+        let json = serde_json::to_string(&status_event)
+            .map_err(|e| format!("failed to encode job status: {}", e))?;
+        if let Err(e) = job_tx.send(json) {
+            debug!(error = %e, "no SSE subscribers");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_commit(operation: &str, status: serde_json::Value) -> String {
+        serde_json::json!({
+            "did": "did:plc:test",
+            "time_us": 1,
+            "kind": "commit",
+            "commit": {
+                "operation": operation,
+                "collection": JOB_COLLECTION,
+                "rkey": "job1",
+                "record": {
+                    "name": "digest",
+                    "instructions": "summarize the day",
+                    "schedule": { "type": "interval", "seconds": 3600 },
+                    "status": status,
+                    "createdAt": "2026-01-01T00:00:00Z",
+                },
+            },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let at = "2026-01-01T00:00:00Z".parse().unwrap();
+        let event = JobStatusEvent {
+            rkey: "job1".to_string(),
+            name: Some("digest".to_string()),
+            status: "failed".to_string(),
+            previous_status: Some("running".to_string()),
+            error: Some("boom".to_string()),
+            last_run: Some(at),
+            next_run: None,
+            at,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["rkey"], "job1");
+        assert_eq!(json["name"], "digest");
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["previous_status"], "running");
+        assert_eq!(json["error"], "boom");
+        assert_eq!(json["last_run"], "2026-01-01T00:00:00Z");
+        assert!(json["next_run"].is_null());
+
+        let decoded: JobStatusEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, event);
+    }
+
+    #[test]
+    fn test_status_change_produces_event() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let mut tracker = JobStatusTracker::default();
+
+        handle_event(
+            &job_commit("create", serde_json::json!("pending")),
+            &mut tracker,
+            &tx,
+        )
+        .unwrap();
+        let created: JobStatusEvent = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(created.status, "pending");
+        assert_eq!(created.previous_status, None);
+
+        handle_event(
+            &job_commit("update", serde_json::json!("running")),
+            &mut tracker,
+            &tx,
+        )
+        .unwrap();
+        let running: JobStatusEvent = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(running.rkey, "job1");
+        assert_eq!(running.name.as_deref(), Some("digest"));
+        assert_eq!(running.status, "running");
+        assert_eq!(running.previous_status.as_deref(), Some("pending"));
+    }
+
+    #[test]
+    fn test_unchanged_status_is_not_sent() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let mut tracker = JobStatusTracker::default();
+        let update = job_commit("update", serde_json::json!("running"));
+
+        handle_event(&update, &mut tracker, &tx).unwrap();
+        assert!(rx.try_recv().is_ok());
+
+        handle_event(&update, &mut tracker, &tx).unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_failure_and_delete_events() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let mut tracker = JobStatusTracker::default();
+
+        handle_event(
+            &job_commit(
+                "update",
+                serde_json::json!({ "failed": { "error": "timeout" } }),
+            ),
+            &mut tracker,
+            &tx,
+        )
+        .unwrap();
+        let failed: JobStatusEvent = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(failed.status, "failed");
+        assert_eq!(failed.error.as_deref(), Some("timeout"));
+
+        let delete = serde_json::json!({
+            "kind": "commit",
+            "commit": { "operation": "delete", "collection": JOB_COLLECTION, "rkey": "job1" },
+        })
+        .to_string();
+        handle_event(&delete, &mut tracker, &tx).unwrap();
+        let deleted: JobStatusEvent = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(deleted.status, "deleted");
+        assert_eq!(deleted.previous_status.as_deref(), Some("failed"));
+    }
+
+    #[test]
+    fn test_other_collections_ignored() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let mut tracker = JobStatusTracker::default();
+        let event = job_commit("create", serde_json::json!("pending"))
+            .replace(JOB_COLLECTION, winter_atproto::FACT_COLLECTION);
+
+        handle_event(&event, &mut tracker, &tx).unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Undo/redo journal for operator edits.
+//!
+//! Every create, update and delete made through the web UI is recorded with
+//! the record's value before and after the change. `/undo` writes the
+//! "before" value back (deleting the record if it didn't exist), and `/redo`
+//! writes the "after" value again. Both only apply if the record is still as
+//! the journal left it, so an undo never overwrites a later edit. The journal
+//! lives in memory, so it starts empty whenever the server restarts.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+use winter_atproto::{AtprotoClient, AtprotoError};
+
+/// Actions kept for undo before the oldest are forgotten.
+pub const DEFAULT_JOURNAL_CAPACITY: usize = 100;
+
+/// One recorded change to a record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub collection: String,
+    pub rkey: String,
+    /// The record before the change (`None` if it was created).
+    pub before: Option<Value>,
+    /// The record after the change (`None` if it was deleted).
+    pub after: Option<Value>,
+    /// CID of the record as the change last left it (`None` while deleted).
+    pub cid: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn new(collection: &str, rkey: &str, before: Option<Value>, after: Option<Value>) -> Self {
+        Self {
+            collection: collection.to_string(),
+            rkey: rkey.to_string(),
+            before,
+            after,
+            cid: None,
+        }
+    }
+
+    /// Set the CID the record was written with.
+    pub fn with_cid(mut self, cid: impl Into<String>) -> Self {
+        self.cid = Some(cid.into());
+        self
+    }
+}
+
+/// Serialize a record for the journal.
+pub fn snapshot<T: Serialize>(record: &T) -> Option<Value> {
+    serde_json::to_value(record).ok()
+}
+
+#[derive(Default)]
+struct Stacks {
+    undo: VecDeque<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+/// Bounded undo and redo stacks of operator edits.
+pub struct Journal {
+    stacks: Mutex<Stacks>,
+    capacity: usize,
+}
+
+impl Journal {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            stacks: Mutex::new(Stacks::default()),
+            capacity,
+        }
+    }
+
+    /// Record a change. A new change discards anything that could be redone.
+    pub fn record(&self, entry: JournalEntry) {
+        let mut stacks = self.lock();
+        self.push_undo(&mut stacks, entry);
+        stacks.redo.clear();
+    }
+
+    /// Reverse the most recent change, returning it, or `None` if there is
+    /// nothing to undo. A change that fails to reverse stays undoable.
+    ///
+    /// Fails with a swap conflict (see [`AtprotoError::is_swap_conflict`])
+    /// if the record was edited since the change.
+    pub async fn undo(&self, client: &AtprotoClient) -> Result<Option<JournalEntry>, AtprotoError> {
+        let Some(mut entry) = self.lock().undo.pop_back() else {
+            return Ok(None);
+        };
+        let before = entry.before.clone();
+        if let Err(e) = apply(client, &mut entry, before.as_ref()).await {
+            self.lock().undo.push_back(entry);
+            return Err(e);
+        }
+        self.lock().redo.push(entry.clone());
+        Ok(Some(entry))
+    }
+
+    /// Reapply the most recently undone change, returning it, or `None` if
+    /// there is nothing to redo. Fails with a swap conflict like
+    /// [`undo`](Self::undo).
+    pub async fn redo(&self, client: &AtprotoClient) -> Result<Option<JournalEntry>, AtprotoError> {
+        let Some(mut entry) = self.lock().redo.pop() else {
+            return Ok(None);
+        };
+        let after = entry.after.clone();
+        if let Err(e) = apply(client, &mut entry, after.as_ref()).await {
+            self.lock().redo.push(entry);
+            return Err(e);
+        }
+        self.push_undo(&mut self.lock(), entry.clone());
+        Ok(Some(entry))
+    }
+
+    fn push_undo(&self, stacks: &mut Stacks, entry: JournalEntry) {
+        if stacks.undo.len() == self.capacity {
+            stacks.undo.pop_front();
+        }
+        stacks.undo.push_back(entry);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Stacks> {
+        self.stacks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Make the record's current state `value`: write it, or delete the record
+/// if `value` is `None`. The record must still have `entry.cid`, which is
+/// updated to the record's new CID.
+async fn apply(
+    client: &AtprotoClient,
+    entry: &mut JournalEntry,
+    value: Option<&Value>,
+) -> Result<(), AtprotoError> {
+    let (collection, rkey) = (entry.collection.as_str(), entry.rkey.as_str());
+    let Some(swap_cid) = entry.cid.as_deref() else {
+        // The change left the record deleted, so it must not have come back
+        match client.get_record::<Value>(collection, rkey).await {
+            Err(AtprotoError::NotFound { .. }) => {}
+            Ok(_) => return Err(conflict(entry)),
+            Err(e) => return Err(e),
+        }
+        if let Some(value) = value {
+            let created = client.create_record(collection, Some(rkey), value).await?;
+            entry.cid = Some(created.cid);
+        }
+        return Ok(());
+    };
+    match value {
+        Some(value) => {
+            let written = client
+                .put_record_swap(collection, rkey, value, Some(swap_cid))
+                .await?;
+            entry.cid = Some(written.cid);
+        }
+        None => {
+            client
+                .delete_record_swap(collection, rkey, Some(swap_cid))
+                .await?;
+            entry.cid = None;
+        }
+    }
+    Ok(())
+}
+
+/// The error for a record that changed since `entry` last touched it,
+/// matching what the PDS returns for a failed swap.
+fn conflict(entry: &JournalEntry) -> AtprotoError {
+    AtprotoError::Xrpc {
+        error: "InvalidSwap".to_string(),
+        message: format!(
+            "{}/{} was recreated after it was deleted",
+            entry.collection, entry.rkey
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(rkey: &str) -> JournalEntry {
+        JournalEntry::new("diy.razorgirl.winter.note", rkey, None, Some(json!({})))
+    }
+
+    #[test]
+    fn oldest_entries_are_forgotten_past_capacity() {
+        let journal = Journal::new(2);
+        journal.record(entry("a"));
+        journal.record(entry("b"));
+        journal.record(entry("c"));
+
+        let stacks = journal.lock();
+        let rkeys: Vec<_> = stacks.undo.iter().map(|e| e.rkey.as_str()).collect();
+        assert_eq!(rkeys, ["b", "c"]);
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let journal = Journal::new(2);
+        journal.lock().redo.push(entry("a"));
+
+        journal.record(entry("b"));
+
+        assert!(journal.lock().redo.is_empty());
+    }
+}
@@ -6,9 +6,18 @@
 //! - Identity (values, interests, self_description)
 //! - Scheduled jobs
 
+mod api;
+mod csrf;
 mod error;
+mod flash;
+mod jetstream;
+mod job_stream;
+mod journal;
 mod routes;
+mod search;
 mod sse;
+#[cfg(test)]
+mod test_support;
 mod thought_stream;
 
 pub use error::WebError;
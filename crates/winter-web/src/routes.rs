@@ -1,44 +1,158 @@
 //! Web routes.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use axum::{
     Form, Router,
-    extract::{Path, State},
-    response::{Html, IntoResponse, Json, Redirect},
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    middleware,
+    response::{Html, IntoResponse, Json, Redirect, Response},
     routing::{get, post},
 };
 use chrono::Utc;
-use serde::Deserialize;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use tokio::sync::{RwLock, broadcast};
 use tower_http::services::ServeDir;
 use tracing::warn;
 
 use winter_atproto::{
-    AtprotoClient, CustomTool, DIRECTIVE_COLLECTION, Directive, DirectiveKind, FACT_COLLECTION,
-    FACT_DECLARATION_COLLECTION, Fact, FactDeclArg, FactDeclaration, IDENTITY_COLLECTION,
-    IDENTITY_KEY, Identity, JOB_COLLECTION, Job, JobSchedule, JobStatus, NOTE_COLLECTION, Note,
-    RULE_COLLECTION, Rule, SECRET_META_COLLECTION, SECRET_META_KEY, SecretMeta, THOUGHT_COLLECTION,
-    TOOL_APPROVAL_COLLECTION, TOOL_COLLECTION, Thought, Tid, ToolApproval, ToolApprovalStatus,
-    WIKI_ENTRY_COLLECTION, WIKI_LINK_COLLECTION, WikiEntry, WikiLink,
+    AtprotoClient, AtprotoError, CustomTool, DIRECTIVE_COLLECTION, Directive, DirectiveKind,
+    FACT_COLLECTION, FACT_DECLARATION_COLLECTION, Fact, FactDeclArg, FactDeclaration,
+    IDENTITY_COLLECTION, IDENTITY_KEY, Identity, JOB_COLLECTION, Job, JobSchedule, JobStatus,
+    NOTE_COLLECTION, Note, RULE_COLLECTION, Rule, SECRET_META_COLLECTION, SECRET_META_KEY,
+    SecretMeta, THOUGHT_COLLECTION, TOOL_APPROVAL_COLLECTION, TOOL_COLLECTION, Thought,
+    ThoughtContentType, Tid, ToolApproval, ToolApprovalStatus, WIKI_ENTRY_COLLECTION,
+    WIKI_LINK_COLLECTION, WikiEntry, WikiLink, directive_history, fact_ref_rkey, note_backlinks,
+    render_markdown, resolve_related_facts,
 };
+use winter_datalog::DependencyGraphExport;
 use winter_mcp::SecretManager;
-
-use crate::sse::create_sse_stream;
+use winter_mcp::tools::wiki;
+
+use crate::WebError;
+use crate::api;
+use crate::csrf::csrf_protect;
+use crate::flash::{Flash, redirect_with_error};
+use crate::job_stream::{job_status_name, subscribe_jobs};
+use crate::journal::{DEFAULT_JOURNAL_CAPACITY, Journal, JournalEntry, snapshot};
+use crate::search::{SearchCorpus, render_groups};
+use crate::sse::{DEFAULT_REPLAY_CAPACITY, EventLog, create_replay_sse_stream, create_sse_stream};
 use crate::thought_stream::subscribe_thoughts;
 
 /// Shared state for the web server.
 pub struct AppState {
     pub client: AtprotoClient,
-    pub thought_tx: broadcast::Sender<String>,
+    /// Live thoughts, with a replay buffer for reconnecting SSE clients.
+    pub thoughts: Arc<EventLog>,
+    /// Job status transitions, as JSON-encoded `JobStatusEvent`s.
+    pub job_tx: broadcast::Sender<String>,
     /// Secret manager for custom tools (optional).
     pub secrets: Option<Arc<RwLock<SecretManager>>>,
+    /// Whether the Jetstream thought subscription is connected
+    /// (`None` when no DID was given and there is no subscription).
+    pub thought_stream_connected: Option<Arc<AtomicBool>>,
+    /// Operator edits, for `/undo` and `/redo`.
+    pub journal: Journal,
+}
+
+impl AppState {
+    /// Create a record and journal the change.
+    async fn create_journaled<T: Serialize>(
+        &self,
+        collection: &str,
+        rkey: &str,
+        record: &T,
+    ) -> Result<(), AtprotoError> {
+        let created = self
+            .client
+            .create_record(collection, Some(rkey), record)
+            .await?;
+        self.journal.record(
+            JournalEntry::new(collection, rkey, None, snapshot(record)).with_cid(created.cid),
+        );
+        Ok(())
+    }
+
+    /// Overwrite a record (see [`AtprotoClient::put_record_swap`]) and
+    /// journal the change from `before`.
+    async fn put_journaled<T: Serialize>(
+        &self,
+        collection: &str,
+        rkey: &str,
+        before: Option<Value>,
+        record: &T,
+        swap_cid: Option<&str>,
+    ) -> Result<(), AtprotoError> {
+        let written = self
+            .client
+            .put_record_swap(collection, rkey, record, swap_cid)
+            .await?;
+        self.journal.record(
+            JournalEntry::new(collection, rkey, before, snapshot(record)).with_cid(written.cid),
+        );
+        Ok(())
+    }
+
+    /// Delete a record and journal the change. The record is read first so
+    /// the delete can be undone; a record that's already gone counts as
+    /// deleted, with nothing to journal.
+    async fn delete_journaled(&self, collection: &str, rkey: &str) -> Result<(), AtprotoError> {
+        let before = match self.client.get_record::<Value>(collection, rkey).await {
+            Ok(record) => record,
+            Err(AtprotoError::NotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        match self
+            .client
+            .delete_record_swap(collection, rkey, before.cid.as_deref())
+            .await
+        {
+            Ok(()) | Err(AtprotoError::NotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+        self.journal.record(JournalEntry::new(
+            collection,
+            rkey,
+            Some(before.value),
+            None,
+        ));
+        Ok(())
+    }
+
+    /// Soft-delete a fact or note by stamping its `deletedAt` tombstone, the
+    /// way the MCP tools do, and journal the change. It stays restorable until
+    /// the daemon purges it after the retention window.
+    async fn tombstone_journaled(&self, collection: &str, rkey: &str) -> Result<(), AtprotoError> {
+        let before = match self.client.get_record::<Value>(collection, rkey).await {
+            Ok(record) => record,
+            Err(AtprotoError::NotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut record = before.value.clone();
+        // Keep the original tombstone if the record is already soft-deleted
+        if let Some(fields) = record.as_object_mut() {
+            fields
+                .entry("deletedAt")
+                .or_insert_with(|| json!(Utc::now()));
+        }
+        self.put_journaled(
+            collection,
+            rkey,
+            Some(before.value),
+            &record,
+            before.cid.as_deref(),
+        )
+        .await
+    }
 }
 
 /// Create the web router.
 ///
-/// If `did` is provided, subscribes to real-time thought updates via Jetstream.
+/// If `did` is provided, subscribes to real-time thought and job status
+/// updates via Jetstream.
 pub fn create_router(
     client: AtprotoClient,
     static_dir: Option<&str>,
@@ -48,30 +162,42 @@ pub fn create_router(
 }
 
 /// Create the web router with optional secret manager.
+///
+/// All POST routes require a CSRF token (see [`crate::csrf`]).
 pub fn create_router_with_secrets(
     client: AtprotoClient,
     static_dir: Option<&str>,
     did: Option<String>,
     secrets: Option<SecretManager>,
 ) -> Router {
-    let (thought_tx, _) = broadcast::channel(100);
+    let thoughts = Arc::new(EventLog::new(DEFAULT_REPLAY_CAPACITY));
+    let (job_tx, _) = broadcast::channel(100);
+    let thought_stream_connected = did.as_ref().map(|_| Arc::new(AtomicBool::new(false)));
 
     let state = Arc::new(AppState {
         client,
-        thought_tx: thought_tx.clone(),
+        thoughts: Arc::clone(&thoughts),
+        job_tx: job_tx.clone(),
         secrets: secrets.map(|s| Arc::new(RwLock::new(s))),
+        thought_stream_connected: thought_stream_connected.clone(),
+        journal: Journal::new(DEFAULT_JOURNAL_CAPACITY),
     });
 
-    // Subscribe to Jetstream for real-time thought updates
-    if let Some(did) = did {
+    // Subscribe to Jetstream for real-time thought and job status updates
+    if let (Some(did), Some(connected)) = (did, thought_stream_connected) {
+        let job_did = did.clone();
+        tokio::spawn(async move {
+            subscribe_jobs(job_did, job_tx).await;
+        });
         tokio::spawn(async move {
-            subscribe_thoughts(did, thought_tx).await;
+            subscribe_thoughts(did, thoughts, connected).await;
         });
     }
 
     let mut router = Router::new()
         .route("/", get(index))
         .route("/stream", get(stream_page))
+        .route("/search", get(search_page))
         // Facts
         .route("/facts", get(facts_page))
         .route("/facts/new", get(fact_new))
@@ -88,6 +214,7 @@ pub fn create_router_with_secrets(
         .route("/api/rules", post(create_rule))
         .route("/api/rules/{rkey}", post(update_rule))
         .route("/api/rules/{rkey}/delete", post(delete_rule))
+        .route("/predicates/graph", get(predicate_graph))
         // Jobs
         .route("/jobs", get(jobs_page))
         .route("/jobs/new", get(job_new))
@@ -96,6 +223,8 @@ pub fn create_router_with_secrets(
         .route("/api/jobs", post(create_job))
         .route("/api/jobs/{rkey}", post(update_job))
         .route("/api/jobs/{rkey}/delete", post(delete_job))
+        .route("/api/jobs/{rkey}/run", post(run_job))
+        .route("/api/jobs/sse", get(jobs_sse))
         // Notes
         .route("/notes", get(notes_page))
         .route("/notes/new", get(note_new))
@@ -117,6 +246,7 @@ pub fn create_router_with_secrets(
         .route("/directives/new", get(directive_new))
         .route("/directives/{rkey}", get(directive_detail))
         .route("/directives/{rkey}/edit", get(directive_edit))
+        .route("/directives/{rkey}/history", get(directive_history_page))
         .route("/api/directives", post(create_directive))
         .route("/api/directives/{rkey}", post(update_directive))
         .route("/api/directives/{rkey}/delete", post(delete_directive))
@@ -139,8 +269,16 @@ pub fn create_router_with_secrets(
         .route("/api/secrets/{name}", post(update_secret))
         .route("/api/secrets/{name}/delete", post(delete_secret))
         // Other
+        // Undo/redo of operator edits
+        .route("/undo", post(undo))
+        .route("/redo", post(redo))
+        .route("/style.css", get(stylesheet))
         .route("/health", get(health))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .route("/api/thoughts/sse", get(thoughts_sse))
+        // JSON API
+        .merge(api::routes())
         .with_state(state);
 
     // Serve static files if directory provided
@@ -148,13 +286,49 @@ pub fn create_router_with_secrets(
         router = router.nest_service("/static", ServeDir::new(dir));
     }
 
-    router
+    router.layer(middleware::from_fn(csrf_protect))
 }
 
 async fn index() -> impl IntoResponse {
     Html(INDEX_HTML)
 }
 
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+}
+
+async fn search_page(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> impl IntoResponse {
+    let query = params.q.unwrap_or_default();
+    let results = if query.trim().is_empty() {
+        String::new()
+    } else {
+        let groups = SearchCorpus::load(&state.client).await.search(&query);
+        if groups.is_empty() {
+            "<p class=\"empty\">No results.</p>".to_string()
+        } else {
+            render_groups(&groups)
+        }
+    };
+
+    Html(
+        SEARCH_HTML
+            .replace("<!-- QUERY -->", &html_escape(&query))
+            .replace("<!-- RESULTS -->", &results),
+    )
+}
+
+/// Styles shared by every page.
+async fn stylesheet() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        STYLE_CSS,
+    )
+}
+
 async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     // Check identity loaded
     let identity_ok = state
@@ -179,6 +353,55 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     }))
 }
 
+/// Liveness probe: the process is up and serving requests.
+async fn healthz() -> impl IntoResponse {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe: the PDS is reachable and, when subscribed, the thought
+/// stream is connected to Jetstream.
+async fn readyz(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let pds_ok = state
+        .client
+        .get_record::<Identity>(IDENTITY_COLLECTION, IDENTITY_KEY)
+        .await
+        .is_ok();
+    let thought_stream = state
+        .thought_stream_connected
+        .as_ref()
+        .map(|connected| connected.load(Ordering::SeqCst));
+
+    let (status, body) = readiness_report(pds_ok, thought_stream);
+    (status, Json(body))
+}
+
+/// Build the readiness status and per-subsystem breakdown.
+fn readiness_report(pds_ok: bool, thought_stream: Option<bool>) -> (StatusCode, serde_json::Value) {
+    let stream_ready = thought_stream.unwrap_or(true);
+    let ready = pds_ok && stream_ready;
+
+    let thought_stream = match thought_stream {
+        Some(connected) => json!({ "ready": connected, "connected": connected }),
+        None => json!({ "ready": true, "state": "not_configured" }),
+    };
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        json!({
+            "ready": ready,
+            "subsystems": {
+                "pds": { "ready": pds_ok, "identity_loaded": pds_ok },
+                "thought_stream": thought_stream,
+            }
+        }),
+    )
+}
+
 async fn stream_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     // Load recent thoughts
     let thoughts = match state
@@ -197,7 +420,8 @@ async fn stream_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     for item in &thoughts {
         let kind = thought_kind_to_string(&item.value.kind);
         let kind_display = kind.replace('_', " ");
-        let content = format_thought_content(&kind, &item.value.content);
+        let content =
+            format_thought_content(&item.value.effective_content_type(), &item.value.content);
         let rel_time = format_relative_time(item.value.created_at);
         let abs_time = item.value.created_at.to_rfc3339();
 
@@ -271,42 +495,44 @@ async fn stream_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     Html(STREAM_HTML.replace("<!-- THOUGHTS -->", &thought_html))
 }
 
-/// Format thought content based on kind.
-fn format_thought_content(kind: &str, content: &str) -> String {
-    if kind == "tool_call" {
-        format_tool_call_content(content)
-    } else {
-        html_escape(content)
+/// Format thought content based on its content type.
+fn format_thought_content(content_type: &ThoughtContentType, content: &str) -> String {
+    match content_type {
+        ThoughtContentType::ToolCall => format_tool_call_content(content),
+        ThoughtContentType::LegacyToolCall => format_legacy_tool_call_content(content),
+        ThoughtContentType::Text => html_escape(content),
     }
 }
 
-/// Format tool call content with syntax highlighting.
+/// Format a JSON tool call payload with syntax highlighting.
 ///
-/// Handles three formats:
-/// 1. JSON format (new): `{"tool":"name","args":{...},"result":{...},"summary":"..."}`
-/// 2. Text format (previous): "Called tool_name\nArgs:\n{...}\nResult: ..."
-/// 3. Legacy format: "Called tool_name [args] - FAILED"
+/// Expects `{"tool":"name","args":{...},"result":{...},"summary":"..."}`.
 fn format_tool_call_content(content: &str) -> String {
-    // Try parsing as JSON first (new format)
     let trimmed = content.trim();
-    if trimmed.starts_with('{') && trimmed.ends_with('}') {
-        match serde_json::from_str::<serde_json::Value>(trimmed) {
-            Ok(json) => return format_tool_call_json(&json),
-            Err(e) => {
-                // Log the error for debugging
-                tracing::warn!(error = %e, content_len = trimmed.len(), "Failed to parse tool call JSON");
-                // Try a simple regex fallback to at least show the tool name
-                if let Some(tool_match) = extract_json_field(trimmed, "tool") {
-                    return format!(
-                        r#"<div class="tool-header"><span class="tool-name">{}</span></div><pre class="tool-json">{}</pre>"#,
-                        html_escape(&tool_match),
-                        syntax_highlight_json(&html_escape(trimmed))
-                    );
-                }
+    match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(json) => format_tool_call_json(&json),
+        Err(e) => {
+            // Log the error for debugging
+            tracing::warn!(error = %e, content_len = trimmed.len(), "Failed to parse tool call JSON");
+            // Try a simple regex fallback to at least show the tool name
+            match extract_json_field(trimmed, "tool") {
+                Some(tool_match) => format!(
+                    r#"<div class="tool-header"><span class="tool-name">{}</span></div><pre class="tool-json">{}</pre>"#,
+                    html_escape(&tool_match),
+                    syntax_highlight_json(&html_escape(trimmed))
+                ),
+                None => html_escape(content),
             }
         }
     }
+}
 
+/// Format a pre-JSON textual tool call.
+///
+/// Handles two formats:
+/// 1. Text format: "Called tool_name\nArgs:\n{...}\nResult: ..."
+/// 2. Oldest format: "Called tool_name [args] - FAILED"
+fn format_legacy_tool_call_content(content: &str) -> String {
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
         return html_escape(content);
@@ -532,7 +758,7 @@ fn syntax_highlight_json(escaped_json: &str) -> String {
 }
 
 /// Convert ThoughtKind to snake_case string for CSS classes.
-fn thought_kind_to_string(kind: &winter_atproto::ThoughtKind) -> String {
+pub(crate) fn thought_kind_to_string(kind: &winter_atproto::ThoughtKind) -> String {
     use winter_atproto::ThoughtKind;
     match kind {
         ThoughtKind::Insight => "insight",
@@ -633,6 +859,10 @@ async fn facts_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
             Vec::new()
         }
     };
+    let facts: Vec<_> = facts
+        .into_iter()
+        .filter(|item| item.value.deleted_at.is_none())
+        .collect();
 
     let mut facts_html = String::new();
     for item in &facts {
@@ -663,14 +893,15 @@ async fn facts_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 async fn fact_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let fact = match state
         .client
         .get_record::<Fact>(FACT_COLLECTION, &rkey)
         .await
     {
         Ok(f) => f.value,
-        Err(_) => return Html(FACT_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("fact {}", rkey), FACT_NOT_FOUND_HTML, e),
     };
 
     let source_html = fact
@@ -690,8 +921,41 @@ async fn fact_detail(
         format!("<p><strong>Tags:</strong> {}</p>", fact.tags.join(", "))
     };
 
+    let notes: std::collections::HashMap<String, Note> =
+        match state.client.list_all_records::<Note>(NOTE_COLLECTION).await {
+            Ok(records) => records
+                .into_iter()
+                .filter(|r| r.value.deleted_at.is_none())
+                .map(|r| {
+                    let key = r.uri.split('/').next_back().unwrap_or("").to_string();
+                    (key, r.value)
+                })
+                .collect(),
+            Err(e) => {
+                warn!(error = %e, "failed to load notes for fact backlinks");
+                std::collections::HashMap::new()
+            }
+        };
+    let backlinks = note_backlinks(&rkey, &notes);
+    let backlinks_html = if backlinks.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<h2>Referenced by Notes</h2><ul class=\"backlinks\">{}</ul>",
+            backlinks
+                .into_iter()
+                .map(|(note_rkey, note)| format!(
+                    "<li><a href=\"/notes/{}\">{}</a></li>",
+                    html_escape(note_rkey),
+                    html_escape(&note.title)
+                ))
+                .collect::<String>()
+        )
+    };
+
     Html(
         FACT_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- PREDICATE -->", &html_escape(&fact.predicate))
             .replace("<!-- ARGS -->", &html_escape(&fact.args.join(", ")))
@@ -701,63 +965,85 @@ async fn fact_detail(
             )
             .replace("<!-- SOURCE -->", &source_html)
             .replace("<!-- TAGS -->", &tags_html)
+            .replace("<!-- BACKLINKS -->", &backlinks_html)
             .replace(
                 "<!-- CREATED_AT -->",
                 &fact.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
             ),
     )
+    .into_response()
 }
 
-async fn fact_new() -> impl IntoResponse {
-    Html(
-        FACT_FORM_HTML
-            .replace("<!-- TITLE -->", "New Fact")
-            .replace("<!-- ACTION -->", "/api/facts")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- PREDICATE -->", "")
-            .replace("<!-- ARGS -->", "")
-            .replace("<!-- CONFIDENCE -->", "1.0")
-            .replace("<!-- SOURCE -->", "")
-            .replace("<!-- TAGS -->", ""),
-    )
+async fn fact_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    Html(fact_form_html(
+        "New Fact",
+        "/api/facts",
+        "",
+        &FactForm::default(),
+        &flash,
+    ))
 }
 
 async fn fact_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let fact = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (fact, cid) = match state
         .client
         .get_record::<Fact>(FACT_COLLECTION, &rkey)
         .await
     {
-        Ok(f) => f.value,
-        Err(_) => return Html(FACT_NOT_FOUND_HTML.to_string()),
+        Ok(f) => (f.value, f.cid),
+        Err(e) => return record_error(&format!("fact {}", rkey), FACT_NOT_FOUND_HTML, e),
     };
 
-    Html(
-        FACT_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Fact")
-            .replace("<!-- ACTION -->", &format!("/api/facts/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- PREDICATE -->", &html_escape(&fact.predicate))
-            .replace("<!-- ARGS -->", &html_escape(&fact.args.join(", ")))
-            .replace(
-                "<!-- CONFIDENCE -->",
-                &format!("{:.2}", fact.confidence.unwrap_or(1.0)),
-            )
-            .replace(
-                "<!-- SOURCE -->",
-                &html_escape(fact.source.as_deref().unwrap_or("")),
-            )
-            .replace("<!-- TAGS -->", &fact.tags.join(", ")),
-    )
+    let form = FactForm {
+        predicate: fact.predicate,
+        args: fact.args.join(", "),
+        confidence: fact.confidence,
+        source: fact.source,
+        tags: Some(fact.tags.join(", ")),
+        cid,
+    };
+    Html(fact_form_html(
+        "Edit Fact",
+        &format!("/api/facts/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
 }
 
-async fn create_fact(
-    State(state): State<Arc<AppState>>,
-    Form(form): Form<FactForm>,
-) -> impl IntoResponse {
+/// Render the fact form filled in from `form`.
+fn fact_form_html(title: &str, action: &str, rkey: &str, form: &FactForm, flash: &Flash) -> String {
+    FACT_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- PREDICATE -->", &html_escape(&form.predicate))
+        .replace("<!-- ARGS -->", &html_escape(&form.args))
+        .replace(
+            "<!-- CONFIDENCE -->",
+            &form.confidence.unwrap_or(1.0).to_string(),
+        )
+        .replace(
+            "<!-- SOURCE -->",
+            &html_escape(form.source.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- TAGS -->",
+            &html_escape(form.tags.as_deref().unwrap_or("")),
+        )
+}
+
+async fn create_fact(State(state): State<Arc<AppState>>, Form(form): Form<FactForm>) -> Response {
     let args: Vec<String> = form
         .args
         .split(',')
@@ -765,6 +1051,14 @@ async fn create_fact(
         .filter(|s| !s.is_empty())
         .collect();
 
+    if let Err(e) = validate_fact(&form.predicate, &args, form.confidence) {
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            fact_form_html("New Fact", "/api/facts", "", &form, &flash),
+        );
+    }
+
     let fact = Fact {
         predicate: form.predicate,
         args,
@@ -774,18 +1068,15 @@ async fn create_fact(
         tags: parse_comma_separated(&form.tags),
         created_at: Utc::now(),
         expires_at: None,
+        deleted_at: None,
     };
 
     let rkey = Tid::now().to_string();
-    match state
-        .client
-        .create_record(FACT_COLLECTION, Some(&rkey), &fact)
-        .await
-    {
-        Ok(_) => Redirect::to(&format!("/facts/{}", rkey)),
+    match state.create_journaled(FACT_COLLECTION, &rkey, &fact).await {
+        Ok(_) => Redirect::to(&format!("/facts/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create fact");
-            Redirect::to("/facts")
+            redirect_with_error("/facts/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -794,14 +1085,28 @@ async fn update_fact(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<FactForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<Fact>(FACT_COLLECTION, &rkey)
         .await
     {
-        Ok(f) => f.value,
-        Err(_) => return Redirect::to("/facts"),
+        Ok(f) => (f.value, f.cid),
+        Err(e) => return WebError::from_pds(&format!("fact {}", rkey), e).into_response(),
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("fact {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                fact_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
     let args: Vec<String> = form
@@ -811,6 +1116,16 @@ async fn update_fact(
         .filter(|s| !s.is_empty())
         .collect();
 
+    let edit_path = format!("/facts/{}/edit", rkey);
+    if let Err(e) = validate_fact(&form.predicate, &args, form.confidence) {
+        let action = format!("/api/facts/{}", rkey);
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            fact_form_html("Edit Fact", &action, &rkey, &form, &flash),
+        );
+    }
+
     let fact = Fact {
         predicate: form.predicate,
         args,
@@ -820,26 +1135,36 @@ async fn update_fact(
         tags: parse_comma_separated(&form.tags),
         created_at: existing.created_at,
         expires_at: existing.expires_at,
+        deleted_at: existing.deleted_at,
     };
 
-    match state.client.put_record(FACT_COLLECTION, &rkey, &fact).await {
-        Ok(_) => Redirect::to(&format!("/facts/{}", rkey)),
+    match state
+        .put_journaled(FACT_COLLECTION, &rkey, before, &fact, swap.as_deref())
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/facts/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("fact {}", rkey));
+            conflict_response(
+                fact_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update fact");
-            Redirect::to(&format!("/facts/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
 
-async fn delete_fact(
-    State(state): State<Arc<AppState>>,
-    Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state.client.delete_record(FACT_COLLECTION, &rkey).await;
+async fn delete_fact(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Redirect {
+    if let Err(e) = state.tombstone_journaled(FACT_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete fact");
+        return redirect_with_error(&format!("/facts/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/facts")
 }
 
-async fn identity_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+async fn identity_page(State(state): State<Arc<AppState>>) -> Response {
     let identity = state
         .client
         .get_record::<Identity>(IDENTITY_COLLECTION, IDENTITY_KEY)
@@ -870,6 +1195,7 @@ async fn identity_page(State(state): State<Arc<AppState>>) -> impl IntoResponse
     let mut guidelines: Vec<&Directive> = Vec::new();
     let mut boundaries: Vec<&Directive> = Vec::new();
     let mut aspirations: Vec<&Directive> = Vec::new();
+    let mut goals: Vec<&Directive> = Vec::new();
 
     for d in &directives {
         match d.kind {
@@ -880,6 +1206,7 @@ async fn identity_page(State(state): State<Arc<AppState>>) -> impl IntoResponse
             DirectiveKind::Guideline => guidelines.push(d),
             DirectiveKind::Boundary => boundaries.push(d),
             DirectiveKind::Aspiration => aspirations.push(d),
+            DirectiveKind::Goal => goals.push(d),
         }
     }
 
@@ -927,8 +1254,9 @@ async fn identity_page(State(state): State<Arc<AppState>>) -> impl IntoResponse
                     .replace("<!-- CREATED -->", &id.created_at.to_rfc3339())
                     .replace("<!-- UPDATED -->", &id.last_updated.to_rfc3339()),
             )
+            .into_response()
         }
-        Err(_) => Html(IDENTITY_NOT_FOUND_HTML.to_string()),
+        Err(e) => record_error("identity", IDENTITY_NOT_FOUND_HTML, e),
     }
 }
 
@@ -948,15 +1276,15 @@ async fn jobs_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
             JobSchedule::Once { at } => format!("once at {}", at.to_rfc3339()),
             JobSchedule::Interval { seconds } => format!("every {}s", seconds),
         };
-        let status = format!("{:?}", item.value.status).to_lowercase();
+        let status = job_status_name(&item.value.status);
 
         jobs_html.push_str(&format!(
-            r#"<tr onclick="window.location='/jobs/{rkey}'" style="cursor:pointer">
+            r#"<tr data-rkey="{rkey}" onclick="window.location='/jobs/{rkey}'" style="cursor:pointer">
                 <td><a href="/jobs/{rkey}"><code>{}</code></a></td>
                 <td>{}</td>
                 <td>{}</td>
                 <td><span class="status {}">{}</span></td>
-                <td>{}</td>
+                <td class="next-run">{}</td>
             </tr>"#,
             html_escape(rkey),
             html_escape(&item.value.name),
@@ -980,10 +1308,11 @@ async fn jobs_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 async fn job_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let job = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await {
         Ok(j) => j.value,
-        Err(_) => return Html(JOB_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("job {}", rkey), JOB_NOT_FOUND_HTML, e),
     };
 
     let schedule_html = match &job.schedule {
@@ -991,22 +1320,38 @@ async fn job_detail(
         JobSchedule::Interval { seconds } => format!("Every {} seconds", seconds),
     };
 
-    let status = match &job.status {
-        JobStatus::Pending => "pending",
-        JobStatus::Running => "running",
-        JobStatus::Completed => "completed",
-        JobStatus::Failed { .. } => "failed",
-    };
+    let status = job_status_name(&job.status);
 
-    let status_detail = match &job.status {
+    let mut status_detail = match &job.status {
         JobStatus::Failed { error } => {
             format!("<p class=\"error\">Error: {}</p>", html_escape(error))
         }
         _ => String::new(),
     };
+    if !job.depends_on.is_empty() {
+        let links: Vec<String> = job
+            .depends_on
+            .iter()
+            .map(|dep| {
+                let dep = html_escape(dep);
+                format!("<a href=\"/jobs/{dep}\"><code>{dep}</code></a>")
+            })
+            .collect();
+        status_detail.push_str(&format!(
+            "<p><strong>Depends On:</strong> {}</p>",
+            links.join(", ")
+        ));
+    }
+    if let Some(requested) = job.run_requested_at {
+        status_detail.push_str(&format!(
+            "<p><strong>Run Requested:</strong> {}</p>",
+            requested.format("%Y-%m-%d %H:%M UTC")
+        ));
+    }
 
     Html(
         JOB_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- NAME -->", &html_escape(&job.name))
             .replace("<!-- INSTRUCTIONS -->", &html_escape(&job.instructions))
@@ -1031,66 +1376,93 @@ async fn job_detail(
                 &job.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
             ),
     )
+    .into_response()
 }
 
-async fn job_new() -> impl IntoResponse {
-    Html(
-        JOB_FORM_HTML
-            .replace("<!-- TITLE -->", "New Job")
-            .replace("<!-- ACTION -->", "/api/jobs")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- NAME -->", "")
-            .replace("<!-- INSTRUCTIONS -->", "")
-            .replace("<!-- SCHEDULE_ONCE_CHECKED -->", "checked")
-            .replace("<!-- SCHEDULE_INTERVAL_CHECKED -->", "")
-            .replace("<!-- SCHEDULE_AT -->", "")
-            .replace("<!-- SCHEDULE_SECONDS -->", ""),
-    )
+async fn job_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    Html(job_form_html(
+        "New Job",
+        "/api/jobs",
+        "",
+        &JobForm::default(),
+        &flash,
+    ))
 }
 
 async fn job_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let job = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await {
-        Ok(j) => j.value,
-        Err(_) => return Html(JOB_NOT_FOUND_HTML.to_string()),
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (job, cid) = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await {
+        Ok(j) => (j.value, j.cid),
+        Err(e) => return record_error(&format!("job {}", rkey), JOB_NOT_FOUND_HTML, e),
     };
 
-    let (once_checked, interval_checked, schedule_at, schedule_seconds) = match &job.schedule {
-        JobSchedule::Once { at } => ("checked", "", at.to_rfc3339(), String::new()),
-        JobSchedule::Interval { seconds } => ("", "checked", String::new(), seconds.to_string()),
+    let (schedule_type, schedule_at, schedule_seconds) = match &job.schedule {
+        JobSchedule::Once { at } => ("once", Some(at.to_rfc3339()), None),
+        JobSchedule::Interval { seconds } => ("interval", None, Some(*seconds)),
+    };
+    let form = JobForm {
+        name: job.name,
+        instructions: job.instructions,
+        schedule_type: schedule_type.to_string(),
+        schedule_at,
+        schedule_seconds,
+        cid,
     };
+    Html(job_form_html(
+        "Edit Job",
+        &format!("/api/jobs/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
+}
 
-    Html(
-        JOB_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Job")
-            .replace("<!-- ACTION -->", &format!("/api/jobs/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- NAME -->", &html_escape(&job.name))
-            .replace("<!-- INSTRUCTIONS -->", &html_escape(&job.instructions))
-            .replace("<!-- SCHEDULE_ONCE_CHECKED -->", once_checked)
-            .replace("<!-- SCHEDULE_INTERVAL_CHECKED -->", interval_checked)
-            .replace("<!-- SCHEDULE_AT -->", &schedule_at)
-            .replace("<!-- SCHEDULE_SECONDS -->", &schedule_seconds),
-    )
+/// Render the job form filled in from `form`.
+fn job_form_html(title: &str, action: &str, rkey: &str, form: &JobForm, flash: &Flash) -> String {
+    let interval = form.schedule_type == "interval";
+    JOB_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- NAME -->", &html_escape(&form.name))
+        .replace("<!-- INSTRUCTIONS -->", &html_escape(&form.instructions))
+        .replace(
+            "<!-- SCHEDULE_ONCE_CHECKED -->",
+            if interval { "" } else { "checked" },
+        )
+        .replace(
+            "<!-- SCHEDULE_INTERVAL_CHECKED -->",
+            if interval { "checked" } else { "" },
+        )
+        .replace(
+            "<!-- SCHEDULE_AT -->",
+            &html_escape(form.schedule_at.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- SCHEDULE_SECONDS -->",
+            &form
+                .schedule_seconds
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+        )
 }
 
-async fn create_job(
-    State(state): State<Arc<AppState>>,
-    Form(form): Form<JobForm>,
-) -> impl IntoResponse {
-    let schedule = if form.schedule_type == "interval" {
-        JobSchedule::Interval {
-            seconds: form.schedule_seconds.unwrap_or(3600),
+async fn create_job(State(state): State<Arc<AppState>>, Form(form): Form<JobForm>) -> Response {
+    let schedule = match validate_job(&form) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            let flash = Flash::from_error(&e);
+            return invalid_form(&e, job_form_html("New Job", "/api/jobs", "", &form, &flash));
         }
-    } else {
-        let at = form
-            .schedule_at
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|| Utc::now() + chrono::Duration::hours(1));
-        JobSchedule::Once { at }
     };
 
     let next_run = match &schedule {
@@ -1109,18 +1481,16 @@ async fn create_job(
         next_run,
         failure_count: 0,
         created_at: Utc::now(),
+        run_requested_at: None,
+        depends_on: Vec::new(),
     };
 
     let rkey = Tid::now().to_string();
-    match state
-        .client
-        .create_record(JOB_COLLECTION, Some(&rkey), &job)
-        .await
-    {
-        Ok(_) => Redirect::to(&format!("/jobs/{}", rkey)),
+    match state.create_journaled(JOB_COLLECTION, &rkey, &job).await {
+        Ok(_) => Redirect::to(&format!("/jobs/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create job");
-            Redirect::to("/jobs")
+            redirect_with_error("/jobs/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -1129,23 +1499,35 @@ async fn update_job(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<JobForm>,
-) -> impl IntoResponse {
-    let existing = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await {
-        Ok(j) => j.value,
-        Err(_) => return Redirect::to("/jobs"),
+) -> Response {
+    let (existing, current_cid) = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await
+    {
+        Ok(j) => (j.value, j.cid),
+        Err(e) => return WebError::from_pds(&format!("job {}", rkey), e).into_response(),
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("job {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                job_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
-    let schedule = if form.schedule_type == "interval" {
-        JobSchedule::Interval {
-            seconds: form.schedule_seconds.unwrap_or(3600),
+    let edit_path = format!("/jobs/{}/edit", rkey);
+    let schedule = match validate_job(&form) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            let action = format!("/api/jobs/{}", rkey);
+            let flash = Flash::from_error(&e);
+            return invalid_form(&e, job_form_html("Edit Job", &action, &rkey, &form, &flash));
         }
-    } else {
-        let at = form
-            .schedule_at
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|| Utc::now() + chrono::Duration::hours(1));
-        JobSchedule::Once { at }
     };
 
     let next_run = match &schedule {
@@ -1165,25 +1547,54 @@ async fn update_job(
         next_run,
         failure_count: existing.failure_count,
         created_at: existing.created_at,
+        run_requested_at: existing.run_requested_at,
+        depends_on: existing.depends_on,
     };
 
-    match state.client.put_record(JOB_COLLECTION, &rkey, &job).await {
-        Ok(_) => Redirect::to(&format!("/jobs/{}", rkey)),
+    match state
+        .put_journaled(JOB_COLLECTION, &rkey, before, &job, swap.as_deref())
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/jobs/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("job {}", rkey));
+            conflict_response(
+                job_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update job");
-            Redirect::to(&format!("/jobs/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
 
-async fn delete_job(
-    State(state): State<Arc<AppState>>,
-    Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state.client.delete_record(JOB_COLLECTION, &rkey).await;
+async fn delete_job(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Redirect {
+    if let Err(e) = state.delete_journaled(JOB_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete job");
+        return redirect_with_error(&format!("/jobs/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/jobs")
 }
 
+/// Ask the scheduler to run a job now, outside its schedule.
+///
+/// The scheduler picks up the request from the job record and leaves
+/// `next_run` alone, so recurring jobs keep their cadence.
+async fn run_job(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Response {
+    let mut job = match state.client.get_record::<Job>(JOB_COLLECTION, &rkey).await {
+        Ok(j) => j.value,
+        Err(e) => return WebError::from_pds(&format!("job {}", rkey), e).into_response(),
+    };
+
+    job.run_requested_at = Some(Utc::now());
+    if let Err(e) = state.client.put_record(JOB_COLLECTION, &rkey, &job).await {
+        warn!(error = %e, "failed to request job run");
+        return redirect_with_error(&format!("/jobs/{}", rkey), &WebError::from(e)).into_response();
+    }
+    Redirect::to(&format!("/jobs/{}", rkey)).into_response()
+}
+
 // =============================================================================
 // Rules CRUD
 // =============================================================================
@@ -1236,14 +1647,15 @@ async fn rules_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 async fn rule_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let rule = match state
         .client
         .get_record::<Rule>(RULE_COLLECTION, &rkey)
         .await
     {
         Ok(r) => r.value,
-        Err(_) => return Html(RULE_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("rule {}", rkey), RULE_NOT_FOUND_HTML, e),
     };
 
     let body_html = rule
@@ -1266,6 +1678,7 @@ async fn rule_detail(
 
     Html(
         RULE_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- NAME -->", &html_escape(&rule.name))
             .replace("<!-- DESCRIPTION -->", &html_escape(&rule.description))
@@ -1282,59 +1695,93 @@ async fn rule_detail(
                 &rule.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
             ),
     )
+    .into_response()
 }
 
-async fn rule_new() -> impl IntoResponse {
-    Html(
-        RULE_FORM_HTML
-            .replace("<!-- TITLE -->", "New Rule")
-            .replace("<!-- ACTION -->", "/api/rules")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- NAME -->", "")
-            .replace("<!-- DESCRIPTION -->", "")
-            .replace("<!-- HEAD -->", "")
-            .replace("<!-- BODY -->", "")
-            .replace("<!-- CONSTRAINTS -->", "")
-            .replace("<!-- ENABLED_CHECKED -->", "checked")
-            .replace("<!-- PRIORITY -->", "0"),
-    )
+async fn rule_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    let form = RuleForm {
+        enabled: Some("on".to_string()),
+        ..Default::default()
+    };
+    Html(rule_form_html("New Rule", "/api/rules", "", &form, &flash))
 }
 
 async fn rule_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let rule = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (rule, cid) = match state
         .client
         .get_record::<Rule>(RULE_COLLECTION, &rkey)
         .await
     {
-        Ok(r) => r.value,
-        Err(_) => return Html(RULE_NOT_FOUND_HTML.to_string()),
+        Ok(r) => (r.value, r.cid),
+        Err(e) => return record_error(&format!("rule {}", rkey), RULE_NOT_FOUND_HTML, e),
     };
 
-    Html(
-        RULE_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Rule")
-            .replace("<!-- ACTION -->", &format!("/api/rules/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- NAME -->", &html_escape(&rule.name))
-            .replace("<!-- DESCRIPTION -->", &html_escape(&rule.description))
-            .replace("<!-- HEAD -->", &html_escape(&rule.head))
-            .replace("<!-- BODY -->", &rule.body.join("\n"))
-            .replace("<!-- CONSTRAINTS -->", &rule.constraints.join("\n"))
-            .replace(
-                "<!-- ENABLED_CHECKED -->",
-                if rule.enabled { "checked" } else { "" },
-            )
-            .replace("<!-- PRIORITY -->", &rule.priority.to_string()),
-    )
+    let form = RuleForm {
+        name: rule.name,
+        description: Some(rule.description),
+        head: rule.head,
+        body: rule.body.join("\n"),
+        constraints: Some(rule.constraints.join("\n")),
+        enabled: rule.enabled.then(|| "on".to_string()),
+        priority: Some(rule.priority),
+        cid,
+    };
+    Html(rule_form_html(
+        "Edit Rule",
+        &format!("/api/rules/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
 }
 
-async fn create_rule(
-    State(state): State<Arc<AppState>>,
-    Form(form): Form<RuleForm>,
-) -> impl IntoResponse {
+/// Render the rule form filled in from `form`.
+fn rule_form_html(title: &str, action: &str, rkey: &str, form: &RuleForm, flash: &Flash) -> String {
+    RULE_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- NAME -->", &html_escape(&form.name))
+        .replace(
+            "<!-- DESCRIPTION -->",
+            &html_escape(form.description.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- HEAD -->", &html_escape(&form.head))
+        .replace("<!-- BODY -->", &html_escape(&form.body))
+        .replace(
+            "<!-- CONSTRAINTS -->",
+            &html_escape(form.constraints.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- ENABLED_CHECKED -->",
+            if form.enabled.is_some() {
+                "checked"
+            } else {
+                ""
+            },
+        )
+        .replace("<!-- PRIORITY -->", &form.priority.unwrap_or(0).to_string())
+}
+
+async fn create_rule(State(state): State<Arc<AppState>>, Form(form): Form<RuleForm>) -> Response {
+    if let Err(e) = validate_rule(&form) {
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            rule_form_html("New Rule", "/api/rules", "", &form, &flash),
+        );
+    }
+
     let rule = Rule {
         name: form.name,
         description: form.description.unwrap_or_default(),
@@ -1351,15 +1798,11 @@ async fn create_rule(
     };
 
     let rkey = Tid::now().to_string();
-    match state
-        .client
-        .create_record(RULE_COLLECTION, Some(&rkey), &rule)
-        .await
-    {
-        Ok(_) => Redirect::to(&format!("/rules/{}", rkey)),
+    match state.create_journaled(RULE_COLLECTION, &rkey, &rule).await {
+        Ok(_) => Redirect::to(&format!("/rules/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create rule");
-            Redirect::to("/rules")
+            redirect_with_error("/rules/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -1368,17 +1811,41 @@ async fn update_rule(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<RuleForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<Rule>(RULE_COLLECTION, &rkey)
         .await
     {
-        Ok(r) => r.value,
-        Err(_) => return Redirect::to("/rules"),
+        Ok(r) => (r.value, r.cid),
+        Err(e) => return WebError::from_pds(&format!("rule {}", rkey), e).into_response(),
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("rule {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                rule_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
-    let rule = Rule {
+    let edit_path = format!("/rules/{}/edit", rkey);
+    if let Err(e) = validate_rule(&form) {
+        let action = format!("/api/rules/{}", rkey);
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            rule_form_html("Edit Rule", &action, &rkey, &form, &flash),
+        );
+    }
+
+    let rule = Rule {
         name: form.name,
         description: form.description.unwrap_or_default(),
         head: form.head,
@@ -1393,23 +1860,80 @@ async fn update_rule(
         created_at: existing.created_at,
     };
 
-    match state.client.put_record(RULE_COLLECTION, &rkey, &rule).await {
-        Ok(_) => Redirect::to(&format!("/rules/{}", rkey)),
+    match state
+        .put_journaled(RULE_COLLECTION, &rkey, before, &rule, swap.as_deref())
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/rules/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("rule {}", rkey));
+            conflict_response(
+                rule_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update rule");
-            Redirect::to(&format!("/rules/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
 
-async fn delete_rule(
-    State(state): State<Arc<AppState>>,
-    Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state.client.delete_record(RULE_COLLECTION, &rkey).await;
+async fn delete_rule(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Redirect {
+    if let Err(e) = state.delete_journaled(RULE_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete rule");
+        return redirect_with_error(&format!("/rules/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/rules")
 }
 
+#[derive(Deserialize)]
+struct PredicateGraphParams {
+    format: Option<String>,
+}
+
+/// Predicate dependency graph as JSON, or Graphviz DOT with `?format=dot`.
+async fn predicate_graph(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PredicateGraphParams>,
+) -> Response {
+    let dot = match params.format.as_deref() {
+        None | Some("json") => false,
+        Some("dot") => true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": format!("invalid format '{}': expected 'json' or 'dot'", other)
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let rules: Vec<Rule> = match state.client.list_all_records::<Rule>(RULE_COLLECTION).await {
+        Ok(r) => r.into_iter().map(|item| item.value).collect(),
+        Err(e) => {
+            warn!(error = %e, "failed to load rules for predicate graph");
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "error": format!("failed to load rules: {}", e) })),
+            )
+                .into_response();
+        }
+    };
+
+    let graph = DependencyGraphExport::from_rules(&rules);
+    if dot {
+        (
+            [(header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            graph.to_dot(),
+        )
+            .into_response()
+    } else {
+        Json(graph).into_response()
+    }
+}
+
 // =============================================================================
 // Directives CRUD
 // =============================================================================
@@ -1472,14 +1996,15 @@ async fn directives_page(State(state): State<Arc<AppState>>) -> impl IntoRespons
 async fn directive_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let directive = match state
         .client
         .get_record::<Directive>(DIRECTIVE_COLLECTION, &rkey)
         .await
     {
         Ok(d) => d.value,
-        Err(_) => return Html(DIRECTIVE_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("directive {}", rkey), DIRECTIVE_NOT_FOUND_HTML, e),
     };
 
     let summary_html = directive
@@ -1505,6 +2030,7 @@ async fn directive_detail(
 
     Html(
         DIRECTIVE_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- KIND -->", &format!("{}", directive.kind))
             .replace("<!-- CONTENT -->", &html_escape(&directive.content))
@@ -1539,95 +2065,206 @@ async fn directive_detail(
                     .unwrap_or_else(|| "-".to_string()),
             ),
     )
+    .into_response()
 }
 
-async fn directive_new() -> impl IntoResponse {
+async fn directive_history_page(
+    State(state): State<Arc<AppState>>,
+    Path(rkey): Path<String>,
+) -> Response {
+    let directives: std::collections::HashMap<String, Directive> = match state
+        .client
+        .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
+        .await
+    {
+        Ok(records) => records
+            .into_iter()
+            .map(|r| {
+                let key = r.uri.split('/').next_back().unwrap_or("").to_string();
+                (key, r.value)
+            })
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, "failed to load directives for history page");
+            return WebError::from(e).into_response();
+        }
+    };
+
+    let chain = directive_history(&rkey, &directives);
+    if chain.is_empty() {
+        return not_found_page(DIRECTIVE_NOT_FOUND_HTML);
+    }
+
+    let mut history_html = String::new();
+    // Newest first, so the current form of the directive leads
+    for (version, (key, directive)) in chain.iter().enumerate().rev() {
+        let current = if *key == rkey { " current" } else { "" };
+        let status = if directive.active {
+            "active"
+        } else {
+            "inactive"
+        };
+        let summary_html = directive
+            .summary
+            .as_ref()
+            .map(|s| format!("<p class=\"summary\">{}</p>", html_escape(s)))
+            .unwrap_or_default();
+        let source_html = directive
+            .source
+            .as_ref()
+            .map(|s| format!("<p class=\"meta\">Source: {}</p>", html_escape(s)))
+            .unwrap_or_default();
+
+        history_html.push_str(&format!(
+            r#"<div class="version{current}">
+                <div class="version-header">
+                    <span>v{} · <a href="/directives/{key}"><code>{key}</code></a></span>
+                    <span class="status {status}">{status}</span>
+                </div>
+                {summary_html}
+                <div class="content">{}</div>
+                {source_html}
+                <p class="meta">Created {} · Confidence {:.2}</p>
+            </div>"#,
+            version + 1,
+            html_escape(&directive.content),
+            directive.created_at.format("%Y-%m-%d %H:%M UTC"),
+            directive.confidence.unwrap_or(1.0),
+            key = html_escape(key),
+        ));
+    }
+
+    let kind = chain[chain.len() - 1].1.kind.to_string();
     Html(
-        DIRECTIVE_FORM_HTML
-            .replace("<!-- TITLE -->", "New Directive")
-            .replace("<!-- ACTION -->", "/api/directives")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- KIND_VALUE_SELECTED -->", "")
-            .replace("<!-- KIND_INTEREST_SELECTED -->", "")
-            .replace("<!-- KIND_BELIEF_SELECTED -->", "")
-            .replace("<!-- KIND_GUIDELINE_SELECTED -->", "")
-            .replace("<!-- KIND_SELF_CONCEPT_SELECTED -->", "")
-            .replace("<!-- KIND_BOUNDARY_SELECTED -->", "")
-            .replace("<!-- KIND_ASPIRATION_SELECTED -->", "")
-            .replace("<!-- CONTENT -->", "")
-            .replace("<!-- SUMMARY -->", "")
-            .replace("<!-- ACTIVE_CHECKED -->", "checked")
-            .replace("<!-- CONFIDENCE -->", "1.0")
-            .replace("<!-- SOURCE -->", "")
-            .replace("<!-- PRIORITY -->", "0")
-            .replace("<!-- TAGS -->", ""),
+        DIRECTIVE_HISTORY_HTML
+            .replace("<!-- RKEY -->", &html_escape(&rkey))
+            .replace("<!-- KIND -->", &kind)
+            .replace("<!-- COUNT -->", &chain.len().to_string())
+            .replace("<!-- HISTORY -->", &history_html),
     )
+    .into_response()
+}
+
+async fn directive_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    let form = DirectiveForm {
+        active: Some("on".to_string()),
+        ..Default::default()
+    };
+    Html(directive_form_html(
+        "New Directive",
+        "/api/directives",
+        "",
+        &form,
+        &flash,
+    ))
 }
 
 async fn directive_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let directive = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (directive, cid) = match state
         .client
         .get_record::<Directive>(DIRECTIVE_COLLECTION, &rkey)
         .await
     {
-        Ok(d) => d.value,
-        Err(_) => return Html(DIRECTIVE_NOT_FOUND_HTML.to_string()),
+        Ok(d) => (d.value, d.cid),
+        Err(e) => {
+            return record_error(&format!("directive {}", rkey), DIRECTIVE_NOT_FOUND_HTML, e);
+        }
     };
 
-    let kind_selections = [
-        ("VALUE", DirectiveKind::Value),
-        ("INTEREST", DirectiveKind::Interest),
-        ("BELIEF", DirectiveKind::Belief),
-        ("GUIDELINE", DirectiveKind::Guideline),
-        ("SELF_CONCEPT", DirectiveKind::SelfConcept),
-        ("BOUNDARY", DirectiveKind::Boundary),
-        ("ASPIRATION", DirectiveKind::Aspiration),
-    ];
+    let form = DirectiveForm {
+        kind: directive.kind.to_string(),
+        content: directive.content,
+        summary: directive.summary,
+        active: directive.active.then(|| "on".to_string()),
+        confidence: directive.confidence,
+        source: directive.source,
+        priority: Some(directive.priority),
+        tags: Some(directive.tags.join(", ")),
+        cid,
+    };
+    Html(directive_form_html(
+        "Edit Directive",
+        &format!("/api/directives/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
+}
 
+/// Render the directive form filled in from `form`.
+fn directive_form_html(
+    title: &str,
+    action: &str,
+    rkey: &str,
+    form: &DirectiveForm,
+    flash: &Flash,
+) -> String {
     let mut html = DIRECTIVE_FORM_HTML
-        .replace("<!-- TITLE -->", "Edit Directive")
-        .replace("<!-- ACTION -->", &format!("/api/directives/{}", rkey))
-        .replace("<!-- RKEY -->", &rkey)
-        .replace("<!-- CONTENT -->", &html_escape(&directive.content))
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- CONTENT -->", &html_escape(&form.content))
         .replace(
             "<!-- SUMMARY -->",
-            &html_escape(directive.summary.as_deref().unwrap_or("")),
+            &html_escape(form.summary.as_deref().unwrap_or("")),
         )
         .replace(
             "<!-- ACTIVE_CHECKED -->",
-            if directive.active { "checked" } else { "" },
+            if form.active.is_some() { "checked" } else { "" },
         )
         .replace(
             "<!-- CONFIDENCE -->",
-            &format!("{:.2}", directive.confidence.unwrap_or(1.0)),
+            &form.confidence.unwrap_or(1.0).to_string(),
         )
         .replace(
             "<!-- SOURCE -->",
-            &html_escape(directive.source.as_deref().unwrap_or("")),
+            &html_escape(form.source.as_deref().unwrap_or("")),
         )
-        .replace("<!-- PRIORITY -->", &directive.priority.to_string())
-        .replace("<!-- TAGS -->", &directive.tags.join(", "));
+        .replace("<!-- PRIORITY -->", &form.priority.unwrap_or(0).to_string())
+        .replace(
+            "<!-- TAGS -->",
+            &html_escape(form.tags.as_deref().unwrap_or("")),
+        );
 
-    for (name, kind) in &kind_selections {
-        let placeholder = format!("<!-- KIND_{}_SELECTED -->", name);
-        let selected = if &directive.kind == kind {
-            "selected"
-        } else {
-            ""
-        };
+    for kind in [
+        "value",
+        "interest",
+        "belief",
+        "guideline",
+        "self_concept",
+        "boundary",
+        "aspiration",
+        "goal",
+    ] {
+        let placeholder = format!("<!-- KIND_{}_SELECTED -->", kind.to_uppercase());
+        let selected = if form.kind == kind { "selected" } else { "" };
         html = html.replace(&placeholder, selected);
     }
-
-    Html(html)
+    html
 }
 
 async fn create_directive(
     State(state): State<Arc<AppState>>,
     Form(form): Form<DirectiveForm>,
-) -> impl IntoResponse {
+) -> Response {
+    if let Err(e) = validate_directive(&form) {
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            directive_form_html("New Directive", "/api/directives", "", &form, &flash),
+        );
+    }
+
     let kind = match form.kind.as_str() {
         "value" => DirectiveKind::Value,
         "interest" => DirectiveKind::Interest,
@@ -1636,6 +2273,7 @@ async fn create_directive(
         "self_concept" => DirectiveKind::SelfConcept,
         "boundary" => DirectiveKind::Boundary,
         "aspiration" => DirectiveKind::Aspiration,
+        "goal" => DirectiveKind::Goal,
         _ => DirectiveKind::Guideline,
     };
 
@@ -1656,14 +2294,13 @@ async fn create_directive(
 
     let rkey = Tid::now().to_string();
     match state
-        .client
-        .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
+        .create_journaled(DIRECTIVE_COLLECTION, &rkey, &directive)
         .await
     {
-        Ok(_) => Redirect::to(&format!("/directives/{}", rkey)),
+        Ok(_) => Redirect::to(&format!("/directives/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create directive");
-            Redirect::to("/directives")
+            redirect_with_error("/directives/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -1672,16 +2309,42 @@ async fn update_directive(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<DirectiveForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<Directive>(DIRECTIVE_COLLECTION, &rkey)
         .await
     {
-        Ok(d) => d.value,
-        Err(_) => return Redirect::to("/directives"),
+        Ok(d) => (d.value, d.cid),
+        Err(e) => {
+            return WebError::from_pds(&format!("directive {}", rkey), e).into_response();
+        }
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("directive {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                directive_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
+    let edit_path = format!("/directives/{}/edit", rkey);
+    if let Err(e) = validate_directive(&form) {
+        let action = format!("/api/directives/{}", rkey);
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            directive_form_html("Edit Directive", &action, &rkey, &form, &flash),
+        );
+    }
+
     let kind = match form.kind.as_str() {
         "value" => DirectiveKind::Value,
         "interest" => DirectiveKind::Interest,
@@ -1690,6 +2353,7 @@ async fn update_directive(
         "self_concept" => DirectiveKind::SelfConcept,
         "boundary" => DirectiveKind::Boundary,
         "aspiration" => DirectiveKind::Aspiration,
+        "goal" => DirectiveKind::Goal,
         _ => existing.kind,
     };
 
@@ -1708,14 +2372,25 @@ async fn update_directive(
     };
 
     match state
-        .client
-        .put_record(DIRECTIVE_COLLECTION, &rkey, &directive)
+        .put_journaled(
+            DIRECTIVE_COLLECTION,
+            &rkey,
+            before,
+            &directive,
+            swap.as_deref(),
+        )
         .await
     {
-        Ok(_) => Redirect::to(&format!("/directives/{}", rkey)),
+        Ok(_) => Redirect::to(&format!("/directives/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("directive {}", rkey));
+            conflict_response(
+                directive_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update directive");
-            Redirect::to(&format!("/directives/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
@@ -1723,11 +2398,11 @@ async fn update_directive(
 async fn delete_directive(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state
-        .client
-        .delete_record(DIRECTIVE_COLLECTION, &rkey)
-        .await;
+) -> Redirect {
+    if let Err(e) = state.delete_journaled(DIRECTIVE_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete directive");
+        return redirect_with_error(&format!("/directives/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/directives")
 }
 
@@ -1779,14 +2454,21 @@ async fn declarations_page(State(state): State<Arc<AppState>>) -> impl IntoRespo
 async fn declaration_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let declaration = match state
         .client
         .get_record::<FactDeclaration>(FACT_DECLARATION_COLLECTION, &rkey)
         .await
     {
         Ok(d) => d.value,
-        Err(_) => return Html(DECLARATION_NOT_FOUND_HTML.to_string()),
+        Err(e) => {
+            return record_error(
+                &format!("declaration {}", rkey),
+                DECLARATION_NOT_FOUND_HTML,
+                e,
+            );
+        }
     };
 
     // Build args table rows
@@ -1817,6 +2499,7 @@ async fn declaration_detail(
 
     Html(
         DECLARATION_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- PREDICATE -->", &html_escape(&declaration.predicate))
             .replace("<!-- ARGS_TABLE -->", &args_html)
@@ -1841,59 +2524,104 @@ async fn declaration_detail(
                     .unwrap_or_else(|| "-".to_string()),
             ),
     )
+    .into_response()
 }
 
-async fn declaration_new() -> impl IntoResponse {
-    Html(
-        DECLARATION_FORM_HTML
-            .replace("<!-- TITLE -->", "New Declaration")
-            .replace("<!-- ACTION -->", "/api/declarations")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- PREDICATE -->", "")
-            .replace("<!-- ARGS_JSON -->", "[]")
-            .replace("<!-- DESCRIPTION -->", "")
-            .replace("<!-- TAGS -->", ""),
-    )
+async fn declaration_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    let form = FactDeclarationForm {
+        args_json: "[]".to_string(),
+        ..Default::default()
+    };
+    Html(declaration_form_html(
+        "New Declaration",
+        "/api/declarations",
+        "",
+        &form,
+        &flash,
+    ))
 }
 
 async fn declaration_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let declaration = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (declaration, cid) = match state
         .client
         .get_record::<FactDeclaration>(FACT_DECLARATION_COLLECTION, &rkey)
         .await
     {
-        Ok(d) => d.value,
-        Err(_) => return Html(DECLARATION_NOT_FOUND_HTML.to_string()),
+        Ok(d) => (d.value, d.cid),
+        Err(e) => {
+            return record_error(
+                &format!("declaration {}", rkey),
+                DECLARATION_NOT_FOUND_HTML,
+                e,
+            );
+        }
     };
 
     // Serialize args to JSON for the form
     let args_json =
         serde_json::to_string_pretty(&declaration.args).unwrap_or_else(|_| "[]".to_string());
 
-    Html(
-        DECLARATION_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Declaration")
-            .replace("<!-- ACTION -->", &format!("/api/declarations/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- PREDICATE -->", &html_escape(&declaration.predicate))
-            .replace("<!-- ARGS_JSON -->", &html_escape(&args_json))
-            .replace(
-                "<!-- DESCRIPTION -->",
-                &html_escape(&declaration.description),
-            )
-            .replace("<!-- TAGS -->", &declaration.tags.join(", ")),
-    )
+    let form = FactDeclarationForm {
+        predicate: declaration.predicate,
+        args_json,
+        description: declaration.description,
+        tags: Some(declaration.tags.join(", ")),
+        cid,
+    };
+    Html(declaration_form_html(
+        "Edit Declaration",
+        &format!("/api/declarations/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
+}
+
+/// Render the declaration form filled in from `form`.
+fn declaration_form_html(
+    title: &str,
+    action: &str,
+    rkey: &str,
+    form: &FactDeclarationForm,
+    flash: &Flash,
+) -> String {
+    DECLARATION_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- PREDICATE -->", &html_escape(&form.predicate))
+        .replace("<!-- ARGS_JSON -->", &html_escape(&form.args_json))
+        .replace("<!-- DESCRIPTION -->", &html_escape(&form.description))
+        .replace(
+            "<!-- TAGS -->",
+            &html_escape(form.tags.as_deref().unwrap_or("")),
+        )
 }
 
 async fn create_declaration(
     State(state): State<Arc<AppState>>,
     Form(form): Form<FactDeclarationForm>,
-) -> impl IntoResponse {
-    // Parse args from JSON
-    let args: Vec<FactDeclArg> = serde_json::from_str(&form.args_json).unwrap_or_default();
+) -> Response {
+    let args = match validate_declaration(&form) {
+        Ok(args) => args,
+        Err(e) => {
+            let flash = Flash::from_error(&e);
+            return invalid_form(
+                &e,
+                declaration_form_html("New Declaration", "/api/declarations", "", &form, &flash),
+            );
+        }
+    };
 
     let now = Utc::now();
     let declaration = FactDeclaration {
@@ -1901,20 +2629,20 @@ async fn create_declaration(
         args,
         description: form.description,
         tags: parse_comma_separated(&form.tags),
+        decay: None,
         created_at: now,
         last_updated: Some(now),
     };
 
     let rkey = Tid::now().to_string();
     match state
-        .client
-        .create_record(FACT_DECLARATION_COLLECTION, Some(&rkey), &declaration)
+        .create_journaled(FACT_DECLARATION_COLLECTION, &rkey, &declaration)
         .await
     {
-        Ok(_) => Redirect::to(&format!("/declarations/{}", rkey)),
+        Ok(_) => Redirect::to(&format!("/declarations/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create declaration");
-            Redirect::to("/declarations")
+            redirect_with_error("/declarations/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -1923,37 +2651,75 @@ async fn update_declaration(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<FactDeclarationForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<FactDeclaration>(FACT_DECLARATION_COLLECTION, &rkey)
         .await
     {
-        Ok(d) => d.value,
-        Err(_) => return Redirect::to("/declarations"),
+        Ok(d) => (d.value, d.cid),
+        Err(e) => {
+            return WebError::from_pds(&format!("declaration {}", rkey), e).into_response();
+        }
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("declaration {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                declaration_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
-    // Parse args from JSON
-    let args: Vec<FactDeclArg> = serde_json::from_str(&form.args_json).unwrap_or_default();
+    let edit_path = format!("/declarations/{}/edit", rkey);
+    let args = match validate_declaration(&form) {
+        Ok(args) => args,
+        Err(e) => {
+            let action = format!("/api/declarations/{}", rkey);
+            let flash = Flash::from_error(&e);
+            return invalid_form(
+                &e,
+                declaration_form_html("Edit Declaration", &action, &rkey, &form, &flash),
+            );
+        }
+    };
 
     let declaration = FactDeclaration {
         predicate: form.predicate,
         args,
         description: form.description,
         tags: parse_comma_separated(&form.tags),
+        decay: existing.decay,
         created_at: existing.created_at,
         last_updated: Some(Utc::now()),
     };
 
     match state
-        .client
-        .put_record(FACT_DECLARATION_COLLECTION, &rkey, &declaration)
+        .put_journaled(
+            FACT_DECLARATION_COLLECTION,
+            &rkey,
+            before,
+            &declaration,
+            swap.as_deref(),
+        )
         .await
     {
-        Ok(_) => Redirect::to(&format!("/declarations/{}", rkey)),
+        Ok(_) => Redirect::to(&format!("/declarations/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("declaration {}", rkey));
+            conflict_response(
+                declaration_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update declaration");
-            Redirect::to(&format!("/declarations/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
@@ -1961,11 +2727,14 @@ async fn update_declaration(
 async fn delete_declaration(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state
-        .client
-        .delete_record(FACT_DECLARATION_COLLECTION, &rkey)
-        .await;
+) -> Redirect {
+    if let Err(e) = state
+        .delete_journaled(FACT_DECLARATION_COLLECTION, &rkey)
+        .await
+    {
+        warn!(error = %e, "failed to delete declaration");
+        return redirect_with_error(&format!("/declarations/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/declarations")
 }
 
@@ -1977,6 +2746,10 @@ async fn notes_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
             Vec::new()
         }
     };
+    let notes: Vec<_> = notes
+        .into_iter()
+        .filter(|item| item.value.deleted_at.is_none())
+        .collect();
 
     let mut notes_html = String::new();
     for item in &notes {
@@ -2014,14 +2787,15 @@ async fn notes_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 async fn note_detail(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     let note = match state
         .client
         .get_record::<Note>(NOTE_COLLECTION, &rkey)
         .await
     {
         Ok(n) => n.value,
-        Err(_) => return Html(NOTE_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("note {}", rkey), NOTE_NOT_FOUND_HTML, e),
     };
 
     let tags_html = if note.tags.is_empty() {
@@ -2046,21 +2820,53 @@ async fn note_detail(
     let related_facts_html = if note.related_facts.is_empty() {
         String::new()
     } else {
+        // Fetch referenced facts together; missing ones are shown unresolved
+        let fetches = note
+            .related_facts
+            .iter()
+            .filter_map(|r| fact_ref_rkey(r))
+            .map(|fact_rkey| async {
+                state
+                    .client
+                    .get_record::<Fact>(FACT_COLLECTION, fact_rkey)
+                    .await
+                    .ok()
+                    .map(|record| (fact_rkey.to_string(), record.value))
+            });
+        let facts: std::collections::HashMap<String, Fact> =
+            futures_util::future::join_all(fetches)
+                .await
+                .into_iter()
+                .flatten()
+                .collect();
+
         format!(
             "<h2>Related Facts</h2><ul class=\"related-facts\">{}</ul>",
-            note.related_facts
-                .iter()
-                .map(|f| format!("<li><code>{}</code></li>", html_escape(f)))
+            resolve_related_facts(&note, &facts)
+                .into_iter()
+                .map(|(reference, fact)| match (fact_ref_rkey(reference), fact) {
+                    (Some(fact_rkey), Some(fact)) => format!(
+                        "<li><a href=\"/facts/{}\"><code>{}({})</code></a></li>",
+                        html_escape(fact_rkey),
+                        html_escape(&fact.predicate),
+                        html_escape(&fact.args.join(", "))
+                    ),
+                    _ => format!(
+                        "<li><code>{}</code> <span class=\"missing\">(missing)</span></li>",
+                        html_escape(reference)
+                    ),
+                })
                 .collect::<String>()
         )
     };
 
     Html(
         NOTE_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", &rkey)
             .replace("<!-- TITLE -->", &html_escape(&note.title))
             .replace("<!-- CATEGORY -->", &category_html)
-            .replace("<!-- CONTENT -->", &html_escape(&note.content))
+            .replace("<!-- CONTENT -->", &render_markdown(&note.content))
             .replace("<!-- TAGS -->", &tags_html)
             .replace("<!-- RELATED_FACTS -->", &related_facts_html)
             .replace(
@@ -2072,53 +2878,83 @@ async fn note_detail(
                 &note.last_updated.format("%Y-%m-%d %H:%M UTC").to_string(),
             ),
     )
+    .into_response()
 }
 
-async fn note_new() -> impl IntoResponse {
-    Html(
-        NOTE_FORM_HTML
-            .replace("<!-- TITLE -->", "New Note")
-            .replace("<!-- ACTION -->", "/api/notes")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- NOTE_TITLE -->", "")
-            .replace("<!-- CONTENT -->", "")
-            .replace("<!-- CATEGORY -->", "")
-            .replace("<!-- TAGS -->", ""),
-    )
+async fn note_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    Html(note_form_html(
+        "New Note",
+        "/api/notes",
+        "",
+        &NoteForm::default(),
+        &flash,
+    ))
 }
 
 async fn note_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let note = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (note, cid) = match state
         .client
         .get_record::<Note>(NOTE_COLLECTION, &rkey)
         .await
     {
-        Ok(n) => n.value,
-        Err(_) => return Html(NOTE_NOT_FOUND_HTML.to_string()),
+        Ok(n) => (n.value, n.cid),
+        Err(e) => return record_error(&format!("note {}", rkey), NOTE_NOT_FOUND_HTML, e),
     };
 
-    Html(
-        NOTE_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Note")
-            .replace("<!-- ACTION -->", &format!("/api/notes/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- NOTE_TITLE -->", &html_escape(&note.title))
-            .replace("<!-- CONTENT -->", &html_escape(&note.content))
-            .replace(
-                "<!-- CATEGORY -->",
-                &html_escape(note.category.as_deref().unwrap_or("")),
-            )
-            .replace("<!-- TAGS -->", &note.tags.join(", ")),
-    )
+    let form = NoteForm {
+        title: note.title,
+        content: note.content,
+        category: note.category,
+        tags: Some(note.tags.join(", ")),
+        cid,
+    };
+    Html(note_form_html(
+        "Edit Note",
+        &format!("/api/notes/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
 }
 
-async fn create_note(
-    State(state): State<Arc<AppState>>,
-    Form(form): Form<NoteForm>,
-) -> impl IntoResponse {
+/// Render the note form filled in from `form`.
+fn note_form_html(title: &str, action: &str, rkey: &str, form: &NoteForm, flash: &Flash) -> String {
+    NOTE_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- NOTE_TITLE -->", &html_escape(&form.title))
+        .replace("<!-- CONTENT -->", &html_escape(&form.content))
+        .replace(
+            "<!-- CATEGORY -->",
+            &html_escape(form.category.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- TAGS -->",
+            &html_escape(form.tags.as_deref().unwrap_or("")),
+        )
+}
+
+async fn create_note(State(state): State<Arc<AppState>>, Form(form): Form<NoteForm>) -> Response {
+    if form.title.trim().is_empty() {
+        let e = WebError::Validation("title is required".to_string());
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            note_form_html("New Note", "/api/notes", "", &form, &flash),
+        );
+    }
+
     let now = Utc::now();
     let note = Note {
         title: form.title,
@@ -2128,18 +2964,15 @@ async fn create_note(
         tags: parse_comma_separated(&form.tags),
         created_at: now,
         last_updated: now,
+        deleted_at: None,
     };
 
     let rkey = Tid::now().to_string();
-    match state
-        .client
-        .create_record(NOTE_COLLECTION, Some(&rkey), &note)
-        .await
-    {
-        Ok(_) => Redirect::to(&format!("/notes/{}", rkey)),
+    match state.create_journaled(NOTE_COLLECTION, &rkey, &note).await {
+        Ok(_) => Redirect::to(&format!("/notes/{}", rkey)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create note");
-            Redirect::to("/notes")
+            redirect_with_error("/notes/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -2148,16 +2981,41 @@ async fn update_note(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<NoteForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<Note>(NOTE_COLLECTION, &rkey)
         .await
     {
-        Ok(n) => n.value,
-        Err(_) => return Redirect::to("/notes"),
+        Ok(n) => (n.value, n.cid),
+        Err(e) => return WebError::from_pds(&format!("note {}", rkey), e).into_response(),
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("note {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                note_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
+    let edit_path = format!("/notes/{}/edit", rkey);
+    if form.title.trim().is_empty() {
+        let e = WebError::Validation("title is required".to_string());
+        let action = format!("/api/notes/{}", rkey);
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            note_form_html("Edit Note", &action, &rkey, &form, &flash),
+        );
+    }
+
     let note = Note {
         title: form.title,
         content: form.content,
@@ -2166,22 +3024,32 @@ async fn update_note(
         tags: parse_comma_separated(&form.tags),
         created_at: existing.created_at,
         last_updated: Utc::now(),
+        deleted_at: existing.deleted_at,
     };
 
-    match state.client.put_record(NOTE_COLLECTION, &rkey, &note).await {
-        Ok(_) => Redirect::to(&format!("/notes/{}", rkey)),
+    match state
+        .put_journaled(NOTE_COLLECTION, &rkey, before, &note, swap.as_deref())
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/notes/{}", rkey)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("note {}", rkey));
+            conflict_response(
+                note_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update note");
-            Redirect::to(&format!("/notes/{}", rkey))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
 
-async fn delete_note(
-    State(state): State<Arc<AppState>>,
-    Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state.client.delete_record(NOTE_COLLECTION, &rkey).await;
+async fn delete_note(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Redirect {
+    if let Err(e) = state.tombstone_journaled(NOTE_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete note");
+        return redirect_with_error(&format!("/notes/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/notes")
 }
 
@@ -2251,7 +3119,8 @@ async fn wiki_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 async fn wiki_detail(
     State(state): State<Arc<AppState>>,
     Path(slug_or_rkey): Path<String>,
-) -> impl IntoResponse {
+    Query(flash): Query<Flash>,
+) -> Response {
     // Try to find by slug first, then by rkey
     let entries = match state
         .client
@@ -2259,7 +3128,7 @@ async fn wiki_detail(
         .await
     {
         Ok(n) => n,
-        Err(_) => return Html(WIKI_NOT_FOUND_HTML.to_string()),
+        Err(e) => return WebError::from(e).into_response(),
     };
 
     let found = entries.iter().find(|item| {
@@ -2270,14 +3139,14 @@ async fn wiki_detail(
 
     let item = match found {
         Some(item) => item,
-        None => return Html(WIKI_NOT_FOUND_HTML.to_string()),
+        None => return not_found_page(WIKI_NOT_FOUND_HTML),
     };
 
     let rkey = item.uri.split('/').next_back().unwrap_or("");
     let entry = &item.value;
 
-    // Render wiki links in content
-    let rendered_content = render_wiki_content(&html_escape(&entry.content), &entries);
+    // Render markdown, then wiki links in the rendered content
+    let rendered_content = render_wiki_content(&render_markdown(&entry.content), &entries);
 
     let tags_html = if entry.tags.is_empty() {
         String::new()
@@ -2330,6 +3199,7 @@ async fn wiki_detail(
 
     Html(
         WIKI_DETAIL_HTML
+            .replace("<!-- FLASH -->", &flash.render())
             .replace("<!-- RKEY -->", rkey)
             .replace("<!-- TITLE -->", &html_escape(&entry.title))
             .replace("<!-- SLUG -->", &html_escape(&entry.slug))
@@ -2350,64 +3220,132 @@ async fn wiki_detail(
                 &entry.last_updated.format("%Y-%m-%d %H:%M UTC").to_string(),
             ),
     )
+    .into_response()
 }
 
-async fn wiki_new() -> impl IntoResponse {
-    Html(
-        WIKI_FORM_HTML
-            .replace("<!-- TITLE -->", "New Wiki Entry")
-            .replace("<!-- ACTION -->", "/api/wiki")
-            .replace("<!-- RKEY -->", "")
-            .replace("<!-- ENTRY_TITLE -->", "")
-            .replace("<!-- SLUG -->", "")
-            .replace("<!-- CONTENT -->", "")
-            .replace("<!-- STATUS -->", "stable")
-            .replace("<!-- SUMMARY -->", "")
-            .replace("<!-- ALIASES -->", "")
-            .replace("<!-- TAGS -->", "")
-            .replace("<!-- SUPERSEDES -->", ""),
-    )
+async fn wiki_new(Query(flash): Query<Flash>) -> impl IntoResponse {
+    Html(wiki_form_html(
+        "New Wiki Entry",
+        "/api/wiki",
+        "",
+        &WikiEntryForm::default(),
+        &flash,
+    ))
 }
 
 async fn wiki_edit(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let entry = match state
+    Query(flash): Query<Flash>,
+) -> Response {
+    let (entry, cid) = match state
         .client
         .get_record::<WikiEntry>(WIKI_ENTRY_COLLECTION, &rkey)
         .await
     {
-        Ok(n) => n.value,
-        Err(_) => return Html(WIKI_NOT_FOUND_HTML.to_string()),
+        Ok(n) => (n.value, n.cid),
+        Err(e) => return record_error(&format!("wiki entry {}", rkey), WIKI_NOT_FOUND_HTML, e),
     };
 
-    Html(
-        WIKI_FORM_HTML
-            .replace("<!-- TITLE -->", "Edit Wiki Entry")
-            .replace("<!-- ACTION -->", &format!("/api/wiki/{}", rkey))
-            .replace("<!-- RKEY -->", &rkey)
-            .replace("<!-- ENTRY_TITLE -->", &html_escape(&entry.title))
-            .replace("<!-- SLUG -->", &html_escape(&entry.slug))
-            .replace("<!-- CONTENT -->", &html_escape(&entry.content))
-            .replace("<!-- STATUS -->", &html_escape(&entry.status))
-            .replace(
-                "<!-- SUMMARY -->",
-                &html_escape(entry.summary.as_deref().unwrap_or("")),
-            )
-            .replace("<!-- ALIASES -->", &entry.aliases.join(", "))
-            .replace("<!-- TAGS -->", &entry.tags.join(", "))
-            .replace(
-                "<!-- SUPERSEDES -->",
-                &html_escape(entry.supersedes.as_deref().unwrap_or("")),
-            ),
-    )
+    let form = WikiEntryForm {
+        title: entry.title,
+        slug: entry.slug,
+        content: entry.content,
+        status: Some(entry.status),
+        summary: entry.summary,
+        aliases: Some(entry.aliases.join(", ")),
+        tags: Some(entry.tags.join(", ")),
+        supersedes: entry.supersedes,
+        cid,
+    };
+    Html(wiki_form_html(
+        "Edit Wiki Entry",
+        &format!("/api/wiki/{}", rkey),
+        &rkey,
+        &form,
+        &flash,
+    ))
+    .into_response()
 }
 
-async fn create_wiki_entry_web(
-    State(state): State<Arc<AppState>>,
-    Form(form): Form<WikiEntryForm>,
-) -> impl IntoResponse {
+/// Render the wiki entry form filled in from `form`.
+fn wiki_form_html(
+    title: &str,
+    action: &str,
+    rkey: &str,
+    form: &WikiEntryForm,
+    flash: &Flash,
+) -> String {
+    WIKI_FORM_HTML
+        .replace("<!-- FLASH -->", &flash.render())
+        .replace(
+            "<!-- CID -->",
+            &html_escape(form.cid.as_deref().unwrap_or("")),
+        )
+        .replace("<!-- TITLE -->", title)
+        .replace("<!-- ACTION -->", action)
+        .replace("<!-- RKEY -->", rkey)
+        .replace("<!-- ENTRY_TITLE -->", &html_escape(&form.title))
+        .replace("<!-- SLUG -->", &html_escape(&form.slug))
+        .replace("<!-- CONTENT -->", &html_escape(&form.content))
+        .replace(
+            "<!-- STATUS -->",
+            &html_escape(form.status.as_deref().unwrap_or("stable")),
+        )
+        .replace(
+            "<!-- SUMMARY -->",
+            &html_escape(form.summary.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- ALIASES -->",
+            &html_escape(form.aliases.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- TAGS -->",
+            &html_escape(form.tags.as_deref().unwrap_or("")),
+        )
+        .replace(
+            "<!-- SUPERSEDES -->",
+            &html_escape(form.supersedes.as_deref().unwrap_or("")),
+        )
+}
+
+async fn create_wiki_entry_web(
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<WikiEntryForm>,
+) -> Response {
+    if form.title.trim().is_empty() || form.slug.trim().is_empty() {
+        let e = WebError::Validation("title and slug are required".to_string());
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            wiki_form_html("New Wiki Entry", "/api/wiki", "", &form, &flash),
+        );
+    }
+
+    let existing = match state
+        .client
+        .list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION)
+        .await
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(error = %e, "failed to load wiki entries");
+            return redirect_with_error("/wiki/new", &WebError::from(e)).into_response();
+        }
+    };
+    if let Some(other) = wiki::slug_owner(existing.iter().map(|r| &r.value), &form.slug) {
+        let e = WebError::Validation(format!(
+            "slug '{}' is already in use by entry '{}'",
+            form.slug, other.title
+        ));
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            wiki_form_html("New Wiki Entry", "/api/wiki", "", &form, &flash),
+        );
+    }
+
     let now = Utc::now();
     let entry = WikiEntry {
         title: form.title,
@@ -2424,14 +3362,13 @@ async fn create_wiki_entry_web(
 
     let rkey = Tid::now().to_string();
     match state
-        .client
-        .create_record(WIKI_ENTRY_COLLECTION, Some(&rkey), &entry)
+        .create_journaled(WIKI_ENTRY_COLLECTION, &rkey, &entry)
         .await
     {
-        Ok(_) => Redirect::to(&format!("/wiki/{}", entry.slug)),
+        Ok(_) => Redirect::to(&format!("/wiki/{}", entry.slug)).into_response(),
         Err(e) => {
             warn!(error = %e, "failed to create wiki entry");
-            Redirect::to("/wiki")
+            redirect_with_error("/wiki/new", &WebError::from(e)).into_response()
         }
     }
 }
@@ -2440,16 +3377,43 @@ async fn update_wiki_entry_web(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
     Form(form): Form<WikiEntryForm>,
-) -> impl IntoResponse {
-    let existing = match state
+) -> Response {
+    let (existing, current_cid) = match state
         .client
         .get_record::<WikiEntry>(WIKI_ENTRY_COLLECTION, &rkey)
         .await
     {
-        Ok(n) => n.value,
-        Err(_) => return Redirect::to("/wiki"),
+        Ok(n) => (n.value, n.cid),
+        Err(e) => {
+            return WebError::from_pds(&format!("wiki entry {}", rkey), e).into_response();
+        }
+    };
+    let before = snapshot(&existing);
+
+    let swap = match check_swap(
+        &format!("wiki entry {}", rkey),
+        form.cid.as_deref(),
+        current_cid.as_deref(),
+    ) {
+        Ok(swap) => swap,
+        Err(e) => {
+            return conflict_response(
+                wiki_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            );
+        }
     };
 
+    let edit_path = format!("/wiki/{}/edit", rkey);
+    if form.title.trim().is_empty() {
+        let e = WebError::Validation("title is required".to_string());
+        let action = format!("/api/wiki/{}", rkey);
+        let flash = Flash::from_error(&e);
+        return invalid_form(
+            &e,
+            wiki_form_html("Edit Wiki Entry", &action, &rkey, &form, &flash),
+        );
+    }
+
     let entry = WikiEntry {
         title: form.title,
         slug: existing.slug.clone(), // Slug is immutable
@@ -2464,14 +3428,25 @@ async fn update_wiki_entry_web(
     };
 
     match state
-        .client
-        .put_record(WIKI_ENTRY_COLLECTION, &rkey, &entry)
+        .put_journaled(
+            WIKI_ENTRY_COLLECTION,
+            &rkey,
+            before,
+            &entry,
+            swap.as_deref(),
+        )
         .await
     {
-        Ok(_) => Redirect::to(&format!("/wiki/{}", entry.slug)),
+        Ok(_) => Redirect::to(&format!("/wiki/{}", entry.slug)).into_response(),
+        Err(e) if e.is_swap_conflict() => {
+            let e = WebError::Conflict(format!("wiki entry {}", rkey));
+            conflict_response(
+                wiki_edit(State(state), Path(rkey), Query(Flash::from_error(&e))).await,
+            )
+        }
         Err(e) => {
             warn!(error = %e, "failed to update wiki entry");
-            Redirect::to(&format!("/wiki/{}", entry.slug))
+            redirect_with_error(&edit_path, &WebError::from(e)).into_response()
         }
     }
 }
@@ -2479,19 +3454,18 @@ async fn update_wiki_entry_web(
 async fn delete_wiki_entry_web(
     State(state): State<Arc<AppState>>,
     Path(rkey): Path<String>,
-) -> impl IntoResponse {
-    let _ = state
-        .client
-        .delete_record(WIKI_ENTRY_COLLECTION, &rkey)
-        .await;
+) -> Redirect {
+    if let Err(e) = state.delete_journaled(WIKI_ENTRY_COLLECTION, &rkey).await {
+        warn!(error = %e, "failed to delete wiki entry");
+        return redirect_with_error(&format!("/wiki/{}", rkey), &WebError::from(e));
+    }
     Redirect::to("/wiki")
 }
 
 /// Render wiki-link syntax in content, replacing [[slug]] with HTML links.
 fn render_wiki_content(escaped_content: &str, all_entries: &[winter_atproto::ListRecordItem<WikiEntry>]) -> String {
-    // Since content is already HTML-escaped, we need to look for escaped brackets
-    // [[...]] becomes &amp;#91;&amp;#91;...&amp;#93;&amp;#93; but actually html_escape doesn't
-    // touch brackets, so [[...]] remains as [[...]] in the escaped output.
+    // Content is already rendered to HTML. Neither the markdown renderer nor
+    // html_escape touch brackets, so [[...]] remains as [[...]] in the output.
     let re = regex::Regex::new(r"\[\[([^\]|]+?)(?:\|([^\]]+))?\]\]").unwrap();
 
     re.replace_all(escaped_content, |caps: &regex::Captures| {
@@ -2586,12 +3560,22 @@ async fn build_backlinks_html(state: &AppState, target_rkey: &str) -> String {
     }
 }
 
-async fn thoughts_sse(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let rx = state.thought_tx.subscribe();
+/// Live thought stream. Honors `Last-Event-ID` so a reconnecting browser
+/// gets the thoughts it missed.
+async fn thoughts_sse(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> impl IntoResponse {
+    let last_event_id = headers.get("last-event-id").and_then(|v| v.to_str().ok());
+    create_replay_sse_stream(&state.thoughts, last_event_id)
+}
+
+async fn jobs_sse(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let rx = state.job_tx.subscribe();
     create_sse_stream(rx)
 }
 
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -2601,7 +3585,7 @@ fn html_escape(s: &str) -> String {
 
 /// Truncate a string to a maximum number of characters (not bytes).
 /// Safe for UTF-8 strings with multi-byte characters.
-fn truncate_chars(s: &str, max_chars: usize) -> String {
+pub(crate) fn truncate_chars(s: &str, max_chars: usize) -> String {
     let char_count = s.chars().count();
     if char_count <= max_chars {
         s.to_string()
@@ -2610,6 +3594,60 @@ fn truncate_chars(s: &str, max_chars: usize) -> String {
     }
 }
 
+// =============================================================================
+// Undo/Redo
+// =============================================================================
+
+/// Reverse the most recent operator edit.
+async fn undo(State(state): State<Arc<AppState>>) -> Result<Redirect, WebError> {
+    match state.journal.undo(&state.client).await {
+        Ok(Some(entry)) => Ok(Redirect::to(&record_page(&entry, entry.before.is_some()))),
+        Ok(None) => Err(WebError::Validation("nothing to undo".to_string())),
+        Err(e) if e.is_swap_conflict() => Err(WebError::Conflict(
+            "the record has changed since this edit, so it can't be undone".to_string(),
+        )),
+        Err(e) => {
+            warn!(error = %e, "failed to undo edit");
+            Err(WebError::from(e))
+        }
+    }
+}
+
+/// Reapply the most recently undone edit.
+async fn redo(State(state): State<Arc<AppState>>) -> Result<Redirect, WebError> {
+    match state.journal.redo(&state.client).await {
+        Ok(Some(entry)) => Ok(Redirect::to(&record_page(&entry, entry.after.is_some()))),
+        Ok(None) => Err(WebError::Validation("nothing to redo".to_string())),
+        Err(e) if e.is_swap_conflict() => Err(WebError::Conflict(
+            "the record has changed since this edit was undone, so it can't be redone".to_string(),
+        )),
+        Err(e) => {
+            warn!(error = %e, "failed to redo edit");
+            Err(WebError::from(e))
+        }
+    }
+}
+
+/// The page showing a journaled record, or its list page if the record no
+/// longer `exists`.
+fn record_page(entry: &JournalEntry, exists: bool) -> String {
+    let list = match entry.collection.as_str() {
+        FACT_COLLECTION => "/facts",
+        JOB_COLLECTION => "/jobs",
+        RULE_COLLECTION => "/rules",
+        DIRECTIVE_COLLECTION => "/directives",
+        FACT_DECLARATION_COLLECTION => "/declarations",
+        NOTE_COLLECTION => "/notes",
+        WIKI_ENTRY_COLLECTION => "/wiki",
+        _ => return "/".to_string(),
+    };
+    if exists {
+        format!("{}/{}", list, entry.rkey)
+    } else {
+        list.to_string()
+    }
+}
+
 // =============================================================================
 // Form Structs and Helpers
 // =============================================================================
@@ -2634,15 +3672,17 @@ fn parse_newline_separated(s: &str) -> Vec<String> {
         .collect()
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct NoteForm {
     title: String,
     content: String,
     category: Option<String>,
     tags: Option<String>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct WikiEntryForm {
     title: String,
     slug: String,
@@ -2652,18 +3692,22 @@ struct WikiEntryForm {
     aliases: Option<String>,
     tags: Option<String>,
     supersedes: Option<String>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct FactForm {
     predicate: String,
     args: String,
     confidence: Option<f64>,
     source: Option<String>,
     tags: Option<String>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct RuleForm {
     name: String,
     description: Option<String>,
@@ -2672,18 +3716,22 @@ struct RuleForm {
     constraints: Option<String>,
     enabled: Option<String>,
     priority: Option<i32>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct JobForm {
     name: String,
     instructions: String,
     schedule_type: String,
     schedule_at: Option<String>,
     schedule_seconds: Option<u64>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct DirectiveForm {
     kind: String,
     content: String,
@@ -2693,16 +3741,150 @@ struct DirectiveForm {
     source: Option<String>,
     priority: Option<i32>,
     tags: Option<String>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 struct FactDeclarationForm {
     predicate: String,
     args_json: String,
     description: String,
     tags: Option<String>,
+    /// CID of the record when the edit form was loaded.
+    cid: Option<String>,
+}
+
+/// Compare the CID an edit form was loaded with against the stored record's.
+///
+/// Returns the CID to send as `swapRecord`, or `Conflict` if the record has
+/// changed since the form was rendered. Submissions without a CID (e.g. from
+/// scripts) skip the check.
+fn check_swap(
+    what: &str,
+    submitted: Option<&str>,
+    current: Option<&str>,
+) -> Result<Option<String>, WebError> {
+    let Some(submitted) = submitted.filter(|cid| !cid.is_empty()) else {
+        return Ok(None);
+    };
+    match current {
+        Some(current) if current != submitted => Err(WebError::Conflict(what.to_string())),
+        _ => Ok(Some(submitted.to_string())),
+    }
+}
+
+/// Re-render an edit page (showing the stored record) with a 409 status.
+fn conflict_response(page: impl IntoResponse) -> Response {
+    (StatusCode::CONFLICT, page).into_response()
+}
+
+/// Re-render a form with the input it was submitted with and the validation
+/// error that rejected it.
+fn invalid_form(error: &WebError, page: String) -> Response {
+    (error.status(), Html(page)).into_response()
+}
+
+/// A resource's own "not found" page, with a 404 status.
+fn not_found_page(page: &str) -> Response {
+    (StatusCode::NOT_FOUND, Html(page.to_string())).into_response()
+}
+
+/// Response for a record that couldn't be loaded: its "not found" page if
+/// the PDS says it doesn't exist, an error page for any other failure.
+fn record_error(what: &str, not_found: &str, error: AtprotoError) -> Response {
+    match WebError::from_pds(what, error) {
+        WebError::NotFound(_) => not_found_page(not_found),
+        other => {
+            warn!(error = %other, "failed to load {}", what);
+            other.into_response()
+        }
+    }
+}
+
+fn validate_fact(
+    predicate: &str,
+    args: &[String],
+    confidence: Option<f64>,
+) -> Result<(), WebError> {
+    if predicate.trim().is_empty() {
+        return Err(WebError::Validation("predicate is required".to_string()));
+    }
+    if args.is_empty() {
+        return Err(WebError::Validation(
+            "at least one argument is required".to_string(),
+        ));
+    }
+    if let Some(c) = confidence
+        && !(0.0..=1.0).contains(&c)
+    {
+        return Err(WebError::Validation(
+            "confidence must be between 0 and 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rule(form: &RuleForm) -> Result<(), WebError> {
+    if form.name.trim().is_empty() {
+        return Err(WebError::Validation("name is required".to_string()));
+    }
+    if form.head.trim().is_empty() || form.body.trim().is_empty() {
+        return Err(WebError::Validation(
+            "head and body are required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a job form and build its schedule. A blank run-at time defaults
+/// to an hour from now; one that doesn't parse is rejected.
+fn validate_job(form: &JobForm) -> Result<JobSchedule, WebError> {
+    if form.name.trim().is_empty() {
+        return Err(WebError::Validation("name is required".to_string()));
+    }
+    if form.schedule_type == "interval" {
+        return Ok(JobSchedule::Interval {
+            seconds: form.schedule_seconds.unwrap_or(3600),
+        });
+    }
+    let at = match form.schedule_at.as_deref().map(str::trim) {
+        None | Some("") => Utc::now() + chrono::Duration::hours(1),
+        Some(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .map_err(|_| {
+                WebError::Validation(format!("invalid run-at time {:?}, expected RFC 3339", s))
+            })?
+            .with_timezone(&Utc),
+    };
+    Ok(JobSchedule::Once { at })
+}
+
+fn validate_directive(form: &DirectiveForm) -> Result<(), WebError> {
+    if form.content.trim().is_empty() {
+        return Err(WebError::Validation("content is required".to_string()));
+    }
+    if let Some(c) = form.confidence
+        && !(0.0..=1.0).contains(&c)
+    {
+        return Err(WebError::Validation(
+            "confidence must be between 0 and 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a declaration form and parse its arguments.
+fn validate_declaration(form: &FactDeclarationForm) -> Result<Vec<FactDeclArg>, WebError> {
+    if form.predicate.trim().is_empty() {
+        return Err(WebError::Validation("predicate is required".to_string()));
+    }
+    serde_json::from_str(&form.args_json)
+        .map_err(|e| WebError::Validation(format!("invalid arguments JSON: {}", e)))
 }
 
+const STYLE_CSS: &str = r#".flash { background: #3b2a2e; border: 1px solid #bf616a; color: #e5b5ba; padding: 0.75rem 1rem; border-radius: 4px; margin: 1rem 0; }
+"#;
+
 const INDEX_HTML: &str = r#"<!DOCTYPE html>
 <html>
 <head>
@@ -2728,11 +3910,15 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
             text-decoration: none;
         }
         nav a:hover { background: #3b4252; }
+        .search input { width: 100%; padding: 0.5rem; background: #2e3440; border: 1px solid #3b4252; color: #e0e0e0; border-radius: 4px; box-sizing: border-box; }
     </style>
 </head>
 <body>
     <h1>Winter</h1>
     <p>Autonomous Bluesky Agent</p>
+    <form action="/search" method="get" class="search">
+        <input type="search" name="q" placeholder="Search facts, notes, directives, rules, wiki, thoughts">
+    </form>
     <nav>
         <a href="/stream">Thought Stream</a>
         <a href="/facts">Facts</a>
@@ -2749,6 +3935,53 @@ const INDEX_HTML: &str = r#"<!DOCTYPE html>
 </body>
 </html>"#;
 
+const SEARCH_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>Winter - Search</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+            max-width: 800px;
+            margin: 0 auto;
+            padding: 2rem;
+            background: #0a0a0a;
+            color: #e0e0e0;
+        }
+        h1 { color: #88c0d0; }
+        h1 a { color: #88c0d0; text-decoration: none; }
+        h2 { color: #81a1c1; font-size: 1.1rem; margin-top: 2rem; }
+        a { color: #81a1c1; }
+        .search { display: flex; gap: 0.5rem; }
+        .search input { flex: 1; padding: 0.5rem; background: #2e3440; border: 1px solid #3b4252; color: #e0e0e0; border-radius: 4px; }
+        .btn { padding: 0.5rem 1rem; background: #5e81ac; color: #fff; border: none; border-radius: 4px; cursor: pointer; }
+        .btn:hover { background: #81a1c1; }
+        .count { color: #888; font-weight: normal; }
+        .hit {
+            padding: 0.75rem 1rem;
+            margin: 0.5rem 0;
+            background: #2e3440;
+            border-radius: 4px;
+        }
+        .type { font-size: 0.75rem; text-transform: uppercase; color: #888; margin-right: 0.5rem; }
+        .title { font-weight: bold; color: #88c0d0; text-decoration: none; }
+        .title:hover { text-decoration: underline; }
+        .snippet { color: #aaa; font-size: 0.9rem; margin-top: 0.25rem; line-height: 1.4; }
+        .empty { color: #888; }
+    </style>
+</head>
+<body>
+    <h1><a href="/">Winter</a> / Search</h1>
+    <form action="/search" method="get" class="search">
+        <input type="search" name="q" value="<!-- QUERY -->" placeholder="Search facts, notes, directives, rules, wiki, thoughts" autofocus>
+        <button type="submit" class="btn">Search</button>
+    </form>
+    <!-- RESULTS -->
+</body>
+</html>"#;
+
 const STREAM_HTML: &str = r#"<!DOCTYPE html>
 <html>
 <head>
@@ -3389,9 +4622,16 @@ const STREAM_HTML: &str = r#"<!DOCTYPE html>
             return div.innerHTML;
         }
 
-        // Format thought content based on kind
-        function formatContent(kind, content) {
-            if (kind === 'tool_call') {
+        // Format thought content based on its content type
+        function formatContent(contentType, content) {
+            if (contentType === 'tool_call') {
+                try {
+                    return formatToolCallJson(JSON.parse(content));
+                } catch (e) {
+                    return escapeHtml(content);
+                }
+            }
+            if (contentType === 'legacy_tool_call') {
                 return formatToolCallContent(content);
             }
             return escapeHtml(content);
@@ -3402,7 +4642,7 @@ const STREAM_HTML: &str = r#"<!DOCTYPE html>
             const date = new Date(thought.created_at);
             const relTime = formatRelativeTime(date);
             const absTime = date.toLocaleString();
-            const content = formatContent(thought.kind, thought.content);
+            const content = formatContent(thought.content_type, thought.content);
 
             let html = '<div class="thought-header">';
             html += '<span class="kind">' + thought.kind.replace('_', ' ') + '</span>';
@@ -3727,6 +4967,8 @@ const JOBS_HTML: &str = r#"<!DOCTYPE html>
         .status.running { background: #81a1c1; color: #000; }
         .status.completed { background: #a3be8c; color: #000; }
         .status.failed { background: #bf616a; color: #fff; }
+        .status.deleted { background: #4c566a; color: #fff; }
+        tr.deleted { opacity: 0.5; }
     </style>
 </head>
 <body>
@@ -3749,6 +4991,47 @@ const JOBS_HTML: &str = r#"<!DOCTYPE html>
             <!-- JOBS -->
         </tbody>
     </table>
+    <script>
+        function formatNextRun(value) {
+            if (!value) return '-';
+            const seconds = Math.floor((new Date(value) - Date.now()) / 1000);
+            if (seconds < 0) return 'now';
+            if (seconds < 60) return 'in a few seconds';
+            const minutes = Math.floor(seconds / 60);
+            if (minutes < 60) return minutes === 1 ? 'in 1 minute' : 'in ' + minutes + ' minutes';
+            const hours = Math.floor(minutes / 60);
+            if (hours < 24) return hours === 1 ? 'in 1 hour' : 'in ' + hours + ' hours';
+            const days = Math.floor(hours / 24);
+            if (days === 1) return 'tomorrow';
+            if (days < 7) return 'in ' + days + ' days';
+            return new Date(value).toLocaleDateString(undefined, { month: 'short', day: '2-digit' });
+        }
+
+        const eventSource = new EventSource('/api/jobs/sse');
+
+        eventSource.onmessage = function(event) {
+            try {
+                const job = JSON.parse(event.data);
+                const row = document.querySelector('tr[data-rkey="' + CSS.escape(job.rkey) + '"]');
+                if (!row) {
+                    // A job created since the page loaded
+                    if (job.status !== 'deleted') window.location.reload();
+                    return;
+                }
+
+                const badge = row.querySelector('.status');
+                badge.className = 'status ' + job.status;
+                badge.textContent = job.status;
+                badge.title = job.error || '';
+                row.classList.toggle('deleted', job.status === 'deleted');
+                if (job.status !== 'deleted') {
+                    row.querySelector('.next-run').textContent = formatNextRun(job.next_run);
+                }
+            } catch (e) {
+                console.error('Failed to parse job status:', e);
+            }
+        };
+    </script>
 </body>
 </html>"#;
 
@@ -3810,6 +5093,7 @@ const NOTE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Note: <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -3839,10 +5123,25 @@ const NOTE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
             background: #2e3440;
             padding: 1.5rem;
             border-radius: 4px;
-            white-space: pre-wrap;
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
             line-height: 1.6;
         }
+        .content pre {
+            background: #1a1a1a;
+            padding: 0.75rem;
+            border-radius: 4px;
+            overflow-x: auto;
+        }
+        .content code {
+            font-family: "SF Mono", "Menlo", monospace;
+            font-size: 0.9rem;
+        }
+        .content blockquote {
+            border-left: 3px solid #4c566a;
+            margin-left: 0;
+            padding-left: 1rem;
+            color: #aaa;
+        }
         .tags {
             color: #81a1c1;
             font-size: 0.9rem;
@@ -3862,6 +5161,10 @@ const NOTE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
             font-family: "SF Mono", "Menlo", monospace;
             font-size: 0.9rem;
         }
+        .related-facts .missing {
+            color: #888;
+            font-style: italic;
+        }
         .actions { margin-top: 2rem; }
         .btn { padding: 0.5rem 1rem; border: none; border-radius: 4px; cursor: pointer; text-decoration: none; margin-right: 0.5rem; }
         .btn-edit { background: #5e81ac; color: #fff; }
@@ -3872,6 +5175,7 @@ const NOTE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/notes">Notes</a> / <!-- TITLE --><!-- CATEGORY --></h1>
+    <!-- FLASH -->
     <p class="meta">Created: <!-- CREATED_AT --> · Updated: <!-- UPDATED_AT --></p>
     <div class="content"><!-- CONTENT --></div>
     <!-- TAGS -->
@@ -3983,17 +5287,14 @@ async fn tools_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     )
 }
 
-async fn tool_detail(
-    State(state): State<Arc<AppState>>,
-    Path(rkey): Path<String>,
-) -> impl IntoResponse {
+async fn tool_detail(State(state): State<Arc<AppState>>, Path(rkey): Path<String>) -> Response {
     let tool = match state
         .client
         .get_record::<CustomTool>(TOOL_COLLECTION, &rkey)
         .await
     {
         Ok(t) => t.value,
-        Err(_) => return Html(TOOL_NOT_FOUND_HTML.to_string()),
+        Err(e) => return record_error(&format!("tool {}", rkey), TOOL_NOT_FOUND_HTML, e),
     };
 
     let approval = state
@@ -4027,6 +5328,7 @@ async fn tool_detail(
                 &html_escape(&serde_json::to_string_pretty(&tool.input_schema).unwrap_or_default()),
             ),
     )
+    .into_response()
 }
 
 
@@ -4467,6 +5769,7 @@ const NOTE_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4502,7 +5805,9 @@ const NOTE_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/notes">Notes</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="title">Title</label>
             <input type="text" id="title" name="title" required value="<!-- NOTE_TITLE -->">
@@ -4536,6 +5841,7 @@ const FACT_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Fact: <!-- PREDICATE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4559,10 +5865,14 @@ const FACT_DETAIL_HTML: &str = r#"<!DOCTYPE html>
         .btn-edit:hover { background: #81a1c1; }
         .btn-delete { background: #bf616a; color: #fff; }
         .btn-delete:hover { background: #d08770; }
+        h2 { color: #88c0d0; font-size: 1.1rem; margin-top: 1.5rem; }
+        .backlinks { list-style: none; padding: 0; }
+        .backlinks li { background: #2e3440; padding: 0.5rem 1rem; margin: 0.5rem 0; border-radius: 4px; }
     </style>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/facts">Facts</a> / <!-- PREDICATE --></h1>
+    <!-- FLASH -->
     <div class="detail">
         <p><strong>Predicate:</strong> <code><!-- PREDICATE --></code></p>
         <p><strong>Arguments:</strong> <!-- ARGS --></p>
@@ -4570,6 +5880,7 @@ const FACT_DETAIL_HTML: &str = r#"<!DOCTYPE html>
         <!-- SOURCE -->
         <!-- TAGS -->
     </div>
+    <!-- BACKLINKS -->
     <p class="meta">Created: <!-- CREATED_AT --></p>
     <div class="actions">
         <a href="/facts/<!-- RKEY -->/edit" class="btn btn-edit">Edit</a>
@@ -4611,6 +5922,7 @@ const FACT_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4645,7 +5957,9 @@ const FACT_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/facts">Facts</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="predicate">Predicate</label>
             <input type="text" id="predicate" name="predicate" required value="<!-- PREDICATE -->" placeholder="e.g., follows, interested_in">
@@ -4738,6 +6052,7 @@ const RULE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Rule: <!-- NAME --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4770,6 +6085,7 @@ const RULE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/rules">Rules</a> / <!-- NAME --></h1>
+    <!-- FLASH -->
     <p><span class="status <!-- ENABLED -->"><!-- ENABLED --></span> Priority: <!-- PRIORITY --></p>
     <div class="description"><!-- DESCRIPTION --></div>
     <h2>Head</h2>
@@ -4818,6 +6134,7 @@ const RULE_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4856,7 +6173,9 @@ const RULE_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/rules">Rules</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="name">Name</label>
             <input type="text" id="name" name="name" required value="<!-- NAME -->" placeholder="e.g., mutual_follow">
@@ -4902,6 +6221,7 @@ const JOB_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Job: <!-- NAME --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -4932,10 +6252,13 @@ const JOB_DETAIL_HTML: &str = r#"<!DOCTYPE html>
         .btn-edit:hover { background: #81a1c1; }
         .btn-delete { background: #bf616a; color: #fff; }
         .btn-delete:hover { background: #d08770; }
+        .btn-run { background: #a3be8c; color: #000; }
+        .btn-run:hover { background: #b8d4a0; }
     </style>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/jobs">Jobs</a> / <!-- NAME --></h1>
+    <!-- FLASH -->
     <div class="detail">
         <p><strong>Schedule:</strong> <!-- SCHEDULE --></p>
         <p><strong>Status:</strong> <span class="status <!-- STATUS -->"><!-- STATUS --></span></p>
@@ -4949,6 +6272,9 @@ const JOB_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <p class="meta">Created: <!-- CREATED_AT --></p>
     <div class="actions">
         <a href="/jobs/<!-- RKEY -->/edit" class="btn btn-edit">Edit</a>
+        <form action="/api/jobs/<!-- RKEY -->/run" method="post" style="display:inline">
+            <button type="submit" class="btn btn-run">Run Now</button>
+        </form>
         <form action="/api/jobs/<!-- RKEY -->/delete" method="post" style="display:inline">
             <button type="submit" class="btn btn-delete" onclick="return confirm('Delete this job?')">Delete</button>
         </form>
@@ -4987,6 +6313,7 @@ const JOB_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5026,7 +6353,9 @@ const JOB_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/jobs">Jobs</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="name">Name</label>
             <input type="text" id="name" name="name" required value="<!-- NAME -->" placeholder="e.g., daily_reflection">
@@ -5136,6 +6465,7 @@ const DIRECTIVE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Directive: <!-- KIND --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5166,6 +6496,7 @@ const DIRECTIVE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/directives">Directives</a> / <!-- KIND --></h1>
+    <!-- FLASH -->
     <div class="detail">
         <p><strong>Kind:</strong> <!-- KIND --></p>
         <p><strong>Status:</strong> <span class="status <!-- ACTIVE -->"><!-- ACTIVE --></span></p>
@@ -5180,6 +6511,7 @@ const DIRECTIVE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <p class="meta">Created: <!-- CREATED_AT --> · Updated: <!-- UPDATED_AT --></p>
     <div class="actions">
         <a href="/directives/<!-- RKEY -->/edit" class="btn btn-edit">Edit</a>
+        <a href="/directives/<!-- RKEY -->/history" class="btn btn-edit">History</a>
         <form action="/api/directives/<!-- RKEY -->/delete" method="post" style="display:inline">
             <button type="submit" class="btn btn-delete" onclick="return confirm('Delete this directive?')">Delete</button>
         </form>
@@ -5187,6 +6519,44 @@ const DIRECTIVE_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 </body>
 </html>"#;
 
+const DIRECTIVE_HISTORY_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>Winter - Directive History: <!-- KIND --></title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+            max-width: 800px;
+            margin: 0 auto;
+            padding: 2rem;
+            background: #0a0a0a;
+            color: #e0e0e0;
+        }
+        h1 { color: #88c0d0; }
+        h1 a { color: #88c0d0; text-decoration: none; }
+        a { color: #81a1c1; }
+        code { background: #3b4252; padding: 0.2rem 0.4rem; border-radius: 3px; font-size: 0.9rem; }
+        .count { color: #888; }
+        .version { border-left: 3px solid #3b4252; padding: 0.5rem 1rem; margin: 1rem 0; }
+        .version.current { border-left-color: #88c0d0; }
+        .version-header { display: flex; justify-content: space-between; align-items: center; color: #88c0d0; }
+        .summary { font-style: italic; color: #d8dee9; }
+        .content { background: #2e3440; padding: 1rem; border-radius: 4px; white-space: pre-wrap; line-height: 1.6; margin-top: 0.5rem; }
+        .status { padding: 0.2rem 0.5rem; border-radius: 3px; font-size: 0.85rem; }
+        .status.active { background: #a3be8c; color: #000; }
+        .status.inactive { background: #4c566a; color: #fff; }
+        .meta { color: #888; font-size: 0.9rem; }
+    </style>
+</head>
+<body>
+    <h1><a href="/">Winter</a> / <a href="/directives">Directives</a> / <a href="/directives/<!-- RKEY -->"><!-- KIND --></a> / History</h1>
+    <p class="count"><!-- COUNT --> versions, newest first</p>
+    <!-- HISTORY -->
+</body>
+</html>"#;
+
 const DIRECTIVE_NOT_FOUND_HTML: &str = r#"<!DOCTYPE html>
 <html>
 <head>
@@ -5218,6 +6588,7 @@ const DIRECTIVE_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5255,7 +6626,9 @@ const DIRECTIVE_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/directives">Directives</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="kind">Kind</label>
             <select id="kind" name="kind" required>
@@ -5266,6 +6639,7 @@ const DIRECTIVE_FORM_HTML: &str = r#"<!DOCTYPE html>
                 <option value="self_concept" <!-- KIND_SELF_CONCEPT_SELECTED -->>Self Concept</option>
                 <option value="boundary" <!-- KIND_BOUNDARY_SELECTED -->>Boundary</option>
                 <option value="aspiration" <!-- KIND_ASPIRATION_SELECTED -->>Aspiration</option>
+                <option value="goal" <!-- KIND_GOAL_SELECTED -->>Goal</option>
             </select>
         </div>
         <div class="form-group">
@@ -5364,6 +6738,7 @@ const DECLARATION_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - Declaration: <!-- PREDICATE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5393,6 +6768,7 @@ const DECLARATION_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/declarations">Declarations</a> / <!-- PREDICATE --></h1>
+    <!-- FLASH -->
     <div class="detail">
         <p><strong>Predicate:</strong> <code><!-- PREDICATE --></code></p>
         <p><strong>Arguments:</strong> <!-- ARG_COUNT --></p>
@@ -5456,6 +6832,7 @@ const DECLARATION_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5493,7 +6870,9 @@ const DECLARATION_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/">Winter</a> / <a href="/declarations">Declarations</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form action="<!-- ACTION -->" method="post">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <div class="form-group">
             <label for="predicate">Predicate</label>
             <input type="text" id="predicate" name="predicate" required value="<!-- PREDICATE -->" placeholder="e.g., thread_completed, user_preference">
@@ -5580,6 +6959,7 @@ const WIKI_DETAIL_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5604,7 +6984,10 @@ const WIKI_DETAIL_HTML: &str = r#"<!DOCTYPE html>
         .status-deprecated { color: #bf616a; background: rgba(191, 97, 106, 0.15); }
         .slug { color: #888; font-family: monospace; margin-bottom: 1rem; }
         .summary { color: #aaa; font-style: italic; margin-bottom: 1rem; }
-        .content { white-space: pre-wrap; line-height: 1.6; background: #2e3440; padding: 1rem; border-radius: 4px; }
+        .content { line-height: 1.6; background: #2e3440; padding: 1rem; border-radius: 4px; }
+        .content pre { background: #1a1a1a; padding: 0.75rem; border-radius: 4px; overflow-x: auto; }
+        .content code { font-family: "SF Mono", "Menlo", monospace; font-size: 0.9rem; }
+        .content blockquote { border-left: 3px solid #4c566a; margin-left: 0; padding-left: 1rem; color: #aaa; }
         .tags, .aliases { color: #81a1c1; margin-top: 1rem; }
         .supersedes { color: #888; margin-top: 0.5rem; }
         .timestamps { color: #888; font-size: 0.85rem; margin-top: 1rem; }
@@ -5619,6 +7002,7 @@ const WIKI_DETAIL_HTML: &str = r#"<!DOCTYPE html>
 <body>
     <div class="header">
         <h1><a href="/wiki">Wiki</a> / <!-- TITLE --></h1>
+        <!-- FLASH -->
         <div class="actions">
             <a href="/wiki/<!-- RKEY -->/edit" class="btn">Edit</a>
             <form method="POST" action="/api/wiki/<!-- RKEY -->/delete" style="display:inline">
@@ -5672,6 +7056,7 @@ const WIKI_FORM_HTML: &str = r#"<!DOCTYPE html>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>Winter - <!-- TITLE --></title>
+    <link rel="stylesheet" href="/style.css">
     <style>
         body {
             font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
@@ -5705,7 +7090,9 @@ const WIKI_FORM_HTML: &str = r#"<!DOCTYPE html>
 </head>
 <body>
     <h1><a href="/wiki">Wiki</a> / <!-- TITLE --></h1>
+    <!-- FLASH -->
     <form method="POST" action="<!-- ACTION -->">
+        <input type="hidden" name="cid" value="<!-- CID -->">
         <label for="title">Title</label>
         <input type="text" id="title" name="title" value="<!-- ENTRY_TITLE -->" required>
 
@@ -5762,6 +7149,29 @@ const WIKI_FORM_HTML: &str = r#"<!DOCTYPE html>
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_readiness_flips_when_thought_stream_connects() {
+        let (status, body) = readiness_report(true, Some(false));
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["subsystems"]["thought_stream"]["connected"], false);
+
+        let (status, body) = readiness_report(true, Some(true));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ready"], true);
+    }
+
+    #[test]
+    fn test_readiness_requires_pds() {
+        let (status, body) = readiness_report(false, None);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["subsystems"]["pds"]["ready"], false);
+        assert_eq!(body["subsystems"]["thought_stream"]["state"], "not_configured");
+
+        let (status, _) = readiness_report(true, None);
+        assert_eq!(status, StatusCode::OK);
+    }
+
     #[test]
     fn test_format_tool_call_json_parses() {
         let json_str = r#"{"tool":"create_fact","args":{"args":["self","test"],"predicate":"capability"},"result":{"rkey":"abc123","predicate":"capability"},"summary":"rkey=abc123, predicate=capability\nView: http://localhost:8080/facts/abc123","link":"http://localhost:8080/facts/abc123"}"#;
@@ -5797,7 +7207,7 @@ mod tests {
     fn test_format_tool_call_text_format() {
         let content = "Called create_fact\nArgs:\n{\"predicate\": \"test\"}\nResult: rkey=abc";
 
-        let result = format_tool_call_content(content);
+        let result = format_legacy_tool_call_content(content);
 
         assert!(result.contains("create_fact"), "Should contain tool name");
         assert!(result.contains("tool-name"), "Should have tool-name class");
@@ -5818,11 +7228,7 @@ mod tests {
     fn test_format_thought_content_routes_tool_call() {
         let json_str = r#"{"tool":"test_tool","args":{}}"#;
 
-        // Simulate the flow: kind -> format_thought_content
-        let kind = thought_kind_to_string(&winter_atproto::ThoughtKind::ToolCall);
-        assert_eq!(kind, "tool_call");
-
-        let result = format_thought_content(&kind, json_str);
+        let result = format_thought_content(&ThoughtContentType::ToolCall, json_str);
         assert!(
             result.contains("test_tool"),
             "Should contain tool name: {}",
@@ -5835,6 +7241,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_thought_content_routes_on_content_type_not_content() {
+        // Text that looks like a legacy tool call stays plain text.
+        let result = format_thought_content(&ThoughtContentType::Text, "Called mom <3");
+        assert_eq!(result, "Called mom &lt;3");
+
+        // JSON-looking text is not rendered as a tool call either.
+        let json_str = r#"{"tool":"test_tool"}"#;
+        let result = format_thought_content(&ThoughtContentType::Text, json_str);
+        assert!(
+            !result.contains("tool-name"),
+            "Should stay text: {}",
+            result
+        );
+
+        // A tool call with surrounding whitespace is still structured.
+        let result = format_thought_content(&ThoughtContentType::ToolCall, "\n{\"tool\":\"x\"}\n");
+        assert!(
+            result.contains("tool-name"),
+            "Should render tool: {}",
+            result
+        );
+
+        let result = format_thought_content(
+            &ThoughtContentType::LegacyToolCall,
+            "Called create_fact - FAILED",
+        );
+        assert!(
+            result.contains("tool-failed"),
+            "Should mark failure: {}",
+            result
+        );
+    }
+
     #[test]
     fn test_format_tool_call_user_example() {
         // The exact JSON from the user's bug report
@@ -5852,4 +7292,491 @@ mod tests {
         assert!(result.contains("tool-name"), "Should have tool-name class");
         assert!(result.contains("tool-link-btn"), "Should have link button");
     }
+
+    #[test]
+    fn test_check_swap() {
+        assert_eq!(check_swap("note a", None, Some("bafy1")).unwrap(), None);
+        assert_eq!(check_swap("note a", Some(""), Some("bafy1")).unwrap(), None);
+        assert_eq!(
+            check_swap("note a", Some("bafy1"), Some("bafy1")).unwrap(),
+            Some("bafy1".to_string())
+        );
+        let err = check_swap("note a", Some("bafy0"), Some("bafy1")).unwrap_err();
+        assert!(matches!(err, WebError::Conflict(_)));
+        assert_eq!(err.status(), StatusCode::CONFLICT);
+    }
+
+    mod cas {
+        use super::*;
+        use crate::test_support::{self, logged_in_router};
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        async fn mock_pds(stored_cid: &str) -> MockServer {
+            let server = test_support::mock_pds().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": stored_cid,
+                    "value": { "title": "Stored", "content": "stored content" }
+                })))
+                .mount(&server)
+                .await;
+            server
+        }
+
+        fn update_note(cid: &str) -> Request<Body> {
+            Request::builder()
+                .method("POST")
+                .uri("/api/notes/abc")
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, "winter_csrf=tok")
+                .body(Body::from(format!(
+                    "title=Edited&content=edited&cid={}&csrf_token=tok",
+                    cid
+                )))
+                .unwrap()
+        }
+
+        async fn body_string(response: Response) -> String {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_clean_update_swaps_on_loaded_cid() {
+            let server = mock_pds("bafycurrent").await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.putRecord"))
+                .and(body_partial_json(json!({ "swapRecord": "bafycurrent" })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": "bafynext"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let response = logged_in_router(&server)
+                .await
+                .oneshot(update_note("bafycurrent"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::SEE_OTHER);
+            assert_eq!(response.headers()[header::LOCATION], "/notes/abc");
+        }
+
+        #[tokio::test]
+        async fn test_stale_cid_rerenders_form_with_conflict() {
+            let server = mock_pds("bafycurrent").await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.putRecord"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            let response = logged_in_router(&server)
+                .await
+                .oneshot(update_note("bafystale"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+            let html = body_string(response).await;
+            assert!(html.contains("was changed since this form was loaded"));
+            // The form is re-rendered from the stored record, with its new cid.
+            assert!(html.contains(r#"name="cid" value="bafycurrent""#));
+            assert!(html.contains(r#"name="csrf_token" value="tok""#));
+            assert!(html.contains("Stored"));
+        }
+
+        #[tokio::test]
+        async fn test_delete_note_writes_tombstone() {
+            let server = mock_pds("bafycurrent").await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.putRecord"))
+                .and(body_partial_json(json!({
+                    "swapRecord": "bafycurrent",
+                    "record": { "title": "Stored" }
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": "bafynext"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.deleteRecord"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/notes/abc/delete")
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, "winter_csrf=tok")
+                .body(Body::from("csrf_token=tok"))
+                .unwrap();
+            let response = logged_in_router(&server)
+                .await
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::SEE_OTHER);
+            assert_eq!(response.headers()[header::LOCATION], "/notes");
+
+            let requests = server.received_requests().await.unwrap();
+            let put = requests
+                .iter()
+                .find(|r| r.url.path() == "/xrpc/com.atproto.repo.putRecord")
+                .unwrap();
+            let body: Value = serde_json::from_slice(&put.body).unwrap();
+            assert!(body["record"]["deletedAt"].is_string());
+        }
+
+        #[tokio::test]
+        async fn test_pds_swap_rejection_is_a_conflict() {
+            let server = mock_pds("bafycurrent").await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.putRecord"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "error": "InvalidSwap",
+                    "message": "Record was at bafyraced"
+                })))
+                .mount(&server)
+                .await;
+
+            let response = logged_in_router(&server)
+                .await
+                .oneshot(update_note("bafycurrent"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+            assert!(
+                body_string(response)
+                    .await
+                    .contains("was changed since this form was loaded")
+            );
+        }
+    }
+
+    mod forms {
+        use super::*;
+        use crate::test_support::{self, logged_in_router};
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        /// A mock PDS that accepts logins and answers reads of note `abc`
+        /// with `status`.
+        async fn mock_pds(status: u16) -> MockServer {
+            let server = test_support::mock_pds().await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(status))
+                .mount(&server)
+                .await;
+            server
+        }
+
+        async fn send(server: &MockServer, request: Request<Body>) -> (StatusCode, String) {
+            let response = logged_in_router(server)
+                .await
+                .oneshot(request)
+                .await
+                .unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, String::from_utf8(bytes.to_vec()).unwrap())
+        }
+
+        fn get(uri: &str) -> Request<Body> {
+            Request::builder().uri(uri).body(Body::empty()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_invalid_form_rerenders_with_input() {
+            let server = mock_pds(404).await;
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/notes")
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, "winter_csrf=tok")
+                .body(Body::from(
+                    "title=&content=half+a+thought+%3Cb%3E&tags=draft&csrf_token=tok",
+                ))
+                .unwrap();
+
+            let (status, html) = send(&server, request).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(html.contains("title is required"));
+            assert!(html.contains("half a thought &lt;b&gt;"));
+            assert!(html.contains(r#"value="draft""#));
+            assert!(html.contains(r#"name="csrf_token" value="tok""#));
+        }
+
+        #[tokio::test]
+        async fn test_duplicate_wiki_slug_is_rejected() {
+            let server = mock_pds(404).await;
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.listRecords"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "records": [{
+                        "uri": "at://did:plc:testuser123/diy.razorgirl.winter.wikiEntry/abc",
+                        "cid": "bafyentry",
+                        "value": {
+                            "title": "Existing Page",
+                            "slug": "taken",
+                            "content": "already here",
+                            "status": "stable",
+                            "createdAt": "2026-01-01T00:00:00Z",
+                            "lastUpdated": "2026-01-01T00:00:00Z"
+                        }
+                    }]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/wiki")
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, "winter_csrf=tok")
+                .body(Body::from(
+                    "title=Second+Page&slug=taken&content=new&csrf_token=tok",
+                ))
+                .unwrap();
+
+            let (status, html) = send(&server, request).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(html.contains("is already in use by entry"), "{html}");
+            assert!(html.contains("Second Page"));
+        }
+
+        #[tokio::test]
+        async fn test_missing_record_is_not_found() {
+            let server = mock_pds(404).await;
+            for uri in ["/notes/abc", "/notes/abc/edit"] {
+                let (status, html) = send(&server, get(uri)).await;
+                assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+                assert!(html.contains("Note Not Found"), "{uri}");
+            }
+        }
+
+        #[tokio::test]
+        async fn test_pds_failure_is_not_reported_as_not_found() {
+            let server = mock_pds(500).await;
+            for uri in ["/notes/abc", "/notes/abc/edit"] {
+                let (status, html) = send(&server, get(uri)).await;
+                assert_eq!(status, StatusCode::BAD_GATEWAY, "{uri}");
+                assert!(!html.contains("Note Not Found"), "{uri}");
+            }
+        }
+
+        #[tokio::test]
+        async fn test_flash_styles_come_from_the_shared_stylesheet() {
+            let server = mock_pds(404).await;
+            let (status, css) = send(&server, get("/style.css")).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(css.contains(".flash"));
+
+            let (_, html) = send(&server, get("/notes/new")).await;
+            assert!(html.contains(r#"<link rel="stylesheet" href="/style.css">"#));
+            assert!(!html.contains(".flash {"));
+        }
+    }
+
+    mod predicate_graph {
+        use super::*;
+        use crate::test_support::{logged_in_router, mock_pds};
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        #[tokio::test]
+        async fn test_unknown_format_is_rejected() {
+            let server = mock_pds().await;
+            let request = Request::builder()
+                .uri("/predicates/graph?format=svg")
+                .body(Body::empty())
+                .unwrap();
+
+            let response = logged_in_router(&server)
+                .await
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(
+                body["error"],
+                "invalid format 'svg': expected 'json' or 'dot'"
+            );
+        }
+    }
+
+    mod undo_redo {
+        use super::*;
+        use crate::test_support::{logged_in_router, mock_pds};
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        /// Serve note `abc` for the read before it is deleted.
+        async fn holds_note(server: &MockServer) {
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": "bafystored",
+                    "value": { "title": "Stored", "content": "stored content" }
+                })))
+                .up_to_n_times(1)
+                .mount(server)
+                .await;
+        }
+
+        /// Expect a delete of `abc` swapped against `cid`.
+        async fn expect_delete(server: &MockServer, cid: &str) {
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.deleteRecord"))
+                .and(body_partial_json(
+                    json!({ "rkey": "abc", "swapRecord": cid }),
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+
+        /// Answer reads of `abc` after the delete with `status`.
+        async fn after_delete(server: &MockServer, status: u16) {
+            Mock::given(method("GET"))
+                .and(path("/xrpc/com.atproto.repo.getRecord"))
+                .respond_with(ResponseTemplate::new(status).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": "bafyrecreated",
+                    "value": { "title": "Recreated", "content": "someone else" }
+                })))
+                .mount(server)
+                .await;
+        }
+
+        fn post(uri: &str) -> Request<Body> {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, "winter_csrf=tok")
+                .body(Body::from("csrf_token=tok"))
+                .unwrap()
+        }
+
+        async fn location(router: &Router, uri: &str) -> (StatusCode, String) {
+            let response = router.clone().oneshot(post(uri)).await.unwrap();
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .map(|l| l.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            (response.status(), location)
+        }
+
+        #[tokio::test]
+        async fn test_undo_restores_deleted_note_and_redo_deletes_it_again() {
+            let server = mock_pds().await;
+            holds_note(&server).await;
+            expect_delete(&server, "bafystored").await;
+            after_delete(&server, 404).await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .and(body_partial_json(json!({
+                    "rkey": "abc",
+                    "record": { "title": "Stored", "content": "stored content" }
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "uri": "at://did:plc:testuser123/diy.razorgirl.winter.note/abc",
+                    "cid": "bafyrestored"
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            expect_delete(&server, "bafyrestored").await;
+            let router = logged_in_router(&server).await;
+
+            assert_eq!(
+                location(&router, "/api/notes/abc/delete").await,
+                (StatusCode::SEE_OTHER, "/notes".to_string())
+            );
+            assert_eq!(
+                location(&router, "/undo").await,
+                (StatusCode::SEE_OTHER, "/notes/abc".to_string())
+            );
+            assert_eq!(
+                location(&router, "/redo").await,
+                (StatusCode::SEE_OTHER, "/notes".to_string())
+            );
+
+            // Both directions are used up
+            let (status, _) = location(&router, "/redo").await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        #[tokio::test]
+        async fn test_undo_refuses_to_overwrite_recreated_record() {
+            let server = mock_pds().await;
+            holds_note(&server).await;
+            expect_delete(&server, "bafystored").await;
+            after_delete(&server, 200).await;
+            Mock::given(method("POST"))
+                .and(path("/xrpc/com.atproto.repo.createRecord"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                .expect(0)
+                .mount(&server)
+                .await;
+            let router = logged_in_router(&server).await;
+
+            location(&router, "/api/notes/abc/delete").await;
+            let (status, _) = location(&router, "/undo").await;
+            assert_eq!(status, StatusCode::CONFLICT);
+        }
+
+        #[tokio::test]
+        async fn test_deleting_missing_record_is_not_an_error() {
+            let server = mock_pds().await;
+            after_delete(&server, 404).await;
+            let router = logged_in_router(&server).await;
+
+            assert_eq!(
+                location(&router, "/api/notes/abc/delete").await,
+                (StatusCode::SEE_OTHER, "/notes".to_string())
+            );
+            // Nothing was journaled
+            let (status, _) = location(&router, "/undo").await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
 }
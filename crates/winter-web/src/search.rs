@@ -0,0 +1,419 @@
+//! Unified search across record types.
+//!
+//! `/search?q=...` runs one case-insensitive substring query over facts,
+//! notes, directives, rules, wiki entries and recent thoughts, and shows the
+//! hits grouped by type. Each group is capped at [`MAX_HITS_PER_TYPE`] and
+//! reports how many records matched in total.
+
+use tracing::warn;
+use winter_atproto::{
+    AtprotoClient, AtprotoError, DIRECTIVE_COLLECTION, Directive, FACT_COLLECTION, Fact,
+    ListRecordItem, NOTE_COLLECTION, Note, RULE_COLLECTION, Rule, THOUGHT_COLLECTION, Thought,
+    WIKI_ENTRY_COLLECTION, WikiEntry,
+};
+
+use crate::routes::{html_escape, thought_kind_to_string, truncate_chars};
+
+/// Most hits shown for each record type.
+pub(crate) const MAX_HITS_PER_TYPE: usize = 20;
+
+/// How many of the most recent thoughts are searched. The thought collection
+/// grows without bound, so it isn't loaded in full.
+const THOUGHT_SCAN_LIMIT: u32 = 100;
+
+/// Characters of content shown under each hit.
+const SNIPPET_CHARS: usize = 160;
+
+/// A single search hit.
+#[derive(Debug)]
+pub(crate) struct SearchHit {
+    pub title: String,
+    pub snippet: String,
+    /// Detail page, if the record type has one.
+    pub href: Option<String>,
+}
+
+/// Hits for one record type.
+#[derive(Debug)]
+pub(crate) struct SearchGroup {
+    pub label: &'static str,
+    pub hits: Vec<SearchHit>,
+    /// Number of matching records, including those past the cap.
+    pub total: usize,
+}
+
+/// The records a search runs over.
+#[derive(Default)]
+pub(crate) struct SearchCorpus {
+    pub facts: Vec<ListRecordItem<Fact>>,
+    pub notes: Vec<ListRecordItem<Note>>,
+    pub directives: Vec<ListRecordItem<Directive>>,
+    pub rules: Vec<ListRecordItem<Rule>>,
+    pub wiki: Vec<ListRecordItem<WikiEntry>>,
+    pub thoughts: Vec<ListRecordItem<Thought>>,
+}
+
+impl SearchCorpus {
+    /// Load every searchable collection. A collection that fails to load is
+    /// logged and searched as empty, so one bad collection doesn't hide hits
+    /// from the rest.
+    pub async fn load(client: &AtprotoClient) -> Self {
+        let (facts, notes, directives, rules, wiki, thoughts) = tokio::join!(
+            client.list_all_records::<Fact>(FACT_COLLECTION),
+            client.list_all_records::<Note>(NOTE_COLLECTION),
+            client.list_all_records::<Directive>(DIRECTIVE_COLLECTION),
+            client.list_all_records::<Rule>(RULE_COLLECTION),
+            client.list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION),
+            client.list_records::<Thought>(THOUGHT_COLLECTION, Some(THOUGHT_SCAN_LIMIT), None),
+        );
+
+        Self {
+            facts: live(or_empty(facts, "facts"), |f| f.deleted_at.is_none()),
+            notes: live(or_empty(notes, "notes"), |n| n.deleted_at.is_none()),
+            directives: or_empty(directives, "directives"),
+            rules: or_empty(rules, "rules"),
+            wiki: or_empty(wiki, "wiki entries"),
+            thoughts: or_empty(thoughts.map(|r| r.records), "thoughts"),
+        }
+    }
+
+    /// Run `query` over every record type. Groups without hits are omitted.
+    pub fn search(&self, query: &str) -> Vec<SearchGroup> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        [
+            group("Facts", &self.facts, &query, fact_matches, |rkey, f| {
+                SearchHit {
+                    title: format!("{}({})", f.predicate, f.args.join(", ")),
+                    snippet: f.tags.join(", "),
+                    href: Some(format!("/facts/{}", rkey)),
+                }
+            }),
+            group("Notes", &self.notes, &query, note_matches, |rkey, n| {
+                SearchHit {
+                    title: n.title.clone(),
+                    snippet: truncate_chars(&n.content, SNIPPET_CHARS),
+                    href: Some(format!("/notes/{}", rkey)),
+                }
+            }),
+            group(
+                "Directives",
+                &self.directives,
+                &query,
+                directive_matches,
+                |rkey, d| SearchHit {
+                    title: d
+                        .summary
+                        .clone()
+                        .unwrap_or_else(|| truncate_chars(&d.content, 80)),
+                    snippet: truncate_chars(&d.content, SNIPPET_CHARS),
+                    href: Some(format!("/directives/{}", rkey)),
+                },
+            ),
+            group("Rules", &self.rules, &query, rule_matches, |rkey, r| {
+                SearchHit {
+                    title: r.name.clone(),
+                    snippet: format!("{} :- {}", r.head, r.body.join(", ")),
+                    href: Some(format!("/rules/{}", rkey)),
+                }
+            }),
+            group("Wiki", &self.wiki, &query, wiki_matches, |_, w| SearchHit {
+                title: w.title.clone(),
+                snippet: w
+                    .summary
+                    .clone()
+                    .unwrap_or_else(|| truncate_chars(&w.content, SNIPPET_CHARS)),
+                href: Some(format!("/wiki/{}", w.slug)),
+            }),
+            group(
+                "Thoughts",
+                &self.thoughts,
+                &query,
+                thought_matches,
+                |_, t| SearchHit {
+                    title: format!(
+                        "{} · {}",
+                        thought_kind_to_string(&t.kind),
+                        t.created_at.format("%Y-%m-%d %H:%M")
+                    ),
+                    snippet: truncate_chars(&t.content, SNIPPET_CHARS),
+                    href: None,
+                },
+            ),
+        ]
+        .into_iter()
+        .filter(|g| g.total > 0)
+        .collect()
+    }
+}
+
+fn or_empty<T>(
+    result: Result<Vec<ListRecordItem<T>>, AtprotoError>,
+    what: &str,
+) -> Vec<ListRecordItem<T>> {
+    result.unwrap_or_else(|e| {
+        warn!(error = %e, "failed to load {} for search", what);
+        Vec::new()
+    })
+}
+
+/// Drop soft-deleted records, which stay hidden until restored.
+fn live<T>(items: Vec<ListRecordItem<T>>, keep: impl Fn(&T) -> bool) -> Vec<ListRecordItem<T>> {
+    items.into_iter().filter(|item| keep(&item.value)).collect()
+}
+
+fn group<T>(
+    label: &'static str,
+    items: &[ListRecordItem<T>],
+    query: &str,
+    matches: fn(&T, &str) -> bool,
+    hit: impl Fn(&str, &T) -> SearchHit,
+) -> SearchGroup {
+    let matching: Vec<_> = items
+        .iter()
+        .filter(|item| matches(&item.value, query))
+        .collect();
+    let hits = matching
+        .iter()
+        .take(MAX_HITS_PER_TYPE)
+        .map(|item| hit(item.uri.split('/').next_back().unwrap_or(""), &item.value))
+        .collect();
+    SearchGroup {
+        label,
+        hits,
+        total: matching.len(),
+    }
+}
+
+/// Whether any of `fields` contains `query`, which must already be lowercase.
+fn contains_any<'a>(query: &str, fields: impl IntoIterator<Item = &'a str>) -> bool {
+    fields
+        .into_iter()
+        .any(|field| field.to_lowercase().contains(query))
+}
+
+pub(crate) fn fact_matches(fact: &Fact, query: &str) -> bool {
+    contains_any(
+        query,
+        std::iter::once(fact.predicate.as_str())
+            .chain(fact.args.iter().map(String::as_str))
+            .chain(fact.tags.iter().map(String::as_str)),
+    )
+}
+
+pub(crate) fn note_matches(note: &Note, query: &str) -> bool {
+    contains_any(
+        query,
+        [note.title.as_str(), note.content.as_str()]
+            .into_iter()
+            .chain(note.tags.iter().map(String::as_str)),
+    )
+}
+
+pub(crate) fn directive_matches(directive: &Directive, query: &str) -> bool {
+    contains_any(
+        query,
+        [
+            directive.content.as_str(),
+            directive.summary.as_deref().unwrap_or(""),
+        ]
+        .into_iter()
+        .chain(directive.tags.iter().map(String::as_str)),
+    )
+}
+
+pub(crate) fn rule_matches(rule: &Rule, query: &str) -> bool {
+    contains_any(
+        query,
+        [
+            rule.name.as_str(),
+            rule.description.as_str(),
+            rule.head.as_str(),
+        ]
+        .into_iter()
+        .chain(rule.body.iter().map(String::as_str)),
+    )
+}
+
+pub(crate) fn wiki_matches(entry: &WikiEntry, query: &str) -> bool {
+    contains_any(
+        query,
+        [
+            entry.title.as_str(),
+            entry.slug.as_str(),
+            entry.content.as_str(),
+        ]
+        .into_iter()
+        .chain(entry.aliases.iter().map(String::as_str))
+        .chain(entry.tags.iter().map(String::as_str)),
+    )
+}
+
+pub(crate) fn thought_matches(thought: &Thought, query: &str) -> bool {
+    contains_any(
+        query,
+        std::iter::once(thought.content.as_str())
+            .chain(thought.trigger.as_deref())
+            .chain(thought.tags.iter().map(String::as_str)),
+    )
+}
+
+/// Render grouped results as HTML.
+pub(crate) fn render_groups(groups: &[SearchGroup]) -> String {
+    let mut html = String::new();
+    for group in groups {
+        let shown = if group.total > group.hits.len() {
+            format!("showing {} of {}", group.hits.len(), group.total)
+        } else {
+            group.total.to_string()
+        };
+        html.push_str(&format!(
+            r#"<section class="group"><h2>{} <span class="count">({})</span></h2>"#,
+            group.label, shown
+        ));
+        for hit in &group.hits {
+            let title = match &hit.href {
+                Some(href) => format!(
+                    r#"<a href="{}" class="title">{}</a>"#,
+                    html_escape(href),
+                    html_escape(&hit.title)
+                ),
+                None => format!(r#"<span class="title">{}</span>"#, html_escape(&hit.title)),
+            };
+            html.push_str(&format!(
+                r#"<div class="hit"><span class="type">{}</span> {}<div class="snippet">{}</div></div>"#,
+                group.label,
+                title,
+                html_escape(&hit.snippet)
+            ));
+        }
+        html.push_str("</section>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item<T: serde::de::DeserializeOwned>(
+        collection: &str,
+        rkey: &str,
+        value: serde_json::Value,
+    ) -> ListRecordItem<T> {
+        ListRecordItem {
+            uri: format!("at://did:plc:test/{}/{}", collection, rkey),
+            cid: "bafytest".to_string(),
+            value: serde_json::from_value(value).unwrap(),
+        }
+    }
+
+    fn corpus() -> SearchCorpus {
+        SearchCorpus {
+            facts: vec![
+                item(
+                    FACT_COLLECTION,
+                    "f1",
+                    json!({ "predicate": "likes", "args": ["self", "Lichen"], "createdAt": "2026-01-01T00:00:00Z" }),
+                ),
+                item(
+                    FACT_COLLECTION,
+                    "f2",
+                    json!({ "predicate": "likes", "args": ["self", "moss"], "createdAt": "2026-01-01T00:00:00Z" }),
+                ),
+            ],
+            notes: vec![item(
+                NOTE_COLLECTION,
+                "n1",
+                json!({ "title": "Field notes", "content": "Found lichen on the north wall." }),
+            )],
+            directives: vec![],
+            rules: vec![item(
+                RULE_COLLECTION,
+                "r1",
+                json!({
+                    "name": "mutual_interest",
+                    "description": "shared interest in lichens",
+                    "head": "mutual(X)",
+                    "body": ["likes(self, X)"],
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }),
+            )],
+            wiki: vec![item(
+                WIKI_ENTRY_COLLECTION,
+                "w1",
+                json!({
+                    "title": "Lichen",
+                    "slug": "lichen",
+                    "content": "A symbiosis.",
+                    "status": "stable"
+                }),
+            )],
+            thoughts: vec![item(
+                THOUGHT_COLLECTION,
+                "t1",
+                json!({
+                    "kind": "insight",
+                    "content": "Unrelated thought",
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }),
+            )],
+        }
+    }
+
+    #[test]
+    fn test_shared_term_hits_multiple_collections() {
+        let groups = corpus().search("LICHEN");
+        let labels: Vec<_> = groups.iter().map(|g| g.label).collect();
+        assert_eq!(labels, ["Facts", "Notes", "Rules", "Wiki"]);
+
+        let facts = &groups[0];
+        assert_eq!(facts.total, 1);
+        assert_eq!(facts.hits[0].href.as_deref(), Some("/facts/f1"));
+        // Wiki links go by slug.
+        assert_eq!(groups[3].hits[0].href.as_deref(), Some("/wiki/lichen"));
+    }
+
+    #[test]
+    fn test_hits_capped_per_type() {
+        let mut corpus = SearchCorpus::default();
+        for i in 0..(MAX_HITS_PER_TYPE + 5) {
+            corpus.notes.push(item(
+                NOTE_COLLECTION,
+                &format!("n{}", i),
+                json!({ "title": format!("lichen {}", i), "content": "" }),
+            ));
+        }
+        let groups = corpus.search("lichen");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].hits.len(), MAX_HITS_PER_TYPE);
+        assert_eq!(groups[0].total, MAX_HITS_PER_TYPE + 5);
+        assert!(render_groups(&groups).contains("showing 20 of 25"));
+    }
+
+    #[test]
+    fn test_blank_query_returns_nothing() {
+        assert!(corpus().search("   ").is_empty());
+    }
+
+    #[test]
+    fn test_render_escapes_and_labels() {
+        let groups = vec![SearchGroup {
+            label: "Notes",
+            hits: vec![SearchHit {
+                title: "<b>bold</b>".to_string(),
+                snippet: "a & b".to_string(),
+                href: Some("/notes/n1".to_string()),
+            }],
+            total: 1,
+        }];
+        let html = render_groups(&groups);
+        assert!(html.contains(r#"<span class="type">Notes</span>"#));
+        assert!(html.contains("&lt;b&gt;bold&lt;/b&gt;"));
+        assert!(html.contains("a &amp; b"));
+    }
+}
@@ -1,12 +1,18 @@
 //! Server-Sent Events for live updates.
 
+use std::collections::VecDeque;
 use std::convert::Infallible;
+use std::sync::Mutex;
 
 use axum::response::sse::{Event, KeepAlive, Sse};
+use tokio::sync::broadcast;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 
+/// Events kept for replay to reconnecting clients.
+pub const DEFAULT_REPLAY_CAPACITY: usize = 256;
+
 /// Create an SSE stream from a broadcast channel.
 pub fn create_sse_stream(
     rx: tokio::sync::broadcast::Receiver<String>,
@@ -18,3 +24,159 @@ pub fn create_sse_stream(
 
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
+
+/// An event published to an [`EventLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Sent as the SSE `id:`, which browsers echo back in `Last-Event-ID`
+    /// when they reconnect.
+    pub id: String,
+    pub data: String,
+}
+
+/// Broadcast channel with a bounded replay buffer.
+///
+/// Browsers reconnect an `EventSource` automatically after a dropped
+/// connection, sending the id of the last event they saw. Keeping the most
+/// recent events lets the stream resume from there instead of silently
+/// skipping whatever was published while the client was away.
+///
+/// Ids are expected to increase in lexical order (record TIDs do), which is
+/// used to resume when the client's last id has already left the buffer.
+pub struct EventLog {
+    tx: broadcast::Sender<SseEvent>,
+    buffer: Mutex<VecDeque<SseEvent>>,
+    capacity: usize,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(100);
+        Self {
+            tx,
+            buffer: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Record an event and send it to live subscribers.
+    pub fn publish(&self, id: impl Into<String>, data: impl Into<String>) {
+        let event = SseEvent {
+            id: id.into(),
+            data: data.into(),
+        };
+        // Hold the buffer lock while sending so `subscribe_since` can't see an
+        // event both in the buffer and on its new receiver, or in neither.
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(event.clone());
+        let _ = self.tx.send(event);
+    }
+
+    /// Buffered events after `last_event_id`, plus a receiver for everything
+    /// published afterwards.
+    ///
+    /// Without a last id there is nothing to catch up on, so nothing is
+    /// replayed. If the id is no longer buffered, every buffered event with a
+    /// later id is replayed.
+    pub fn subscribe_since(
+        &self,
+        last_event_id: Option<&str>,
+    ) -> (Vec<SseEvent>, broadcast::Receiver<SseEvent>) {
+        let buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = self.tx.subscribe();
+        let Some(last) = last_event_id else {
+            return (Vec::new(), rx);
+        };
+
+        let replay = match buffer.iter().rposition(|e| e.id == last) {
+            Some(pos) => buffer.iter().skip(pos + 1).cloned().collect(),
+            None => buffer
+                .iter()
+                .filter(|e| e.id.as_str() > last)
+                .cloned()
+                .collect(),
+        };
+        (replay, rx)
+    }
+}
+
+/// Stream of replayed events followed by live ones.
+pub fn replay_stream(
+    log: &EventLog,
+    last_event_id: Option<&str>,
+) -> impl tokio_stream::Stream<Item = SseEvent> + use<> {
+    let (replay, rx) = log.subscribe_since(last_event_id);
+    let live = BroadcastStream::new(rx).filter_map(Result::ok);
+    tokio_stream::iter(replay).chain(live)
+}
+
+/// Create an SSE stream from an [`EventLog`], resuming after `last_event_id`.
+pub fn create_replay_sse_stream(
+    log: &EventLog,
+    last_event_id: Option<&str>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>> + use<>> {
+    let stream = replay_stream(log, last_event_id)
+        .map(|event| Ok(Event::default().id(event.id).data(event.data)));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(events: &[SseEvent]) -> Vec<&str> {
+        events.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_replays_only_newer_events() {
+        let log = EventLog::new(10);
+        for id in ["3a", "3b", "3c", "3d"] {
+            log.publish(id, format!("data {}", id));
+        }
+
+        let (replay, _) = log.subscribe_since(Some("3b"));
+        assert_eq!(ids(&replay), ["3c", "3d"]);
+        assert_eq!(replay[0].data, "data 3c");
+
+        let (replay, _) = log.subscribe_since(Some("3d"));
+        assert!(replay.is_empty());
+    }
+
+    #[test]
+    fn test_no_last_id_replays_nothing() {
+        let log = EventLog::new(10);
+        log.publish("3a", "a");
+        let (replay, _) = log.subscribe_since(None);
+        assert!(replay.is_empty());
+    }
+
+    #[test]
+    fn test_evicted_last_id_falls_back_to_ordering() {
+        let log = EventLog::new(2);
+        for id in ["3a", "3b", "3c", "3d"] {
+            log.publish(id, id);
+        }
+        // "3a" was evicted; everything still buffered is newer.
+        let (replay, _) = log.subscribe_since(Some("3a"));
+        assert_eq!(ids(&replay), ["3c", "3d"]);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_then_continues_live() {
+        let log = EventLog::new(10);
+        log.publish("3a", "a");
+        log.publish("3b", "b");
+
+        let stream = replay_stream(&log, Some("3a"));
+        tokio::pin!(stream);
+        log.publish("3c", "c");
+
+        assert_eq!(stream.next().await.unwrap().id, "3b");
+        assert_eq!(stream.next().await.unwrap().id, "3c");
+    }
+}
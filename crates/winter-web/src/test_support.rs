@@ -0,0 +1,35 @@
+//! Helpers for tests that drive the router against a mock PDS.
+
+use axum::Router;
+use serde_json::json;
+use winter_atproto::AtprotoClient;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::create_router;
+
+/// DID of the account the mock PDS logs in.
+pub const DID: &str = "did:plc:testuser123";
+
+/// Start a mock PDS that accepts logins.
+pub async fn mock_pds() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/xrpc/com.atproto.server.createSession"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "did": DID,
+            "handle": "test.example.com",
+            "accessJwt": "test-access-token",
+            "refreshJwt": "test-refresh-token"
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// A router whose client is logged in to `server`.
+pub async fn logged_in_router(server: &MockServer) -> Router {
+    let client = AtprotoClient::new(server.uri());
+    client.login("test.example.com", "password").await.unwrap();
+    create_router(client, None, None)
+}
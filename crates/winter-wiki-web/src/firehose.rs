@@ -8,7 +8,9 @@ use iroh_car::CarReader;
 use tokio_tungstenite::connect_async;
 use tracing::{debug, info, trace, warn};
 
-use winter_atproto::{WIKI_ENTRY_COLLECTION, WIKI_LINK_COLLECTION, WikiEntry, WikiLink};
+use winter_atproto::{
+    FirehoseMetrics, WIKI_ENTRY_COLLECTION, WIKI_LINK_COLLECTION, WikiEntry, WikiLink,
+};
 
 use crate::db::WikiDb;
 
@@ -16,11 +18,16 @@ use crate::db::WikiDb;
 pub struct FirehoseConsumer {
     relay_url: String,
     db: Arc<WikiDb>,
+    metrics: Arc<FirehoseMetrics>,
 }
 
 impl FirehoseConsumer {
-    pub fn new(relay_url: String, db: Arc<WikiDb>) -> Self {
-        Self { relay_url, db }
+    pub fn new(relay_url: String, db: Arc<WikiDb>, metrics: Arc<FirehoseMetrics>) -> Self {
+        Self {
+            relay_url,
+            db,
+            metrics,
+        }
     }
 
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -93,6 +100,7 @@ impl FirehoseConsumer {
         let header: FrameHeader = ciborium::from_reader(&mut cursor)?;
 
         if header.op != 1 || header.t.as_deref() != Some("#commit") {
+            self.metrics.record(None, None);
             return Ok(0);
         }
 
@@ -102,6 +110,12 @@ impl FirehoseConsumer {
             serde_ipld_dagcbor::from_slice(&data[payload_offset..])?;
 
         let seq = payload.seq;
+        let time_us = payload
+            .time
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp_micros());
+        self.metrics.record(time_us, (seq > 0).then_some(seq));
 
         // Quick check: does this commit touch wiki collections?
         let has_wiki_ops = payload.ops.iter().any(|op| {
@@ -199,6 +213,9 @@ struct CommitPayload {
     repo: String,
     #[serde(default)]
     seq: i64,
+    /// When the commit was emitted by the relay (RFC 3339).
+    #[serde(default)]
+    time: Option<String>,
     ops: Vec<CommitOp>,
     #[serde(with = "serde_bytes")]
     blocks: Vec<u8>,
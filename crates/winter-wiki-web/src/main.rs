@@ -12,10 +12,12 @@ mod resolver;
 mod routes;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use clap::Parser;
 use tokio::sync::RwLock;
 use tracing::info;
+use winter_atproto::FirehoseMetrics;
 
 use crate::db::WikiDb;
 use crate::firehose::FirehoseConsumer;
@@ -36,6 +38,10 @@ struct Args {
     /// SQLite database path.
     #[arg(long, default_value = "wiki.db")]
     db: String,
+
+    /// Seconds without a firehose event before /health reports degraded.
+    #[arg(long, default_value_t = 60)]
+    stale_after_secs: u64,
 }
 
 #[tokio::main]
@@ -57,7 +63,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let resolver = Arc::new(RwLock::new(HandleResolver::new()));
 
     // Start firehose consumer
-    let firehose = FirehoseConsumer::new(args.relay.clone(), Arc::clone(&db));
+    let metrics = Arc::new(FirehoseMetrics::new());
+    let firehose =
+        FirehoseConsumer::new(args.relay.clone(), Arc::clone(&db), Arc::clone(&metrics));
     tokio::spawn(async move {
         if let Err(e) = firehose.run().await {
             tracing::error!(error = %e, "firehose consumer failed");
@@ -65,7 +73,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Start web server
-    let router = routes::create_router(Arc::clone(&db), resolver);
+    let router = routes::create_router(
+        Arc::clone(&db),
+        resolver,
+        metrics,
+        Duration::from_secs(args.stale_after_secs),
+    );
     let listener = tokio::net::TcpListener::bind(&args.listen).await?;
 
     info!(listen = %args.listen, relay = %args.relay, "winter-wiki-web started");
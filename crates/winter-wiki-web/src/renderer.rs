@@ -1,9 +1,9 @@
 //! Markdown rendering with wiki-link resolution.
 
-use pulldown_cmark::{Options, Parser, html};
 use regex::Regex;
+use winter_atproto::render_markdown;
 
-/// Render markdown to HTML, resolving wiki-link syntax.
+/// Render markdown to sanitized HTML, resolving wiki-link syntax.
 ///
 /// `resolve_slug` is called for `[[slug]]` references to check if the entry exists
 /// and get the author handle for URL construction.
@@ -15,16 +15,8 @@ pub fn render_wiki_markdown(
     // First pass: replace [[wiki-link]] syntax with HTML links
     let with_links = resolve_wiki_links(content, author_handle, resolve_slug);
 
-    // Second pass: render markdown to HTML
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    options.insert(Options::ENABLE_TABLES);
-
-    let parser = Parser::new_ext(&with_links, options);
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
-
-    html_output
+    // Second pass: render markdown to sanitized HTML
+    render_markdown(&with_links)
 }
 
 /// Replace [[wiki-link]] syntax with HTML anchor tags.
@@ -106,4 +98,15 @@ mod tests {
         assert!(result.contains("My Page"));
         assert!(result.contains("/u/alice.bsky.social/my-page"));
     }
+
+    #[test]
+    fn test_render_wiki_markdown_sanitizes() {
+        let html = render_wiki_markdown(
+            "See [[my-page]]\n\n<script>alert(1)</script>",
+            "alice.bsky.social",
+            |_, _| None,
+        );
+        assert!(html.contains(r#"<a href="/u/alice.bsky.social/my-page">my-page</a>"#));
+        assert!(!html.contains("<script>"));
+    }
 }
@@ -1,16 +1,19 @@
 //! Web routes for the wiki browser.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    Router,
+    Json, Router,
     extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse},
     routing::{get, post},
 };
 use serde::Deserialize;
+use serde_json::json;
 use tokio::sync::RwLock;
+use winter_atproto::FirehoseMetrics;
 
 use crate::backfill;
 use crate::db::WikiDb;
@@ -21,14 +24,28 @@ use crate::resolver::HandleResolver;
 pub struct AppState {
     pub db: Arc<WikiDb>,
     pub resolver: Arc<RwLock<HandleResolver>>,
+    pub firehose: Arc<FirehoseMetrics>,
+    /// Firehose silence after which `/health` reports degraded.
+    pub stale_after: Duration,
 }
 
 /// Create the web router.
-pub fn create_router(db: Arc<WikiDb>, resolver: Arc<RwLock<HandleResolver>>) -> Router {
-    let state = Arc::new(AppState { db, resolver });
+pub fn create_router(
+    db: Arc<WikiDb>,
+    resolver: Arc<RwLock<HandleResolver>>,
+    firehose: Arc<FirehoseMetrics>,
+    stale_after: Duration,
+) -> Router {
+    let state = Arc::new(AppState {
+        db,
+        resolver,
+        firehose,
+        stale_after,
+    });
 
     Router::new()
         .route("/", get(index))
+        .route("/health", get(health))
         .route("/u/{handle_or_did}", get(user_entries))
         .route("/u/{handle_or_did}/{slug}", get(entry_detail))
         .route("/search", get(search))
@@ -244,6 +261,25 @@ async fn search(
 // Admin
 // ============================================================================
 
+/// Firehose throughput and lag. Returns 503 with `"status": "degraded"` once
+/// no event has arrived within the staleness threshold.
+async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let firehose = state.firehose.snapshot(state.stale_after);
+    let (status, label) = if firehose.stale {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    } else {
+        (StatusCode::OK, "ok")
+    };
+    (
+        status,
+        Json(json!({
+            "status": label,
+            "firehose": firehose,
+            "cursor": state.db.get_cursor().ok().flatten(),
+        })),
+    )
+}
+
 async fn admin_backfill(
     State(state): State<Arc<AppState>>,
     Path(handle_or_did): Path<String>,
@@ -480,3 +516,57 @@ const NOT_FOUND_HTML: &str = r#"<!DOCTYPE html>
     <a href="/">Back to Wiki</a>
 </body>
 </html>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get_health(
+        stale_after: Duration,
+        metrics: Arc<FirehoseMetrics>,
+    ) -> (StatusCode, serde_json::Value) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = WikiDb::open(dir.path().join("wiki.db").to_str().unwrap()).unwrap();
+        let router = create_router(
+            Arc::new(db),
+            Arc::new(RwLock::new(HandleResolver::new())),
+            metrics,
+            stale_after,
+        );
+        let response = router
+            .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_firehose_metrics() {
+        let metrics = Arc::new(FirehoseMetrics::new());
+        metrics.record(None, Some(42));
+
+        let (status, body) = get_health(Duration::from_secs(60), metrics).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["firehose"]["total_events"], 1);
+        assert_eq!(body["firehose"]["last_seq"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_health_degraded_when_firehose_stale() {
+        let start = chrono::Utc::now().timestamp_micros() - 120_000_000;
+        let metrics = Arc::new(FirehoseMetrics::new_at(start));
+
+        let (status, body) = get_health(Duration::from_secs(60), metrics).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["firehose"]["stale"], true);
+    }
+}
@@ -0,0 +1,256 @@
+//! Goal selection for the `awaken` job.
+//!
+//! The `awaken` job starts Winter's autonomous thought cycles. Its own
+//! instructions never change, so on each run the daemon appends one of
+//! Winter's active [`DirectiveKind::Goal`] directives, picked by an
+//! [`AwakenGoalStrategy`], to vary what idle time is spent on.
+//!
+//! An awakening that comes too soon after Winter's last tool call is skipped
+//! instead (see [`in_quiet_period`]); the job runs again at its next interval.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use winter_atproto::{Directive, DirectiveKind};
+
+/// Name of the job whose instructions get a goal appended.
+pub const AWAKEN_JOB_NAME: &str = "awaken";
+
+/// How the goal for each awakening is picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AwakenGoalStrategy {
+    /// Take the goals in turn, oldest first.
+    #[default]
+    RoundRobin,
+    /// Pick at random, weighting each goal by its priority (minimum 1).
+    Weighted,
+}
+
+impl FromStr for AwakenGoalStrategy {
+    type Err = String;
+
+    /// Parse `round-robin` or `weighted`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "round-robin" => Ok(Self::RoundRobin),
+            "weighted" => Ok(Self::Weighted),
+            other => Err(format!(
+                "invalid awaken goal strategy '{}': expected round-robin or weighted",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AwakenGoalStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+/// Picks the goal for each awakening.
+#[derive(Debug)]
+pub struct GoalSelector {
+    strategy: AwakenGoalStrategy,
+    /// Awakenings so far, which drives the round-robin rotation.
+    turn: usize,
+}
+
+impl GoalSelector {
+    /// Create a selector that starts at the oldest goal.
+    pub fn new(strategy: AwakenGoalStrategy) -> Self {
+        Self { strategy, turn: 0 }
+    }
+
+    /// Pick a goal from `directives`, ignoring anything that isn't an active goal.
+    ///
+    /// `roll` is a uniform sample from `[0, 1)`, used only by the weighted
+    /// strategy. Returns `None` when there are no active goals.
+    pub fn select<'a>(&mut self, directives: &'a [Directive], roll: f64) -> Option<&'a Directive> {
+        let mut goals: Vec<&Directive> = directives
+            .iter()
+            .filter(|d| d.active && d.kind == DirectiveKind::Goal)
+            .collect();
+        if goals.is_empty() {
+            return None;
+        }
+        goals.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.content.cmp(&b.content))
+        });
+
+        match self.strategy {
+            AwakenGoalStrategy::RoundRobin => {
+                let goal = goals[self.turn % goals.len()];
+                self.turn = self.turn.wrapping_add(1);
+                Some(goal)
+            }
+            AwakenGoalStrategy::Weighted => {
+                let weights: Vec<u64> = goals.iter().map(|g| g.priority.max(1) as u64).collect();
+                let total: u64 = weights.iter().sum();
+                let mut target = (roll.clamp(0.0, 1.0) * total as f64) as u64;
+                for (goal, weight) in goals.iter().zip(&weights) {
+                    if target < *weight {
+                        return Some(goal);
+                    }
+                    target -= weight;
+                }
+                goals.last().copied()
+            }
+        }
+    }
+}
+
+/// Whether an awakening at `now` falls within `quiet_period` of Winter's
+/// last activity, and should be skipped.
+///
+/// Never true without a quiet period or before any activity has been seen.
+pub fn in_quiet_period(
+    quiet_period: Option<Duration>,
+    last_activity_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    let (Some(quiet_period), Some(active_at)) = (quiet_period, last_activity_at) else {
+        return false;
+    };
+    let quiet_period = chrono::Duration::from_std(quiet_period).unwrap_or(chrono::Duration::MAX);
+    now.signed_duration_since(active_at) < quiet_period
+}
+
+/// Append `goal` to the awaken job's `instructions`.
+pub fn with_goal(instructions: &str, goal: &Directive) -> String {
+    format!(
+        "{}\n\nGoal for this awakening: {}",
+        instructions.trim_end(),
+        goal.content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(content: &str, priority: i32, age_secs: i64) -> Directive {
+        Directive {
+            kind: DirectiveKind::Goal,
+            content: content.to_string(),
+            summary: None,
+            active: true,
+            confidence: None,
+            source: None,
+            supersedes: None,
+            tags: vec![],
+            priority,
+            created_at: Utc::now() - chrono::Duration::seconds(age_secs),
+            last_updated: None,
+        }
+    }
+
+    fn goals() -> Vec<Directive> {
+        vec![
+            goal("explore the timeline", 1, 20),
+            goal("reflect", 3, 30),
+            goal("review facts", 0, 10),
+        ]
+    }
+
+    #[test]
+    fn strategy_parses_and_displays() {
+        for strategy in [AwakenGoalStrategy::RoundRobin, AwakenGoalStrategy::Weighted] {
+            assert_eq!(strategy.to_string().parse(), Ok(strategy));
+        }
+        assert!("random".parse::<AwakenGoalStrategy>().is_err());
+    }
+
+    #[test]
+    fn round_robin_rotates_oldest_first() {
+        let goals = goals();
+        let mut selector = GoalSelector::new(AwakenGoalStrategy::RoundRobin);
+
+        let picked: Vec<&str> = (0..6)
+            .map(|_| selector.select(&goals, 0.0).unwrap().content.as_str())
+            .collect();
+        assert_eq!(
+            picked,
+            [
+                "reflect",
+                "explore the timeline",
+                "review facts",
+                "reflect",
+                "explore the timeline",
+                "review facts",
+            ]
+        );
+    }
+
+    #[test]
+    fn weighted_follows_priorities() {
+        let goals = goals();
+        let mut selector = GoalSelector::new(AwakenGoalStrategy::Weighted);
+
+        // Weights are 3 (reflect), 1 (explore) and 1 (review, priority 0),
+        // so evenly spread rolls land 3:1:1
+        let mut counts = std::collections::HashMap::new();
+        for i in 0..500 {
+            let roll = (i as f64 + 0.5) / 500.0;
+            let goal = selector.select(&goals, roll).unwrap();
+            *counts.entry(goal.content.as_str()).or_insert(0) += 1;
+        }
+        assert_eq!(counts["reflect"], 300);
+        assert_eq!(counts["explore the timeline"], 100);
+        assert_eq!(counts["review facts"], 100);
+    }
+
+    #[test]
+    fn only_active_goals_are_selected() {
+        let mut inactive = goal("sleep", 10, 40);
+        inactive.active = false;
+        let mut value = goal("honesty", 10, 50);
+        value.kind = DirectiveKind::Value;
+        let directives = vec![inactive, value];
+
+        for strategy in [AwakenGoalStrategy::RoundRobin, AwakenGoalStrategy::Weighted] {
+            let mut selector = GoalSelector::new(strategy);
+            assert!(selector.select(&directives, 0.5).is_none());
+        }
+    }
+
+    #[test]
+    fn awakening_skipped_while_quiet_after_activity() {
+        let active = Utc::now();
+        let quiet = Some(Duration::from_secs(600));
+
+        assert!(in_quiet_period(
+            quiet,
+            Some(active),
+            active + chrono::Duration::seconds(599)
+        ));
+        assert!(!in_quiet_period(
+            quiet,
+            Some(active),
+            active + chrono::Duration::seconds(600)
+        ));
+    }
+
+    #[test]
+    fn awakening_proceeds_without_quiet_period_or_past_activity() {
+        let now = Utc::now();
+        assert!(!in_quiet_period(None, Some(now), now));
+        assert!(!in_quiet_period(Some(Duration::from_secs(600)), None, now));
+    }
+
+    #[test]
+    fn goal_is_appended_to_instructions() {
+        let goal = goal("reflect", 0, 0);
+        assert_eq!(
+            with_goal("Think freely.\n", &goal),
+            "Think freely.\n\nGoal for this awakening: reflect"
+        );
+    }
+}
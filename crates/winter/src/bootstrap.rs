@@ -1,7 +1,10 @@
 //! Bootstrap command for initializing Winter's identity.
 
-use chrono::Utc;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
 use miette::Result;
+use serde::Deserialize;
 use tracing::{info, warn};
 
 use winter_atproto::{
@@ -34,17 +37,126 @@ const DEFAULT_INTERESTS: &[&str] = &[
     "emergent behavior",
 ];
 
+/// The self-description and directive lists a bootstrap seeds Winter with.
+///
+/// Comes either from the `--values`, `--interests` and `--self-description`
+/// flags or from an identity document passed with `--from-file`:
+///
+/// ```yaml
+/// self_description: |
+///   I am Winter, ...
+/// values:
+///   - intellectual honesty
+/// interests:
+///   - distributed systems
+/// beliefs: []
+/// guidelines: []
+/// boundaries: []
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdentitySeed {
+    /// Falls back to [`DEFAULT_SELF_DESCRIPTION`] when absent.
+    pub self_description: Option<String>,
+    #[serde(default)]
+    pub values: Vec<String>,
+    #[serde(default)]
+    pub interests: Vec<String>,
+    #[serde(default)]
+    pub beliefs: Vec<String>,
+    #[serde(default)]
+    pub guidelines: Vec<String>,
+    #[serde(default)]
+    pub boundaries: Vec<String>,
+}
+
+impl IdentitySeed {
+    /// Seed from the comma-separated CLI flags, using the default values and
+    /// interests for flags that weren't given.
+    pub fn from_flags(
+        values: Option<String>,
+        interests: Option<String>,
+        self_description: Option<String>,
+    ) -> Self {
+        Self {
+            self_description,
+            values: split_or_default(values, DEFAULT_VALUES),
+            interests: split_or_default(interests, DEFAULT_INTERESTS),
+            ..Default::default()
+        }
+    }
+
+    /// Load an identity document. YAML is a superset of JSON, so JSON seeds
+    /// such as `examples/identity_seed.json` load too.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| miette::miette!("failed to read {}: {}", path.display(), e))?;
+        Self::parse(&text)
+            .map_err(|e| miette::miette!("invalid identity file {}: {}", path.display(), e))
+    }
+
+    fn parse(text: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(text)
+    }
+
+    /// The directives this seed creates, self-concept first.
+    pub fn directives(&self, now: DateTime<Utc>) -> Vec<Directive> {
+        let self_description = self
+            .self_description
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SELF_DESCRIPTION.to_string());
+
+        let lists = [
+            (DirectiveKind::Value, &self.values),
+            (DirectiveKind::Interest, &self.interests),
+            (DirectiveKind::Belief, &self.beliefs),
+            (DirectiveKind::Guideline, &self.guidelines),
+            (DirectiveKind::Boundary, &self.boundaries),
+        ];
+
+        let mut directives = vec![bootstrap_directive(
+            DirectiveKind::SelfConcept,
+            self_description,
+            now,
+        )];
+        for (kind, contents) in lists {
+            for content in contents {
+                directives.push(bootstrap_directive(kind.clone(), content.clone(), now));
+            }
+        }
+        directives
+    }
+}
+
+fn split_or_default(list: Option<String>, default: &[&str]) -> Vec<String> {
+    list.map(|l| l.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_else(|| default.iter().map(|s| s.to_string()).collect())
+}
+
+fn bootstrap_directive(kind: DirectiveKind, content: String, now: DateTime<Utc>) -> Directive {
+    Directive {
+        kind,
+        content,
+        summary: None,
+        active: true,
+        confidence: None,
+        source: Some("bootstrap".to_string()),
+        supersedes: None,
+        tags: vec![],
+        priority: 0,
+        created_at: now,
+        last_updated: None,
+    }
+}
+
 /// Run the bootstrap command.
-#[allow(clippy::too_many_arguments)]
 pub async fn run(
     pds_url: &str,
     handle: &str,
     app_password: &str,
     operator_did: &str,
     overwrite: bool,
-    values: Option<String>,
-    interests: Option<String>,
-    self_description: Option<String>,
+    seed: IdentitySeed,
 ) -> Result<()> {
     info!("bootstrapping Winter identity");
 
@@ -85,7 +197,7 @@ pub async fn run(
         }
 
         // Create initial directives
-        create_initial_directives(&client, values, interests, self_description).await?;
+        create_initial_directives(&client, &seed).await?;
     }
 
     // Create default rules
@@ -98,99 +210,253 @@ pub async fn run(
     Ok(())
 }
 
-/// Create initial directives for a new Winter instance.
-async fn create_initial_directives(
-    client: &AtprotoClient,
-    values: Option<String>,
-    interests: Option<String>,
-    self_description: Option<String>,
-) -> Result<()> {
-    let now = Utc::now();
-
-    // Create self_concept directive
-    let self_desc = self_description.unwrap_or_else(|| DEFAULT_SELF_DESCRIPTION.to_string());
-    let directive = Directive {
-        kind: DirectiveKind::SelfConcept,
-        content: self_desc,
-        summary: None,
-        active: true,
-        confidence: None,
-        source: Some("bootstrap".to_string()),
-        supersedes: None,
-        tags: vec![],
-        priority: 0,
-        created_at: now,
-        last_updated: None,
-    };
-    let rkey = Tid::now().to_string();
+/// Compare what a bootstrap with `seed` would write against the PDS,
+/// without writing anything.
+///
+/// Directives are compared as `bootstrap --overwrite` would write them,
+/// since a plain bootstrap leaves an existing identity's directives alone.
+pub async fn check(
+    pds_url: &str,
+    handle: &str,
+    app_password: &str,
+    operator_did: &str,
+    seed: &IdentitySeed,
+) -> Result<DriftReport> {
+    let client = AtprotoClient::new(pds_url);
     client
-        .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
+        .login(handle, app_password)
         .await
         .map_err(|e| miette::miette!("{}", e))?;
-    info!("created self_concept directive");
-
-    // Create value directives
-    let values_list: Vec<String> = values
-        .map(|v| v.split(',').map(|s| s.trim().to_string()).collect())
-        .unwrap_or_else(|| DEFAULT_VALUES.iter().map(|s| s.to_string()).collect());
-
-    for value in values_list {
-        let directive = Directive {
-            kind: DirectiveKind::Value,
-            content: value.clone(),
-            summary: None,
-            active: true,
-            confidence: None,
-            source: Some("bootstrap".to_string()),
-            supersedes: None,
-            tags: vec![],
-            priority: 0,
-            created_at: now,
-            last_updated: None,
+
+    let identity = client
+        .get_record::<Identity>(IDENTITY_COLLECTION, IDENTITY_KEY)
+        .await
+        .ok()
+        .map(|r| r.value);
+    let directives: Vec<Directive> = client
+        .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
+        .await
+        .map_err(|e| miette::miette!("{}", e))?
+        .into_iter()
+        .map(|r| r.value)
+        .collect();
+    let rules: Vec<Rule> = client
+        .list_all_records::<Rule>(RULE_COLLECTION)
+        .await
+        .map_err(|e| miette::miette!("{}", e))?
+        .into_iter()
+        .map(|r| r.value)
+        .collect();
+    let state_exists = client
+        .get_record::<DaemonState>(STATE_COLLECTION, STATE_KEY)
+        .await
+        .is_ok();
+
+    let mut report = DriftReport::new(
+        operator_did,
+        identity.as_ref(),
+        &seed.directives(Utc::now()),
+        &directives,
+        &rules,
+    );
+    report.state_missing = !state_exists;
+    Ok(report)
+}
+
+/// How the identity record differs from what bootstrap would write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityDrift {
+    Unchanged,
+    Missing,
+    OperatorChanged { from: String, to: String },
+}
+
+/// A directive whose content would be replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveChange {
+    pub kind: DirectiveKind,
+    pub from: String,
+    pub to: String,
+}
+
+/// What a bootstrap would change, compared with what is in the PDS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriftReport {
+    pub identity: IdentityDrift,
+    /// Seeded directives with no active counterpart in the PDS.
+    pub new_directives: Vec<(DirectiveKind, String)>,
+    /// The self-concept, when the seeded one differs from the PDS.
+    pub changed_directives: Vec<DirectiveChange>,
+    /// Active directives of seeded kinds that the seed doesn't include.
+    pub extra_directives: Vec<(DirectiveKind, String)>,
+    /// Default rules missing from the PDS.
+    pub new_rules: Vec<String>,
+    /// Whether the daemon state record would be created.
+    pub state_missing: bool,
+}
+
+impl DriftReport {
+    /// Diff `planned` directives and the default rules against the PDS.
+    ///
+    /// Directives other than the self-concept are matched by kind and
+    /// content: a value or interest has no identity beyond its text, so an
+    /// edited one shows as one new and one extra directive.
+    fn new(
+        operator_did: &str,
+        identity: Option<&Identity>,
+        planned: &[Directive],
+        existing: &[Directive],
+        rules: &[Rule],
+    ) -> Self {
+        let identity = match identity {
+            None => IdentityDrift::Missing,
+            Some(id) if id.operator_did != operator_did => IdentityDrift::OperatorChanged {
+                from: id.operator_did.clone(),
+                to: operator_did.to_string(),
+            },
+            Some(_) => IdentityDrift::Unchanged,
         };
-        let rkey = Tid::now().to_string();
-        client
-            .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-        info!(value = %value, "created value directive");
+
+        let mut unmatched: Vec<&Directive> = existing.iter().filter(|d| d.active).collect();
+        let mut new_directives = Vec::new();
+        let mut changed_directives = Vec::new();
+        for directive in planned {
+            let same = unmatched.iter().position(|d| {
+                d.kind == directive.kind && d.content.trim() == directive.content.trim()
+            });
+            if let Some(i) = same {
+                unmatched.remove(i);
+                continue;
+            }
+            let replaced = (directive.kind == DirectiveKind::SelfConcept)
+                .then(|| unmatched.iter().position(|d| d.kind == directive.kind))
+                .flatten();
+            match replaced {
+                Some(i) => changed_directives.push(DirectiveChange {
+                    kind: directive.kind.clone(),
+                    from: unmatched.remove(i).content.clone(),
+                    to: directive.content.clone(),
+                }),
+                None => new_directives.push((directive.kind.clone(), directive.content.clone())),
+            }
+        }
+
+        // Aspirations and goals aren't seeded, so they can't have drifted
+        let extra_directives = unmatched
+            .into_iter()
+            .filter(|d| !matches!(d.kind, DirectiveKind::Aspiration | DirectiveKind::Goal))
+            .map(|d| (d.kind.clone(), d.content.clone()))
+            .collect();
+
+        let new_rules = default_rules()
+            .into_iter()
+            .filter(|rule| !rules.iter().any(|r| r.name == rule.name))
+            .map(|rule| rule.name)
+            .collect();
+
+        Self {
+            identity,
+            new_directives,
+            changed_directives,
+            extra_directives,
+            new_rules,
+            state_missing: false,
+        }
     }
 
-    // Create interest directives
-    let interests_list: Vec<String> = interests
-        .map(|i| i.split(',').map(|s| s.trim().to_string()).collect())
-        .unwrap_or_else(|| DEFAULT_INTERESTS.iter().map(|s| s.to_string()).collect());
-
-    for interest in interests_list {
-        let directive = Directive {
-            kind: DirectiveKind::Interest,
-            content: interest.clone(),
-            summary: None,
-            active: true,
-            confidence: None,
-            source: Some("bootstrap".to_string()),
-            supersedes: None,
-            tags: vec![],
-            priority: 0,
-            created_at: now,
-            last_updated: None,
-        };
+    /// Whether bootstrap would leave the PDS as it is.
+    pub fn is_empty(&self) -> bool {
+        self.identity == IdentityDrift::Unchanged
+            && self.new_directives.is_empty()
+            && self.changed_directives.is_empty()
+            && self.extra_directives.is_empty()
+            && self.new_rules.is_empty()
+            && !self.state_missing
+    }
+}
+
+impl std::fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No drift: the PDS matches the bootstrap seed.");
+        }
+
+        match &self.identity {
+            IdentityDrift::Unchanged => writeln!(f, "Identity: unchanged")?,
+            IdentityDrift::Missing => writeln!(f, "Identity: would be created")?,
+            IdentityDrift::OperatorChanged { from, to } => {
+                writeln!(f, "Identity: operator would change from {} to {}", from, to)?
+            }
+        }
+
+        if !self.new_directives.is_empty()
+            || !self.changed_directives.is_empty()
+            || !self.extra_directives.is_empty()
+        {
+            writeln!(
+                f,
+                "\nDirectives (+ new, ~ changed, - in PDS but not in seed):"
+            )?;
+            for (kind, content) in &self.new_directives {
+                writeln!(f, "  + {}: {}", kind, preview(content))?;
+            }
+            for change in &self.changed_directives {
+                writeln!(
+                    f,
+                    "  ~ {}: {} -> {}",
+                    change.kind,
+                    preview(&change.from),
+                    preview(&change.to)
+                )?;
+            }
+            for (kind, content) in &self.extra_directives {
+                writeln!(f, "  - {}: {}", kind, preview(content))?;
+            }
+        }
+
+        if !self.new_rules.is_empty() {
+            writeln!(
+                f,
+                "\nRules that would be created: {}",
+                self.new_rules.join(", ")
+            )?;
+        }
+        if self.state_missing {
+            writeln!(f, "\nDaemon state: would be created")?;
+        }
+        Ok(())
+    }
+}
+
+/// First line of `content`, cut to 60 characters.
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("");
+    let mut preview: String = line.chars().take(60).collect();
+    if preview.len() < content.len() {
+        preview.push_str("...");
+    }
+    format!("{:?}", preview)
+}
+
+/// Create initial directives for a new Winter instance.
+async fn create_initial_directives(client: &AtprotoClient, seed: &IdentitySeed) -> Result<()> {
+    for directive in seed.directives(Utc::now()) {
         let rkey = Tid::now().to_string();
         client
             .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
-        info!(interest = %interest, "created interest directive");
+        info!(kind = %directive.kind, "created directive");
     }
 
     Ok(())
 }
 
-/// Create default datalog rules.
-async fn create_default_rules(client: &AtprotoClient) -> Result<()> {
+/// Default datalog rules created by bootstrap.
+fn default_rules() -> Vec<Rule> {
     // Note: All predicates now have rkey as their last argument.
     // Use _ to ignore rkey when not needed.
-    let default_rules = vec![
+    vec![
         Rule {
             name: "mutual_follow".to_string(),
             description: "Two accounts that follow each other".to_string(),
@@ -233,9 +499,12 @@ async fn create_default_rules(client: &AtprotoClient) -> Result<()> {
             args: Vec::new(),
             created_at: Utc::now(),
         },
-    ];
+    ]
+}
 
-    for rule in default_rules {
+/// Create default datalog rules.
+async fn create_default_rules(client: &AtprotoClient) -> Result<()> {
+    for rule in default_rules() {
         let rkey = Tid::now().to_string();
 
         // Check if a rule with this name already exists
@@ -299,6 +568,7 @@ async fn initialize_state(
         notification_cursor: cursor.clone(),
         dm_cursor: None,
         followers: Vec::new(),
+        followers_synced_at: None,
         created_at: now,
         last_updated: now,
     };
@@ -311,3 +581,200 @@ async fn initialize_state(
     info!(cursor = ?cursor, "created state record with notification cursor");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directive(kind: DirectiveKind, content: &str) -> Directive {
+        bootstrap_directive(kind, content.to_string(), Utc::now())
+    }
+
+    fn identity(operator_did: &str) -> Identity {
+        Identity {
+            operator_did: operator_did.to_string(),
+            created_at: Utc::now(),
+            last_updated: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_drift_identifies_new_changed_and_extra_directives() {
+        let planned = [
+            directive(DirectiveKind::SelfConcept, "I am new."),
+            directive(DirectiveKind::Value, "honesty"),
+            directive(DirectiveKind::Value, "patience"),
+        ];
+        let existing = [
+            directive(DirectiveKind::SelfConcept, "I am old."),
+            directive(DirectiveKind::Value, "honesty"),
+            directive(DirectiveKind::Interest, "lichens"),
+            directive(DirectiveKind::Aspiration, "write more"),
+        ];
+
+        let report = DriftReport::new(
+            "did:plc:op",
+            Some(&identity("did:plc:op")),
+            &planned,
+            &existing,
+            &default_rules(),
+        );
+
+        assert_eq!(report.identity, IdentityDrift::Unchanged);
+        assert_eq!(
+            report.new_directives,
+            [(DirectiveKind::Value, "patience".to_string())]
+        );
+        assert_eq!(
+            report.changed_directives,
+            [DirectiveChange {
+                kind: DirectiveKind::SelfConcept,
+                from: "I am old.".to_string(),
+                to: "I am new.".to_string(),
+            }]
+        );
+        assert_eq!(
+            report.extra_directives,
+            [(DirectiveKind::Interest, "lichens".to_string())]
+        );
+        assert!(report.new_rules.is_empty());
+    }
+
+    #[test]
+    fn test_drift_ignores_inactive_directives() {
+        let mut retired = directive(DirectiveKind::Value, "honesty");
+        retired.active = false;
+
+        let report = DriftReport::new(
+            "did:plc:op",
+            Some(&identity("did:plc:op")),
+            &[directive(DirectiveKind::Value, "honesty")],
+            &[retired],
+            &default_rules(),
+        );
+
+        assert_eq!(
+            report.new_directives,
+            [(DirectiveKind::Value, "honesty".to_string())]
+        );
+        assert!(report.extra_directives.is_empty());
+    }
+
+    #[test]
+    fn test_drift_on_fresh_pds() {
+        let planned = [directive(DirectiveKind::SelfConcept, "I am new.")];
+
+        let report = DriftReport::new("did:plc:op", None, &planned, &[], &[]);
+
+        assert_eq!(report.identity, IdentityDrift::Missing);
+        assert_eq!(report.new_directives.len(), 1);
+        assert_eq!(
+            report.new_rules,
+            ["mutual_follow", "shared_interest", "potential_conversation"]
+        );
+    }
+
+    #[test]
+    fn test_no_drift_when_pds_matches() {
+        let planned = [directive(DirectiveKind::SelfConcept, "I am.")];
+
+        let report = DriftReport::new(
+            "did:plc:op",
+            Some(&identity("did:plc:other")),
+            &planned,
+            &planned,
+            &default_rules(),
+        );
+        assert_eq!(
+            report.identity,
+            IdentityDrift::OperatorChanged {
+                from: "did:plc:other".to_string(),
+                to: "did:plc:op".to_string(),
+            }
+        );
+        assert!(!report.is_empty());
+
+        let report = DriftReport::new(
+            "did:plc:op",
+            Some(&identity("did:plc:op")),
+            &planned,
+            &planned,
+            &default_rules(),
+        );
+        assert!(report.is_empty());
+        assert!(report.to_string().starts_with("No drift"));
+    }
+
+    #[test]
+    fn test_identity_file_creates_each_directive_kind() {
+        let seed = IdentitySeed::parse(
+            r#"
+self_description: I am a test instance.
+values:
+  - honesty
+  - patience
+interests: [lichens]
+beliefs: [small things matter]
+guidelines: [ask before assuming]
+boundaries: [no impersonation]
+"#,
+        )
+        .unwrap();
+
+        let directives: Vec<_> = seed
+            .directives(Utc::now())
+            .into_iter()
+            .map(|d| (d.kind, d.content))
+            .collect();
+        assert_eq!(
+            directives,
+            [
+                (
+                    DirectiveKind::SelfConcept,
+                    "I am a test instance.".to_string()
+                ),
+                (DirectiveKind::Value, "honesty".to_string()),
+                (DirectiveKind::Value, "patience".to_string()),
+                (DirectiveKind::Interest, "lichens".to_string()),
+                (DirectiveKind::Belief, "small things matter".to_string()),
+                (DirectiveKind::Guideline, "ask before assuming".to_string()),
+                (DirectiveKind::Boundary, "no impersonation".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identity_file_omitted_sections_create_nothing() {
+        let seed = IdentitySeed::parse("values: [honesty]\n").unwrap();
+
+        let directives = seed.directives(Utc::now());
+        assert_eq!(directives.len(), 2);
+        assert_eq!(directives[0].kind, DirectiveKind::SelfConcept);
+        assert_eq!(directives[0].content, DEFAULT_SELF_DESCRIPTION);
+        assert!(
+            directives
+                .iter()
+                .all(|d| d.source.as_deref() == Some("bootstrap"))
+        );
+    }
+
+    #[test]
+    fn test_identity_file_rejects_unknown_sections() {
+        assert!(IdentitySeed::parse("valeus: [honesty]\n").is_err());
+    }
+
+    #[test]
+    fn test_example_json_seed_loads() {
+        let seed =
+            IdentitySeed::parse(include_str!("../../../examples/identity_seed.json")).unwrap();
+        assert_eq!(seed.values.len(), 5);
+        assert_eq!(seed.interests.len(), 5);
+    }
+
+    #[test]
+    fn test_flags_fall_back_to_defaults() {
+        let seed = IdentitySeed::from_flags(None, Some("a, b".to_string()), None);
+        assert_eq!(seed.values, DEFAULT_VALUES);
+        assert_eq!(seed.interests, ["a", "b"]);
+    }
+}
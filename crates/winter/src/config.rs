@@ -0,0 +1,856 @@
+//! Layered configuration for the `winter` binary.
+//!
+//! Settings come from three layers, each overriding the one before it: a
+//! `winter.toml` file, `WINTER_*` environment variables, and command-line
+//! flags. Every layer is a [`WinterConfig`] whose fields are all optional;
+//! [`WinterConfig::overlay`] stacks them, and built-in defaults fill whatever
+//! is still unset.
+//!
+//! ```toml
+//! secrets_path = "/etc/winter/secrets.json"
+//! secret_providers = ["env", "file"]
+//!
+//! [account]
+//! pds_url = "https://pds.example.com"
+//! handle = "winter.example.com"
+//! app_password = "xxxx-xxxx-xxxx-xxxx"
+//!
+//! [daemon]
+//! max_poll_interval = 120
+//!
+//! [mcp]
+//! port = 3847
+//! metrics = true
+//! ```
+
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+use winter_agent::ThinkingCapture;
+use winter_mcp::secrets::SECRET_ENV_PREFIX;
+use winter_mcp::thought_queue::OverflowPolicy;
+use winter_mcp::{SecretSource, ToolSettings};
+
+use crate::awaken::AwakenGoalStrategy;
+
+/// Config file read from the working directory when `--config` isn't given.
+pub const DEFAULT_CONFIG_FILE: &str = "winter.toml";
+
+/// Default notification poll interval in seconds.
+pub const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+/// Default follower sync interval in seconds.
+pub const DEFAULT_FOLLOWER_SYNC_INTERVAL: u64 = 86400;
+
+/// Default port for the MCP HTTP server.
+pub const DEFAULT_MCP_PORT: u16 = 3847;
+
+/// Default port for the web UI.
+pub const DEFAULT_WEB_PORT: u16 = 8080;
+
+/// Errors loading or resolving configuration.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("invalid value for {var}: {message}")]
+    Env { var: &'static str, message: String },
+
+    #[error("missing {key}: pass --{flag}, set {env} or add it to {DEFAULT_CONFIG_FILE}")]
+    Missing {
+        key: &'static str,
+        flag: &'static str,
+        env: &'static str,
+    },
+}
+
+/// Parse boolean from environment variable, accepting common truthy values.
+/// Accepts "1", "true", "yes", "on" (case-insensitive) as true.
+/// Accepts "0", "false", "no", "off", "" (case-insensitive) as false.
+pub fn parse_bool_env(s: &str) -> Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => Err(format!(
+            "invalid boolean value '{}', expected 1/true/yes/on or 0/false/no/off",
+            s
+        )),
+    }
+}
+
+/// All settings, from one layer or several overlaid.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WinterConfig {
+    /// Path to the secrets JSON file for custom tools.
+    pub secrets_path: Option<PathBuf>,
+    /// Where custom tools get secrets, first match winning (default: file).
+    pub secret_providers: Option<Vec<SecretSource>>,
+    /// DID of the human operator, used by `bootstrap` and to look up custom
+    /// tool approvals.
+    pub operator_did: Option<String>,
+    /// Winter's workspace: where the Claude CLI runs, and the one directory
+    /// custom tools and image uploads may use.
+    pub workspace: Option<PathBuf>,
+    /// Public URL of the web UI, linked from tool-call thoughts and operator DMs.
+    pub web_url: Option<String>,
+    /// Hard cap on `query_facts` results per call. (default 1000)
+    pub query_max_results: Option<usize>,
+    pub account: AccountConfig,
+    pub daemon: DaemonConfig,
+    pub mcp: McpConfig,
+    pub web: WebConfig,
+}
+
+/// PDS account credentials, shared by every subcommand that talks to the PDS.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, clap::Args)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountConfig {
+    /// PDS URL
+    #[arg(long)]
+    pub pds_url: Option<String>,
+
+    /// Account handle
+    #[arg(long)]
+    pub handle: Option<String>,
+
+    /// App password
+    #[arg(long)]
+    pub app_password: Option<String>,
+}
+
+/// Resolved PDS account credentials.
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub pds_url: String,
+    pub handle: String,
+    pub app_password: String,
+}
+
+/// Settings for `winter daemon`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, clap::Args)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Notification poll interval in seconds; the minimum when backing off
+    /// (default 5)
+    #[arg(long, visible_alias = "min-poll-interval")]
+    pub poll_interval: Option<u64>,
+
+    /// Longest notification poll interval in seconds, reached by backing
+    /// off after consecutive empty polls. Set equal to --poll-interval to
+    /// poll at a fixed rate. (default 60)
+    #[arg(long)]
+    pub max_poll_interval: Option<u64>,
+
+    /// Follower sync interval in seconds (for populating is_followed_by predicate).
+    /// New followers are detected immediately via Follow notifications.
+    /// This full sync is mainly for reconciliation (catching unfollows). (default 86400)
+    #[arg(long)]
+    pub follower_sync_interval: Option<u64>,
+
+    /// Fast-forward notification and DM cursors to current time on startup.
+    /// This skips all existing notifications/DMs and only processes new ones.
+    /// Accepts "1", "true", "yes", or "on".
+    #[arg(long, value_parser = parse_bool_env)]
+    pub fast_forward: Option<bool>,
+
+    /// Interrupt the session when more than this many inbox items are pending
+    #[arg(long)]
+    pub queue_high_watermark: Option<usize>,
+
+    /// Clear the queue-pressure interrupt once fewer than this many items are pending
+    #[arg(long)]
+    pub queue_low_watermark: Option<usize>,
+
+    /// Interrupt the session once it has cost this many USD (unset: no cap)
+    #[arg(long)]
+    pub session_max_cost_usd: Option<f64>,
+
+    /// Interrupt the session once it has taken this many turns (unset: no cap)
+    #[arg(long)]
+    pub session_max_turns: Option<u64>,
+
+    /// Record Winter's extended thinking as reflection thoughts: off, full,
+    /// or redacted to a placeholder. (default off)
+    #[arg(long)]
+    #[serde(deserialize_with = "from_str_opt")]
+    pub record_thinking: Option<ThinkingCapture>,
+
+    /// How each awakening picks one of Winter's goal directives: round-robin
+    /// or weighted (by priority). (default round-robin)
+    #[arg(long)]
+    #[serde(deserialize_with = "from_str_opt")]
+    pub awaken_goal_strategy: Option<AwakenGoalStrategy>,
+
+    /// Skip an awakening that comes within this many seconds of Winter's
+    /// last tool call, leaving it to the job's next interval. (default 0:
+    /// never skip)
+    #[arg(long)]
+    pub awaken_quiet_secs: Option<u64>,
+
+    /// DM poll interval in seconds (default: --poll-interval)
+    #[arg(long)]
+    pub dm_poll_interval: Option<u64>,
+
+    /// Notification poll interval in seconds, overriding --poll-interval
+    /// for notifications only
+    #[arg(long)]
+    pub notif_poll_interval: Option<u64>,
+
+    /// Trigger evaluation interval in seconds (default 300)
+    #[arg(long)]
+    pub trigger_interval: Option<u64>,
+
+    /// Seconds without a tool call before the watchdog interrupts a stalled
+    /// session (default 300)
+    #[arg(long)]
+    pub stall_timeout: Option<u64>,
+
+    /// URL of the MCP HTTP server's /mcp endpoint. Set, the daemon and the
+    /// Claude CLI use HTTP (mcp-http.json) instead of a stdio server.
+    #[arg(long)]
+    pub mcp_url: Option<String>,
+}
+
+/// Settings for `winter mcp-server-http`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, clap::Args)]
+#[serde(default, deny_unknown_fields)]
+pub struct McpConfig {
+    /// HTTP server port (default 3847)
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Expose Prometheus metrics at /metrics.
+    #[arg(long, value_parser = parse_bool_env)]
+    pub metrics: Option<bool>,
+
+    /// Bearer token required on the JSON-RPC and daemon endpoints.
+    #[arg(long, env = "WINTER_MCP_TOKEN", hide_env_values = true)]
+    pub auth_token: Option<String>,
+
+    /// Bind to all interfaces instead of localhost only (requires an auth token).
+    #[arg(long, value_parser = parse_bool_env)]
+    pub bind_public: Option<bool>,
+
+    /// Seconds without a firehose event before /readyz reports degraded.
+    #[arg(long)]
+    pub firehose_stale_secs: Option<u64>,
+
+    /// What to do with tool-call thoughts when the write queue is full:
+    /// drop-oldest, drop-newest, block or block:<ms>. (default drop-newest)
+    #[arg(long)]
+    #[serde(deserialize_with = "from_str_opt")]
+    pub thought_overflow: Option<OverflowPolicy>,
+
+    /// Thoughts written per applyWrites batch (1 disables batching).
+    #[arg(long)]
+    pub thought_batch_size: Option<usize>,
+
+    /// Bytes of thought content kept before truncating. (default 32000)
+    #[arg(long)]
+    pub thought_max_bytes: Option<usize>,
+
+    /// File to persist pending inbox items to, so they survive restarts.
+    #[arg(long)]
+    pub inbox_path: Option<PathBuf>,
+}
+
+/// Settings for `winter web`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, clap::Args)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebConfig {
+    /// Web server port (default 8080)
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Static files directory
+    #[arg(long)]
+    pub static_dir: Option<String>,
+}
+
+impl WinterConfig {
+    /// Load the config file (if any) with environment variables on top.
+    ///
+    /// An explicit `path` must exist; without one, [`DEFAULT_CONFIG_FILE`] is
+    /// read from the working directory only if present.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let file = match Self::config_file(path) {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        Ok(file.overlay(Self::from_env()?))
+    }
+
+    /// The config file [`WinterConfig::load`] reads for `path`, if any.
+    pub fn config_file(path: Option<&Path>) -> Option<PathBuf> {
+        match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists()),
+        }
+    }
+
+    /// Parse a TOML config file.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Read the `WINTER_*` environment variables.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_env_with(|var| std::env::var(var).ok())
+    }
+
+    /// Read settings through `lookup`, which maps a variable name to its value.
+    /// Empty values count as unset.
+    pub fn from_env_with(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let get = |var: &str| lookup(var).filter(|v| !v.is_empty());
+
+        Ok(Self {
+            secrets_path: get("WINTER_SECRETS_PATH").map(PathBuf::from),
+            secret_providers: env_parse(
+                &get,
+                "WINTER_SECRETS_PROVIDERS",
+                SecretSource::parse_list,
+            )?,
+            operator_did: get("WINTER_OPERATOR_DID"),
+            workspace: get("WINTER_WORKSPACE").map(PathBuf::from),
+            web_url: get("WINTER_WEB_URL"),
+            query_max_results: env_parse(&get, "WINTER_QUERY_MAX_RESULTS", parse_from_str)?,
+            account: AccountConfig {
+                pds_url: get("WINTER_PDS_URL"),
+                handle: get("WINTER_HANDLE"),
+                app_password: get("WINTER_APP_PASSWORD"),
+            },
+            daemon: DaemonConfig {
+                poll_interval: None,
+                max_poll_interval: env_parse(&get, "WINTER_MAX_POLL_INTERVAL", parse_from_str)?,
+                follower_sync_interval: None,
+                fast_forward: env_parse(&get, "WINTER_FAST_FORWARD", parse_bool_env)?,
+                queue_high_watermark: env_parse(
+                    &get,
+                    "WINTER_QUEUE_HIGH_WATERMARK",
+                    parse_from_str,
+                )?,
+                queue_low_watermark: env_parse(&get, "WINTER_QUEUE_LOW_WATERMARK", parse_from_str)?,
+                session_max_cost_usd: env_parse(
+                    &get,
+                    "WINTER_SESSION_MAX_COST_USD",
+                    parse_from_str,
+                )?,
+                session_max_turns: env_parse(&get, "WINTER_SESSION_MAX_TURNS", parse_from_str)?,
+                record_thinking: env_parse(&get, "WINTER_RECORD_THINKING", parse_from_str)?,
+                awaken_goal_strategy: env_parse(
+                    &get,
+                    "WINTER_AWAKEN_GOAL_STRATEGY",
+                    parse_from_str,
+                )?,
+                awaken_quiet_secs: env_parse(&get, "WINTER_AWAKEN_QUIET_SECS", parse_from_str)?,
+                dm_poll_interval: env_parse(&get, "WINTER_DM_POLL_INTERVAL", parse_from_str)?,
+                notif_poll_interval: env_parse(&get, "WINTER_NOTIF_POLL_INTERVAL", parse_from_str)?,
+                trigger_interval: env_parse(&get, "WINTER_TRIGGER_INTERVAL", parse_from_str)?,
+                stall_timeout: env_parse(&get, "WINTER_STALL_TIMEOUT", parse_from_str)?,
+                mcp_url: get("WINTER_MCP_URL"),
+            },
+            mcp: McpConfig {
+                port: None,
+                metrics: env_parse(&get, "WINTER_MCP_METRICS", parse_bool_env)?,
+                auth_token: get("WINTER_MCP_TOKEN"),
+                bind_public: env_parse(&get, "WINTER_MCP_BIND_PUBLIC", parse_bool_env)?,
+                firehose_stale_secs: env_parse(&get, "WINTER_FIREHOSE_STALE_SECS", parse_from_str)?,
+                thought_overflow: env_parse(&get, "WINTER_THOUGHT_OVERFLOW", parse_from_str)?,
+                thought_batch_size: env_parse(&get, "WINTER_THOUGHT_BATCH_SIZE", parse_from_str)?,
+                thought_max_bytes: env_parse(&get, "WINTER_THOUGHT_MAX_BYTES", parse_from_str)?,
+                inbox_path: get("WINTER_INBOX_PATH").map(PathBuf::from),
+            },
+            web: WebConfig::default(),
+        })
+    }
+
+    /// Names of every variable [`WinterConfig::from_env_with`] reads.
+    pub fn env_var_names() -> Vec<String> {
+        let names = RefCell::new(Vec::new());
+        // Nothing is set, so every lookup runs and none can fail to parse
+        let _ = Self::from_env_with(|var| {
+            names.borrow_mut().push(var.to_string());
+            None
+        });
+        names.into_inner()
+    }
+
+    /// Variables a `winter mcp-server` spawned over stdio needs from the
+    /// daemon's environment: everything the config loader and CLI read, plus
+    /// `WINTER_SECRET_*` when the env secret provider is enabled.
+    ///
+    /// `WINTER_CONFIG` is left out; the daemon passes the file it loaded.
+    pub fn mcp_server_env_vars(&self) -> Vec<String> {
+        let mut vars = Self::env_var_names();
+        vars.push("WINTER_LOG_FORMAT".to_string());
+        if self.secret_sources().contains(&SecretSource::Env) {
+            vars.push(format!("{}*", SECRET_ENV_PREFIX));
+        }
+        vars
+    }
+
+    /// Layer `over` on top of `self`: every setting `over` has wins.
+    pub fn overlay(self, over: Self) -> Self {
+        Self {
+            secrets_path: over.secrets_path.or(self.secrets_path),
+            secret_providers: over.secret_providers.or(self.secret_providers),
+            operator_did: over.operator_did.or(self.operator_did),
+            workspace: over.workspace.or(self.workspace),
+            web_url: over.web_url.or(self.web_url),
+            query_max_results: over.query_max_results.or(self.query_max_results),
+            account: self.account.overlay(over.account),
+            daemon: self.daemon.overlay(over.daemon),
+            mcp: self.mcp.overlay(over.mcp),
+            web: self.web.overlay(over.web),
+        }
+    }
+
+    /// Secret providers to layer for custom tools.
+    pub fn secret_sources(&self) -> Vec<SecretSource> {
+        self.secret_providers
+            .clone()
+            .unwrap_or_else(|| vec![SecretSource::File])
+    }
+
+    /// Settings the MCP server's tools read.
+    pub fn tool_settings(&self) -> ToolSettings {
+        ToolSettings {
+            web_url: self.web_url.clone(),
+            operator_did: self.operator_did.clone(),
+            workspace: self.workspace.clone(),
+            query_max_results: self.query_max_results,
+        }
+    }
+
+    /// The operator DID, which `bootstrap` can't run without.
+    pub fn require_operator_did(&self) -> Result<String, ConfigError> {
+        self.operator_did.clone().ok_or(ConfigError::Missing {
+            key: "operator_did",
+            flag: "operator-did",
+            env: "WINTER_OPERATOR_DID",
+        })
+    }
+}
+
+impl AccountConfig {
+    fn overlay(self, over: Self) -> Self {
+        Self {
+            pds_url: over.pds_url.or(self.pds_url),
+            handle: over.handle.or(self.handle),
+            app_password: over.app_password.or(self.app_password),
+        }
+    }
+
+    /// Resolve the credentials, all of which are required.
+    pub fn require(&self) -> Result<Account, ConfigError> {
+        let missing = |key, flag, env| ConfigError::Missing { key, flag, env };
+        Ok(Account {
+            pds_url: self
+                .pds_url
+                .clone()
+                .ok_or_else(|| missing("account.pds_url", "pds-url", "WINTER_PDS_URL"))?,
+            handle: self
+                .handle
+                .clone()
+                .ok_or_else(|| missing("account.handle", "handle", "WINTER_HANDLE"))?,
+            app_password: self.app_password.clone().ok_or_else(|| {
+                missing(
+                    "account.app_password",
+                    "app-password",
+                    "WINTER_APP_PASSWORD",
+                )
+            })?,
+        })
+    }
+}
+
+impl DaemonConfig {
+    fn overlay(self, over: Self) -> Self {
+        Self {
+            poll_interval: over.poll_interval.or(self.poll_interval),
+            max_poll_interval: over.max_poll_interval.or(self.max_poll_interval),
+            follower_sync_interval: over.follower_sync_interval.or(self.follower_sync_interval),
+            fast_forward: over.fast_forward.or(self.fast_forward),
+            queue_high_watermark: over.queue_high_watermark.or(self.queue_high_watermark),
+            queue_low_watermark: over.queue_low_watermark.or(self.queue_low_watermark),
+            session_max_cost_usd: over.session_max_cost_usd.or(self.session_max_cost_usd),
+            session_max_turns: over.session_max_turns.or(self.session_max_turns),
+            record_thinking: over.record_thinking.or(self.record_thinking),
+            awaken_goal_strategy: over.awaken_goal_strategy.or(self.awaken_goal_strategy),
+            awaken_quiet_secs: over.awaken_quiet_secs.or(self.awaken_quiet_secs),
+            dm_poll_interval: over.dm_poll_interval.or(self.dm_poll_interval),
+            notif_poll_interval: over.notif_poll_interval.or(self.notif_poll_interval),
+            trigger_interval: over.trigger_interval.or(self.trigger_interval),
+            stall_timeout: over.stall_timeout.or(self.stall_timeout),
+            mcp_url: over.mcp_url.or(self.mcp_url),
+        }
+    }
+}
+
+impl McpConfig {
+    fn overlay(self, over: Self) -> Self {
+        Self {
+            port: over.port.or(self.port),
+            metrics: over.metrics.or(self.metrics),
+            auth_token: over.auth_token.or(self.auth_token),
+            bind_public: over.bind_public.or(self.bind_public),
+            firehose_stale_secs: over.firehose_stale_secs.or(self.firehose_stale_secs),
+            thought_overflow: over.thought_overflow.or(self.thought_overflow),
+            thought_batch_size: over.thought_batch_size.or(self.thought_batch_size),
+            thought_max_bytes: over.thought_max_bytes.or(self.thought_max_bytes),
+            inbox_path: over.inbox_path.or(self.inbox_path),
+        }
+    }
+}
+
+impl WebConfig {
+    fn overlay(self, over: Self) -> Self {
+        Self {
+            port: over.port.or(self.port),
+            static_dir: over.static_dir.or(self.static_dir),
+        }
+    }
+}
+
+/// Parse environment variable `var`, if set, naming it in any error.
+fn env_parse<T>(
+    get: &impl Fn(&str) -> Option<String>,
+    var: &'static str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, ConfigError> {
+    get(var)
+        .map(|v| parse(&v).map_err(|message| ConfigError::Env { var, message }))
+        .transpose()
+}
+
+fn parse_from_str<T: FromStr>(s: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    s.parse().map_err(|e: T::Err| e.to_string())
+}
+
+/// Deserialize an optional value through its `FromStr` impl.
+fn from_str_opt<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        account: AccountConfig,
+        #[command(flatten)]
+        daemon: DaemonConfig,
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |var| vars.get(var).cloned()
+    }
+
+    fn cli(args: &[&str]) -> WinterConfig {
+        let cli =
+            TestCli::try_parse_from(std::iter::once("winter").chain(args.iter().copied())).unwrap();
+        WinterConfig {
+            account: cli.account,
+            daemon: cli.daemon,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parses_complete_config_file() {
+        let config: WinterConfig = toml::from_str(
+            r#"
+            secrets_path = "/etc/winter/secrets.json"
+            secret_providers = ["env", "file"]
+            operator_did = "did:plc:operator"
+            workspace = "/home/winter/workspace"
+            web_url = "https://winter.example.com"
+            query_max_results = 500
+
+            [account]
+            pds_url = "https://pds.example.com"
+            handle = "winter.example.com"
+            app_password = "xxxx-xxxx"
+
+            [daemon]
+            poll_interval = 10
+            max_poll_interval = 120
+            follower_sync_interval = 3600
+            fast_forward = true
+            queue_high_watermark = 50
+            queue_low_watermark = 10
+            session_max_cost_usd = 2.5
+            session_max_turns = 40
+            record_thinking = "redacted"
+            awaken_goal_strategy = "weighted"
+            awaken_quiet_secs = 900
+            dm_poll_interval = 15
+            notif_poll_interval = 20
+            trigger_interval = 120
+            stall_timeout = 600
+            mcp_url = "http://mcp-server:3847/mcp"
+
+            [mcp]
+            port = 4000
+            metrics = true
+            auth_token = "secret"
+            bind_public = true
+            firehose_stale_secs = 300
+            thought_overflow = "block:250"
+            thought_batch_size = 8
+            thought_max_bytes = 16000
+            inbox_path = "/var/lib/winter/inbox.json"
+
+            [web]
+            port = 9090
+            static_dir = "/srv/winter"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            WinterConfig {
+                secrets_path: Some(PathBuf::from("/etc/winter/secrets.json")),
+                secret_providers: Some(vec![SecretSource::Env, SecretSource::File]),
+                operator_did: Some("did:plc:operator".to_string()),
+                workspace: Some(PathBuf::from("/home/winter/workspace")),
+                web_url: Some("https://winter.example.com".to_string()),
+                query_max_results: Some(500),
+                account: AccountConfig {
+                    pds_url: Some("https://pds.example.com".to_string()),
+                    handle: Some("winter.example.com".to_string()),
+                    app_password: Some("xxxx-xxxx".to_string()),
+                },
+                daemon: DaemonConfig {
+                    poll_interval: Some(10),
+                    max_poll_interval: Some(120),
+                    follower_sync_interval: Some(3600),
+                    fast_forward: Some(true),
+                    queue_high_watermark: Some(50),
+                    queue_low_watermark: Some(10),
+                    session_max_cost_usd: Some(2.5),
+                    session_max_turns: Some(40),
+                    record_thinking: Some(ThinkingCapture::Redacted),
+                    awaken_goal_strategy: Some(AwakenGoalStrategy::Weighted),
+                    awaken_quiet_secs: Some(900),
+                    dm_poll_interval: Some(15),
+                    notif_poll_interval: Some(20),
+                    trigger_interval: Some(120),
+                    stall_timeout: Some(600),
+                    mcp_url: Some("http://mcp-server:3847/mcp".to_string()),
+                },
+                mcp: McpConfig {
+                    port: Some(4000),
+                    metrics: Some(true),
+                    auth_token: Some("secret".to_string()),
+                    bind_public: Some(true),
+                    firehose_stale_secs: Some(300),
+                    thought_overflow: Some(OverflowPolicy::Block {
+                        timeout: Duration::from_millis(250),
+                    }),
+                    thought_batch_size: Some(8),
+                    thought_max_bytes: Some(16000),
+                    inbox_path: Some(PathBuf::from("/var/lib/winter/inbox.json")),
+                },
+                web: WebConfig {
+                    port: Some(9090),
+                    static_dir: Some("/srv/winter".to_string()),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        let err = toml::from_str::<WinterConfig>("[daemon]\npoll_intervl = 5\n").unwrap_err();
+        assert!(err.to_string().contains("poll_intervl"));
+    }
+
+    #[test]
+    fn test_empty_file_is_all_unset() {
+        let config = toml::from_str::<WinterConfig>("").unwrap();
+        assert_eq!(config, WinterConfig::default());
+        assert_eq!(config.secret_sources(), vec![SecretSource::File]);
+    }
+
+    #[test]
+    fn test_precedence_cli_over_env_over_file() {
+        let file: WinterConfig = toml::from_str(
+            r#"
+            [account]
+            pds_url = "https://file.example.com"
+            handle = "file.example.com"
+            app_password = "file-password"
+
+            [daemon]
+            poll_interval = 10
+            max_poll_interval = 100
+            "#,
+        )
+        .unwrap();
+        let env = WinterConfig::from_env_with(env(&[
+            ("WINTER_HANDLE", "env.example.com"),
+            ("WINTER_APP_PASSWORD", "env-password"),
+            ("WINTER_MAX_POLL_INTERVAL", "200"),
+        ]))
+        .unwrap();
+        let cli = cli(&["--app-password", "cli-password", "--poll-interval", "3"]);
+
+        let config = file.overlay(env).overlay(cli);
+        let account = config.account.require().unwrap();
+        assert_eq!(account.pds_url, "https://file.example.com");
+        assert_eq!(account.handle, "env.example.com");
+        assert_eq!(account.app_password, "cli-password");
+        assert_eq!(config.daemon.poll_interval, Some(3));
+        assert_eq!(config.daemon.max_poll_interval, Some(200));
+        assert_eq!(config.daemon.follower_sync_interval, None);
+    }
+
+    #[test]
+    fn test_env_values_are_parsed() {
+        let config = WinterConfig::from_env_with(env(&[
+            ("WINTER_FAST_FORWARD", "yes"),
+            ("WINTER_SESSION_MAX_COST_USD", "1.5"),
+            ("WINTER_THOUGHT_OVERFLOW", "drop-oldest"),
+            ("WINTER_THOUGHT_MAX_BYTES", "64000"),
+            ("WINTER_RECORD_THINKING", "full"),
+            ("WINTER_AWAKEN_GOAL_STRATEGY", "round-robin"),
+            ("WINTER_SECRETS_PATH", ""),
+            ("WINTER_SECRETS_PROVIDERS", "env,file"),
+            ("WINTER_STALL_TIMEOUT", "900"),
+            ("WINTER_MCP_URL", "http://mcp-server:3847/mcp"),
+            ("WINTER_WORKSPACE", "/home/winter/workspace"),
+            ("WINTER_QUERY_MAX_RESULTS", "250"),
+        ]))
+        .unwrap();
+        assert_eq!(config.daemon.fast_forward, Some(true));
+        assert_eq!(config.daemon.session_max_cost_usd, Some(1.5));
+        assert_eq!(
+            config.mcp.thought_overflow,
+            Some(OverflowPolicy::DropOldest)
+        );
+        assert_eq!(config.mcp.thought_max_bytes, Some(64000));
+        assert_eq!(config.daemon.record_thinking, Some(ThinkingCapture::Full));
+        assert_eq!(
+            config.daemon.awaken_goal_strategy,
+            Some(AwakenGoalStrategy::RoundRobin)
+        );
+        assert_eq!(config.daemon.stall_timeout, Some(900));
+        assert_eq!(
+            config.daemon.mcp_url.as_deref(),
+            Some("http://mcp-server:3847/mcp")
+        );
+        assert_eq!(
+            config.workspace,
+            Some(PathBuf::from("/home/winter/workspace"))
+        );
+        assert_eq!(config.query_max_results, Some(250));
+        assert_eq!(config.secrets_path, None);
+        assert_eq!(
+            config.secret_sources(),
+            vec![SecretSource::Env, SecretSource::File]
+        );
+    }
+
+    #[test]
+    fn test_stdio_mcp_scope_covers_config_env_vars() {
+        let names = WinterConfig::env_var_names();
+        assert!(names.iter().any(|name| name == "WINTER_APP_PASSWORD"));
+        assert!(names.iter().any(|name| name == "WINTER_INBOX_PATH"));
+
+        let config = WinterConfig::default();
+        let scope = winter_agent::Agent::new("mcp.json")
+            .with_mcp_server_env_vars(config.mcp_server_env_vars())
+            .env_scope();
+        for name in &names {
+            assert!(scope.allows(name), "{} not passed to the MCP server", name);
+        }
+        assert!(scope.allows("WINTER_LOG_FORMAT"));
+        assert!(!scope.allows("WINTER_SECRET_API_KEY"));
+
+        let config = WinterConfig {
+            secret_providers: Some(vec![SecretSource::Env]),
+            ..Default::default()
+        };
+        let scope = winter_agent::Agent::new("mcp.json")
+            .with_mcp_server_env_vars(config.mcp_server_env_vars())
+            .env_scope();
+        assert!(scope.allows("WINTER_SECRET_API_KEY"));
+    }
+
+    #[test]
+    fn test_invalid_env_value_names_variable() {
+        let err = WinterConfig::from_env_with(env(&[("WINTER_QUEUE_HIGH_WATERMARK", "lots")]))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid value for WINTER_QUEUE_HIGH_WATERMARK:")
+        );
+    }
+
+    #[test]
+    fn test_missing_account_setting() {
+        let err = cli(&["--pds-url", "https://pds.example.com"])
+            .account
+            .require()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing account.handle: pass --handle, set WINTER_HANDLE or add it to winter.toml"
+        );
+    }
+
+    #[test]
+    fn test_load_reads_explicit_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.toml");
+        std::fs::write(&path, "[web]\nport = 9000\n").unwrap();
+        assert_eq!(WinterConfig::from_file(&path).unwrap().web.port, Some(9000));
+        assert!(matches!(
+            WinterConfig::from_file(&dir.path().join("missing.toml")),
+            Err(ConfigError::Read { .. })
+        ));
+    }
+}
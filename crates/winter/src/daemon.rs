@@ -3,7 +3,8 @@
 //! The daemon uses a persistent session architecture:
 //! - Single persistent Claude Code session polling an inbox for work
 //! - Dedicated DM poller (pushes to inbox at priority 200 for operator, 150 for others)
-//! - Notification poller (pushes to inbox at priority 100)
+//! - Notification poller (pushes to inbox at priority 100; likes and reposts are
+//!   coalesced per post and pushed at priority 25)
 //! - Scheduler (pushes jobs to inbox at priority 50)
 //! - Watchdog for detecting stuck sessions
 
@@ -13,12 +14,12 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use miette::Result;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing::{debug, error, info, warn};
 
 use winter_agent::{
-    Agent, AgentContext, ContextTrigger, ConversationHistoryMessage, IdentityManager,
-    StateManager,
+    Agent, AgentContext, ContextTrigger, ConversationHistoryMessage, IdentityManager, StateManager,
+    ThinkingCapture,
 };
 use winter_atproto::{
     AtprotoClient, DIRECTIVE_COLLECTION, Directive, OperatorEvent, RULE_COLLECTION, RepoCache,
@@ -29,15 +30,55 @@ use winter_mcp::{
     BlueskyClient, BlueskyError, InboxConversationHistoryMessage, InboxItem, InboxPostRef,
     InterruptionState,
 };
-use winter_mcp::bluesky::NotificationReason;
+use winter_mcp::bluesky::{BlueskyNotification, NotificationReason};
+use winter_mcp::tools::tombstones;
+use winter_mcp::tools::triggers::TriggerEvent;
 use winter_scheduler::Scheduler;
 
+use crate::awaken::{
+    AWAKEN_JOB_NAME, AwakenGoalStrategy, GoalSelector, in_quiet_period, with_goal,
+};
+
 /// Default DM poll interval in seconds.
 const DEFAULT_DM_POLL_INTERVAL: u64 = 5;
 
 /// Default notification poll interval in seconds.
 const DEFAULT_NOTIF_POLL_INTERVAL: u64 = 10;
 
+/// Default upper bound in seconds for the idle notification poll backoff.
+pub const DEFAULT_NOTIF_POLL_MAX_INTERVAL: u64 = 60;
+
+/// Default seconds without a tool call before a session counts as stalled.
+const DEFAULT_STALL_TIMEOUT: u64 = 300;
+
+/// Default trigger evaluation interval in seconds.
+const DEFAULT_TRIGGER_INTERVAL: u64 = 300;
+
+/// How long likes and reposts on one post are gathered before being pushed
+/// to the inbox as a single item.
+const NOTIFICATION_COALESCE_WINDOW: Duration = Duration::from_secs(300);
+
+/// Interruption reason set by the watchdog when a session stalls.
+const STALLED_INTERRUPT_REASON: &str = "stalled";
+
+/// Interruption reason set while the inbox is above its high-watermark.
+const QUEUE_PRESSURE_INTERRUPT_REASON: &str = "queue_pressure";
+
+/// Interruption reason set when a session reaches its cost or turn cap.
+const BUDGET_INTERRUPT_REASON: &str = "budget";
+
+/// How often soft-deleted facts and notes past retention are purged.
+const TOMBSTONE_PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long daemon tasks get to finish after a shutdown signal.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default inbox depth above which the session is interrupted.
+pub const DEFAULT_QUEUE_HIGH_WATERMARK: usize = 20;
+
+/// Default inbox depth below which queue pressure is relieved.
+pub const DEFAULT_QUEUE_LOW_WATERMARK: usize = 5;
+
 /// Configuration for the daemon.
 pub struct DaemonConfig {
     pub pds_url: String,
@@ -53,7 +94,120 @@ pub struct DaemonConfig {
     /// DM poll interval in seconds (default 5).
     pub dm_poll_interval: Option<u64>,
     /// Notification poll interval in seconds (default 10).
+    ///
+    /// This is the base interval; idle polls back off from it.
     pub notif_poll_interval: Option<u64>,
+    /// Longest notification poll interval in seconds after backing off while
+    /// idle (default 60). Set to the base interval to disable backoff.
+    pub notif_poll_max_interval: Option<u64>,
+    /// Trigger evaluation interval in seconds (default 300).
+    pub trigger_interval: Option<u64>,
+    /// Seconds without a tool call before the watchdog interrupts a stalled
+    /// session (default 300).
+    pub stall_timeout: Option<u64>,
+    /// URL of the MCP HTTP server's `/mcp` endpoint (Docker). Unset, the
+    /// daemon uses the local server's default address.
+    pub mcp_url: Option<String>,
+    /// Winter's workspace, where the Claude CLI runs.
+    pub workspace: Option<PathBuf>,
+    /// Absolute path of the config file the daemon loaded, handed on to a
+    /// stdio MCP server.
+    pub config_file: Option<PathBuf>,
+    /// Variables a stdio MCP server reads from the environment.
+    pub mcp_server_env_vars: Vec<String>,
+    /// Limits the watchdog enforces by interrupting the session.
+    pub watchdog: WatchdogConfig,
+    /// Whether Winter's extended thinking is recorded as thoughts.
+    pub thinking: ThinkingCapture,
+    /// How the `awaken` job is run.
+    pub awaken: AwakenConfig,
+}
+
+/// How the `awaken` job is run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AwakenConfig {
+    /// How the goal directive handed to each awakening is picked.
+    pub goal_strategy: AwakenGoalStrategy,
+    /// Skip an awakening that comes this soon after Winter's last tool call.
+    pub quiet_period: Option<Duration>,
+}
+
+/// Limits the watchdog enforces by interrupting the session.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WatchdogConfig {
+    /// Inbox depth watermarks for queue-pressure interruption.
+    pub queue_pressure: QueuePressureConfig,
+    /// Per-session cost and turn caps.
+    pub budget: SessionBudget,
+}
+
+/// Per-session cost and turn caps.
+///
+/// When a session's cost or turn count reaches a cap, the session is
+/// interrupted with reason `budget` so Winter can wrap up cleanly instead of
+/// being cut off mid-stream. Unset caps are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionBudget {
+    pub max_cost_usd: Option<f64>,
+    pub max_turns: Option<u64>,
+}
+
+/// Inbox depth watermarks for queue-pressure interruption.
+///
+/// When more than `high_watermark` items are pending, the session is
+/// interrupted with reason `queue_pressure` so a long job yields to the
+/// backlog. The interrupt is cleared once fewer than `low_watermark` items
+/// remain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePressureConfig {
+    pub high_watermark: usize,
+    pub low_watermark: usize,
+}
+
+impl Default for QueuePressureConfig {
+    fn default() -> Self {
+        Self {
+            high_watermark: DEFAULT_QUEUE_HIGH_WATERMARK,
+            low_watermark: DEFAULT_QUEUE_LOW_WATERMARK,
+        }
+    }
+}
+
+/// Poll interval that backs off while a source is idle.
+///
+/// Each empty poll doubles the interval up to `max`; the first poll that
+/// returns something snaps it back to `min`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollBackoff {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl PollBackoff {
+    /// Create a backoff starting at `min`. A `max` below `min` disables backoff.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            current: min,
+        }
+    }
+
+    /// Interval to wait before the next poll.
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Record a poll that returned nothing.
+    pub fn record_empty(&mut self) {
+        self.current = (self.current * 2).min(self.max);
+    }
+
+    /// Record a poll that returned new items.
+    pub fn record_activity(&mut self) {
+        self.current = self.min;
+    }
 }
 
 /// Fetch deduplicated rule heads from the PDS or cache.
@@ -182,74 +336,48 @@ async fn fetch_directives(client: &AtprotoClient, cache: Option<&RepoCache>) ->
     }
 }
 
-/// Run the daemon.
-pub async fn run(
-    pds_url: &str,
-    handle: &str,
-    app_password: &str,
-    poll_interval: u64,
-    follower_sync_interval: u64,
-    fast_forward: bool,
-) -> Result<()> {
-    // Use HTTP MCP config when WINTER_MCP_URL is set (Docker environment),
-    // otherwise fall back to stdio config for local development
-    let mcp_config_path = if std::env::var("WINTER_MCP_URL").is_ok() {
-        // Docker: use HTTP transport config
-        dirs::home_dir()
-            .map(|h| h.join(".config/winter/mcp-http.json"))
-            .unwrap_or_else(|| PathBuf::from("/etc/winter/mcp-http.json"))
+/// The Claude CLI's MCP config: HTTP when the daemon talks to an MCP HTTP
+/// server (Docker), otherwise stdio for local development.
+pub fn mcp_config_path(http: bool) -> PathBuf {
+    let (file, fallback) = if http {
+        ("mcp-http.json", "/etc/winter/mcp-http.json")
     } else {
-        // Local: use stdio transport config
-        dirs::home_dir()
-            .map(|h| h.join(".config/winter/mcp.json"))
-            .unwrap_or_else(|| PathBuf::from("/etc/winter/mcp.json"))
+        ("mcp.json", "/etc/winter/mcp.json")
     };
-
-    run_with_config(DaemonConfig {
-        pds_url: pds_url.to_string(),
-        handle: handle.to_string(),
-        app_password: app_password.to_string(),
-        poll_interval,
-        mcp_config_path,
-        follower_sync_interval,
-        fast_forward,
-        dm_poll_interval: None,
-        notif_poll_interval: None,
-    })
-    .await
+    dirs::home_dir()
+        .map(|h| h.join(".config/winter").join(file))
+        .unwrap_or_else(|| PathBuf::from(fallback))
 }
 
 /// Run the daemon with full configuration.
 pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
     info!("starting Winter daemon");
 
-    // Read configuration from env vars with defaults
     // Use specific intervals if provided, fall back to poll_interval, then to defaults
-    let dm_poll_interval = Duration::from_secs(config.dm_poll_interval.unwrap_or_else(|| {
-        std::env::var("WINTER_DM_POLL_INTERVAL")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(if config.poll_interval > 0 {
-                config.poll_interval
-            } else {
-                DEFAULT_DM_POLL_INTERVAL
-            })
-    }));
-    let notif_poll_interval =
-        Duration::from_secs(config.notif_poll_interval.unwrap_or_else(|| {
-            std::env::var("WINTER_NOTIF_POLL_INTERVAL")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(if config.poll_interval > 0 {
-                    config.poll_interval
-                } else {
-                    DEFAULT_NOTIF_POLL_INTERVAL
-                })
-        }));
+    let dm_poll_interval = Duration::from_secs(config.dm_poll_interval.unwrap_or(
+        if config.poll_interval > 0 {
+            config.poll_interval
+        } else {
+            DEFAULT_DM_POLL_INTERVAL
+        },
+    ));
+    let notif_poll_interval = Duration::from_secs(config.notif_poll_interval.unwrap_or(
+        if config.poll_interval > 0 {
+            config.poll_interval
+        } else {
+            DEFAULT_NOTIF_POLL_INTERVAL
+        },
+    ));
+    let notif_poll_max_interval = Duration::from_secs(
+        config
+            .notif_poll_max_interval
+            .unwrap_or(DEFAULT_NOTIF_POLL_MAX_INTERVAL),
+    );
 
     info!(
         dm_poll_interval_secs = dm_poll_interval.as_secs(),
         notif_poll_interval_secs = notif_poll_interval.as_secs(),
+        notif_poll_max_interval_secs = notif_poll_max_interval.as_secs(),
         "daemon configuration"
     );
 
@@ -334,13 +462,8 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
         .map_err(|e| miette::miette!("{}", e))?;
 
     // Build operator event callback for tool approvals → inbox
-    let operator_http_client = Arc::new(reqwest::Client::new());
-    let operator_mcp_base_url = Arc::new(
-        std::env::var("WINTER_MCP_URL")
-            .ok()
-            .and_then(|url| url.strip_suffix("/mcp").map(String::from))
-            .unwrap_or_else(|| "http://127.0.0.1:3847".to_string()),
-    );
+    let operator_http_client = Arc::new(winter_mcp::http::authenticated_client());
+    let operator_mcp_base_url = Arc::new(winter_mcp::http::base_url(config.mcp_url.as_deref()));
     let operator_callback: winter_atproto::OperatorEventCallback = {
         let http_client = Arc::clone(&operator_http_client);
         let mcp_base_url = Arc::clone(&operator_mcp_base_url);
@@ -374,10 +497,10 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
     // Create shutdown channel
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
-    // Handle shutdown signals
+    // Flip the shutdown channel on SIGTERM/SIGINT
     let shutdown_tx_clone = shutdown_tx.clone();
     tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.ok();
+        crate::wait_for_shutdown_signal().await;
         info!("received shutdown signal");
         let _ = shutdown_tx_clone.send(true);
     });
@@ -492,39 +615,41 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
     scheduler.start_update_listener(Arc::clone(&cache));
 
     // Create agent for Claude invocation
-    let agent = Arc::new(Agent::new(&config.mcp_config_path));
+    // Winter's workspace is the only place her built-in file tools should look
+    let mut agent = Agent::new(&config.mcp_config_path)
+        .with_thinking(config.thinking)
+        .with_mcp_url(config.mcp_url.clone())
+        .with_mcp_server_env_vars(config.mcp_server_env_vars.clone())
+        .with_config_file(config.config_file.clone());
+    if let Some(workspace) = &config.workspace {
+        agent = agent.with_working_dir(workspace);
+    }
+    let agent = Arc::new(agent);
 
     // HTTP client and MCP base URL for pushing inbox items to the MCP server
-    let http_client = Arc::new(reqwest::Client::new());
-    let mcp_base_url = Arc::new(
-        std::env::var("WINTER_MCP_URL")
-            .ok()
-            .and_then(|url| url.strip_suffix("/mcp").map(String::from))
-            .unwrap_or_else(|| "http://127.0.0.1:3847".to_string()),
-    );
+    let http_client = Arc::new(winter_mcp::http::authenticated_client());
+    let mcp_base_url = Arc::new(winter_mcp::http::base_url(config.mcp_url.as_deref()));
 
     // Create shared interruption state for background sessions
     let interruption_state = Arc::new(InterruptionState::new());
 
-    // Create job executor that pushes to inbox via HTTP
-    let executor: winter_scheduler::JobExecutor = {
-        let http_client = Arc::clone(&http_client);
-        let mcp_base_url = Arc::clone(&mcp_base_url);
-
-        Box::new(move |job| {
-            let http_client = Arc::clone(&http_client);
-            let mcp_base_url = Arc::clone(&mcp_base_url);
+    // Start time of the running persistent session, if any (read by the watchdog)
+    let session_started_at: Arc<tokio::sync::RwLock<Option<chrono::DateTime<chrono::Utc>>>> =
+        Arc::new(tokio::sync::RwLock::new(None));
 
-            Box::pin(async move {
-                info!(name = %job.name, "scheduling job to inbox");
+    // When Winter last called a tool (set by the watchdog, read by the awaken quiet period)
+    let last_activity_at: Arc<tokio::sync::RwLock<Option<chrono::DateTime<chrono::Utc>>>> =
+        Arc::new(tokio::sync::RwLock::new(None));
 
-                let item = InboxItem::job(job.name.clone(), job.instructions.clone());
-                push_inbox_item(&http_client, &mcp_base_url, item).await;
-
-                Ok(())
-            })
-        })
-    };
+    // Create job executor that pushes to inbox via HTTP
+    let executor = job_executor(
+        Arc::clone(&http_client),
+        Arc::clone(&mcp_base_url),
+        Arc::clone(&client),
+        Arc::clone(&cache),
+        config.awaken,
+        Arc::clone(&last_activity_at),
+    );
 
     // Spawn dedicated DM poller (pushes to inbox via HTTP)
     let dm_handle = {
@@ -616,12 +741,23 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                 }
             }
 
+            // Save the cursor one last time so a restart doesn't replay DMs
+            if let Some(cursor) = dm_bluesky.last_dm_cursor()
+                && let Err(e) = state_manager.set_dm_cursor(Some(cursor.to_string())).await
+            {
+                warn!(error = %e, "failed to persist DM cursor on shutdown");
+            }
+
             info!("DM poller stopped");
         })
     };
 
+    // Notifications are forwarded to the trigger engine for event triggers
+    let (trigger_event_tx, trigger_event_rx) = mpsc::channel::<TriggerEvent>(256);
+
     // Spawn notification poller (pushes to inbox via HTTP)
     let notif_handle = {
+        let trigger_event_tx = trigger_event_tx.clone();
         let state_manager = Arc::clone(&state_manager);
         let datalog_cache = datalog_cache.clone();
         let mut shutdown_rx = shutdown_rx.clone();
@@ -631,8 +767,10 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
 
         tokio::spawn(async move {
             info!("notification poller started");
-            let mut interval = tokio::time::interval(notif_poll_interval);
+            let mut idle_backoff = PollBackoff::new(notif_poll_interval, notif_poll_max_interval);
+            let mut next_poll = tokio::time::Instant::now();
             let mut rate_limit_backoff = Duration::from_secs(0);
+            let mut coalescer = NotificationCoalescer::new(NOTIFICATION_COALESCE_WINDOW);
 
             loop {
                 if rate_limit_backoff > Duration::ZERO {
@@ -652,13 +790,28 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                         }
                     }
 
-                    _ = interval.tick() => {
+                    _ = tokio::time::sleep_until(next_poll) => {
                         match notif_bluesky.get_notifications(Some(50)).await {
                             Ok(notifications) => {
                                 rate_limit_backoff = Duration::ZERO;
+                                if notifications.is_empty() {
+                                    idle_backoff.record_empty();
+                                } else {
+                                    if idle_backoff.current() > notif_poll_interval {
+                                        debug!("notifications resumed, resetting poll interval");
+                                    }
+                                    idle_backoff.record_activity();
+                                }
 
                                 for notif in &notifications {
-                                    // Handle Follow notifications incrementally
+                                    let event = TriggerEvent::from_notification(notif);
+                                    if let Err(e) = trigger_event_tx.try_send(event) {
+                                        debug!(error = %e, "trigger event not queued");
+                                    }
+
+                                    // Follows only update the datalog follower set here.
+                                    // The inbox learns of them once, through the
+                                    // followers_changed item from the next follower sync
                                     if notif.reason == NotificationReason::Follow {
                                         if let Some(ref dc) = datalog_cache
                                             && dc.add_follower(notif.author_did.clone()).await
@@ -675,7 +828,18 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                                         continue;
                                     }
 
-                                    // Skip non-wakeup notifications (likes, reposts)
+                                    // Fold likes and reposts into one item per post,
+                                    // pushed when the window closes
+                                    if coalescer.add(notif, chrono::Utc::now()) {
+                                        debug!(
+                                            reason = ?notif.reason,
+                                            author = %notif.author_handle,
+                                            "coalescing notification"
+                                        );
+                                        continue;
+                                    }
+
+                                    // Skip other non-wakeup notifications
                                     if !notif.reason.triggers_wakeup() {
                                         debug!(
                                             reason = ?notif.reason,
@@ -717,11 +881,14 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                                     interruption_state.set_interrupt("inbox_items").await;
                                 }
 
-                                // Persist cursor after all notifications pushed
+                                // Persist cursor after all notifications pushed, held
+                                // back before any still being coalesced so a crash
+                                // replays them instead of losing them
                                 if let Some(cursor) = notif_bluesky.last_seen_at() {
+                                    let cursor = coalescer.held_cursor(cursor);
                                     debug!(cursor = %cursor, "persisting notification cursor");
                                     if let Err(e) = state_manager
-                                        .set_notification_cursor(Some(cursor.to_string()))
+                                        .set_notification_cursor(Some(cursor))
                                         .await
                                     {
                                         warn!(error = %e, "failed to persist notification cursor");
@@ -740,10 +907,30 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                                 warn!(error = %e, "notification poll failed");
                             }
                         }
+
+                        // Coalesced items don't interrupt: they're low priority by design
+                        for item in coalescer.drain_due(chrono::Utc::now()) {
+                            push_inbox_item(&http_client, &mcp_base_url, item).await;
+                        }
+                        next_poll = tokio::time::Instant::now() + idle_backoff.current();
                     }
                 }
             }
 
+            // The cursor is already past coalesced notifications, so push them now
+            for item in coalescer.drain_all() {
+                push_inbox_item(&http_client, &mcp_base_url, item).await;
+            }
+
+            // Save the cursor one last time so a restart doesn't replay notifications
+            if let Some(cursor) = notif_bluesky.last_seen_at()
+                && let Err(e) = state_manager
+                    .set_notification_cursor(Some(cursor.to_string()))
+                    .await
+            {
+                warn!(error = %e, "failed to persist notification cursor on shutdown");
+            }
+
             info!("notification poller stopped");
         })
     };
@@ -783,6 +970,8 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
     let follower_sync_handle = {
         let datalog_cache = datalog_cache.clone();
         let state_manager = Arc::clone(&state_manager);
+        let http_client = Arc::clone(&http_client);
+        let mcp_base_url = Arc::clone(&mcp_base_url);
         let mut shutdown_rx = shutdown_rx.clone();
         let follower_sync_interval = Duration::from_secs(config.follower_sync_interval);
 
@@ -799,7 +988,17 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
             // Do initial sync immediately on startup
             if let Some(ref datalog_cache) = datalog_cache {
                 match sync_followers(&sync_bluesky, &state_manager, datalog_cache).await {
-                    Ok(count) => info!(count, "initial follower sync complete"),
+                    Ok((count, diff)) => {
+                        info!(
+                            count,
+                            added = diff.added.len(),
+                            removed = diff.removed.len(),
+                            "initial follower sync complete"
+                        );
+                        if let Some(item) = diff.into_inbox_item() {
+                            push_inbox_item(&http_client, &mcp_base_url, item).await;
+                        }
+                    }
                     Err(e) => warn!(error = %e, "initial follower sync failed"),
                 }
             }
@@ -819,7 +1018,17 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                     _ = interval.tick() => {
                         if let Some(ref datalog_cache) = datalog_cache {
                             match sync_followers(&sync_bluesky, &state_manager, datalog_cache).await {
-                                Ok(count) => info!(count, "synced followers"),
+                                Ok((count, diff)) => {
+                                    info!(
+                                        count,
+                                        added = diff.added.len(),
+                                        removed = diff.removed.len(),
+                                        "synced followers"
+                                    );
+                                    if let Some(item) = diff.into_inbox_item() {
+                                        push_inbox_item(&http_client, &mcp_base_url, item).await;
+                                    }
+                                }
                                 Err(e) => warn!(error = %e, "follower sync failed"),
                             }
                         }
@@ -831,20 +1040,63 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
         })
     };
 
+    // Spawn tombstone purge task (hard-deletes soft-deleted records past retention)
+    let purge_handle = {
+        let cache = Arc::clone(&cache);
+        let client = Arc::clone(&client);
+        let mut shutdown_rx = shutdown_rx.clone();
+
+        tokio::spawn(async move {
+            info!("tombstone purge started");
+            let mut interval = tokio::time::interval(TOMBSTONE_PURGE_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    biased;
+
+                    _ = shutdown_rx.changed() => {
+                        if *shutdown_rx.borrow() {
+                            break;
+                        }
+                    }
+
+                    _ = interval.tick() => {
+                        match tombstones::purge_expired(
+                            &client,
+                            Some(&cache),
+                            tombstones::retention(),
+                            chrono::Utc::now(),
+                        )
+                        .await
+                        {
+                            Ok(report) if report.facts + report.notes > 0 => info!(
+                                facts = report.facts,
+                                notes = report.notes,
+                                "purged expired tombstones"
+                            ),
+                            Ok(_) => {}
+                            Err(e) => warn!(error = %e, "tombstone purge failed"),
+                        }
+                    }
+                }
+            }
+
+            info!("tombstone purge stopped");
+        })
+    };
+
     // Spawn trigger evaluation task
     let trigger_handle = {
         let cache = Arc::clone(&cache);
         let datalog_cache = datalog_cache.clone();
         let client = Arc::clone(&client);
+        let scheduler = Arc::clone(&scheduler);
+        let mut trigger_event_rx = trigger_event_rx;
         let mcp_base_url = Arc::clone(&mcp_base_url);
         let mut shutdown_rx = shutdown_rx.clone();
 
-        let trigger_interval = Duration::from_secs(
-            std::env::var("WINTER_TRIGGER_INTERVAL")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(300),
-        );
+        let trigger_interval =
+            Duration::from_secs(config.trigger_interval.unwrap_or(DEFAULT_TRIGGER_INTERVAL));
 
         tokio::spawn(async move {
             info!(
@@ -869,10 +1121,12 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                 return;
             };
 
+            let mut cache_rx = cache.subscribe();
             let engine = crate::trigger_engine::TriggerEngine::new(
                 cache,
                 Arc::clone(&datalog),
                 client,
+                scheduler,
                 (*mcp_base_url).clone(),
             );
 
@@ -893,6 +1147,24 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                             warn!(error = %e, "trigger evaluation failed");
                         }
                     }
+
+                    Some(event) = trigger_event_rx.recv() => {
+                        engine.evaluate_event(&event).await;
+                    }
+
+                    update = cache_rx.recv() => {
+                        match update {
+                            Ok(update) => {
+                                if let Some(event) = TriggerEvent::from_cache_update(&update) {
+                                    engine.evaluate_event(&event).await;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                warn!(skipped, "trigger engine lagged behind cache updates");
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
                 }
             }
 
@@ -907,6 +1179,9 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
         let cache = Arc::clone(&cache);
         let client = Arc::clone(&client);
         let interruption_state = Arc::clone(&interruption_state);
+        let session_started_at = Arc::clone(&session_started_at);
+        let http_client = Arc::clone(&http_client);
+        let mcp_base_url = Arc::clone(&mcp_base_url);
         let mut shutdown_rx = shutdown_rx.clone();
 
         tokio::spawn(async move {
@@ -934,8 +1209,10 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                     fetch_recent_thoughts_scoped(&client, Some(&cache), 10, &ScopeFilter::Global)
                 );
 
-                // Clear interruption state before starting
+                // Clear interruption state and metrics from the previous session before starting
                 interruption_state.clear().await;
+                clear_interrupt(&http_client, &mcp_base_url, None).await;
+                reset_session_metrics(&http_client, &mcp_base_url).await;
 
                 let context = AgentContext::new(identity)
                     .with_directives(directives)
@@ -944,6 +1221,7 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                     .with_trigger(ContextTrigger::PersistentSession);
 
                 info!("starting persistent session");
+                *session_started_at.write().await = Some(chrono::Utc::now());
 
                 // Run persistent session
                 tokio::select! {
@@ -965,7 +1243,9 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                 }
 
                 // Clear interruption state after session ends
+                *session_started_at.write().await = None;
                 interruption_state.clear().await;
+                clear_interrupt(&http_client, &mcp_base_url, None).await;
 
                 // Cooldown before restarting
                 info!("persistent session ended, restarting in 5s");
@@ -984,15 +1264,33 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
         })
     };
 
-    // Spawn watchdog: if inbox has pending items and no tool calls for >5 min, restart session
+    // Spawn watchdog: interrupt the session if it goes too long without a tool call,
+    // while the inbox backlog is above its high-watermark, or once it hits its budget
     let watchdog_handle = {
+        let queue_pressure_config = config.watchdog.queue_pressure;
+        let budget = config.watchdog.budget;
         let http_client = Arc::clone(&http_client);
         let mcp_base_url = Arc::clone(&mcp_base_url);
+        let session_started_at = Arc::clone(&session_started_at);
+        let last_activity_at = Arc::clone(&last_activity_at);
         let mut shutdown_rx = shutdown_rx.clone();
 
+        let stall_timeout =
+            Duration::from_secs(config.stall_timeout.unwrap_or(DEFAULT_STALL_TIMEOUT));
+
         tokio::spawn(async move {
-            info!("watchdog started");
+            info!(
+                stall_timeout_secs = stall_timeout.as_secs(),
+                queue_high_watermark = queue_pressure_config.high_watermark,
+                queue_low_watermark = queue_pressure_config.low_watermark,
+                max_cost_usd = ?budget.max_cost_usd,
+                max_turns = ?budget.max_turns,
+                "watchdog started"
+            );
             let mut interval = tokio::time::interval(Duration::from_secs(30));
+            let mut watchdog = StallWatchdog::new(stall_timeout);
+            let mut queue_pressure = QueuePressure::new(queue_pressure_config);
+            let mut budget_guard = BudgetGuard::new(budget);
 
             loop {
                 tokio::select! {
@@ -1005,24 +1303,75 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
                     }
 
                     _ = interval.tick() => {
-                        // Query inbox status + last tool call time via HTTP
-                        let status = get_inbox_status(&http_client, &mcp_base_url).await;
-
-                        if status.is_empty {
+                        // Queue pressure carries over between sessions: it only
+                        // re-arms once the backlog drops below the low-watermark,
+                        // so a session started to work through the backlog isn't
+                        // interrupted straight away
+                        let Some(started_at) = *session_started_at.read().await else {
+                            watchdog.session_ended();
+                            budget_guard.reset();
                             continue;
+                        };
+                        if watchdog.session_started(started_at) {
+                            // The session loop clears interrupts and metrics on restart
+                            budget_guard.reset();
                         }
 
-                        let stale_secs = status.stale_secs();
+                        // Query inbox status + last tool call time via HTTP
+                        let status = get_inbox_status(&http_client, &mcp_base_url).await;
+                        if let Some(last_tool_call_at) = status.last_tool_call_at {
+                            watchdog.record_activity(last_tool_call_at);
+                            *last_activity_at.write().await = Some(last_tool_call_at);
+                        }
 
-                        if stale_secs > 300 {
+                        if let Some(stale_secs) = watchdog.poll(chrono::Utc::now()) {
                             warn!(
                                 stale_secs,
                                 inbox_pending = status.pending,
-                                "watchdog: session appears stuck, inbox items pending for >5min with no tool calls"
+                                "watchdog: session stalled with no tool calls, interrupting"
                             );
-                            // The session loop will handle restart — the watchdog just logs for now.
-                            // A harder kill would need to abort the session handle, which we could
-                            // add later if needed.
+                            set_interrupt(&http_client, &mcp_base_url, STALLED_INTERRUPT_REASON)
+                                .await;
+                        }
+
+                        match queue_pressure.observe(status.pending) {
+                            Some(QueuePressureChange::Raised) => {
+                                info!(
+                                    inbox_pending = status.pending,
+                                    "watchdog: inbox above high-watermark, interrupting"
+                                );
+                                set_interrupt(
+                                    &http_client,
+                                    &mcp_base_url,
+                                    QUEUE_PRESSURE_INTERRUPT_REASON,
+                                )
+                                .await;
+                            }
+                            Some(QueuePressureChange::Relieved) => {
+                                info!(
+                                    inbox_pending = status.pending,
+                                    "watchdog: inbox below low-watermark, clearing interrupt"
+                                );
+                                clear_interrupt(
+                                    &http_client,
+                                    &mcp_base_url,
+                                    Some(QUEUE_PRESSURE_INTERRUPT_REASON),
+                                )
+                                .await;
+                            }
+                            None => {}
+                        }
+
+                        if let Some(exceeded) =
+                            budget_guard.observe(status.total_cost_usd, status.turn_count)
+                        {
+                            warn!(
+                                limit = %exceeded,
+                                total_cost_usd = status.total_cost_usd,
+                                turn_count = status.turn_count,
+                                "watchdog: session reached its budget, interrupting"
+                            );
+                            set_interrupt(&http_client, &mcp_base_url, &exceeded.reason()).await;
                         }
                     }
                 }
@@ -1040,26 +1389,106 @@ pub async fn run_with_config(config: DaemonConfig) -> Result<()> {
         }
     }
 
-    info!("shutting down daemon tasks");
+    info!(
+        timeout_secs = SHUTDOWN_TIMEOUT.as_secs(),
+        "shutting down daemon tasks"
+    );
 
-    // Wait for all tasks to complete
-    let _ = dm_handle.await;
-    let _ = notif_handle.await;
-    let _ = scheduler_handle.await;
-    let _ = follower_sync_handle.await;
-    let _ = trigger_handle.await;
-    let _ = session_handle.await;
-    let _ = watchdog_handle.await;
+    // Wait for every task, including the sync coordinator, to finish. The
+    // pollers persist their cursors and the scheduler finishes its current
+    // job on the way out.
+    let mut handles = vec![
+        dm_handle,
+        notif_handle,
+        scheduler_handle,
+        follower_sync_handle,
+        purge_handle,
+        trigger_handle,
+        session_handle,
+        watchdog_handle,
+    ];
+    handles.extend(sync_handle);
+    let all_stopped = async {
+        for handle in handles {
+            let _ = handle.await;
+        }
+    };
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, all_stopped)
+        .await
+        .is_err()
+    {
+        warn!("daemon tasks did not stop within the shutdown timeout, exiting anyway");
+        return Ok(());
+    }
 
-    // Wait for sync coordinator to finish
-    if let Some(handle) = sync_handle {
-        handle.await.ok();
+    // Persist any follower updates still held in memory
+    if let Some(ref dc) = datalog_cache
+        && let Err(e) = dc.flush_dirty_predicates().await
+    {
+        warn!(error = %e, "failed to flush datalog cache on shutdown");
     }
 
     info!("daemon shut down gracefully");
     Ok(())
 }
 
+/// Build the scheduler's job executor, which pushes each due job to the inbox.
+///
+/// Awakenings get one of Winter's goal directives appended, and are skipped
+/// while `last_activity_at` falls within the awaken quiet period.
+fn job_executor(
+    http_client: Arc<reqwest::Client>,
+    mcp_base_url: Arc<String>,
+    client: Arc<AtprotoClient>,
+    cache: Arc<RepoCache>,
+    awaken: AwakenConfig,
+    last_activity_at: Arc<tokio::sync::RwLock<Option<chrono::DateTime<chrono::Utc>>>>,
+) -> winter_scheduler::JobExecutor {
+    let goal_selector = Arc::new(tokio::sync::Mutex::new(GoalSelector::new(
+        awaken.goal_strategy,
+    )));
+
+    Box::new(move |job| {
+        let http_client = Arc::clone(&http_client);
+        let mcp_base_url = Arc::clone(&mcp_base_url);
+        let client = Arc::clone(&client);
+        let cache = Arc::clone(&cache);
+        let goal_selector = Arc::clone(&goal_selector);
+        let last_activity_at = Arc::clone(&last_activity_at);
+
+        Box::pin(async move {
+            // Winter was just busy, so leave this awakening to the next interval
+            if job.name == AWAKEN_JOB_NAME
+                && in_quiet_period(
+                    awaken.quiet_period,
+                    *last_activity_at.read().await,
+                    chrono::Utc::now(),
+                )
+            {
+                info!("skipping awakening: Winter called a tool within the quiet period");
+                return Ok(());
+            }
+
+            info!(name = %job.name, "scheduling job to inbox");
+
+            // Hand each awakening one of Winter's goal directives
+            let mut instructions = job.instructions.clone();
+            if job.name == AWAKEN_JOB_NAME {
+                let directives = fetch_directives(&client, Some(&cache)).await;
+                let roll = rand::random::<f64>();
+                if let Some(goal) = goal_selector.lock().await.select(&directives, roll) {
+                    info!(goal = %goal.content, "awakening with goal");
+                    instructions = with_goal(&instructions, goal);
+                }
+            }
+
+            let item = InboxItem::job(job.name.clone(), instructions);
+            push_inbox_item(&http_client, &mcp_base_url, item).await;
+
+            Ok(())
+        })
+    })
+}
 
 /// Push an inbox item to the MCP server via HTTP POST.
 async fn push_inbox_item(http_client: &reqwest::Client, mcp_base_url: &str, item: InboxItem) {
@@ -1081,25 +1510,342 @@ async fn push_inbox_item(http_client: &reqwest::Client, mcp_base_url: &str, item
     }
 }
 
-/// Inbox status response from the MCP server.
-struct InboxStatus {
-    pending: usize,
-    is_empty: bool,
-    last_tool_call_at: Option<chrono::DateTime<chrono::Utc>>,
+/// Set the interruption state on the MCP server, where the session checks it.
+async fn set_interrupt(http_client: &reqwest::Client, mcp_base_url: &str, reason: &str) {
+    let url = format!("{}/interrupt", mcp_base_url);
+    let body = serde_json::json!({ "reason": reason });
+    match http_client.post(&url).json(&body).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            debug!(reason, "interruption set via HTTP");
+        }
+        Ok(resp) => {
+            warn!(status = %resp.status(), reason, "failed to set interruption via HTTP");
+        }
+        Err(e) => {
+            warn!(error = %e, reason, "failed to set interruption via HTTP");
+        }
+    }
 }
 
-impl InboxStatus {
-    /// Seconds since the last tool call. Returns 0 if unknown.
-    fn stale_secs(&self) -> u64 {
-        self.last_tool_call_at
-            .map(|t| {
-                let elapsed = chrono::Utc::now().signed_duration_since(t);
-                elapsed.num_seconds().max(0) as u64
+/// Clear the interruption state on the MCP server.
+///
+/// With a `reason`, only an interruption set for that reason is cleared.
+async fn clear_interrupt(http_client: &reqwest::Client, mcp_base_url: &str, reason: Option<&str>) {
+    let url = format!("{}/interrupt", mcp_base_url);
+    let mut request = http_client.delete(&url);
+    if let Some(reason) = reason {
+        request = request.query(&[("reason", reason)]);
+    }
+    if let Err(e) = request.send().await {
+        debug!(error = %e, "failed to clear interruption via HTTP");
+    }
+}
+
+/// Folds bursts of likes or reposts of one post into one inbox item.
+///
+/// The first notification for a reason and subject opens a window; later ones
+/// for the same pair join it until it closes, and the group is then pushed
+/// once with a count, so a burst of likes doesn't queue an item per like.
+///
+/// Open groups only live in memory, so the persisted notification cursor is
+/// held back before them (see [`held_cursor`](Self::held_cursor)).
+struct NotificationCoalescer {
+    window: chrono::Duration,
+    groups: Vec<CoalescedGroup>,
+}
+
+/// Notifications gathered for one reason and subject.
+struct CoalescedGroup {
+    reason: NotificationReason,
+    subject_uri: String,
+    opened_at: chrono::DateTime<chrono::Utc>,
+    /// `indexed_at` of the oldest notification in the group.
+    first_indexed_at: Option<chrono::DateTime<chrono::Utc>>,
+    author_handles: Vec<String>,
+}
+
+impl NotificationCoalescer {
+    fn new(window: Duration) -> Self {
+        Self {
+            window: chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Add a like or repost to the open group for its reason and subject,
+    /// opening one at `now` if there is none.
+    ///
+    /// Returns false, leaving the notification to the caller, for any other
+    /// reason or for a like or repost without a subject.
+    fn add(&mut self, notif: &BlueskyNotification, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if !matches!(
+            notif.reason,
+            NotificationReason::Like | NotificationReason::Repost
+        ) {
+            return false;
+        }
+        let Some(subject_uri) = notif.reason_subject.as_deref() else {
+            return false;
+        };
+        let indexed_at = notif
+            .indexed_at
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Utc));
+        match self
+            .groups
+            .iter_mut()
+            .find(|g| g.reason == notif.reason && g.subject_uri == subject_uri)
+        {
+            Some(group) => {
+                group.author_handles.push(notif.author_handle.clone());
+                group.first_indexed_at = match (group.first_indexed_at, indexed_at) {
+                    (Some(first), Some(this)) => Some(first.min(this)),
+                    (first, this) => first.or(this),
+                };
+            }
+            None => self.groups.push(CoalescedGroup {
+                reason: notif.reason,
+                subject_uri: subject_uri.to_string(),
+                opened_at: now,
+                first_indexed_at: indexed_at,
+                author_handles: vec![notif.author_handle.clone()],
+            }),
+        }
+        true
+    }
+
+    /// The notification cursor to persist when the client has seen up to
+    /// `last_seen_at`: just before the oldest open group's first notification,
+    /// so a restart fetches the group's notifications again.
+    fn held_cursor(&self, last_seen_at: &str) -> String {
+        let oldest_open = self.groups.iter().filter_map(|g| g.first_indexed_at).min();
+        let last_seen = chrono::DateTime::parse_from_rfc3339(last_seen_at)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc));
+        match (oldest_open, last_seen) {
+            (Some(oldest), Some(last_seen)) if oldest <= last_seen => {
+                // The client skips notifications at or before the cursor
+                (oldest - chrono::Duration::milliseconds(1))
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+            }
+            _ => last_seen_at.to_string(),
+        }
+    }
+
+    /// Take the groups whose window has closed by `now` as inbox items.
+    fn drain_due(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<InboxItem> {
+        let window = self.window;
+        self.drain_where(|group| now.signed_duration_since(group.opened_at) >= window)
+    }
+
+    /// Take every group as inbox items, whether or not its window has closed.
+    fn drain_all(&mut self) -> Vec<InboxItem> {
+        self.drain_where(|_| true)
+    }
+
+    fn drain_where(&mut self, due: impl Fn(&CoalescedGroup) -> bool) -> Vec<InboxItem> {
+        let (closed, open): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.groups).into_iter().partition(due);
+        self.groups = open;
+        closed
+            .into_iter()
+            .map(|group| {
+                InboxItem::coalesced_notification(
+                    group.reason.as_str().to_string(),
+                    group.subject_uri,
+                    group.author_handles,
+                )
             })
-            .unwrap_or(0)
+            .collect()
+    }
+}
+
+/// Decides when a persistent session has stalled.
+///
+/// A session is stalled once it has gone `timeout` without a tool call (or,
+/// before its first tool call, `timeout` since it started). Each stall fires
+/// once; any later tool activity resets the timer.
+struct StallWatchdog {
+    timeout: chrono::Duration,
+    session_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_activity: Option<chrono::DateTime<chrono::Utc>>,
+    fired: bool,
+}
+
+impl StallWatchdog {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout: chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX),
+            session_started_at: None,
+            last_activity: None,
+            fired: false,
+        }
+    }
+
+    /// Note the start time of the running session, resetting if it's a new one.
+    ///
+    /// Returns whether this is a new session.
+    fn session_started(&mut self, started_at: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.session_started_at == Some(started_at) {
+            return false;
+        }
+        self.session_started_at = Some(started_at);
+        self.last_activity = Some(started_at);
+        self.fired = false;
+        true
+    }
+
+    /// Forget the session once it has ended.
+    fn session_ended(&mut self) {
+        self.session_started_at = None;
+        self.last_activity = None;
+        self.fired = false;
+    }
+
+    /// Record a tool call. Calls older than the latest known activity are ignored.
+    fn record_activity(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        if self.session_started_at.is_none() {
+            return;
+        }
+        if self.last_activity.is_none_or(|last| at > last) {
+            self.last_activity = Some(at);
+            self.fired = false;
+        }
+    }
+
+    /// Returns the stale duration in seconds if the session just stalled.
+    fn poll(&mut self, now: chrono::DateTime<chrono::Utc>) -> Option<u64> {
+        let last_activity = self.last_activity?;
+        let stale = now.signed_duration_since(last_activity);
+        if self.fired || stale < self.timeout {
+            return None;
+        }
+        self.fired = true;
+        Some(stale.num_seconds().max(0) as u64)
+    }
+}
+
+/// A change in queue pressure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueuePressureChange {
+    /// The inbox went above the high-watermark.
+    Raised,
+    /// The inbox went below the low-watermark.
+    Relieved,
+}
+
+/// Tracks inbox depth against the queue-pressure watermarks.
+///
+/// Pressure is raised when the depth goes above the high-watermark and stays
+/// raised until it drops below the low-watermark, so a backlog hovering
+/// around one threshold doesn't toggle the interrupt on every poll.
+struct QueuePressure {
+    config: QueuePressureConfig,
+    raised: bool,
+}
+
+impl QueuePressure {
+    fn new(config: QueuePressureConfig) -> Self {
+        Self {
+            config,
+            raised: false,
+        }
+    }
+
+    /// Observe the current inbox depth, returning any change in pressure.
+    fn observe(&mut self, pending: usize) -> Option<QueuePressureChange> {
+        if !self.raised && pending > self.config.high_watermark {
+            self.raised = true;
+            Some(QueuePressureChange::Raised)
+        } else if self.raised && pending < self.config.low_watermark {
+            self.raised = false;
+            Some(QueuePressureChange::Relieved)
+        } else {
+            None
+        }
     }
 }
 
+/// Reset the MCP server's session metrics so caps apply per session.
+async fn reset_session_metrics(http_client: &reqwest::Client, mcp_base_url: &str) {
+    let url = format!("{}/session-metrics", mcp_base_url);
+    if let Err(e) = http_client.delete(&url).send().await {
+        debug!(error = %e, "failed to reset session metrics via HTTP");
+    }
+}
+
+/// The budget cap a session reached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BudgetExceeded {
+    Cost { limit: f64 },
+    Turns { limit: u64 },
+}
+
+impl BudgetExceeded {
+    /// Interruption reason for this cap, naming the limit that was hit.
+    fn reason(&self) -> String {
+        format!("{}: {}", BUDGET_INTERRUPT_REASON, self)
+    }
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetExceeded::Cost { limit } => write!(f, "cost cap ${:.2}", limit),
+            BudgetExceeded::Turns { limit } => write!(f, "turn cap {}", limit),
+        }
+    }
+}
+
+/// Checks session metrics against the budget, firing once per session.
+struct BudgetGuard {
+    budget: SessionBudget,
+    fired: bool,
+}
+
+impl BudgetGuard {
+    fn new(budget: SessionBudget) -> Self {
+        Self {
+            budget,
+            fired: false,
+        }
+    }
+
+    /// Returns the cap that was reached, the first time one is.
+    fn observe(&mut self, total_cost_usd: f64, turn_count: u64) -> Option<BudgetExceeded> {
+        if self.fired {
+            return None;
+        }
+        let exceeded = match self.budget {
+            SessionBudget {
+                max_cost_usd: Some(limit),
+                ..
+            } if total_cost_usd >= limit => BudgetExceeded::Cost { limit },
+            SessionBudget {
+                max_turns: Some(limit),
+                ..
+            } if turn_count >= limit => BudgetExceeded::Turns { limit },
+            _ => return None,
+        };
+        self.fired = true;
+        Some(exceeded)
+    }
+
+    /// Re-arm for a new session.
+    fn reset(&mut self) {
+        self.fired = false;
+    }
+}
+
+/// Inbox status response from the MCP server.
+struct InboxStatus {
+    pending: usize,
+    last_tool_call_at: Option<chrono::DateTime<chrono::Utc>>,
+    total_cost_usd: f64,
+    turn_count: u64,
+}
+
 /// Query inbox status from the MCP server via HTTP GET.
 async fn get_inbox_status(http_client: &reqwest::Client, mcp_base_url: &str) -> InboxStatus {
     let url = format!("{}/inbox/status", mcp_base_url);
@@ -1107,20 +1853,39 @@ async fn get_inbox_status(http_client: &reqwest::Client, mcp_base_url: &str) ->
         Ok(resp) if resp.status().is_success() => {
             if let Ok(body) = resp.json::<serde_json::Value>().await {
                 let pending = body.get("pending").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let is_empty = body.get("is_empty").and_then(|v| v.as_bool()).unwrap_or(true);
                 let last_tool_call_at = body
                     .get("last_tool_call_at")
                     .and_then(|v| v.as_str())
                     .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                     .map(|dt| dt.with_timezone(&chrono::Utc));
-                InboxStatus { pending, is_empty, last_tool_call_at }
+                let total_cost_usd = body
+                    .get("total_cost_usd")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(0.0);
+                let turn_count = body.get("turn_count").and_then(|v| v.as_u64()).unwrap_or(0);
+                InboxStatus {
+                    pending,
+                    last_tool_call_at,
+                    total_cost_usd,
+                    turn_count,
+                }
             } else {
-                InboxStatus { pending: 0, is_empty: true, last_tool_call_at: None }
+                InboxStatus {
+                    pending: 0,
+                    last_tool_call_at: None,
+                    total_cost_usd: 0.0,
+                    turn_count: 0,
+                }
             }
         }
         _ => {
             // If we can't reach the MCP server, treat as empty (avoid false alarms)
-            InboxStatus { pending: 0, is_empty: true, last_tool_call_at: None }
+            InboxStatus {
+                pending: 0,
+                last_tool_call_at: None,
+                total_cost_usd: 0.0,
+                turn_count: 0,
+            }
         }
     }
 }
@@ -1163,15 +1928,72 @@ async fn fetch_dm_history(
     }
 }
 
-/// Sync followers from the Bluesky API to the state record and datalog cache.
+/// Followers gained and lost between two follower syncs.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct FollowerDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl FollowerDiff {
+    /// Compare the followers recorded by the previous sync with the current ones.
+    ///
+    /// `None` means no sync has been recorded yet, so there is nothing to
+    /// compare against and the diff is empty.
+    fn between(previous: Option<&[String]>, current: &[String]) -> Self {
+        let Some(previous) = previous else {
+            return Self::default();
+        };
+        let previous: HashSet<&String> = previous.iter().collect();
+        let current_set: HashSet<&String> = current.iter().collect();
+
+        let mut added: Vec<String> = current
+            .iter()
+            .filter(|did| !previous.contains(did))
+            .cloned()
+            .collect();
+        let mut removed: Vec<String> = previous
+            .iter()
+            .filter(|did| !current_set.contains(*did))
+            .map(|did| (*did).clone())
+            .collect();
+        added.sort();
+        added.dedup();
+        removed.sort();
+        Self { added, removed }
+    }
+
+    /// The inbox item announcing this diff, unless nothing changed.
+    fn into_inbox_item(self) -> Option<InboxItem> {
+        if self.added.is_empty() && self.removed.is_empty() {
+            return None;
+        }
+        Some(InboxItem::followers_changed(self.added, self.removed))
+    }
+}
+
+/// Fetch all followers, record them, and report what changed since the last sync.
+///
+/// Returns the follower count and the diff against the followers recorded
+/// in the state record by the previous sync. Nothing is recorded if the
+/// previous followers can't be loaded, so the next sync still diffs against them.
 async fn sync_followers(
     bluesky: &BlueskyClient,
     state_manager: &StateManager,
     datalog_cache: &DatalogCache,
-) -> Result<usize, BlueskyError> {
-    let followers = bluesky.get_all_followers().await?;
+) -> Result<(usize, FollowerDiff)> {
+    let followers = bluesky
+        .get_all_followers()
+        .await
+        .map_err(|e| miette::miette!("failed to fetch followers: {}", e))?;
     let count = followers.len();
 
+    let previous = state_manager
+        .get_followers()
+        .await
+        .map_err(|e| miette::miette!("failed to load previous followers: {}", e))?;
+    let diff = FollowerDiff::between(previous.as_deref(), &followers);
+
     // Persist to PDS state record (so MCP servers can get it from CAR file)
     if let Err(e) = state_manager.set_followers(followers.clone()).await {
         warn!(error = %e, "failed to persist followers to state record");
@@ -1185,7 +2007,7 @@ async fn sync_followers(
     if let Err(e) = datalog_cache.flush_dirty_predicates().await {
         warn!(error = %e, "failed to flush is_followed_by after follower sync");
     }
-    Ok(count)
+    Ok((count, diff))
 }
 
 #[cfg(test)]
@@ -1194,10 +2016,44 @@ mod tests {
     use chrono::Utc;
     use winter_atproto::{Thought, ThoughtKind};
 
+    #[test]
+    fn poll_backoff_doubles_up_to_max() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(10), Duration::from_secs(60));
+        assert_eq!(backoff.current(), Duration::from_secs(10));
+
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            backoff.record_empty();
+            seen.push(backoff.current().as_secs());
+        }
+        assert_eq!(seen, vec![20, 40, 60, 60, 60]);
+    }
+
+    #[test]
+    fn poll_backoff_resets_on_activity() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(5), Duration::from_secs(300));
+        backoff.record_empty();
+        backoff.record_empty();
+        assert_eq!(backoff.current(), Duration::from_secs(20));
+
+        backoff.record_activity();
+        assert_eq!(backoff.current(), Duration::from_secs(5));
+        backoff.record_empty();
+        assert_eq!(backoff.current(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn poll_backoff_disabled_when_max_not_above_min() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(10), Duration::from_secs(3));
+        backoff.record_empty();
+        assert_eq!(backoff.current(), Duration::from_secs(10));
+    }
+
     fn make_thought(trigger: Option<&str>) -> Thought {
         Thought {
             kind: ThoughtKind::Insight,
             content: "test".to_string(),
+            content_type: None,
             trigger: trigger.map(String::from),
             tags: vec![],
             duration_ms: None,
@@ -1319,4 +2175,440 @@ mod tests {
             &ScopeFilter::Global
         ));
     }
+
+    fn like(author: &str, subject: &str) -> BlueskyNotification {
+        BlueskyNotification {
+            reason: NotificationReason::Like,
+            author_did: format!("did:plc:{}", author),
+            author_handle: format!("{}.bsky.social", author),
+            text: None,
+            uri: format!("at://did:plc:{}/app.bsky.feed.like/1", author),
+            cid: "bafylike".to_string(),
+            reason_subject: Some(subject.to_string()),
+            indexed_at: None,
+            parent: None,
+            root: None,
+            facets: vec![],
+        }
+    }
+
+    const POST: &str = "at://did:plc:winter/app.bsky.feed.post/1";
+
+    #[test]
+    fn coalescer_folds_burst_of_likes_into_one_item() {
+        let start = Utc::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::from_secs(300));
+
+        for (i, author) in ["alice", "bob", "carol"].iter().enumerate() {
+            coalescer.add(
+                &like(author, POST),
+                start + chrono::Duration::seconds(i as i64),
+            );
+        }
+        assert!(
+            coalescer
+                .drain_due(start + chrono::Duration::seconds(299))
+                .is_empty()
+        );
+
+        let items = coalescer.drain_due(start + chrono::Duration::seconds(300));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].priority, 25);
+        let winter_mcp::InboxPayload::CoalescedNotification {
+            reason,
+            subject_uri,
+            count,
+            author_handles,
+        } = &items[0].payload
+        else {
+            panic!("expected coalesced payload, got {:?}", items[0].payload);
+        };
+        assert_eq!(reason, "like");
+        assert_eq!(subject_uri, POST);
+        assert_eq!(*count, 3);
+        assert_eq!(
+            author_handles,
+            &["alice.bsky.social", "bob.bsky.social", "carol.bsky.social"]
+        );
+
+        // The window closed with the drain
+        assert!(coalescer.drain_all().is_empty());
+    }
+
+    #[test]
+    fn coalescer_keeps_subjects_and_reasons_apart() {
+        let start = Utc::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::from_secs(300));
+        let other_post = "at://did:plc:winter/app.bsky.feed.post/2";
+        let mut repost = like("dave", POST);
+        repost.reason = NotificationReason::Repost;
+
+        coalescer.add(&like("alice", POST), start);
+        coalescer.add(&like("bob", other_post), start);
+        coalescer.add(&repost, start);
+
+        let items = coalescer.drain_due(start + chrono::Duration::seconds(300));
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].context_tag, format!("coalesced:like:{}", POST));
+        assert_eq!(
+            items[1].context_tag,
+            format!("coalesced:like:{}", other_post)
+        );
+        assert_eq!(items[2].context_tag, format!("coalesced:repost:{}", POST));
+    }
+
+    #[test]
+    fn coalescer_opens_new_window_after_drain() {
+        let start = Utc::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::from_secs(300));
+
+        coalescer.add(&like("alice", POST), start);
+        assert_eq!(
+            coalescer
+                .drain_due(start + chrono::Duration::seconds(300))
+                .len(),
+            1
+        );
+
+        // A like after the window closed starts a fresh group
+        let later = start + chrono::Duration::seconds(400);
+        coalescer.add(&like("bob", POST), later);
+        assert!(
+            coalescer
+                .drain_due(later + chrono::Duration::seconds(10))
+                .is_empty()
+        );
+        assert_eq!(coalescer.drain_all().len(), 1);
+    }
+
+    #[test]
+    fn coalescer_leaves_follows_and_other_reasons_alone() {
+        let start = Utc::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::from_secs(300));
+
+        for author in ["alice", "bob"] {
+            let mut follow = like(author, POST);
+            follow.reason = NotificationReason::Follow;
+            follow.uri = format!("at://did:plc:{}/app.bsky.graph.follow/1", author);
+            follow.reason_subject = None;
+            assert!(!coalescer.add(&follow, start));
+        }
+        let mut mention = like("carol", POST);
+        mention.reason = NotificationReason::Mention;
+        assert!(!coalescer.add(&mention, start));
+
+        assert!(coalescer.drain_all().is_empty());
+    }
+
+    #[test]
+    fn cursor_is_held_before_open_groups() {
+        let start = Utc::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::from_secs(300));
+        let last_seen = "2026-01-01T00:10:00.000Z";
+        assert_eq!(coalescer.held_cursor(last_seen), last_seen);
+
+        let mut first = like("alice", POST);
+        first.indexed_at = Some("2026-01-01T00:05:00.000Z".to_string());
+        let mut second = like("bob", POST);
+        second.indexed_at = Some("2026-01-01T00:08:00.000Z".to_string());
+        coalescer.add(&second, start);
+        coalescer.add(&first, start);
+        assert_eq!(coalescer.held_cursor(last_seen), "2026-01-01T00:04:59.999Z");
+
+        // Once the group is pushed the cursor can move on
+        coalescer.drain_all();
+        assert_eq!(coalescer.held_cursor(last_seen), last_seen);
+    }
+
+    fn dids(dids: &[&str]) -> Vec<String> {
+        dids.iter().map(|did| did.to_string()).collect()
+    }
+
+    #[test]
+    fn follower_diff_lists_new_and_lost_followers() {
+        let previous = dids(&["did:plc:alice", "did:plc:bob", "did:plc:carol"]);
+        let current = dids(&[
+            "did:plc:dave",
+            "did:plc:alice",
+            "did:plc:erin",
+            "did:plc:carol",
+        ]);
+
+        assert_eq!(
+            FollowerDiff::between(Some(&previous[..]), &current),
+            FollowerDiff {
+                added: dids(&["did:plc:dave", "did:plc:erin"]),
+                removed: dids(&["did:plc:bob"]),
+            }
+        );
+    }
+
+    #[test]
+    fn follower_diff_empty_when_unchanged_or_without_baseline() {
+        let followers = dids(&["did:plc:alice", "did:plc:bob"]);
+
+        let unchanged = FollowerDiff::between(Some(&followers[..]), &followers);
+        assert_eq!(unchanged, FollowerDiff::default());
+        assert!(unchanged.into_inbox_item().is_none());
+
+        // The first sync has nothing to compare against
+        assert_eq!(
+            FollowerDiff::between(None, &followers),
+            FollowerDiff::default()
+        );
+
+        // ...but a sync that found no followers does
+        assert_eq!(
+            FollowerDiff::between(Some(&[]), &followers),
+            FollowerDiff {
+                added: followers.clone(),
+                removed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn follower_diff_becomes_inbox_item() {
+        let diff =
+            FollowerDiff::between(Some(&dids(&["did:plc:alice"])[..]), &dids(&["did:plc:bob"]));
+        let item = diff.into_inbox_item().unwrap();
+
+        let winter_mcp::InboxPayload::FollowersChanged { added, removed } = &item.payload else {
+            panic!("expected followers payload, got {:?}", item.payload);
+        };
+        assert_eq!(added, &dids(&["did:plc:bob"]));
+        assert_eq!(removed, &dids(&["did:plc:alice"]));
+    }
+
+    fn stall_watchdog_at(start: chrono::DateTime<Utc>) -> StallWatchdog {
+        let mut watchdog = StallWatchdog::new(Duration::from_secs(300));
+        watchdog.session_started(start);
+        watchdog
+    }
+
+    #[test]
+    fn stall_watchdog_fires_after_timeout_without_activity() {
+        let start = Utc::now();
+        let mut watchdog = stall_watchdog_at(start);
+
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(299)), None);
+        assert_eq!(
+            watchdog.poll(start + chrono::Duration::seconds(301)),
+            Some(301)
+        );
+        // Fires once per stall
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(400)), None);
+    }
+
+    #[test]
+    fn stall_watchdog_activity_resets_timer() {
+        let start = Utc::now();
+        let mut watchdog = stall_watchdog_at(start);
+
+        watchdog.record_activity(start + chrono::Duration::seconds(200));
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(400)), None);
+        assert_eq!(
+            watchdog.poll(start + chrono::Duration::seconds(501)),
+            Some(301)
+        );
+
+        // New activity after a stall re-arms the watchdog
+        watchdog.record_activity(start + chrono::Duration::seconds(600));
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(700)), None);
+        assert_eq!(
+            watchdog.poll(start + chrono::Duration::seconds(901)),
+            Some(301)
+        );
+    }
+
+    #[test]
+    fn stall_watchdog_ignores_activity_from_before_session() {
+        let start = Utc::now();
+        let mut watchdog = stall_watchdog_at(start);
+
+        // last_tool_call_at left over from a previous session
+        watchdog.record_activity(start - chrono::Duration::seconds(1000));
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(10)), None);
+    }
+
+    #[test]
+    fn stall_watchdog_idle_without_session() {
+        let start = Utc::now();
+        let mut watchdog = stall_watchdog_at(start);
+        watchdog.session_ended();
+
+        watchdog.record_activity(start);
+        assert_eq!(watchdog.poll(start + chrono::Duration::seconds(1000)), None);
+
+        // A new session starts a fresh timer
+        let restart = start + chrono::Duration::seconds(2000);
+        watchdog.session_started(restart);
+        assert_eq!(watchdog.poll(restart + chrono::Duration::seconds(10)), None);
+        assert_eq!(
+            watchdog.poll(restart + chrono::Duration::seconds(300)),
+            Some(300)
+        );
+    }
+
+    fn queue_pressure() -> QueuePressure {
+        QueuePressure::new(QueuePressureConfig {
+            high_watermark: 10,
+            low_watermark: 3,
+        })
+    }
+
+    #[test]
+    fn queue_pressure_raises_above_high_watermark() {
+        let mut pressure = queue_pressure();
+        assert_eq!(pressure.observe(0), None);
+        assert_eq!(pressure.observe(10), None);
+        assert_eq!(pressure.observe(11), Some(QueuePressureChange::Raised));
+        // Stays raised without re-signalling
+        assert_eq!(pressure.observe(15), None);
+    }
+
+    #[test]
+    fn queue_pressure_holds_between_watermarks() {
+        let mut pressure = queue_pressure();
+        assert_eq!(pressure.observe(11), Some(QueuePressureChange::Raised));
+        assert_eq!(pressure.observe(9), None);
+        assert_eq!(pressure.observe(3), None);
+        assert_eq!(pressure.observe(11), None);
+        assert_eq!(pressure.observe(2), Some(QueuePressureChange::Relieved));
+    }
+
+    #[test]
+    fn queue_pressure_does_not_raise_between_watermarks_after_relief() {
+        let mut pressure = queue_pressure();
+        assert_eq!(pressure.observe(11), Some(QueuePressureChange::Raised));
+        assert_eq!(pressure.observe(0), Some(QueuePressureChange::Relieved));
+        assert_eq!(pressure.observe(2), None);
+        assert_eq!(pressure.observe(10), None);
+        assert_eq!(pressure.observe(11), Some(QueuePressureChange::Raised));
+    }
+
+    #[test]
+    fn queue_pressure_stays_raised_while_backlog_persists() {
+        let mut pressure = queue_pressure();
+        assert_eq!(pressure.observe(20), Some(QueuePressureChange::Raised));
+        // A new session starting on the same backlog isn't interrupted again
+        for _ in 0..3 {
+            assert_eq!(pressure.observe(20), None);
+        }
+        assert_eq!(pressure.observe(2), Some(QueuePressureChange::Relieved));
+        assert_eq!(pressure.observe(20), Some(QueuePressureChange::Raised));
+    }
+
+    #[test]
+    fn budget_guard_fires_when_cost_cap_crossed() {
+        let mut guard = BudgetGuard::new(SessionBudget {
+            max_cost_usd: Some(5.0),
+            max_turns: None,
+        });
+        assert_eq!(guard.observe(4.99, 100), None);
+
+        let exceeded = guard.observe(5.01, 101).unwrap();
+        assert_eq!(exceeded, BudgetExceeded::Cost { limit: 5.0 });
+        assert_eq!(exceeded.reason(), "budget: cost cap $5.00");
+
+        // Fires once per session
+        assert_eq!(guard.observe(6.0, 102), None);
+        guard.reset();
+        assert!(guard.observe(6.0, 102).is_some());
+    }
+
+    #[test]
+    fn budget_guard_fires_when_turn_cap_reached() {
+        let mut guard = BudgetGuard::new(SessionBudget {
+            max_cost_usd: Some(50.0),
+            max_turns: Some(10),
+        });
+        assert_eq!(guard.observe(1.0, 9), None);
+        assert_eq!(
+            guard.observe(1.0, 10),
+            Some(BudgetExceeded::Turns { limit: 10 })
+        );
+    }
+
+    #[tokio::test]
+    async fn budget_interrupt_sets_interrupt_state() {
+        let state = Arc::new(winter_mcp::http::HttpState::new(
+            winter_mcp::McpServer::new(winter_mcp::ToolRegistry::empty()),
+        ));
+        let router = winter_mcp::http::create_router(Arc::clone(&state));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        let exceeded = BudgetExceeded::Turns { limit: 10 };
+        set_interrupt(
+            &reqwest::Client::new(),
+            &format!("http://{}", addr),
+            &exceeded.reason(),
+        )
+        .await;
+
+        let (interrupted, reason) = state.interruption().check().await;
+        assert!(interrupted);
+        assert_eq!(reason.as_deref(), Some("budget: turn cap 10"));
+    }
+
+    #[test]
+    fn budget_guard_without_caps_never_fires() {
+        let mut guard = BudgetGuard::new(SessionBudget::default());
+        assert_eq!(guard.observe(1_000.0, 1_000), None);
+    }
+
+    fn job(name: &str) -> winter_atproto::Job {
+        winter_atproto::Job {
+            name: name.to_string(),
+            instructions: "Think freely.".to_string(),
+            schedule: winter_atproto::JobSchedule::Interval { seconds: 3600 },
+            status: Default::default(),
+            last_run: None,
+            next_run: None,
+            failure_count: 0,
+            created_at: Utc::now(),
+            run_requested_at: None,
+            depends_on: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn executor_skips_awakening_soon_after_tool_activity() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // Only the non-awaken job and the awakening after the quiet period arrive
+        Mock::given(method("POST"))
+            .and(path("/inbox"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let last_activity_at = Arc::new(tokio::sync::RwLock::new(Some(
+            Utc::now() - chrono::Duration::seconds(60),
+        )));
+        let executor = job_executor(
+            Arc::new(reqwest::Client::new()),
+            Arc::new(server.uri()),
+            Arc::new(AtprotoClient::new(server.uri())),
+            RepoCache::new(),
+            AwakenConfig {
+                goal_strategy: AwakenGoalStrategy::RoundRobin,
+                quiet_period: Some(Duration::from_secs(600)),
+            },
+            Arc::clone(&last_activity_at),
+        );
+
+        executor(job(AWAKEN_JOB_NAME)).await.unwrap();
+        executor(job("check-feeds")).await.unwrap();
+
+        *last_activity_at.write().await = Some(Utc::now() - chrono::Duration::seconds(700));
+        executor(job(AWAKEN_JOB_NAME)).await.unwrap();
+    }
 }
@@ -0,0 +1,254 @@
+//! `winter doctor`: check that everything Winter depends on is wired up.
+//!
+//! Runs each check without starting the daemon, prints a pass/fail table and
+//! fails if any check did.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use miette::Result;
+use winter_atproto::{AtprotoClient, DEFAULT_JETSTREAM_URL, jetstream};
+use winter_datalog::SouffleExecutor;
+use winter_mcp::{DenoExecutor, SecretSource};
+
+/// How long a network check may take before it counts as failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of one check: a detail line either way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub result: Result<String, String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            result: Ok(detail.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            result: Err(detail.into()),
+        }
+    }
+}
+
+/// Results of every check, in the order they ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// Number of checks that failed.
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|c| c.result.is_err()).count()
+    }
+
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .checks
+            .iter()
+            .map(|c| c.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for check in &self.checks {
+            let (status, detail) = match &check.result {
+                Ok(detail) => ("PASS", detail),
+                Err(detail) => ("FAIL", detail),
+            };
+            writeln!(f, "{}  {:<width$}  {}", status, check.name, detail)?;
+        }
+        if self.passed() {
+            writeln!(f, "\nAll {} checks passed.", self.checks.len())
+        } else {
+            writeln!(
+                f,
+                "\n{} of {} checks failed.",
+                self.failures(),
+                self.checks.len()
+            )
+        }
+    }
+}
+
+/// Run every check, print the table and fail if any check failed.
+pub async fn run(
+    pds_url: &str,
+    handle: &str,
+    app_password: &str,
+    secrets_path: Option<&Path>,
+    secret_sources: &[SecretSource],
+) -> Result<()> {
+    let report = check_all(pds_url, handle, app_password, secrets_path, secret_sources).await;
+    print!("{}", report);
+    if report.passed() {
+        Ok(())
+    } else {
+        Err(miette::miette!(
+            "{} of {} checks failed",
+            report.failures(),
+            report.checks.len()
+        ))
+    }
+}
+
+async fn check_all(
+    pds_url: &str,
+    handle: &str,
+    app_password: &str,
+    secrets_path: Option<&Path>,
+    secret_sources: &[SecretSource],
+) -> DoctorReport {
+    let client = AtprotoClient::new(pds_url);
+    let login = match client.login(handle, app_password).await {
+        Ok(()) => match client.did().await {
+            Some(did) => Check::pass("PDS login", format!("logged in as {}", did)),
+            None => Check::fail("PDS login", "login succeeded but returned no DID"),
+        },
+        Err(e) => Check::fail("PDS login", e.to_string()),
+    };
+    let did = client.did().await;
+
+    let checks = vec![
+        login,
+        check_did_resolution(pds_url, handle, did.as_deref()).await,
+        check_firehose().await,
+        check_deno().await,
+        check_secrets(secrets_path, secret_sources).await,
+        check_souffle().await,
+    ];
+    DoctorReport { checks }
+}
+
+/// The handle must resolve to the DID the PDS logged us in as.
+async fn check_did_resolution(pds_url: &str, handle: &str, did: Option<&str>) -> Check {
+    const NAME: &str = "DID resolution";
+    let Some(did) = did else {
+        return Check::fail(NAME, "skipped: PDS login failed");
+    };
+
+    let url = format!(
+        "{}/xrpc/com.atproto.identity.resolveHandle?handle={}",
+        pds_url, handle
+    );
+    let resolved = async {
+        let response = reqwest::Client::new()
+            .get(&url)
+            .timeout(CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("resolveHandle returned {}", response.status()));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        body.get("did")
+            .and_then(|d| d.as_str())
+            .map(String::from)
+            .ok_or_else(|| "resolveHandle returned no DID".to_string())
+    };
+
+    match resolved.await {
+        Ok(resolved) if resolved == did => Check::pass(NAME, format!("{} -> {}", handle, did)),
+        Ok(resolved) => Check::fail(
+            NAME,
+            format!("{} resolves to {}, expected {}", handle, resolved, did),
+        ),
+        Err(e) => Check::fail(NAME, e),
+    }
+}
+
+async fn check_firehose() -> Check {
+    const NAME: &str = "Firehose";
+    match tokio::time::timeout(CHECK_TIMEOUT, jetstream::probe(DEFAULT_JETSTREAM_URL)).await {
+        Ok(Ok(())) => Check::pass(NAME, format!("connected to {}", DEFAULT_JETSTREAM_URL)),
+        Ok(Err(e)) => Check::fail(NAME, e.to_string()),
+        Err(_) => Check::fail(
+            NAME,
+            format!("timed out connecting to {}", DEFAULT_JETSTREAM_URL),
+        ),
+    }
+}
+
+async fn check_deno() -> Check {
+    if DenoExecutor::is_available().await {
+        Check::pass("Deno", "deno found")
+    } else {
+        Check::fail("Deno", "deno not found in PATH; custom tools won't run")
+    }
+}
+
+async fn check_secrets(secrets_path: Option<&Path>, secret_sources: &[SecretSource]) -> Check {
+    match SecretSource::build(secret_sources, secrets_path.map(Path::to_path_buf)).await {
+        Ok(provider) => Check::pass("Secrets", format!("loaded {} provider", provider.name())),
+        Err(e) => Check::fail("Secrets", e.to_string()),
+    }
+}
+
+async fn check_souffle() -> Check {
+    if SouffleExecutor::is_souffle_available().await {
+        Check::pass("Soufflé", "souffle found")
+    } else {
+        Check::fail(
+            "Soufflé",
+            "souffle not found in PATH; datalog queries won't run",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(checks: Vec<Check>) -> DoctorReport {
+        DoctorReport { checks }
+    }
+
+    #[test]
+    fn test_all_passing_checks_pass() {
+        let report = report(vec![
+            Check::pass("PDS login", "logged in as did:plc:x"),
+            Check::pass("Deno", "deno found"),
+        ]);
+
+        assert!(report.passed());
+        assert_eq!(report.failures(), 0);
+        assert!(report.to_string().ends_with("All 2 checks passed.\n"));
+    }
+
+    #[test]
+    fn test_any_failing_check_fails_the_report() {
+        let report = report(vec![
+            Check::pass("PDS login", "logged in as did:plc:x"),
+            Check::fail("Deno", "deno not found"),
+            Check::fail("Soufflé", "souffle not found"),
+        ]);
+
+        assert!(!report.passed());
+        assert_eq!(report.failures(), 2);
+
+        let table = report.to_string();
+        assert!(table.contains("PASS  PDS login  logged in as did:plc:x"));
+        assert!(table.contains("FAIL  Deno       deno not found"));
+        assert!(table.ends_with("2 of 3 checks failed.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_did_resolution_is_skipped_without_login() {
+        let check = check_did_resolution("http://127.0.0.1:1", "winter.test", None).await;
+        assert_eq!(check.result, Err("skipped: PDS login failed".to_string()));
+    }
+}
@@ -0,0 +1,117 @@
+//! Log output setup.
+//!
+//! Logs are human-readable by default. With `--log-format json` (or
+//! `WINTER_LOG_FORMAT=json`) each event is one JSON object per line, with its
+//! fields at the top level and the fields of the enclosing spans under
+//! `span` and `spans`, for shipping to a log aggregator.
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// How log events are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Install the global subscriber, filtered by `RUST_LOG` (default
+/// `winter=info`) and writing to stdout.
+pub fn init(format: LogFormat) {
+    let filter =
+        EnvFilter::new(std::env::var("RUST_LOG").unwrap_or_else(|_| "winter=info".to_string()));
+    subscriber(format, filter, std::io::stdout).init();
+}
+
+fn subscriber<W>(format: LogFormat, filter: EnvFilter, writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Clone + Send + Sync + 'static,
+{
+    let json = (format == LogFormat::Json).then(|| {
+        fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_writer(writer.clone())
+    });
+    let text = (format == LogFormat::Text).then(|| fmt::layer().with_writer(writer));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(json)
+        .with(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    /// Collects everything written to it.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture(format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let subscriber = subscriber(format, EnvFilter::new("info"), buffer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("tool_call", tool = "create_fact");
+            let _entered = span.enter();
+            tracing::warn!(error = "pds unavailable", attempt = 2, "tool failed");
+        });
+
+        String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_json_format_writes_parseable_lines() {
+        let output = capture(LogFormat::Json);
+
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+
+        let event = &lines[0];
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["message"], "tool failed");
+        assert_eq!(event["error"], "pds unavailable");
+        assert_eq!(event["attempt"], 2);
+        assert_eq!(event["span"]["name"], "tool_call");
+        assert_eq!(event["span"]["tool"], "create_fact");
+    }
+
+    #[test]
+    fn test_text_format_is_not_json() {
+        let output = capture(LogFormat::Text);
+
+        assert!(output.contains("tool failed"));
+        assert!(serde_json::from_str::<serde_json::Value>(output.trim()).is_err());
+    }
+}
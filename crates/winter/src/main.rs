@@ -4,28 +4,23 @@
 //! - `daemon`: Main loop (notification polling, scheduler)
 //! - `mcp-server`: MCP server mode for Claude Code
 //! - `web`: Read-only observation web UI
+//! - `mcp-schema`: Dump the MCP tool catalog as JSON
 //! - `bootstrap`: Initialize identity and rules
+//! - `doctor`: Check that PDS, firehose, Deno, secrets and Soufflé are wired up
+
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, Subcommand};
+use config::{AccountConfig, DaemonConfig, McpConfig, WebConfig, WinterConfig};
+use logging::LogFormat;
 use miette::Result;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-/// Parse boolean from environment variable, accepting common truthy values.
-/// Accepts "1", "true", "yes", "on" (case-insensitive) as true.
-/// Accepts "0", "false", "no", "off", "" (case-insensitive) as false.
-fn parse_bool_env(s: &str) -> Result<bool, String> {
-    match s.to_lowercase().as_str() {
-        "1" | "true" | "yes" | "on" => Ok(true),
-        "0" | "false" | "no" | "off" | "" => Ok(false),
-        _ => Err(format!(
-            "invalid boolean value '{}', expected 1/true/yes/on or 0/false/no/off",
-            s
-        )),
-    }
-}
 
+mod awaken;
 mod bootstrap;
+mod config;
 mod daemon;
+mod doctor;
+mod logging;
 mod migrate;
 pub mod trigger_engine;
 
@@ -33,6 +28,21 @@ pub mod trigger_engine;
 #[command(name = "winter")]
 #[command(about = "Autonomous Bluesky Agent", long_about = None)]
 struct Cli {
+    /// Config file; settings in it are overridden by WINTER_* environment
+    /// variables and then by flags (default: ./winter.toml if present)
+    #[arg(long, global = true, env = "WINTER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Log output format
+    #[arg(
+        long,
+        global = true,
+        env = "WINTER_LOG_FORMAT",
+        value_enum,
+        default_value_t
+    )]
+    log_format: LogFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -41,109 +51,52 @@ struct Cli {
 enum Commands {
     /// Run the main daemon (notification polling, scheduler)
     Daemon {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
-
-        /// Notification poll interval in seconds
-        #[arg(long, default_value = "5")]
-        poll_interval: u64,
-
-        /// Follower sync interval in seconds (for populating is_followed_by predicate).
-        /// New followers are detected immediately via Follow notifications.
-        /// This full sync is mainly for reconciliation (catching unfollows).
-        #[arg(long, default_value = "86400")]
-        follower_sync_interval: u64,
-
-        /// Fast-forward notification and DM cursors to current time on startup.
-        /// This skips all existing notifications/DMs and only processes new ones.
-        /// Accepts "1", "true", "yes", or any non-empty value.
-        #[arg(long, env = "WINTER_FAST_FORWARD", value_parser = parse_bool_env, default_value = "false")]
-        fast_forward: bool,
+        #[command(flatten)]
+        account: AccountConfig,
+
+        #[command(flatten)]
+        daemon: DaemonConfig,
     },
 
     /// Run the MCP server (for Claude Code) using stdio transport
     McpServer {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
+        #[command(flatten)]
+        account: AccountConfig,
     },
 
     /// Run the MCP server with HTTP transport (persistent, for Docker)
     McpServerHttp {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
+        #[command(flatten)]
+        account: AccountConfig,
 
-        /// HTTP server port
-        #[arg(long, default_value = "3847")]
-        port: u16,
+        #[command(flatten)]
+        mcp: McpConfig,
     },
 
     /// Run the web UI server
     Web {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
+        #[command(flatten)]
+        account: AccountConfig,
 
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
-
-        /// Web server port
-        #[arg(long, default_value = "8080")]
-        port: u16,
+        #[command(flatten)]
+        web: WebConfig,
+    },
 
-        /// Static files directory
-        #[arg(long)]
-        static_dir: Option<String>,
+    /// Print a JSON catalog of all built-in MCP tools and their input schemas
+    McpSchema {
+        /// Write the catalog to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
     },
 
     /// Initialize identity and default rules
     Bootstrap {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
+        #[command(flatten)]
+        account: AccountConfig,
 
         /// Operator DID (the human who controls this instance)
-        #[arg(long, env = "WINTER_OPERATOR_DID")]
-        operator_did: String,
+        #[arg(long)]
+        operator_did: Option<String>,
 
         /// Overwrite existing identity if it exists
         #[arg(long)]
@@ -160,36 +113,34 @@ enum Commands {
         /// Initial self-description
         #[arg(long)]
         self_description: Option<String>,
+
+        /// Identity document (YAML or JSON) with the self-description,
+        /// values, interests, beliefs, guidelines and boundaries to seed
+        #[arg(long, conflicts_with_all = ["values", "interests", "self_description"])]
+        from_file: Option<PathBuf>,
+
+        /// Report how the PDS differs from what bootstrap would write,
+        /// without writing anything
+        #[arg(long, conflicts_with = "overwrite")]
+        dry_run: bool,
+    },
+
+    /// Check PDS login, DID resolution, firehose, Deno, secrets and Soufflé
+    Doctor {
+        #[command(flatten)]
+        account: AccountConfig,
     },
 
     /// Migrate identity from legacy format to directives (deprecated, use `migrate` instead)
     MigrateIdentity {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
+        #[command(flatten)]
+        account: AccountConfig,
     },
 
     /// Run data migrations
     Migrate {
-        /// PDS URL
-        #[arg(long, env = "WINTER_PDS_URL")]
-        pds_url: String,
-
-        /// Account handle
-        #[arg(long, env = "WINTER_HANDLE")]
-        handle: String,
-
-        /// App password
-        #[arg(long, env = "WINTER_APP_PASSWORD")]
-        app_password: String,
+        #[command(flatten)]
+        account: AccountConfig,
 
         /// Migration name to run
         #[arg(value_name = "MIGRATION")]
@@ -211,101 +162,238 @@ enum Commands {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "winter=info".to_string()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     let cli = Cli::parse();
+    logging::init(cli.log_format);
+
+    let config = WinterConfig::load(cli.config.as_deref()).map_err(|e| miette::miette!("{}", e))?;
+    let resolve = |account: AccountConfig| {
+        let config = config.clone().overlay(WinterConfig {
+            account,
+            ..Default::default()
+        });
+        config
+            .account
+            .require()
+            .map_err(|e| miette::miette!("{}", e))
+    };
 
     match cli.command {
         Commands::Daemon {
-            pds_url,
-            handle,
-            app_password,
-            poll_interval,
-            follower_sync_interval,
-            fast_forward,
+            account,
+            daemon: daemon_config,
         } => {
-            daemon::run(
-                &pds_url,
-                &handle,
-                &app_password,
+            let account = resolve(account)?;
+            let daemon_config = config.daemon.clone().overlay(daemon_config);
+            let poll_interval = daemon_config
+                .poll_interval
+                .unwrap_or(config::DEFAULT_POLL_INTERVAL);
+            let max_poll_interval = daemon_config
+                .max_poll_interval
+                .unwrap_or(daemon::DEFAULT_NOTIF_POLL_MAX_INTERVAL);
+            let queue_high_watermark = daemon_config
+                .queue_high_watermark
+                .unwrap_or(daemon::DEFAULT_QUEUE_HIGH_WATERMARK);
+            let queue_low_watermark = daemon_config
+                .queue_low_watermark
+                .unwrap_or(daemon::DEFAULT_QUEUE_LOW_WATERMARK);
+            if queue_low_watermark > queue_high_watermark {
+                return Err(miette::miette!(
+                    "--queue-low-watermark ({}) must not exceed --queue-high-watermark ({})",
+                    queue_low_watermark,
+                    queue_high_watermark
+                ));
+            }
+            if max_poll_interval < poll_interval {
+                return Err(miette::miette!(
+                    "--max-poll-interval ({}) must not be less than --poll-interval ({})",
+                    max_poll_interval,
+                    poll_interval
+                ));
+            }
+            daemon::run_with_config(daemon::DaemonConfig {
+                pds_url: account.pds_url,
+                handle: account.handle,
+                app_password: account.app_password,
                 poll_interval,
-                follower_sync_interval,
-                fast_forward,
+                mcp_config_path: daemon::mcp_config_path(daemon_config.mcp_url.is_some()),
+                follower_sync_interval: daemon_config
+                    .follower_sync_interval
+                    .unwrap_or(config::DEFAULT_FOLLOWER_SYNC_INTERVAL),
+                fast_forward: daemon_config.fast_forward.unwrap_or(false),
+                dm_poll_interval: daemon_config.dm_poll_interval,
+                notif_poll_interval: daemon_config.notif_poll_interval,
+                notif_poll_max_interval: Some(max_poll_interval),
+                trigger_interval: daemon_config.trigger_interval,
+                stall_timeout: daemon_config.stall_timeout,
+                mcp_url: daemon_config.mcp_url,
+                workspace: config.workspace.clone(),
+                config_file: WinterConfig::config_file(cli.config.as_deref())
+                    .map(|path| std::path::absolute(&path))
+                    .transpose()
+                    .map_err(|e| miette::miette!("{}", e))?,
+                mcp_server_env_vars: config.mcp_server_env_vars(),
+                watchdog: daemon::WatchdogConfig {
+                    queue_pressure: daemon::QueuePressureConfig {
+                        high_watermark: queue_high_watermark,
+                        low_watermark: queue_low_watermark,
+                    },
+                    budget: daemon::SessionBudget {
+                        max_cost_usd: daemon_config.session_max_cost_usd,
+                        max_turns: daemon_config.session_max_turns,
+                    },
+                },
+                thinking: daemon_config.record_thinking.unwrap_or_default(),
+                awaken: daemon::AwakenConfig {
+                    goal_strategy: daemon_config.awaken_goal_strategy.unwrap_or_default(),
+                    quiet_period: daemon_config
+                        .awaken_quiet_secs
+                        .filter(|&secs| secs > 0)
+                        .map(std::time::Duration::from_secs),
+                },
+            })
+            .await
+        }
+
+        Commands::McpServer { account } => {
+            let account = resolve(account)?;
+            run_mcp_server(
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
+                config.secrets_path.as_deref(),
+                &config.secret_sources(),
+                config.tool_settings(),
             )
             .await
         }
 
-        Commands::McpServer {
-            pds_url,
-            handle,
-            app_password,
-        } => run_mcp_server(&pds_url, &handle, &app_password).await,
-
-        Commands::McpServerHttp {
-            pds_url,
-            handle,
-            app_password,
-            port,
-        } => run_mcp_server_http(&pds_url, &handle, &app_password, port).await,
-
-        Commands::Web {
-            pds_url,
-            handle,
-            app_password,
-            port,
-            static_dir,
-        } => {
-            run_web_server(&pds_url, &handle, &app_password, port, static_dir.as_deref()).await
+        Commands::McpServerHttp { account, mcp } => {
+            let account = resolve(account)?;
+            let mcp = config.mcp.clone().overlay(mcp);
+            let firehose_stale_after = mcp
+                .firehose_stale_secs
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(winter_mcp::http::DEFAULT_FIREHOSE_STALE_AFTER);
+            let http_config = winter_mcp::http::HttpServerConfig::new(
+                mcp.port.unwrap_or(config::DEFAULT_MCP_PORT),
+            )
+            .with_metrics(mcp.metrics.unwrap_or(false))
+            .with_auth_token(mcp.auth_token)
+            .with_bind_public(mcp.bind_public.unwrap_or(false))
+            .with_firehose_stale_after(firehose_stale_after)
+            .with_thought_overflow_policy(
+                mcp.thought_overflow
+                    .unwrap_or(winter_mcp::thought_queue::OverflowPolicy::DropNewest),
+            )
+            .with_thought_batch_size(mcp.thought_batch_size.unwrap_or(1))
+            .with_thought_max_bytes(
+                mcp.thought_max_bytes
+                    .unwrap_or(winter_mcp::thought_queue::DEFAULT_MAX_THOUGHT_CONTENT_BYTES),
+            )
+            .with_inbox_path(mcp.inbox_path);
+            run_mcp_server_http(
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
+                config.secrets_path.as_deref(),
+                &config.secret_sources(),
+                config.tool_settings(),
+                http_config,
+            )
+            .await
         }
 
+        Commands::Web { account, web } => {
+            let account = resolve(account)?;
+            let web = config.web.clone().overlay(web);
+            run_web_server(
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
+                config.secrets_path.as_deref(),
+                web.port.unwrap_or(config::DEFAULT_WEB_PORT),
+                web.static_dir.as_deref(),
+            )
+            .await
+        }
+
+        Commands::McpSchema { output } => run_mcp_schema(output.as_deref()),
+
         Commands::Bootstrap {
-            pds_url,
-            handle,
-            app_password,
+            account,
             operator_did,
             overwrite,
             values,
             interests,
             self_description,
+            from_file,
+            dry_run,
         } => {
+            let account = resolve(account)?;
+            let operator_did = config
+                .clone()
+                .overlay(WinterConfig {
+                    operator_did,
+                    ..Default::default()
+                })
+                .require_operator_did()
+                .map_err(|e| miette::miette!("{}", e))?;
+            let seed = match from_file {
+                Some(path) => bootstrap::IdentitySeed::from_file(&path)?,
+                None => bootstrap::IdentitySeed::from_flags(values, interests, self_description),
+            };
+            if dry_run {
+                let report = bootstrap::check(
+                    &account.pds_url,
+                    &account.handle,
+                    &account.app_password,
+                    &operator_did,
+                    &seed,
+                )
+                .await?;
+                print!("{}", report);
+                return Ok(());
+            }
             bootstrap::run(
-                &pds_url,
-                &handle,
-                &app_password,
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
                 &operator_did,
                 overwrite,
-                values,
-                interests,
-                self_description,
+                seed,
+            )
+            .await
+        }
+
+        Commands::Doctor { account } => {
+            let account = resolve(account)?;
+            doctor::run(
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
+                config.secrets_path.as_deref(),
+                &config.secret_sources(),
             )
             .await
         }
 
-        Commands::MigrateIdentity {
-            pds_url,
-            handle,
-            app_password,
-        } => migrate::run(&pds_url, &handle, &app_password).await,
+        Commands::MigrateIdentity { account } => {
+            let account = resolve(account)?;
+            migrate::run(&account.pds_url, &account.handle, &account.app_password).await
+        }
 
         Commands::Migrate {
-            pds_url,
-            handle,
-            app_password,
+            account,
             migration,
             list,
             dry_run,
             all,
         } => {
+            let account = resolve(account)?;
             migrate::run_migrate_command(
-                &pds_url,
-                &handle,
-                &app_password,
+                &account.pds_url,
+                &account.handle,
+                &account.app_password,
                 migration.as_deref(),
                 list,
                 dry_run,
@@ -316,11 +404,33 @@ async fn main() -> Result<()> {
     }
 }
 
-async fn run_mcp_server(pds_url: &str, handle: &str, app_password: &str) -> Result<()> {
+fn run_mcp_schema(output: Option<&str>) -> Result<()> {
+    let catalog = winter_mcp::ToolRegistry::to_json_schema_catalog();
+    let json = serde_json::to_string_pretty(&catalog)
+        .map_err(|e| miette::miette!("failed to serialize tool catalog: {}", e))?;
+
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")
+            .map_err(|e| miette::miette!("failed to write {}: {}", path, e)),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+async fn run_mcp_server(
+    pds_url: &str,
+    handle: &str,
+    app_password: &str,
+    secrets_path: Option<&Path>,
+    secret_sources: &[SecretSource],
+    settings: winter_mcp::ToolSettings,
+) -> Result<()> {
     use std::sync::Arc;
     use winter_atproto::{AtprotoClient, RepoCache, SyncCoordinator};
     use winter_datalog::DatalogCache;
-    use winter_mcp::{BlueskyClient, DenoExecutor, McpServer, SecretManager, tools::ToolRegistry};
+    use winter_mcp::{BlueskyClient, DenoExecutor, McpServer, SecretSource, tools::ToolRegistry};
 
     // Create two clients - one for tools, one for sync
     let client = AtprotoClient::new(pds_url);
@@ -348,6 +458,7 @@ async fn run_mcp_server(pds_url: &str, handle: &str, app_password: &str) -> Resu
         .map_err(|e| miette::miette!("failed to create Bluesky client: {}", e))?;
 
     let tools = ToolRegistry::new(client).with_bluesky(bluesky);
+    tools.set_settings(settings.clone()).await;
 
     // Set up RepoCache and DatalogCache for derived predicates
     let repo_cache = RepoCache::new();
@@ -386,23 +497,22 @@ async fn run_mcp_server(pds_url: &str, handle: &str, app_password: &str) -> Resu
 
     tracing::info!("datalog cache initialized for MCP server");
 
-    // Load secrets from configured path or default
-    let secrets_path = std::env::var("WINTER_SECRETS_PATH")
-        .ok()
-        .map(std::path::PathBuf::from);
-    match SecretManager::load(secrets_path).await {
-        Ok(secrets) => {
-            tools.set_secrets(secrets).await;
-            tracing::info!("loaded secrets manager");
+    // Layer the configured secret providers for custom tools
+    match SecretSource::build(secret_sources, secrets_path.map(Path::to_path_buf)).await {
+        Ok(provider) => {
+            tracing::info!(provider = provider.name(), "loaded secret provider");
+            tools.set_secret_provider(provider).await;
         }
         Err(e) => {
-            tracing::warn!(error = %e, "failed to load secrets manager, custom tools will have no secrets");
+            tracing::warn!(error = %e, "failed to load secret provider, custom tools will have no secrets");
         }
     }
 
     // Set up Deno executor for custom tools
     if DenoExecutor::is_available().await {
-        tools.set_deno(DenoExecutor::default()).await;
+        tools
+            .set_deno(DenoExecutor::default().with_workspace(settings.workspace.clone()))
+            .await;
         tracing::info!("Deno executor available for custom tools");
     } else {
         tracing::warn!("Deno not found, custom tools will not be executable");
@@ -411,6 +521,12 @@ async fn run_mcp_server(pds_url: &str, handle: &str, app_password: &str) -> Resu
     let server = McpServer::new(tools);
     server.run().await.map_err(|e| miette::miette!("{}", e))?;
 
+    // stdin closed: write out whatever thoughts are still queued
+    server
+        .tools()
+        .flush_thoughts(winter_mcp::http::THOUGHT_FLUSH_TIMEOUT)
+        .await;
+
     Ok(())
 }
 
@@ -418,16 +534,19 @@ async fn run_mcp_server_http(
     pds_url: &str,
     handle: &str,
     app_password: &str,
-    port: u16,
+    secrets_path: Option<&Path>,
+    secret_sources: &[SecretSource],
+    settings: winter_mcp::ToolSettings,
+    config: winter_mcp::http::HttpServerConfig,
 ) -> Result<()> {
     use std::sync::Arc;
     use winter_atproto::{AtprotoClient, RepoCache, SyncCoordinator};
     use winter_datalog::DatalogCache;
     use winter_mcp::{
-        BlueskyClient, DenoExecutor, McpServer, SecretManager, http, tools::ToolRegistry,
+        BlueskyClient, DenoExecutor, McpServer, SecretSource, http, tools::ToolRegistry,
     };
 
-    tracing::info!("starting MCP HTTP server on port {}", port);
+    tracing::info!("starting MCP HTTP server on port {}", config.port);
 
     // Create two clients - one for tools, one for sync
     let client = AtprotoClient::new(pds_url);
@@ -455,6 +574,7 @@ async fn run_mcp_server_http(
         .map_err(|e| miette::miette!("failed to create Bluesky client: {}", e))?;
 
     let tools = ToolRegistry::new(client).with_bluesky(bluesky);
+    tools.set_settings(settings.clone()).await;
 
     // Set up RepoCache and DatalogCache for derived predicates
     let repo_cache = RepoCache::new();
@@ -476,12 +596,18 @@ async fn run_mcp_server_http(
     // Start sync coordinator to populate repo cache from PDS
     let sync_coordinator = SyncCoordinator::new(sync_client, &did, Arc::clone(&repo_cache));
 
-    // Create a shutdown channel (HTTP server runs until process exits)
-    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    // Shutdown channel shared by the sync coordinator and the HTTP server,
+    // flipped on SIGTERM/SIGINT
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        tracing::info!("received shutdown signal");
+        let _ = shutdown_tx.send(true);
+    });
 
     // Start the sync (list_all_records + Jetstream for live updates)
-    let _sync_handle = sync_coordinator
-        .start(shutdown_rx)
+    let sync_handle = sync_coordinator
+        .start(shutdown_rx.clone())
         .await
         .map_err(|e| miette::miette!("failed to start sync: {}", e))?;
 
@@ -494,23 +620,22 @@ async fn run_mcp_server_http(
 
     tracing::info!("datalog cache initialized for MCP HTTP server");
 
-    // Load secrets from configured path or default
-    let secrets_path = std::env::var("WINTER_SECRETS_PATH")
-        .ok()
-        .map(std::path::PathBuf::from);
-    match SecretManager::load(secrets_path).await {
-        Ok(secrets) => {
-            tools.set_secrets(secrets).await;
-            tracing::info!("loaded secrets manager");
+    // Layer the configured secret providers for custom tools
+    match SecretSource::build(secret_sources, secrets_path.map(Path::to_path_buf)).await {
+        Ok(provider) => {
+            tracing::info!(provider = provider.name(), "loaded secret provider");
+            tools.set_secret_provider(provider).await;
         }
         Err(e) => {
-            tracing::warn!(error = %e, "failed to load secrets manager, custom tools will have no secrets");
+            tracing::warn!(error = %e, "failed to load secret provider, custom tools will have no secrets");
         }
     }
 
     // Set up Deno executor for custom tools
     if DenoExecutor::is_available().await {
-        tools.set_deno(DenoExecutor::default()).await;
+        tools
+            .set_deno(DenoExecutor::default().with_workspace(settings.workspace.clone()))
+            .await;
         tracing::info!("Deno executor available for custom tools");
     } else {
         tracing::warn!("Deno not found, custom tools will not be executable");
@@ -518,18 +643,55 @@ async fn run_mcp_server_http(
 
     let server = McpServer::new(tools);
 
-    // Run the HTTP server (blocks until shutdown)
-    http::run_server(server, port)
+    // Run the HTTP server (blocks until shutdown and in-flight requests drain)
+    http::run_server(server, config, shutdown_rx)
         .await
         .map_err(|e| miette::miette!("HTTP server error: {}", e))?;
 
+    // Let the sync coordinator observe the shutdown and stop cleanly
+    if let Err(e) = sync_handle.await {
+        tracing::warn!(error = %e, "sync coordinator task failed during shutdown");
+    }
+
     Ok(())
 }
 
+/// Wait for SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %e, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
 async fn run_web_server(
     pds_url: &str,
     handle: &str,
     app_password: &str,
+    secrets_path: Option<&Path>,
     port: u16,
     static_dir: Option<&str>,
 ) -> Result<()> {
@@ -546,10 +708,7 @@ async fn run_web_server(
     let did = client.did().await;
 
     // Load secret manager for the secrets page
-    let secrets_path = std::env::var("WINTER_SECRETS_PATH")
-        .ok()
-        .map(std::path::PathBuf::from);
-    let secrets = match SecretManager::load(secrets_path).await {
+    let secrets = match SecretManager::load(secrets_path.map(Path::to_path_buf)).await {
         Ok(s) => {
             tracing::info!("loaded secrets manager for web UI");
             Some(s)
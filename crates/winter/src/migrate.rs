@@ -1,21 +1,29 @@
 //! Migration framework for Winter data transformations.
 //!
 //! This module provides a general-purpose migration framework that supports:
-//! - `--dry-run` mode to preview changes without applying them
+//! - Typed plans of record writes, rendered as a diff by `--dry-run` and
+//!   executed verbatim otherwise
 //! - Multiple named migrations that can be run independently
+//! - Dependency ordering between migrations
+//! - Applied-migration tracking in the PDS, so each migration runs once
 //! - Extensible design for future data migrations
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use miette::Result;
+use serde::Serialize;
+use serde_json::Value;
 use tracing::info;
 
 use winter_atproto::{
-    AtUri, AtprotoClient, DIRECTIVE_COLLECTION, Directive, DirectiveKind, FACT_COLLECTION, Fact,
-    IDENTITY_COLLECTION, IDENTITY_KEY, Identity, LegacyIdentity, NOTE_COLLECTION, Note,
-    RULE_COLLECTION, Rule, Tid, WIKI_ENTRY_COLLECTION, WikiEntry,
+    AppliedMigration, AtUri, AtprotoClient, AtprotoError, DIRECTIVE_COLLECTION, Directive,
+    DirectiveKind, FACT_COLLECTION, Fact, IDENTITY_COLLECTION, IDENTITY_KEY, Identity,
+    LegacyIdentity, MIGRATION_STATE_COLLECTION, MIGRATION_STATE_KEY, MigrationState,
+    NOTE_COLLECTION, Note, RULE_COLLECTION, Rule, THOUGHT_COLLECTION, Thought, Tid,
+    WIKI_ENTRY_COLLECTION, WikiEntry,
 };
 use winter_datalog::DerivedFactGenerator;
 
@@ -23,17 +31,140 @@ use winter_datalog::DerivedFactGenerator;
 // Migration Framework Types
 // =============================================================================
 
-/// Preview of what a migration would change.
-pub struct MigrationPreview {
-    /// Number of records that would be updated.
-    pub records_to_update: usize,
-    /// Human-readable descriptions of changes.
-    pub changes: Vec<String>,
+/// A single record write planned by a migration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordOp {
+    /// Create a new record.
+    Create {
+        collection: &'static str,
+        rkey: String,
+        record: Value,
+    },
+    /// Replace an existing record.
+    Update {
+        collection: &'static str,
+        rkey: String,
+        before: Value,
+        after: Value,
+    },
+    /// Delete an existing record.
+    Delete {
+        collection: &'static str,
+        rkey: String,
+        before: Value,
+    },
+}
+
+/// The complete set of writes a migration will make.
+///
+/// The runner renders the plan in dry-run mode and executes exactly the same
+/// plan otherwise, so a preview can never drift from what gets applied.
+#[derive(Debug, Default)]
+pub struct MigrationPlan {
+    /// Writes to perform, in order.
+    pub ops: Vec<RecordOp>,
+    /// Problems found while planning (e.g. unresolvable references).
+    /// The affected records are left out of `ops`.
+    pub errors: Vec<String>,
+}
+
+impl MigrationPlan {
+    /// Whether there is nothing to write and nothing to report.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty() && self.errors.is_empty()
+    }
+
+    /// Plan creating a record.
+    fn create<T: Serialize>(
+        &mut self,
+        collection: &'static str,
+        rkey: String,
+        record: &T,
+    ) -> Result<()> {
+        self.ops.push(RecordOp::Create {
+            collection,
+            rkey,
+            record: to_value(record)?,
+        });
+        Ok(())
+    }
+
+    /// Plan replacing a record.
+    fn update<B: Serialize, A: Serialize>(
+        &mut self,
+        collection: &'static str,
+        rkey: String,
+        before: &B,
+        after: &A,
+    ) -> Result<()> {
+        self.ops.push(RecordOp::Update {
+            collection,
+            rkey,
+            before: to_value(before)?,
+            after: to_value(after)?,
+        });
+        Ok(())
+    }
+
+    /// Plan deleting a record.
+    fn delete<T: Serialize>(
+        &mut self,
+        collection: &'static str,
+        rkey: String,
+        before: &T,
+    ) -> Result<()> {
+        self.ops.push(RecordOp::Delete {
+            collection,
+            rkey,
+            before: to_value(before)?,
+        });
+        Ok(())
+    }
+
+    /// Render the plan as a human-readable diff.
+    ///
+    /// Creates and deletes show the whole record; updates show only the
+    /// top-level fields that change, as `before → after`.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for op in &self.ops {
+            match op {
+                RecordOp::Create {
+                    collection,
+                    rkey,
+                    record,
+                } => {
+                    let _ = writeln!(out, "+ create {}/{}", collection, rkey);
+                    write_indented(&mut out, record);
+                }
+                RecordOp::Update {
+                    collection,
+                    rkey,
+                    before,
+                    after,
+                } => {
+                    let _ = writeln!(out, "~ update {}/{}", collection, rkey);
+                    for (field, old, new) in changed_fields(before, after) {
+                        let _ = writeln!(out, "    {}: {} → {}", field, old, new);
+                    }
+                }
+                RecordOp::Delete {
+                    collection,
+                    rkey,
+                    before,
+                } => {
+                    let _ = writeln!(out, "- delete {}/{}", collection, rkey);
+                    write_indented(&mut out, before);
+                }
+            }
+        }
+        out
+    }
 }
 
 /// Result of applying a migration.
 pub struct MigrationResult {
-    /// Number of records that were updated.
+    /// Number of records that were written.
     pub records_updated: usize,
     /// Errors encountered (non-fatal, migration continued).
     pub errors: Vec<String>,
@@ -42,26 +173,119 @@ pub struct MigrationResult {
 /// A migration that can be applied to the PDS.
 #[async_trait]
 pub trait Migration: Send + Sync {
-    /// Unique name for this migration.
+    /// Unique name for this migration, also used as its id in the ledger.
     fn name(&self) -> &'static str;
 
     /// Human-readable description.
     fn description(&self) -> &'static str;
 
-    /// Check if this migration needs to be applied.
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool>;
+    /// Names of migrations that must be applied before this one.
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Compute every write this migration would make. An empty plan means
+    /// the migration has nothing to do.
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan>;
+}
+
+/// Destination for the writes in a migration plan.
+#[async_trait]
+pub trait RecordWriter: Send + Sync {
+    async fn create(&self, collection: &str, rkey: &str, record: &Value) -> Result<()>;
+    async fn put(&self, collection: &str, rkey: &str, record: &Value) -> Result<()>;
+    async fn delete(&self, collection: &str, rkey: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl RecordWriter for AtprotoClient {
+    async fn create(&self, collection: &str, rkey: &str, record: &Value) -> Result<()> {
+        self.create_record(collection, Some(rkey), record)
+            .await
+            .map_err(|e| miette::miette!("{}", e))?;
+        Ok(())
+    }
+
+    async fn put(&self, collection: &str, rkey: &str, record: &Value) -> Result<()> {
+        self.put_record(collection, rkey, record)
+            .await
+            .map_err(|e| miette::miette!("{}", e))?;
+        Ok(())
+    }
 
-    /// Preview what would change (dry-run).
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview>;
+    async fn delete(&self, collection: &str, rkey: &str) -> Result<()> {
+        self.delete_record(collection, rkey)
+            .await
+            .map_err(|e| miette::miette!("{}", e))
+    }
+}
 
-    /// Apply the migration.
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult>;
+/// Execute a plan's writes in order.
+///
+/// Stops at the first failed write; planning errors are carried through to
+/// the result.
+pub async fn apply_plan(
+    plan: &MigrationPlan,
+    writer: &dyn RecordWriter,
+) -> Result<MigrationResult> {
+    for op in &plan.ops {
+        match op {
+            RecordOp::Create {
+                collection,
+                rkey,
+                record,
+            } => writer.create(collection, rkey, record).await?,
+            RecordOp::Update {
+                collection,
+                rkey,
+                after,
+                ..
+            } => writer.put(collection, rkey, after).await?,
+            RecordOp::Delete {
+                collection, rkey, ..
+            } => writer.delete(collection, rkey).await?,
+        }
+    }
+    Ok(MigrationResult {
+        records_updated: plan.ops.len(),
+        errors: plan.errors.clone(),
+    })
 }
 
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
+/// Serialize a record for a migration plan.
+fn to_value<T: Serialize>(record: &T) -> Result<Value> {
+    serde_json::to_value(record).map_err(|e| miette::miette!("{}", e))
+}
+
+/// Append a record as indented, pretty-printed JSON.
+fn write_indented(out: &mut String, record: &Value) {
+    let pretty = serde_json::to_string_pretty(record).unwrap_or_default();
+    for line in pretty.lines() {
+        let _ = writeln!(out, "    {}", line);
+    }
+}
+
+/// Top-level fields that differ between two records, as compact JSON.
+/// Missing fields render as `(absent)`.
+fn changed_fields(before: &Value, after: &Value) -> Vec<(String, String, String)> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let render = |v: Option<&Value>| v.map_or_else(|| "(absent)".to_string(), Value::to_string);
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|k| before.get(*k) != after.get(*k))
+        .map(|k| (k.clone(), render(before.get(k)), render(after.get(k))))
+        .collect()
+}
+
 /// Extract the rkey from an AT URI.
 fn extract_rkey(uri: &str) -> String {
     AtUri::extract_rkey(uri).to_string()
@@ -110,90 +334,27 @@ impl Migration for FactReferencesToUris {
         "Convert fact source and supersedes fields from CID to AT URI format"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        let facts = client
-            .list_all_records::<Fact>(FACT_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        for record in facts {
-            if record
-                .value
-                .source
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s))
-            {
-                return Ok(true);
-            }
-            if record
-                .value
-                .supersedes
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s))
-            {
-                return Ok(true);
-            }
-        }
-        Ok(false)
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
-        let facts = client
-            .list_all_records::<Fact>(FACT_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-        let mut changes = Vec::new();
-        let mut count = 0;
-
-        for record in facts {
-            let rkey = extract_rkey(&record.uri);
-            let needs_source = record
-                .value
-                .source
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s));
-            let needs_supersedes = record
-                .value
-                .supersedes
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s));
-
-            if needs_source || needs_supersedes {
-                count += 1;
-                let fields: Vec<&str> = [
-                    needs_source.then_some("source"),
-                    needs_supersedes.then_some("supersedes"),
-                ]
-                .into_iter()
-                .flatten()
-                .collect();
-                changes.push(format!(
-                    "Fact {} ({}): convert {} to AT URI",
-                    record.value.predicate,
-                    rkey,
-                    fields.join(", ")
-                ));
-            }
-        }
-
-        Ok(MigrationPreview {
-            records_to_update: count,
-            changes,
-        })
+    fn dependencies(&self) -> &'static [&'static str] {
+        // Rewriting facts changes their CIDs, so notes must resolve their
+        // CID references first.
+        &["note-related-facts-to-uris"]
     }
 
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
         let (cid_map, _) = build_reference_maps(client, FACT_COLLECTION).await?;
         let facts = client
             .list_all_records::<Fact>(FACT_COLLECTION)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
-        let mut updated = 0;
-        let mut errors = Vec::new();
+        let mut plan = MigrationPlan::default();
 
         for record in facts {
+            // Tombstoned facts stay as they were until restored
+            if record.value.deleted_at.is_some() {
+                continue;
+            }
             let rkey = extract_rkey(&record.uri);
-            let mut fact = record.value;
+            let mut fact = record.value.clone();
             let mut changed = false;
 
             if let Some(ref source) = fact.source
@@ -203,7 +364,7 @@ impl Migration for FactReferencesToUris {
                     fact.source = Some(uri.clone());
                     changed = true;
                 } else {
-                    errors.push(format!(
+                    plan.errors.push(format!(
                         "Fact {}: Could not resolve source CID {}",
                         rkey, source
                     ));
@@ -217,7 +378,7 @@ impl Migration for FactReferencesToUris {
                     fact.supersedes = Some(uri.clone());
                     changed = true;
                 } else {
-                    errors.push(format!(
+                    plan.errors.push(format!(
                         "Fact {}: Could not resolve supersedes CID {}",
                         rkey, supersedes
                     ));
@@ -225,18 +386,11 @@ impl Migration for FactReferencesToUris {
             }
 
             if changed {
-                client
-                    .put_record(FACT_COLLECTION, &rkey, &fact)
-                    .await
-                    .map_err(|e| miette::miette!("{}", e))?;
-                updated += 1;
+                plan.update(FACT_COLLECTION, rkey, &record.value, &fact)?;
             }
         }
 
-        Ok(MigrationResult {
-            records_updated: updated,
-            errors,
-        })
+        Ok(plan)
     }
 }
 
@@ -257,58 +411,7 @@ impl Migration for DirectiveSupersedesToUris {
         "Convert directive supersedes field from rkey to AT URI format"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        let directives = client
-            .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        for record in directives {
-            if record
-                .value
-                .supersedes
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s))
-            {
-                return Ok(true);
-            }
-        }
-        Ok(false)
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
-        let directives = client
-            .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-        let mut changes = Vec::new();
-        let mut count = 0;
-
-        for record in directives {
-            if record
-                .value
-                .supersedes
-                .as_ref()
-                .is_some_and(|s| needs_conversion(s))
-            {
-                count += 1;
-                let rkey = extract_rkey(&record.uri);
-                changes.push(format!(
-                    "Directive {:?} ({}): convert supersedes '{}' to AT URI",
-                    record.value.kind,
-                    rkey,
-                    record.value.supersedes.as_ref().unwrap()
-                ));
-            }
-        }
-
-        Ok(MigrationPreview {
-            records_to_update: count,
-            changes,
-        })
-    }
-
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
         let (_, rkey_map) = build_reference_maps(client, DIRECTIVE_COLLECTION).await?;
         let did = client
             .did()
@@ -318,12 +421,11 @@ impl Migration for DirectiveSupersedesToUris {
             .list_all_records::<Directive>(DIRECTIVE_COLLECTION)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
-        let mut updated = 0;
-        let errors = Vec::new();
+        let mut plan = MigrationPlan::default();
 
         for record in directives {
             let rkey = extract_rkey(&record.uri);
-            let mut directive = record.value;
+            let mut directive = record.value.clone();
 
             if let Some(ref supersedes) = directive.supersedes
                 && needs_conversion(supersedes)
@@ -334,18 +436,11 @@ impl Migration for DirectiveSupersedesToUris {
                 });
                 directive.supersedes = Some(uri);
                 directive.last_updated = Some(Utc::now());
-                client
-                    .put_record(DIRECTIVE_COLLECTION, &rkey, &directive)
-                    .await
-                    .map_err(|e| miette::miette!("{}", e))?;
-                updated += 1;
+                plan.update(DIRECTIVE_COLLECTION, rkey, &record.value, &directive)?;
             }
         }
 
-        Ok(MigrationResult {
-            records_updated: updated,
-            errors,
-        })
+        Ok(plan)
     }
 }
 
@@ -366,67 +461,21 @@ impl Migration for NoteRelatedFactsToUris {
         "Convert note relatedFacts from CID format to AT URI format"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        let notes = client
-            .list_all_records::<Note>(NOTE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        for record in notes {
-            for rf in &record.value.related_facts {
-                if needs_conversion(rf) {
-                    return Ok(true);
-                }
-            }
-        }
-        Ok(false)
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
-        let notes = client
-            .list_all_records::<Note>(NOTE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-        let mut changes = Vec::new();
-        let mut count = 0;
-
-        for record in notes {
-            let cid_refs: Vec<_> = record
-                .value
-                .related_facts
-                .iter()
-                .filter(|rf| needs_conversion(rf))
-                .collect();
-            if !cid_refs.is_empty() {
-                count += 1;
-                let rkey = extract_rkey(&record.uri);
-                changes.push(format!(
-                    "Note '{}' ({}): {} CID-format relatedFacts to convert",
-                    record.value.title,
-                    rkey,
-                    cid_refs.len()
-                ));
-            }
-        }
-
-        Ok(MigrationPreview {
-            records_to_update: count,
-            changes,
-        })
-    }
-
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
         let (cid_map, _) = build_reference_maps(client, FACT_COLLECTION).await?;
         let notes = client
             .list_all_records::<Note>(NOTE_COLLECTION)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
-        let mut updated = 0;
-        let mut errors = Vec::new();
+        let mut plan = MigrationPlan::default();
 
         for record in notes {
+            // Tombstoned notes stay as they were until restored
+            if record.value.deleted_at.is_some() {
+                continue;
+            }
             let rkey = extract_rkey(&record.uri);
-            let mut note = record.value;
+            let mut note = record.value.clone();
             let mut changed = false;
 
             for rf in &mut note.related_facts {
@@ -435,7 +484,7 @@ impl Migration for NoteRelatedFactsToUris {
                         *rf = uri.clone();
                         changed = true;
                     } else {
-                        errors.push(format!(
+                        plan.errors.push(format!(
                             "Note '{}': Could not resolve CID {}",
                             note.title, rf
                         ));
@@ -445,18 +494,11 @@ impl Migration for NoteRelatedFactsToUris {
 
             if changed {
                 note.last_updated = Utc::now();
-                client
-                    .put_record(NOTE_COLLECTION, &rkey, &note)
-                    .await
-                    .map_err(|e| miette::miette!("{}", e))?;
-                updated += 1;
+                plan.update(NOTE_COLLECTION, rkey, &record.value, &note)?;
             }
         }
 
-        Ok(MigrationResult {
-            records_updated: updated,
-            errors,
-        })
+        Ok(plan)
     }
 }
 
@@ -467,6 +509,36 @@ impl Migration for NoteRelatedFactsToUris {
 /// Migration: Convert legacy identity (values, interests, selfDescription) to directives.
 struct LegacyIdentityToDirectives;
 
+impl LegacyIdentityToDirectives {
+    /// Build a directive migrated from a legacy identity field.
+    fn migrated_directive(kind: DirectiveKind, content: &str, now: DateTime<Utc>) -> Directive {
+        Directive {
+            kind,
+            content: content.to_string(),
+            summary: None,
+            active: true,
+            confidence: None,
+            source: Some("migrated from legacy identity".to_string()),
+            supersedes: None,
+            tags: vec!["migrated".to_string()],
+            priority: 0,
+            created_at: now,
+            last_updated: None,
+        }
+    }
+
+    /// Record key for the `index`th directive migrated from an identity
+    /// created at `created_at`.
+    ///
+    /// Derived from the identity rather than the clock, so a dry run shows
+    /// the keys a real run writes and a retried run can't duplicate
+    /// directives it already created.
+    fn directive_rkey(created_at: DateTime<Utc>, index: usize) -> String {
+        let at = created_at + chrono::Duration::microseconds(index as i64);
+        Tid::from_datetime(at, 0).to_string()
+    }
+}
+
 #[async_trait]
 impl Migration for LegacyIdentityToDirectives {
     fn name(&self) -> &'static str {
@@ -477,73 +549,18 @@ impl Migration for LegacyIdentityToDirectives {
         "Convert legacy identity format (values, interests, selfDescription) to directive records"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        // Try to load as legacy format
-        match client
-            .get_record::<LegacyIdentity>(IDENTITY_COLLECTION, IDENTITY_KEY)
-            .await
-        {
-            Ok(record) => {
-                let legacy = record.value;
-                // If any legacy fields are populated, needs migration
-                Ok(!legacy.values.is_empty()
-                    || !legacy.interests.is_empty()
-                    || !legacy.self_description.is_empty())
-            }
-            Err(winter_atproto::AtprotoError::NotFound { .. }) => Ok(false),
-            Err(e) => Err(miette::miette!("Failed to check identity: {}", e)),
-        }
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
-        let legacy = match client
-            .get_record::<LegacyIdentity>(IDENTITY_COLLECTION, IDENTITY_KEY)
-            .await
-        {
-            Ok(record) => record.value,
-            Err(winter_atproto::AtprotoError::NotFound { .. }) => {
-                return Ok(MigrationPreview {
-                    records_to_update: 0,
-                    changes: vec!["No identity record found".to_string()],
-                });
-            }
-            Err(e) => return Err(miette::miette!("Failed to get identity: {}", e)),
-        };
-
-        let mut changes = Vec::new();
-
-        if !legacy.self_description.is_empty() {
-            changes.push("Create self_concept directive from selfDescription".to_string());
-        }
-        for value in &legacy.values {
-            changes.push(format!("Create value directive: '{}'", value));
-        }
-        for interest in &legacy.interests {
-            changes.push(format!("Create interest directive: '{}'", interest));
-        }
-        if !changes.is_empty() {
-            changes.push("Update identity record to slim version".to_string());
-        }
-
-        let count = if changes.is_empty() { 0 } else { 1 }; // The identity record
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
+        let mut plan = MigrationPlan::default();
 
-        Ok(MigrationPreview {
-            records_to_update: count,
-            changes,
-        })
-    }
-
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
         // Load existing identity (try as legacy format)
         let legacy = match client
             .get_record::<LegacyIdentity>(IDENTITY_COLLECTION, IDENTITY_KEY)
             .await
         {
             Ok(record) => record.value,
-            Err(winter_atproto::AtprotoError::NotFound { .. }) => {
-                return Err(miette::miette!(
-                    "Identity not found. Run 'winter bootstrap' to create a new identity."
-                ));
+            Err(AtprotoError::NotFound { .. }) => {
+                info!("no identity record found; nothing to migrate");
+                return Ok(plan);
             }
             Err(e) => return Err(miette::miette!("Failed to get identity: {}", e)),
         };
@@ -554,10 +571,7 @@ impl Migration for LegacyIdentityToDirectives {
             && legacy.self_description.is_empty()
         {
             info!("identity appears to already be migrated (no legacy fields found)");
-            return Ok(MigrationResult {
-                records_updated: 0,
-                errors: vec![],
-            });
+            return Ok(plan);
         }
 
         info!(
@@ -568,102 +582,45 @@ impl Migration for LegacyIdentityToDirectives {
         );
 
         let now = Utc::now();
-        let mut directives_created = 0;
+        let mut created = 0;
+        let mut next_rkey = || {
+            created += 1;
+            Self::directive_rkey(legacy.created_at, created - 1)
+        };
 
         // Create self_concept directive from selfDescription
         if !legacy.self_description.is_empty() {
-            let directive = Directive {
-                kind: DirectiveKind::SelfConcept,
-                content: legacy.self_description.clone(),
-                summary: None,
-                active: true,
-                confidence: None,
-                source: Some("migrated from legacy identity".to_string()),
-                supersedes: None,
-                tags: vec!["migrated".to_string()],
-                priority: 0,
-                created_at: now,
-                last_updated: None,
-            };
-            let rkey = Tid::now().to_string();
-            client
-                .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
-                .await
-                .map_err(|e| miette::miette!("{}", e))?;
-            info!("created self_concept directive from selfDescription");
-            directives_created += 1;
+            let directive =
+                Self::migrated_directive(DirectiveKind::SelfConcept, &legacy.self_description, now);
+            plan.create(DIRECTIVE_COLLECTION, next_rkey(), &directive)?;
         }
 
         // Create value directives
         for value in &legacy.values {
-            let directive = Directive {
-                kind: DirectiveKind::Value,
-                content: value.clone(),
-                summary: None,
-                active: true,
-                confidence: None,
-                source: Some("migrated from legacy identity".to_string()),
-                supersedes: None,
-                tags: vec!["migrated".to_string()],
-                priority: 0,
-                created_at: now,
-                last_updated: None,
-            };
-            let rkey = Tid::now().to_string();
-            client
-                .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
-                .await
-                .map_err(|e| miette::miette!("{}", e))?;
-            info!(value = %value, "created value directive");
-            directives_created += 1;
+            let directive = Self::migrated_directive(DirectiveKind::Value, value, now);
+            plan.create(DIRECTIVE_COLLECTION, next_rkey(), &directive)?;
         }
 
         // Create interest directives
         for interest in &legacy.interests {
-            let directive = Directive {
-                kind: DirectiveKind::Interest,
-                content: interest.clone(),
-                summary: None,
-                active: true,
-                confidence: None,
-                source: Some("migrated from legacy identity".to_string()),
-                supersedes: None,
-                tags: vec!["migrated".to_string()],
-                priority: 0,
-                created_at: now,
-                last_updated: None,
-            };
-            let rkey = Tid::now().to_string();
-            client
-                .create_record(DIRECTIVE_COLLECTION, Some(&rkey), &directive)
-                .await
-                .map_err(|e| miette::miette!("{}", e))?;
-            info!(interest = %interest, "created interest directive");
-            directives_created += 1;
+            let directive = Self::migrated_directive(DirectiveKind::Interest, interest, now);
+            plan.create(DIRECTIVE_COLLECTION, next_rkey(), &directive)?;
         }
 
         // Update identity to slim version
         let slim_identity = Identity {
-            operator_did: legacy.operator_did,
+            operator_did: legacy.operator_did.clone(),
             created_at: legacy.created_at,
             last_updated: now,
         };
-
-        client
-            .put_record(IDENTITY_COLLECTION, IDENTITY_KEY, &slim_identity)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-        info!("updated identity record to slim version");
-
-        info!(
-            directives_created = directives_created,
-            "migration complete"
-        );
-
-        Ok(MigrationResult {
-            records_updated: directives_created + 1, // directives + identity record
-            errors: vec![],
-        })
+        plan.update(
+            IDENTITY_COLLECTION,
+            IDENTITY_KEY.to_string(),
+            &legacy,
+            &slim_identity,
+        )?;
+
+        Ok(plan)
     }
 }
 
@@ -806,6 +763,10 @@ impl RulePredicateArityMigration {
         let mut predicates: HashMap<String, usize> = HashMap::new();
 
         for record in facts {
+            // Tombstoned facts are invisible to queries
+            if record.value.deleted_at.is_some() {
+                continue;
+            }
             let predicate = &record.value.predicate;
             // New arity = args count + 1 for rkey
             let arity = record.value.args.len() + 1;
@@ -826,74 +787,7 @@ impl Migration for RulePredicateArityMigration {
         "Update rule bodies to add rkey argument (as _) to predicates that now include it"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        let user_predicates = Self::fetch_user_predicates(client).await?;
-        let arities = Self::build_arity_map(&user_predicates);
-
-        let rules = client
-            .list_all_records::<Rule>(RULE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        for record in rules {
-            for clause in &record.value.body {
-                if Self::clause_needs_update(clause, &arities).is_some() {
-                    return Ok(true);
-                }
-            }
-        }
-
-        Ok(false)
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
-        let user_predicates = Self::fetch_user_predicates(client).await?;
-        let arities = Self::build_arity_map(&user_predicates);
-
-        info!(
-            derived_count = Self::derived_predicates_with_arity().len(),
-            user_count = user_predicates.len(),
-            "built arity map"
-        );
-
-        let rules = client
-            .list_all_records::<Rule>(RULE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        let mut changes = Vec::new();
-        let mut count = 0;
-
-        for record in rules {
-            let (updated_body, changed) = Self::update_rule_body(&record.value.body, &arities);
-            if changed {
-                count += 1;
-                let rkey = extract_rkey(&record.uri);
-                // Show which clauses changed
-                let clause_changes: Vec<_> = record
-                    .value
-                    .body
-                    .iter()
-                    .zip(updated_body.iter())
-                    .filter(|(old, new)| old != new)
-                    .map(|(old, new)| format!("  {} → {}", old, new))
-                    .collect();
-                changes.push(format!(
-                    "Rule '{}' ({}):\n{}",
-                    record.value.name,
-                    rkey,
-                    clause_changes.join("\n")
-                ));
-            }
-        }
-
-        Ok(MigrationPreview {
-            records_to_update: count,
-            changes,
-        })
-    }
-
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
         let user_predicates = Self::fetch_user_predicates(client).await?;
         let arities = Self::build_arity_map(&user_predicates);
 
@@ -908,32 +802,20 @@ impl Migration for RulePredicateArityMigration {
             .list_all_records::<Rule>(RULE_COLLECTION)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
-
-        let mut updated = 0;
-        let errors = Vec::new();
+        let mut plan = MigrationPlan::default();
 
         for record in rules {
             let rkey = extract_rkey(&record.uri);
             let (new_body, changed) = Self::update_rule_body(&record.value.body, &arities);
 
             if changed {
-                let mut rule = record.value;
+                let mut rule = record.value.clone();
                 rule.body = new_body;
-
-                client
-                    .put_record(RULE_COLLECTION, &rkey, &rule)
-                    .await
-                    .map_err(|e| miette::miette!("{}", e))?;
-
-                info!(rule = %rule.name, "updated rule body clauses");
-                updated += 1;
+                plan.update(RULE_COLLECTION, rkey, &record.value, &rule)?;
             }
         }
 
-        Ok(MigrationResult {
-            records_updated: updated,
-            errors,
-        })
+        Ok(plan)
     }
 }
 
@@ -999,22 +881,13 @@ impl Migration for NotesToWikiEntries {
         "Convert note records to wiki entries with generated slugs"
     }
 
-    async fn needs_migration(&self, client: &AtprotoClient) -> Result<bool> {
-        let notes = client
-            .list_all_records::<Note>(NOTE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        Ok(!notes.is_empty())
-    }
-
-    async fn preview(&self, client: &AtprotoClient) -> Result<MigrationPreview> {
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
         let notes = client
             .list_all_records::<Note>(NOTE_COLLECTION)
             .await
             .map_err(|e| miette::miette!("{}", e))?;
 
-        // Also check existing wiki entries for slug collisions
+        // Check existing wiki entries for slug collisions
         let existing_entries = client
             .list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION)
             .await
@@ -1024,68 +897,29 @@ impl Migration for NotesToWikiEntries {
             .map(|e| e.value.slug.clone())
             .collect();
 
-        let mut changes = Vec::new();
+        let mut plan = MigrationPlan::default();
 
         for record in &notes {
-            let rkey = extract_rkey(&record.uri);
+            // Tombstoned notes are not carried into the wiki
+            if record.value.deleted_at.is_some() {
+                continue;
+            }
+            let note_rkey = extract_rkey(&record.uri);
             let base_slug = Self::slugify(&record.value.title);
             let slug = Self::unique_slug(&base_slug, &used_slugs);
             used_slugs.push(slug.clone());
 
-            let mut extra = String::new();
-            if let Some(ref cat) = record.value.category {
-                extra = format!(" (category '{}' -> tag)", cat);
-            }
-
-            changes.push(format!(
-                "Note '{}' ({}) -> wiki entry slug '{}'{}",
-                record.value.title, rkey, slug, extra
-            ));
-        }
-
-        Ok(MigrationPreview {
-            records_to_update: notes.len(),
-            changes,
-        })
-    }
-
-    async fn apply(&self, client: &AtprotoClient) -> Result<MigrationResult> {
-        let notes = client
-            .list_all_records::<Note>(NOTE_COLLECTION)
-            .await
-            .map_err(|e| miette::miette!("{}", e))?;
-
-        // Check existing wiki entries for slug collisions
-        let existing_entries = client
-            .list_all_records::<WikiEntry>(WIKI_ENTRY_COLLECTION)
-            .await
-            .unwrap_or_default();
-        let mut used_slugs: Vec<String> = existing_entries
-            .iter()
-            .map(|e| e.value.slug.clone())
-            .collect();
-
-        let mut created = 0;
-        let mut deleted = 0;
-        let mut errors = Vec::new();
-
-        for record in &notes {
-            let note_rkey = extract_rkey(&record.uri);
-            let base_slug = Self::slugify(&record.value.title);
-            let slug = Self::unique_slug(&base_slug, &used_slugs);
-            used_slugs.push(slug.clone());
-
-            // Build tags: preserve existing + add category if present
-            let mut tags = record.value.tags.clone();
-            if let Some(ref category) = record.value.category {
-                if !category.is_empty() && !tags.contains(category) {
-                    tags.push(category.clone());
-                }
+            // Build tags: preserve existing + add category if present
+            let mut tags = record.value.tags.clone();
+            if let Some(ref category) = record.value.category {
+                if !category.is_empty() && !tags.contains(category) {
+                    tags.push(category.clone());
+                }
             }
 
             let entry = WikiEntry {
                 title: record.value.title.clone(),
-                slug: slug.clone(),
+                slug,
                 aliases: Vec::new(),
                 summary: None,
                 content: record.value.content.clone(),
@@ -1096,52 +930,65 @@ impl Migration for NotesToWikiEntries {
                 last_updated: record.value.last_updated,
             };
 
-            let wiki_rkey = Tid::now().to_string();
-            match client
-                .create_record(WIKI_ENTRY_COLLECTION, Some(&wiki_rkey), &entry)
-                .await
-            {
-                Ok(_) => {
-                    info!(
-                        note = %record.value.title,
-                        slug = %slug,
-                        "created wiki entry from note"
-                    );
-                    created += 1;
-
-                    // Delete the original note
-                    match client.delete_record(NOTE_COLLECTION, &note_rkey).await {
-                        Ok(()) => {
-                            deleted += 1;
-                        }
-                        Err(e) => {
-                            errors.push(format!(
-                                "Created wiki entry but failed to delete note '{}': {}",
-                                record.value.title, e
-                            ));
-                        }
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!(
-                        "Failed to create wiki entry for note '{}': {}",
-                        record.value.title, e
-                    ));
-                }
-            }
+            // Create the entry before deleting the note, so a failed write
+            // never loses content. The entry takes over the note's rkey, so
+            // every plan for the same note writes the same record.
+            plan.create(WIKI_ENTRY_COLLECTION, note_rkey.clone(), &entry)?;
+            plan.delete(NOTE_COLLECTION, note_rkey, &record.value)?;
         }
 
-        info!(
-            created = created,
-            deleted = deleted,
-            errors = errors.len(),
-            "notes-to-wiki-entries migration complete"
-        );
+        Ok(plan)
+    }
+}
+
+// =============================================================================
+// Migration: Thought Content Types
+// =============================================================================
+
+/// Migration: Backfill `contentType` on thoughts written before it existed.
+///
+/// The content type is inferred once from the kind and content shape, so
+/// readers can route rendering on the stored discriminator afterwards.
+struct ThoughtContentTypes;
+
+impl ThoughtContentTypes {
+    /// The thought with its content type filled in, or `None` if it already
+    /// has one.
+    fn backfilled(thought: &Thought) -> Option<Thought> {
+        if thought.content_type.is_some() {
+            return None;
+        }
+        let mut updated = thought.clone();
+        updated.content_type = Some(thought.effective_content_type());
+        Some(updated)
+    }
+}
+
+#[async_trait]
+impl Migration for ThoughtContentTypes {
+    fn name(&self) -> &'static str {
+        "thought-content-types"
+    }
+
+    fn description(&self) -> &'static str {
+        "Backfill contentType on thoughts so tool calls render without content sniffing"
+    }
 
-        Ok(MigrationResult {
-            records_updated: created + deleted,
-            errors,
-        })
+    async fn plan(&self, client: &AtprotoClient) -> Result<MigrationPlan> {
+        let thoughts = client
+            .list_all_records::<Thought>(THOUGHT_COLLECTION)
+            .await
+            .map_err(|e| miette::miette!("{}", e))?;
+        let mut plan = MigrationPlan::default();
+
+        for record in thoughts {
+            if let Some(thought) = Self::backfilled(&record.value) {
+                let rkey = extract_rkey(&record.uri);
+                plan.update(THOUGHT_COLLECTION, rkey, &record.value, &thought)?;
+            }
+        }
+
+        Ok(plan)
     }
 }
 
@@ -1158,9 +1005,261 @@ pub fn available_migrations() -> Vec<Box<dyn Migration>> {
         Box::new(LegacyIdentityToDirectives),
         Box::new(RulePredicateArityMigration),
         Box::new(NotesToWikiEntries),
+        Box::new(ThoughtContentTypes),
     ]
 }
 
+// =============================================================================
+// Applied-Migration Ledger
+// =============================================================================
+
+/// Record of which migrations have been applied.
+#[async_trait]
+pub trait MigrationLedger: Send + Sync {
+    /// Names of all applied migrations.
+    async fn applied(&self) -> Result<HashSet<String>>;
+
+    /// Record a migration as applied.
+    async fn mark_applied(&self, name: &str) -> Result<()>;
+}
+
+/// Ledger stored as a singleton migration state record in the PDS.
+pub struct PdsLedger<'a> {
+    client: &'a AtprotoClient,
+}
+
+impl<'a> PdsLedger<'a> {
+    pub fn new(client: &'a AtprotoClient) -> Self {
+        Self { client }
+    }
+
+    async fn load(&self) -> Result<MigrationState> {
+        match self
+            .client
+            .get_record::<MigrationState>(MIGRATION_STATE_COLLECTION, MIGRATION_STATE_KEY)
+            .await
+        {
+            Ok(record) => Ok(record.value),
+            Err(AtprotoError::NotFound { .. }) => Ok(MigrationState::default()),
+            Err(e) => Err(miette::miette!("{}", e)),
+        }
+    }
+}
+
+#[async_trait]
+impl MigrationLedger for PdsLedger<'_> {
+    async fn applied(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .load()
+            .await?
+            .applied
+            .into_iter()
+            .map(|m| m.id)
+            .collect())
+    }
+
+    async fn mark_applied(&self, name: &str) -> Result<()> {
+        let mut state = self.load().await?;
+        if state.applied.iter().any(|m| m.id == name) {
+            return Ok(());
+        }
+        state.applied.push(AppliedMigration {
+            id: name.to_string(),
+            applied_at: Utc::now(),
+        });
+        self.client
+            .put_record(MIGRATION_STATE_COLLECTION, MIGRATION_STATE_KEY, &state)
+            .await
+            .map_err(|e| miette::miette!("{}", e))?;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Dependency Ordering
+// =============================================================================
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+/// Order migrations so every migration comes after its dependencies.
+///
+/// With a `target`, only that migration and its transitive dependencies are
+/// returned. Ties are broken by registry order. Returns indices into
+/// `migrations`.
+fn resolve_order(migrations: &[Box<dyn Migration>], target: Option<&str>) -> Result<Vec<usize>> {
+    let index: HashMap<&str, usize> = migrations
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (m.name(), i))
+        .collect();
+
+    for m in migrations {
+        for dep in m.dependencies() {
+            if !index.contains_key(dep) {
+                return Err(miette::miette!(
+                    "Migration '{}' depends on unknown migration '{}'",
+                    m.name(),
+                    dep
+                ));
+            }
+        }
+    }
+
+    fn visit(
+        i: usize,
+        migrations: &[Box<dyn Migration>],
+        index: &HashMap<&str, usize>,
+        marks: &mut [Mark],
+        order: &mut Vec<usize>,
+    ) -> Result<()> {
+        match marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                return Err(miette::miette!(
+                    "Migration dependency cycle involving '{}'",
+                    migrations[i].name()
+                ));
+            }
+            Mark::Unvisited => {}
+        }
+        marks[i] = Mark::Visiting;
+        for dep in migrations[i].dependencies() {
+            visit(index[dep], migrations, index, marks, order)?;
+        }
+        marks[i] = Mark::Done;
+        order.push(i);
+        Ok(())
+    }
+
+    let roots: Vec<usize> = match target {
+        Some(name) => vec![
+            *index
+                .get(name)
+                .ok_or_else(|| miette::miette!("Unknown migration: {}", name))?,
+        ],
+        None => (0..migrations.len()).collect(),
+    };
+
+    let mut marks = vec![Mark::Unvisited; migrations.len()];
+    let mut order = Vec::new();
+    for i in roots {
+        visit(i, migrations, &index, &mut marks, &mut order)?;
+    }
+    Ok(order)
+}
+
+// =============================================================================
+// Runner
+// =============================================================================
+
+/// Run every pending migration (or `target` and its dependencies) in
+/// dependency order.
+///
+/// Each pending migration is planned once. In dry-run the plan is rendered;
+/// otherwise that same plan is executed through `writer`. Migrations already
+/// in the ledger are skipped, and one with an empty plan is recorded as
+/// applied without writing anything. A migration that reports errors is not
+/// recorded, and the run stops so its dependents never see partially
+/// migrated data.
+///
+/// Returns the plans that were applied (or, in dry-run, would be).
+pub async fn run_pending(
+    client: &AtprotoClient,
+    writer: &dyn RecordWriter,
+    migrations: &[Box<dyn Migration>],
+    ledger: &dyn MigrationLedger,
+    target: Option<&str>,
+    dry_run: bool,
+) -> Result<Vec<(&'static str, MigrationPlan)>> {
+    let order = resolve_order(migrations, target)?;
+    let applied = ledger.applied().await?;
+    let pending: Vec<&dyn Migration> = order
+        .into_iter()
+        .map(|i| migrations[i].as_ref())
+        .filter(|m| !applied.contains(m.name()))
+        .collect();
+
+    if pending.is_empty() {
+        println!("No pending migrations to run.");
+        return Ok(Vec::new());
+    }
+
+    let mut ran = Vec::new();
+    for m in pending {
+        println!("\n=== {} ===", m.name());
+        println!("{}\n", m.description());
+
+        let plan = m.plan(client).await?;
+
+        if plan.is_empty() {
+            if dry_run {
+                println!("Dry-run: nothing to change");
+            } else {
+                println!("Nothing to change; recording as applied");
+                ledger.mark_applied(m.name()).await?;
+            }
+            continue;
+        }
+
+        if dry_run {
+            println!("Dry-run: {} record write(s) planned\n", plan.ops.len());
+            print!("{}", plan.render());
+            for err in &plan.errors {
+                println!("  Warning: {}", err);
+            }
+            ran.push((m.name(), plan));
+            continue;
+        }
+
+        let result = apply_plan(&plan, writer).await?;
+        println!("Applied: {} record write(s)", result.records_updated);
+        if !result.errors.is_empty() {
+            for err in &result.errors {
+                println!("  Error: {}", err);
+            }
+            return Err(miette::miette!(
+                "Migration '{}' finished with {} error(s); not recording it as applied",
+                m.name(),
+                result.errors.len()
+            ));
+        }
+        ledger.mark_applied(m.name()).await?;
+        info!(migration = m.name(), "recorded migration as applied");
+        ran.push((m.name(), plan));
+    }
+
+    Ok(ran)
+}
+
+/// Print every migration in dependency order with its ledger status.
+async fn list_migrations(
+    migrations: &[Box<dyn Migration>],
+    ledger: &dyn MigrationLedger,
+) -> Result<()> {
+    let applied = ledger.applied().await?;
+    println!("Available migrations:\n");
+    for i in resolve_order(migrations, None)? {
+        let m = &migrations[i];
+        let status = if applied.contains(m.name()) {
+            "[APPLIED]"
+        } else {
+            "[PENDING]"
+        };
+        println!("  {} {}", status, m.name());
+        println!("      {}", m.description());
+        if !m.dependencies().is_empty() {
+            println!("      requires: {}", m.dependencies().join(", "));
+        }
+        println!();
+    }
+    Ok(())
+}
+
 // =============================================================================
 // Command Handler
 // =============================================================================
@@ -1182,70 +1281,23 @@ pub async fn run_migrate_command(
         .map_err(|e| miette::miette!("{}", e))?;
 
     let migrations = available_migrations();
+    let ledger = PdsLedger::new(&client);
 
     if list {
-        println!("Available migrations:\n");
-        for m in &migrations {
-            let needs = m.needs_migration(&client).await.unwrap_or(false);
-            let status = if needs { "[PENDING]" } else { "[APPLIED]" };
-            println!("  {} {}", status, m.name());
-            println!("      {}\n", m.description());
-        }
-        return Ok(());
+        return list_migrations(&migrations, &ledger).await;
     }
 
-    let to_run: Vec<_> = if all {
-        // Run all pending migrations
-        let mut pending = Vec::new();
-        for m in migrations {
-            if m.needs_migration(&client).await? {
-                pending.push(m);
-            }
-        }
-        pending
+    let target = if all {
+        None
     } else if let Some(name) = migration_name {
-        // Run specific migration
-        let m = migrations
-            .into_iter()
-            .find(|m| m.name() == name)
-            .ok_or_else(|| miette::miette!("Unknown migration: {}", name))?;
-        vec![m]
+        Some(name)
     } else {
         return Err(miette::miette!(
             "Specify a migration name, --all, or --list"
         ));
     };
 
-    if to_run.is_empty() {
-        println!("No pending migrations to run.");
-        return Ok(());
-    }
-
-    for m in to_run {
-        println!("\n=== {} ===", m.name());
-        println!("{}\n", m.description());
-
-        if dry_run {
-            let preview = m.preview(&client).await?;
-            println!(
-                "Dry-run: {} record(s) would be updated",
-                preview.records_to_update
-            );
-            if !preview.changes.is_empty() {
-                println!("\nChanges:");
-                for change in &preview.changes {
-                    println!("  - {}", change);
-                }
-            }
-        } else {
-            let result = m.apply(&client).await?;
-            println!("Applied: {} record(s) updated", result.records_updated);
-            for err in &result.errors {
-                println!("  Warning: {}", err);
-            }
-        }
-    }
-
+    run_pending(&client, &client, &migrations, &ledger, target, dry_run).await?;
     Ok(())
 }
 
@@ -1267,7 +1319,299 @@ pub async fn run(pds_url: &str, handle: &str, app_password: &str) -> Result<()>
 
 #[cfg(test)]
 mod tests {
-    use super::NotesToWikiEntries;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use winter_atproto::{ThoughtContentType, ThoughtKind};
+
+    struct FakeMigration {
+        name: &'static str,
+        deps: &'static [&'static str],
+        plans: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Migration for FakeMigration {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn description(&self) -> &'static str {
+            "fake"
+        }
+
+        fn dependencies(&self) -> &'static [&'static str] {
+            self.deps
+        }
+
+        async fn plan(&self, _client: &AtprotoClient) -> Result<MigrationPlan> {
+            self.plans.fetch_add(1, Ordering::SeqCst);
+            let mut plan = MigrationPlan::default();
+            plan.create(FACT_COLLECTION, format!("{}-new", self.name), &self.name)?;
+            plan.update(
+                RULE_COLLECTION,
+                format!("{}-rule", self.name),
+                &serde_json::json!({"name": self.name, "body": ["old(X)"]}),
+                &serde_json::json!({"name": self.name, "body": ["old(X, _)"]}),
+            )?;
+            plan.delete(NOTE_COLLECTION, format!("{}-old", self.name), &self.name)?;
+            Ok(plan)
+        }
+    }
+
+    /// Records every write it receives as the `RecordOp` it corresponds to.
+    #[derive(Default)]
+    struct RecordingWriter {
+        writes: Mutex<Vec<(String, String, Option<Value>)>>,
+    }
+
+    #[async_trait]
+    impl RecordWriter for RecordingWriter {
+        async fn create(&self, collection: &str, rkey: &str, record: &Value) -> Result<()> {
+            self.writes.lock().unwrap().push((
+                collection.to_string(),
+                rkey.to_string(),
+                Some(record.clone()),
+            ));
+            Ok(())
+        }
+
+        async fn put(&self, collection: &str, rkey: &str, record: &Value) -> Result<()> {
+            self.create(collection, rkey, record).await
+        }
+
+        async fn delete(&self, collection: &str, rkey: &str) -> Result<()> {
+            self.writes
+                .lock()
+                .unwrap()
+                .push((collection.to_string(), rkey.to_string(), None));
+            Ok(())
+        }
+    }
+
+    /// The writes a plan should produce, in the shape `RecordingWriter` logs.
+    fn expected_writes(plans: &[(&str, MigrationPlan)]) -> Vec<(String, String, Option<Value>)> {
+        plans
+            .iter()
+            .flat_map(|(_, plan)| &plan.ops)
+            .map(|op| match op {
+                RecordOp::Create {
+                    collection,
+                    rkey,
+                    record,
+                } => (collection.to_string(), rkey.clone(), Some(record.clone())),
+                RecordOp::Update {
+                    collection,
+                    rkey,
+                    after,
+                    ..
+                } => (collection.to_string(), rkey.clone(), Some(after.clone())),
+                RecordOp::Delete {
+                    collection, rkey, ..
+                } => (collection.to_string(), rkey.clone(), None),
+            })
+            .collect()
+    }
+
+    #[derive(Default)]
+    struct MemoryLedger {
+        applied: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MigrationLedger for MemoryLedger {
+        async fn applied(&self) -> Result<HashSet<String>> {
+            Ok(self.applied.lock().unwrap().iter().cloned().collect())
+        }
+
+        async fn mark_applied(&self, name: &str) -> Result<()> {
+            self.applied.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        migrations: Vec<Box<dyn Migration>>,
+        plans: Vec<Arc<AtomicUsize>>,
+    }
+
+    fn fixture(specs: &[(&'static str, &'static [&'static str])]) -> Fixture {
+        let mut migrations: Vec<Box<dyn Migration>> = Vec::new();
+        let mut plans = Vec::new();
+        for &(name, deps) in specs {
+            let counter = Arc::new(AtomicUsize::new(0));
+            plans.push(counter.clone());
+            migrations.push(Box::new(FakeMigration {
+                name,
+                deps,
+                plans: counter,
+            }));
+        }
+        Fixture { migrations, plans }
+    }
+
+    fn names(migrations: &[Box<dyn Migration>], order: &[usize]) -> Vec<&'static str> {
+        order.iter().map(|&i| migrations[i].name()).collect()
+    }
+
+    #[test]
+    fn resolve_order_puts_dependencies_first() {
+        let f = fixture(&[("c", &["b"]), ("a", &[]), ("b", &["a"]), ("d", &[])]);
+        let order = resolve_order(&f.migrations, None).unwrap();
+        assert_eq!(names(&f.migrations, &order), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn resolve_order_target_pulls_in_dependencies() {
+        let f = fixture(&[("a", &[]), ("b", &["a"]), ("c", &[]), ("d", &["b"])]);
+        let order = resolve_order(&f.migrations, Some("d")).unwrap();
+        assert_eq!(names(&f.migrations, &order), vec!["a", "b", "d"]);
+        assert!(resolve_order(&f.migrations, Some("nope")).is_err());
+    }
+
+    #[test]
+    fn resolve_order_rejects_cycles_and_unknown_dependencies() {
+        let cyclic = fixture(&[("a", &["b"]), ("b", &["a"])]);
+        assert!(resolve_order(&cyclic.migrations, None).is_err());
+
+        let dangling = fixture(&[("a", &["missing"])]);
+        assert!(resolve_order(&dangling.migrations, None).is_err());
+    }
+
+    #[test]
+    fn builtin_migrations_resolve() {
+        let migrations = available_migrations();
+        let order = resolve_order(&migrations, None).unwrap();
+        let order = names(&migrations, &order);
+        assert_eq!(order.len(), migrations.len());
+        let pos = |n: &str| order.iter().position(|m| *m == n).unwrap();
+        assert!(pos("note-related-facts-to-uris") < pos("fact-references-to-uris"));
+    }
+
+    fn ran_names(ran: &[(&'static str, MigrationPlan)]) -> Vec<&'static str> {
+        ran.iter().map(|(name, _)| *name).collect()
+    }
+
+    #[tokio::test]
+    async fn run_pending_is_idempotent() {
+        let f = fixture(&[("b", &["a"]), ("a", &[])]);
+        let client = AtprotoClient::new("http://localhost");
+        let writer = RecordingWriter::default();
+        let ledger = MemoryLedger::default();
+
+        let ran = run_pending(&client, &writer, &f.migrations, &ledger, None, false)
+            .await
+            .unwrap();
+        assert_eq!(ran_names(&ran), vec!["a", "b"]);
+        assert_eq!(writer.writes.lock().unwrap().len(), 6);
+
+        let ran = run_pending(&client, &writer, &f.migrations, &ledger, None, false)
+            .await
+            .unwrap();
+        assert!(ran.is_empty());
+        assert_eq!(writer.writes.lock().unwrap().len(), 6);
+        for counter in &f.plans {
+            assert_eq!(counter.load(Ordering::SeqCst), 1);
+        }
+        assert_eq!(*ledger.applied.lock().unwrap(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn run_pending_skips_already_applied() {
+        let f = fixture(&[("a", &[]), ("b", &["a"])]);
+        let client = AtprotoClient::new("http://localhost");
+        let writer = RecordingWriter::default();
+        let ledger = MemoryLedger::default();
+        ledger.mark_applied("a").await.unwrap();
+
+        let ran = run_pending(&client, &writer, &f.migrations, &ledger, Some("b"), false)
+            .await
+            .unwrap();
+        assert_eq!(ran_names(&ran), vec!["b"]);
+        assert_eq!(f.plans[0].load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn dry_run_plan_matches_applied_writes() {
+        let f = fixture(&[("b", &["a"]), ("a", &[])]);
+        let client = AtprotoClient::new("http://localhost");
+        let writer = RecordingWriter::default();
+        let ledger = MemoryLedger::default();
+
+        let previewed = run_pending(&client, &writer, &f.migrations, &ledger, None, true)
+            .await
+            .unwrap();
+        assert!(writer.writes.lock().unwrap().is_empty());
+        assert!(ledger.applied.lock().unwrap().is_empty());
+
+        let applied = run_pending(&client, &writer, &f.migrations, &ledger, None, false)
+            .await
+            .unwrap();
+        assert_eq!(ran_names(&previewed), ran_names(&applied));
+        for ((_, dry), (_, real)) in previewed.iter().zip(&applied) {
+            assert_eq!(dry.ops, real.ops);
+        }
+        assert_eq!(*writer.writes.lock().unwrap(), expected_writes(&previewed));
+    }
+
+    #[test]
+    fn render_shows_record_diffs() {
+        let mut plan = MigrationPlan::default();
+        plan.create(
+            WIKI_ENTRY_COLLECTION,
+            "new".to_string(),
+            &serde_json::json!({"slug": "x"}),
+        )
+        .unwrap();
+        plan.update(
+            FACT_COLLECTION,
+            "f1".to_string(),
+            &serde_json::json!({"predicate": "p", "source": "bafy123"}),
+            &serde_json::json!({"predicate": "p", "source": "at://did/c/r", "extra": 1}),
+        )
+        .unwrap();
+        plan.delete(
+            NOTE_COLLECTION,
+            "n1".to_string(),
+            &serde_json::json!({"title": "t"}),
+        )
+        .unwrap();
+
+        let rendered = plan.render();
+        assert_eq!(
+            rendered,
+            format!(
+                "+ create {wiki}/new\n    {{\n      \"slug\": \"x\"\n    }}\n\
+                 ~ update {fact}/f1\n    extra: (absent) → 1\n    source: \"bafy123\" → \"at://did/c/r\"\n\
+                 - delete {note}/n1\n    {{\n      \"title\": \"t\"\n    }}\n",
+                wiki = WIKI_ENTRY_COLLECTION,
+                fact = FACT_COLLECTION,
+                note = NOTE_COLLECTION,
+            )
+        );
+    }
+
+    #[test]
+    fn empty_plan_with_errors_is_not_empty() {
+        let mut plan = MigrationPlan::default();
+        assert!(plan.is_empty());
+        plan.errors.push("unresolved".to_string());
+        assert!(!plan.is_empty());
+    }
+
+    #[test]
+    fn directive_rkeys_are_stable_and_distinct() {
+        let created_at = Utc::now();
+        let first = LegacyIdentityToDirectives::directive_rkey(created_at, 0);
+        let second = LegacyIdentityToDirectives::directive_rkey(created_at, 1);
+        assert_eq!(
+            first,
+            LegacyIdentityToDirectives::directive_rkey(created_at, 0)
+        );
+        assert_ne!(first, second);
+    }
 
     #[test]
     fn slugify_basic() {
@@ -1320,4 +1664,104 @@ mod tests {
         let used = vec!["test".to_string(), "test-2".to_string()];
         assert_eq!(NotesToWikiEntries::unique_slug("test", &used), "test-3");
     }
+
+    fn legacy_thought(kind: ThoughtKind, content: &str) -> Thought {
+        Thought {
+            kind,
+            content: content.to_string(),
+            content_type: None,
+            trigger: None,
+            tags: vec![],
+            duration_ms: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn thought_content_types_backfills_from_content_shape() {
+        let cases = [
+            (
+                ThoughtKind::ToolCall,
+                r#"{"tool":"x"}"#,
+                ThoughtContentType::ToolCall,
+            ),
+            (
+                ThoughtKind::ToolCall,
+                "Called x\nArgs:\n{}",
+                ThoughtContentType::LegacyToolCall,
+            ),
+            (
+                ThoughtKind::Insight,
+                r#"{"tool":"x"}"#,
+                ThoughtContentType::Text,
+            ),
+        ];
+        for (kind, content, expected) in cases {
+            let updated = ThoughtContentTypes::backfilled(&legacy_thought(kind, content)).unwrap();
+            assert_eq!(updated.content_type, Some(expected));
+            assert_eq!(updated.content, content);
+        }
+    }
+
+    #[test]
+    fn thought_content_types_skips_typed_thoughts() {
+        let mut thought = legacy_thought(ThoughtKind::ToolCall, r#"{"tool":"x"}"#);
+        thought.content_type = Some(ThoughtContentType::Text);
+        assert!(ThoughtContentTypes::backfilled(&thought).is_none());
+    }
+
+    #[tokio::test]
+    async fn notes_to_wiki_entries_skips_tombstoned_notes() {
+        use serde_json::json;
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.createSession"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "did": "did:plc:winter",
+                "handle": "winter.test",
+                "accessJwt": "access",
+                "refreshJwt": "refresh"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.listRecords"))
+            .and(query_param("collection", NOTE_COLLECTION))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "records": [
+                    {
+                        "uri": "at://did:plc:winter/diy.razorgirl.winter.note/live",
+                        "cid": "bafylive",
+                        "value": { "title": "Live", "content": "kept" }
+                    },
+                    {
+                        "uri": "at://did:plc:winter/diy.razorgirl.winter.note/gone",
+                        "cid": "bafygone",
+                        "value": {
+                            "title": "Gone",
+                            "content": "deleted",
+                            "deletedAt": "2026-01-01T00:00:00Z"
+                        }
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.listRecords"))
+            .and(query_param("collection", WIKI_ENTRY_COLLECTION))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "records": [] })))
+            .mount(&server)
+            .await;
+        let client = AtprotoClient::new(server.uri());
+        client.login("winter.test", "password").await.unwrap();
+
+        let plan = NotesToWikiEntries.plan(&client).await.unwrap();
+        let writes = expected_writes(&[("notes-to-wiki-entries", plan)]);
+        let rkeys: Vec<&str> = writes.iter().map(|(_, rkey, _)| rkey.as_str()).collect();
+        assert_eq!(rkeys, ["live", "live"]);
+    }
 }
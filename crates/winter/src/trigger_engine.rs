@@ -3,6 +3,11 @@
 //! Periodically evaluates datalog conditions from trigger records and executes
 //! actions when new result tuples appear. Deduplicates across evaluation cycles
 //! so each unique result tuple fires at most once.
+//!
+//! Triggers whose condition reads `_event`, or that have an event filter, are
+//! evaluated against each incoming notification and fact event instead,
+//! firing once per match. Matching is
+//! shared with the `test_trigger` tool via [`winter_mcp::tools::triggers`].
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -11,8 +16,13 @@ use chrono::Utc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-use winter_atproto::{AtprotoClient, Fact, FactDeclArg, RepoCache, Tid, TriggerAction};
+use winter_atproto::{AtprotoClient, Fact, RepoCache, Tid, TriggerAction};
 use winter_datalog::DatalogCache;
+use winter_mcp::InboxItem;
+use winter_mcp::tools::triggers::{
+    TriggerEvent, evaluate_condition, is_event_trigger, match_trigger, resolve_action,
+};
+use winter_scheduler::Scheduler;
 
 /// Maximum actions per trigger per evaluation cycle.
 const MAX_ACTIONS_PER_TRIGGER: usize = 50;
@@ -23,6 +33,7 @@ pub struct TriggerEngine {
     cache: Arc<RepoCache>,
     datalog: Arc<DatalogCache>,
     atproto: Arc<AtprotoClient>,
+    scheduler: Arc<Scheduler>,
     mcp_base_url: String,
     http: reqwest::Client,
     /// Deduplication state: trigger rkey -> set of result tuples seen.
@@ -35,14 +46,16 @@ impl TriggerEngine {
         cache: Arc<RepoCache>,
         datalog: Arc<DatalogCache>,
         atproto: Arc<AtprotoClient>,
+        scheduler: Arc<Scheduler>,
         mcp_base_url: String,
     ) -> Self {
         Self {
             cache,
             datalog,
             atproto,
+            scheduler,
             mcp_base_url,
-            http: reqwest::Client::new(),
+            http: winter_mcp::http::authenticated_client(),
             last_fired: RwLock::new(HashMap::new()),
         }
     }
@@ -70,30 +83,20 @@ impl TriggerEngine {
                 continue;
             }
 
-            // Build query from condition body
-            let (query, rules) = Self::build_trigger_query(
-                &trigger.condition,
-                trigger.condition_rules.as_deref(),
-            );
-
-            // Build typed declaration for _trigger_result if trigger has args
-            let extra_decls = Self::build_trigger_result_declaration(&trigger.args);
-            let extra_decls_option: Option<&[String]> = if extra_decls.is_empty() {
-                None
-            } else {
-                Some(&extra_decls)
-            };
+            // Event triggers only fire from evaluate_event
+            if is_event_trigger(trigger) {
+                continue;
+            }
 
             // Run the condition query
-            let results = match self
-                .datalog
-                .execute_query_with_facts_and_declarations(
-                    &query,
-                    rules.as_deref(),
-                    None,
-                    extra_decls_option,
-                )
-                .await
+            let results = match evaluate_condition(
+                &self.datalog,
+                &trigger.condition,
+                trigger.condition_rules.as_deref(),
+                &trigger.args,
+                None,
+            )
+            .await
             {
                 Ok(results) => results,
                 Err(e) => {
@@ -157,10 +160,8 @@ impl TriggerEngine {
             );
 
             for tuple in to_process {
-                match self
-                    .execute_action(&trigger.name, &trigger.action, tuple)
-                    .await
-                {
+                let action = resolve_action(&trigger.action, tuple);
+                match self.execute_action(&trigger.name, &action, None).await {
                     Ok(()) => {
                         // Only add to last_fired on success
                         let mut last_fired = self.last_fired.write().await;
@@ -189,12 +190,67 @@ impl TriggerEngine {
         Ok(())
     }
 
-    /// Execute a single trigger action with variable substitution from the tuple.
+    /// Evaluate enabled event triggers against a single incoming event.
+    ///
+    /// Every match fires; there's no deduplication because each event is
+    /// seen only once.
+    pub async fn evaluate_event(&self, event: &TriggerEvent) {
+        for (rkey, cached_trigger) in self.cache.list_triggers() {
+            let trigger = &cached_trigger.value;
+
+            if !trigger.enabled || !is_event_trigger(trigger) {
+                continue;
+            }
+
+            let matches = match match_trigger(&self.datalog, trigger, Some(event)).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    error!(
+                        trigger_name = %trigger.name,
+                        trigger_rkey = %rkey,
+                        error = %e,
+                        "failed to evaluate trigger condition against event"
+                    );
+                    continue;
+                }
+            };
+
+            if matches.is_empty() {
+                continue;
+            }
+
+            info!(
+                trigger_name = %trigger.name,
+                trigger_rkey = %rkey,
+                event_kind = %event.kind,
+                matches = matches.len(),
+                "executing trigger actions for event"
+            );
+
+            for (_, action) in matches.iter().take(MAX_ACTIONS_PER_TRIGGER) {
+                if let Err(e) = self
+                    .execute_action(&trigger.name, action, Some(event))
+                    .await
+                {
+                    error!(
+                        trigger_name = %trigger.name,
+                        trigger_rkey = %rkey,
+                        error = %e,
+                        "failed to execute trigger action"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Execute a single trigger action whose variables are already substituted.
+    ///
+    /// `event` is the event that fired an event trigger, if any.
     async fn execute_action(
         &self,
         trigger_name: &str,
         action: &TriggerAction,
-        tuple: &[String],
+        event: Option<&TriggerEvent>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         match action {
             TriggerAction::CreateFact {
@@ -202,20 +258,16 @@ impl TriggerEngine {
                 args,
                 tags,
             } => {
-                let substituted_args: Vec<String> = args
-                    .iter()
-                    .map(|arg| Self::substitute_variables(arg, tuple))
-                    .collect();
-
                 let fact = Fact {
                     predicate: predicate.clone(),
-                    args: substituted_args,
+                    args: args.clone(),
                     confidence: None,
                     source: Some(format!("trigger:{}", trigger_name)),
                     supersedes: None,
                     tags: tags.clone(),
                     created_at: Utc::now(),
                     expires_at: None,
+                    deleted_at: None,
                 };
 
                 let rkey = Tid::now().to_string();
@@ -232,23 +284,17 @@ impl TriggerEngine {
             }
 
             TriggerAction::CreateInboxItem { message } => {
-                let substituted_message = Self::substitute_variables(message, tuple);
-                let full_message = format!("[trigger:{}] {}", trigger_name, substituted_message);
+                let mut item = InboxItem::trigger(trigger_name.to_string(), message.clone());
+                if let Some(event) = event {
+                    item = item.with_event(
+                        event.subject.clone(),
+                        event.author.clone(),
+                        event.text.clone(),
+                    );
+                }
 
                 let url = format!("{}/inbox", self.mcp_base_url);
-                let body = serde_json::json!({
-                    "id": Tid::now().to_string(),
-                    "kind": "system",
-                    "priority": 50,
-                    "created_at": Utc::now().to_rfc3339(),
-                    "context_tag": format!("trigger:{}", trigger_name),
-                    "payload": {
-                        "type": "system",
-                        "message": full_message
-                    }
-                });
-
-                let response = self.http.post(&url).json(&body).send().await?;
+                let response = self.http.post(&url).json(&item).send().await?;
 
                 if !response.status().is_success() {
                     let status = response.status();
@@ -267,115 +313,34 @@ impl TriggerEngine {
             }
 
             TriggerAction::DeleteFact { rkey } => {
-                let substituted_rkey = Self::substitute_variables(rkey, tuple);
-
                 self.atproto
-                    .delete_record("diy.razorgirl.winter.fact", &substituted_rkey)
+                    .delete_record("diy.razorgirl.winter.fact", rkey)
                     .await?;
 
                 info!(
                     trigger_name = %trigger_name,
-                    rkey = %substituted_rkey,
+                    rkey = %rkey,
                     "deleted fact from trigger"
                 );
             }
-        }
-
-        Ok(())
-    }
-
-    /// Build a query and extra_rules for a trigger condition.
-    ///
-    /// Trigger conditions are rule bodies (conjunctions of literals) like
-    /// `follows_me(X, _), !has_impression(X)`. These can't be passed directly
-    /// as queries because `execute_query` expects a single predicate.
-    ///
-    /// This wraps the condition into a rule:
-    ///   `_trigger_result(X) :- follows_me(X, _), !has_impression(X).`
-    /// and queries `_trigger_result(X)`.
-    fn build_trigger_query(condition: &str, condition_rules: Option<&str>) -> (String, Option<String>) {
-        // Extract unique uppercase variables from the condition, preserving first-seen order
-        let vars = Self::extract_variables(condition);
-
-        let query = if vars.is_empty() {
-            "_trigger_result()".to_string()
-        } else {
-            format!("_trigger_result({})", vars.join(", "))
-        };
-
-        // Build the wrapper rule
-        let condition_trimmed = condition.trim().trim_end_matches('.');
-        let wrapper_rule = if vars.is_empty() {
-            format!("_trigger_result() :- {}.", condition_trimmed)
-        } else {
-            format!(
-                "_trigger_result({}) :- {}.",
-                vars.join(", "),
-                condition_trimmed
-            )
-        };
 
-        // Combine with any existing condition_rules
-        let rules = match condition_rules {
-            Some(existing) => format!("{}\n{}", existing, wrapper_rule),
-            None => wrapper_rule,
-        };
-
-        (query, Some(rules))
-    }
+            TriggerAction::RunJob { job } => {
+                let Some(scheduled) = self.scheduler.get_job_by_name(job).await else {
+                    return Err(format!("job not found: {}", job).into());
+                };
 
-    /// Extract unique uppercase variable names from a datalog condition body,
-    /// preserving first-seen order. Skips `_` (anonymous variable).
-    fn extract_variables(condition: &str) -> Vec<String> {
-        let mut seen = HashSet::new();
-        let mut vars = Vec::new();
+                self.scheduler.run_now(&scheduled.rkey).await?;
 
-        // Split on typical datalog delimiters, then check each token
-        for token in condition.split(|c: char| !c.is_alphanumeric() && c != '_') {
-            if token.is_empty() || token == "_" {
-                continue;
-            }
-            if let Some(first) = token.chars().next() {
-                if first.is_uppercase()
-                    && token.chars().all(|c| c.is_alphanumeric() || c == '_')
-                    && !seen.contains(token)
-                {
-                    seen.insert(token.to_string());
-                    vars.push(token.to_string());
-                }
+                info!(
+                    trigger_name = %trigger_name,
+                    job = %job,
+                    rkey = %scheduled.rkey,
+                    "requested job run from trigger"
+                );
             }
         }
 
-        vars
-    }
-
-    /// Build an extra_declarations entry for `_trigger_result` when the trigger has typed args.
-    ///
-    /// Returns an empty vec if args is empty (fall back to default all-symbol declaration).
-    fn build_trigger_result_declaration(args: &[FactDeclArg]) -> Vec<String> {
-        if args.is_empty() {
-            return Vec::new();
-        }
-        let params: Vec<String> = args
-            .iter()
-            .map(|a| format!("{}: {}", a.name, a.r#type))
-            .collect();
-        vec![format!("_trigger_result({})", params.join(", "))]
-    }
-
-    /// Replace `$0`, `$1`, etc. in a template with values from the tuple.
-    ///
-    /// Out-of-range `$N` references are left as literals.
-    fn substitute_variables(template: &str, tuple: &[String]) -> String {
-        let mut result = template.to_string();
-
-        // Replace from highest index to lowest to avoid $1 replacing part of $10
-        for i in (0..tuple.len()).rev() {
-            let placeholder = format!("${}", i);
-            result = result.replace(&placeholder, &tuple[i]);
-        }
-
-        result
+        Ok(())
     }
 }
 
@@ -383,104 +348,116 @@ impl TriggerEngine {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_substitute_variables_basic() {
-        let tuple = vec!["alice".to_string(), "bob".to_string()];
-        let result = TriggerEngine::substitute_variables("$0 follows $1", &tuple);
-        assert_eq!(result, "alice follows bob");
-    }
+    use axum::{Json, Router, extract::State, routing::post};
+    use tokio::sync::Mutex;
+    use winter_atproto::Trigger;
+
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// Serve a stand-in for the MCP inbox endpoint, returning its base URL
+    /// and the items posted to it.
+    async fn inbox_stub() -> (String, Received) {
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route(
+                "/inbox",
+                post(
+                    |State(received): State<Received>, Json(item): Json<serde_json::Value>| async move {
+                        received.lock().await.push(item);
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&received));
 
-    #[test]
-    fn test_substitute_variables_repeated() {
-        let tuple = vec!["hello".to_string()];
-        let result = TriggerEngine::substitute_variables("$0 and $0 again", &tuple);
-        assert_eq!(result, "hello and hello again");
-    }
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
 
-    #[test]
-    fn test_substitute_variables_out_of_range() {
-        let tuple = vec!["only".to_string()];
-        let result = TriggerEngine::substitute_variables("$0 and $1 and $2", &tuple);
-        assert_eq!(result, "only and $1 and $2");
+        (format!("http://{}", addr), received)
     }
 
-    #[test]
-    fn test_substitute_variables_empty_tuple() {
-        let tuple: Vec<String> = vec![];
-        let result = TriggerEngine::substitute_variables("no vars here", &tuple);
-        assert_eq!(result, "no vars here");
-    }
+    async fn engine_with_mention_trigger() -> (TriggerEngine, Received) {
+        let cache = RepoCache::new();
+        cache.upsert_trigger(
+            "t1".to_string(),
+            Trigger {
+                name: "greet".to_string(),
+                description: "Queue a note for each mention".to_string(),
+                condition: r#"_event("mention", U, A, _)"#.to_string(),
+                condition_rules: None,
+                action: TriggerAction::CreateInboxItem {
+                    message: "mentioned by $1".to_string(),
+                },
+                enabled: true,
+                args: Vec::new(),
+                filter: None,
+                created_at: Utc::now(),
+            },
+            "cid".to_string(),
+        );
 
-    #[test]
-    fn test_substitute_variables_no_placeholders() {
-        let tuple = vec!["unused".to_string()];
-        let result = TriggerEngine::substitute_variables("plain text", &tuple);
-        assert_eq!(result, "plain text");
+        let atproto = Arc::new(AtprotoClient::new("http://localhost:0"));
+        let (base_url, received) = inbox_stub().await;
+        let engine = TriggerEngine::new(
+            cache,
+            DatalogCache::new_temp().unwrap(),
+            Arc::clone(&atproto),
+            Arc::new(Scheduler::new(atproto)),
+            base_url,
+        );
+        (engine, received)
     }
 
-    #[test]
-    fn test_substitute_variables_double_digit_index() {
-        let tuple: Vec<String> = (0..11).map(|i| format!("val{}", i)).collect();
-        let result = TriggerEngine::substitute_variables("$0 $10", &tuple);
-        // $10 should be replaced with "val10", $0 with "val0"
-        assert_eq!(result, "val0 val10");
+    fn event(kind: &str) -> TriggerEvent {
+        TriggerEvent {
+            kind: kind.to_string(),
+            subject: "at://did:plc:a/app.bsky.feed.post/1".to_string(),
+            author: "did:plc:a".to_string(),
+            text: "hello".to_string(),
+            record: serde_json::Value::Null,
+        }
     }
 
-    #[test]
-    fn test_extract_variables_basic() {
-        let vars = TriggerEngine::extract_variables("follows_me(X, _), !has_impression(X)");
-        assert_eq!(vars, vec!["X"]);
+    #[tokio::test]
+    async fn test_matching_event_enqueues_inbox_item() {
+        let (engine, received) = engine_with_mention_trigger().await;
+
+        engine.evaluate_event(&event("mention")).await;
+
+        let received = received.lock().await;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["context_tag"], "trigger:greet");
+        assert_eq!(received[0]["kind"], "trigger");
+        let payload = &received[0]["payload"];
+        assert_eq!(payload["type"], "trigger");
+        assert_eq!(payload["name"], "greet");
+        assert_eq!(payload["message"], "mentioned by did:plc:a");
+        assert_eq!(
+            payload["subject_uri"],
+            "at://did:plc:a/app.bsky.feed.post/1"
+        );
+        assert_eq!(payload["author_did"], "did:plc:a");
+        assert_eq!(payload["text"], "hello");
     }
 
-    #[test]
-    fn test_extract_variables_multiple() {
-        let vars = TriggerEngine::extract_variables("follows(Self, X, _), is_followed_by(X, Self)");
-        assert_eq!(vars, vec!["Self", "X"]);
-    }
+    #[tokio::test]
+    async fn test_non_matching_event_does_nothing() {
+        let (engine, received) = engine_with_mention_trigger().await;
 
-    #[test]
-    fn test_extract_variables_with_strings() {
-        let vars = TriggerEngine::extract_variables(r#"fact_tag(R, "social", _), _fact(R, P, _)"#);
-        assert_eq!(vars, vec!["R", "P"]);
-    }
+        engine.evaluate_event(&event("like")).await;
 
-    #[test]
-    fn test_extract_variables_none() {
-        let vars = TriggerEngine::extract_variables(r#"has_fact("hello", "world", _)"#);
-        assert!(vars.is_empty());
+        assert!(received.lock().await.is_empty());
     }
 
-    #[test]
-    fn test_build_trigger_query_conjunction() {
-        let (query, rules) = TriggerEngine::build_trigger_query(
-            "follows_me(X, _), !has_impression(X)",
-            None,
-        );
-        assert_eq!(query, "_trigger_result(X)");
-        let rules = rules.unwrap();
-        assert!(rules.contains("_trigger_result(X) :- follows_me(X, _), !has_impression(X)."));
-    }
+    #[tokio::test]
+    async fn test_periodic_evaluation_skips_event_triggers() {
+        let (engine, received) = engine_with_mention_trigger().await;
 
-    #[test]
-    fn test_build_trigger_query_with_condition_rules() {
-        let (query, rules) = TriggerEngine::build_trigger_query(
-            "mutual(X)",
-            Some("mutual(X) :- follows(Self, X, _), is_followed_by(X, Self)."),
-        );
-        assert_eq!(query, "_trigger_result(X)");
-        let rules = rules.unwrap();
-        assert!(rules.contains("mutual(X) :- follows(Self, X, _), is_followed_by(X, Self)."));
-        assert!(rules.contains("_trigger_result(X) :- mutual(X)."));
-    }
+        engine.evaluate_all().await.unwrap();
 
-    #[test]
-    fn test_build_trigger_query_nullary() {
-        let (query, rules) = TriggerEngine::build_trigger_query(
-            r#"has_fact("stale_check", _, _)"#,
-            None,
-        );
-        assert_eq!(query, "_trigger_result()");
-        let rules = rules.unwrap();
-        assert!(rules.contains("_trigger_result() :- has_fact(\"stale_check\", _, _)."));
+        assert!(received.lock().await.is_empty());
+        assert!(engine.last_fired.read().await.is_empty());
     }
 }
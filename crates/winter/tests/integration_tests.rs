@@ -17,6 +17,7 @@ fn test_fact(predicate: &str, args: &[&str]) -> Fact {
         tags: vec![],
         created_at: chrono::Utc::now(),
         expires_at: None,
+        deleted_at: None,
     }
 }
 
@@ -187,6 +188,7 @@ mod directive_serialization {
             (DirectiveKind::SelfConcept, "self_concept"),
             (DirectiveKind::Boundary, "boundary"),
             (DirectiveKind::Aspiration, "aspiration"),
+            (DirectiveKind::Goal, "goal"),
         ];
 
         for (kind, expected) in kinds {
@@ -228,6 +230,7 @@ mod thought_serialization {
             let thought = Thought {
                 kind,
                 content: "test".to_string(),
+                content_type: None,
                 trigger: None,
                 tags: vec![],
                 duration_ms: None,
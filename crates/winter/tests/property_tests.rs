@@ -40,6 +40,7 @@ fn directive_kind() -> impl Strategy<Value = DirectiveKind> {
         Just(DirectiveKind::SelfConcept),
         Just(DirectiveKind::Boundary),
         Just(DirectiveKind::Aspiration),
+        Just(DirectiveKind::Goal),
     ]
 }
 
@@ -123,6 +124,7 @@ proptest! {
             tags: tags.clone(),
             created_at: chrono::Utc::now(),
             expires_at: None,
+            deleted_at: None,
         };
 
         // Serialize and deserialize
@@ -191,6 +193,7 @@ proptest! {
             tags: tags.clone(),
             created_at: chrono::Utc::now(),
             last_updated: chrono::Utc::now(),
+            deleted_at: None,
         };
 
         // Serialize and deserialize
@@ -214,6 +217,7 @@ proptest! {
         let thought = Thought {
             kind: kind.clone(),
             content: content.clone(),
+            content_type: None,
             trigger: trigger.clone(),
             tags: vec![],
             duration_ms,
@@ -259,6 +263,7 @@ mod datalog {
                 tags: vec!["tag1".to_string()],
                 created_at: chrono::Utc::now(),
                 expires_at: None,
+                deleted_at: None,
             };
 
             let fact2 = Fact {
@@ -270,6 +275,7 @@ mod datalog {
                 tags: vec!["tag2".to_string()],
                 created_at: chrono::Utc::now(),
                 expires_at: None,
+                deleted_at: None,
             };
 
             // Same predicate and args means same semantic fact